@@ -0,0 +1,26 @@
+use trillium::Conn;
+use trillium_acme::{Acme, LETS_ENCRYPT_STAGING};
+use trillium_client::Client;
+use trillium_rustls::RustlsConfig;
+use trillium_smol::ClientConfig;
+
+fn main() {
+    env_logger::init();
+    // run this on a server that is reachable on port 443 at DOMAIN
+    let domain = std::env::var("DOMAIN").expect("DOMAIN must be set");
+    let client = Client::new(RustlsConfig::<ClientConfig>::default()).with_default_pool();
+    let acme = Acme::new(client, [domain])
+        .with_directory_url(LETS_ENCRYPT_STAGING)
+        .with_cache_dir("./acme-cache");
+
+    let acceptor = acme.acceptor();
+    acme.spawn();
+
+    trillium_smol::config()
+        .with_host("0.0.0.0")
+        .with_port(443)
+        .with_acceptor(acceptor)
+        .run((trillium_logger::logger(), |conn: Conn| async move {
+            conn.ok("hello over https")
+        }));
+}
@@ -0,0 +1,111 @@
+use crate::Error;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::{
+    digest::{digest, SHA256},
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, KeyPair as _, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use serde_json::{json, Value};
+use std::fmt::{self, Debug, Formatter};
+
+/// An ES256 acme account key, which signs every request to the acme
+/// server as a [json web signature](https://www.rfc-editor.org/rfc/rfc7515)
+pub(crate) struct AccountKey {
+    key_pair: EcdsaKeyPair,
+    pem: String,
+    thumbprint: String,
+    rng: SystemRandom,
+}
+
+impl Debug for AccountKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AccountKey")
+            .field("thumbprint", &self.thumbprint)
+            .finish()
+    }
+}
+
+impl AccountKey {
+    pub(crate) fn generate() -> Result<Self, Error> {
+        let key_pair = rcgen::KeyPair::generate_for(&rcgen::PKCS_ECDSA_P256_SHA256)?;
+        Self::from_pem(&key_pair.serialize_pem())
+    }
+
+    pub(crate) fn from_pem(pem: &str) -> Result<Self, Error> {
+        let rng = SystemRandom::new();
+        let key_pair = EcdsaKeyPair::from_pkcs8(
+            &ECDSA_P256_SHA256_FIXED_SIGNING,
+            rcgen::KeyPair::from_pem(pem)?.serialized_der(),
+            &rng,
+        )
+        .map_err(|e| Error::protocol(format!("account key is not a p-256 key: {e}")))?;
+
+        let mut account_key = Self {
+            key_pair,
+            pem: pem.to_string(),
+            thumbprint: String::new(),
+            rng,
+        };
+
+        // rfc 7638: the members of the jwk in lexicographic order
+        // with no whitespace, which is how serde_json serializes it
+        let jwk = account_key.jwk().to_string();
+        account_key.thumbprint = base64(digest(&SHA256, jwk.as_bytes()));
+        Ok(account_key)
+    }
+
+    pub(crate) fn pem(&self) -> &str {
+        &self.pem
+    }
+
+    fn jwk(&self) -> Value {
+        // an uncompressed point: 0x04 followed by x and y
+        let public_key = self.key_pair.public_key().as_ref();
+        json!({
+            "crv": "P-256",
+            "kty": "EC",
+            "x": base64(&public_key[1..33]),
+            "y": base64(&public_key[33..]),
+        })
+    }
+
+    /// the response to a challenge with this token
+    pub(crate) fn key_authorization(&self, token: &str) -> String {
+        format!("{token}.{}", self.thumbprint)
+    }
+
+    /// builds a flattened json web signature of the payload. a
+    /// payload of None is a POST-as-GET request
+    pub(crate) fn sign(
+        &self,
+        url: &str,
+        nonce: &str,
+        kid: Option<&str>,
+        payload: Option<&Value>,
+    ) -> Result<String, Error> {
+        let mut protected = json!({ "alg": "ES256", "nonce": nonce, "url": url });
+        match kid {
+            Some(kid) => protected["kid"] = kid.into(),
+            None => protected["jwk"] = self.jwk(),
+        }
+
+        let protected = base64(protected.to_string());
+        let payload = payload.map(|payload| base64(payload.to_string()));
+        let payload = payload.unwrap_or_default();
+        let signature = self
+            .key_pair
+            .sign(&self.rng, format!("{protected}.{payload}").as_bytes())
+            .map_err(|e| Error::protocol(format!("could not sign request: {e}")))?;
+
+        Ok(json!({
+            "protected": protected,
+            "payload": payload,
+            "signature": base64(signature),
+        })
+        .to_string())
+    }
+}
+
+pub(crate) fn base64(bytes: impl AsRef<[u8]>) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
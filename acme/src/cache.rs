@@ -0,0 +1,77 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Persists the account key and certificates to a directory, if one
+/// is configured
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cache(Option<PathBuf>);
+
+impl Cache {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self(Some(dir))
+    }
+
+    fn read(&self, name: &str) -> io::Result<Option<String>> {
+        let Some(dir) = &self.0 else {
+            return Ok(None);
+        };
+
+        match fs::read_to_string(dir.join(name)) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write(&self, name: &str, contents: &str) -> io::Result<()> {
+        let Some(dir) = &self.0 else {
+            return Ok(());
+        };
+
+        fs::create_dir_all(dir)?;
+        // write and then rename so that a partially written file is
+        // never read
+        let tmp = dir.join(format!(".{name}.tmp"));
+        write_private(&tmp, contents)?;
+        fs::rename(tmp, dir.join(name))
+    }
+
+    pub(crate) fn account_key(&self) -> io::Result<Option<String>> {
+        self.read("account.pem")
+    }
+
+    pub(crate) fn store_account_key(&self, pem: &str) -> io::Result<()> {
+        self.write("account.pem", pem)
+    }
+
+    pub(crate) fn certificate(&self, domains: &[String]) -> io::Result<Option<String>> {
+        self.read(&certificate_file(domains))
+    }
+
+    pub(crate) fn store_certificate(&self, domains: &[String], pem: &str) -> io::Result<()> {
+        self.write(&certificate_file(domains), pem)
+    }
+}
+
+fn certificate_file(domains: &[String]) -> String {
+    format!("{}.pem", domains.join("+"))
+}
+
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    use std::{io::Write, os::unix::fs::OpenOptionsExt};
+    fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    fs::write(path, contents)
+}
@@ -0,0 +1,46 @@
+/// Reasons that a certificate could not be provisioned
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// a request to the acme server failed
+    #[error(transparent)]
+    Http(#[from] trillium_client::Error),
+
+    /// the acme server responded with an
+    /// [rfc 7807](https://www.rfc-editor.org/rfc/rfc7807) problem document
+    #[error("acme server responded with {kind}: {detail}")]
+    Problem {
+        /// the problem type, such as `urn:ietf:params:acme:error:rateLimited`
+        kind: String,
+        /// a human-readable explanation of the problem
+        detail: String,
+    },
+
+    /// the acme server responded with something other than what the
+    /// acme protocol specifies, or an order or authorization could
+    /// not be completed
+    #[error("{0}")]
+    Protocol(String),
+
+    /// a json body could not be serialized or deserialized
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    /// the certificate cache could not be read or written
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// a key or certificate signing request could not be generated
+    #[error(transparent)]
+    Rcgen(#[from] rcgen::Error),
+
+    /// an issued or cached certificate could not be used by rustls
+    #[error(transparent)]
+    Rustls(#[from] trillium_rustls::rustls::Error),
+}
+
+impl Error {
+    pub(crate) fn protocol(message: impl Into<String>) -> Self {
+        Self::Protocol(message.into())
+    }
+}
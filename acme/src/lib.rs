@@ -0,0 +1,496 @@
+/*!
+# Automatic acme (Let's Encrypt) certificates for trillium.rs
+
+[`Acme`] obtains a certificate for a set of domains from an
+[acme](https://www.rfc-editor.org/rfc/rfc8555) certificate authority
+such as [Let's Encrypt](https://letsencrypt.org), serves it with a
+[`RustlsAcceptor`], and renews it before it expires, replacing the
+certificate in the running acceptor without a restart.
+
+```rust,no_run
+use trillium::Conn;
+use trillium_acme::Acme;
+use trillium_client::Client;
+use trillium_smol::ClientConfig;
+use trillium_rustls::RustlsConfig;
+
+let client = Client::new(RustlsConfig::<ClientConfig>::default()).with_default_pool();
+let acme = Acme::new(client, ["example.com", "www.example.com"])
+    .with_contact("mailto:admin@example.com")
+    .with_cache_dir("./acme-cache");
+
+let acceptor = acme.acceptor();
+acme.spawn();
+
+trillium_smol::config()
+    .with_port(443)
+    .with_acceptor(acceptor)
+    .run(|conn: Conn| async move { conn.ok("hello over https") });
+```
+
+## Challenges
+
+By default, domains are validated with the
+[tls-alpn-01](https://www.rfc-editor.org/rfc/rfc8737) challenge,
+which is answered by the acceptor itself and requires the server to
+be reachable on port 443. To use the http-01 challenge instead, which
+requires the server to be reachable on port 80, share an
+[`AcmeChallenges`] with an [`HttpsRedirect`] on port 80:
+
+```rust,no_run
+use trillium::Conn;
+use trillium_acme::Acme;
+use trillium_client::Client;
+use trillium_rustls::RustlsConfig;
+use trillium_smol::{AcmeChallenges, ClientConfig, HttpsRedirect, Listener};
+
+let challenges = AcmeChallenges::new();
+let client = Client::new(RustlsConfig::<ClientConfig>::default()).with_default_pool();
+let acme = Acme::new(client, ["example.com"])
+    .with_cache_dir("./acme-cache")
+    .with_http01_challenges(challenges.clone());
+
+let acceptor = acme.acceptor();
+acme.spawn();
+
+trillium_smol::config()
+    .with_port(443)
+    .with_acceptor(acceptor)
+    .with_https_redirect(
+        Listener::tcp("0.0.0.0", 80),
+        HttpsRedirect::new().with_acme_challenges(challenges),
+    )
+    .run(|conn: Conn| async move { conn.ok("hello over https") });
+```
+
+## Persistence
+
+The account key and certificates are written to the directory
+provided to [`Acme::with_cache_dir`], and a cached certificate is
+used instead of ordering a new one as long as it is not due for
+renewal. Without a cache directory, a new account and certificate are
+requested every time the server starts, which quickly runs into
+[Let's Encrypt's rate
+limits](https://letsencrypt.org/docs/rate-limits/). Consider using
+[`LETS_ENCRYPT_STAGING`] while testing.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod account;
+mod cache;
+mod error;
+mod resolver;
+mod session;
+
+pub use error::Error;
+pub use trillium_server_common::{AcmeChallenges, HttpsRedirect};
+
+use account::AccountKey;
+use async_io::Timer;
+use cache::Cache;
+use rcgen::{CertificateParams, DistinguishedName, KeyPair};
+use resolver::{challenge_certificate, CertResolver, Certificate, ACME_TLS_ALPN};
+use session::{Session, Status};
+use std::{
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use trillium_client::Client;
+use trillium_rustls::{
+    rustls::{crypto::CryptoProvider, sign::CertifiedKey, ServerConfig},
+    RustlsAcceptor,
+};
+
+/// The directory url for Let's Encrypt's production environment
+pub const LETS_ENCRYPT_PRODUCTION: &str = "https://acme-v02.api.letsencrypt.org/directory";
+
+/// The directory url for Let's Encrypt's staging environment, which
+/// has much higher rate limits but issues certificates that are not
+/// trusted by browsers
+pub const LETS_ENCRYPT_STAGING: &str = "https://acme-staging-v02.api.letsencrypt.org/directory";
+
+const POLL_ATTEMPTS: usize = 30;
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/**
+Obtains and renews a certificate for a set of domains from an acme
+certificate authority. See the crate-level docs for usage.
+*/
+#[derive(Debug, Clone)]
+pub struct Acme {
+    client: Client,
+    domains: Vec<String>,
+    directory_url: String,
+    contact: Vec<String>,
+    cache: Cache,
+    http01_challenges: Option<AcmeChallenges>,
+    renew_before: Duration,
+    retry_interval: Duration,
+    resolver: Arc<CertResolver>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl Acme {
+    /**
+    Builds a new Acme for the provided domains, which will all be
+    included in a single certificate. The [`Client`] is used to make
+    requests to the acme server, and so must support https.
+
+    By default, this uses [`LETS_ENCRYPT_PRODUCTION`], validates
+    domains with the tls-alpn-01 challenge, and renews certificates
+    thirty days before they expire.
+    */
+    pub fn new(client: Client, domains: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            client,
+            domains: domains.into_iter().map(Into::into).collect(),
+            directory_url: LETS_ENCRYPT_PRODUCTION.into(),
+            contact: vec![],
+            cache: Cache::default(),
+            http01_challenges: None,
+            renew_before: Duration::from_secs(30 * 24 * 60 * 60),
+            retry_interval: Duration::from_secs(60 * 60),
+            resolver: Arc::default(),
+            provider: trillium_rustls::crypto_provider(),
+        }
+    }
+
+    /// use the acme server with this directory url, such as
+    /// [`LETS_ENCRYPT_STAGING`]
+    pub fn with_directory_url(mut self, directory_url: impl Into<String>) -> Self {
+        self.directory_url = directory_url.into();
+        self
+    }
+
+    /// add a contact url for the acme account, such as
+    /// `mailto:admin@example.com`. This can be called more than once.
+    pub fn with_contact(mut self, contact: impl Into<String>) -> Self {
+        self.contact.push(contact.into());
+        self
+    }
+
+    /// persist the account key and certificates in this directory,
+    /// which will be created if it does not exist
+    pub fn with_cache_dir(mut self, cache_dir: impl Into<PathBuf>) -> Self {
+        self.cache = Cache::new(cache_dir.into());
+        self
+    }
+
+    /// validate domains with the http-01 challenge, answered by an
+    /// [`HttpsRedirect`] on port 80 that shares these
+    /// [`AcmeChallenges`], instead of the tls-alpn-01 challenge
+    pub fn with_http01_challenges(mut self, acme_challenges: AcmeChallenges) -> Self {
+        self.http01_challenges = Some(acme_challenges);
+        self
+    }
+
+    /// renew the certificate when it will expire within this duration
+    pub fn with_renew_before(mut self, renew_before: Duration) -> Self {
+        self.renew_before = renew_before;
+        self
+    }
+
+    /// after failing to obtain a certificate, wait this long before
+    /// trying again. The default is one hour.
+    pub fn with_retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /**
+    Builds a rustls [`ServerConfig`] that serves the current
+    certificate. This can be customized before converting it into a
+    [`RustlsAcceptor`], but the certificate resolver must not be
+    replaced. When using the tls-alpn-01 challenge, the `acme-tls/1`
+    alpn protocol must remain in
+    [`alpn_protocols`](ServerConfig::alpn_protocols).
+    */
+    pub fn rustls_server_config(&self) -> ServerConfig {
+        let mut server_config = ServerConfig::builder_with_provider(self.provider.clone())
+            .with_safe_default_protocol_versions()
+            .expect("crypto provider did not support safe default protocol versions")
+            .with_no_client_auth()
+            .with_cert_resolver(self.resolver.clone());
+
+        if self.http01_challenges.is_none() {
+            server_config.alpn_protocols = vec![b"http/1.1".to_vec(), ACME_TLS_ALPN.to_vec()];
+        }
+
+        server_config
+    }
+
+    /// Builds a [`RustlsAcceptor`] that serves the current certificate.
+    /// Until a certificate has been obtained, tls handshakes will fail.
+    pub fn acceptor(&self) -> RustlsAcceptor {
+        RustlsAcceptor::new(self.rustls_server_config())
+    }
+
+    /// the certificate currently being served, if one has been obtained
+    pub fn certified_key(&self) -> Option<Arc<CertifiedKey>> {
+        self.resolver
+            .certificate()
+            .map(|certificate| certificate.certified_key)
+    }
+
+    /// when the certificate currently being served expires, if one has
+    /// been obtained
+    pub fn expiration(&self) -> Option<SystemTime> {
+        self.resolver
+            .certificate()
+            .map(|certificate| certificate.not_after)
+    }
+
+    fn needs_renewal(&self, not_after: SystemTime) -> bool {
+        SystemTime::now() + self.renew_before >= not_after
+    }
+
+    /**
+    Ensures that a certificate that is not due for renewal is being
+    served, loading it from the cache directory or ordering a new one
+    from the acme server if needed, and returns when it expires.
+
+    This is called periodically by [`Acme::run`], and can be called
+    directly to obtain a certificate before starting a server.
+    */
+    pub async fn provision(&self) -> Result<SystemTime, Error> {
+        match self.resolver.certificate() {
+            Some(certificate) if !self.needs_renewal(certificate.not_after) => {
+                return Ok(certificate.not_after)
+            }
+
+            Some(_) => {}
+
+            None => {
+                if let Some(pem) = self.cache.certificate(&self.domains)? {
+                    match Certificate::from_pem(&pem, &self.provider) {
+                        Ok(certificate) => {
+                            let not_after = certificate.not_after;
+                            self.resolver.set_certificate(certificate);
+                            if !self.needs_renewal(not_after) {
+                                log::info!("using cached certificate for {}", self.describe());
+                                return Ok(not_after);
+                            }
+                        }
+
+                        Err(e) => log::warn!("ignoring cached certificate: {e}"),
+                    }
+                }
+            }
+        }
+
+        log::info!("ordering a certificate for {}", self.describe());
+        let pem = self.order().await?;
+        let certificate = Certificate::from_pem(&pem, &self.provider)?;
+        let not_after = certificate.not_after;
+        self.resolver.set_certificate(certificate);
+        log::info!("obtained a certificate for {}", self.describe());
+
+        if let Err(e) = self.cache.store_certificate(&self.domains, &pem) {
+            log::error!("could not cache certificate: {e}");
+        }
+
+        Ok(not_after)
+    }
+
+    /**
+    Calls [`Acme::provision`] whenever the certificate is due for
+    renewal, retrying after failures. This future never resolves, so
+    it should be spawned, for example with [`Acme::spawn`].
+    */
+    pub async fn run(self) {
+        loop {
+            let wait = match self.provision().await {
+                Ok(not_after) => not_after
+                    .duration_since(SystemTime::now())
+                    .unwrap_or_default()
+                    .saturating_sub(self.renew_before)
+                    .max(self.retry_interval),
+
+                Err(e) => {
+                    log::error!(
+                        "could not obtain a certificate for {}: {e}",
+                        self.describe()
+                    );
+                    self.retry_interval
+                }
+            };
+
+            Timer::after(wait).await;
+        }
+    }
+
+    /**
+    Spawns [`Acme::run`] with the [`Client`]'s runtime. With
+    `trillium-tokio`, this must be called from within the tokio
+    runtime.
+    */
+    pub fn spawn(self) {
+        let connector = self.client.connector().clone();
+        connector.spawn(Box::pin(self.run()));
+    }
+
+    fn describe(&self) -> String {
+        self.domains.join(", ")
+    }
+
+    fn account_key(&self) -> Result<AccountKey, Error> {
+        if let Some(pem) = self.cache.account_key()? {
+            return AccountKey::from_pem(&pem);
+        }
+
+        let account_key = AccountKey::generate()?;
+        self.cache.store_account_key(account_key.pem())?;
+        Ok(account_key)
+    }
+
+    /// orders a certificate, returning the private key and certificate
+    /// chain as pem
+    async fn order(&self) -> Result<String, Error> {
+        let account_key = self.account_key()?;
+        let mut session = Session::new(&self.client, &account_key, &self.directory_url).await?;
+        session.new_account(&self.contact).await?;
+
+        let (order_url, order) = session.new_order(&self.domains).await?;
+        for authorization_url in &order.authorizations {
+            self.authorize(&mut session, authorization_url).await?;
+        }
+
+        let order = self.poll_order(&mut session, &order_url).await?;
+        if order.status != Status::Ready {
+            return Err(order_error(order, "ready"));
+        }
+
+        let key_pair = KeyPair::generate()?;
+        let mut params = CertificateParams::new(self.domains.clone())?;
+        params.distinguished_name = DistinguishedName::new();
+        let csr = params.serialize_request(&key_pair)?;
+        session.finalize(&order.finalize, csr.der()).await?;
+
+        let order = self.poll_order(&mut session, &order_url).await?;
+        let Some(certificate_url) = order
+            .certificate
+            .as_deref()
+            .filter(|_| order.status == Status::Valid)
+        else {
+            return Err(order_error(order, "valid"));
+        };
+
+        let chain = session.certificate(certificate_url).await?;
+        Ok(format!("{}{chain}", key_pair.serialize_pem()))
+    }
+
+    /// waits for an order to no longer be pending or processing
+    async fn poll_order(
+        &self,
+        session: &mut Session<'_>,
+        order_url: &str,
+    ) -> Result<session::Order, Error> {
+        for _ in 0..POLL_ATTEMPTS {
+            let order = session.order(order_url).await?;
+            if !matches!(order.status, Status::Pending | Status::Processing) {
+                return Ok(order);
+            }
+            Timer::after(POLL_INTERVAL).await;
+        }
+
+        Err(Error::protocol("timed out waiting for the acme order"))
+    }
+
+    async fn authorize(&self, session: &mut Session<'_>, url: &str) -> Result<(), Error> {
+        let authorization = session.authorization(url).await?;
+        if authorization.status == Status::Valid {
+            return Ok(());
+        }
+
+        let domain = authorization.identifier.value;
+        let kind = if self.http01_challenges.is_some() {
+            "http-01"
+        } else {
+            "tls-alpn-01"
+        };
+
+        let challenge = authorization
+            .challenges
+            .into_iter()
+            .find(|challenge| challenge.kind == kind)
+            .ok_or_else(|| {
+                Error::protocol(format!("acme server did not offer {kind} for {domain}"))
+            })?;
+
+        let key_authorization = session.key_authorization(&challenge.token);
+        match &self.http01_challenges {
+            Some(challenges) => challenges.insert(&challenge.token, key_authorization),
+            None => self.resolver.insert_challenge(
+                &domain,
+                challenge_certificate(&domain, &key_authorization, &self.provider)?,
+            ),
+        }
+
+        let result = self.validate(session, url, &challenge.url, &domain).await;
+
+        match &self.http01_challenges {
+            Some(challenges) => {
+                challenges.remove(&challenge.token);
+            }
+            None => self.resolver.remove_challenge(&domain),
+        }
+
+        result
+    }
+
+    async fn validate(
+        &self,
+        session: &mut Session<'_>,
+        authorization_url: &str,
+        challenge_url: &str,
+        domain: &str,
+    ) -> Result<(), Error> {
+        session.respond(challenge_url).await?;
+
+        for _ in 0..POLL_ATTEMPTS {
+            let authorization = session.authorization(authorization_url).await?;
+            match authorization.status {
+                Status::Valid => return Ok(()),
+                Status::Pending => {
+                    Timer::after(POLL_INTERVAL).await;
+                }
+                status => {
+                    let problem = authorization
+                        .challenges
+                        .into_iter()
+                        .find_map(|challenge| challenge.error);
+                    return Err(match problem {
+                        Some(problem) => problem.into(),
+                        None => {
+                            Error::protocol(format!("authorization for {domain} was {status:?}"))
+                        }
+                    });
+                }
+            }
+        }
+
+        Err(Error::protocol(format!(
+            "timed out waiting for validation of {domain}"
+        )))
+    }
+}
+
+fn order_error(order: session::Order, expected: &str) -> Error {
+    match order.error {
+        Some(problem) => problem.into(),
+        None => Error::protocol(format!(
+            "expected acme order to be {expected}, but it was {:?}",
+            order.status
+        )),
+    }
+}
@@ -0,0 +1,161 @@
+use crate::Error;
+use rcgen::{CertificateParams, CustomExtension, KeyPair};
+use ring::digest::{digest, SHA256};
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+use trillium_rustls::rustls::{
+    crypto::CryptoProvider,
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+};
+use yasna::{tags, ASN1Result, BERReader};
+
+/// the alpn protocol that acme servers use to validate tls-alpn-01
+/// challenges, as specified in [rfc 8737](https://www.rfc-editor.org/rfc/rfc8737)
+pub(crate) const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+
+/// A certificate chain and private key, and when the certificate expires
+#[derive(Debug, Clone)]
+pub(crate) struct Certificate {
+    pub(crate) certified_key: Arc<CertifiedKey>,
+    pub(crate) not_after: SystemTime,
+}
+
+impl Certificate {
+    /// parses a pem private key followed by a pem certificate chain
+    pub(crate) fn from_pem(pem: &str, provider: &CryptoProvider) -> Result<Self, Error> {
+        let chain = rustls_pemfile::certs(&mut Cursor::new(pem)).collect::<Result<Vec<_>, _>>()?;
+        let key = rustls_pemfile::private_key(&mut Cursor::new(pem))?
+            .ok_or_else(|| Error::protocol("no private key found"))?;
+        let not_after = chain
+            .first()
+            .and_then(|cert| not_after(cert))
+            .ok_or_else(|| Error::protocol("no valid certificate found"))?;
+
+        Ok(Self {
+            certified_key: certified_key(chain, key, provider)?,
+            not_after,
+        })
+    }
+}
+
+fn certified_key(
+    chain: Vec<CertificateDer<'static>>,
+    key: PrivateKeyDer<'static>,
+    provider: &CryptoProvider,
+) -> Result<Arc<CertifiedKey>, Error> {
+    let signing_key = provider.key_provider.load_private_key(key)?;
+    Ok(Arc::new(CertifiedKey::new(chain, signing_key)))
+}
+
+/// builds a self-signed certificate for a tls-alpn-01 challenge
+pub(crate) fn challenge_certificate(
+    domain: &str,
+    key_authorization: &str,
+    provider: &CryptoProvider,
+) -> Result<Arc<CertifiedKey>, Error> {
+    let mut params = CertificateParams::new(vec![domain.to_string()])?;
+    params.custom_extensions = vec![CustomExtension::new_acme_identifier(
+        digest(&SHA256, key_authorization.as_bytes()).as_ref(),
+    )];
+    let key_pair = KeyPair::generate()?;
+    let cert = params.self_signed(&key_pair)?;
+    let key = PrivatePkcs8KeyDer::from(key_pair.serialize_der());
+    certified_key(vec![cert.der().clone()], key.into(), provider)
+}
+
+/// reads the notAfter time from a der x.509 certificate
+fn not_after(cert: &[u8]) -> Option<SystemTime> {
+    fn time(reader: BERReader<'_, '_>) -> ASN1Result<SystemTime> {
+        if reader.lookahead_tag()? == tags::TAG_UTCTIME {
+            Ok((*reader.read_utctime()?.datetime()).into())
+        } else {
+            Ok((*reader.read_generalized_time()?.datetime()).into())
+        }
+    }
+
+    yasna::parse_der(cert, |reader| {
+        reader.read_sequence(|certificate| {
+            let not_after = certificate.next().read_sequence(|tbs_certificate| {
+                // version, which is optional, then serial number,
+                // signature algorithm, and issuer
+                tbs_certificate.read_optional(|version| {
+                    version.read_tagged(yasna::Tag::context(0), |version| version.read_der())
+                })?;
+                for _ in 0..3 {
+                    tbs_certificate.next().read_der()?;
+                }
+
+                let not_after = tbs_certificate.next().read_sequence(|validity| {
+                    time(validity.next())?;
+                    time(validity.next())
+                })?;
+
+                while tbs_certificate
+                    .read_optional(|rest| rest.read_der())?
+                    .is_some()
+                {}
+                Ok(not_after)
+            })?;
+
+            certificate.next().read_der()?;
+            certificate.next().read_der()?;
+            Ok(not_after)
+        })
+    })
+    .ok()
+}
+
+/// A rustls certificate resolver that serves the current certificate,
+/// or a tls-alpn-01 challenge certificate to an acme server that
+/// negotiates the `acme-tls/1` protocol
+#[derive(Debug, Default)]
+pub(crate) struct CertResolver {
+    certificate: RwLock<Option<Certificate>>,
+    challenges: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl CertResolver {
+    pub(crate) fn certificate(&self) -> Option<Certificate> {
+        self.certificate.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_certificate(&self, certificate: Certificate) {
+        *self.certificate.write().unwrap() = Some(certificate);
+    }
+
+    pub(crate) fn insert_challenge(&self, domain: &str, certified_key: Arc<CertifiedKey>) {
+        self.challenges
+            .write()
+            .unwrap()
+            .insert(domain.to_string(), certified_key);
+    }
+
+    pub(crate) fn remove_challenge(&self, domain: &str) {
+        self.challenges.write().unwrap().remove(domain);
+    }
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let is_acme_challenge = client_hello
+            .alpn()
+            .is_some_and(|mut protocols| protocols.any(|protocol| protocol == ACME_TLS_ALPN));
+
+        if is_acme_challenge {
+            let domain = client_hello.server_name()?;
+            self.challenges.read().unwrap().get(domain).cloned()
+        } else {
+            self.certificate
+                .read()
+                .unwrap()
+                .as_ref()
+                .map(|certificate| certificate.certified_key.clone())
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::{account::AccountKey, Error};
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use trillium_client::{
+    Client, Conn,
+    KnownHeaderName::{ContentType, Location},
+    Method,
+};
+
+const REPLAY_NONCE: &str = "replay-nonce";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Directory {
+    new_nonce: String,
+    new_account: String,
+    new_order: String,
+}
+
+/// the status of an order, authorization, or challenge
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Status {
+    Pending,
+    Ready,
+    Processing,
+    Valid,
+    Invalid,
+    Deactivated,
+    Expired,
+    Revoked,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Problem {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    detail: String,
+}
+
+impl From<Problem> for Error {
+    fn from(Problem { kind, detail }: Problem) -> Self {
+        Error::Problem { kind, detail }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Order {
+    pub(crate) status: Status,
+    pub(crate) authorizations: Vec<String>,
+    pub(crate) finalize: String,
+    pub(crate) certificate: Option<String>,
+    pub(crate) error: Option<Problem>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Identifier {
+    pub(crate) value: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Authorization {
+    pub(crate) status: Status,
+    pub(crate) identifier: Identifier,
+    pub(crate) challenges: Vec<Challenge>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Challenge {
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    pub(crate) url: String,
+    pub(crate) token: String,
+    pub(crate) error: Option<Problem>,
+}
+
+/// A series of signed requests to an acme server, as described in
+/// [rfc 8555](https://www.rfc-editor.org/rfc/rfc8555)
+#[derive(Debug)]
+pub(crate) struct Session<'a> {
+    client: &'a Client,
+    key: &'a AccountKey,
+    directory: Directory,
+    nonce: Option<String>,
+    kid: Option<String>,
+}
+
+impl<'a> Session<'a> {
+    pub(crate) async fn new(
+        client: &'a Client,
+        key: &'a AccountKey,
+        directory_url: &str,
+    ) -> Result<Session<'a>, Error> {
+        let mut conn = client.get(directory_url).await?;
+        let directory = response_json(&mut conn).await?;
+        Ok(Self {
+            client,
+            key,
+            directory,
+            nonce: None,
+            kid: None,
+        })
+    }
+
+    async fn nonce(&mut self) -> Result<String, Error> {
+        if let Some(nonce) = self.nonce.take() {
+            return Ok(nonce);
+        }
+
+        let conn = self
+            .client
+            .build_conn(Method::Head, &*self.directory.new_nonce)
+            .await?;
+
+        conn.response_headers()
+            .get_str(REPLAY_NONCE)
+            .map(String::from)
+            .ok_or_else(|| Error::protocol("acme server did not provide a nonce"))
+    }
+
+    async fn post(&mut self, url: &str, payload: Option<&Value>) -> Result<Conn, Error> {
+        let mut retried = false;
+        loop {
+            let nonce = self.nonce().await?;
+            let body = self.key.sign(url, &nonce, self.kid.as_deref(), payload)?;
+            let mut conn = self
+                .client
+                .post(url)
+                .with_request_header(ContentType, "application/jose+json")
+                .with_body(body)
+                .await?;
+
+            self.nonce = conn
+                .response_headers()
+                .get_str(REPLAY_NONCE)
+                .map(String::from);
+
+            if conn.status().is_some_and(|status| status.is_success()) {
+                return Ok(conn);
+            }
+
+            let problem: Problem = response_json(&mut conn).await?;
+            if !retried && problem.kind == "urn:ietf:params:acme:error:badNonce" {
+                log::debug!("acme server rejected nonce, retrying");
+                retried = true;
+                continue;
+            }
+
+            return Err(problem.into());
+        }
+    }
+
+    async fn post_json<T: DeserializeOwned>(
+        &mut self,
+        url: &str,
+        payload: Option<&Value>,
+    ) -> Result<(T, Option<String>), Error> {
+        let mut conn = self.post(url, payload).await?;
+        let location = conn.response_headers().get_str(Location).map(String::from);
+        Ok((response_json(&mut conn).await?, location))
+    }
+
+    pub(crate) fn key_authorization(&self, token: &str) -> String {
+        self.key.key_authorization(token)
+    }
+
+    /// registers the account key, or finds the existing account for
+    /// it, agreeing to the acme server's terms of service
+    pub(crate) async fn new_account(&mut self, contact: &[String]) -> Result<(), Error> {
+        let url = self.directory.new_account.clone();
+        let payload = json!({ "termsOfServiceAgreed": true, "contact": contact });
+        let (_, location): (Value, _) = self.post_json(&url, Some(&payload)).await?;
+        self.kid = Some(
+            location
+                .ok_or_else(|| Error::protocol("acme server did not provide an account url"))?,
+        );
+        Ok(())
+    }
+
+    pub(crate) async fn new_order(&mut self, domains: &[String]) -> Result<(String, Order), Error> {
+        let url = self.directory.new_order.clone();
+        let identifiers = domains
+            .iter()
+            .map(|domain| json!({ "type": "dns", "value": domain }))
+            .collect::<Vec<_>>();
+        let payload = json!({ "identifiers": identifiers });
+        let (order, location) = self.post_json(&url, Some(&payload)).await?;
+        let location =
+            location.ok_or_else(|| Error::protocol("acme server did not provide an order url"))?;
+        Ok((location, order))
+    }
+
+    pub(crate) async fn order(&mut self, url: &str) -> Result<Order, Error> {
+        Ok(self.post_json(url, None).await?.0)
+    }
+
+    pub(crate) async fn authorization(&mut self, url: &str) -> Result<Authorization, Error> {
+        Ok(self.post_json(url, None).await?.0)
+    }
+
+    /// tells the acme server that the challenge is ready to be validated
+    pub(crate) async fn respond(&mut self, challenge_url: &str) -> Result<(), Error> {
+        let _: (Value, _) = self.post_json(challenge_url, Some(&json!({}))).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn finalize(&mut self, url: &str, csr: &[u8]) -> Result<Order, Error> {
+        let payload = json!({ "csr": crate::account::base64(csr) });
+        Ok(self.post_json(url, Some(&payload)).await?.0)
+    }
+
+    /// downloads a pem certificate chain
+    pub(crate) async fn certificate(&mut self, url: &str) -> Result<String, Error> {
+        let mut conn = self.post(url, None).await?;
+        Ok(conn.response_body().read_string().await?)
+    }
+}
+
+async fn response_json<T: DeserializeOwned>(conn: &mut Conn) -> Result<T, Error> {
+    let body = conn.response_body().read_string().await?;
+    Ok(serde_json::from_str(&body)?)
+}
@@ -0,0 +1,308 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rcgen::{date_time_ymd, CertificateParams, IsCa, KeyPair, PublicKeyData, SignatureAlgorithm};
+use serde_json::{json, Value};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use trillium::{Conn, KnownHeaderName::Location, Method, Status};
+use trillium_acme::{Acme, AcmeChallenges, Error};
+use trillium_client::Client;
+use trillium_testing::block_on;
+
+const DIRECTORY: &str = "http://acme.test/directory";
+
+/// An in-memory acme server that issues certificates signed by a
+/// test certificate authority
+#[derive(Clone)]
+struct MockAcme {
+    challenge_type: &'static str,
+    http01_challenges: Option<AcmeChallenges>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    orders: usize,
+    validated: bool,
+    certificate: Option<String>,
+}
+
+impl MockAcme {
+    fn new(challenge_type: &'static str) -> Self {
+        Self {
+            challenge_type,
+            http01_challenges: None,
+            state: Arc::default(),
+        }
+    }
+
+    fn with_http01_challenges(mut self, http01_challenges: AcmeChallenges) -> Self {
+        self.http01_challenges = Some(http01_challenges);
+        self
+    }
+
+    fn orders(&self) -> usize {
+        self.state.lock().unwrap().orders
+    }
+
+    fn order(&self) -> Value {
+        let state = self.state.lock().unwrap();
+        let status = match (state.validated, &state.certificate) {
+            (_, Some(_)) => "valid",
+            (true, None) => "ready",
+            (false, None) => "pending",
+        };
+        json!({
+            "status": status,
+            "authorizations": ["http://acme.test/authz/1"],
+            "finalize": "http://acme.test/finalize/1",
+            "certificate": "http://acme.test/cert/1",
+        })
+    }
+
+    fn authorization(&self) -> Value {
+        let status = if self.state.lock().unwrap().validated {
+            "valid"
+        } else {
+            "pending"
+        };
+        json!({
+            "status": status,
+            "identifier": { "type": "dns", "value": "example.com" },
+            "challenges": [
+                { "type": "dns-01", "url": "http://acme.test/challenge/2", "token": "dns-token", "status": "pending" },
+                { "type": self.challenge_type, "url": "http://acme.test/challenge/1", "token": "token", "status": status },
+            ],
+        })
+    }
+
+    fn validate(&self) -> Value {
+        if let Some(challenges) = &self.http01_challenges {
+            let key_authorization = challenges
+                .get("token")
+                .expect("challenge was not provisioned");
+            assert!(key_authorization.starts_with("token."));
+        }
+        self.state.lock().unwrap().validated = true;
+        json!({ "type": self.challenge_type, "status": "valid", "token": "token" })
+    }
+
+    fn finalize(&self, payload: Value) -> Value {
+        let csr = URL_SAFE_NO_PAD
+            .decode(payload["csr"].as_str().unwrap())
+            .unwrap();
+        let public_key = csr_public_key(&csr);
+
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(vec![]).unwrap();
+        ca_params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let mut params = CertificateParams::new(vec!["example.com".to_string()]).unwrap();
+        params.not_after = date_time_ymd(2100, 1, 1);
+        let certificate = params.signed_by(&public_key, &ca, &ca_key).unwrap();
+
+        self.state.lock().unwrap().certificate = Some(certificate.pem() + ca.pem().as_str());
+        self.order()
+    }
+}
+
+/// the p-256 public key from a certificate signing request
+struct CsrPublicKey(Vec<u8>);
+
+impl PublicKeyData for CsrPublicKey {
+    fn der_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn algorithm(&self) -> &SignatureAlgorithm {
+        &rcgen::PKCS_ECDSA_P256_SHA256
+    }
+}
+
+fn csr_public_key(csr: &[u8]) -> CsrPublicKey {
+    yasna::parse_der(csr, |reader| {
+        reader.read_sequence(|csr| {
+            let public_key = csr.next().read_sequence(|info| {
+                info.next().read_der()?; // version
+                info.next().read_der()?; // subject
+                let public_key = info.next().read_sequence(|public_key_info| {
+                    public_key_info.next().read_der()?; // algorithm
+                    Ok(public_key_info.next().read_bitvec_bytes()?.0)
+                })?;
+                while info.read_optional(|rest| rest.read_der())?.is_some() {}
+                Ok(public_key)
+            })?;
+            csr.next().read_der()?;
+            csr.next().read_der()?;
+            Ok(CsrPublicKey(public_key))
+        })
+    })
+    .unwrap()
+}
+
+#[trillium::async_trait]
+impl trillium::Handler for MockAcme {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        conn = conn.with_response_header("replay-nonce", "nonce");
+        if conn.method() == Method::Get && conn.path() == "/directory" {
+            return conn.ok(json!({
+                "newNonce": "http://acme.test/nonce",
+                "newAccount": "http://acme.test/account",
+                "newOrder": "http://acme.test/order",
+            })
+            .to_string());
+        }
+
+        if conn.method() == Method::Head {
+            return conn.with_status(Status::Ok).halt();
+        }
+
+        let body: Value = serde_json::from_str(&conn.request_body_string().await.unwrap()).unwrap();
+        let payload = URL_SAFE_NO_PAD
+            .decode(body["payload"].as_str().unwrap())
+            .unwrap();
+        let payload: Value = serde_json::from_slice(&payload).unwrap_or(Value::Null);
+
+        let response = match conn.path() {
+            "/account" => {
+                conn = conn.with_response_header(Location, "http://acme.test/account/1");
+                json!({ "status": "valid" })
+            }
+
+            "/order" => {
+                let mut state = self.state.lock().unwrap();
+                *state = State {
+                    orders: state.orders + 1,
+                    ..State::default()
+                };
+                drop(state);
+                conn = conn.with_response_header(Location, "http://acme.test/order/1");
+                self.order()
+            }
+
+            "/order/1" => self.order(),
+            "/authz/1" => self.authorization(),
+            "/challenge/1" => self.validate(),
+            "/finalize/1" => self.finalize(payload),
+            "/cert/1" => {
+                let certificate = self.state.lock().unwrap().certificate.clone().unwrap();
+                return conn.ok(certificate);
+            }
+
+            _ => return conn.with_status(Status::NotFound).halt(),
+        };
+
+        conn.ok(response.to_string())
+    }
+}
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("trillium-acme-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn acme(mock: &MockAcme) -> Acme {
+    Acme::new(
+        Client::new(trillium_testing::connector(mock.clone())),
+        ["example.com"],
+    )
+    .with_directory_url(DIRECTORY)
+}
+
+#[test]
+fn http01() {
+    block_on(async {
+        let challenges = AcmeChallenges::new();
+        let mock = MockAcme::new("http-01").with_http01_challenges(challenges.clone());
+        let acme = acme(&mock).with_http01_challenges(challenges.clone());
+
+        assert!(acme.certified_key().is_none());
+        let expiration = acme.provision().await.unwrap();
+        assert_eq!(expiration, SystemTime::from(date_time_ymd(2100, 1, 1)));
+        assert_eq!(acme.expiration(), Some(expiration));
+        assert_eq!(acme.certified_key().unwrap().cert.len(), 2);
+        assert!(challenges.get("token").is_none());
+        assert_eq!(mock.orders(), 1);
+
+        // not yet due for renewal
+        acme.provision().await.unwrap();
+        assert_eq!(mock.orders(), 1);
+    });
+}
+
+#[test]
+fn tls_alpn01() {
+    block_on(async {
+        let mock = MockAcme::new("tls-alpn-01");
+        let acme = acme(&mock);
+        acme.provision().await.unwrap();
+        assert!(acme.certified_key().is_some());
+
+        let server_config = acme.rustls_server_config();
+        assert!(server_config
+            .alpn_protocols
+            .contains(&b"acme-tls/1".to_vec()));
+    });
+}
+
+#[test]
+fn missing_challenge_type() {
+    block_on(async {
+        let mock = MockAcme::new("tls-alpn-01");
+        let acme = acme(&mock).with_http01_challenges(AcmeChallenges::new());
+        let error = acme.provision().await.unwrap_err();
+        assert!(matches!(error, Error::Protocol(_)));
+        assert_eq!(
+            error.to_string(),
+            "acme server did not offer http-01 for example.com"
+        );
+    });
+}
+
+#[test]
+fn cache() {
+    block_on(async {
+        let dir = cache_dir("cache");
+        let mock = MockAcme::new("tls-alpn-01");
+        let first = acme(&mock).with_cache_dir(&dir);
+        first.provision().await.unwrap();
+        assert!(dir.join("account.pem").exists());
+        assert!(dir.join("example.com.pem").exists());
+
+        // a new Acme with the same cache dir does not order a new certificate
+        let cached = acme(&mock).with_cache_dir(&dir);
+        assert_eq!(
+            cached.provision().await.unwrap(),
+            first.expiration().unwrap()
+        );
+        assert_eq!(
+            cached.certified_key().unwrap().cert,
+            first.certified_key().unwrap().cert
+        );
+        assert_eq!(mock.orders(), 1);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    });
+}
+
+#[test]
+fn renewal() {
+    block_on(async {
+        let mock = MockAcme::new("tls-alpn-01");
+        // the mock certificates expire in 2100, so they are always due
+        let acme = acme(&mock).with_renew_before(Duration::from_secs(200 * 365 * 24 * 60 * 60));
+
+        acme.provision().await.unwrap();
+        let first = acme.certified_key().unwrap();
+
+        acme.provision().await.unwrap();
+        let second = acme.certified_key().unwrap();
+
+        assert_eq!(mock.orders(), 2);
+        assert_ne!(first.cert, second.cert);
+    });
+}
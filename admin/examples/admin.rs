@@ -0,0 +1,22 @@
+use trillium::Conn;
+use trillium_admin::{admin, RecentErrors};
+use trillium_basic_auth::BasicAuth;
+use trillium_router::router;
+
+fn main() {
+    let config = trillium_smol::config();
+    let recent_errors = RecentErrors::default();
+    let admin = admin()
+        .with_server_handle(config.handle())
+        .with_recent_errors(recent_errors.clone());
+
+    // try `curl http://localhost:8080/fail` and then
+    // `curl -u admin:admin http://localhost:8080/admin`
+    config.run((
+        recent_errors,
+        router()
+            .get("/admin", (BasicAuth::new("admin", "admin"), admin))
+            .get("/fail", |conn: Conn| async move { conn.with_status(500) })
+            .get("/", "ok"),
+    ));
+}
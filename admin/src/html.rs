@@ -0,0 +1,72 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em}\
+table{border-collapse:collapse}\
+th,td{border:1px solid #ccc;padding:.25em .5em;text-align:left;vertical-align:top}\
+th{background:#f4f4f4}";
+
+pub(crate) fn render(title: &str, snapshot: &Value) -> String {
+    let title = escape(title);
+    let mut html = format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>{STYLE}</style></head><body><h1>{title}</h1>"
+    );
+
+    if let Value::Object(sections) = snapshot {
+        for (name, section) in sections {
+            let _ = write!(html, "<section><h2>{}</h2>", escape(name));
+            render_value(&mut html, section);
+            html.push_str("</section>");
+        }
+    }
+
+    html.push_str("</body></html>");
+    html
+}
+
+fn render_value(html: &mut String, value: &Value) {
+    match value {
+        Value::Object(map) if map.is_empty() => html.push_str("<em>none</em>"),
+        Value::Array(array) if array.is_empty() => html.push_str("<em>none</em>"),
+
+        Value::Object(map) => {
+            html.push_str("<table>");
+            for (key, value) in map {
+                let _ = write!(html, "<tr><th>{}</th><td>", escape(key));
+                render_value(html, value);
+                html.push_str("</td></tr>");
+            }
+            html.push_str("</table>");
+        }
+
+        Value::Array(array) => {
+            html.push_str("<ol>");
+            for value in array {
+                html.push_str("<li>");
+                render_value(html, value);
+                html.push_str("</li>");
+            }
+            html.push_str("</ol>");
+        }
+
+        Value::String(string) => html.push_str(&escape(string)),
+        Value::Null => html.push_str("<em>-</em>"),
+        other => html.push_str(&other.to_string()),
+    }
+}
+
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,256 @@
+/*!
+A runtime introspection dashboard for trillium applications.
+
+[`Admin`] is a handler that renders the live state of a server as
+html, or as json for requests that accept `application/json`. It does
+not perform any authentication of its own, so it should always be
+mounted behind an authentication handler such as trillium-basic-auth,
+and usually under a path with trillium-router.
+
+The dashboard is made up of named sections:
+
+* `server`: the server and listener descriptions and the uptime, which
+  are always included
+* `connections`: the open connections and requests in flight, with
+  [`Admin::with_server_handle`]
+* `recent_errors`: the most recent `5xx` responses, with
+  [`Admin::with_recent_errors`]
+* any number of application-defined sections with
+  [`Admin::with_section`], which can be used to expose the state of
+  other components, such as the topics of a trillium-sse hub
+
+```
+use trillium_admin::{admin, RecentErrors};
+use trillium_basic_auth::BasicAuth;
+use trillium_router::router;
+
+let recent_errors = RecentErrors::default();
+let handle = trillium_smol::config().handle();
+
+let app = (
+    recent_errors.clone(),
+    router().all(
+        "/admin",
+        (
+            BasicAuth::new("admin", "hunter2"),
+            admin()
+                .with_server_handle(handle.clone())
+                .with_recent_errors(recent_errors)
+                .with_section("build", || serde_json::json!({ "version": "1.2.3" })),
+        ),
+    ),
+);
+```
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod html;
+mod recent_errors;
+pub use recent_errors::{RecentError, RecentErrors};
+
+use serde_json::{json, Map, Value};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+    time::Instant,
+};
+use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName, Method};
+use trillium_server_common::ServerHandle;
+
+type Section = Box<dyn Fn() -> Value + Send + Sync + 'static>;
+
+/**
+The admin dashboard handler. See the [crate-level docs](crate) for
+details.
+
+This handler responds to `GET` requests and passes along all other
+requests.
+*/
+pub struct Admin {
+    title: Cow<'static, str>,
+    server: Value,
+    started_at: Instant,
+    server_handle: Option<ServerHandle>,
+    recent_errors: Option<RecentErrors>,
+    sections: Vec<(Cow<'static, str>, Section)>,
+}
+
+impl Debug for Admin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Admin")
+            .field("title", &self.title)
+            .field("server", &self.server)
+            .field("server_handle", &self.server_handle)
+            .field("recent_errors", &self.recent_errors)
+            .field(
+                "sections",
+                &self
+                    .sections
+                    .iter()
+                    .map(|(name, _)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for Admin {
+    fn default() -> Self {
+        Self {
+            title: Cow::Borrowed("trillium admin"),
+            server: Value::Null,
+            started_at: Instant::now(),
+            server_handle: None,
+            recent_errors: None,
+            sections: Vec::new(),
+        }
+    }
+}
+
+impl Admin {
+    /// Builds a new admin dashboard with only the `server` section
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the title of the html dashboard
+    pub fn with_title(mut self, title: impl Into<Cow<'static, str>>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    /// Includes the open connections and requests in flight for the
+    /// server with this [`ServerHandle`]
+    pub fn with_server_handle(mut self, server_handle: ServerHandle) -> Self {
+        self.server_handle = Some(server_handle);
+        self
+    }
+
+    /// Includes the errors recorded by this [`RecentErrors`], which
+    /// must also be run as a handler
+    pub fn with_recent_errors(mut self, recent_errors: RecentErrors) -> Self {
+        self.recent_errors = Some(recent_errors);
+        self
+    }
+
+    /// Adds a section that is rendered from the value returned by this
+    /// function on every request. Sections are displayed in the order
+    /// they are added, after the built-in sections, and a section with
+    /// the same name as an existing section replaces it.
+    pub fn with_section(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        section: impl Fn() -> Value + Send + Sync + 'static,
+    ) -> Self {
+        let name = name.into();
+        let section = Box::new(section) as Section;
+        match self.sections.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = section,
+            None => self.sections.push((name, section)),
+        }
+        self
+    }
+
+    /// The current state of every section, as a json object keyed by
+    /// section name
+    pub fn snapshot(&self) -> Value {
+        let mut snapshot = Map::new();
+
+        let mut server = match &self.server {
+            Value::Object(server) => server.clone(),
+            _ => Map::new(),
+        };
+        server.insert(
+            "uptime_secs".into(),
+            self.started_at.elapsed().as_secs().into(),
+        );
+        snapshot.insert("server".into(), Value::Object(server));
+
+        if let Some(server_handle) = &self.server_handle {
+            snapshot.insert("connections".into(), connections(server_handle));
+        }
+
+        if let Some(recent_errors) = &self.recent_errors {
+            snapshot.insert(
+                "recent_errors".into(),
+                recent_errors
+                    .errors()
+                    .iter()
+                    .map(RecentError::to_json)
+                    .collect(),
+            );
+        }
+
+        for (name, section) in &self.sections {
+            snapshot.insert(name.to_string(), section());
+        }
+
+        Value::Object(snapshot)
+    }
+}
+
+fn connections(server_handle: &ServerHandle) -> Value {
+    let open = server_handle
+        .connections()
+        .iter()
+        .map(|connection| {
+            json!({
+                "peer_ip": connection.peer_ip().map(|ip| ip.to_string()),
+                "age_ms": u64::try_from(connection.age().as_millis()).unwrap_or(u64::MAX),
+                "requests_in_flight": connection.requests_in_flight(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "open_connections": server_handle.open_connections(),
+        "requests_in_flight": server_handle.requests_in_flight(),
+        "open": open,
+    })
+}
+
+fn accepts_json(conn: &Conn) -> bool {
+    conn.request_headers()
+        .get_str(KnownHeaderName::Accept)
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+#[async_trait]
+impl Handler for Admin {
+    async fn init(&mut self, info: &mut Info) {
+        self.started_at = Instant::now();
+        self.server = json!({
+            "server": info.server_description(),
+            "listener": info.listener_description(),
+        });
+    }
+
+    async fn run(&self, conn: Conn) -> Conn {
+        if conn.method() != Method::Get {
+            return conn;
+        }
+
+        let snapshot = self.snapshot();
+        if accepts_json(&conn) {
+            conn.with_response_header(KnownHeaderName::ContentType, "application/json")
+                .ok(snapshot.to_string())
+        } else {
+            conn.with_response_header(KnownHeaderName::ContentType, "text/html; charset=utf-8")
+                .ok(html::render(&self.title, &snapshot))
+        }
+        .with_response_header(KnownHeaderName::CacheControl, "no-store")
+    }
+}
+
+/// Convenience alias for [`Admin::new`]
+pub fn admin() -> Admin {
+    Admin::new()
+}
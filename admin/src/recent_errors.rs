@@ -0,0 +1,123 @@
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use trillium::{async_trait, Conn, Handler, Method, Status};
+
+/**
+A handler that records the most recent server error responses
+
+Place this handler early in the application so that its `before_send`
+sees the final status of every response, and pass a clone of it to
+[`Admin::with_recent_errors`](crate::Admin::with_recent_errors). Every
+response with a `5xx` status is recorded, and only the most recent
+errors are kept.
+
+```
+use trillium_admin::{admin, RecentErrors};
+let recent_errors = RecentErrors::new(50);
+let handler = (
+    recent_errors.clone(),
+    admin().with_recent_errors(recent_errors),
+);
+```
+*/
+#[derive(Clone, Debug)]
+pub struct RecentErrors {
+    capacity: usize,
+    errors: Arc<Mutex<VecDeque<RecentError>>>,
+}
+
+/// A server error response recorded by [`RecentErrors`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecentError {
+    at: SystemTime,
+    method: Method,
+    path: String,
+    status: Status,
+}
+
+impl RecentError {
+    /// The time that the response was sent
+    pub fn at(&self) -> SystemTime {
+        self.at
+    }
+
+    /// The request method
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// The request path, without the query
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// The response status
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub(crate) fn to_json(&self) -> Value {
+        json!({
+            "at": self.at.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs(),
+            "method": self.method.as_ref(),
+            "path": self.path,
+            "status": self.status as u16,
+        })
+    }
+}
+
+impl Default for RecentErrors {
+    fn default() -> Self {
+        Self::new(100)
+    }
+}
+
+impl RecentErrors {
+    /// Builds a new `RecentErrors` that keeps at most this many errors
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            errors: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// The recorded errors, most recent first
+    pub fn errors(&self) -> Vec<RecentError> {
+        self.errors.lock().unwrap().iter().rev().cloned().collect()
+    }
+
+    fn record(&self, error: RecentError) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut errors = self.errors.lock().unwrap();
+        if errors.len() == self.capacity {
+            errors.pop_front();
+        }
+        errors.push_back(error);
+    }
+}
+
+#[async_trait]
+impl Handler for RecentErrors {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn
+    }
+
+    async fn before_send(&self, conn: Conn) -> Conn {
+        if let Some(status) = conn.status().filter(|status| status.is_server_error()) {
+            self.record(RecentError {
+                at: SystemTime::now(),
+                method: conn.method(),
+                path: conn.path().to_string(),
+                status,
+            });
+        }
+        conn
+    }
+}
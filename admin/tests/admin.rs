@@ -0,0 +1,96 @@
+use serde_json::{json, Value};
+use trillium::Conn;
+use trillium_admin::{admin, RecentErrors};
+use trillium_testing::prelude::*;
+
+fn json_snapshot(conn: &mut trillium_testing::TestConn) -> Value {
+    serde_json::from_str(&conn.take_response_body_string().unwrap()).unwrap()
+}
+
+#[test]
+fn renders_sections_as_json() {
+    let handler = admin().with_section("build", || json!({ "version": "1.2.3" }));
+    let mut conn = get("/")
+        .with_request_header("accept", "application/json")
+        .on(&handler);
+
+    assert_status!(&conn, 200);
+    assert_headers!(&conn, "content-type" => "application/json", "cache-control" => "no-store");
+    let snapshot = json_snapshot(&mut conn);
+    assert!(snapshot["server"]["uptime_secs"].is_u64());
+    assert_eq!(snapshot["build"], json!({ "version": "1.2.3" }));
+    assert!(snapshot.get("connections").is_none());
+}
+
+#[test]
+fn renders_html_and_escapes() {
+    let handler = admin()
+        .with_title("<admin>")
+        .with_section("notes", || json!(["<script>", "ok"]));
+    let mut conn = get("/").on(&handler);
+    assert_headers!(&conn, "content-type" => "text/html; charset=utf-8");
+    assert_body_contains!(conn, "<title>&lt;admin&gt;</title>");
+    let mut conn = get("/").on(&handler);
+    assert_body_contains!(conn, "<li>&lt;script&gt;</li>");
+}
+
+#[test]
+fn only_handles_get() {
+    assert_not_handled!(post("/").on(&admin()));
+}
+
+#[test]
+fn records_recent_errors() {
+    let recent_errors = RecentErrors::new(2);
+    let handler = (
+        recent_errors.clone(),
+        trillium_router::router()
+            .get("/admin", admin().with_recent_errors(recent_errors.clone()))
+            .get("/ok", "ok")
+            .get(
+                "/fail/:n",
+                |conn: Conn| async move { conn.with_status(500) },
+            ),
+    );
+
+    get("/ok").on(&handler);
+    for n in 1..=3 {
+        assert_status!(get(format!("/fail/{n}")).on(&handler), 500);
+    }
+
+    let errors = recent_errors.errors();
+    assert_eq!(
+        errors.iter().map(|e| e.path()).collect::<Vec<_>>(),
+        ["/fail/3", "/fail/2"]
+    );
+
+    let mut conn = get("/admin")
+        .with_request_header("accept", "application/json")
+        .on(&handler);
+    let snapshot = json_snapshot(&mut conn);
+    assert_eq!(snapshot["recent_errors"][0]["path"], "/fail/3");
+    assert_eq!(snapshot["recent_errors"][0]["status"], 500);
+    assert_eq!(snapshot["recent_errors"][0]["method"], "GET");
+}
+
+#[test]
+fn connections_from_server_handle() {
+    trillium_testing::block_on(async {
+        let handle = trillium_smol::config()
+            .with_host("127.0.0.1")
+            .with_port(0)
+            .without_signals()
+            .spawn("ok");
+        handle.info().await;
+
+        let handler = admin().with_server_handle(handle.clone());
+        let mut conn = get("/")
+            .with_request_header("accept", "application/json")
+            .on(&handler);
+        let snapshot = json_snapshot(&mut conn);
+        assert_eq!(snapshot["connections"]["open_connections"], 0);
+        assert_eq!(snapshot["connections"]["requests_in_flight"], 0);
+        assert_eq!(snapshot["connections"]["open"], json!([]));
+        handle.stop().await;
+    });
+}
@@ -0,0 +1,19 @@
+use trillium::Conn;
+use trillium_api_key::{ApiKeyAuth, ApiKeyConnExt, KeySource};
+
+async fn hello(conn: Conn) -> Conn {
+    let label = *conn.api_key_label::<&str>().unwrap();
+    conn.ok(format!("hello, {label}"))
+}
+
+fn main() {
+    env_logger::init();
+    // try `curl -H "X-Api-Key: trillium" http://localhost:8080`
+    trillium_smol::run((
+        trillium_logger::logger(),
+        ApiKeyAuth::new()
+            .with_source(KeySource::header("x-api-key"))
+            .with_key("trillium", "example client"),
+        hello,
+    ));
+}
@@ -0,0 +1,263 @@
+/*!
+# Api key authentication for trillium.rs
+
+[`ApiKeyAuth`] checks each request for one of a set of static keys,
+each of which has an associated label. By default the key is read
+from an `Authorization: Bearer` header, but it can also be read from
+any other header or from a query param with
+[`ApiKeyAuth::with_source`].
+
+Requests without a key are halted with `401 Unauthorized`, and
+requests with an unrecognized key are halted with `403 Forbidden`.
+When a key is recognized, its label is available to subsequent
+handlers through [`ApiKeyConnExt::api_key_label`].
+
+```
+use trillium::Conn;
+use trillium_api_key::{ApiKeyAuth, ApiKeyConnExt};
+
+let handler = (
+    ApiKeyAuth::new()
+        .with_key("4a3f5c", "billing service")
+        .with_key("91be0d", "reporting service"),
+    |conn: Conn| async move {
+        let label = conn.api_key_label::<&str>().unwrap();
+        let body = format!("hello, {label}");
+        conn.ok(body)
+    },
+);
+
+use trillium_testing::prelude::*;
+assert_ok!(
+    get("/")
+        .with_request_header("authorization", "Bearer 91be0d")
+        .on(&handler),
+    "hello, reporting service"
+);
+assert_response!(get("/").on(&handler), 401, "", "www-authenticate" => "Bearer");
+assert_status!(
+    get("/").with_request_header("authorization", "Bearer nope").on(&handler),
+    403
+);
+```
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+use querystrong::QueryStrong;
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+};
+use trillium::{
+    async_trait, Conn, Handler, HeaderName,
+    KnownHeaderName::{Authorization, WwwAuthenticate},
+    StateSet, Status,
+};
+
+/// Where [`ApiKeyAuth`] looks for a key on each request
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KeySource {
+    /// an `Authorization: Bearer {key}` header. This is the default.
+    Bearer,
+
+    /// the full value of the named header, such as `X-Api-Key`
+    Header(HeaderName<'static>),
+
+    /// the named query param, such as `?api_key={key}`
+    QueryParam(Cow<'static, str>),
+}
+
+impl KeySource {
+    /// read the key from the full value of the named header
+    pub fn header(name: impl Into<HeaderName<'static>>) -> Self {
+        Self::Header(name.into())
+    }
+
+    /// read the key from the named query param
+    pub fn query_param(name: impl Into<Cow<'static, str>>) -> Self {
+        Self::QueryParam(name.into())
+    }
+
+    fn key(&self, conn: &Conn) -> Option<String> {
+        match self {
+            Self::Bearer => {
+                let (scheme, token) = conn
+                    .request_headers()
+                    .get_str(Authorization)?
+                    .split_once(' ')?;
+                scheme
+                    .eq_ignore_ascii_case("bearer")
+                    .then(|| token.trim().to_string())
+            }
+
+            Self::Header(name) => conn
+                .request_headers()
+                .get_str(name.clone())
+                .map(|value| value.trim().to_string()),
+
+            Self::QueryParam(name) => QueryStrong::parse(conn.querystring())
+                .ok()?
+                .get_str(&**name)
+                .map(String::from),
+        }
+        .filter(|key| !key.is_empty())
+    }
+}
+
+/**
+Trillium handler that authenticates requests with static api keys
+
+See crate-level docs for an explanation
+*/
+pub struct ApiKeyAuth<Label = &'static str> {
+    keys: Vec<(String, Label)>,
+    source: KeySource,
+}
+
+impl<Label: Debug> Debug for ApiKeyAuth<Label> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKeyAuth")
+            .field(
+                "keys",
+                &self.keys.iter().map(|(_, label)| label).collect::<Vec<_>>(),
+            )
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl<Label> Default for ApiKeyAuth<Label> {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            source: KeySource::Bearer,
+        }
+    }
+}
+
+impl<Label> ApiKeyAuth<Label>
+where
+    Label: Clone + Send + Sync + 'static,
+{
+    /// Constructs a new ApiKeyAuth handler with no keys, reading keys
+    /// from an `Authorization: Bearer` header
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Accept this key, inserting the label into the conn's state when
+    it is presented. The label can be any `Clone + Send + Sync`
+    type, such as a string or an application-specific account id.
+
+    ```
+    use trillium_api_key::{ApiKeyAuth, ApiKeyConnExt, KeySource};
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct AccountId(u64);
+
+    let handler = (
+        ApiKeyAuth::new()
+            .with_source(KeySource::header("x-api-key"))
+            .with_key("4a3f5c", AccountId(7)),
+        |conn: trillium::Conn| async move {
+            let account_id = conn.api_key_label::<AccountId>().unwrap().0;
+            conn.ok(account_id.to_string())
+        },
+    );
+
+    use trillium_testing::prelude::*;
+    assert_ok!(
+        get("/").with_request_header("x-api-key", "4a3f5c").on(&handler),
+        "7"
+    );
+    ```
+    */
+    pub fn with_key(mut self, key: impl Into<String>, label: Label) -> Self {
+        self.keys.push((key.into(), label));
+        self
+    }
+
+    /// Read keys from the provided [`KeySource`] instead of an
+    /// `Authorization: Bearer` header
+    pub fn with_source(mut self, source: KeySource) -> Self {
+        self.source = source;
+        self
+    }
+
+    // every key is compared without short-circuiting, so the time
+    // taken does not reveal how much of a key matched
+    fn label(&self, presented: &str) -> Option<&Label> {
+        self.keys.iter().fold(None, |found, (key, label)| {
+            let matches = constant_time_eq(key.as_bytes(), presented.as_bytes());
+            found.or(matches.then_some(label))
+        })
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Alias for [`ApiKeyAuth::new`]
+pub fn api_key_auth() -> ApiKeyAuth {
+    ApiKeyAuth::new()
+}
+
+struct ApiKeyLabel<Label>(Label);
+
+#[async_trait]
+impl<Label> Handler for ApiKeyAuth<Label>
+where
+    Label: Clone + Send + Sync + 'static,
+{
+    async fn run(&self, conn: Conn) -> Conn {
+        let Some(key) = self.source.key(&conn) else {
+            log::debug!("no api key provided for {} {}", conn.method(), conn.path());
+            let conn = conn.with_status(Status::Unauthorized);
+            return match self.source {
+                KeySource::Bearer => conn.with_response_header(WwwAuthenticate, "Bearer"),
+                _ => conn,
+            }
+            .halt();
+        };
+
+        match self.label(&key) {
+            Some(label) => conn.with_state(ApiKeyLabel(label.clone())),
+            None => {
+                log::debug!("unrecognized api key for {} {}", conn.method(), conn.path());
+                conn.with_status(Status::Forbidden).halt()
+            }
+        }
+    }
+}
+
+/// Extension trait to retrieve the label of the api key presented
+/// with a request
+pub trait ApiKeyConnExt {
+    /// Returns the label of the key that was presented with this
+    /// request, if it was recognized by an [`ApiKeyAuth`] with labels
+    /// of type `Label`
+    fn api_key_label<Label: Send + Sync + 'static>(&self) -> Option<&Label>;
+}
+
+impl<ConnLike> ApiKeyConnExt for ConnLike
+where
+    ConnLike: AsRef<StateSet>,
+{
+    fn api_key_label<Label: Send + Sync + 'static>(&self) -> Option<&Label> {
+        self.as_ref()
+            .get::<ApiKeyLabel<Label>>()
+            .map(|label| &label.0)
+    }
+}
@@ -0,0 +1,116 @@
+use trillium::Conn;
+use trillium_api_key::{api_key_auth, ApiKeyAuth, ApiKeyConnExt, KeySource};
+use trillium_testing::prelude::*;
+
+async fn echo_label(conn: Conn) -> Conn {
+    let label = conn.api_key_label::<&str>().unwrap().to_string();
+    conn.ok(label)
+}
+
+fn handler(source: KeySource) -> impl trillium::Handler {
+    (
+        api_key_auth()
+            .with_key("first-key", "first")
+            .with_key("second-key", "second")
+            .with_source(source),
+        echo_label,
+    )
+}
+
+#[test]
+fn bearer() {
+    let handler = handler(KeySource::Bearer);
+    assert_ok!(
+        get("/")
+            .with_request_header("authorization", "Bearer first-key")
+            .on(&handler),
+        "first"
+    );
+    assert_ok!(
+        get("/")
+            .with_request_header("authorization", "bearer  second-key ")
+            .on(&handler),
+        "second"
+    );
+
+    assert_response!(get("/").on(&handler), 401, "", "www-authenticate" => "Bearer");
+    assert_status!(
+        get("/")
+            .with_request_header("authorization", "Basic Zmlyc3Q6c2Vjb25k")
+            .on(&handler),
+        401
+    );
+    assert_status!(
+        get("/")
+            .with_request_header("authorization", "Bearer ")
+            .on(&handler),
+        401
+    );
+    assert_status!(
+        get("/")
+            .with_request_header("authorization", "Bearer first-ke")
+            .on(&handler),
+        403
+    );
+}
+
+#[test]
+fn header() {
+    let handler = handler(KeySource::header("x-api-key"));
+    assert_ok!(
+        get("/")
+            .with_request_header("x-api-key", "second-key")
+            .on(&handler),
+        "second"
+    );
+
+    let conn = get("/")
+        .with_request_header("authorization", "Bearer first-key")
+        .on(&handler);
+    assert_status!(&conn, 401);
+    assert_headers!(&conn, "www-authenticate" => None);
+
+    assert_status!(
+        get("/")
+            .with_request_header("x-api-key", "third-key")
+            .on(&handler),
+        403
+    );
+}
+
+#[test]
+fn query_param() {
+    let handler = handler(KeySource::query_param("api_key"));
+    assert_ok!(get("/?api_key=first-key").on(&handler), "first");
+    assert_ok!(get("/?other=1&api_key=second-key").on(&handler), "second");
+    assert_status!(get("/?api_key=").on(&handler), 401);
+    assert_status!(get("/?key=first-key").on(&handler), 401);
+    assert_status!(get("/?api_key=second").on(&handler), 403);
+}
+
+#[test]
+fn custom_labels() {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Scope {
+        Read,
+        Write,
+    }
+
+    let handler = (
+        ApiKeyAuth::new()
+            .with_key("reader", Scope::Read)
+            .with_key("writer", Scope::Write),
+        |conn: Conn| async move {
+            let scope = *conn.api_key_label::<Scope>().unwrap();
+            assert!(conn.api_key_label::<&str>().is_none());
+            conn.ok(format!("{scope:?}"))
+        },
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("authorization", "Bearer writer")
+            .on(&handler),
+        "Write"
+    );
+}
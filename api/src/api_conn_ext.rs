@@ -2,11 +2,11 @@ use mime::Mime;
 use serde::{de::DeserializeOwned, Serialize};
 use trillium::{
     Conn,
-    KnownHeaderName::{Accept, ContentType},
+    KnownHeaderName::{Accept, ContentType, Vary},
     Status,
 };
 
-use crate::{Error, Result};
+use crate::{ContentNegotiation, Error, IntoProblemDetails, Result};
 
 /// Extension trait that adds api methods to [`trillium::Conn`]
 #[trillium::async_trait]
@@ -62,6 +62,16 @@ pub trait ApiConnExt {
     */
     fn with_json(self, response: &impl Serialize) -> Self;
 
+    /**
+    Sends an `application/problem+json` response body from anything
+    that implements [`IntoProblemDetails`]. This sets the status from
+    the problem details if it has one, or to 500 if neither the problem
+    details nor the conn has a status. This does not halt the conn.
+
+    See [`ProblemDetails`](crate::ProblemDetails) for an example.
+    */
+    fn with_problem_details(self, problem: impl IntoProblemDetails) -> Self;
+
     /**
     Attempts to deserialize a type from the request body, based on the
     request content type.
@@ -154,7 +164,11 @@ pub trait ApiConnExt {
     where
         T: DeserializeOwned;
 
-    /// Serializes the provided body using Accepts header content negotiation
+    /// Serializes the provided body using Accept header content
+    /// negotiation with the [`ContentNegotiation`] in this conn's state,
+    /// or the default [`ContentNegotiation`] if there is none. This sets
+    /// the content-type and adds `Accept` to the Vary header, but does
+    /// not set a status.
     async fn serialize<T>(&mut self, body: &T) -> Result<()>
     where
         T: Serialize + Sync;
@@ -185,6 +199,26 @@ impl ApiConnExt for Conn {
         }
     }
 
+    fn with_problem_details(mut self, problem: impl IntoProblemDetails) -> Self {
+        let problem = problem.into_problem_details();
+        match serde_json::to_string(&problem) {
+            Ok(body) => {
+                if let Some(status) = problem.status() {
+                    self.set_status(status);
+                } else if self.status().is_none() {
+                    self.set_status(Status::InternalServerError);
+                }
+
+                self.response_headers_mut()
+                    .insert(ContentType, "application/problem+json");
+
+                self.with_body(body)
+            }
+
+            Err(error) => self.with_state(Error::from(error)),
+        }
+    }
+
     async fn deserialize<T>(&mut self) -> Result<T>
     where
         T: DeserializeOwned,
@@ -250,50 +284,18 @@ impl ApiConnExt for Conn {
     where
         T: Serialize + Sync,
     {
-        let accept = self
-            .request_headers()
-            .get_str(Accept)
-            .unwrap_or("*/*")
-            .split(',')
-            .map(|s| s.trim())
-            .find_map(acceptable_mime_type);
-
-        match accept {
-            Some(AcceptableMime::Json) => {
-                self.set_body(serde_json::to_string(body)?);
-                self.response_headers_mut()
-                    .insert(ContentType, "application/json");
-                Ok(())
-            }
-
-            #[cfg(feature = "forms")]
-            Some(AcceptableMime::Form) => {
-                self.set_body(serde_urlencoded::to_string(body)?);
-                self.response_headers_mut()
-                    .insert(ContentType, "application/x-www-form-urlencoded");
-                Ok(())
-            }
-
-            None => Err(Error::FailureToNegotiateContent),
-        }
-    }
-}
-
-enum AcceptableMime {
-    Json,
-    #[cfg(feature = "forms")]
-    Form,
-}
-
-fn acceptable_mime_type(mime: &str) -> Option<AcceptableMime> {
-    let mime: Mime = mime.parse().ok()?;
-    let suffix_or_subtype = mime.suffix().unwrap_or_else(|| mime.subtype()).as_str();
-    match suffix_or_subtype {
-        "*" | "json" => Some(AcceptableMime::Json),
-
-        #[cfg(feature = "forms")]
-        "x-www-form-urlencoded" => Some(AcceptableMime::Form),
-
-        _ => None,
+        let negotiation = self
+            .state::<ContentNegotiation>()
+            .cloned()
+            .unwrap_or_default();
+        let encoder = negotiation
+            .negotiate(self.request_headers().get_str(Accept))
+            .ok_or(Error::FailureToNegotiateContent)?;
+
+        self.set_body(encoder.encode(body)?);
+        let headers = self.response_headers_mut();
+        headers.insert(ContentType, encoder.content_type().to_string());
+        headers.append(Vary, "Accept");
+        Ok(())
     }
 }
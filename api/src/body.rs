@@ -62,8 +62,8 @@ where
         }
     }
 
-    async fn before_send(&self, mut conn: Conn) -> Conn {
-        if let Some(error) = conn.take_state::<crate::Error>() {
+    async fn before_send(&self, conn: Conn) -> Conn {
+        if let Some(error) = conn.state::<crate::Error>().cloned() {
             error.before_send(conn).await
         } else {
             conn
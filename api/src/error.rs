@@ -1,4 +1,4 @@
-use crate::ApiConnExt;
+use crate::{ApiConnExt, ProblemDetailsErrors};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::fmt::Display;
@@ -35,6 +35,12 @@ pub enum Error {
     /// The client did not provide a content-type
     #[error("Missing content type")]
     MissingContentType,
+    /// The request body exceeded a configured limit
+    #[error("Payload too large: {message}")]
+    PayloadTooLarge {
+        /// a description of the limit that was exceeded
+        message: String,
+    },
     /// Miscellaneous other errors -- please open an issue on
     /// trillium-api if you find yourself parsing the contents of
     /// this.
@@ -44,6 +50,14 @@ pub enum Error {
         message: String,
     },
 
+    /// The endpoint does not support the request method. The methods
+    /// it does support are sent in the `Allow` header.
+    #[error("Method not allowed: {method}")]
+    MethodNotAllowed {
+        /// the request method
+        method: String,
+    },
+
     #[error("No negotiated mime type")]
     /// we were unable to find a content type that matches the Accept
     /// header. Please open an issue if you'd like an additional
@@ -68,6 +82,10 @@ impl From<trillium::Error> for Error {
                 message: e.to_string(),
             },
 
+            trillium::Error::ReceivedBodyTooLong(max_len) => Self::PayloadTooLarge {
+                message: format!("request body longer than {max_len} bytes"),
+            },
+
             other => Self::Other {
                 message: other.to_string(),
             },
@@ -75,6 +93,12 @@ impl From<trillium::Error> for Error {
     }
 }
 
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        trillium::Error::from(error).into()
+    }
+}
+
 impl<E: Display> From<serde_path_to_error::Error<E>> for Error {
     fn from(e: serde_path_to_error::Error<E>) -> Self {
         Error::ParseError {
@@ -110,8 +134,12 @@ impl Handler for Error {
 
     async fn before_send(&self, mut conn: Conn) -> Conn {
         if let Some(error) = conn.take_state::<Self>() {
-            conn.with_json(&json!({ "error": &error }))
-                .with_status(&error)
+            if conn.state::<ProblemDetailsErrors>().is_some() {
+                conn.with_problem_details(error)
+            } else {
+                conn.with_json(&json!({ "error": &error }))
+                    .with_status(&error)
+            }
         } else {
             conn
         }
@@ -126,7 +154,9 @@ impl From<&Error> for Status {
                 Status::UnsupportedMediaType
             }
             Error::FailureToNegotiateContent => Status::NotAcceptable,
+            Error::PayloadTooLarge { .. } => Status::PayloadTooLarge,
             Error::IoError { .. } => Status::BadRequest,
+            Error::MethodNotAllowed { .. } => Status::MethodNotAllowed,
             _ => Status::InternalServerError,
         }
     }
@@ -10,11 +10,42 @@ breaking changes in this crate then in the trillium crate.
 Currently, this crate supports *receiving* `application/json` and
 `application/x-form-www-urlencoded` by default. To disable
 `application/x-form-www-urlencoded` support, use `default-features =
-false`.
+false`. `multipart/form-data` is supported with the `multipart` cargo
+feature, either by streaming fields with [`MultipartReader`] or by
+extracting a [`Multipart`] that buffers each part in memory or in a
+temporary file.
 
-This crate currently only supports sending json responses, but may
-eventually add `Accepts` negotiation and further outbound response
-content types.
+Responses sent with [`Body`] or [`ApiConnExt::serialize`] are encoded
+based on the request's `Accept` header, as configured by
+[`ContentNegotiation`]. Json is always supported, as is
+`application/x-www-form-urlencoded` with the default `forms` feature.
+`application/cbor`, `application/msgpack`, and `text/csv` are
+supported with the `cbor`, `msgpack`, and `csv` cargo features, and
+other formats can be added by implementing [`Encoder`].
+[`ApiConnExt::with_json`] and [`Json`] always send json.
+
+Errors are sent as json by default, or as [RFC
+9457](https://www.rfc-editor.org/rfc/rfc9457) `application/problem+json`
+with [`ProblemDetailsErrors`]. Application errors can be sent as
+problem details by implementing [`IntoProblemDetails`].
+
+With the `openapi` cargo feature, [`OpenApi`] serves an OpenAPI 3.1
+document describing [`Operation`]s, which can be derived from an
+[`ApiHandler`]'s extractor and response types with
+[`ApiHandler::operation`]. Types are described by deriving
+[`schemars::JsonSchema`].
+
+With the `webhooks` cargo feature, [`Webhooks`] delivers signed
+outbound webhook events with trillium-client, retrying failed
+deliveries with backoff.
+
+With the `operations` cargo feature, [`Operations`] runs long-running
+jobs in the background, responding `202 Accepted` with a status
+endpoint that clients poll for progress and the job's result.
+
+[`Methods`] dispatches an endpoint's requests to a handler for each
+http method, answering `HEAD`, `OPTIONS`, and unsupported methods with
+an `Allow` header.
 
 The [`ApiConnExt`] extension trait and [`ApiHandler`] can be used
 independently or in combination.
@@ -41,21 +72,67 @@ mod error;
 mod from_conn;
 mod halt;
 mod json;
+mod methods;
+#[cfg(feature = "multipart")]
+mod multipart;
+mod negotiation;
+#[cfg(feature = "openapi")]
+mod openapi;
+#[cfg(feature = "operations")]
+mod operations;
+#[cfg(feature = "router")]
+mod path;
+mod problem_details;
+#[cfg(feature = "forms")]
+mod query;
 mod state;
 mod try_from_conn;
+#[cfg(feature = "webhooks")]
+mod webhooks;
 
 pub use api_conn_ext::ApiConnExt;
 pub use api_handler::{api, ApiHandler};
 pub use before_send::BeforeSend;
 pub use body::Body;
 pub use cancel_on_disconnect::{cancel_on_disconnect, CancelOnDisconnect};
+pub use erased_serde;
 pub use error::Error;
 pub use from_conn::FromConn;
 pub use halt::Halt;
 pub use json::Json;
+pub use methods::{methods, Methods};
+#[cfg(feature = "multipart")]
+pub use multipart::{Field, Multipart, MultipartConfig, MultipartReader, Part, PartData, TempPath};
+#[cfg(feature = "cbor")]
+pub use negotiation::CborEncoder;
+#[cfg(feature = "csv")]
+pub use negotiation::CsvEncoder;
+#[cfg(feature = "forms")]
+pub use negotiation::FormEncoder;
+#[cfg(feature = "msgpack")]
+pub use negotiation::MsgPackEncoder;
+pub use negotiation::{ContentNegotiation, Encoder, JsonEncoder};
+#[cfg(feature = "openapi")]
+pub use openapi::{OpenApi, Operation, OperationInput, OperationOutput};
+#[cfg(feature = "operations")]
+pub use operations::{
+    Accepted, MemoryOperationStore, OperationId, OperationState, OperationStatus, OperationStore,
+    Operations, Progress,
+};
+#[cfg(feature = "router")]
+pub use path::Path;
+pub use problem_details::{IntoProblemDetails, ProblemDetails, ProblemDetailsErrors};
+#[cfg(feature = "forms")]
+pub use query::Query;
+#[cfg(feature = "openapi")]
+pub use schemars;
 pub use serde_json::{json, Value};
 pub use state::State;
 pub use try_from_conn::TryFromConn;
+#[cfg(feature = "webhooks")]
+pub use webhooks::{
+    webhook_signature, DeliveryId, DeliveryState, DeliveryStatus, EndpointId, Webhooks,
+};
 
 /// trait alias for a result with this crate's [`Error`]
 pub type Result<T> = std::result::Result<T, Error>;
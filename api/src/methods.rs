@@ -0,0 +1,218 @@
+use crate::Error;
+use std::borrow::Cow;
+use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName, Method, Status, Upgrade};
+
+/**
+# Dispatches requests to handlers by http method
+
+An [`ApiHandler`](crate::ApiHandler) runs for every request that
+reaches it, regardless of method. `Methods` assigns a handler to each
+method supported by an endpoint, and answers the methods that are not
+supported consistently:
+
+- `HEAD` runs the `GET` handler, unless a `HEAD` handler was provided
+  with [`Methods::with`]. The response body is not sent.
+- `OPTIONS` responds `200 Ok` with an `Allow` header, unless an
+  `OPTIONS` handler was provided.
+- Any other method responds `405 Method Not Allowed` with an `Allow`
+  header and an [`Error::MethodNotAllowed`], which is rendered like
+  any other [`Error`], including as problem details with
+  [`ProblemDetailsErrors`](crate::ProblemDetailsErrors).
+
+Because `Methods` handles every method itself, mount it with
+`Router::all` when using trillium-router, so that the router passes
+along requests for methods that the endpoint does not implement
+instead of returning an unhandled conn.
+
+```
+use trillium_api::{api, methods, Json};
+use trillium_testing::{prelude::*, TestConn};
+
+let handler = methods()
+    .get(api(|_: &mut trillium::Conn, ()| async { Json("widgets") }))
+    .post(api(|_: &mut trillium::Conn, ()| async { Json("created") }));
+
+assert_ok!(get("/").on(&handler), r#""widgets""#);
+assert_status!(TestConn::build(Method::Head, "/", ()).on(&handler), 200);
+
+let conn = delete("/").on(&handler);
+assert_status!(&conn, 405);
+assert_headers!(&conn, "allow" => "GET, HEAD, POST, OPTIONS");
+
+let conn = TestConn::build(Method::Options, "/", ()).on(&handler);
+assert_headers!(&conn, "allow" => "GET, HEAD, POST, OPTIONS");
+```
+*/
+#[derive(Debug, Default)]
+pub struct Methods {
+    handlers: Vec<(Method, Box<dyn Handler>)>,
+}
+
+/// Convenience alias for [`Methods::new`]
+pub fn methods() -> Methods {
+    Methods::new()
+}
+
+// marks a HEAD request that is being handled by the GET handler
+struct HeadAsGet;
+
+impl Methods {
+    /// Builds a new `Methods` with no handlers. Every request will
+    /// receive a `405 Method Not Allowed` except `OPTIONS`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handles requests with this method with the provided handler,
+    /// replacing any handler previously provided for the same method
+    #[must_use]
+    pub fn with(mut self, method: Method, handler: impl Handler) -> Self {
+        let handler = Box::new(handler) as Box<dyn Handler>;
+        match self.handlers.iter_mut().find(|(m, _)| *m == method) {
+            Some((_, existing)) => *existing = handler,
+            None => self.handlers.push((method, handler)),
+        }
+        self
+    }
+
+    /// Handles `GET` requests, as well as `HEAD` requests unless a
+    /// `HEAD` handler is provided
+    #[must_use]
+    pub fn get(self, handler: impl Handler) -> Self {
+        self.with(Method::Get, handler)
+    }
+
+    /// Handles `POST` requests
+    #[must_use]
+    pub fn post(self, handler: impl Handler) -> Self {
+        self.with(Method::Post, handler)
+    }
+
+    /// Handles `PUT` requests
+    #[must_use]
+    pub fn put(self, handler: impl Handler) -> Self {
+        self.with(Method::Put, handler)
+    }
+
+    /// Handles `PATCH` requests
+    #[must_use]
+    pub fn patch(self, handler: impl Handler) -> Self {
+        self.with(Method::Patch, handler)
+    }
+
+    /// Handles `DELETE` requests
+    #[must_use]
+    pub fn delete(self, handler: impl Handler) -> Self {
+        self.with(Method::Delete, handler)
+    }
+
+    /// The methods that this endpoint responds to, in the order that
+    /// they are listed in the `Allow` header. This always includes
+    /// `OPTIONS`, and includes `HEAD` if there is a `GET` handler.
+    pub fn allowed_methods(&self) -> Vec<Method> {
+        let mut allowed = Vec::with_capacity(self.handlers.len() + 2);
+        for (method, _) in &self.handlers {
+            if !allowed.contains(method) {
+                allowed.push(*method);
+            }
+
+            if *method == Method::Get && !allowed.contains(&Method::Head) {
+                allowed.push(Method::Head);
+            }
+        }
+
+        if !allowed.contains(&Method::Options) {
+            allowed.push(Method::Options);
+        }
+
+        allowed
+    }
+
+    fn allow(&self) -> String {
+        self.allowed_methods()
+            .iter()
+            .map(Method::as_ref)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn handler(&self, method: Method) -> Option<&dyn Handler> {
+        self.handlers
+            .iter()
+            .find_map(|(m, handler)| (*m == method).then_some(&**handler))
+    }
+}
+
+#[async_trait]
+impl Handler for Methods {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        let method = conn.method();
+        if let Some(handler) = self.handler(method) {
+            return handler.run(conn).await;
+        }
+
+        match method {
+            Method::Head => {
+                if let Some(handler) = self.handler(Method::Get) {
+                    conn.inner_mut().set_method(Method::Get);
+                    return handler.run(conn.with_state(HeadAsGet)).await;
+                }
+            }
+
+            Method::Options => {
+                return conn
+                    .with_response_header(KnownHeaderName::Allow, self.allow())
+                    .with_status(Status::Ok)
+                    .halt();
+            }
+
+            _ => {}
+        }
+
+        let error = Error::MethodNotAllowed {
+            method: method.to_string(),
+        };
+        let conn = error
+            .run(conn.with_response_header(KnownHeaderName::Allow, self.allow()))
+            .await;
+        error.before_send(conn).await
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        for (_, handler) in self.handlers.iter().rev() {
+            conn = handler.before_send(conn).await;
+        }
+
+        if conn.take_state::<HeadAsGet>().is_some() {
+            conn.inner_mut().set_method(Method::Head);
+        }
+
+        conn
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        for (_, handler) in &mut self.handlers {
+            handler.init(info).await;
+        }
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        format!("methods({})", self.allow()).into()
+    }
+
+    fn has_upgrade(&self, upgrade: &Upgrade) -> bool {
+        self.handlers
+            .iter()
+            .any(|(_, handler)| handler.has_upgrade(upgrade))
+    }
+
+    async fn upgrade(&self, upgrade: Upgrade) {
+        if let Some((_, handler)) = self
+            .handlers
+            .iter()
+            .find(|(_, handler)| handler.has_upgrade(&upgrade))
+        {
+            handler.upgrade(upgrade).await;
+        }
+    }
+}
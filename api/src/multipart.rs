@@ -0,0 +1,683 @@
+use crate::{ApiConnExt, Error, Result, TryFromConn};
+use blocking::Unblock;
+use futures_lite::{AsyncReadExt, AsyncWriteExt};
+use memchr::memmem;
+use mime::Mime;
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs::File,
+    path::{Path, PathBuf},
+};
+use tempfile::NamedTempFile;
+pub use tempfile::TempPath;
+use trillium::{async_trait, Conn, Handler, Headers, KnownHeaderName};
+use trillium_http::{transport::BoxedTransport, ReceivedBody};
+
+const READ_LEN: usize = 8 * 1024;
+const MAX_PART_HEADERS: usize = 16;
+const MAX_PART_HEAD_LEN: usize = 8 * 1024;
+
+/**
+Limits for extracting a [`Multipart`]
+
+Add this handler before an api handler that extracts [`Multipart`]
+to override the defaults for those conns. Parts that are larger than
+the memory limit are written to a temporary file, which is deleted
+when the [`Part`] is dropped.
+
+```
+use trillium_api::{api, Multipart, MultipartConfig};
+use trillium::Conn;
+
+let handler = (
+    MultipartConfig::new()
+        .with_memory_limit(64 * 1024)
+        .with_max_part_len(10 * 1024 * 1024),
+    api(|conn: &mut Conn, multipart: Multipart| async move {
+        format!("received {} parts", multipart.parts().len())
+    }),
+);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct MultipartConfig {
+    memory_limit: usize,
+    max_part_len: u64,
+    max_parts: usize,
+    temp_dir: Option<PathBuf>,
+}
+
+impl Default for MultipartConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit: 1024 * 1024,
+            max_part_len: 100 * 1024 * 1024,
+            max_parts: 128,
+            temp_dir: None,
+        }
+    }
+}
+
+impl MultipartConfig {
+    /// Constructs a new MultipartConfig with default limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest part that will be held in memory, in bytes.
+    /// Larger parts are written to a temporary file. The default is
+    /// one megabyte.
+    pub fn with_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    /// Sets the largest part that will be accepted, in bytes. The
+    /// default is 100 megabytes.
+    pub fn with_max_part_len(mut self, max_part_len: u64) -> Self {
+        self.max_part_len = max_part_len;
+        self
+    }
+
+    /// Sets the largest number of parts that will be accepted. The
+    /// default is 128.
+    pub fn with_max_parts(mut self, max_parts: usize) -> Self {
+        self.max_parts = max_parts;
+        self
+    }
+
+    /// Sets the directory that temporary files are created in. The
+    /// default is [`std::env::temp_dir`].
+    pub fn with_temp_dir(mut self, temp_dir: impl Into<PathBuf>) -> Self {
+        self.temp_dir = Some(temp_dir.into());
+        self
+    }
+}
+
+#[async_trait]
+impl Handler for MultipartConfig {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_state(self.clone())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReaderState {
+    Preamble,
+    Head,
+    Body,
+    Done,
+}
+
+/**
+Streams the fields of a `multipart/form-data` request body
+
+Each field must be read before the next one is returned, and any
+field content that has not been read is skipped when
+[`MultipartReader::next_field`] is called.
+
+```
+use trillium::Conn;
+use trillium_api::MultipartReader;
+
+async fn handler(mut conn: Conn) -> Conn {
+    let mut names = vec![];
+    let mut reader = match MultipartReader::new(&mut conn).await {
+        Ok(reader) => reader,
+        Err(error) => return conn.with_state(error).halt(),
+    };
+
+    while let Ok(Some(field)) = reader.next_field().await {
+        names.push(field.name().unwrap_or_default().to_string());
+    }
+
+    conn.ok(names.join(","))
+}
+
+use trillium_testing::prelude::*;
+assert_ok!(
+    post("/")
+        .with_request_header("content-type", "multipart/form-data; boundary=X")
+        .with_request_body("--X\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\n1\r\n--X\r\nContent-Disposition: form-data; name=\"b\"\r\n\r\n2\r\n--X--\r\n")
+        .on(&handler),
+    "a,b"
+);
+```
+*/
+pub struct MultipartReader<'a> {
+    body: ReceivedBody<'a, BoxedTransport>,
+    delimiter: Vec<u8>,
+    buffer: Vec<u8>,
+    state: ReaderState,
+}
+
+impl Debug for MultipartReader<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartReader")
+            .field("body", &self.body)
+            .field("delimiter", &String::from_utf8_lossy(&self.delimiter))
+            .field("state", &self.state)
+            .finish()
+    }
+}
+
+impl<'a> MultipartReader<'a> {
+    /// Begins reading the request body of this conn as
+    /// `multipart/form-data`, returning an error if the content type
+    /// is not multipart or does not include a boundary
+    pub async fn new(conn: &'a mut Conn) -> Result<Self> {
+        let boundary = boundary(&conn.content_type()?)?;
+        let mut delimiter = b"\r\n--".to_vec();
+        delimiter.extend_from_slice(boundary.as_bytes());
+
+        Ok(Self {
+            body: conn.request_body().await,
+            delimiter,
+            // the first delimiter is not required to follow a crlf
+            buffer: b"\r\n".to_vec(),
+            state: ReaderState::Preamble,
+        })
+    }
+
+    /// Returns the next field, or None if all fields have been read
+    pub async fn next_field(&mut self) -> Result<Option<Field<'_, 'a>>> {
+        loop {
+            match self.state {
+                ReaderState::Done => return Ok(None),
+
+                ReaderState::Body => while self.read_chunk().await?.is_some() {},
+
+                ReaderState::Preamble => match memmem::find(&self.buffer, &self.delimiter) {
+                    Some(index) => {
+                        self.buffer.drain(..index + self.delimiter.len());
+                        self.state = ReaderState::Head;
+                    }
+
+                    None => {
+                        let keep = self.delimiter.len() - 1;
+                        if self.buffer.len() > keep {
+                            self.buffer.drain(..self.buffer.len() - keep);
+                        }
+                        self.fill().await?;
+                    }
+                },
+
+                ReaderState::Head => {
+                    if self.buffer.starts_with(b"--") {
+                        self.state = ReaderState::Done;
+                        return Ok(None);
+                    }
+
+                    let head_end = match memmem::find(&self.buffer, b"\r\n\r\n") {
+                        Some(head_end) => head_end,
+                        None if self.buffer.len() > MAX_PART_HEAD_LEN => {
+                            return Err(parse_error("multipart part headers are too long"))
+                        }
+                        None => {
+                            self.fill().await?;
+                            continue;
+                        }
+                    };
+
+                    let head = self.parse_head(head_end)?;
+                    self.buffer.drain(..head_end + 4);
+                    self.state = ReaderState::Body;
+                    return Ok(Some(Field { reader: self, head }));
+                }
+            }
+        }
+    }
+
+    fn parse_head(&self, head_end: usize) -> Result<PartHead> {
+        // the rest of the delimiter line may only contain whitespace
+        let line_end = memmem::find(&self.buffer, b"\r\n").unwrap_or(head_end);
+        if !self.buffer[..line_end]
+            .iter()
+            .all(|byte| matches!(byte, b' ' | b'\t'))
+        {
+            return Err(parse_error("invalid multipart boundary"));
+        }
+
+        let mut parsed = [httparse::EMPTY_HEADER; MAX_PART_HEADERS];
+        let parsed =
+            match httparse::parse_headers(&self.buffer[line_end + 2..head_end + 4], &mut parsed) {
+                Ok(httparse::Status::Complete((_, parsed))) => parsed,
+                _ => return Err(parse_error("invalid multipart part headers")),
+            };
+
+        let mut headers = Headers::new();
+        for header in parsed {
+            headers.append(header.name.to_string(), header.value.to_vec());
+        }
+
+        let (name, filename) = headers
+            .get_str(KnownHeaderName::ContentDisposition)
+            .map(content_disposition)
+            .unwrap_or_default();
+
+        let content_type = headers
+            .get_str(KnownHeaderName::ContentType)
+            .and_then(|content_type| content_type.parse().ok());
+
+        Ok(PartHead {
+            headers,
+            name,
+            filename,
+            content_type,
+        })
+    }
+
+    async fn fill(&mut self) -> Result<()> {
+        let len = self.buffer.len();
+        self.buffer.resize(len + READ_LEN, 0);
+        let read = self.body.read(&mut self.buffer[len..]).await;
+        self.buffer.truncate(len + *read.as_ref().unwrap_or(&0));
+        match read? {
+            0 => Err(parse_error("unexpected end of multipart body")),
+            _ => Ok(()),
+        }
+    }
+
+    async fn read_chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        while self.state == ReaderState::Body {
+            match memmem::find(&self.buffer, &self.delimiter) {
+                Some(0) => {
+                    self.buffer.drain(..self.delimiter.len());
+                    self.state = ReaderState::Head;
+                }
+
+                Some(index) => return Ok(Some(self.buffer.drain(..index).collect())),
+
+                None => {
+                    // retain enough to recognize a delimiter split across reads
+                    let available = self.buffer.len().saturating_sub(self.delimiter.len() - 1);
+                    if available > 0 {
+                        return Ok(Some(self.buffer.drain(..available).collect()));
+                    }
+                    self.fill().await?;
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/**
+A single field of a `multipart/form-data` body, as returned by
+[`MultipartReader::next_field`]
+*/
+pub struct Field<'r, 'a> {
+    reader: &'r mut MultipartReader<'a>,
+    head: PartHead,
+}
+
+impl Debug for Field<'_, '_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field").field("head", &self.head).finish()
+    }
+}
+
+#[derive(Debug)]
+struct PartHead {
+    headers: Headers,
+    name: Option<String>,
+    filename: Option<String>,
+    content_type: Option<Mime>,
+}
+
+impl Field<'_, '_> {
+    /// The `name` parameter of this field's content disposition
+    pub fn name(&self) -> Option<&str> {
+        self.head.name.as_deref()
+    }
+
+    /// The `filename` parameter of this field's content disposition
+    pub fn filename(&self) -> Option<&str> {
+        self.head.filename.as_deref()
+    }
+
+    /// The parsed content type of this field, if one was provided
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.head.content_type.as_ref()
+    }
+
+    /// All of the headers of this field
+    pub fn headers(&self) -> &Headers {
+        &self.head.headers
+    }
+
+    /// Reads the next chunk of this field's content, or None if the
+    /// field has been read to the end
+    pub async fn chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        self.reader.read_chunk().await
+    }
+
+    /// Reads the rest of this field's content into a Vec
+    pub async fn bytes(mut self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        while let Some(chunk) = self.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+        Ok(bytes)
+    }
+
+    /// Reads the rest of this field's content into a String
+    pub async fn text(self) -> Result<String> {
+        String::from_utf8(self.bytes().await?)
+            .map_err(|_| parse_error("multipart field is not valid utf8"))
+    }
+}
+
+/// The content of a [`Part`]
+#[derive(Debug)]
+pub enum PartData {
+    /// content that was small enough to be held in memory
+    Memory(Vec<u8>),
+
+    /// content that was written to a temporary file, which is
+    /// deleted when this is dropped unless it is
+    /// [persisted](TempPath::persist)
+    File(TempPath),
+}
+
+/// A buffered field of a [`Multipart`] body
+#[derive(Debug)]
+pub struct Part {
+    head: PartHead,
+    len: u64,
+    data: PartData,
+}
+
+impl Part {
+    /// The `name` parameter of this part's content disposition
+    pub fn name(&self) -> Option<&str> {
+        self.head.name.as_deref()
+    }
+
+    /// The `filename` parameter of this part's content disposition
+    pub fn filename(&self) -> Option<&str> {
+        self.head.filename.as_deref()
+    }
+
+    /// The parsed content type of this part, if one was provided
+    pub fn content_type(&self) -> Option<&Mime> {
+        self.head.content_type.as_ref()
+    }
+
+    /// All of the headers of this part
+    pub fn headers(&self) -> &Headers {
+        &self.head.headers
+    }
+
+    /// The length of this part's content in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns true if this part has no content
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Borrows this part's content
+    pub fn data(&self) -> &PartData {
+        &self.data
+    }
+
+    /// Takes ownership of this part's content
+    pub fn into_data(self) -> PartData {
+        self.data
+    }
+
+    /// Returns this part's content if it was held in memory
+    pub fn bytes(&self) -> Option<&[u8]> {
+        match &self.data {
+            PartData::Memory(bytes) => Some(bytes),
+            PartData::File(_) => None,
+        }
+    }
+
+    /// Returns this part's content if it was held in memory and is
+    /// valid utf8
+    pub fn text(&self) -> Option<&str> {
+        std::str::from_utf8(self.bytes()?).ok()
+    }
+
+    /// Returns the path of the temporary file containing this part's
+    /// content if it was too large to be held in memory
+    pub fn path(&self) -> Option<&Path> {
+        match &self.data {
+            PartData::Memory(_) => None,
+            PartData::File(path) => Some(path),
+        }
+    }
+}
+
+/**
+Extractor for a buffered `multipart/form-data` request body
+
+Limits are configured with [`MultipartConfig`]. To process fields as
+they are received instead, use [`MultipartReader`].
+
+```
+use trillium::Conn;
+use trillium_api::{api, Multipart};
+
+let handler = api(|_: &mut Conn, multipart: Multipart| async move {
+    let upload = multipart.get("upload").unwrap();
+    format!(
+        "{} ({}): {}",
+        upload.filename().unwrap(),
+        upload.content_type().unwrap(),
+        upload.text().unwrap()
+    )
+});
+
+use trillium_testing::prelude::*;
+assert_ok!(
+    post("/")
+        .with_request_header("content-type", "multipart/form-data; boundary=X")
+        .with_request_body("--X\r\nContent-Disposition: form-data; name=\"upload\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nhello\r\n--X--\r\n")
+        .on(&handler),
+    "a.txt (text/plain): hello"
+);
+```
+*/
+#[derive(Debug, Default)]
+pub struct Multipart {
+    parts: Vec<Part>,
+}
+
+impl Multipart {
+    /// Reads every part of this conn's request body with the provided
+    /// limits
+    pub async fn read(conn: &mut Conn, config: &MultipartConfig) -> Result<Self> {
+        let mut reader = MultipartReader::new(conn).await?;
+        let mut parts = vec![];
+
+        while let Some(mut field) = reader.next_field().await? {
+            if parts.len() >= config.max_parts {
+                return Err(Error::PayloadTooLarge {
+                    message: format!("more than {} multipart parts", config.max_parts),
+                });
+            }
+
+            let mut len = 0;
+            let mut memory = vec![];
+            let mut file = None;
+
+            while let Some(chunk) = field.chunk().await? {
+                len += chunk.len() as u64;
+                if len > config.max_part_len {
+                    return Err(Error::PayloadTooLarge {
+                        message: format!("multipart part longer than {}", config.max_part_len),
+                    });
+                }
+
+                if file.is_none() && memory.len() + chunk.len() > config.memory_limit {
+                    let spilled = std::mem::take(&mut memory);
+                    file = Some(TempFile::create(config.temp_dir.clone(), spilled).await?);
+                }
+
+                match &mut file {
+                    Some(file) => file.write(&chunk).await?,
+                    None => memory.extend_from_slice(&chunk),
+                }
+            }
+
+            let data = match file {
+                Some(file) => PartData::File(file.finish().await?),
+                None => PartData::Memory(memory),
+            };
+
+            parts.push(Part {
+                head: field.head,
+                len,
+                data,
+            });
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Borrows all of the parts, in the order they were received
+    pub fn parts(&self) -> &[Part] {
+        &self.parts
+    }
+
+    /// Returns the first part with the provided name
+    pub fn get(&self, name: &str) -> Option<&Part> {
+        self.parts.iter().find(|part| part.name() == Some(name))
+    }
+
+    /// Returns every part with the provided name
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Part> {
+        self.parts
+            .iter()
+            .filter(move |part| part.name() == Some(name))
+    }
+
+    /// Takes ownership of all of the parts
+    pub fn into_parts(self) -> Vec<Part> {
+        self.parts
+    }
+}
+
+impl IntoIterator for Multipart {
+    type Item = Part;
+    type IntoIter = std::vec::IntoIter<Part>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.parts.into_iter()
+    }
+}
+
+#[async_trait]
+impl TryFromConn for Multipart {
+    type Error = Error;
+    async fn try_from_conn(conn: &mut Conn) -> Result<Self> {
+        let config = conn.state::<MultipartConfig>().cloned().unwrap_or_default();
+        Self::read(conn, &config).await
+    }
+}
+
+struct TempFile {
+    file: Unblock<File>,
+    path: TempPath,
+}
+
+impl TempFile {
+    async fn create(temp_dir: Option<PathBuf>, initial: Vec<u8>) -> Result<Self> {
+        let (file, path) = blocking::unblock(move || match temp_dir {
+            Some(temp_dir) => NamedTempFile::new_in(temp_dir),
+            None => NamedTempFile::new(),
+        })
+        .await?
+        .into_parts();
+
+        let mut temp_file = Self {
+            file: Unblock::new(file),
+            path,
+        };
+        temp_file.write(&initial).await?;
+        Ok(temp_file)
+    }
+
+    async fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        Ok(self.file.write_all(bytes).await?)
+    }
+
+    async fn finish(mut self) -> Result<TempPath> {
+        self.file.flush().await?;
+        Ok(self.path)
+    }
+}
+
+fn parse_error(message: &str) -> Error {
+    Error::ParseError {
+        path: String::new(),
+        message: message.into(),
+    }
+}
+
+fn boundary(content_type: &Mime) -> Result<String> {
+    if content_type.type_() != mime::MULTIPART {
+        return Err(Error::UnsupportedMimeType {
+            mime_type: content_type.to_string(),
+        });
+    }
+
+    content_type
+        .get_param(mime::BOUNDARY)
+        .map(|boundary| boundary.to_string())
+        .ok_or_else(|| parse_error("multipart content type is missing a boundary"))
+}
+
+// returns the name and filename parameters of a content-disposition header
+fn content_disposition(value: &str) -> (Option<String>, Option<String>) {
+    let mut name = None;
+    let mut filename = None;
+    let mut rest = value.split_once(';').map_or("", |(_, rest)| rest);
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        let Some((key, after_key)) = rest.split_once('=') else {
+            break;
+        };
+
+        let (param, after_value) = match after_key.strip_prefix('"') {
+            Some(quoted) => {
+                let mut param = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((index, c)) = chars.next() {
+                    match c {
+                        '\\' => param.extend(chars.next().map(|(_, c)| c)),
+                        '"' => {
+                            end = index + 1;
+                            break;
+                        }
+                        c => param.push(c),
+                    }
+                }
+                (param, &quoted[end..])
+            }
+
+            None => {
+                let (param, after_value) = after_key.split_once(';').unwrap_or((after_key, ""));
+                (param.trim().to_string(), after_value)
+            }
+        };
+
+        match key.trim().to_ascii_lowercase().as_str() {
+            "name" => name = Some(param),
+            "filename" => filename = Some(param),
+            _ => {}
+        }
+
+        rest = after_value;
+    }
+
+    (name, filename)
+}
@@ -0,0 +1,499 @@
+use crate::Result;
+use mime::Mime;
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+use trillium::{async_trait, Conn, Handler};
+
+/**
+Serializes response bodies as a particular content type
+
+Encoders are registered with [`ContentNegotiation`], which chooses
+one of them for each response based on the request's `Accept` header.
+Any type that implements [`serde::Serialize`] can be passed to
+[`Encoder::encode`] as a `&dyn erased_serde::Serialize`, and that
+trait object itself implements [`serde::Serialize`].
+
+```
+use trillium_api::{erased_serde, ContentNegotiation, Encoder, Result};
+
+#[derive(Debug)]
+struct PrettyJson;
+
+impl Encoder for PrettyJson {
+    fn content_type(&self) -> &str {
+        "application/json"
+    }
+
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec_pretty(body)?)
+    }
+}
+
+let negotiation = ContentNegotiation::new().with_default(PrettyJson);
+```
+*/
+pub trait Encoder: Debug + Send + Sync + 'static {
+    /// The content type of the bodies produced by this encoder, which
+    /// is sent as the response content-type
+    fn content_type(&self) -> &str;
+
+    /// Whether this encoder can produce a body for this media type
+    /// from an `Accept` header. By default, this compares the type and
+    /// subtype with [`Encoder::content_type`], ignoring parameters.
+    /// Wildcards are handled by [`ContentNegotiation`] and do not
+    /// need to be handled here.
+    fn accepts(&self, media_type: &Mime) -> bool {
+        self.content_type()
+            .parse::<Mime>()
+            .is_ok_and(|content_type| content_type.essence_str() == media_type.essence_str())
+    }
+
+    /// Serializes the body
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>>;
+}
+
+/// Encodes `application/json` with serde_json. This also accepts any
+/// media type with a `+json` suffix.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonEncoder;
+
+impl Encoder for JsonEncoder {
+    fn content_type(&self) -> &str {
+        "application/json"
+    }
+
+    fn accepts(&self, media_type: &Mime) -> bool {
+        media_type.suffix().unwrap_or_else(|| media_type.subtype()) == "json"
+    }
+
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_json::to_vec(body)?)
+    }
+}
+
+/// Encodes `application/x-www-form-urlencoded` with serde_urlencoded
+#[cfg(feature = "forms")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FormEncoder;
+
+#[cfg(feature = "forms")]
+impl Encoder for FormEncoder {
+    fn content_type(&self) -> &str {
+        "application/x-www-form-urlencoded"
+    }
+
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        Ok(serde_urlencoded::to_string(body)?.into_bytes())
+    }
+}
+
+/// Encodes `application/cbor` with ciborium
+#[cfg(feature = "cbor")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CborEncoder;
+
+#[cfg(feature = "cbor")]
+impl Encoder for CborEncoder {
+    fn content_type(&self) -> &str {
+        "application/cbor"
+    }
+
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        ciborium::into_writer(body, &mut bytes).map_err(|e| crate::Error::Other {
+            message: e.to_string(),
+        })?;
+        Ok(bytes)
+    }
+}
+
+/// Encodes `application/msgpack` with rmp-serde, serializing structs
+/// as maps. This also accepts `application/x-msgpack` and
+/// `application/vnd.msgpack`.
+#[cfg(feature = "msgpack")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MsgPackEncoder;
+
+#[cfg(feature = "msgpack")]
+impl Encoder for MsgPackEncoder {
+    fn content_type(&self) -> &str {
+        "application/msgpack"
+    }
+
+    fn accepts(&self, media_type: &Mime) -> bool {
+        media_type.type_() == mime::APPLICATION
+            && matches!(
+                media_type.subtype().as_str(),
+                "msgpack" | "x-msgpack" | "vnd.msgpack"
+            )
+    }
+
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(body).map_err(|e| crate::Error::Other {
+            message: e.to_string(),
+        })
+    }
+}
+
+/// Encodes `text/csv` with the csv crate. A sequence is written as
+/// one row per element, and anything else is written as a single row.
+/// A header row is written if the rows are structs.
+#[cfg(feature = "csv")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsvEncoder;
+
+#[cfg(feature = "csv")]
+impl Encoder for CsvEncoder {
+    fn content_type(&self) -> &str {
+        "text/csv"
+    }
+
+    fn encode(&self, body: &dyn erased_serde::Serialize) -> Result<Vec<u8>> {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        match serde::Serialize::serialize(body, csv_rows::Rows(&mut writer)) {
+            Ok(()) => {}
+            Err(csv_rows::RowsError::NotASequence) => {
+                writer.serialize(body).map_err(csv_rows::error)?
+            }
+            Err(csv_rows::RowsError::Csv(e)) => return Err(csv_rows::error(e)),
+        }
+        writer
+            .into_inner()
+            .map_err(|e| csv_rows::error(e.into_error().into()))
+    }
+}
+
+/**
+Chooses an [`Encoder`] for response bodies based on the `Accept`
+header
+
+This is used by [`ApiConnExt::serialize`](crate::ApiConnExt::serialize)
+and the [`Body`](crate::Body) handler. Add this handler before them
+to configure the available encoders for those conns. If it is not
+added, the default configuration is used, which encodes json and
+each format enabled by a cargo feature (`forms`, `cbor`, `msgpack`,
+and `csv`).
+
+When the request has no `Accept` header or accepts any media type, the
+default encoder is used, which is json unless configured with
+[`ContentNegotiation::with_default`]. When no encoder is acceptable,
+the conn halts with [`Error::FailureToNegotiateContent`](crate::Error::FailureToNegotiateContent), which is
+sent as a 406 Not Acceptable, unless
+[`ContentNegotiation::with_fallback_to_default`] is enabled.
+
+```
+use trillium_api::{api, Body, ContentNegotiation, JsonEncoder};
+use trillium::Conn;
+use trillium_testing::prelude::*;
+
+let handler = (
+    ContentNegotiation::empty().with_default(JsonEncoder),
+    api(|_: &mut Conn, ()| async { Body(vec!["hello", "world"]) }),
+);
+
+assert_response!(
+    get("/").with_request_header("accept", "text/html").on(&handler),
+    Status::NotAcceptable
+);
+
+assert_ok!(
+    get("/").with_request_header("accept", "text/html, application/json;q=0.5").on(&handler),
+    r#"["hello","world"]"#,
+    "content-type" => "application/json"
+);
+```
+*/
+#[derive(Clone)]
+pub struct ContentNegotiation {
+    encoders: Vec<Arc<dyn Encoder>>,
+    default: Option<usize>,
+    fallback_to_default: bool,
+}
+
+impl Debug for ContentNegotiation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ContentNegotiation")
+            .field("encoders", &self.encoders)
+            .field("default", &self.default_encoder())
+            .field("fallback_to_default", &self.fallback_to_default)
+            .finish()
+    }
+}
+
+impl Default for ContentNegotiation {
+    fn default() -> Self {
+        let negotiation = Self::empty().with_default(JsonEncoder);
+
+        #[cfg(feature = "forms")]
+        let negotiation = negotiation.with_encoder(FormEncoder);
+
+        #[cfg(feature = "cbor")]
+        let negotiation = negotiation.with_encoder(CborEncoder);
+
+        #[cfg(feature = "msgpack")]
+        let negotiation = negotiation.with_encoder(MsgPackEncoder);
+
+        #[cfg(feature = "csv")]
+        let negotiation = negotiation.with_encoder(CsvEncoder);
+
+        negotiation
+    }
+}
+
+impl ContentNegotiation {
+    /// Constructs a new ContentNegotiation with json as the default and
+    /// every encoder enabled by cargo features
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Constructs a new ContentNegotiation with no encoders. Without
+    /// any encoders, every response fails to negotiate.
+    pub fn empty() -> Self {
+        Self {
+            encoders: vec![],
+            default: None,
+            fallback_to_default: false,
+        }
+    }
+
+    /// Adds an encoder. If an encoder with the same content type is
+    /// already registered, it is replaced. Encoders are considered in
+    /// the order they are added for `Accept` headers that do not
+    /// prefer one over another.
+    pub fn with_encoder(mut self, encoder: impl Encoder) -> Self {
+        self.insert(Arc::new(encoder));
+        self
+    }
+
+    /// Adds an encoder and uses it when the request does not express a
+    /// preference, replacing any existing encoder with the same
+    /// content type
+    pub fn with_default(mut self, encoder: impl Encoder) -> Self {
+        self.default = Some(self.insert(Arc::new(encoder)));
+        self
+    }
+
+    /// Uses the default encoder instead of halting with a 406 Not
+    /// Acceptable when the `Accept` header does not match any encoder.
+    /// This is disabled by default.
+    pub fn with_fallback_to_default(mut self, fallback_to_default: bool) -> Self {
+        self.fallback_to_default = fallback_to_default;
+        self
+    }
+
+    /// Returns the encoder that will be used when the request does not
+    /// express a preference, if any
+    pub fn default_encoder(&self) -> Option<&dyn Encoder> {
+        self.default.map(|index| &*self.encoders[index])
+    }
+
+    /// Chooses an encoder for the provided `Accept` header value,
+    /// honoring quality values. Returns None if no encoder is
+    /// acceptable.
+    pub fn negotiate(&self, accept: Option<&str>) -> Option<&dyn Encoder> {
+        let Some(accept) = accept else {
+            return self.default_encoder();
+        };
+
+        let mut media_ranges = accept
+            .split(',')
+            .filter_map(|media_range| media_range.trim().parse::<Mime>().ok())
+            .map(|media_range| {
+                let quality = media_range
+                    .get_param("q")
+                    .and_then(|q| q.as_str().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (media_range, quality)
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .collect::<Vec<_>>();
+        media_ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        media_ranges
+            .iter()
+            .find_map(|(media_range, _)| self.find(media_range))
+            .or_else(|| {
+                if self.fallback_to_default {
+                    self.default_encoder()
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn find(&self, media_range: &Mime) -> Option<&dyn Encoder> {
+        if media_range.type_() == mime::STAR {
+            return self.default_encoder();
+        }
+
+        if media_range.subtype() == mime::STAR {
+            return self
+                .default_encoder()
+                .into_iter()
+                .chain(self.encoders.iter().map(|encoder| &**encoder))
+                .find(|encoder| {
+                    encoder
+                        .content_type()
+                        .parse::<Mime>()
+                        .is_ok_and(|content_type| content_type.type_() == media_range.type_())
+                });
+        }
+
+        self.encoders
+            .iter()
+            .map(|encoder| &**encoder)
+            .find(|encoder| encoder.accepts(media_range))
+    }
+
+    fn insert(&mut self, encoder: Arc<dyn Encoder>) -> usize {
+        let existing = self
+            .encoders
+            .iter()
+            .position(|existing| existing.content_type() == encoder.content_type());
+
+        if let Some(index) = existing {
+            self.encoders[index] = encoder;
+            index
+        } else {
+            self.encoders.push(encoder);
+            self.encoders.len() - 1
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for ContentNegotiation {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_state(self.clone())
+    }
+}
+
+#[cfg(feature = "csv")]
+mod csv_rows {
+    use crate::Error;
+    use serde::ser::{Impossible, Serialize, SerializeSeq, Serializer};
+    use std::fmt::{self, Display, Formatter};
+
+    pub(super) fn error(error: csv::Error) -> Error {
+        Error::Other {
+            message: error.to_string(),
+        }
+    }
+
+    #[derive(Debug)]
+    pub(super) enum RowsError {
+        NotASequence,
+        Csv(csv::Error),
+    }
+
+    impl Display for RowsError {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            match self {
+                RowsError::NotASequence => f.write_str("not a sequence"),
+                RowsError::Csv(e) => Display::fmt(e, f),
+            }
+        }
+    }
+
+    impl std::error::Error for RowsError {}
+
+    impl serde::ser::Error for RowsError {
+        fn custom<T: Display>(msg: T) -> Self {
+            RowsError::Csv(csv::Error::from(std::io::Error::other(msg.to_string())))
+        }
+    }
+
+    // a serializer that writes each element of a top-level sequence as
+    // a csv row, and declines anything else so that it can be written
+    // as a single row instead
+    pub(super) struct Rows<'a>(pub(super) &'a mut csv::Writer<Vec<u8>>);
+
+    macro_rules! not_a_sequence {
+        ($($method:ident($($arg:ty),*) -> $ok:ty;)*) => {
+            $(fn $method(self, $(_: $arg),*) -> Result<$ok, RowsError> {
+                Err(RowsError::NotASequence)
+            })*
+        };
+    }
+
+    impl<'a> Serializer for Rows<'a> {
+        type Ok = ();
+        type Error = RowsError;
+        type SerializeSeq = Self;
+        type SerializeTuple = Impossible<(), RowsError>;
+        type SerializeTupleStruct = Impossible<(), RowsError>;
+        type SerializeTupleVariant = Impossible<(), RowsError>;
+        type SerializeMap = Impossible<(), RowsError>;
+        type SerializeStruct = Impossible<(), RowsError>;
+        type SerializeStructVariant = Impossible<(), RowsError>;
+
+        fn serialize_seq(self, _: Option<usize>) -> Result<Self, RowsError> {
+            Ok(self)
+        }
+
+        not_a_sequence! {
+            serialize_bool(bool) -> ();
+            serialize_i8(i8) -> ();
+            serialize_i16(i16) -> ();
+            serialize_i32(i32) -> ();
+            serialize_i64(i64) -> ();
+            serialize_u8(u8) -> ();
+            serialize_u16(u16) -> ();
+            serialize_u32(u32) -> ();
+            serialize_u64(u64) -> ();
+            serialize_f32(f32) -> ();
+            serialize_f64(f64) -> ();
+            serialize_char(char) -> ();
+            serialize_str(&str) -> ();
+            serialize_bytes(&[u8]) -> ();
+            serialize_none() -> ();
+            serialize_unit() -> ();
+            serialize_unit_struct(&'static str) -> ();
+            serialize_unit_variant(&'static str, u32, &'static str) -> ();
+            serialize_tuple(usize) -> Self::SerializeTuple;
+            serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+            serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+            serialize_map(Option<usize>) -> Self::SerializeMap;
+            serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+            serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+        }
+
+        fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<(), RowsError> {
+            Err(RowsError::NotASequence)
+        }
+
+        fn serialize_newtype_struct<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            _: &T,
+        ) -> Result<(), RowsError> {
+            Err(RowsError::NotASequence)
+        }
+
+        fn serialize_newtype_variant<T: ?Sized + Serialize>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<(), RowsError> {
+            Err(RowsError::NotASequence)
+        }
+    }
+
+    impl<'a> SerializeSeq for Rows<'a> {
+        type Ok = ();
+        type Error = RowsError;
+
+        fn serialize_element<T: ?Sized + Serialize>(&mut self, row: &T) -> Result<(), RowsError> {
+            self.0.serialize(row).map_err(RowsError::Csv)
+        }
+
+        fn end(self) -> Result<(), RowsError> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,584 @@
+use crate::{ApiHandler, Body, Error, Halt, Json, ProblemDetails};
+use schemars::{
+    generate::{SchemaGenerator, SchemaSettings},
+    JsonSchema, Schema,
+};
+use serde_json::{json, Map, Value};
+use std::{collections::BTreeMap, sync::OnceLock};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Method, Status};
+
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema_for<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+fn inline_schema_for<T: JsonSchema>(_: &mut SchemaGenerator) -> Schema {
+    SchemaSettings::draft2020_12()
+        .with(|settings| {
+            settings.inline_subschemas = true;
+            settings.meta_schema = None;
+        })
+        .into_generator()
+        .into_root_schema_for::<T>()
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Response {
+    content_type: Option<&'static str>,
+    schema: Option<SchemaFn>,
+}
+
+/**
+An OpenAPI operation, describing a single method on a single route
+
+Operations can be built up by hand, or derived from an [`ApiHandler`]'s
+extractor and response types with [`ApiHandler::operation`]. Types are
+described with [`schemars::JsonSchema`], which can be derived.
+
+```
+use trillium_api::Operation;
+
+#[derive(schemars::JsonSchema)]
+struct Widget {
+    name: String,
+}
+
+let operation = Operation::new()
+    .with_summary("create a widget")
+    .with_tag("widgets")
+    .with_json_body::<Widget>()
+    .with_json_response::<Widget>(201);
+```
+*/
+#[derive(Debug, Clone, Default)]
+pub struct Operation {
+    summary: Option<String>,
+    description: Option<String>,
+    operation_id: Option<String>,
+    tags: Vec<String>,
+    path_params: Option<SchemaFn>,
+    query: Option<SchemaFn>,
+    request_body: Option<(&'static str, SchemaFn)>,
+    responses: BTreeMap<String, Response>,
+}
+
+impl Operation {
+    /// Constructs an empty Operation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a short summary of what this operation does
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Sets a longer description of this operation
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets a unique identifier for this operation
+    pub fn with_operation_id(mut self, operation_id: impl Into<String>) -> Self {
+        self.operation_id = Some(operation_id.into());
+        self
+    }
+
+    /// Adds a tag, used to group operations
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Describes the route's params with this type, as deserialized by
+    /// [`Path`](crate::Path). Route params are always documented, as
+    /// strings unless described by this type.
+    pub fn with_path_params<T: JsonSchema>(mut self) -> Self {
+        self.path_params = Some(inline_schema_for::<T>);
+        self
+    }
+
+    /// Describes the query string with this type, as deserialized by
+    /// [`Query`](crate::Query). Each field is documented as a query
+    /// param.
+    pub fn with_query<T: JsonSchema>(mut self) -> Self {
+        self.query = Some(inline_schema_for::<T>);
+        self
+    }
+
+    /// Describes a required `application/json` request body of this type
+    pub fn with_json_body<T: JsonSchema>(mut self) -> Self {
+        self.request_body = Some(("application/json", schema_for::<T>));
+        self
+    }
+
+    /// Describes a response with this status and no body
+    pub fn with_response(mut self, status: impl TryInto<Status>) -> Self {
+        self.insert_response(status_key(status), None, None);
+        self
+    }
+
+    /// Describes an `application/json` response of this type with this
+    /// status
+    pub fn with_json_response<T: JsonSchema>(mut self, status: impl TryInto<Status>) -> Self {
+        self.insert_response(
+            status_key(status),
+            Some("application/json"),
+            Some(schema_for::<T>),
+        );
+        self
+    }
+
+    fn insert_response(
+        &mut self,
+        key: String,
+        content_type: Option<&'static str>,
+        schema: Option<SchemaFn>,
+    ) {
+        self.responses.insert(
+            key,
+            Response {
+                content_type,
+                schema,
+            },
+        );
+    }
+
+    fn to_value(&self, route: &str, generator: &mut SchemaGenerator) -> Value {
+        let mut operation = Map::new();
+        if let Some(summary) = &self.summary {
+            operation.insert("summary".into(), summary.as_str().into());
+        }
+        if let Some(description) = &self.description {
+            operation.insert("description".into(), description.as_str().into());
+        }
+        if let Some(operation_id) = &self.operation_id {
+            operation.insert("operationId".into(), operation_id.as_str().into());
+        }
+        if !self.tags.is_empty() {
+            operation.insert("tags".into(), self.tags.clone().into());
+        }
+
+        let mut parameters = self.path_parameters(route, generator);
+        parameters.extend(self.query_parameters(generator));
+        if !parameters.is_empty() {
+            operation.insert("parameters".into(), parameters.into());
+        }
+
+        if let Some((content_type, schema)) = self.request_body {
+            operation.insert(
+                "requestBody".into(),
+                json!({
+                    "required": true,
+                    "content": { content_type: { "schema": schema(generator) } }
+                }),
+            );
+        }
+
+        let responses = self
+            .responses
+            .iter()
+            .map(|(key, response)| {
+                let description = key
+                    .parse::<u16>()
+                    .ok()
+                    .and_then(|status| Status::try_from(status).ok())
+                    .map_or("Error", |status| status.canonical_reason());
+                let mut value = json!({ "description": description });
+                if let Some(content_type) = response.content_type {
+                    let schema = response
+                        .schema
+                        .map_or_else(|| json!({}), |schema| schema(generator).to_value());
+                    value["content"] = json!({ content_type: { "schema": schema } });
+                }
+                (key.clone(), value)
+            })
+            .collect::<Map<_, _>>();
+        operation.insert("responses".into(), responses.into());
+
+        operation.into()
+    }
+
+    fn path_parameters(&self, route: &str, generator: &mut SchemaGenerator) -> Vec<Value> {
+        let names = route
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix(':'))
+            .collect::<Vec<_>>();
+        let schema = self.path_params.map(|schema| schema(generator));
+        let schema = schema.as_ref();
+        let properties = schema.and_then(|schema| schema.get("properties"));
+        let items = schema.and_then(|schema| schema.get("prefixItems"));
+
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let schema = match (properties, items, schema) {
+                    (Some(properties), _, _) => properties.get(name).cloned(),
+                    (None, Some(items), _) => items.get(index).cloned(),
+                    (None, None, Some(schema)) if names.len() == 1 => {
+                        Some(schema.as_value().clone())
+                    }
+                    _ => None,
+                };
+                json!({
+                    "name": name,
+                    "in": "path",
+                    "required": true,
+                    "schema": schema.unwrap_or_else(|| json!({ "type": "string" })),
+                })
+            })
+            .collect()
+    }
+
+    fn query_parameters(&self, generator: &mut SchemaGenerator) -> Vec<Value> {
+        let Some(schema) = self.query.map(|schema| schema(generator)) else {
+            return vec![];
+        };
+        let required = schema
+            .get("required")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        schema
+            .get("properties")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flatten()
+            .map(|(name, schema)| {
+                json!({
+                    "name": name,
+                    "in": "query",
+                    "required": required.contains(&Value::from(name.as_str())),
+                    "schema": schema,
+                })
+            })
+            .collect()
+    }
+}
+
+fn status_key(status: impl TryInto<Status>) -> String {
+    (status.try_into().unwrap_or(Status::Ok) as u16).to_string()
+}
+
+fn openapi_path(route: &str) -> String {
+    route
+        .split('/')
+        .map(|segment| match segment.strip_prefix(':') {
+            Some(name) => format!("{{{name}}}"),
+            None => segment.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Describes the parts of an [`Operation`] that a [`TryFromConn`](crate::TryFromConn)
+/// extractor reads from the request. Implement this for your own
+/// extractors to include them in [`ApiHandler::operation`].
+pub trait OperationInput {
+    /// Adds this extractor's params and request body to the operation
+    fn describe(operation: Operation) -> Operation {
+        operation
+    }
+}
+
+/// Describes the responses that a [`Handler`] returned from an
+/// [`ApiHandler`] may send. Implement this for your own response types
+/// to include them in [`ApiHandler::operation`].
+pub trait OperationOutput {
+    /// Adds this handler's responses to the operation
+    fn describe(operation: Operation) -> Operation {
+        operation
+    }
+}
+
+impl<F, OutputHandler, Extracted> ApiHandler<F, OutputHandler, Extracted>
+where
+    OutputHandler: OperationOutput,
+    Extracted: OperationInput,
+{
+    /**
+    Builds an [`Operation`] from this handler's extractor and response
+    types. Summaries and other details can be added to the returned
+    Operation.
+
+    ```
+    use trillium::Conn;
+    use trillium_api::{api, Json};
+
+    #[derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+    struct Widget {
+        name: String,
+    }
+
+    let handler = api(|_: &mut Conn, Json(widget): Json<Widget>| async move { Json(widget) });
+    let operation = handler.operation().with_summary("echo a widget");
+    ```
+    */
+    pub fn operation(&self) -> Operation {
+        OutputHandler::describe(Extracted::describe(Operation::new()))
+    }
+}
+
+impl OperationInput for () {}
+impl OperationInput for Method {}
+impl OperationInput for trillium::Headers {}
+impl OperationInput for String {}
+impl OperationInput for Vec<u8> {}
+impl<T> OperationInput for crate::State<T> {}
+impl<T: OperationInput> OperationInput for Option<T> {
+    fn describe(operation: Operation) -> Operation {
+        T::describe(operation)
+    }
+}
+impl<T: OperationInput, E> OperationInput for Result<T, E> {
+    fn describe(operation: Operation) -> Operation {
+        T::describe(operation)
+    }
+}
+
+impl<T: JsonSchema> OperationInput for Json<T> {
+    fn describe(operation: Operation) -> Operation {
+        operation.with_json_body::<T>()
+    }
+}
+
+impl<T: JsonSchema> OperationInput for Body<T> {
+    fn describe(operation: Operation) -> Operation {
+        operation.with_json_body::<T>()
+    }
+}
+
+#[cfg(feature = "forms")]
+impl<T: JsonSchema> OperationInput for crate::Query<T> {
+    fn describe(operation: Operation) -> Operation {
+        operation.with_query::<T>()
+    }
+}
+
+#[cfg(feature = "router")]
+impl<T: JsonSchema> OperationInput for crate::Path<T> {
+    fn describe(operation: Operation) -> Operation {
+        operation.with_path_params::<T>()
+    }
+}
+
+macro_rules! impl_operation_input_tuple {
+    ($($name:ident)+) => (
+        impl<$($name: OperationInput),*> OperationInput for ($($name,)*) {
+            fn describe(operation: Operation) -> Operation {
+                $(let operation = $name::describe(operation);)*
+                operation
+            }
+        }
+    )
+}
+
+impl_operation_input_tuple! { A B }
+impl_operation_input_tuple! { A B C }
+impl_operation_input_tuple! { A B C D }
+impl_operation_input_tuple! { A B C D E }
+impl_operation_input_tuple! { A B C D E F }
+impl_operation_input_tuple! { A B C D E F G }
+impl_operation_input_tuple! { A B C D E F G H }
+impl_operation_input_tuple! { A B C D E F G H I }
+impl_operation_input_tuple! { A B C D E F G H I J }
+impl_operation_input_tuple! { A B C D E F G H I J K }
+impl_operation_input_tuple! { A B C D E F G H I J K L }
+
+impl OperationOutput for () {}
+impl OperationOutput for Status {}
+impl OperationOutput for Halt {}
+
+impl OperationOutput for String {
+    fn describe(mut operation: Operation) -> Operation {
+        operation.insert_response(
+            status_key(Status::Ok),
+            Some("text/plain"),
+            Some(schema_for::<String>),
+        );
+        operation
+    }
+}
+
+impl OperationOutput for &'static str {
+    fn describe(operation: Operation) -> Operation {
+        <String as OperationOutput>::describe(operation)
+    }
+}
+
+impl<T: JsonSchema> OperationOutput for Json<T> {
+    fn describe(operation: Operation) -> Operation {
+        operation.with_json_response::<T>(Status::Ok)
+    }
+}
+
+impl<T: JsonSchema> OperationOutput for Body<T> {
+    fn describe(operation: Operation) -> Operation {
+        operation.with_json_response::<T>(Status::Ok)
+    }
+}
+
+impl OperationOutput for Error {
+    fn describe(mut operation: Operation) -> Operation {
+        operation.insert_response("default".into(), Some("application/json"), None);
+        operation
+    }
+}
+
+impl OperationOutput for ProblemDetails {
+    fn describe(mut operation: Operation) -> Operation {
+        operation.insert_response("default".into(), Some("application/problem+json"), None);
+        operation
+    }
+}
+
+impl<T: OperationOutput> OperationOutput for Option<T> {
+    fn describe(operation: Operation) -> Operation {
+        T::describe(operation)
+    }
+}
+
+impl<T: OperationOutput, E: OperationOutput> OperationOutput for Result<T, E> {
+    fn describe(operation: Operation) -> Operation {
+        E::describe(T::describe(operation))
+    }
+}
+
+/**
+An [OpenAPI 3.1](https://spec.openapis.org/oas/v3.1.0) document
+
+Operations are registered with the method and route they are served
+at, using the same route syntax as trillium-router. As a [`Handler`],
+this serves the document as json at `/openapi.json`, or at the path
+provided to [`OpenApi::with_path`], and passes all other requests
+along.
+
+```
+use trillium::{Conn, Method};
+use trillium_api::{api, Json, OpenApi, Path};
+use trillium_router::router;
+use trillium_testing::prelude::*;
+
+#[derive(serde::Serialize, schemars::JsonSchema)]
+struct Widget {
+    id: u64,
+}
+
+let get_widget = api(|_: &mut Conn, Path(id): Path<u64>| async move { Json(Widget { id }) });
+
+let openapi = OpenApi::new("widgets", "1.0.0").with_operation(
+    Method::Get,
+    "/widgets/:id",
+    get_widget.operation().with_summary("get a widget"),
+);
+
+let app = (openapi, router().get("/widgets/:id", get_widget));
+
+let mut conn = get("/openapi.json").on(&app);
+assert_ok!(&mut conn);
+let document: serde_json::Value = serde_json::from_str(&conn.take_response_body_string().unwrap()).unwrap();
+assert_eq!(document["paths"]["/widgets/{id}"]["get"]["summary"], "get a widget");
+assert_ok!(get("/widgets/10").on(&app), r#"{"id":10}"#);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct OpenApi {
+    title: String,
+    version: String,
+    description: Option<String>,
+    path: String,
+    operations: Vec<(Method, String, Operation)>,
+    document: OnceLock<String>,
+}
+
+impl OpenApi {
+    /// Constructs a new OpenApi document for an api with this title and
+    /// version
+    pub fn new(title: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            version: version.into(),
+            description: None,
+            path: String::from("/openapi.json"),
+            operations: vec![],
+            document: OnceLock::new(),
+        }
+    }
+
+    /// Sets a description of this api
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Sets the path that this document is served at. Defaults to
+    /// `/openapi.json`.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Registers an operation for this method and route
+    pub fn with_operation(
+        mut self,
+        method: Method,
+        route: impl Into<String>,
+        operation: Operation,
+    ) -> Self {
+        self.operations.push((method, route.into(), operation));
+        self.document = OnceLock::new();
+        self
+    }
+
+    /// Builds the document as a json value
+    pub fn document(&self) -> Value {
+        let mut generator = SchemaSettings::draft2020_12()
+            .with(|settings| {
+                settings.definitions_path = "/components/schemas".into();
+                settings.meta_schema = None;
+            })
+            .into_generator();
+
+        let mut paths = Map::new();
+        for (method, route, operation) in &self.operations {
+            let operation = operation.to_value(route, &mut generator);
+            let path = paths
+                .entry(openapi_path(route))
+                .or_insert_with(|| json!({}));
+            path[method.as_str().to_ascii_lowercase()] = operation;
+        }
+
+        let mut info = json!({ "title": self.title, "version": self.version });
+        if let Some(description) = &self.description {
+            info["description"] = description.as_str().into();
+        }
+
+        let mut document = json!({ "openapi": "3.1.0", "info": info, "paths": paths });
+        let schemas = generator.take_definitions(true);
+        if !schemas.is_empty() {
+            document["components"] = json!({ "schemas": schemas });
+        }
+        document
+    }
+}
+
+#[async_trait]
+impl Handler for OpenApi {
+    async fn run(&self, conn: Conn) -> Conn {
+        if conn.method() != Method::Get || conn.path() != self.path {
+            return conn;
+        }
+
+        let document = self
+            .document
+            .get_or_init(|| self.document().to_string())
+            .clone();
+
+        conn.with_response_header(KnownHeaderName::ContentType, "application/json")
+            .ok(document)
+    }
+}
@@ -0,0 +1,456 @@
+use crate::{ApiConnExt, FromConn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug, Display, Formatter},
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Method, Status};
+
+const RETAINED_OPERATIONS: usize = 1024;
+
+type Spawn = dyn Fn(Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static;
+
+/**
+Long-running operations that respond `202 Accepted` and are polled
+for their result
+
+A handler starts an operation with [`Operations::start`], which spawns
+the job in the background and returns an [`Accepted`] handler. That
+responds with a `202 Accepted` status, a `Location` header for the
+operation's status endpoint, and the [`OperationStatus`] as json.
+Clients poll the status endpoint until the operation has succeeded,
+with its serialized result, or failed, with its error message. Jobs
+can report intermediate progress with [`Progress::report`].
+
+As a [`Handler`], this serves `GET` requests for the status endpoint,
+which is `/operations/{id}` by default and can be changed with
+[`Operations::with_path_prefix`]. For any other request it makes itself
+available to later handlers, either from conn state or as a
+[`FromConn`] argument to an [`api`](crate::api) handler.
+
+Operation statuses are kept in an [`OperationStore`], which is an in
+memory [`MemoryOperationStore`] by default. Services with more than
+one instance can use [`Operations::with_store`] to keep them in a
+shared database.
+
+```
+use trillium::{Conn, KnownHeaderName::Location, Status};
+use trillium_api::{api, Operations, Progress};
+use trillium_testing::prelude::*;
+
+let operations = Operations::new(trillium_smol::spawn);
+
+let app = (
+    operations.clone(),
+    api(|_: &mut Conn, operations: Operations| async move {
+        operations
+            .start(|progress: Progress| async move {
+                progress.report(&"halfway").await;
+                Ok::<_, String>(42)
+            })
+            .await
+    }),
+);
+
+let conn = post("/").on(&app);
+assert_status!(&conn, Status::Accepted);
+let location = conn.response_headers().get_str(Location).unwrap().to_string();
+assert!(location.starts_with("/operations/"));
+```
+*/
+#[derive(Clone)]
+pub struct Operations {
+    store: Arc<dyn OperationStore>,
+    spawn: Arc<Spawn>,
+    path_prefix: Arc<str>,
+}
+
+impl Debug for Operations {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Operations")
+            .field("store", &self.store)
+            .field("path_prefix", &self.path_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+/// An identifier for a long-running operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OperationId(u64);
+
+impl OperationId {
+    /// builds an operation id from a number, for use by
+    /// [`OperationStore`] implementations
+    pub fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// the number that this id was built from
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for OperationId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "op_{}", self.0)
+    }
+}
+
+impl FromStr for OperationId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("op_").unwrap_or(s).parse().map(Self)
+    }
+}
+
+impl Serialize for OperationId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for OperationId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// The current state of an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationState {
+    /// the job is still running
+    Running,
+    /// the job completed successfully, and its result is available
+    Succeeded,
+    /// the job returned an error
+    Failed,
+}
+
+/// A snapshot of an operation's progress, which is the body of the
+/// status endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationStatus {
+    id: OperationId,
+    state: OperationState,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    progress: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl OperationStatus {
+    /// the status of an operation that has just started
+    pub fn new(id: OperationId) -> Self {
+        Self {
+            id,
+            state: OperationState::Running,
+            progress: None,
+            result: None,
+            error: None,
+        }
+    }
+
+    /// the id of this operation
+    pub fn id(&self) -> OperationId {
+        self.id
+    }
+
+    /// whether this operation is running, succeeded, or failed
+    pub fn state(&self) -> OperationState {
+        self.state
+    }
+
+    /// the most recent progress reported by the job, if any
+    pub fn progress(&self) -> Option<&Value> {
+        self.progress.as_ref()
+    }
+
+    /// the serialized result of a successful job
+    pub fn result(&self) -> Option<&Value> {
+        self.result.as_ref()
+    }
+
+    /// the error message of a failed job
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+}
+
+/**
+Storage for [`OperationStatus`]es
+
+Implement this to share operations between instances of a service.
+Ids returned by [`OperationStore::next_id`] must be unique across
+every instance that shares the store.
+*/
+#[async_trait]
+pub trait OperationStore: Debug + Send + Sync + 'static {
+    /// allocates an id for a new operation
+    async fn next_id(&self) -> OperationId;
+
+    /// inserts or replaces the status for an operation
+    async fn save(&self, status: &OperationStatus);
+
+    /// retrieves the status for an operation, if it is known
+    async fn load(&self, id: OperationId) -> Option<OperationStatus>;
+}
+
+/**
+The default [`OperationStore`], which keeps operations in memory
+
+Only the most recent operations are retained once they are no longer
+running.
+*/
+#[derive(Debug)]
+pub struct MemoryOperationStore {
+    ids: AtomicU64,
+    operations: RwLock<BTreeMap<OperationId, OperationStatus>>,
+}
+
+impl Default for MemoryOperationStore {
+    fn default() -> Self {
+        Self {
+            ids: AtomicU64::new(1),
+            operations: RwLock::default(),
+        }
+    }
+}
+
+impl MemoryOperationStore {
+    /// builds a new empty MemoryOperationStore
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl OperationStore for MemoryOperationStore {
+    async fn next_id(&self) -> OperationId {
+        OperationId(self.ids.fetch_add(1, Ordering::Relaxed))
+    }
+
+    async fn save(&self, status: &OperationStatus) {
+        let mut operations = self.operations.write().unwrap();
+        operations.insert(status.id, status.clone());
+        if operations.len() > RETAINED_OPERATIONS {
+            let finished = operations
+                .values()
+                .find(|status| status.state != OperationState::Running)
+                .map(|status| status.id);
+            if let Some(id) = finished {
+                operations.remove(&id);
+            }
+        }
+    }
+
+    async fn load(&self, id: OperationId) -> Option<OperationStatus> {
+        self.operations.read().unwrap().get(&id).cloned()
+    }
+}
+
+/// Passed to a job started with [`Operations::start`] to report its
+/// progress
+#[derive(Debug, Clone)]
+pub struct Progress {
+    id: OperationId,
+    store: Arc<dyn OperationStore>,
+}
+
+impl Progress {
+    /// the id of the operation this job is running for
+    pub fn id(&self) -> OperationId {
+        self.id
+    }
+
+    /// replaces the progress shown on the status endpoint with this
+    /// serialized value
+    pub async fn report(&self, progress: &impl Serialize) {
+        let progress = match serde_json::to_value(progress) {
+            Ok(progress) => progress,
+            Err(e) => {
+                log::error!("could not serialize progress for {}: {e}", self.id);
+                return;
+            }
+        };
+
+        if let Some(mut status) = self.store.load(self.id).await {
+            if status.state == OperationState::Running {
+                status.progress = Some(progress);
+                self.store.save(&status).await;
+            }
+        }
+    }
+}
+
+impl Operations {
+    /**
+    Builds a new Operations that runs jobs with this spawn function,
+    such as `trillium_smol::spawn`, `trillium_tokio::spawn`, or
+    `trillium_async_std::spawn`
+    */
+    pub fn new<F>(spawn: F) -> Self
+    where
+        F: Fn(Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static,
+    {
+        Self {
+            store: Arc::new(MemoryOperationStore::new()),
+            spawn: Arc::new(spawn),
+            path_prefix: Arc::from("/operations"),
+        }
+    }
+
+    /// Keeps operation statuses in this store instead of in memory
+    pub fn with_store(mut self, store: impl OperationStore) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// Serves status endpoints at `{path_prefix}/{id}` instead of the
+    /// default `/operations/{id}`
+    pub fn with_path_prefix(mut self, path_prefix: &str) -> Self {
+        self.path_prefix = Arc::from(path_prefix.trim_end_matches('/'));
+        self
+    }
+
+    /// The path of the status endpoint for this operation, which is
+    /// sent as the `Location` header
+    pub fn location(&self, id: OperationId) -> String {
+        format!("{}/{id}", self.path_prefix)
+    }
+
+    /// Returns the current status of an operation
+    pub async fn status(&self, id: OperationId) -> Option<OperationStatus> {
+        self.store.load(id).await
+    }
+
+    /**
+    Spawns this job in the background, returning an [`Accepted`]
+    handler that responds with the operation's status endpoint
+
+    The job is passed a [`Progress`] for reporting intermediate
+    progress. When it completes, its result is serialized as the
+    operation's result, or its error is displayed as the operation's
+    error.
+    */
+    pub async fn start<F, Fut, T, E>(&self, job: F) -> Accepted
+    where
+        F: FnOnce(Progress) -> Fut,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Serialize,
+        E: Display,
+    {
+        let id = self.store.next_id().await;
+        let status = OperationStatus::new(id);
+        self.store.save(&status).await;
+
+        let store = Arc::clone(&self.store);
+        let job = job(Progress {
+            id,
+            store: Arc::clone(&store),
+        });
+
+        (self.spawn)(Box::pin(async move {
+            let (result, error) = match job.await {
+                Ok(result) => match serde_json::to_value(result) {
+                    Ok(result) => (Some(result), None),
+                    Err(e) => (None, Some(e.to_string())),
+                },
+                Err(e) => (None, Some(e.to_string())),
+            };
+
+            let mut status = store.load(id).await.unwrap_or(OperationStatus::new(id));
+            status.state = if error.is_none() {
+                OperationState::Succeeded
+            } else {
+                OperationState::Failed
+            };
+            log::debug!("operation {id}: {:?}", status.state);
+            status.result = result;
+            status.error = error;
+            store.save(&status).await;
+        }));
+
+        Accepted {
+            location: self.location(id),
+            status,
+        }
+    }
+
+    fn status_id(&self, conn: &Conn) -> Option<OperationId> {
+        if conn.method() != Method::Get {
+            return None;
+        }
+
+        conn.path()
+            .strip_prefix(&*self.path_prefix)?
+            .strip_prefix('/')?
+            .parse()
+            .ok()
+    }
+}
+
+/// A [`Handler`] that responds `202 Accepted` for an operation started
+/// with [`Operations::start`]
+#[derive(Debug, Clone)]
+pub struct Accepted {
+    location: String,
+    status: OperationStatus,
+}
+
+impl Accepted {
+    /// the id of the operation that was started
+    pub fn id(&self) -> OperationId {
+        self.status.id
+    }
+
+    /// the path of the operation's status endpoint
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+}
+
+#[async_trait]
+impl Handler for Accepted {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_status(Status::Accepted)
+            .with_response_header(KnownHeaderName::Location, self.location.clone())
+            .with_json(&self.status)
+            .halt()
+    }
+}
+
+#[async_trait]
+impl Handler for Operations {
+    async fn run(&self, conn: Conn) -> Conn {
+        let Some(id) = self.status_id(&conn) else {
+            return conn.with_state(self.clone());
+        };
+
+        match self.store.load(id).await {
+            Some(status) => conn.with_json(&status).halt(),
+            None => conn.with_status(Status::NotFound).halt(),
+        }
+    }
+}
+
+#[async_trait]
+impl FromConn for Operations {
+    async fn from_conn(conn: &mut Conn) -> Option<Self> {
+        conn.state().cloned()
+    }
+}
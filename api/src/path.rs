@@ -0,0 +1,94 @@
+use crate::TryFromConn;
+use serde::de::DeserializeOwned;
+use std::ops::{Deref, DerefMut};
+use trillium::{async_trait, Conn};
+use trillium_router::RouterConnExt;
+
+/**
+Router params extractor
+
+Deserializes the params captured by [`trillium_router`] for the matched
+route, as described at [`RouterConnExt::params`].
+
+If the params cannot be deserialized, the conn halts with an
+[`Error::ParseError`](crate::Error::ParseError).
+
+```
+use trillium::Conn;
+use trillium_api::{api, Path};
+use trillium_router::router;
+use trillium_testing::prelude::*;
+
+#[derive(serde::Deserialize)]
+struct Issue {
+    repo: String,
+    number: u64,
+}
+
+let router = router()
+    .get("/issues/:id", api(|_: &mut Conn, Path(id): Path<u64>| async move {
+        format!("issue {id}")
+    }))
+    .get("/:repo/issues/:number", api(|_: &mut Conn, Path(issue): Path<Issue>| async move {
+        format!("{} issue {}", issue.repo, issue.number)
+    }))
+    .get("/:org/:repo", api(|_: &mut Conn, Path((org, repo)): Path<(String, String)>| async move {
+        format!("{org}/{repo}")
+    }));
+
+assert_ok!(get("/issues/10").on(&router), "issue 10");
+assert_ok!(get("/trillium/issues/10").on(&router), "trillium issue 10");
+assert_ok!(get("/trillium-rs/trillium%20rs").on(&router), "trillium-rs/trillium rs");
+assert_status!(get("/issues/ten").on(&router), 422);
+```
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub struct Path<T>(pub T);
+
+impl<T> Path<T> {
+    /// construct a new Path
+    pub fn new(t: T) -> Self {
+        Self(t)
+    }
+
+    /// Unwrap this Path
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Path<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Path<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Path<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[async_trait]
+impl<T> TryFromConn for Path<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    type Error = crate::Error;
+    async fn try_from_conn(conn: &mut Conn) -> Result<Self, Self::Error> {
+        conn.params()
+            .map(Self)
+            .map_err(|error| crate::Error::ParseError {
+                path: error.param().unwrap_or(".").to_string(),
+                message: error.to_string(),
+            })
+    }
+}
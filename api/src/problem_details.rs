@@ -0,0 +1,250 @@
+use crate::{ApiConnExt, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use trillium::{async_trait, Conn, Handler, Status};
+
+/**
+An [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457) problem details
+object
+
+Problem details are a standard format for http api errors, sent with a
+content-type of `application/problem+json`. As a [`Handler`], this
+sends itself as the response and halts the conn. It can also be sent
+with [`ApiConnExt::with_problem_details`].
+
+```
+use trillium::{Conn, Status};
+use trillium_api::{api, ProblemDetails};
+use trillium_testing::prelude::*;
+
+let handler = api(|_: &mut Conn, ()| async {
+    ProblemDetails::new(Status::Forbidden)
+        .with_type("https://example.com/probs/out-of-credit")
+        .with_detail("your current balance is 30, but that costs 50")
+        .with_extension("balance", 30)
+});
+
+assert_response!(
+    get("/").on(&handler),
+    Status::Forbidden,
+    r#"{"type":"https://example.com/probs/out-of-credit","title":"Forbidden","status":403,"detail":"your current balance is 30, but that costs 50","balance":30}"#,
+    "content-type" => "application/problem+json"
+);
+```
+*/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProblemDetails {
+    #[serde(rename = "type", default = "about_blank")]
+    problem_type: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    status: Option<u16>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+
+    #[serde(flatten)]
+    extensions: Map<String, Value>,
+}
+
+fn about_blank() -> String {
+    String::from("about:blank")
+}
+
+impl ProblemDetails {
+    /// Constructs a new ProblemDetails with this status, a type of
+    /// `about:blank`, and the status's canonical reason as the title
+    pub fn new(status: impl TryInto<Status>) -> Self {
+        let status = status.try_into().unwrap_or(Status::InternalServerError);
+        Self {
+            problem_type: about_blank(),
+            title: Some(status.canonical_reason().to_string()),
+            status: Some(status as u16),
+            detail: None,
+            instance: None,
+            extensions: Map::new(),
+        }
+    }
+
+    /// Sets the type, a uri that identifies the kind of problem
+    pub fn with_type(mut self, problem_type: impl Into<String>) -> Self {
+        self.problem_type = problem_type.into();
+        self
+    }
+
+    /// Sets the title, a short summary of the kind of problem
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Sets the detail, an explanation specific to this occurrence of
+    /// the problem
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Sets the instance, a uri that identifies this occurrence of the
+    /// problem
+    pub fn with_instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+
+    /// Adds an extension member. Extension members are serialized
+    /// alongside the standard members, and must not use their names.
+    pub fn with_extension(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.extensions.insert(name.into(), value.into());
+        self
+    }
+
+    /// Returns the type of this problem
+    pub fn problem_type(&self) -> &str {
+        &self.problem_type
+    }
+
+    /// Returns the title of this problem, if any
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the status of this problem, if any
+    pub fn status(&self) -> Option<Status> {
+        self.status.and_then(|status| status.try_into().ok())
+    }
+
+    /// Returns the detail of this problem, if any
+    pub fn detail(&self) -> Option<&str> {
+        self.detail.as_deref()
+    }
+
+    /// Returns the instance of this problem, if any
+    pub fn instance(&self) -> Option<&str> {
+        self.instance.as_deref()
+    }
+
+    /// Returns the extension members of this problem
+    pub fn extensions(&self) -> &Map<String, Value> {
+        &self.extensions
+    }
+}
+
+#[async_trait]
+impl Handler for ProblemDetails {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_problem_details(self.clone()).halt()
+    }
+}
+
+/**
+A conversion into [`ProblemDetails`]
+
+Implement this for application error types so that they can be sent
+with [`ApiConnExt::with_problem_details`] or converted into a
+[`ProblemDetails`] handler.
+
+```
+use trillium::{Conn, Status};
+use trillium_api::{api, ApiConnExt, IntoProblemDetails, ProblemDetails};
+use trillium_testing::prelude::*;
+
+enum AppError {
+    NotFound(u64),
+}
+
+impl IntoProblemDetails for AppError {
+    fn into_problem_details(self) -> ProblemDetails {
+        match self {
+            AppError::NotFound(id) => ProblemDetails::new(Status::NotFound)
+                .with_detail(format!("no widget with id {id}"))
+                .with_extension("id", id),
+        }
+    }
+}
+
+let handler = |conn: Conn| async move { conn.with_problem_details(AppError::NotFound(10)) };
+
+assert_response!(
+    get("/").on(&handler),
+    Status::NotFound,
+    r#"{"type":"about:blank","title":"Not Found","status":404,"detail":"no widget with id 10","id":10}"#,
+    "content-type" => "application/problem+json"
+);
+```
+*/
+pub trait IntoProblemDetails {
+    /// Converts self into a [`ProblemDetails`]
+    fn into_problem_details(self) -> ProblemDetails;
+}
+
+impl IntoProblemDetails for ProblemDetails {
+    fn into_problem_details(self) -> ProblemDetails {
+        self
+    }
+}
+
+impl IntoProblemDetails for Error {
+    fn into_problem_details(self) -> ProblemDetails {
+        let problem = ProblemDetails::new(Status::from(&self)).with_detail(self.to_string());
+        match self {
+            Error::ParseError { path, .. } => problem.with_extension("path", path),
+            _ => problem,
+        }
+    }
+}
+
+impl IntoProblemDetails for &Error {
+    fn into_problem_details(self) -> ProblemDetails {
+        self.clone().into_problem_details()
+    }
+}
+
+/**
+Renders this crate's [`Error`]s as [`ProblemDetails`]
+
+By default, an [`Error`] is sent as a json object with an `error`
+member. Add this handler before any handlers that may produce an
+[`Error`] to send `application/problem+json` instead.
+
+```
+use trillium::Conn;
+use trillium_api::{api, Json, ProblemDetailsErrors};
+use trillium_testing::prelude::*;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+struct Widget {
+    count: u8,
+}
+
+let handler = (
+    ProblemDetailsErrors,
+    api(|_: &mut Conn, Json(widget): Json<Widget>| async move { Json(widget) }),
+);
+
+assert_response!(
+    post("/")
+        .with_request_body(r#"{"count":"ten"}"#)
+        .with_request_header("content-type", "application/json")
+        .on(&handler),
+    422,
+    r#"{"type":"about:blank","title":"Unprocessable Entity","status":422,"detail":"Parse error at count: count: invalid type: string \"ten\", expected u8 at line 1 column 14","path":"count"}"#,
+    "content-type" => "application/problem+json"
+);
+```
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProblemDetailsErrors;
+
+#[async_trait]
+impl Handler for ProblemDetailsErrors {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_state(Self)
+    }
+}
@@ -0,0 +1,81 @@
+use crate::TryFromConn;
+use serde::de::DeserializeOwned;
+use std::ops::{Deref, DerefMut};
+use trillium::{async_trait, Conn};
+
+/**
+Query string extractor
+
+Deserializes the request's query string with serde_urlencoded. A
+request without a query string is deserialized from an empty query,
+so fields that are optional or have defaults will succeed. If the
+query cannot be deserialized, the conn halts with an
+[`Error::ParseError`](crate::Error::ParseError).
+
+```
+use trillium::Conn;
+use trillium_api::{api, Query};
+use trillium_testing::prelude::*;
+
+#[derive(serde::Deserialize)]
+struct Pagination {
+    page: usize,
+    per_page: Option<usize>,
+}
+
+let handler = api(|_: &mut Conn, Query(pagination): Query<Pagination>| async move {
+    format!("page {} of {}", pagination.page, pagination.per_page.unwrap_or(10))
+});
+
+assert_ok!(get("/?page=2&per_page=25").on(&handler), "page 2 of 25");
+assert_ok!(get("/?page=3").on(&handler), "page 3 of 10");
+assert_status!(get("/?page=first").on(&handler), 422);
+```
+*/
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Ord, PartialOrd, Hash)]
+pub struct Query<T>(pub T);
+
+impl<T> Query<T> {
+    /// construct a new Query
+    pub fn new(t: T) -> Self {
+        Self(t)
+    }
+
+    /// Unwrap this Query
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Query<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> Deref for Query<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Query<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+#[async_trait]
+impl<T> TryFromConn for Query<T>
+where
+    T: DeserializeOwned + Send + Sync + 'static,
+{
+    type Error = crate::Error;
+    async fn try_from_conn(conn: &mut Conn) -> Result<Self, Self::Error> {
+        let query = form_urlencoded::parse(conn.querystring().as_bytes());
+        let deserializer = serde_urlencoded::Deserializer::new(query);
+        Ok(Self(serde_path_to_error::deserialize(deserializer)?))
+    }
+}
@@ -0,0 +1,409 @@
+use crate::{Error, FromConn};
+use async_io::Timer;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use trillium::{async_trait, Conn, Handler};
+use trillium_client::{Client, IntoUrl, Url};
+
+const RETAINED_DELIVERIES: usize = 1024;
+
+/**
+Outbound webhook delivery
+
+Endpoints are registered with a url and a secret. Each event that is
+[enqueued](Webhooks::enqueue) is posted to every registered endpoint
+in the background as json of the form `{"type": ..., "data": ...}`,
+signed as described by [Standard
+Webhooks](https://www.standardwebhooks.com/) with the `webhook-id`,
+`webhook-timestamp`, and `webhook-signature` headers. Deliveries that
+do not receive a 2xx response are retried with exponential backoff,
+and their progress can be inspected with [`Webhooks::delivery`].
+
+As a [`Handler`], this makes itself available to later handlers,
+either from conn state or as a [`FromConn`] argument to an
+[`api`](crate::api) handler.
+
+```
+use trillium::Conn;
+use trillium_api::{api, Webhooks};
+use trillium_client::Client;
+use trillium_testing::prelude::*;
+
+let receiver = |conn: Conn| async move { conn.ok("received") };
+let webhooks = Webhooks::new(Client::new(trillium_testing::connector(receiver)));
+webhooks.register("http://example.com/hooks", "secret").unwrap();
+
+let app = (
+    webhooks.clone(),
+    api(|_: &mut Conn, webhooks: Webhooks| async move {
+        let deliveries = webhooks.enqueue("widget.created", &10).unwrap();
+        format!("{} deliveries", deliveries.len())
+    }),
+);
+
+assert_ok!(post("/").on(&app), "1 deliveries");
+```
+*/
+#[derive(Clone, Debug)]
+pub struct Webhooks {
+    inner: Arc<Inner>,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+#[derive(Debug)]
+struct Inner {
+    client: Client,
+    ids: AtomicU64,
+    endpoints: RwLock<BTreeMap<EndpointId, Endpoint>>,
+    deliveries: RwLock<BTreeMap<DeliveryId, DeliveryStatus>>,
+}
+
+#[derive(Debug, Clone)]
+struct Endpoint {
+    url: Url,
+    secret: Arc<[u8]>,
+}
+
+/// An identifier for a registered webhook endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EndpointId(u64);
+
+/// An identifier for a single event's delivery to a single endpoint,
+/// sent as the `webhook-id` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DeliveryId(u64);
+
+impl Display for EndpointId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "ep_{}", self.0)
+    }
+}
+
+impl Display for DeliveryId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "msg_{}", self.0)
+    }
+}
+
+/// The current state of a delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryState {
+    /// the delivery has not yet succeeded, and will be attempted again
+    Pending,
+    /// the endpoint responded with a success status
+    Delivered,
+    /// every attempt failed, and the delivery will not be retried
+    Failed,
+}
+
+/// A snapshot of a delivery's progress
+#[derive(Debug, Clone, Serialize)]
+pub struct DeliveryStatus {
+    id: DeliveryId,
+    endpoint: EndpointId,
+    event_type: String,
+    state: DeliveryState,
+    attempts: u32,
+    last_status: Option<u16>,
+    last_error: Option<String>,
+}
+
+impl Serialize for EndpointId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for DeliveryId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl DeliveryStatus {
+    /// the id of this delivery
+    pub fn id(&self) -> DeliveryId {
+        self.id
+    }
+
+    /// the endpoint this delivery is sent to
+    pub fn endpoint(&self) -> EndpointId {
+        self.endpoint
+    }
+
+    /// the type of the event being delivered
+    pub fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    /// whether this delivery is pending, delivered, or failed
+    pub fn state(&self) -> DeliveryState {
+        self.state
+    }
+
+    /// the number of attempts made so far
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// the response status of the most recent attempt, if it received
+    /// a response
+    pub fn last_status(&self) -> Option<u16> {
+        self.last_status
+    }
+
+    /// a description of why the most recent attempt failed, if it did
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+}
+
+/**
+Computes a `webhook-signature` header value for this message
+
+The signature is a base64 HMAC-SHA256 of `{message_id}.{timestamp}.{body}`,
+prefixed with the version `v1,`. Receivers can use this to verify
+deliveries.
+
+```
+let signature = trillium_api::webhook_signature(b"secret", "msg_1", 1700000000, br#"{"type":"ping","data":null}"#);
+assert!(signature.starts_with("v1,"));
+```
+*/
+pub fn webhook_signature(secret: &[u8], message_id: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("hmac accepts keys of any length");
+    mac.update(format!("{message_id}.{timestamp}.").as_bytes());
+    mac.update(body);
+    format!("v1,{}", STANDARD.encode(mac.finalize().into_bytes()))
+}
+
+impl Webhooks {
+    /// Builds a new Webhooks that delivers events with this client.
+    /// By default, each delivery is attempted up to five times, with
+    /// backoff starting at one second and capped at five minutes.
+    pub fn new(client: impl Into<Client>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client: client.into(),
+                ids: AtomicU64::new(1),
+                endpoints: RwLock::default(),
+                deliveries: RwLock::default(),
+            }),
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+        }
+    }
+
+    /// Sets the maximum number of attempts for each delivery,
+    /// including the first
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Sets the delay before the first retry, which doubles for each
+    /// subsequent retry up to `max`
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    fn next_id(&self) -> u64 {
+        self.inner.ids.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Registers an endpoint that will receive every subsequently
+    /// enqueued event, signed with this secret
+    pub fn register(
+        &self,
+        url: impl IntoUrl,
+        secret: impl AsRef<[u8]>,
+    ) -> crate::Result<EndpointId> {
+        let url = url.into_url(None)?;
+        let id = EndpointId(self.next_id());
+        let endpoint = Endpoint {
+            url,
+            secret: Arc::from(secret.as_ref()),
+        };
+        self.inner.endpoints.write().unwrap().insert(id, endpoint);
+        Ok(id)
+    }
+
+    /// Removes an endpoint, returning whether it was registered.
+    /// Deliveries that are already pending will still be attempted.
+    pub fn unregister(&self, endpoint: EndpointId) -> bool {
+        self.inner
+            .endpoints
+            .write()
+            .unwrap()
+            .remove(&endpoint)
+            .is_some()
+    }
+
+    /// Enqueues an event for delivery to every registered endpoint,
+    /// returning an id for each delivery
+    pub fn enqueue(
+        &self,
+        event_type: &str,
+        data: &impl Serialize,
+    ) -> crate::Result<Vec<DeliveryId>> {
+        let body: Arc<[u8]> = serde_json::to_vec(&json!({ "type": event_type, "data": data }))
+            .map_err(|e| Error::Other {
+                message: e.to_string(),
+            })?
+            .into();
+
+        let endpoints = self.inner.endpoints.read().unwrap().clone();
+        let mut ids = Vec::with_capacity(endpoints.len());
+        for (endpoint_id, endpoint) in endpoints {
+            let id = DeliveryId(self.next_id());
+            self.insert_status(DeliveryStatus {
+                id,
+                endpoint: endpoint_id,
+                event_type: event_type.to_string(),
+                state: DeliveryState::Pending,
+                attempts: 0,
+                last_status: None,
+                last_error: None,
+            });
+
+            let webhooks = self.clone();
+            let body = body.clone();
+            self.inner.client.connector().spawn(Box::pin(async move {
+                webhooks.deliver(id, endpoint, body).await
+            }));
+            ids.push(id);
+        }
+
+        Ok(ids)
+    }
+
+    /// Returns the current status of a delivery. Only the most recent
+    /// deliveries are retained once they are no longer pending.
+    pub fn delivery(&self, id: DeliveryId) -> Option<DeliveryStatus> {
+        self.inner.deliveries.read().unwrap().get(&id).cloned()
+    }
+
+    /// Returns the status of every retained delivery, oldest first
+    pub fn deliveries(&self) -> Vec<DeliveryStatus> {
+        self.inner
+            .deliveries
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    fn insert_status(&self, status: DeliveryStatus) {
+        let mut deliveries = self.inner.deliveries.write().unwrap();
+        deliveries.insert(status.id, status);
+        if deliveries.len() > RETAINED_DELIVERIES {
+            let finished = deliveries
+                .values()
+                .find(|status| status.state != DeliveryState::Pending)
+                .map(|status| status.id);
+            if let Some(id) = finished {
+                deliveries.remove(&id);
+            }
+        }
+    }
+
+    fn update_status(&self, id: DeliveryId, f: impl FnOnce(&mut DeliveryStatus)) {
+        if let Some(status) = self.inner.deliveries.write().unwrap().get_mut(&id) {
+            f(status);
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff)
+    }
+
+    async fn deliver(self, id: DeliveryId, endpoint: Endpoint, body: Arc<[u8]>) {
+        let message_id = id.to_string();
+        for attempt in 1..=self.max_attempts {
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs());
+            let signature = webhook_signature(&endpoint.secret, &message_id, timestamp, &body);
+
+            let result = self
+                .inner
+                .client
+                .post(endpoint.url.clone())
+                .with_request_header("content-type", "application/json")
+                .with_request_header("webhook-id", message_id.clone())
+                .with_request_header("webhook-timestamp", timestamp.to_string())
+                .with_request_header("webhook-signature", signature)
+                .with_body(body.to_vec())
+                .await;
+
+            let (status, error) = match result {
+                Ok(conn) => {
+                    let status = conn.status();
+                    conn.recycle().await;
+                    match status {
+                        Some(status) if status.is_success() => (Some(status), None),
+                        Some(status) => (Some(status), Some(format!("unexpected status {status}"))),
+                        None => (None, Some(String::from("no response status"))),
+                    }
+                }
+                Err(e) => (None, Some(e.to_string())),
+            };
+
+            let state = match (&error, attempt < self.max_attempts) {
+                (None, _) => DeliveryState::Delivered,
+                (Some(_), true) => DeliveryState::Pending,
+                (Some(_), false) => DeliveryState::Failed,
+            };
+
+            log::debug!("webhook {message_id} attempt {attempt}: {state:?}");
+
+            self.update_status(id, |delivery| {
+                delivery.attempts = attempt;
+                delivery.last_status = status.map(|status| status as u16);
+                delivery.last_error = error;
+                delivery.state = state;
+            });
+
+            if state != DeliveryState::Pending {
+                break;
+            }
+
+            Timer::after(self.backoff(attempt)).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for Webhooks {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_state(self.clone())
+    }
+}
+
+#[async_trait]
+impl FromConn for Webhooks {
+    async fn from_conn(conn: &mut Conn) -> Option<Self> {
+        conn.state().cloned()
+    }
+}
@@ -0,0 +1,92 @@
+use trillium_api::*;
+use trillium_router::router;
+use trillium_testing::{prelude::*, TestConn};
+
+fn widgets() -> Methods {
+    methods()
+        .get(api(|conn: &mut Conn, ()| {
+            let method = conn.method();
+            async move { Json(method.to_string()) }
+        }))
+        .put(api(|_: &mut Conn, Json(value): Json<Value>| async {
+            Json(value)
+        }))
+}
+
+#[test]
+fn dispatches_by_method() {
+    let handler = widgets();
+    assert_ok!(get("/").on(&handler), r#""GET""#);
+    assert_ok!(
+        put("/")
+            .with_request_header("content-type", "application/json")
+            .with_request_body(r#"{"name":"widget"}"#)
+            .on(&handler),
+        r#"{"name":"widget"}"#
+    );
+}
+
+#[test]
+fn head_runs_get_handler() {
+    let handler = widgets();
+    let conn = TestConn::build(Method::Head, "/", ()).on(&handler);
+    assert_status!(&conn, 200);
+    assert_eq!(conn.method(), Method::Head);
+
+    let handler = widgets().with(Method::Head, |conn: Conn| async move {
+        conn.with_response_header("x-head", "explicit").ok("")
+    });
+    let conn = TestConn::build(Method::Head, "/", ()).on(&handler);
+    assert_headers!(&conn, "x-head" => "explicit");
+}
+
+#[test]
+fn options_lists_allowed_methods() {
+    let handler = widgets();
+    assert_eq!(
+        handler.allowed_methods(),
+        [Method::Get, Method::Head, Method::Put, Method::Options]
+    );
+
+    let conn = TestConn::build(Method::Options, "/", ()).on(&handler);
+    assert_status!(&conn, 200);
+    assert_headers!(&conn, "allow" => "GET, HEAD, PUT, OPTIONS");
+
+    let handler = widgets().with(Method::Options, |conn: Conn| async move {
+        conn.with_status(204).halt()
+    });
+    assert_status!(TestConn::build(Method::Options, "/", ()).on(&handler), 204);
+}
+
+#[test]
+fn method_not_allowed() {
+    let handler = widgets();
+    let mut conn = delete("/").on(&handler);
+    assert_status!(&conn, 405);
+    assert_headers!(&conn, "allow" => "GET, HEAD, PUT, OPTIONS");
+    assert_eq!(
+        conn.take_response_body_string().unwrap(),
+        r#"{"error":{"method":"DELETE","type":"method_not_allowed"}}"#
+    );
+
+    let handler = (ProblemDetailsErrors, widgets());
+    let conn = post("/").on(&handler);
+    assert_status!(&conn, 405);
+    assert_headers!(
+        &conn,
+        "content-type" => "application/problem+json",
+        "allow" => "GET, HEAD, PUT, OPTIONS"
+    );
+}
+
+#[test]
+fn mounted_on_router() {
+    let handler = router().all("/widgets", widgets());
+    assert_ok!(get("/widgets").on(&handler), r#""GET""#);
+    assert_status!(
+        TestConn::build(Method::Head, "/widgets", ()).on(&handler),
+        200
+    );
+    assert_status!(delete("/widgets").on(&handler), 405);
+    assert_not_handled!(delete("/other").on(&handler));
+}
@@ -0,0 +1,166 @@
+use trillium::Conn;
+use trillium_api::*;
+use trillium_testing::prelude::*;
+
+const CONTENT_TYPE: &str = "multipart/form-data; boundary=----boundary";
+
+fn body(parts: &[(&str, Option<&str>, &str)]) -> String {
+    let mut body = String::from("preamble\r\n");
+    for (name, filename, content) in parts {
+        body.push_str("------boundary\r\n");
+        match filename {
+            Some(filename) => body.push_str(&format!(
+                "Content-Disposition: form-data; name=\"{name}\"; filename=\"{filename}\"\r\nContent-Type: application/octet-stream\r\n"
+            )),
+            None => body.push_str(&format!("Content-Disposition: form-data; name=\"{name}\"\r\n")),
+        }
+        body.push_str("\r\n");
+        body.push_str(content);
+        body.push_str("\r\n");
+    }
+    body.push_str("------boundary--\r\nepilogue");
+    body
+}
+
+fn describe(multipart: &Multipart) -> String {
+    multipart
+        .parts()
+        .iter()
+        .map(|part| {
+            format!(
+                "{}:{}:{}:{}",
+                part.name().unwrap_or("-"),
+                part.filename().unwrap_or("-"),
+                part.len(),
+                match part.data() {
+                    PartData::Memory(bytes) => String::from_utf8_lossy(bytes).into_owned(),
+                    PartData::File(path) =>
+                        format!("file:{}", std::fs::read_to_string(path).unwrap()),
+                }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+fn app() -> impl trillium::Handler {
+    api(|_: &mut Conn, multipart: Multipart| async move { describe(&multipart) })
+}
+
+#[test]
+fn buffered_parts() {
+    let content = "x".repeat(20_000) + "\r\n--not-the-boundary\r\n";
+    let request_body = body(&[
+        ("a", None, "1"),
+        ("empty", None, ""),
+        ("upload", Some("file \\\"quoted\\\".bin"), &content),
+    ]);
+
+    assert_ok!(
+        post("/")
+            .with_request_header("content-type", CONTENT_TYPE)
+            .with_request_body(request_body)
+            .on(&app()),
+        format!(
+            "a:-:1:1|empty:-:0:|upload:file \"quoted\".bin:{}:{content}",
+            content.len()
+        )
+    );
+}
+
+#[test]
+fn large_parts_are_written_to_temp_files() {
+    let handler = (MultipartConfig::new().with_memory_limit(4), app());
+    assert_ok!(
+        post("/")
+            .with_request_header("content-type", CONTENT_TYPE)
+            .with_request_body(body(&[("small", None, "1234"), ("large", None, "12345")]))
+            .on(&handler),
+        "small:-:4:1234|large:-:5:file:12345"
+    );
+}
+
+#[test]
+fn limits() {
+    let handler = (MultipartConfig::new().with_max_part_len(4), app());
+    assert_status!(
+        post("/")
+            .with_request_header("content-type", CONTENT_TYPE)
+            .with_request_body(body(&[("a", None, "12345")]))
+            .on(&handler),
+        413
+    );
+
+    let handler = (MultipartConfig::new().with_max_parts(1), app());
+    assert_status!(
+        post("/")
+            .with_request_header("content-type", CONTENT_TYPE)
+            .with_request_body(body(&[("a", None, "1"), ("b", None, "2")]))
+            .on(&handler),
+        413
+    );
+}
+
+#[test]
+fn malformed_bodies() {
+    assert_status!(
+        post("/")
+            .with_request_header("content-type", CONTENT_TYPE)
+            .with_request_body(
+                "------boundary\r\nContent-Disposition: form-data; name=\"a\"\r\n\r\nno end"
+            )
+            .on(&app()),
+        422
+    );
+
+    assert_status!(
+        post("/")
+            .with_request_header("content-type", "multipart/form-data")
+            .with_request_body(body(&[("a", None, "1")]))
+            .on(&app()),
+        422
+    );
+
+    assert_status!(
+        post("/")
+            .with_request_header("content-type", "application/json")
+            .with_request_body("{}")
+            .on(&app()),
+        415
+    );
+}
+
+#[test]
+fn streaming_fields() {
+    let handler = |mut conn: Conn| async move {
+        let mut reader = MultipartReader::new(&mut conn).await.unwrap();
+        let mut fields = vec![];
+        while let Some(mut field) = reader.next_field().await.unwrap() {
+            let name = field.name().unwrap().to_string();
+            if name == "skipped" {
+                continue;
+            }
+            let mut chunks = 0;
+            let mut len = 0;
+            while let Some(chunk) = field.chunk().await.unwrap() {
+                chunks += 1;
+                len += chunk.len();
+            }
+            fields.push(format!("{name}:{len}:{}", chunks > 1));
+        }
+        conn.ok(fields.join(","))
+    };
+
+    let large = "y".repeat(50_000);
+    assert_ok!(
+        post("/")
+            .with_request_header("content-type", CONTENT_TYPE)
+            .with_request_body(body(&[
+                ("skipped", None, &large),
+                ("large", None, &large),
+                ("small", None, "abc")
+            ]))
+            .on(&handler),
+        "large:50000:true,small:3:false"
+    );
+}
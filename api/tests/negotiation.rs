@@ -0,0 +1,138 @@
+use serde::Serialize;
+use trillium::Handler;
+use trillium_api::*;
+use trillium_testing::prelude::*;
+
+#[derive(Serialize, Debug)]
+struct Row {
+    a: u8,
+    b: &'static str,
+}
+
+fn app() -> impl Handler {
+    api(|_: &mut Conn, ()| async { Body(vec![Row { a: 1, b: "one" }, Row { a: 2, b: "two" }]) })
+}
+
+fn body(conn: &Conn) -> &[u8] {
+    conn.response_body().unwrap().static_bytes().unwrap()
+}
+
+#[test]
+fn json_by_default() {
+    let expected = r#"[{"a":1,"b":"one"},{"a":2,"b":"two"}]"#;
+    assert_ok!(get("/").on(&app()), expected, "content-type" => "application/json", "vary" => "Accept");
+
+    assert_ok!(
+        get("/").with_request_header("accept", "*/*").on(&app()),
+        expected,
+        "content-type" => "application/json"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("accept", "application/vnd.api+json")
+            .on(&app()),
+        expected,
+        "content-type" => "application/json"
+    );
+}
+
+#[test]
+fn csv() {
+    assert_ok!(
+        get("/").with_request_header("accept", "text/csv").on(&app()),
+        "a,b\n1,one\n2,two\n",
+        "content-type" => "text/csv"
+    );
+
+    let single_row = api(|_: &mut Conn, ()| async { Body(Row { a: 1, b: "one" }) });
+    assert_ok!(
+        get("/").with_request_header("accept", "text/*").on(&single_row),
+        "a,b\n1,one\n",
+        "content-type" => "text/csv"
+    );
+}
+
+#[test]
+fn cbor() {
+    let single = api(|_: &mut Conn, ()| async { Body(Row { a: 1, b: "one" }) });
+    let conn = get("/")
+        .with_request_header("accept", "application/cbor")
+        .on(&single);
+    assert_status!(&conn, 200);
+    assert_headers!(&conn, "content-type" => "application/cbor");
+    assert_eq!(
+        body(&conn),
+        [0xa2, 0x61, b'a', 0x01, 0x61, b'b', 0x63, b'o', b'n', b'e']
+    );
+}
+
+#[test]
+fn msgpack() {
+    let single = api(|_: &mut Conn, ()| async { Body(Row { a: 1, b: "one" }) });
+    let conn = get("/")
+        .with_request_header("accept", "application/x-msgpack")
+        .on(&single);
+    assert_status!(&conn, 200);
+    assert_headers!(&conn, "content-type" => "application/msgpack");
+    assert_eq!(
+        body(&conn),
+        [0x82, 0xa1, b'a', 0x01, 0xa1, b'b', 0xa3, b'o', b'n', b'e']
+    );
+}
+
+#[test]
+fn quality_values() {
+    assert_ok!(
+        get("/")
+            .with_request_header("accept", "application/json;q=0.5, text/csv")
+            .on(&app()),
+        "a,b\n1,one\n2,two\n",
+        "content-type" => "text/csv"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("accept", "text/csv;q=0, application/json")
+            .on(&app()),
+        r#"[{"a":1,"b":"one"},{"a":2,"b":"two"}]"#,
+        "content-type" => "application/json"
+    );
+}
+
+#[test]
+fn not_acceptable() {
+    assert_response!(
+        get("/")
+            .with_request_header("accept", "image/png")
+            .on(&app()),
+        406,
+        r#"{"error":{"type":"failure_to_negotiate_content"}}"#
+    );
+}
+
+#[test]
+fn configured_default_and_fallback() {
+    let app = (
+        ContentNegotiation::new()
+            .with_default(CsvEncoder)
+            .with_fallback_to_default(true),
+        app(),
+    );
+
+    assert_ok!(get("/").on(&app), "a,b\n1,one\n2,two\n", "content-type" => "text/csv");
+
+    assert_ok!(
+        get("/").with_request_header("accept", "image/png").on(&app),
+        "a,b\n1,one\n2,two\n",
+        "content-type" => "text/csv"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header("accept", "application/json")
+            .on(&app),
+        r#"[{"a":1,"b":"one"},{"a":2,"b":"two"}]"#,
+        "content-type" => "application/json"
+    );
+}
@@ -0,0 +1,155 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use trillium::{Handler, Method};
+use trillium_api::*;
+use trillium_router::router;
+use trillium_testing::prelude::*;
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+struct Widget {
+    name: String,
+    count: Option<u8>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[allow(dead_code)]
+struct Pagination {
+    page: usize,
+    per_page: Option<usize>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct WidgetPath {
+    org: String,
+    id: u64,
+}
+
+fn app() -> impl Handler {
+    let list =
+        api(|_: &mut Conn, Query(_): Query<Pagination>| async move { Json(Vec::<Widget>::new()) });
+    let create = api(|_: &mut Conn, Json(widget): Json<Widget>| async move { Json(widget) });
+    let show = api(|_: &mut Conn, Path(path): Path<WidgetPath>| async move {
+        if path.id == 0 {
+            Err(ProblemDetails::new(404))
+        } else {
+            Ok(format!("{}/{}", path.org, path.id))
+        }
+    });
+
+    let openapi = OpenApi::new("widgets", "1.0.0")
+        .with_description("an api for widgets")
+        .with_path("/docs/openapi.json")
+        .with_operation(
+            Method::Get,
+            "/:org/widgets",
+            list.operation().with_tag("widgets"),
+        )
+        .with_operation(
+            Method::Post,
+            "/:org/widgets",
+            create.operation().with_summary("create a widget"),
+        )
+        .with_operation(
+            Method::Get,
+            "/:org/widgets/:id",
+            show.operation().with_operation_id("showWidget"),
+        )
+        .with_operation(
+            Method::Delete,
+            "/:org/widgets/:id",
+            Operation::new().with_response(204),
+        );
+
+    (
+        openapi,
+        router()
+            .get("/:org/widgets", list)
+            .post("/:org/widgets", create)
+            .get("/:org/widgets/:id", show),
+    )
+}
+
+#[test]
+fn document() {
+    let app = app();
+    assert_not_handled!(get("/openapi.json").on(&app));
+    assert_ok!(get("/acme/widgets/10").on(&app), "acme/10");
+
+    let mut conn = get("/docs/openapi.json").on(&app);
+    assert_ok!(&mut conn);
+    assert_headers!(&conn, "content-type" => "application/json");
+    let document: Value = serde_json::from_str(&conn.take_response_body_string().unwrap()).unwrap();
+
+    assert_eq!(document["openapi"], "3.1.0");
+    assert_eq!(
+        document["info"],
+        json!({ "title": "widgets", "version": "1.0.0", "description": "an api for widgets" })
+    );
+    assert_eq!(
+        document["components"]["schemas"]["Widget"]["required"],
+        json!(["name"])
+    );
+
+    let list = &document["paths"]["/{org}/widgets"]["get"];
+    assert_eq!(list["tags"], json!(["widgets"]));
+    assert_eq!(
+        list["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|param| (&param["name"], &param["in"], &param["required"]))
+            .collect::<Vec<_>>(),
+        [
+            (&json!("org"), &json!("path"), &json!(true)),
+            (&json!("page"), &json!("query"), &json!(true)),
+            (&json!("per_page"), &json!("query"), &json!(false)),
+        ]
+    );
+    assert_eq!(
+        list["responses"]["200"]["content"]["application/json"]["schema"],
+        json!({ "type": "array", "items": { "$ref": "#/components/schemas/Widget" } })
+    );
+
+    let create = &document["paths"]["/{org}/widgets"]["post"];
+    assert_eq!(create["summary"], "create a widget");
+    assert_eq!(
+        create["requestBody"],
+        json!({
+            "required": true,
+            "content": {
+                "application/json": { "schema": { "$ref": "#/components/schemas/Widget" } }
+            }
+        })
+    );
+
+    let show = &document["paths"]["/{org}/widgets/{id}"]["get"];
+    assert_eq!(show["operationId"], "showWidget");
+    assert_eq!(show["parameters"][1]["schema"]["type"], "integer");
+    assert_eq!(
+        show["responses"]["200"]["content"]["text/plain"]["schema"],
+        json!({ "type": "string" })
+    );
+    assert!(show["responses"]["default"]["content"]["application/problem+json"].is_object());
+
+    let delete = &document["paths"]["/{org}/widgets/{id}"]["delete"];
+    assert_eq!(
+        delete["parameters"][1]["schema"],
+        json!({ "type": "string" })
+    );
+    assert_eq!(
+        delete["responses"],
+        json!({ "204": { "description": "No Content" } })
+    );
+}
+
+#[test]
+fn tuple_path_params() {
+    let handler =
+        api(|_: &mut Conn, Path((a, b)): Path<(String, u8)>| async move { format!("{a}{b}") });
+    let document = OpenApi::new("tuples", "0.1.0")
+        .with_operation(Method::Get, "/:a/:b", handler.operation())
+        .document();
+    let parameters = &document["paths"]["/{a}/{b}"]["get"]["parameters"];
+    assert_eq!(parameters[0]["schema"]["type"], "string");
+    assert_eq!(parameters[1]["schema"]["type"], "integer");
+}
@@ -0,0 +1,121 @@
+use async_channel::{bounded, Receiver};
+use async_io::Timer;
+use std::time::Duration;
+use trillium::{Conn, KnownHeaderName::Location, Status};
+use trillium_api::*;
+use trillium_testing::prelude::*;
+
+async fn settle(operations: &Operations, id: OperationId) -> OperationStatus {
+    for _ in 0..200 {
+        let status = operations.status(id).await.unwrap();
+        if status.state() != OperationState::Running {
+            return status;
+        }
+        Timer::after(Duration::from_millis(5)).await;
+    }
+    panic!("operation {id} did not settle");
+}
+
+fn app(operations: &Operations, proceed: Receiver<()>) -> impl trillium::Handler {
+    (
+        operations.clone(),
+        api(move |conn: &mut Conn, operations: Operations| {
+            let proceed = proceed.clone();
+            let fail = conn.querystring() == "fail";
+            async move {
+                operations
+                    .start(move |progress: Progress| async move {
+                        progress.report(&json!({ "percent": 50 })).await;
+                        let _ = proceed.recv().await;
+                        if fail {
+                            Err("it broke")
+                        } else {
+                            Ok(json!({ "answer": 42 }))
+                        }
+                    })
+                    .await
+            }
+        }),
+    )
+}
+
+#[test]
+fn accepted_then_polled() {
+    block_on(async {
+        let operations = Operations::new(trillium_smol::spawn);
+        let (proceed_tx, proceed_rx) = bounded(1);
+        let app = app(&operations, proceed_rx);
+
+        let mut conn = post("/").run_async(&app).await;
+        assert_status!(&conn, Status::Accepted);
+        let location = conn
+            .response_headers()
+            .get_str(Location)
+            .unwrap()
+            .to_string();
+        assert_eq!(location, "/operations/op_1");
+        assert_eq!(
+            conn.take_response_body_string().unwrap(),
+            r#"{"id":"op_1","state":"running"}"#
+        );
+
+        let id: OperationId = "op_1".parse().unwrap();
+        for _ in 0..200 {
+            if operations.status(id).await.unwrap().progress().is_some() {
+                break;
+            }
+            Timer::after(Duration::from_millis(5)).await;
+        }
+
+        assert_ok!(
+            get(&location).run_async(&app).await,
+            r#"{"id":"op_1","state":"running","progress":{"percent":50}}"#,
+            "content-type" => "application/json"
+        );
+
+        proceed_tx.send(()).await.unwrap();
+        let status = settle(&operations, id).await;
+        assert_eq!(status.state(), OperationState::Succeeded);
+        assert_eq!(status.result(), Some(&json!({ "answer": 42 })));
+
+        assert_ok!(
+            get(&location).run_async(&app).await,
+            r#"{"id":"op_1","state":"succeeded","progress":{"percent":50},"result":{"answer":42}}"#
+        );
+    });
+}
+
+#[test]
+fn failed_operations() {
+    block_on(async {
+        let operations = Operations::new(trillium_smol::spawn);
+        let (proceed_tx, proceed_rx) = bounded(1);
+        let app = app(&operations, proceed_rx);
+
+        let conn = post("/?fail").run_async(&app).await;
+        assert_status!(&conn, Status::Accepted);
+        proceed_tx.send(()).await.unwrap();
+
+        let status = settle(&operations, "op_1".parse().unwrap()).await;
+        assert_eq!(status.state(), OperationState::Failed);
+        assert_eq!(status.error(), Some("it broke"));
+        assert_eq!(status.result(), None);
+    });
+}
+
+#[test]
+fn path_prefix_and_unknown_operations() {
+    block_on(async {
+        let operations = Operations::new(trillium_smol::spawn).with_path_prefix("/jobs/");
+        let (proceed_tx, proceed_rx) = bounded(1);
+        let app = app(&operations, proceed_rx);
+
+        let conn = post("/").run_async(&app).await;
+        assert_status!(&conn, Status::Accepted);
+        assert_headers!(&conn, "location" => "/jobs/op_1");
+
+        assert_status!(get("/jobs/op_2").run_async(&app).await, Status::NotFound);
+        assert_status!(get("/jobs/nope").run_async(&app).await, Status::Accepted);
+        proceed_tx.close();
+    });
+}
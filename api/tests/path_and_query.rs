@@ -0,0 +1,88 @@
+use serde::Deserialize;
+use trillium::Handler;
+use trillium_api::*;
+use trillium_router::router;
+use trillium_testing::prelude::*;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Sort {
+    Newest,
+    Oldest,
+}
+
+#[derive(Deserialize, Debug)]
+struct Filters {
+    sort: Option<Sort>,
+    #[serde(default)]
+    limit: usize,
+}
+
+#[derive(Deserialize, Debug)]
+struct Repo {
+    org: String,
+    repo: String,
+}
+
+fn app() -> impl Handler {
+    router()
+        .get(
+            "/:org/:repo/issues",
+            api(
+                |_: &mut Conn, (Path(repo), Query(filters)): (Path<Repo>, Query<Filters>)| async move {
+                    format!(
+                        "{}/{} sort={:?} limit={}",
+                        repo.org, repo.repo, filters.sort, filters.limit
+                    )
+                },
+            ),
+        )
+        .get(
+            "/sort/:sort",
+            api(|_: &mut Conn, Path(sort): Path<Sort>| async move { format!("{sort:?}") }),
+        )
+        .get(
+            "/two/:a/:b",
+            api(|_: &mut Conn, Path(n): Path<u8>| async move { format!("{n}") }),
+        )
+        .get(
+            "/triple/:a/:b",
+            api(|_: &mut Conn, Path(t): Path<(u8, u8, u8)>| async move { format!("{t:?}") }),
+        )
+}
+
+#[test]
+fn path_and_query() {
+    assert_ok!(
+        get("/trillium-rs/trillium%20rs/issues?sort=oldest&limit=5").on(&app()),
+        "trillium-rs/trillium rs sort=Some(Oldest) limit=5"
+    );
+
+    assert_ok!(
+        get("/trillium-rs/trillium/issues").on(&app()),
+        "trillium-rs/trillium sort=None limit=0"
+    );
+
+    assert_response!(
+        get("/trillium-rs/trillium/issues?sort=sideways").on(&app()),
+        422,
+        r#"{"error":{"message":"sort: unknown variant `sideways`, expected `newest` or `oldest`","path":"sort","type":"parse_error"}}"#
+    );
+}
+
+#[test]
+fn single_param() {
+    assert_ok!(get("/sort/newest").on(&app()), "Newest");
+    assert_status!(get("/sort/upwards").on(&app()), 422);
+
+    assert_response!(
+        get("/two/1/2").on(&app()),
+        422,
+        r#"{"error":{"message":"invalid length 2, expected exactly one param","path":".","type":"parse_error"}}"#
+    );
+}
+
+#[test]
+fn tuple_length() {
+    assert_status!(get("/triple/1/2").on(&app()), 422);
+}
@@ -0,0 +1,69 @@
+use trillium::Handler;
+use trillium_api::*;
+use trillium_testing::prelude::*;
+
+fn app() -> impl Handler {
+    api(|_: &mut Conn, ()| async { Body(json!({ "ok": true })) })
+}
+
+#[test]
+fn errors_are_json_by_default() {
+    assert_response!(
+        get("/").with_request_header("accept", "image/png").on(&app()),
+        406,
+        r#"{"error":{"type":"failure_to_negotiate_content"}}"#,
+        "content-type" => "application/json"
+    );
+}
+
+#[test]
+fn problem_details_errors() {
+    let app = (ProblemDetailsErrors, app());
+    assert_response!(
+        get("/").with_request_header("accept", "image/png").on(&app),
+        406,
+        r#"{"type":"about:blank","title":"Not Acceptable","status":406,"detail":"No negotiated mime type"}"#,
+        "content-type" => "application/problem+json"
+    );
+}
+
+#[test]
+fn result_with_problem_details() {
+    let app = api(|conn: &mut Conn, ()| {
+        let id = conn.querystring().parse::<u64>();
+        async move {
+            id.map(|id| format!("widget {id}")).map_err(|_| {
+                ProblemDetails::new(404)
+                    .with_title("widget not found")
+                    .with_instance("/widgets/unknown")
+            })
+        }
+    });
+
+    assert_ok!(get("/?10").on(&app), "widget 10");
+    assert_response!(
+        get("/?ten").on(&app),
+        404,
+        r#"{"type":"about:blank","title":"widget not found","status":404,"instance":"/widgets/unknown"}"#,
+        "content-type" => "application/problem+json"
+    );
+}
+
+#[test]
+fn deserialize() {
+    let problem: ProblemDetails = serde_json::from_str(
+        r#"{"type":"https://example.com/probs/out-of-credit","title":"You do not have enough credit.","balance":30}"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        problem.problem_type(),
+        "https://example.com/probs/out-of-credit"
+    );
+    assert_eq!(problem.title(), Some("You do not have enough credit."));
+    assert_eq!(problem.status(), None);
+    assert_eq!(problem.extensions().get("balance"), Some(&json!(30)));
+
+    let problem: ProblemDetails = serde_json::from_str("{}").unwrap();
+    assert_eq!(problem.problem_type(), "about:blank");
+}
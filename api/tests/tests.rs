@@ -126,3 +126,20 @@ fn error_handler_works() {
         "my error format: UnsupportedMimeType { mime_type: \"application/x-www-form-urlencoded\" }"
     );
 }
+
+#[test]
+fn request_body_too_long() {
+    let handler = (
+        |conn: Conn| async move { conn.with_received_body_max_len(10) },
+        app_with_body(),
+    );
+
+    assert_response!(
+        post("/")
+            .with_request_header("content-type", "application/json")
+            .with_request_body(r#"{"string": "string", "numbers": [ 1, 2, 3]}"#)
+            .on(&handler),
+        413,
+        r#"{"error":{"message":"request body longer than 10 bytes","type":"payload_too_large"}}"#
+    );
+}
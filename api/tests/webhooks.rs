@@ -0,0 +1,136 @@
+use async_io::Timer;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use trillium::{Conn, Status};
+use trillium_api::*;
+use trillium_client::Client;
+use trillium_testing::{connector, prelude::*};
+
+async fn settle(webhooks: &Webhooks, id: DeliveryId) -> DeliveryStatus {
+    for _ in 0..200 {
+        let status = webhooks.delivery(id).unwrap();
+        if status.state() != DeliveryState::Pending {
+            return status;
+        }
+        Timer::after(Duration::from_millis(5)).await;
+    }
+    panic!("delivery {id} did not settle");
+}
+
+#[test]
+fn delivers_signed_events() {
+    block_on(async {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let receiver = {
+            let received = received.clone();
+            move |mut conn: Conn| {
+                let received = received.clone();
+                async move {
+                    let body = conn.request_body_string().await.unwrap();
+                    let header = |name| conn.request_headers().get_str(name).unwrap().to_string();
+                    received.lock().unwrap().push((
+                        header("webhook-id"),
+                        header("webhook-timestamp"),
+                        header("webhook-signature"),
+                        body,
+                    ));
+                    conn.with_status(204)
+                }
+            }
+        };
+
+        let webhooks = Webhooks::new(Client::new(connector(receiver)));
+        let endpoint = webhooks
+            .register("http://example.com/hooks", "shh")
+            .unwrap();
+
+        let ids = webhooks
+            .enqueue("widget.created", &json!({ "id": 10 }))
+            .unwrap();
+        assert_eq!(ids.len(), 1);
+
+        let status = settle(&webhooks, ids[0]).await;
+        assert_eq!(status.state(), DeliveryState::Delivered);
+        assert_eq!(status.attempts(), 1);
+        assert_eq!(status.last_status(), Some(204));
+        assert_eq!(status.endpoint(), endpoint);
+        assert_eq!(status.event_type(), "widget.created");
+
+        let (id, timestamp, signature, body) = received.lock().unwrap().pop().unwrap();
+        assert_eq!(id, ids[0].to_string());
+        assert_eq!(body, r#"{"data":{"id":10},"type":"widget.created"}"#);
+        assert_eq!(
+            signature,
+            webhook_signature(b"shh", &id, timestamp.parse().unwrap(), body.as_bytes())
+        );
+    });
+}
+
+#[test]
+fn retries_then_fails() {
+    block_on(async {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let receiver = {
+            let attempts = attempts.clone();
+            move |conn: Conn| {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                async move { conn.with_status(Status::ServiceUnavailable) }
+            }
+        };
+
+        let webhooks = Webhooks::new(Client::new(connector(receiver)))
+            .with_max_attempts(3)
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(2));
+        webhooks
+            .register("http://example.com/hooks", "shh")
+            .unwrap();
+
+        let ids = webhooks.enqueue("widget.deleted", &10).unwrap();
+        let status = settle(&webhooks, ids[0]).await;
+        assert_eq!(status.state(), DeliveryState::Failed);
+        assert_eq!(status.attempts(), 3);
+        assert_eq!(status.last_status(), Some(503));
+        assert!(status.last_error().is_some());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    });
+}
+
+#[test]
+fn retries_until_success() {
+    block_on(async {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let receiver = {
+            let attempts = attempts.clone();
+            move |conn: Conn| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if attempt == 0 {
+                        conn.with_status(500)
+                    } else {
+                        conn.ok("ok")
+                    }
+                }
+            }
+        };
+
+        let webhooks = Webhooks::new(Client::new(connector(receiver)))
+            .with_backoff(Duration::from_millis(1), Duration::from_millis(1));
+        webhooks.register("http://example.com/a", "a").unwrap();
+        let unregistered = webhooks.register("http://example.com/b", "b").unwrap();
+        assert!(webhooks.unregister(unregistered));
+        assert!(!webhooks.unregister(unregistered));
+
+        let ids = webhooks.enqueue("ping", &()).unwrap();
+        assert_eq!(ids.len(), 1);
+        let status = settle(&webhooks, ids[0]).await;
+        assert_eq!(status.state(), DeliveryState::Delivered);
+        assert_eq!(status.attempts(), 2);
+        assert_eq!(status.last_error(), None);
+        assert_eq!(webhooks.deliveries().len(), 1);
+    });
+}
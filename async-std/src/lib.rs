@@ -35,7 +35,10 @@ async fn main() {
 use std::future::Future;
 
 use trillium::Handler;
-pub use trillium_server_common::{Binding, CloneCounterObserver, Stopper};
+pub use trillium_server_common::{
+    AcmeChallenges, Binding, CloneCounterObserver, HttpsRedirect, Listener, ProxyProtocolAcceptor,
+    Stopper,
+};
 
 mod client;
 pub use client::ClientConfig;
@@ -41,6 +41,12 @@ impl Server for AsyncStdServer {
         self.0.local_addr().unwrap().into()
     }
 
+    fn try_clone(&self) -> Result<Self> {
+        use std::os::windows::io::AsSocket;
+        let tcp = std::net::TcpListener::from(self.0.as_socket().try_clone_to_owned()?);
+        Ok(tcp.into())
+    }
+
     fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         spawn(fut);
     }
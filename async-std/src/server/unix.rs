@@ -98,6 +98,16 @@ impl Server for AsyncStdServer {
         }
     }
 
+    fn try_clone(&self) -> Result<Self> {
+        use std::os::fd::AsFd;
+        match &self.0 {
+            Tcp(t) => Ok(std::net::TcpListener::from(t.as_fd().try_clone_to_owned()?).into()),
+            Unix(u) => {
+                Ok(std::os::unix::net::UnixListener::from(u.as_fd().try_clone_to_owned()?).into())
+            }
+        }
+    }
+
     fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         spawn(fut);
     }
@@ -1,7 +1,7 @@
 use async_std::net::{TcpStream, ToSocketAddrs};
 use std::{io::Result, net::SocketAddr};
 use trillium_macros::{AsyncRead, AsyncWrite};
-use trillium_server_common::{AsyncRead, AsyncWrite, Transport};
+use trillium_server_common::{AsyncRead, AsyncWrite, Transport, TransportKind};
 
 /// A transport newtype for async-std
 #[derive(Debug, Clone, AsyncRead, AsyncWrite)]
@@ -32,6 +32,14 @@ impl Transport for AsyncStdTransport<TcpStream> {
         self.0.peer_addr().map(Some)
     }
 
+    fn local_addr(&self) -> Result<Option<SocketAddr>> {
+        self.0.local_addr().map(Some)
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        TransportKind::Tcp
+    }
+
     fn set_ip_ttl(&mut self, ttl: u32) -> Result<()> {
         self.0.set_ttl(ttl)
     }
@@ -42,4 +50,12 @@ impl Transport for AsyncStdTransport<TcpStream> {
 }
 
 #[cfg(unix)]
-impl Transport for AsyncStdTransport<async_std::os::unix::net::UnixStream> {}
+impl Transport for AsyncStdTransport<async_std::os::unix::net::UnixStream> {
+    fn transport_kind(&self) -> TransportKind {
+        TransportKind::Unix
+    }
+
+    fn peer_credentials(&self) -> Result<Option<trillium_server_common::PeerCredentials>> {
+        trillium_server_common::unix_peer_credentials(&self.0)
+    }
+}
@@ -0,0 +1,256 @@
+use lamedh_runtime::Context;
+use serde_derive::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, error::Error, future::Future};
+use trillium::async_trait;
+
+/// The result of handling a non-http [`LambdaEvent`]. The value is
+/// serialized as the lambda's response, and an error fails the whole
+/// invocation.
+pub type EventResult = Result<Value, Box<dyn Error + Send + Sync>>;
+
+/**
+A non-http event received by a lambda
+
+Events that are not recognized as sqs or eventbridge events are
+provided as [`LambdaEvent::Other`].
+*/
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum LambdaEvent {
+    /// a batch of sqs messages
+    Sqs(SqsEvent),
+
+    /// an eventbridge event
+    EventBridge(EventBridgeEvent),
+
+    /// any other json event
+    Other(Value),
+}
+
+impl LambdaEvent {
+    pub(crate) fn from_value(value: Value) -> Self {
+        let is_sqs = value
+            .pointer("/Records/0/eventSource")
+            .is_some_and(|source| source == "aws:sqs");
+
+        if is_sqs {
+            if let Ok(event) = serde_json::from_value(value.clone()) {
+                return Self::Sqs(event);
+            }
+        } else if value.get("detail-type").is_some() && value.get("source").is_some() {
+            if let Ok(event) = serde_json::from_value(value.clone()) {
+                return Self::EventBridge(event);
+            }
+        }
+
+        Self::Other(value)
+    }
+}
+
+/// A batch of messages delivered from an sqs queue
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SqsEvent {
+    /// the messages in this batch
+    #[serde(rename = "Records")]
+    pub records: Vec<SqsMessage>,
+}
+
+/// A single message delivered from an sqs queue
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsMessage {
+    /// the unique id of this message, used to report it as failed with
+    /// [`SqsBatchResponse::with_failure`]
+    pub message_id: String,
+
+    /// the receipt handle of this message
+    #[serde(default)]
+    pub receipt_handle: String,
+
+    /// the body of this message
+    #[serde(default)]
+    pub body: String,
+
+    /// system attributes such as `ApproximateReceiveCount`
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+
+    /// custom message attributes
+    #[serde(default)]
+    pub message_attributes: HashMap<String, Value>,
+
+    /// the arn of the queue this message was sent to
+    #[serde(default, rename = "eventSourceARN")]
+    pub event_source_arn: String,
+
+    /// the region of the queue this message was sent to
+    #[serde(default)]
+    pub aws_region: String,
+}
+
+/**
+A response to an [`SqsEvent`] that reports individual messages as
+failed, so that only those messages are retried. This requires the
+event source mapping to be configured with
+`ReportBatchItemFailures`.
+*/
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SqsBatchResponse {
+    batch_item_failures: Vec<BatchItemFailure>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct BatchItemFailure {
+    item_identifier: String,
+}
+
+impl SqsBatchResponse {
+    /// Constructs a response that reports no failures
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reports the message with this id as failed
+    pub fn with_failure(mut self, message_id: impl Into<String>) -> Self {
+        self.batch_item_failures.push(BatchItemFailure {
+            item_identifier: message_id.into(),
+        });
+        self
+    }
+}
+
+impl From<SqsBatchResponse> for Value {
+    fn from(response: SqsBatchResponse) -> Self {
+        serde_json::to_value(response).unwrap_or_default()
+    }
+}
+
+/// An event delivered by an eventbridge rule
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct EventBridgeEvent {
+    /// the unique id of this event
+    pub id: String,
+
+    /// the kind of event, which together with the source identifies
+    /// the fields in the detail
+    #[serde(rename = "detail-type")]
+    pub detail_type: String,
+
+    /// the service or application that emitted this event
+    pub source: String,
+
+    /// the account this event was emitted in
+    #[serde(default)]
+    pub account: String,
+
+    /// the time this event was emitted, as an rfc 3339 timestamp
+    #[serde(default)]
+    pub time: String,
+
+    /// the region this event was emitted in
+    #[serde(default)]
+    pub region: String,
+
+    /// the arns of the resources involved in this event
+    #[serde(default)]
+    pub resources: Vec<String>,
+
+    /// the event-specific content of this event
+    #[serde(default)]
+    pub detail: Value,
+}
+
+/**
+Handles non-http events received by a lambda that also serves http
+requests
+
+This is implemented for async functions and closures that take a
+[`LambdaEvent`] and a [`lamedh_runtime::Context`] and return an
+[`EventResult`].
+*/
+#[async_trait]
+pub trait EventHandler: Send + Sync + 'static {
+    /// Handles a single event
+    async fn handle(&self, event: LambdaEvent, context: Context) -> EventResult;
+}
+
+#[async_trait]
+impl<F, Fut> EventHandler for F
+where
+    F: Fn(LambdaEvent, Context) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = EventResult> + Send + 'static,
+{
+    async fn handle(&self, event: LambdaEvent, context: Context) -> EventResult {
+        self(event, context).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn sqs() {
+        let event = LambdaEvent::from_value(json!({
+            "Records": [{
+                "messageId": "059f36b4-87a3-44ab-83d2-661975830a7d",
+                "receiptHandle": "AQEBwJnKyrHigUMZj6rYigCgxlaS3SLy0a",
+                "body": "hello",
+                "attributes": { "ApproximateReceiveCount": "1" },
+                "messageAttributes": {},
+                "md5OfBody": "5d41402abc4b2a76b9719d911017c592",
+                "eventSource": "aws:sqs",
+                "eventSourceARN": "arn:aws:sqs:us-east-2:123456789012:my-queue",
+                "awsRegion": "us-east-2"
+            }]
+        }));
+
+        let LambdaEvent::Sqs(SqsEvent { records }) = event else {
+            panic!("expected an sqs event, got {event:?}");
+        };
+        assert_eq!(records[0].body, "hello");
+        assert_eq!(
+            records[0].event_source_arn,
+            "arn:aws:sqs:us-east-2:123456789012:my-queue"
+        );
+
+        assert_eq!(
+            Value::from(SqsBatchResponse::new().with_failure(&records[0].message_id)),
+            json!({ "batchItemFailures": [{ "itemIdentifier": "059f36b4-87a3-44ab-83d2-661975830a7d" }] })
+        );
+    }
+
+    #[test]
+    fn eventbridge() {
+        let event = LambdaEvent::from_value(json!({
+            "version": "0",
+            "id": "53dc4d37-cffa-4f76-80c9-8b7d4a4d2eaa",
+            "detail-type": "Scheduled Event",
+            "source": "aws.events",
+            "account": "123456789012",
+            "time": "2015-10-08T16:53:06Z",
+            "region": "us-east-1",
+            "resources": ["arn:aws:events:us-east-1:123456789012:rule/my-rule"],
+            "detail": {}
+        }));
+
+        let LambdaEvent::EventBridge(event) = event else {
+            panic!("expected an eventbridge event, got {event:?}");
+        };
+        assert_eq!(event.detail_type, "Scheduled Event");
+        assert_eq!(event.source, "aws.events");
+    }
+
+    #[test]
+    fn other() {
+        let value = json!({ "anything": ["else"] });
+        assert_eq!(
+            LambdaEvent::from_value(value.clone()),
+            LambdaEvent::Other(value)
+        );
+    }
+}
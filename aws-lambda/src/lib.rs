@@ -17,10 +17,46 @@ trillium_aws_lambda::run(|conn: trillium::Conn| async move {
     conn.ok("hello lambda")
 });
 ```
+
+## Non-http events
+
+The same lambda can also receive events that are not http requests,
+such as sqs batches and eventbridge events, by providing an
+[`EventHandler`] to [`run_with_events`]. Http requests are still
+routed to the trillium handler.
+
+```rust,no_run
+use trillium_aws_lambda::{LambdaEvent, SqsBatchResponse};
+
+trillium_aws_lambda::run_with_events(
+    |conn: trillium::Conn| async move { conn.ok("hello lambda") },
+    |event: LambdaEvent, _context| async move {
+        match event {
+            LambdaEvent::Sqs(sqs) => {
+                let mut response = SqsBatchResponse::new();
+                for message in sqs.records {
+                    if message.body.is_empty() {
+                        response = response.with_failure(message.message_id);
+                    }
+                }
+                Ok(response.into())
+            }
+
+            _ => Ok(serde_json::Value::Null),
+        }
+    },
+);
+```
 */
 
 use lamedh_runtime::{Context, Handler as AwsHandler};
-use std::{future::Future, pin::Pin, sync::Arc};
+use std::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    io::{Error, ErrorKind},
+    pin::Pin,
+    sync::Arc,
+};
 use tokio::runtime;
 use trillium::{Conn, Handler};
 use trillium_http::{Conn as HttpConn, Synthetic};
@@ -29,21 +65,40 @@ mod context;
 pub use context::LambdaConnExt;
 use context::LambdaContext;
 
+mod event;
+pub use event::{
+    EventBridgeEvent, EventHandler, EventResult, LambdaEvent, SqsBatchResponse, SqsEvent,
+    SqsMessage,
+};
+
 mod request;
 use request::LambdaRequest;
 
 mod response;
 use response::{AlbMultiHeadersResponse, AlbResponse, LambdaResponse};
 
-#[derive(Debug)]
-struct HandlerWrapper<H>(Arc<H>);
+struct HandlerWrapper<H>(Arc<H>, Option<Arc<dyn EventHandler>>);
+
+impl<H: Debug> Debug for HandlerWrapper<H> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("HandlerWrapper")
+            .field(&self.0)
+            .field(&self.1.as_ref().map(|_| "EventHandler"))
+            .finish()
+    }
+}
 
 impl<H: Handler> AwsHandler<LambdaRequest, LambdaResponse> for HandlerWrapper<H> {
-    type Error = std::io::Error;
+    type Error = Error;
     type Fut = Pin<Box<dyn Future<Output = Result<LambdaResponse, Self::Error>> + Send + 'static>>;
 
     fn call(&mut self, request: LambdaRequest, context: Context) -> Self::Fut {
-        Box::pin(handler_fn(request, context, Arc::clone(&self.0)))
+        Box::pin(handler_fn(
+            request,
+            context,
+            Arc::clone(&self.0),
+            self.1.clone(),
+        ))
     }
 }
 
@@ -56,6 +111,7 @@ async fn handler_fn(
     request: LambdaRequest,
     context: Context,
     handler: Arc<impl Handler>,
+    events: Option<Arc<dyn EventHandler>>,
 ) -> std::io::Result<LambdaResponse> {
     match request {
         LambdaRequest::Alb(request) => {
@@ -73,6 +129,22 @@ async fn handler_fn(
                 AlbMultiHeadersResponse::from_conn(conn).await,
             ))
         }
+
+        LambdaRequest::Event(value) => {
+            let Some(events) = events else {
+                log::error!("received a non-http event without an event handler: {value}");
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "received a non-http event, but no event handler was provided",
+                ));
+            };
+
+            events
+                .handle(LambdaEvent::from_value(value), context)
+                .await
+                .map(LambdaResponse::Event)
+                .map_err(Error::other)
+        }
     }
 }
 /**
@@ -80,10 +152,26 @@ async fn handler_fn(
 
 This function will poll pending until the server shuts down.
 */
-pub async fn run_async(mut handler: impl Handler) {
+pub async fn run_async(handler: impl Handler) {
+    serve(handler, None).await
+}
+
+/**
+# Runs a trillium handler and an [`EventHandler`] on an already-running tokio runtime
+
+Http requests are routed to the trillium handler, and all other events
+are routed to the event handler.
+
+This function will poll pending until the server shuts down.
+*/
+pub async fn run_async_with_events(handler: impl Handler, event_handler: impl EventHandler) {
+    serve(handler, Some(Arc::new(event_handler))).await
+}
+
+async fn serve(mut handler: impl Handler, events: Option<Arc<dyn EventHandler>>) {
     let mut info = "aws lambda".into();
     handler.init(&mut info).await;
-    lamedh_runtime::run(HandlerWrapper(Arc::new(handler)))
+    lamedh_runtime::run(HandlerWrapper(Arc::new(handler), events))
         .await
         .unwrap()
 }
@@ -105,3 +193,23 @@ pub fn run(handler: impl Handler) {
         .unwrap()
         .block_on(run_async(handler));
 }
+
+/**
+# Runs a trillium handler and an [`EventHandler`] in a sync context
+
+Http requests are routed to the trillium handler, and all other events
+are routed to the event handler.
+
+This function creates a new tokio runtime and executes the handlers
+on it for aws lambda.
+
+This function will block the current thread until the server shuts
+down
+*/
+pub fn run_with_events(handler: impl Handler, event_handler: impl EventHandler) {
+    runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run_async_with_events(handler, event_handler));
+}
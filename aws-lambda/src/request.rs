@@ -11,7 +11,8 @@ mod test {
 
     #[test]
     fn test() {
-        let _t: serde_json::Result<super::LambdaRequest> = serde_json::from_str(JSON);
+        let request: super::LambdaRequest = serde_json::from_str(JSON).unwrap();
+        assert!(matches!(request, super::LambdaRequest::AlbMultiHeaders(_)));
     }
 }
 
@@ -105,4 +106,5 @@ impl AlbMultiHeadersRequest {
 pub(crate) enum LambdaRequest {
     Alb(AlbRequest),
     AlbMultiHeaders(AlbMultiHeadersRequest),
+    Event(serde_json::Value),
 }
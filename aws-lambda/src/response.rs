@@ -88,4 +88,5 @@ impl AlbResponse {
 pub(crate) enum LambdaResponse {
     Alb(AlbResponse),
     AlbMultiHeaders(AlbMultiHeadersResponse),
+    Event(serde_json::Value),
 }
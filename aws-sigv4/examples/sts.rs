@@ -0,0 +1,32 @@
+use trillium_aws_sigv4::{CredentialsChain, Signer};
+use trillium_client::{Client, RequestBuilder};
+use trillium_rustls::RustlsConfig;
+use trillium_smol::ClientConfig;
+
+pub fn main() -> Result<(), Box<dyn std::error::Error>> {
+    trillium_smol::async_global_executor::block_on(async {
+        env_logger::init();
+        // try `AWS_ACCESS_KEY_ID=... AWS_SECRET_ACCESS_KEY=... cargo run --example sts`
+        let client = Client::new(RustlsConfig::<ClientConfig>::default()).with_default_pool();
+        let signer = Signer::new(
+            "sts",
+            "us-east-1",
+            CredentialsChain::default_with_client(client.clone()),
+        );
+
+        let request = RequestBuilder::new(
+            "get",
+            "https://sts.amazonaws.com/?Action=GetCallerIdentity&Version=2011-06-15",
+        );
+        let response_body = signer
+            .execute(&client, request)
+            .await?
+            .success()
+            .map_err(|e| e.to_string())?
+            .response_body()
+            .await?;
+
+        println!("{response_body}");
+        Ok(())
+    })
+}
@@ -0,0 +1,289 @@
+use crate::{signing::days_from_civil, Error};
+use serde::Deserialize;
+use std::{
+    env,
+    error::Error as StdError,
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use trillium_client::{async_trait, Client, KnownHeaderName};
+
+type BoxedError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// aws credentials used to sign requests
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    expires_at: Option<SystemTime>,
+}
+
+impl Debug for Credentials {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"..")
+            .field("session_token", &self.session_token.as_ref().map(|_| ".."))
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl Credentials {
+    /// long-term credentials that do not expire
+    pub fn new(access_key_id: impl Into<String>, secret_access_key: impl Into<String>) -> Self {
+        Self {
+            access_key_id: access_key_id.into(),
+            secret_access_key: secret_access_key.into(),
+            session_token: None,
+            expires_at: None,
+        }
+    }
+
+    /// chainable setter for the session token of temporary credentials
+    pub fn with_session_token(mut self, session_token: impl Into<String>) -> Self {
+        self.session_token = Some(session_token.into());
+        self
+    }
+
+    /// chainable setter for the time at which temporary credentials
+    /// expire. credentials are refreshed from their
+    /// [`CredentialsProvider`] shortly before this time
+    pub fn with_expires_at(mut self, expires_at: SystemTime) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// the access key id
+    pub fn access_key_id(&self) -> &str {
+        &self.access_key_id
+    }
+
+    pub(crate) fn secret_access_key(&self) -> &str {
+        &self.secret_access_key
+    }
+
+    /// the session token, for temporary credentials
+    pub fn session_token(&self) -> Option<&str> {
+        self.session_token.as_deref()
+    }
+
+    /// the time at which these credentials expire, if they are temporary
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_at
+    }
+
+    /// whether these credentials expire before the provided time
+    pub(crate) fn expires_before(&self, time: SystemTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= time)
+    }
+}
+
+/**
+an async source of [`Credentials`]
+
+This is implemented for [`Credentials`] themselves, for the providers
+in this crate, and for any `Fn() -> impl Future<Output =
+Result<Credentials, E>>` where E can be converted into a boxed error,
+such as a `String`.
+*/
+#[async_trait]
+pub trait CredentialsProvider: Send + Sync + 'static {
+    /// fetch credentials
+    async fn credentials(&self) -> Result<Credentials, BoxedError>;
+}
+
+#[async_trait]
+impl CredentialsProvider for Credentials {
+    async fn credentials(&self) -> Result<Credentials, BoxedError> {
+        Ok(self.clone())
+    }
+}
+
+#[async_trait]
+impl<F, Fut, E> CredentialsProvider for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Credentials, E>> + Send,
+    E: Into<BoxedError>,
+{
+    async fn credentials(&self) -> Result<Credentials, BoxedError> {
+        self().await.map_err(Into::into)
+    }
+}
+
+/// reads credentials from the `AWS_ACCESS_KEY_ID`,
+/// `AWS_SECRET_ACCESS_KEY`, and `AWS_SESSION_TOKEN` environment variables
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EnvironmentCredentials;
+
+#[async_trait]
+impl CredentialsProvider for EnvironmentCredentials {
+    async fn credentials(&self) -> Result<Credentials, BoxedError> {
+        let access_key_id =
+            env::var("AWS_ACCESS_KEY_ID").map_err(|_| "AWS_ACCESS_KEY_ID is not set")?;
+        let secret_access_key =
+            env::var("AWS_SECRET_ACCESS_KEY").map_err(|_| "AWS_SECRET_ACCESS_KEY is not set")?;
+        let credentials = Credentials::new(access_key_id, secret_access_key);
+        Ok(match env::var("AWS_SESSION_TOKEN") {
+            Ok(session_token) if !session_token.is_empty() => {
+                credentials.with_session_token(session_token)
+            }
+            _ => credentials,
+        })
+    }
+}
+
+const CONTAINER_CREDENTIALS_HOST: &str = "http://169.254.170.2";
+
+/**
+fetches temporary credentials from the ecs or eks pod identity
+container credentials endpoint, as specified by the
+`AWS_CONTAINER_CREDENTIALS_RELATIVE_URI` or
+`AWS_CONTAINER_CREDENTIALS_FULL_URI` environment variables, with an
+authorization token from `AWS_CONTAINER_AUTHORIZATION_TOKEN` or
+`AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE`
+*/
+#[derive(Clone, Debug)]
+pub struct ContainerCredentials {
+    client: Client,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerCredentialsResponse {
+    access_key_id: String,
+    secret_access_key: String,
+    token: Option<String>,
+    expiration: Option<String>,
+}
+
+impl ContainerCredentials {
+    /// fetch credentials with this client
+    pub fn new(client: impl Into<Client>) -> Self {
+        Self {
+            client: client.into(),
+        }
+    }
+
+    fn url() -> Result<String, BoxedError> {
+        if let Ok(relative_uri) = env::var("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI") {
+            Ok(format!("{CONTAINER_CREDENTIALS_HOST}{relative_uri}"))
+        } else if let Ok(full_uri) = env::var("AWS_CONTAINER_CREDENTIALS_FULL_URI") {
+            Ok(full_uri)
+        } else {
+            Err("no container credentials uri is set".into())
+        }
+    }
+
+    fn authorization_token() -> Result<Option<String>, BoxedError> {
+        if let Ok(token) = env::var("AWS_CONTAINER_AUTHORIZATION_TOKEN") {
+            Ok(Some(token))
+        } else if let Ok(path) = env::var("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE") {
+            Ok(Some(std::fs::read_to_string(path)?.trim().to_string()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for ContainerCredentials {
+    async fn credentials(&self) -> Result<Credentials, BoxedError> {
+        let mut conn = self.client.get(Self::url()?.as_str());
+        if let Some(token) = Self::authorization_token()? {
+            conn = conn.with_request_header(KnownHeaderName::Authorization, token);
+        }
+
+        let response: ContainerCredentialsResponse = conn.await?.success()?.response_json().await?;
+
+        let mut credentials = Credentials::new(response.access_key_id, response.secret_access_key);
+        if let Some(token) = response.token {
+            credentials = credentials.with_session_token(token);
+        }
+        if let Some(expiration) = response.expiration {
+            credentials = credentials.with_expires_at(
+                parse_timestamp(&expiration)
+                    .ok_or_else(|| format!("could not parse expiration {expiration}"))?,
+            );
+        }
+        Ok(credentials)
+    }
+}
+
+/// parses an iso 8601 timestamp in utc, such as `2024-05-01T12:00:00Z`,
+/// ignoring any fractional seconds
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    let timestamp = timestamp.strip_suffix('Z')?;
+    let (date, time) = timestamp.split_once('T')?;
+    let mut date = date.splitn(3, '-').map(str::parse::<u32>);
+    let (year, month, day) = (date.next()?.ok()?, date.next()?.ok()?, date.next()?.ok()?);
+    let time = time.split('.').next()?;
+    let mut time = time.splitn(3, ':').map(str::parse::<u64>);
+    let (hour, minute, second) = (time.next()?.ok()?, time.next()?.ok()?, time.next()?.ok()?);
+
+    let days = u64::try_from(days_from_civil(year.into(), month, day)).ok()?;
+    let seconds = days * 86_400 + hour * 3600 + minute * 60 + second;
+    Some(UNIX_EPOCH + Duration::from_secs(seconds))
+}
+
+/**
+tries each [`CredentialsProvider`] in order, returning the first
+credentials that are successfully provided
+
+The default chain reads credentials from the environment with
+[`EnvironmentCredentials`], and then from the container credentials
+endpoint with [`ContainerCredentials`].
+*/
+pub struct CredentialsChain(Vec<Box<dyn CredentialsProvider>>);
+
+impl Debug for CredentialsChain {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CredentialsChain")
+            .field(&format_args!("{} providers", self.0.len()))
+            .finish()
+    }
+}
+
+impl CredentialsChain {
+    /// an empty chain
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// the default chain, using this client to fetch container credentials
+    pub fn default_with_client(client: impl Into<Client>) -> Self {
+        Self::new()
+            .with_provider(EnvironmentCredentials)
+            .with_provider(ContainerCredentials::new(client))
+    }
+
+    /// chainable method to add a provider to the end of this chain
+    pub fn with_provider(mut self, provider: impl CredentialsProvider) -> Self {
+        self.0.push(Box::new(provider));
+        self
+    }
+}
+
+impl Default for CredentialsChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for CredentialsChain {
+    async fn credentials(&self) -> Result<Credentials, BoxedError> {
+        let mut errors = Vec::with_capacity(self.0.len());
+        for provider in &self.0 {
+            match provider.credentials().await {
+                Ok(credentials) => return Ok(credentials),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        Err(Box::new(Error::NoCredentials(errors.join("; "))))
+    }
+}
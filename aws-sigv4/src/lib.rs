@@ -0,0 +1,214 @@
+/*!
+# AWS signature version 4 request signing for trillium-client
+
+[`Signer`] signs [`RequestBuilder`]s for a single aws service and
+region, using [`Credentials`] from a [`CredentialsProvider`].
+Credentials are cached and refreshed from the provider shortly before
+they expire, so temporary credentials from the environment or from a
+container credentials endpoint can be used for long-running processes.
+
+```
+use trillium_aws_sigv4::{Credentials, Signer};
+use trillium_client::{Client, RequestBuilder};
+use trillium_testing::prelude::*;
+
+let handler = |conn: trillium::Conn| async move {
+    let authorization = conn
+        .request_headers()
+        .get_str(trillium::KnownHeaderName::Authorization)
+        .unwrap_or_default()
+        .to_string();
+    conn.ok(authorization)
+};
+
+let client = Client::new(trillium_testing::connector(handler));
+let signer = Signer::new(
+    "sts",
+    "us-east-1",
+    Credentials::new("AKIDEXAMPLE", "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+);
+
+block_on(async move {
+    let request = RequestBuilder::new("get", "https://sts.amazonaws.com/?Action=GetCallerIdentity&Version=2011-06-15");
+    let mut conn = signer.execute(&client, request).await.unwrap();
+    let authorization = conn.response_body().read_string().await.unwrap();
+    assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+});
+```
+
+Request bodies that are set as static bytes or strings are included
+in the signature. Streaming bodies are signed as `UNSIGNED-PAYLOAD`,
+which some services do not accept.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod credentials;
+mod signing;
+
+pub use credentials::{
+    ContainerCredentials, Credentials, CredentialsChain, CredentialsProvider,
+    EnvironmentCredentials,
+};
+
+use async_lock::RwLock;
+use std::{
+    error::Error as StdError,
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use trillium_client::{Client, Conn, RequestBuilder};
+
+/// credentials are refreshed when they expire within this duration
+const REFRESH_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Reasons that a request could not be signed or executed
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// the credentials provider returned an error
+    #[error("could not fetch aws credentials: {0}")]
+    Credentials(#[source] Box<dyn StdError + Send + Sync + 'static>),
+
+    /// none of the providers in a [`CredentialsChain`] returned credentials
+    #[error("no aws credentials were found: {0}")]
+    NoCredentials(String),
+
+    /// the signed request could not be executed
+    #[error(transparent)]
+    Http(#[from] trillium_client::Error),
+}
+
+/**
+Signs requests for a single aws service and region
+
+This is cheap to clone, and clones share cached credentials.
+*/
+#[derive(Clone)]
+pub struct Signer(Arc<SignerInner>);
+
+struct SignerInner {
+    service: String,
+    region: String,
+    provider: Box<dyn CredentialsProvider>,
+    credentials: RwLock<Option<Credentials>>,
+}
+
+impl Debug for Signer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("service", &self.0.service)
+            .field("region", &self.0.region)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Signer {
+    /**
+    build a new signer for the provided service name (such as `"s3"`
+    or `"sts"`) and region (such as `"us-east-1"`), with credentials
+    from the provided [`CredentialsProvider`]
+    */
+    pub fn new(
+        service: impl Into<String>,
+        region: impl Into<String>,
+        provider: impl CredentialsProvider,
+    ) -> Self {
+        Self(Arc::new(SignerInner {
+            service: service.into(),
+            region: region.into(),
+            provider: Box::new(provider),
+            credentials: RwLock::new(None),
+        }))
+    }
+
+    /// the service name that requests are signed for
+    pub fn service(&self) -> &str {
+        &self.0.service
+    }
+
+    /// the region that requests are signed for
+    pub fn region(&self) -> &str {
+        &self.0.region
+    }
+
+    /// returns the cached credentials, fetching them from the provider
+    /// if there are none or if they expire soon
+    pub async fn credentials(&self) -> Result<Credentials, Error> {
+        let refresh_at = SystemTime::now() + REFRESH_WINDOW;
+        if let Some(credentials) = &*self.0.credentials.read().await {
+            if !credentials.expires_before(refresh_at) {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let mut cached = self.0.credentials.write().await;
+        // another request may have refreshed the credentials while this
+        // one waited for the lock
+        if let Some(credentials) = &*cached {
+            if !credentials.expires_before(refresh_at) {
+                return Ok(credentials.clone());
+            }
+        }
+
+        let credentials =
+            self.0
+                .provider
+                .credentials()
+                .await
+                .map_err(|e| match e.downcast::<Error>() {
+                    Ok(e) => *e,
+                    Err(e) => Error::Credentials(e),
+                })?;
+        *cached = Some(credentials.clone());
+        Ok(credentials)
+    }
+
+    /// discards the cached credentials, so that the next request
+    /// fetches them from the provider
+    pub async fn invalidate(&self) {
+        self.0.credentials.write().await.take();
+    }
+
+    /// sign this request with the current time
+    pub async fn sign(&self, request: RequestBuilder) -> Result<RequestBuilder, Error> {
+        self.sign_at(request, SystemTime::now()).await
+    }
+
+    /// sign this request as if it were sent at the provided time
+    pub async fn sign_at(
+        &self,
+        mut request: RequestBuilder,
+        time: SystemTime,
+    ) -> Result<RequestBuilder, Error> {
+        let credentials = self.credentials().await?;
+        signing::sign(
+            &mut request,
+            &credentials,
+            &self.0.service,
+            &self.0.region,
+            time,
+        );
+        Ok(request)
+    }
+
+    /**
+    sign this request and execute it with the provided client
+
+    The returned conn has been awaited, so the response status and
+    headers are available. Unsuccessful statuses are not an error.
+    */
+    pub async fn execute(&self, client: &Client, request: RequestBuilder) -> Result<Conn, Error> {
+        let request = self.sign(request).await?;
+        Ok(client.execute(request).await?)
+    }
+}
@@ -0,0 +1,224 @@
+use crate::Credentials;
+use hmac::{Hmac, Mac};
+use percent_encoding::percent_decode_str;
+use sha2::{Digest, Sha256};
+use std::{
+    fmt::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use trillium_client::{KnownHeaderName, RequestBuilder};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+
+/// adds the `x-amz-*` and `authorization` headers for these
+/// credentials to the request
+pub(crate) fn sign(
+    request: &mut RequestBuilder,
+    credentials: &Credentials,
+    service: &str,
+    region: &str,
+    time: SystemTime,
+) {
+    let (date, amz_date) = timestamps(time);
+    let payload_hash = payload_hash(request);
+
+    let headers = request.request_headers_mut();
+    headers.remove(KnownHeaderName::Authorization);
+    headers.insert("x-amz-date", amz_date.clone());
+    if service == "s3" {
+        headers.insert("x-amz-content-sha256", payload_hash.clone());
+    }
+    match credentials.session_token() {
+        Some(session_token) => headers.insert("x-amz-security-token", session_token.to_string()),
+        None => {
+            headers.remove("x-amz-security-token");
+        }
+    }
+
+    let (canonical_headers, signed_headers) = canonical_headers(request);
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{canonical_headers}\n{signed_headers}\n{payload_hash}",
+        request.method(),
+        canonical_uri(request, service),
+        canonical_query(request),
+    );
+    log::trace!("canonical request:\n{canonical_request}");
+
+    let scope = format!("{date}/{region}/{service}/aws4_request");
+    let string_to_sign = format!(
+        "{ALGORITHM}\n{amz_date}\n{scope}\n{}",
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = [date.as_str(), region, service, "aws4_request"]
+        .into_iter()
+        .fold(
+            format!("AWS4{}", credentials.secret_access_key()).into_bytes(),
+            |key, part| hmac(&key, part.as_bytes()),
+        );
+    let signature = hex(&hmac(&signing_key, string_to_sign.as_bytes()));
+
+    request.request_headers_mut().insert(
+        KnownHeaderName::Authorization,
+        format!(
+            "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            credentials.access_key_id()
+        ),
+    );
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
+
+fn payload_hash(request: &RequestBuilder) -> String {
+    match request.request_body() {
+        None => hex(&Sha256::digest(b"")),
+        Some(body) => match body.static_bytes() {
+            Some(bytes) => hex(&Sha256::digest(bytes)),
+            None => String::from(UNSIGNED_PAYLOAD),
+        },
+    }
+}
+
+// percent-encodes everything except the unreserved characters, as
+// specified for sigv4
+fn uri_encode(input: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char);
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => {
+                let _ = write!(encoded, "%{byte:02X}");
+            }
+        }
+    }
+    encoded
+}
+
+fn canonical_uri(request: &RequestBuilder, service: &str) -> String {
+    let path = request.url().path();
+    let decoded = percent_decode_str(path).decode_utf8_lossy();
+    let encoded = uri_encode(&decoded, false);
+    if service == "s3" {
+        encoded
+    } else {
+        // every service other than s3 expects each path segment to
+        // be encoded twice
+        uri_encode(&encoded, false)
+    }
+}
+
+fn canonical_query(request: &RequestBuilder) -> String {
+    let mut pairs = request
+        .url()
+        .query_pairs()
+        .map(|(name, value)| (uri_encode(&name, true), uri_encode(&value, true)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn host(request: &RequestBuilder) -> String {
+    let url = request.url();
+    let host = url.host_str().unwrap_or_default();
+    match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    }
+}
+
+fn canonical_headers(request: &RequestBuilder) -> (String, String) {
+    let mut headers = request
+        .request_headers()
+        .iter()
+        .filter(|(name, _)| !name.as_ref().eq_ignore_ascii_case("host"))
+        .map(|(name, values)| {
+            let values = values
+                .iter()
+                .map(|value| {
+                    String::from_utf8_lossy(value.as_ref())
+                        .split_whitespace()
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect::<Vec<_>>()
+                .join(",");
+            (name.as_ref().to_ascii_lowercase(), values)
+        })
+        .chain([(String::from("host"), host(request))])
+        .collect::<Vec<_>>();
+    headers.sort();
+
+    let canonical_headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}:{value}\n"))
+        .collect::<String>();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+    (canonical_headers, signed_headers)
+}
+
+/// returns the date (`YYYYMMDD`) and timestamp (`YYYYMMDDTHHMMSSZ`)
+/// for this time in utc
+pub(crate) fn timestamps(time: SystemTime) -> (String, String) {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let seconds_of_day = seconds % 86_400;
+    let date = format!("{year:04}{month:02}{day:02}");
+    let amz_date = format!(
+        "{date}T{:02}{:02}{:02}Z",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    );
+    (date, amz_date)
+}
+
+// https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+// https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
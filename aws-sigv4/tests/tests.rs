@@ -0,0 +1,186 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use test_harness::test;
+use trillium::{Conn, KnownHeaderName, Status};
+use trillium_aws_sigv4::{Credentials, CredentialsChain, Error, Signer};
+use trillium_client::{Client, RequestBuilder};
+use trillium_testing::{connector, harness};
+
+const ACCESS_KEY_ID: &str = "AKIDEXAMPLE";
+const SECRET_ACCESS_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+
+// 20150830T123600Z, the time used by the aws sigv4 test suite
+fn test_suite_time() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_440_938_160)
+}
+
+fn authorization(request: &RequestBuilder) -> &str {
+    request
+        .request_headers()
+        .get_str(KnownHeaderName::Authorization)
+        .unwrap()
+}
+
+#[test(harness)]
+async fn get_vanilla() {
+    let signer = Signer::new(
+        "service",
+        "us-east-1",
+        Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY),
+    );
+    let request = RequestBuilder::new("get", "https://example.amazonaws.com/");
+    let request = signer.sign_at(request, test_suite_time()).await.unwrap();
+
+    assert_eq!(
+        request.request_headers().get_str("x-amz-date"),
+        Some("20150830T123600Z")
+    );
+    assert_eq!(
+        authorization(&request),
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+         SignedHeaders=host;x-amz-date, \
+         Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+    );
+}
+
+#[test(harness)]
+async fn get_vanilla_query_order_key() {
+    let signer = Signer::new(
+        "service",
+        "us-east-1",
+        Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY),
+    );
+    let request = RequestBuilder::new(
+        "get",
+        "https://example.amazonaws.com/?Param2=value2&Param1=value1",
+    );
+    let request = signer.sign_at(request, test_suite_time()).await.unwrap();
+
+    assert_eq!(
+        authorization(&request),
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+         SignedHeaders=host;x-amz-date, \
+         Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+    );
+}
+
+#[test(harness)]
+async fn session_token() {
+    let signer = Signer::new(
+        "service",
+        "us-east-1",
+        Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY).with_session_token("session"),
+    );
+    let request = RequestBuilder::new("get", "https://example.amazonaws.com/");
+    let request = signer.sign_at(request, test_suite_time()).await.unwrap();
+
+    assert_eq!(
+        request.request_headers().get_str("x-amz-security-token"),
+        Some("session")
+    );
+    assert!(authorization(&request).contains("SignedHeaders=host;x-amz-date;x-amz-security-token,"));
+}
+
+#[test(harness)]
+async fn s3_signs_payload_hash() {
+    let signer = Signer::new(
+        "s3",
+        "us-east-1",
+        Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY),
+    );
+    let request = RequestBuilder::new("put", "https://bucket.s3.amazonaws.com/key").with_body("");
+    let request = signer.sign_at(request, test_suite_time()).await.unwrap();
+
+    assert_eq!(
+        request.request_headers().get_str("x-amz-content-sha256"),
+        Some("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+    );
+    assert!(authorization(&request).contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,"));
+}
+
+#[test(harness)]
+async fn execute() {
+    let handler = |conn: Conn| async move {
+        let authorization = conn
+            .request_headers()
+            .get_str(KnownHeaderName::Authorization)
+            .unwrap_or_default()
+            .to_string();
+        conn.ok(authorization)
+    };
+
+    let client = Client::new(connector(handler));
+    let signer = Signer::new(
+        "sts",
+        "us-east-1",
+        Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY),
+    );
+    let request = RequestBuilder::new("post", "https://sts.amazonaws.com/")
+        .with_body("Action=GetCallerIdentity&Version=2011-06-15");
+    let mut conn = signer.execute(&client, request).await.unwrap();
+
+    assert_eq!(conn.status(), Some(Status::Ok));
+    let authorization = conn.response_body().read_string().await.unwrap();
+    assert!(authorization.starts_with("AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/"));
+    assert!(authorization.contains("/us-east-1/sts/aws4_request, SignedHeaders=host;x-amz-date,"));
+}
+
+#[test(harness)]
+async fn refreshes_expiring_credentials() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let provider = {
+        let fetches = Arc::clone(&fetches);
+        move || {
+            let fetches = Arc::clone(&fetches);
+            async move {
+                let n = fetches.fetch_add(1, Ordering::SeqCst);
+                // the first credentials expire within the refresh window
+                let expires_at = if n == 0 {
+                    SystemTime::now() + Duration::from_secs(60)
+                } else {
+                    SystemTime::now() + Duration::from_secs(60 * 60)
+                };
+                Ok::<_, String>(
+                    Credentials::new(format!("AKID{n}"), SECRET_ACCESS_KEY)
+                        .with_session_token("session")
+                        .with_expires_at(expires_at),
+                )
+            }
+        }
+    };
+
+    let signer = Signer::new("service", "us-east-1", provider);
+    assert_eq!(signer.credentials().await.unwrap().access_key_id(), "AKID0");
+    assert_eq!(signer.credentials().await.unwrap().access_key_id(), "AKID1");
+    assert_eq!(signer.credentials().await.unwrap().access_key_id(), "AKID1");
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    signer.invalidate().await;
+    assert_eq!(signer.credentials().await.unwrap().access_key_id(), "AKID2");
+}
+
+#[test(harness)]
+async fn credentials_chain() {
+    let chain = CredentialsChain::new()
+        .with_provider(|| async { Err::<Credentials, _>("first provider has no credentials") })
+        .with_provider(Credentials::new(ACCESS_KEY_ID, SECRET_ACCESS_KEY));
+    let signer = Signer::new("service", "us-east-1", chain);
+    assert_eq!(
+        signer.credentials().await.unwrap().access_key_id(),
+        ACCESS_KEY_ID
+    );
+
+    let chain = CredentialsChain::new()
+        .with_provider(|| async { Err::<Credentials, _>("first provider has no credentials") });
+    let signer = Signer::new("service", "us-east-1", chain);
+    let request = RequestBuilder::new("get", "https://example.amazonaws.com/");
+    assert!(matches!(
+        signer.sign(request).await,
+        Err(Error::NoCredentials(message)) if message == "first provider has no credentials"
+    ));
+}
@@ -94,3 +94,79 @@ fn edge_cases() {
         401
     );
 }
+
+#[test]
+fn multiple_credentials() {
+    let handler = (
+        BasicAuth::new("jacob", "7r1ll1um").with_credentials("gunter", "quack"),
+        |conn: trillium::Conn| async move {
+            let username = conn.state::<Credentials>().unwrap().username().to_string();
+            conn.ok(username)
+        },
+    );
+
+    for (username, password) in [("jacob", "7r1ll1um"), ("gunter", "quack")] {
+        assert_ok!(
+            get("/")
+                .with_request_header(
+                    "Authorization",
+                    format!("Basic {}", BASE64.encode(format!("{username}:{password}")))
+                )
+                .on(&handler),
+            username
+        );
+    }
+
+    assert_status!(
+        get("/")
+            .with_request_header(
+                "Authorization",
+                format!("Basic {}", BASE64.encode("jacob:quack"))
+            )
+            .on(&handler),
+        401
+    );
+}
+
+#[test]
+fn async_verifier() {
+    let handler = (
+        BasicAuth::from_verifier(|credentials: Credentials| async move {
+            credentials.password() == format!("{}-password", credentials.username())
+        })
+        .with_credentials("static", "user"),
+        "ok",
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header(
+                "Authorization",
+                format!("Basic {}", BASE64.encode("anyone:anyone-password"))
+            )
+            .on(&handler),
+        "ok"
+    );
+
+    assert_ok!(
+        get("/")
+            .with_request_header(
+                "Authorization",
+                format!("basic {}", BASE64.encode("static:user"))
+            )
+            .on(&handler),
+        "ok"
+    );
+
+    assert_response!(
+        get("/")
+            .with_request_header(
+                "Authorization",
+                format!("Basic {}", BASE64.encode("anyone:wrong"))
+            )
+            .on(&handler),
+        401, "", "www-authenticate" => "Basic"
+    );
+
+    assert_status!(get("/").on(&handler), 401);
+}
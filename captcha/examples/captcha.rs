@@ -0,0 +1,13 @@
+use trillium::Conn;
+use trillium_captcha::Captcha;
+
+fn main() {
+    env_logger::init();
+    // try `curl -i http://localhost:8080` and then solve the
+    // challenge with `trillium_captcha::solve`
+    trillium_smol::run((
+        trillium_logger::logger(),
+        Captcha::proof_of_work(16),
+        |conn: Conn| async move { conn.ok("hello, human (or diligent robot)") },
+    ));
+}
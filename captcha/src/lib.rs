@@ -0,0 +1,390 @@
+/*!
+# Captcha and proof-of-work challenges for trillium.rs
+
+[`Captcha`] gates requests behind a challenge. Requests that have not
+passed a challenge are halted with `403 Forbidden`, and requests
+that present a valid solution are allowed through and receive a
+signed cookie that allows subsequent requests through without
+another challenge until it expires.
+
+Two kinds of challenge are supported:
+
+* A stateless proof of work, with [`Captcha::proof_of_work`]. The
+  challenge is sent in a `captcha-challenge` response header, and
+  the client must find a nonce that makes its sha-256 digest begin
+  with a configured number of zero bits, sending the result in a
+  `captcha-solution` request header (see [`solve`]). This requires
+  no third party and no user interaction, and makes automated abuse
+  of an api proportionally more expensive.
+* A captcha widget such as [Cloudflare
+  Turnstile](https://developers.cloudflare.com/turnstile/) or
+  [hCaptcha](https://www.hcaptcha.com/), with [`Captcha::turnstile`],
+  [`Captcha::hcaptcha`], or [`Captcha::siteverify`]. The widget's
+  response token must be sent in a `captcha-token` request header,
+  and is verified with the provider using a
+  [`trillium_client::Client`].
+
+To only gate some routes, place the Captcha inside of a router
+route or other handler that only runs for those routes. Individual
+requests can also be exempted with [`Captcha::with_exemption`],
+for example to allow requests that have already been authenticated
+or that have not been marked as suspicious by an earlier handler.
+
+```
+use trillium::Conn;
+use trillium_captcha::{solve, Captcha};
+use trillium_testing::prelude::*;
+
+let handler = (
+    Captcha::proof_of_work(8).with_exemption(|conn: &Conn| conn.method() == trillium::Method::Get),
+    |conn: Conn| async move { conn.ok("created") },
+);
+
+assert_ok!(get("/").on(&handler), "created");
+
+let conn = post("/").on(&handler);
+assert_status!(&conn, 403);
+let challenge = conn.response_headers().get_str("captcha-challenge").unwrap();
+
+let solution = solve(challenge).unwrap();
+let mut conn = post("/")
+    .with_request_header("captcha-solution", solution)
+    .on(&handler);
+assert_ok!(&mut conn, "created");
+
+let cookie = conn.response_headers().get_str("set-cookie").unwrap();
+let cookie = cookie.split_once(';').unwrap().0.to_string();
+assert_ok!(
+    post("/").with_request_header("cookie", cookie).on(&handler),
+    "created"
+);
+```
+
+## Signing keys
+
+Challenges and pass cookies are signed, so no state is stored on the
+server. By default, a random signing key is generated when the
+Captcha is constructed. When running more than one server process,
+or to allow pass cookies to remain valid across restarts, provide a
+shared key with [`Captcha::with_signing_key`].
+
+Proof of work challenges are not single use, so a solved challenge
+can be replayed until it expires. Keep the challenge duration short
+with [`Captcha::with_challenge_duration`].
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod proof_of_work;
+pub use proof_of_work::solve;
+use proof_of_work::ProofOfWork;
+
+mod verifier;
+use verifier::Verifier;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use trillium::{
+    async_trait, Conn, Handler, Info,
+    KnownHeaderName::{Cookie, SetCookie},
+    Status,
+};
+use trillium_client::{Client, Url};
+
+const CHALLENGE_HEADER: &str = "captcha-challenge";
+const SOLUTION_HEADER: &str = "captcha-solution";
+const TOKEN_HEADER: &str = "captcha-token";
+
+type Exemption = Box<dyn Fn(&Conn) -> bool + Send + Sync + 'static>;
+
+#[derive(Debug)]
+enum Challenge {
+    ProofOfWork(ProofOfWork),
+    Verifier(Verifier),
+}
+
+#[derive(Clone)]
+pub(crate) struct SigningKey(Vec<u8>);
+
+impl SigningKey {
+    fn mac(&self, purpose: &str, payload: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(&self.0).expect("hmac accepts any key length");
+        mac.update(purpose.as_bytes());
+        mac.update(b".");
+        mac.update(payload.as_bytes());
+        mac
+    }
+
+    pub(crate) fn sign(&self, purpose: &str, payload: &str) -> String {
+        URL_SAFE_NO_PAD.encode(self.mac(purpose, payload).finalize().into_bytes())
+    }
+
+    pub(crate) fn verify(&self, purpose: &str, payload: &str, signature: &str) -> bool {
+        URL_SAFE_NO_PAD
+            .decode(signature)
+            .is_ok_and(|signature| self.mac(purpose, payload).verify_slice(&signature).is_ok())
+    }
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+pub(crate) fn random_token() -> String {
+    let mut bytes = [0; 16];
+    getrandom::getrandom(&mut bytes).expect("could not generate random bytes");
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/**
+Trillium handler that gates requests behind a captcha or proof-of-work
+challenge
+
+See crate-level docs for an explanation
+*/
+pub struct Captcha {
+    challenge: Challenge,
+    key: SigningKey,
+    pass_duration: Duration,
+    cookie_name: Cow<'static, str>,
+    exemptions: Vec<Exemption>,
+    response: Box<dyn Handler>,
+}
+
+impl Debug for Captcha {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Captcha")
+            .field("challenge", &self.challenge)
+            .field("pass_duration", &self.pass_duration)
+            .field("cookie_name", &self.cookie_name)
+            .field("exemptions", &self.exemptions.len())
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+impl Captcha {
+    fn new(challenge: Challenge) -> Self {
+        let mut key = vec![0; 32];
+        getrandom::getrandom(&mut key).expect("could not generate random bytes");
+        Self {
+            challenge,
+            key: SigningKey(key),
+            pass_duration: Duration::from_secs(60 * 60),
+            cookie_name: Cow::Borrowed("trillium.captcha"),
+            exemptions: Vec::new(),
+            response: Box::new(Status::Forbidden),
+        }
+    }
+
+    /**
+    Constructs a new Captcha that issues proof-of-work challenges
+    requiring `difficulty` leading zero bits. Each additional bit
+    doubles the expected work for the client; 20 takes on the order
+    of a second in a browser.
+    */
+    pub fn proof_of_work(difficulty: u8) -> Self {
+        Self::new(Challenge::ProofOfWork(ProofOfWork {
+            difficulty,
+            challenge_duration: Duration::from_secs(5 * 60),
+        }))
+    }
+
+    /**
+    Constructs a new Captcha that verifies [Cloudflare
+    Turnstile](https://developers.cloudflare.com/turnstile/) tokens
+    with the provided secret key
+
+    ```
+    use trillium_captcha::Captcha;
+    use trillium_client::Client;
+    let handler = Captcha::turnstile(
+        Client::new(trillium_smol::ClientConfig::default()),
+        "turnstile-secret-key",
+    );
+    ```
+    */
+    pub fn turnstile(client: Client, secret: impl Into<String>) -> Self {
+        Self::new(Challenge::Verifier(Verifier::turnstile(
+            client,
+            secret.into(),
+        )))
+    }
+
+    /// Constructs a new Captcha that verifies
+    /// [hCaptcha](https://www.hcaptcha.com/) tokens with the provided
+    /// secret key
+    pub fn hcaptcha(client: Client, secret: impl Into<String>) -> Self {
+        Self::new(Challenge::Verifier(Verifier::hcaptcha(
+            client,
+            secret.into(),
+        )))
+    }
+
+    /// Constructs a new Captcha that verifies tokens with any
+    /// provider that implements the same siteverify protocol as
+    /// Turnstile and hCaptcha, such as reCAPTCHA
+    pub fn siteverify(client: Client, url: Url, secret: impl Into<String>) -> Self {
+        Self::new(Challenge::Verifier(Verifier::new(
+            client,
+            url,
+            secret.into(),
+        )))
+    }
+
+    /// Sign challenges and pass cookies with the provided key
+    /// instead of a randomly generated one. This should be at least
+    /// 32 bytes of random data.
+    pub fn with_signing_key(mut self, key: impl AsRef<[u8]>) -> Self {
+        self.key = SigningKey(key.as_ref().to_vec());
+        self
+    }
+
+    /// Sets how long a client is allowed through after passing a
+    /// challenge. The default is one hour.
+    pub fn with_pass_duration(mut self, pass_duration: Duration) -> Self {
+        self.pass_duration = pass_duration;
+        self
+    }
+
+    /// Sets how long a proof-of-work challenge may be solved for
+    /// after it is issued, rounded down to whole seconds. The default
+    /// is five minutes. This has no effect on captcha widget tokens,
+    /// which expire as determined by the provider.
+    pub fn with_challenge_duration(mut self, challenge_duration: Duration) -> Self {
+        if let Challenge::ProofOfWork(proof_of_work) = &mut self.challenge {
+            proof_of_work.challenge_duration = challenge_duration;
+        }
+        self
+    }
+
+    /// Sets the name of the pass cookie. The default is
+    /// `trillium.captcha`
+    pub fn with_cookie_name(mut self, cookie_name: impl Into<Cow<'static, str>>) -> Self {
+        self.cookie_name = cookie_name.into();
+        self
+    }
+
+    /**
+    Allow any request for which the provided function returns true
+    through without a challenge. This may be called more than once,
+    and a request is exempt if any of the functions return true.
+
+    ```
+    use trillium::Conn;
+    use trillium_captcha::Captcha;
+
+    #[derive(Clone, Copy)]
+    struct Suspicious;
+
+    // an earlier handler marks some conns as suspicious
+    let handler = Captcha::proof_of_work(16)
+        .with_exemption(|conn: &Conn| conn.state::<Suspicious>().is_none());
+    ```
+    */
+    pub fn with_exemption<F>(mut self, exemption: F) -> Self
+    where
+        F: Fn(&Conn) -> bool + Send + Sync + 'static,
+    {
+        self.exemptions.push(Box::new(exemption));
+        self
+    }
+
+    /**
+    Replaces the default `403 Forbidden` response with a custom
+    handler, such as a page that renders a captcha widget. When using
+    proof of work, the `captcha-challenge` header will already be set
+    when this handler is run, and the conn will be halted after it is
+    run.
+    */
+    pub fn with_response(mut self, response: impl Handler) -> Self {
+        self.response = Box::new(response);
+        self
+    }
+
+    fn has_pass(&self, conn: &Conn) -> bool {
+        let Some(cookies) = conn.request_headers().get_str(Cookie) else {
+            return false;
+        };
+
+        cookies
+            .split(';')
+            .filter_map(|cookie| cookie.trim().split_once('='))
+            .filter(|(name, _)| *name == self.cookie_name)
+            .filter_map(|(_, value)| value.split_once('.'))
+            .any(|(expires, signature)| {
+                expires.parse::<u64>().is_ok_and(|expires| expires > now())
+                    && self.key.verify("pass", expires, signature)
+            })
+    }
+
+    async fn verify(&self, conn: &Conn) -> bool {
+        match &self.challenge {
+            Challenge::ProofOfWork(proof_of_work) => conn
+                .request_headers()
+                .get_str(SOLUTION_HEADER)
+                .is_some_and(|solution| proof_of_work.verify(&self.key, solution)),
+
+            Challenge::Verifier(verifier) => match conn.request_headers().get_str(TOKEN_HEADER) {
+                Some(token) => verifier.verify(token.trim(), conn.peer_ip()).await,
+                None => false,
+            },
+        }
+    }
+
+    fn pass_cookie(&self, secure: bool) -> String {
+        let expires = (now() + self.pass_duration.as_secs()).to_string();
+        let signature = self.key.sign("pass", &expires);
+        format!(
+            "{}={expires}.{signature}; Path=/; Max-Age={}; HttpOnly; SameSite=Lax{}",
+            self.cookie_name,
+            self.pass_duration.as_secs(),
+            if secure { "; Secure" } else { "" }
+        )
+    }
+}
+
+#[async_trait]
+impl Handler for Captcha {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        if self.exemptions.iter().any(|exemption| exemption(&conn)) || self.has_pass(&conn) {
+            return conn;
+        }
+
+        if self.verify(&conn).await {
+            let cookie = self.pass_cookie(conn.is_secure());
+            conn.response_headers_mut().append(SetCookie, cookie);
+            return conn;
+        }
+
+        log::debug!("challenging {} {}", conn.method(), conn.path());
+
+        if let Challenge::ProofOfWork(proof_of_work) = &self.challenge {
+            conn.response_headers_mut()
+                .insert(CHALLENGE_HEADER, proof_of_work.issue(&self.key));
+        }
+
+        self.response.run(conn).await.halt()
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        self.response.init(info).await;
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{now, SigningKey};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// Issues and verifies stateless proof-of-work challenges
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ProofOfWork {
+    pub(crate) difficulty: u8,
+    pub(crate) challenge_duration: Duration,
+}
+
+impl ProofOfWork {
+    /// a challenge has the form `{difficulty}.{expires}.{random}.{signature}`
+    pub(crate) fn issue(&self, key: &SigningKey) -> String {
+        let expires = now() + self.challenge_duration.as_secs();
+        let payload = format!("{}.{expires}.{}", self.difficulty, crate::random_token());
+        let signature = key.sign("challenge", &payload);
+        format!("{payload}.{signature}")
+    }
+
+    /// a solution has the form `{challenge}:{nonce}`
+    pub(crate) fn verify(&self, key: &SigningKey, solution: &str) -> bool {
+        let Some((challenge, nonce)) = solution.trim().rsplit_once(':') else {
+            return false;
+        };
+
+        let Some((payload, signature)) = challenge.rsplit_once('.') else {
+            return false;
+        };
+
+        if !key.verify("challenge", payload, signature) {
+            log::debug!("proof of work challenge signature did not match");
+            return false;
+        }
+
+        let mut parts = payload.split('.');
+        let difficulty = parts.next().and_then(|d| d.parse::<u8>().ok());
+        let expires = parts.next().and_then(|e| e.parse::<u64>().ok());
+
+        match (difficulty, expires) {
+            (Some(difficulty), Some(expires))
+                if difficulty >= self.difficulty && expires > now() =>
+            {
+                leading_zero_bits(challenge, nonce) >= u32::from(difficulty)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn leading_zero_bits(challenge: &str, nonce: &str) -> u32 {
+    let digest = Sha256::new()
+        .chain_update(challenge)
+        .chain_update(":")
+        .chain_update(nonce)
+        .finalize();
+
+    let mut zeros = 0;
+    for byte in digest {
+        zeros += byte.leading_zeros();
+        if byte != 0 {
+            break;
+        }
+    }
+    zeros
+}
+
+/**
+Solves a proof-of-work challenge as issued in the `captcha-challenge`
+response header, returning the value to send in the
+`captcha-solution` request header.
+
+This is primarily useful for tests and for rust clients of a
+[`Captcha`](crate::Captcha)-protected api. Browser clients will need
+an equivalent javascript implementation: find a decimal nonce such
+that the sha-256 digest of `{challenge}:{nonce}` begins with at least
+`difficulty` zero bits, where `difficulty` is the portion of the
+challenge before the first `.`, and send `{challenge}:{nonce}`.
+
+Returns None if the challenge is not well formed.
+*/
+pub fn solve(challenge: &str) -> Option<String> {
+    let difficulty = challenge.split_once('.')?.0.parse::<u32>().ok()?;
+    (0u64..)
+        .map(|nonce| nonce.to_string())
+        .find(|nonce| leading_zero_bits(challenge, nonce) >= difficulty)
+        .map(|nonce| format!("{challenge}:{nonce}"))
+}
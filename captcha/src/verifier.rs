@@ -0,0 +1,88 @@
+use serde::Deserialize;
+use std::{
+    error::Error,
+    fmt::{self, Debug, Formatter},
+    net::IpAddr,
+};
+use trillium::KnownHeaderName::ContentType;
+use trillium_client::{Client, Url};
+use url::form_urlencoded::Serializer;
+
+const TURNSTILE_URL: &str = "https://challenges.cloudflare.com/turnstile/v0/siteverify";
+const HCAPTCHA_URL: &str = "https://api.hcaptcha.com/siteverify";
+
+/// Verifies widget response tokens with a captcha provider's
+/// siteverify endpoint
+pub(crate) struct Verifier {
+    client: Client,
+    url: Url,
+    secret: String,
+}
+
+impl Debug for Verifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Verifier")
+            .field("client", &self.client)
+            .field("url", &self.url)
+            .field("secret", &"..")
+            .finish()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct SiteverifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+impl Verifier {
+    pub(crate) fn turnstile(client: Client, secret: String) -> Self {
+        Self::new(client, TURNSTILE_URL.parse().unwrap(), secret)
+    }
+
+    pub(crate) fn hcaptcha(client: Client, secret: String) -> Self {
+        Self::new(client, HCAPTCHA_URL.parse().unwrap(), secret)
+    }
+
+    pub(crate) fn new(client: Client, url: Url, secret: String) -> Self {
+        Self {
+            client,
+            url,
+            secret,
+        }
+    }
+
+    pub(crate) async fn verify(&self, token: &str, remote_ip: Option<IpAddr>) -> bool {
+        let mut params = vec![("secret", self.secret.clone()), ("response", token.into())];
+        if let Some(remote_ip) = remote_ip {
+            params.push(("remoteip", remote_ip.to_string()));
+        }
+        let body = Serializer::new(String::new()).extend_pairs(params).finish();
+
+        let result: Result<SiteverifyResponse, Box<dyn Error + Send + Sync>> = async {
+            Ok(self
+                .client
+                .post(self.url.clone())
+                .with_request_header(ContentType, "application/x-www-form-urlencoded")
+                .with_body(body)
+                .await?
+                .success()?
+                .response_json()
+                .await?)
+        }
+        .await;
+
+        match result {
+            Ok(SiteverifyResponse { success: true, .. }) => true,
+            Ok(SiteverifyResponse { error_codes, .. }) => {
+                log::debug!("captcha token rejected: {}", error_codes.join(", "));
+                false
+            }
+            Err(e) => {
+                log::error!("could not verify captcha token with {}: {e}", self.url);
+                false
+            }
+        }
+    }
+}
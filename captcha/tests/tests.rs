@@ -0,0 +1,200 @@
+use std::time::Duration;
+use trillium::{Conn, Handler};
+use trillium_captcha::{solve, Captcha};
+use trillium_client::Client;
+use trillium_testing::{prelude::*, TestConn};
+
+async fn ok(conn: Conn) -> Conn {
+    conn.ok("ok")
+}
+
+fn challenge(handler: &impl Handler) -> String {
+    let conn = post("/").on(handler);
+    assert_status!(&conn, 403);
+    conn.response_headers()
+        .get_str("captcha-challenge")
+        .unwrap()
+        .to_string()
+}
+
+fn pass_cookie(conn: &TestConn) -> String {
+    let cookie = conn.response_headers().get_str("set-cookie").unwrap();
+    assert!(cookie.starts_with("trillium.captcha="));
+    cookie.split_once(';').unwrap().0.to_string()
+}
+
+#[test]
+fn proof_of_work() {
+    let handler = (Captcha::proof_of_work(8), ok);
+    let issued = challenge(&handler);
+    assert!(issued.starts_with("8."));
+
+    let solution = solve(&issued).unwrap();
+    let mut conn = post("/")
+        .with_request_header("captcha-solution", solution.clone())
+        .on(&handler);
+    assert_ok!(&mut conn, "ok");
+    let cookie = pass_cookie(&conn);
+
+    // solved challenges can be replayed until they expire
+    assert_ok!(
+        post("/")
+            .with_request_header("captcha-solution", solution)
+            .on(&handler),
+        "ok"
+    );
+
+    let mut conn = post("/").with_request_header("cookie", cookie).on(&handler);
+    assert_ok!(&mut conn, "ok");
+    assert!(conn.response_headers().get_str("set-cookie").is_none());
+}
+
+#[test]
+fn invalid_solutions() {
+    let handler = (Captcha::proof_of_work(8), ok);
+    let issued = challenge(&handler);
+
+    assert_status!(
+        post("/")
+            .with_request_header("captcha-solution", format!("{issued}:not-a-solution"))
+            .on(&handler),
+        403
+    );
+
+    // a challenge issued by a different key
+    let other = challenge(&(Captcha::proof_of_work(8), ok));
+    assert_status!(
+        post("/")
+            .with_request_header("captcha-solution", solve(&other).unwrap())
+            .on(&handler),
+        403
+    );
+
+    // a challenge with a lower difficulty than is required
+    let easy = (Captcha::proof_of_work(1).with_signing_key("shared key"), ok);
+    let hard = (Captcha::proof_of_work(8).with_signing_key("shared key"), ok);
+    let easy_challenge = challenge(&easy);
+    assert_status!(
+        post("/")
+            .with_request_header("captcha-solution", solve(&easy_challenge).unwrap())
+            .on(&hard),
+        403
+    );
+
+    // an expired challenge
+    let expired = (
+        Captcha::proof_of_work(1).with_challenge_duration(Duration::ZERO),
+        ok,
+    );
+    let expired_challenge = challenge(&expired);
+    assert_status!(
+        post("/")
+            .with_request_header("captcha-solution", solve(&expired_challenge).unwrap())
+            .on(&expired),
+        403
+    );
+
+    assert_status!(
+        post("/")
+            .with_request_header("cookie", "trillium.captcha=99999999999.forged")
+            .on(&handler),
+        403
+    );
+}
+
+#[test]
+fn signing_key_and_pass_duration() {
+    let first = (Captcha::proof_of_work(4).with_signing_key("shared key"), ok);
+    let second = (Captcha::proof_of_work(4).with_signing_key("shared key"), ok);
+
+    let conn = post("/")
+        .with_request_header("captcha-solution", solve(&challenge(&first)).unwrap())
+        .on(&first);
+    let cookie = pass_cookie(&conn);
+    assert_ok!(
+        post("/").with_request_header("cookie", cookie).on(&second),
+        "ok"
+    );
+
+    let expiring = (
+        Captcha::proof_of_work(4).with_pass_duration(Duration::ZERO),
+        ok,
+    );
+    let conn = post("/")
+        .with_request_header("captcha-solution", solve(&challenge(&expiring)).unwrap())
+        .on(&expiring);
+    let cookie = pass_cookie(&conn);
+    assert_status!(
+        post("/")
+            .with_request_header("cookie", cookie)
+            .on(&expiring),
+        403
+    );
+}
+
+#[test]
+fn exemptions_and_response() {
+    let handler = (
+        Captcha::proof_of_work(8)
+            .with_exemption(|conn: &Conn| conn.path() == "/health")
+            .with_exemption(|conn: &Conn| conn.request_headers().has_header("x-trusted"))
+            .with_response(|conn: Conn| async move {
+                conn.with_status(429).with_body("solve the challenge")
+            }),
+        ok,
+    );
+
+    assert_ok!(post("/health").on(&handler), "ok");
+    assert_ok!(
+        post("/").with_request_header("x-trusted", "1").on(&handler),
+        "ok"
+    );
+
+    let conn = post("/").on(&handler);
+    assert!(conn.response_headers().has_header("captcha-challenge"));
+    assert_response!(conn, 429, "solve the challenge");
+}
+
+#[test]
+fn siteverify() {
+    let provider = |mut conn: Conn| async move {
+        let body = conn.request_body_string().await.unwrap();
+        let params: Vec<(String, String)> = url::form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect();
+        assert!(params.contains(&("secret".into(), "secret key".into())));
+        let success = params.contains(&("response".into(), "valid token".into()));
+        conn.ok(format!(
+            r#"{{"success": {success}, "error-codes": ["invalid-input-response"]}}"#
+        ))
+    };
+
+    let handler = (
+        Captcha::turnstile(
+            Client::new(trillium_testing::connector(provider)),
+            "secret key",
+        ),
+        ok,
+    );
+
+    let conn = post("/").on(&handler);
+    assert_status!(&conn, 403);
+    assert!(!conn.response_headers().has_header("captcha-challenge"));
+
+    assert_status!(
+        post("/")
+            .with_request_header("captcha-token", "invalid token")
+            .on(&handler),
+        403
+    );
+
+    let mut conn = post("/")
+        .with_request_header("captcha-token", "valid token")
+        .on(&handler);
+    assert_ok!(&mut conn, "ok");
+    let cookie = pass_cookie(&conn);
+    assert_ok!(
+        post("/").with_request_header("cookie", cookie).on(&handler),
+        "ok"
+    );
+}
@@ -0,0 +1,149 @@
+use querystrong::QueryStrong;
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+use trillium::{async_trait, Conn, KnownHeaderName, Status};
+
+/**
+# Trait for verifying the token a client sends when connecting
+
+When a [`Channel`](crate::Channel) is configured with
+[`Channel::with_authenticator`](crate::Channel::with_authenticator),
+every websocket upgrade request must include a token as a query
+param, as phoenix clients do with `new Socket("/socket", { params: {
+token } })`. The token is passed to [`ChannelAuthenticator::verify`]
+before the websocket is established. If verification fails or the
+token is missing, the upgrade is rejected with a `403 Forbidden`
+status. Otherwise the returned identity is available to every
+[`ChannelHandler`](crate::ChannelHandler) callback through
+[`ChannelConn::identity`](crate::ChannelConn::identity).
+
+## Example
+
+```
+use trillium::Conn;
+use trillium_channels::{channel, ChannelAuthenticator, ChannelConn, ChannelEvent, ChannelHandler};
+
+struct User {
+    id: u64,
+}
+
+struct TokenAuthenticator;
+#[trillium::async_trait]
+impl ChannelAuthenticator for TokenAuthenticator {
+    type Identity = User;
+
+    async fn verify(&self, token: &str, _conn: &Conn) -> Option<User> {
+        // verify a signed token here
+        token.strip_prefix("user-")?.parse().ok().map(|id| User { id })
+    }
+}
+
+struct ChatChannel;
+#[trillium::async_trait]
+impl ChannelHandler for ChatChannel {
+    async fn join_channel(&self, conn: ChannelConn<'_>, event: ChannelEvent) {
+        let Some(user) = conn.identity::<User>() else { return };
+        if event.topic() == format!("users:{}", user.id) {
+            conn.allow_join(&event, &()).await;
+        } else {
+            conn.reply_error(&event, &"unauthorized").await;
+        }
+    }
+}
+
+let handler = channel(ChatChannel).with_authenticator(TokenAuthenticator);
+
+use trillium_testing::prelude::*;
+let upgrade = |path| {
+    get(path)
+        .with_request_header("connection", "upgrade")
+        .with_request_header("upgrade", "websocket")
+        .with_request_header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+};
+assert_status!(upgrade("/?token=user-10").on(&handler), 101);
+assert_status!(upgrade("/?token=nope").on(&handler), 403);
+assert_status!(upgrade("/").on(&handler), 403);
+```
+*/
+#[async_trait]
+pub trait ChannelAuthenticator: Send + Sync + 'static {
+    /// the verified identity that is made available to channel handlers
+    type Identity: Send + Sync + 'static;
+
+    /**
+    `verify` is called with the token from the websocket upgrade
+    request's query string, and the conn for that request. Return
+    `None` to reject the connection.
+    */
+    async fn verify(&self, token: &str, conn: &Conn) -> Option<Self::Identity>;
+
+    /**
+    the name of the query param that contains the token. The default
+    implementation returns `"token"`.
+    */
+    fn token_param(&self) -> Cow<'static, str> {
+        "token".into()
+    }
+}
+
+pub(crate) struct Identity<T>(pub(crate) T);
+
+#[async_trait]
+trait ErasedAuthenticator: Send + Sync + 'static {
+    async fn authenticate(&self, conn: &mut Conn) -> bool;
+}
+
+#[async_trait]
+impl<A: ChannelAuthenticator> ErasedAuthenticator for A {
+    async fn authenticate(&self, conn: &mut Conn) -> bool {
+        let query = QueryStrong::parse(conn.querystring()).unwrap_or_default();
+        let Some(token) = query.get_str(&*self.token_param()) else {
+            return false;
+        };
+
+        match self.verify(token, conn).await {
+            Some(identity) => {
+                conn.insert_state(Identity(identity));
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Authentication(Option<Arc<dyn ErasedAuthenticator>>);
+
+impl Debug for Authentication {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Authentication")
+            .field(&self.0.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl Authentication {
+    pub(crate) fn new(authenticator: impl ChannelAuthenticator) -> Self {
+        Self(Some(Arc::new(authenticator)))
+    }
+
+    /// returns Err with a halted conn if the websocket upgrade should be rejected
+    pub(crate) async fn authenticate(&self, mut conn: Conn) -> Result<Conn, Conn> {
+        let Some(authenticator) = &self.0 else {
+            return Ok(conn);
+        };
+
+        let upgrade_requested = conn
+            .request_headers()
+            .eq_ignore_ascii_case(KnownHeaderName::Upgrade, "websocket");
+
+        if !upgrade_requested || authenticator.authenticate(&mut conn).await {
+            Ok(conn)
+        } else {
+            Err(conn.with_status(Status::Forbidden).halt())
+        }
+    }
+}
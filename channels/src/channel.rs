@@ -1,5 +1,9 @@
-use crate::{ChannelBroadcaster, ChannelCentral, ChannelEvent, ChannelHandler};
+use crate::{
+    Authentication, ChannelAuthenticator, ChannelBroadcaster, ChannelCentral, ChannelEvent,
+    ChannelHandler, ChannelPersistence, SlowConsumer, SlowConsumerPolicy,
+};
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use trillium::{async_trait, Conn, Handler, Upgrade};
 use trillium_websockets::WebSocket;
 
@@ -10,7 +14,7 @@ This is constructed from a [`ChannelHandler`] using [`Channel::new`]
 and dereferences to that type.
 */
 #[derive(Debug)]
-pub struct Channel<CH>(WebSocket<ChannelCentral<CH>>);
+pub struct Channel<CH>(WebSocket<ChannelCentral<CH>>, Authentication);
 
 #[async_trait]
 impl<CH> Handler for Channel<CH>
@@ -18,7 +22,10 @@ where
     CH: ChannelHandler,
 {
     async fn run(&self, conn: Conn) -> Conn {
-        self.0.run(conn).await
+        match self.1.authenticate(conn).await {
+            Ok(conn) => self.0.run(conn).await,
+            Err(conn) => conn,
+        }
     }
 
     async fn init(&mut self, info: &mut trillium::Info) {
@@ -44,7 +51,87 @@ impl<CH: ChannelHandler> Channel<CH> {
     [`ChannelHandler`] implementation
      */
     pub fn new(channel_handler: CH) -> Self {
-        Self(WebSocket::new(ChannelCentral::new(channel_handler)))
+        Self(
+            WebSocket::new(ChannelCentral::new(channel_handler)),
+            Authentication::default(),
+        )
+    }
+
+    /**
+    Configure a [`ChannelPersistence`] that is called with every
+    broadcast event and can replay missed events to rejoining
+    clients. This should be called before [`Channel::broadcaster`],
+    as broadcasters retrieved earlier will not persist their events.
+     */
+    pub fn with_persistence(mut self, persistence: impl ChannelPersistence) -> Self {
+        self.0.set_persistence(persistence);
+        self
+    }
+
+    /**
+    Configure a [`ChannelAuthenticator`] that verifies a token sent
+    by each client when connecting. Connections without a valid token
+    are rejected before the websocket is established, and the verified
+    identity is available to the [`ChannelHandler`] through
+    [`ChannelConn::identity`](crate::ChannelConn::identity).
+     */
+    pub fn with_authenticator(mut self, authenticator: impl ChannelAuthenticator) -> Self {
+        self.1 = Authentication::new(authenticator);
+        self
+    }
+
+    /**
+    Configure the number of broadcast events that can be queued for
+    each client before the [`SlowConsumerPolicy`] is applied. This
+    defaults to 10. Like [`Channel::with_persistence`], this should
+    be called before [`Channel::broadcaster`].
+     */
+    pub fn with_outbound_capacity(mut self, capacity: usize) -> Self {
+        self.0
+            .configure_clients(|config| config.capacity = capacity);
+        self
+    }
+
+    /**
+    Configure what happens when a broadcast event arrives for a
+    client whose outbound queue is full. See [`SlowConsumerPolicy`]
+    for the options. This should be called before
+    [`Channel::broadcaster`].
+     */
+    pub fn with_slow_consumer_policy(mut self, policy: SlowConsumerPolicy) -> Self {
+        self.0.configure_clients(|config| config.policy = policy);
+        self
+    }
+
+    /**
+    Configure a hook that is called every time the
+    [`SlowConsumerPolicy`] is applied to a client, in order to log or
+    record metrics about clients that fall behind. The hook is called
+    synchronously from wherever the broadcast originated. This should
+    be called before [`Channel::broadcaster`].
+
+    ```
+    use trillium_channels::{channel, ChannelConn, ChannelEvent, ChannelHandler, SlowConsumerPolicy};
+    # struct ChatChannel;
+    # #[trillium::async_trait]
+    # impl ChannelHandler for ChatChannel {
+    #     async fn join_channel(&self, conn: ChannelConn<'_>, event: ChannelEvent) {}
+    # }
+    let channel = channel(ChatChannel)
+        .with_outbound_capacity(100)
+        .with_slow_consumer_policy(SlowConsumerPolicy::Disconnect)
+        .with_slow_consumer_hook(|slow_consumer| {
+            log::warn!("disconnecting a client after {:?}", slow_consumer.event());
+        });
+    ```
+     */
+    pub fn with_slow_consumer_hook(
+        mut self,
+        hook: impl Fn(&SlowConsumer) + Send + Sync + 'static,
+    ) -> Self {
+        self.0
+            .configure_clients(|config| config.hook = Some(Arc::new(hook)));
+        self
     }
 
     /**
@@ -1,4 +1,4 @@
-use crate::ChannelEvent;
+use crate::{clients::Clients, ChannelEvent, Persistence};
 use async_broadcast::{InactiveReceiver, Receiver as ActiveReceiver, Sender};
 use futures_lite::Stream;
 use std::{
@@ -25,6 +25,8 @@ servers.
 pub struct ChannelBroadcaster {
     sender: Sender<ChannelEvent>,
     receiver: Receiver<ChannelEvent>,
+    clients: Clients,
+    persistence: Persistence,
 }
 
 #[derive(Debug)]
@@ -77,10 +79,14 @@ impl ChannelBroadcaster {
     pub(crate) fn new(
         sender: Sender<ChannelEvent>,
         receiver: InactiveReceiver<ChannelEvent>,
+        clients: Clients,
+        persistence: Persistence,
     ) -> Self {
         Self {
             sender,
             receiver: Receiver::Inactive(receiver),
+            clients,
+            persistence,
         }
     }
 
@@ -88,9 +94,12 @@ impl ChannelBroadcaster {
     Send this ChannelEvent to all subscribed channel clients
     */
     pub fn broadcast(&self, event: impl Into<ChannelEvent>) {
-        // we don't care about whether there are any connected clients
-        // here, so we ignore error results.
-        self.sender.try_broadcast(event.into()).ok();
+        let event = event.into();
+        self.persistence.persist(&event);
+        self.clients.broadcast(&event);
+        // we don't care about whether there are any broadcaster
+        // streams listening here, so we ignore error results.
+        self.sender.try_broadcast(event).ok();
     }
 
     /**
@@ -99,7 +108,24 @@ impl ChannelBroadcaster {
     this, and currently that number is not available.
     */
     pub fn connected_clients(&self) -> usize {
-        self.sender.receiver_count()
+        self.clients.len()
+    }
+
+    /**
+    Returns the total number of broadcast events that connected
+    clients did not receive because they fell behind. See
+    [`SlowConsumerPolicy`](crate::SlowConsumerPolicy).
+    */
+    pub fn dropped_events(&self) -> u64 {
+        self.clients.dropped_events()
+    }
+
+    /**
+    Returns the total number of clients that have been disconnected
+    by [`SlowConsumerPolicy::Disconnect`](crate::SlowConsumerPolicy::Disconnect).
+    */
+    pub fn evicted_clients(&self) -> u64 {
+        self.clients.evicted_clients()
     }
 }
 
@@ -1,6 +1,8 @@
 use crate::{
-    client_receiver::ClientReceiver, ChannelBroadcaster, ChannelClient, ChannelConn, ChannelEvent,
-    ChannelHandler, Version,
+    client_receiver::ClientReceiver,
+    clients::{Clients, Config},
+    ChannelBroadcaster, ChannelClient, ChannelConn, ChannelEvent, ChannelHandler,
+    ChannelPersistence, Persistence, Version,
 };
 use async_broadcast::{InactiveReceiver, Sender};
 use querystrong::QueryStrong;
@@ -15,6 +17,8 @@ pub(crate) struct ChannelCentral<CH> {
     handler: CH,
     broadcast_sender: Sender<ChannelEvent>,
     broadcast_receiver: InactiveReceiver<ChannelEvent>,
+    persistence: Persistence,
+    clients: Clients,
 }
 
 impl<CH> ChannelCentral<CH>
@@ -29,24 +33,42 @@ where
             handler,
             broadcast_sender,
             broadcast_receiver,
+            persistence: Persistence::default(),
+            clients: Clients::default(),
         }
     }
 
+    pub(crate) fn set_persistence(&mut self, persistence: impl ChannelPersistence) {
+        self.persistence = Persistence::new(persistence);
+    }
+
+    pub(crate) fn configure_clients(&mut self, f: impl FnOnce(&mut Config)) {
+        self.clients.configure(f);
+    }
+
     pub(crate) fn channel_broadcaster(&self) -> ChannelBroadcaster {
         ChannelBroadcaster::new(
             self.broadcast_sender.clone(),
             self.broadcast_receiver.clone(),
+            self.clients.clone(),
+            self.persistence.clone(),
         )
     }
 
     pub(crate) fn broadcast(&self, event: impl Into<ChannelEvent>) {
-        trillium::log_error!(self.broadcast_sender.try_broadcast(event.into()));
+        let event = event.into();
+        self.persistence.persist(&event);
+        self.clients.broadcast(&event);
+        // we don't care about whether there are any broadcaster
+        // streams listening here, so we ignore error results.
+        self.broadcast_sender.try_broadcast(event).ok();
     }
 
     fn build_client(&self, version: Version) -> (ChannelClient, ClientReceiver) {
         ChannelClient::new(
             self.broadcast_sender.clone(),
-            self.broadcast_receiver.activate_cloned(),
+            self.clients.clone(),
+            self.persistence.clone(),
             version,
         )
     }
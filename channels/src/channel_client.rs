@@ -1,5 +1,8 @@
-use crate::{client_receiver::ClientReceiver, subscriptions::Subscriptions, ChannelEvent, Version};
-use async_broadcast::{Receiver, Sender as BroadcastSender};
+use crate::{
+    client_receiver::ClientReceiver, clients::Clients, subscriptions::Subscriptions, ChannelEvent,
+    Persistence, Version,
+};
+use async_broadcast::Sender as BroadcastSender;
 use async_channel::Sender;
 use serde::Serialize;
 use trillium::log_error;
@@ -18,25 +21,31 @@ pub struct ChannelClient {
     subscriptions: Subscriptions,
     sender: Sender<ChannelEvent>,
     broadcast_sender: BroadcastSender<ChannelEvent>,
+    clients: Clients,
+    persistence: Persistence,
     version: Version,
 }
 
 impl ChannelClient {
     pub(crate) fn new(
         broadcast_sender: BroadcastSender<ChannelEvent>,
-        broadcast_receiver: Receiver<ChannelEvent>,
+        clients: Clients,
+        persistence: Persistence,
         version: Version,
     ) -> (Self, ClientReceiver) {
         let (sender, individual) = async_channel::unbounded();
         let subscriptions = Subscriptions::default();
+        let (registration, broadcast) = clients.register(subscriptions.clone());
         (
             Self {
                 subscriptions: subscriptions.clone(),
                 sender,
                 broadcast_sender,
+                clients,
+                persistence,
                 version,
             },
-            ClientReceiver::new(individual, broadcast_receiver, subscriptions, version),
+            ClientReceiver::new(individual, broadcast, registration, subscriptions, version),
         )
     }
 
@@ -48,7 +57,11 @@ impl ChannelClient {
     pub fn broadcast(&self, event: impl Into<ChannelEvent>) {
         let mut event = event.into();
         event.reference = None;
-        log_error!(self.broadcast_sender.try_broadcast(event));
+        self.persistence.persist(&event);
+        self.clients.broadcast(&event);
+        // we don't care about whether there are any broadcaster
+        // streams listening here, so we ignore error results.
+        self.broadcast_sender.try_broadcast(event).ok();
     }
 
     /**
@@ -137,6 +150,21 @@ impl ChannelClient {
         self.reply_ok(event, payload).await;
     }
 
+    /**
+    Resend events on the provided topic that were broadcast after the
+    provided cursor to this specific client, as returned by the
+    [`ChannelPersistence`](crate::ChannelPersistence) configured with
+    [`Channel::with_persistence`](crate::Channel::with_persistence).
+
+    Since clients only receive events for topics they subscribe to,
+    this should be called after [`ChannelClient::allow_join`].
+    */
+    pub async fn replay(&self, topic: &str, cursor: &str) {
+        for event in self.persistence.replay(topic, cursor).await {
+            self.send_event(event).await;
+        }
+    }
+
     /**
     Borrow this client's subscriptions
      */
@@ -1,4 +1,4 @@
-use crate::{ChannelClient, ChannelEvent};
+use crate::{authentication::Identity, ChannelClient, ChannelEvent};
 use serde::Serialize;
 use std::ops::{Deref, DerefMut};
 use trillium_websockets::WebSocketConn;
@@ -36,6 +36,17 @@ impl ChannelConn<'_> {
         self.state()
     }
 
+    /**
+    Borrow the identity returned by the
+    [`ChannelAuthenticator`](crate::ChannelAuthenticator) when this
+    client connected. This returns None if no authenticator is
+    configured or if the type does not match the authenticator's
+    [`Identity`](crate::ChannelAuthenticator::Identity).
+    */
+    pub fn identity<T: Send + Sync + 'static>(&self) -> Option<&T> {
+        self.state::<Identity<T>>().map(|identity| &identity.0)
+    }
+
     /**
     Borrow the websocket conn
     */
@@ -102,6 +113,14 @@ impl ChannelConn<'_> {
     pub async fn allow_leave(&self, event: &ChannelEvent, payload: &impl Serialize) {
         channel_client!(self).allow_leave(event, payload).await;
     }
+
+    /**
+    Resend events on the provided topic that were broadcast after the
+    provided cursor to this client. See [`ChannelClient::replay`].
+    */
+    pub async fn replay(&self, topic: &str, cursor: &str) {
+        channel_client!(self).replay(topic, cursor).await;
+    }
 }
 
 impl Deref for ChannelConn<'_> {
@@ -1,5 +1,4 @@
-use crate::{subscriptions::Subscriptions, ChannelEvent, Version};
-use async_broadcast::Receiver as BroadcastReceiver;
+use crate::{clients::Registration, subscriptions::Subscriptions, ChannelEvent, Version};
 use async_channel::Receiver;
 use futures_lite::{stream::Race, Stream, StreamExt};
 use std::{
@@ -11,14 +10,17 @@ use trillium_websockets::Message;
 #[derive(Debug)]
 pub struct ClientReceiver {
     subscriptions: Subscriptions,
-    race: Pin<Box<Race<BroadcastReceiver<ChannelEvent>, Receiver<ChannelEvent>>>>,
+    race: Pin<Box<Race<Receiver<ChannelEvent>, Receiver<ChannelEvent>>>>,
     version: Version,
+    registration: Registration,
+    closing: bool,
 }
 
 impl ClientReceiver {
-    pub fn new(
+    pub(crate) fn new(
         individual: Receiver<ChannelEvent>,
-        broadcast: BroadcastReceiver<ChannelEvent>,
+        broadcast: Receiver<ChannelEvent>,
+        registration: Registration,
         subscriptions: Subscriptions,
         version: Version,
     ) -> Self {
@@ -26,28 +28,47 @@ impl ClientReceiver {
             race: Box::pin(broadcast.race(individual)),
             subscriptions,
             version,
+            registration,
+            closing: false,
         }
     }
+
+    fn serialize(&self, event: &ChannelEvent) -> Option<Message> {
+        let text = event.serialize(self.version).ok()?;
+        log::trace!(
+            "serialized {:?} with {:?} as {:?}",
+            event,
+            &self.version,
+            &text
+        );
+        Some(Message::Text(text))
+    }
 }
 
 impl Stream for ClientReceiver {
     type Item = Message;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.closing {
+            return Poll::Ready(None);
+        }
+
         loop {
             match self.race.poll_next(cx) {
                 Poll::Ready(Some(event)) if !self.subscriptions.subscribes(&event) => continue,
                 Poll::Ready(Some(event)) => {
-                    if let Ok(text) = event.serialize(self.version) {
-                        log::trace!(
-                            "serialized {:?} with {:?} as {:?}",
-                            event,
-                            &self.version,
-                            &text
-                        );
-                        break Poll::Ready(Some(Message::Text(text)));
+                    if let Some(message) = self.serialize(&event) {
+                        break Poll::Ready(Some(message));
                     }
                 }
+                Poll::Pending if self.registration.is_evicted() => {
+                    // the broadcast queue was closed and drained, so
+                    // tell the client why before ending the stream
+                    self.closing = true;
+                    let event =
+                        crate::event!("phoenix", "phx_error", { "reason": "slow_consumer" });
+                    break Poll::Ready(self.serialize(&event));
+                }
                 Poll::Pending => break Poll::Pending,
                 Poll::Ready(None) => break Poll::Ready(None),
             }
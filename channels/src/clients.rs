@@ -0,0 +1,215 @@
+use crate::{subscriptions::Subscriptions, ChannelEvent, SlowConsumer, SlowConsumerPolicy};
+use async_channel::{Receiver, Sender, TrySendError};
+use dashmap::DashMap;
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering::Relaxed},
+        Arc,
+    },
+};
+
+const DEFAULT_CAPACITY: usize = 10;
+
+type SlowConsumerHook = Arc<dyn Fn(&SlowConsumer) + Send + Sync + 'static>;
+
+/// The registry of connected clients and their bounded outbound
+/// queues, which broadcast events are fanned out to
+#[derive(Clone, Debug)]
+pub(crate) struct Clients(Arc<ClientsInner>);
+
+#[derive(Debug)]
+struct ClientsInner {
+    config: Config,
+    clients: DashMap<u64, ClientQueue>,
+    next_id: AtomicU64,
+    dropped_events: AtomicU64,
+    evicted_clients: AtomicU64,
+}
+
+#[derive(Clone)]
+pub(crate) struct Config {
+    pub(crate) capacity: usize,
+    pub(crate) policy: SlowConsumerPolicy,
+    pub(crate) hook: Option<SlowConsumerHook>,
+}
+
+impl Debug for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("capacity", &self.capacity)
+            .field("policy", &self.policy)
+            .field("hook", &self.hook.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            policy: SlowConsumerPolicy::default(),
+            hook: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ClientQueue {
+    sender: Sender<ChannelEvent>,
+    subscriptions: Subscriptions,
+    evicted: Arc<AtomicBool>,
+    dropped_events: AtomicU64,
+}
+
+/// A client's membership in [`Clients`], which removes the client
+/// when dropped
+#[derive(Debug)]
+pub(crate) struct Registration {
+    id: u64,
+    clients: Clients,
+    evicted: Arc<AtomicBool>,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.clients.0.clients.remove(&self.id);
+    }
+}
+
+impl Registration {
+    /// whether this client was disconnected by
+    /// [`SlowConsumerPolicy::Disconnect`]
+    pub(crate) fn is_evicted(&self) -> bool {
+        self.evicted.load(Relaxed)
+    }
+}
+
+impl Default for Clients {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
+
+impl Clients {
+    fn new(config: Config) -> Self {
+        Self(Arc::new(ClientsInner {
+            config,
+            clients: DashMap::new(),
+            next_id: AtomicU64::new(0),
+            dropped_events: AtomicU64::new(0),
+            evicted_clients: AtomicU64::new(0),
+        }))
+    }
+
+    /// Reconfigures these clients. If this registry has been shared,
+    /// it is replaced with a new one, as with
+    /// [`Channel::with_persistence`](crate::Channel::with_persistence)
+    pub(crate) fn configure(&mut self, f: impl FnOnce(&mut Config)) {
+        if Arc::get_mut(&mut self.0).is_none() {
+            *self = Self::new(self.0.config.clone());
+        }
+
+        if let Some(inner) = Arc::get_mut(&mut self.0) {
+            f(&mut inner.config);
+        }
+    }
+
+    pub(crate) fn register(
+        &self,
+        subscriptions: Subscriptions,
+    ) -> (Registration, Receiver<ChannelEvent>) {
+        let (sender, receiver) = async_channel::bounded(self.0.config.capacity.max(1));
+        let id = self.0.next_id.fetch_add(1, Relaxed);
+        let evicted = Arc::new(AtomicBool::new(false));
+        self.0.clients.insert(
+            id,
+            ClientQueue {
+                sender,
+                subscriptions,
+                evicted: Arc::clone(&evicted),
+                dropped_events: AtomicU64::new(0),
+            },
+        );
+
+        (
+            Registration {
+                id,
+                clients: self.clone(),
+                evicted,
+            },
+            receiver,
+        )
+    }
+
+    /// Enqueues this event for every client that subscribes to its
+    /// topic, applying the [`SlowConsumerPolicy`] to clients whose
+    /// queues are full
+    pub(crate) fn broadcast(&self, event: &ChannelEvent) {
+        let Config {
+            capacity,
+            policy,
+            ref hook,
+        } = self.0.config;
+
+        let mut slow_consumers = vec![];
+        for client in self.0.clients.iter() {
+            if !client.subscriptions.subscribes(event) {
+                continue;
+            }
+
+            let undelivered = match policy {
+                SlowConsumerPolicy::DropOldest => {
+                    client.sender.force_send(event.clone()).ok().flatten()
+                }
+
+                SlowConsumerPolicy::Skip => match client.sender.try_send(event.clone()) {
+                    Err(TrySendError::Full(event)) => Some(event),
+                    _ => None,
+                },
+
+                SlowConsumerPolicy::Disconnect => match client.sender.try_send(event.clone()) {
+                    Err(TrySendError::Full(event)) => {
+                        client.evicted.store(true, Relaxed);
+                        client.sender.close();
+                        self.0.evicted_clients.fetch_add(1, Relaxed);
+                        Some(event)
+                    }
+                    _ => None,
+                },
+            };
+
+            if let Some(event) = undelivered {
+                self.0.dropped_events.fetch_add(1, Relaxed);
+                let dropped_events = client.dropped_events.fetch_add(1, Relaxed) + 1;
+                log::debug!("client {} fell behind, applying {policy:?}", client.key());
+                slow_consumers.push(SlowConsumer {
+                    policy,
+                    event,
+                    capacity,
+                    dropped_events,
+                });
+            }
+        }
+
+        // the hook is called after releasing the registry so that it
+        // can broadcast without deadlocking
+        if let Some(hook) = hook {
+            for slow_consumer in &slow_consumers {
+                hook(slow_consumer);
+            }
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.0.clients.len()
+    }
+
+    pub(crate) fn dropped_events(&self) -> u64 {
+        self.0.dropped_events.load(Relaxed)
+    }
+
+    pub(crate) fn evicted_clients(&self) -> u64 {
+        self.0.evicted_clients.load(Relaxed)
+    }
+}
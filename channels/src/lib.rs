@@ -53,6 +53,35 @@ appropriate for your application and deployment. Open a discussion on
 the trillium repo for ideas on how this might work for you.
 
 
+### Missed-message replay
+
+Phoenix channels do not store messages. Trillium channels can
+optionally call a [`ChannelPersistence`] implementation for every
+broadcast, and replay missed events to a client that rejoins a topic
+with [`ChannelConn::replay`]. See [`ChannelPersistence`] for an
+example.
+
+
+### Slow consumers
+
+Each client has a bounded queue of broadcast events, and a
+[`SlowConsumerPolicy`] decides whether a client that falls behind
+loses its oldest events, loses new events, or is disconnected. See
+[`Channel::with_slow_consumer_policy`],
+[`Channel::with_outbound_capacity`], and
+[`Channel::with_slow_consumer_hook`].
+
+
+### Connection authentication
+
+A [`ChannelAuthenticator`] can be configured with
+[`Channel::with_authenticator`] to verify a token that clients send
+as a query param when connecting, like phoenix socket params. Upgrade
+requests without a valid token are rejected, and the verified
+identity is available in every [`ChannelHandler`] callback through
+[`ChannelConn::identity`].
+
+
 ### Event routing is handled in user code
 
 Phoenix channels has a notion of registering channel handlers for
@@ -122,6 +151,11 @@ pub use channel_client::ChannelClient;
 
 pub(crate) mod client_receiver;
 
+pub(crate) mod clients;
+
+mod slow_consumer;
+pub use slow_consumer::{SlowConsumer, SlowConsumerPolicy};
+
 mod channel_handler;
 pub use channel_handler::ChannelHandler;
 
@@ -136,6 +170,14 @@ pub use channel_conn::ChannelConn;
 mod version;
 pub use version::Version;
 
+mod persistence;
+pub use persistence::ChannelPersistence;
+pub(crate) use persistence::Persistence;
+
+mod authentication;
+pub(crate) use authentication::Authentication;
+pub use authentication::ChannelAuthenticator;
+
 /**
 This macro provides a convenient constructor for a
 [`ChannelEvent`]. It is called with a topic, an event, and an optional
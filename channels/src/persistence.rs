@@ -0,0 +1,125 @@
+use crate::ChannelEvent;
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+    time::SystemTime,
+};
+use trillium::async_trait;
+
+/**
+# Trait for storing broadcast events so they can be replayed later
+
+When a [`Channel`](crate::Channel) is configured with
+[`Channel::with_persistence`](crate::Channel::with_persistence),
+[`ChannelPersistence::persist`] is called for every broadcast event
+before that event is delivered to connected clients, and
+[`ChannelClient::replay`](crate::ChannelClient::replay) can be used
+to resend persisted events to a client that has just rejoined a
+topic. Since a client may receive an event both live and through
+replay, this provides at-least-once delivery, and clients should be
+prepared to ignore duplicates.
+
+The format of the cursor is entirely up to the implementation. It
+will usually be a database row id or a timestamp that is also
+included in the event payload, so that clients know what cursor to
+send when they rejoin.
+
+## Example
+
+```
+use std::{sync::{Arc, Mutex}, time::SystemTime};
+use trillium_channels::{channel, ChannelConn, ChannelEvent, ChannelHandler, ChannelPersistence};
+
+#[derive(Clone, Default)]
+struct InMemory(Arc<Mutex<Vec<ChannelEvent>>>);
+
+#[trillium::async_trait]
+impl ChannelPersistence for InMemory {
+    fn persist(&self, event: &ChannelEvent, _timestamp: SystemTime) {
+        self.0.lock().unwrap().push(event.clone());
+    }
+
+    async fn replay(&self, topic: &str, cursor: &str) -> Vec<ChannelEvent> {
+        let cursor = cursor.parse().unwrap_or(0);
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.topic() == topic)
+            .filter(|event| event.payload()["id"].as_u64().is_some_and(|id| id > cursor))
+            .cloned()
+            .collect()
+    }
+}
+
+struct ChatChannel;
+#[trillium::async_trait]
+impl ChannelHandler for ChatChannel {
+    async fn join_channel(&self, conn: ChannelConn<'_>, event: ChannelEvent) {
+        conn.allow_join(&event, &()).await;
+        if let Some(cursor) = event.payload()["cursor"].as_str() {
+            conn.replay(event.topic(), cursor).await;
+        }
+    }
+}
+
+let persistence = InMemory::default();
+let channel = channel(ChatChannel).with_persistence(persistence.clone());
+channel.broadcast(("rooms:lobby", "new:msg", serde_json::json!({ "id": 1 })));
+channel.broadcaster().broadcast(("rooms:lobby", "new:msg", serde_json::json!({ "id": 2 })));
+assert_eq!(persistence.0.lock().unwrap().len(), 2);
+```
+*/
+#[async_trait]
+pub trait ChannelPersistence: Send + Sync + 'static {
+    /**
+    `persist` is called with every broadcast event and the time it
+    was broadcast, before it is delivered to any clients.
+
+    This is called synchronously from wherever the broadcast
+    originated, so implementations that write to a database or other
+    slow storage should enqueue the write (for example, onto a channel
+    that is drained by a spawned task) instead of blocking.
+    */
+    fn persist(&self, event: &ChannelEvent, timestamp: SystemTime);
+
+    /**
+    `replay` returns the persisted events for the provided topic that
+    were broadcast after the provided cursor, in the order they should
+    be sent to the client.
+    */
+    async fn replay(&self, topic: &str, cursor: &str) -> Vec<ChannelEvent>;
+}
+
+#[derive(Clone, Default)]
+pub(crate) struct Persistence(Option<Arc<dyn ChannelPersistence>>);
+
+impl Debug for Persistence {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Persistence")
+            .field(&self.0.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl Persistence {
+    pub(crate) fn new(persistence: impl ChannelPersistence) -> Self {
+        Self(Some(Arc::new(persistence)))
+    }
+
+    pub(crate) fn persist(&self, event: &ChannelEvent) {
+        if let Some(persistence) = &self.0 {
+            persistence.persist(event, SystemTime::now());
+        }
+    }
+
+    pub(crate) async fn replay(&self, topic: &str, cursor: &str) -> Vec<ChannelEvent> {
+        match &self.0 {
+            Some(persistence) => persistence.replay(topic, cursor).await,
+            None => {
+                log::warn!("replay requested for {topic} but no ChannelPersistence is configured");
+                Vec::new()
+            }
+        }
+    }
+}
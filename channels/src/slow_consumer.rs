@@ -0,0 +1,78 @@
+use crate::ChannelEvent;
+
+/**
+# What to do when a client falls behind on broadcast events
+
+Each connected client has a bounded queue of broadcast events that
+have not yet been written to its websocket, configured with
+[`Channel::with_outbound_capacity`](crate::Channel::with_outbound_capacity).
+When a broadcast arrives for a client whose queue is full, the
+configured policy decides what happens, so that one slow client
+cannot grow memory without bound. Replies and events sent directly
+to a client with
+[`ChannelClient::send_event`](crate::ChannelClient::send_event) are
+not subject to this policy.
+
+The policy is configured with
+[`Channel::with_slow_consumer_policy`](crate::Channel::with_slow_consumer_policy),
+and defaults to [`SlowConsumerPolicy::DropOldest`].
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SlowConsumerPolicy {
+    /// Discard the oldest queued event to make room for the new one.
+    #[default]
+    DropOldest,
+
+    /// Disconnect the client. After any events that were already
+    /// queued, the client receives a `"phx_error"` event on the
+    /// `"phoenix"` topic with the payload `{"reason": "slow_consumer"}`,
+    /// and the websocket is closed.
+    Disconnect,
+
+    /// Discard the new event, keeping the events that are already
+    /// queued.
+    Skip,
+}
+
+/**
+# A client that fell behind on broadcast events
+
+This is passed to the hook configured with
+[`Channel::with_slow_consumer_hook`](crate::Channel::with_slow_consumer_hook)
+every time the [`SlowConsumerPolicy`] is applied to a client.
+*/
+#[derive(Debug)]
+pub struct SlowConsumer {
+    pub(crate) policy: SlowConsumerPolicy,
+    pub(crate) event: ChannelEvent,
+    pub(crate) capacity: usize,
+    pub(crate) dropped_events: u64,
+}
+
+impl SlowConsumer {
+    /// the policy that was applied to this client
+    pub fn policy(&self) -> SlowConsumerPolicy {
+        self.policy
+    }
+
+    /**
+    the event that was not delivered. For
+    [`SlowConsumerPolicy::DropOldest`] this is the oldest queued event,
+    and otherwise it is the event that was being broadcast.
+    */
+    pub fn event(&self) -> &ChannelEvent {
+        &self.event
+    }
+
+    /// the number of broadcast events this client's queue holds
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// the total number of events that this client has not received
+    /// because it fell behind, including this one
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped_events
+    }
+}
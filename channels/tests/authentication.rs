@@ -0,0 +1,77 @@
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use trillium::Conn;
+use trillium_channels::{channel, ChannelAuthenticator, ChannelConn, ChannelEvent, ChannelHandler};
+use trillium_client::{websocket::Message, Client, WebSocketConn};
+use trillium_testing::{prelude::*, with_server, ClientConfig};
+
+struct UserId(u64);
+
+struct Authenticator;
+#[trillium::async_trait]
+impl ChannelAuthenticator for Authenticator {
+    type Identity = UserId;
+
+    async fn verify(&self, token: &str, _conn: &Conn) -> Option<UserId> {
+        token.strip_prefix("user-")?.parse().ok().map(UserId)
+    }
+}
+
+struct UserChannel;
+#[trillium::async_trait]
+impl ChannelHandler for UserChannel {
+    async fn join_channel(&self, conn: ChannelConn<'_>, event: ChannelEvent) {
+        match conn.identity::<UserId>() {
+            Some(UserId(id)) if event.topic() == format!("users:{id}") => {
+                conn.allow_join(&event, &json!({ "id": id })).await
+            }
+            _ => conn.reply_error(&event, &"unauthorized").await,
+        }
+    }
+}
+
+#[test]
+fn rejects_upgrade_without_valid_token() {
+    let handler = channel(UserChannel).with_authenticator(Authenticator);
+    let upgrade = |path| {
+        get(path)
+            .with_request_header("connection", "upgrade")
+            .with_request_header("upgrade", "websocket")
+            .with_request_header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+    };
+
+    assert_status!(upgrade("/").on(&handler), 403);
+    assert_status!(upgrade("/?token=").on(&handler), 403);
+    assert_status!(upgrade("/?token=admin").on(&handler), 403);
+    assert_status!(upgrade("/?token=user-1").on(&handler), 101);
+    assert_not_handled!(get("/").on(&handler));
+}
+
+async fn reply(ws: &mut WebSocketConn) -> Value {
+    match ws.next().await {
+        Some(Ok(Message::Text(text))) => serde_json::from_str(&text).unwrap(),
+        other => panic!("unexpected {other:?}"),
+    }
+}
+
+#[test]
+fn exposes_identity_to_channel_handler() {
+    let handler = channel(UserChannel).with_authenticator(Authenticator);
+    let client = Client::new(ClientConfig::new());
+
+    with_server(handler, move |url| async move {
+        let mut ws = client
+            .get(url.join("/?token=user-10")?)
+            .into_websocket()
+            .await?;
+
+        let join = json!({ "topic": "users:10", "event": "phx_join", "payload": {}, "ref": "1" });
+        ws.send_string(join.to_string()).await?;
+        let response = reply(&mut ws).await;
+        assert_eq!(response["ref"], "1");
+        assert_eq!(response["payload"]["status"], "ok");
+        assert_eq!(response["payload"]["response"]["id"], 10);
+
+        Ok(())
+    });
+}
@@ -0,0 +1,136 @@
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use trillium_channels::{
+    channel, ChannelBroadcaster, ChannelConn, ChannelEvent, ChannelHandler, SlowConsumerPolicy,
+};
+use trillium_client::{websocket::Message, Client, WebSocketConn};
+use trillium_testing::{with_server, ClientConfig};
+
+struct OpenChannel;
+#[trillium::async_trait]
+impl ChannelHandler for OpenChannel {
+    async fn join_channel(&self, conn: ChannelConn<'_>, event: ChannelEvent) {
+        conn.allow_join(&event, &()).await;
+    }
+}
+
+async fn next_event(ws: &mut WebSocketConn) -> Option<Value> {
+    match ws.next().await {
+        Some(Ok(Message::Text(text))) => Some(serde_json::from_str(&text).unwrap()),
+        _ => None,
+    }
+}
+
+async fn join(ws: &mut WebSocketConn) {
+    let join = json!({ "topic": "rooms:lobby", "event": "phx_join", "payload": {}, "ref": "1" });
+    ws.send_string(join.to_string()).await.unwrap();
+    assert_eq!(next_event(ws).await.unwrap()["ref"], "1");
+}
+
+// each of these events is large enough that the client's socket
+// buffers fill up long before they have all been broadcast
+fn flood(broadcaster: &ChannelBroadcaster) {
+    let body = "x".repeat(1024 * 1024);
+    for id in 0..64 {
+        broadcaster.broadcast(("rooms:lobby", "flood", json!({ "id": id, "body": body })));
+    }
+}
+
+#[test]
+fn drop_oldest() {
+    let hook_calls = Arc::new(AtomicU64::new(0));
+    let handler = channel(OpenChannel)
+        .with_outbound_capacity(2)
+        .with_slow_consumer_hook({
+            let hook_calls = Arc::clone(&hook_calls);
+            move |slow_consumer| {
+                assert_eq!(slow_consumer.policy(), SlowConsumerPolicy::DropOldest);
+                assert_eq!(slow_consumer.capacity(), 2);
+                hook_calls.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+    let broadcaster = handler.broadcaster();
+    let client = Client::new(ClientConfig::new());
+
+    with_server(handler, move |url| async move {
+        let mut ws = client.get(url).into_websocket().await?;
+        join(&mut ws).await;
+        assert_eq!(broadcaster.connected_clients(), 1);
+
+        flood(&broadcaster);
+        let dropped = broadcaster.dropped_events();
+        assert!(dropped > 0);
+        assert_eq!(hook_calls.load(Ordering::SeqCst), dropped);
+        assert_eq!(broadcaster.evicted_clients(), 0);
+
+        // the most recent event is always delivered
+        let mut last_id = None;
+        while last_id != Some(63) {
+            last_id = next_event(&mut ws).await.unwrap()["payload"]["id"].as_u64();
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn skip() {
+    let handler = channel(OpenChannel)
+        .with_outbound_capacity(2)
+        .with_slow_consumer_policy(SlowConsumerPolicy::Skip);
+    let broadcaster = handler.broadcaster();
+    let client = Client::new(ClientConfig::new());
+
+    with_server(handler, move |url| async move {
+        let mut ws = client.get(url).into_websocket().await?;
+        join(&mut ws).await;
+
+        flood(&broadcaster);
+        let dropped = broadcaster.dropped_events();
+        assert!(dropped > 0);
+
+        // every event that was not skipped is delivered, in order
+        let mut previous = None;
+        for _ in 0..64 - dropped {
+            let id = next_event(&mut ws).await.unwrap()["payload"]["id"].as_u64();
+            assert!(id > previous);
+            previous = id;
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn disconnect() {
+    let handler = channel(OpenChannel)
+        .with_outbound_capacity(2)
+        .with_slow_consumer_policy(SlowConsumerPolicy::Disconnect);
+    let broadcaster = handler.broadcaster();
+    let client = Client::new(ClientConfig::new());
+
+    with_server(handler, move |url| async move {
+        let mut ws = client.get(url).into_websocket().await?;
+        join(&mut ws).await;
+
+        flood(&broadcaster);
+        assert_eq!(broadcaster.evicted_clients(), 1);
+
+        let error = loop {
+            let event = next_event(&mut ws).await.unwrap();
+            if event["event"] == "phx_error" {
+                break event;
+            }
+        };
+
+        assert_eq!(error["topic"], "phoenix");
+        assert_eq!(error["payload"]["reason"], "slow_consumer");
+        assert!(next_event(&mut ws).await.is_none());
+
+        Ok(())
+    });
+}
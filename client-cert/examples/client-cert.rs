@@ -0,0 +1,36 @@
+use trillium::{Conn, PeerCertificates};
+use trillium_client_cert::ClientCertAuth;
+use trillium_rustls::{ClientAuth, RustlsAcceptor};
+
+const KEY: &[u8] = include_bytes!("../../rustls/examples/key.pem");
+const CERT: &[u8] = include_bytes!("../../rustls/examples/cert.pem");
+
+#[derive(Clone, Debug)]
+struct ClientCertificate(Vec<u8>);
+
+async fn hello(conn: Conn) -> Conn {
+    let len = conn.state::<ClientCertificate>().unwrap().0.len();
+    conn.ok(format!("hello, client with a {len} byte certificate"))
+}
+
+fn main() {
+    env_logger::init();
+    // try `CLIENT_CA=ca.pem cargo run --example client-cert`, and then
+    // `curl -k --cert client.pem --key client-key.pem https://localhost:8080`
+    let client_ca = std::fs::read(std::env::var("CLIENT_CA").expect("CLIENT_CA must be set"))
+        .expect("could not read CLIENT_CA");
+
+    trillium_smol::config()
+        .with_acceptor(RustlsAcceptor::from_single_cert_with_client_auth(
+            CERT,
+            KEY,
+            ClientAuth::required(&client_ca).expect("could not read client ca"),
+        ))
+        .run((
+            trillium_logger::logger(),
+            ClientCertAuth::new(|certificates: PeerCertificates| async move {
+                Some(ClientCertificate(certificates.end_entity().to_vec()))
+            }),
+            hello,
+        ));
+}
@@ -0,0 +1,129 @@
+/*!
+# Client certificate authentication for trillium.rs
+
+[`ClientCertAuth`] maps the certificate chain that a client presented
+during a mutual tls handshake to an application-specific identity,
+which is stored in the conn's state for subsequent handlers.
+
+Requests without a client certificate are halted with `401
+Unauthorized`, and requests with a certificate that does not map to an
+identity are halted with `403 Forbidden`.
+
+Certificates are verified during the tls handshake, so the acceptor
+must be configured to request them, for example with
+[`trillium_rustls::RustlsAcceptor::from_single_cert_with_client_auth`](https://docs.rs/trillium-rustls/latest/trillium_rustls/struct.RustlsAcceptor.html#method.from_single_cert_with_client_auth).
+This handler does not verify certificates itself.
+
+```
+use trillium::{Conn, PeerCertificates};
+use trillium_client_cert::ClientCertAuth;
+
+#[derive(Clone, Debug)]
+struct ServiceName(&'static str);
+
+const BILLING_SERVICE_CERT: &[u8] = b"(a der-encoded certificate)";
+
+let handler = (
+    ClientCertAuth::new(|certificates: PeerCertificates| async move {
+        // look up the certificate, or parse its subject, here
+        (certificates.end_entity() == BILLING_SERVICE_CERT).then_some(ServiceName("billing"))
+    }),
+    |conn: Conn| async move {
+        let ServiceName(name) = conn.state::<ServiceName>().unwrap().clone();
+        conn.ok(format!("hello, {name}"))
+    },
+);
+
+use trillium_testing::prelude::*;
+assert_status!(get("/").on(&handler), 401);
+```
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+use std::{
+    any::type_name,
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+use trillium::{async_trait, Conn, Handler, PeerCertificates, Status};
+
+type Mapper<Identity> = Arc<
+    dyn Fn(PeerCertificates) -> Pin<Box<dyn Future<Output = Option<Identity>> + Send + 'static>>
+        + Send
+        + Sync,
+>;
+
+/**
+Trillium handler that authenticates requests with client certificates
+
+See crate-level docs for an explanation
+*/
+pub struct ClientCertAuth<Identity> {
+    mapper: Mapper<Identity>,
+}
+
+impl<Identity> Clone for ClientCertAuth<Identity> {
+    fn clone(&self) -> Self {
+        Self {
+            mapper: Arc::clone(&self.mapper),
+        }
+    }
+}
+
+impl<Identity> Debug for ClientCertAuth<Identity> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCertAuth")
+            .field("identity", &type_name::<Identity>())
+            .finish()
+    }
+}
+
+impl<Identity> ClientCertAuth<Identity>
+where
+    Identity: Send + Sync + 'static,
+{
+    /**
+    build a new client certificate handler that calls the provided
+    async function with the certificate chain presented by each
+    client. If it resolves to `Some(identity)`, the identity is
+    inserted into the conn's state. If it resolves to `None`, the
+    request is halted with `403 Forbidden`.
+    */
+    pub fn new<F, Fut>(mapper: F) -> Self
+    where
+        F: Fn(PeerCertificates) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<Identity>> + Send + 'static,
+    {
+        Self {
+            mapper: Arc::new(move |certificates| Box::pin(mapper(certificates))),
+        }
+    }
+}
+
+#[async_trait]
+impl<Identity> Handler for ClientCertAuth<Identity>
+where
+    Identity: Send + Sync + 'static,
+{
+    async fn run(&self, conn: Conn) -> Conn {
+        let Some(certificates) = conn.peer_certificates() else {
+            return conn.with_status(Status::Unauthorized).halt();
+        };
+
+        match (self.mapper)(certificates).await {
+            Some(identity) => conn.with_state(identity),
+            None => conn.with_status(Status::Forbidden).halt(),
+        }
+    }
+}
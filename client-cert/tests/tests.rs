@@ -0,0 +1,154 @@
+use std::{error::Error, future::Future};
+use trillium::{Conn, PeerCertificates};
+use trillium_client::Client;
+use trillium_client_cert::ClientCertAuth;
+use trillium_rustls::{
+    crypto_provider,
+    rustls::{
+        pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+        ClientConfig, RootCertStore,
+    },
+    ClientAuth, RustlsAcceptor, RustlsConfig,
+};
+use trillium_testing::{block_on, client_config, config, prelude::*, TestCertificate, Url};
+
+#[derive(Clone, Debug)]
+struct ServiceName(&'static str);
+
+fn handler(known_client: &TestCertificate) -> impl trillium::Handler {
+    let known_client = known_client.cert_der().to_vec();
+    (
+        ClientCertAuth::new(move |certificates: PeerCertificates| {
+            let known = certificates.end_entity() == known_client;
+            async move { known.then_some(ServiceName("billing")) }
+        }),
+        |conn: Conn| async move {
+            let ServiceName(name) = conn.state::<ServiceName>().unwrap().clone();
+            conn.ok(format!("hello, {name}"))
+        },
+    )
+}
+
+struct Certificates {
+    server: TestCertificate,
+    // trusted by the server and mapped to an identity
+    known: TestCertificate,
+    // trusted by the server but not mapped to an identity
+    unknown: TestCertificate,
+    // not trusted by the server
+    untrusted: TestCertificate,
+}
+
+fn with_mtls_server<Fun, Fut>(client_auth: fn(&[u8]) -> std::io::Result<ClientAuth>, tests: Fun)
+where
+    Fun: FnOnce(Url, Certificates) -> Fut,
+    Fut: Future<Output = Result<(), Box<dyn Error>>>,
+{
+    block_on(async move {
+        let certificates = Certificates {
+            server: TestCertificate::generate(),
+            known: TestCertificate::generate(),
+            unknown: TestCertificate::generate(),
+            untrusted: TestCertificate::generate(),
+        };
+        let port = portpicker::pick_unused_port().expect("could not pick a port");
+        let url = format!("https://localhost:{port}").parse().unwrap();
+        let client_ca = format!(
+            "{}{}",
+            certificates.known.cert_pem(),
+            certificates.unknown.cert_pem()
+        );
+        let acceptor = RustlsAcceptor::from_single_cert_with_client_auth(
+            certificates.server.cert_pem().as_bytes(),
+            certificates.server.key_pem().as_bytes(),
+            client_auth(client_ca.as_bytes()).unwrap(),
+        );
+
+        let handle = config()
+            .with_host("localhost")
+            .with_port(port)
+            .with_acceptor(acceptor)
+            .spawn(handler(&certificates.known));
+        handle.info().await;
+        tests(url, certificates).await.unwrap();
+        handle.stop().await;
+    });
+}
+
+fn mtls_client(server: &TestCertificate, client: Option<&TestCertificate>) -> Client {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from(server.cert_der().to_vec()))
+        .unwrap();
+
+    let builder = ClientConfig::builder_with_provider(crypto_provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+
+    let rustls_config = match client {
+        Some(client) => builder
+            .with_client_auth_cert(
+                vec![CertificateDer::from(client.cert_der().to_vec())],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(client.key_der().to_vec())),
+            )
+            .unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+
+    Client::new(RustlsConfig::new(rustls_config, client_config()))
+}
+
+#[test]
+fn known_client_certificate() {
+    with_mtls_server(ClientAuth::required, |url, certs| async move {
+        let mut conn = mtls_client(&certs.server, Some(&certs.known))
+            .get(url)
+            .await?;
+        assert_eq!(conn.status(), Some(Status::Ok));
+        assert_eq!(conn.response_body().read_string().await?, "hello, billing");
+        Ok(())
+    });
+}
+
+#[test]
+fn unknown_client_certificate() {
+    with_mtls_server(ClientAuth::required, |url, certs| async move {
+        let conn = mtls_client(&certs.server, Some(&certs.unknown))
+            .get(url)
+            .await?;
+        assert_eq!(conn.status(), Some(Status::Forbidden));
+        Ok(())
+    });
+}
+
+#[test]
+fn untrusted_client_certificate() {
+    with_mtls_server(ClientAuth::optional, |url, certs| async move {
+        let result = mtls_client(&certs.server, Some(&certs.untrusted))
+            .get(url)
+            .await;
+        assert!(result.is_err());
+        Ok(())
+    });
+}
+
+#[test]
+fn missing_client_certificate() {
+    with_mtls_server(ClientAuth::optional, |url, certs| async move {
+        let conn = mtls_client(&certs.server, None).get(url).await?;
+        assert_eq!(conn.status(), Some(Status::Unauthorized));
+        Ok(())
+    });
+
+    with_mtls_server(ClientAuth::required, |url, certs| async move {
+        assert!(mtls_client(&certs.server, None).get(url).await.is_err());
+        Ok(())
+    });
+}
+
+#[test]
+fn without_tls() {
+    let client = TestCertificate::generate();
+    assert_status!(get("/").on(&handler(&client)), 401);
+}
@@ -0,0 +1,555 @@
+use crate::{async_trait, Conn};
+use dashmap::DashMap;
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use trillium_http::{
+    transport::BoxedTransport,
+    HeaderName, Headers,
+    KnownHeaderName::{
+        Age, Authorization, CacheControl, ContentLength, Date, Etag, Expires, IfModifiedSince,
+        IfNoneMatch, LastModified, TransferEncoding, Vary,
+    },
+    Method, ReceivedBodyState, Result, Status, Synthetic,
+};
+
+/**
+Whether a response was served from an [`HttpCache`]
+
+This is available with [`Conn::cache_status`] for conns sent by a
+[`Client`](crate::Client) that has a cache.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CacheStatus {
+    /// the response was sent by the origin server, and may have been
+    /// stored
+    Miss,
+
+    /// a fresh response was served from the cache without sending the
+    /// request
+    Hit,
+
+    /// a stale response was revalidated with the origin server, which
+    /// responded `304 Not Modified`, and the cached response was served
+    Revalidated,
+}
+
+/**
+A response stored in an [`HttpCache`]
+
+Cached responses are produced by the cache and are opaque to
+[`CacheStore`] implementations, which only need to retain and return
+them by key.
+*/
+#[derive(Clone, Debug)]
+pub struct CachedResponse {
+    status: Status,
+    headers: Headers,
+    body: Arc<Vec<u8>>,
+    stored_at: SystemTime,
+    vary: Vec<(HeaderName<'static>, Option<String>)>,
+}
+
+impl CachedResponse {
+    /// the response status
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// the response headers
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    /// the response body
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// the time that this response was received or last revalidated
+    pub fn stored_at(&self) -> SystemTime {
+        self.stored_at
+    }
+
+    fn matches_vary(&self, request_headers: &Headers) -> bool {
+        self.vary
+            .iter()
+            .all(|(name, value)| header_string(request_headers, name.clone()) == *value)
+    }
+
+    fn cache_control(&self) -> Directives {
+        Directives::parse(&self.headers)
+    }
+
+    fn age(&self) -> Duration {
+        let initial_age = self
+            .headers
+            .get_str(Age)
+            .and_then(|age| age.parse().ok())
+            .map_or(Duration::ZERO, Duration::from_secs);
+        initial_age + self.stored_at.elapsed().unwrap_or_default()
+    }
+
+    fn freshness_lifetime(&self, shared: bool) -> Duration {
+        let directives = self.cache_control();
+        if let Some(s_maxage) = directives.s_maxage.filter(|_| shared) {
+            return s_maxage;
+        }
+
+        if let Some(max_age) = directives.max_age {
+            return max_age;
+        }
+
+        let Some(expires) = self
+            .headers
+            .get_str(Expires)
+            .and_then(|expires| httpdate::parse_http_date(expires).ok())
+        else {
+            return Duration::ZERO;
+        };
+
+        let date = self
+            .headers
+            .get_str(Date)
+            .and_then(|date| httpdate::parse_http_date(date).ok())
+            .unwrap_or(self.stored_at);
+
+        expires.duration_since(date).unwrap_or_default()
+    }
+
+    fn has_validators(&self) -> bool {
+        self.headers.has_header(Etag) || self.headers.has_header(LastModified)
+    }
+
+    // merges the headers from a 304 response into the stored headers,
+    // as the origin may send updated freshness information
+    fn revalidated(&mut self, not_modified: &Headers) {
+        for (name, values) in not_modified {
+            if name != ContentLength && name != TransferEncoding {
+                self.headers
+                    .insert(name.clone().into_owned(), values.clone());
+            }
+        }
+        self.headers.remove(Age);
+        self.stored_at = SystemTime::now();
+    }
+
+    fn serve(&self, conn: &mut Conn, cache_status: CacheStatus) {
+        let mut headers = self.headers.clone();
+        if cache_status == CacheStatus::Hit {
+            headers.insert(Age, self.age().as_secs().to_string());
+        }
+
+        conn.status = Some(self.status);
+        conn.response_headers = headers;
+        conn.transport = Some(BoxedTransport::new(Synthetic::from(Vec::clone(&self.body))));
+        conn.buffer = Vec::new().into();
+        conn.response_body_state = ReceivedBodyState::Start;
+        conn.cache_status = Some(cache_status);
+    }
+}
+
+/**
+Storage for an [`HttpCache`]
+
+The default store is a [`MemoryStore`]. Implement this trait to share
+cached responses between clients in other ways.
+*/
+#[async_trait]
+pub trait CacheStore: Debug + Send + Sync + 'static {
+    /// retrieve the response stored with this key, if any
+    async fn get(&self, key: &str) -> Option<CachedResponse>;
+
+    /// store a response with this key, replacing any previous response
+    async fn insert(&self, key: &str, response: CachedResponse);
+
+    /// remove the response stored with this key, if any
+    async fn remove(&self, key: &str);
+}
+
+/**
+An in-memory [`CacheStore`], which is the default for [`HttpCache`]
+
+When the store is full, an arbitrary entry is removed to make room
+for each new entry.
+*/
+#[derive(Debug, Clone)]
+pub struct MemoryStore {
+    entries: Arc<DashMap<String, CachedResponse>>,
+    max_entries: usize,
+}
+
+impl Default for MemoryStore {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+impl MemoryStore {
+    /// builds a new memory store that holds at most this many responses
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Arc::new(DashMap::new()),
+            max_entries,
+        }
+    }
+
+    /// the number of responses currently stored
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// whether no responses are currently stored
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryStore {
+    async fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.entries.get(key).map(|entry| entry.value().clone())
+    }
+
+    async fn insert(&self, key: &str, response: CachedResponse) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        if self.entries.len() >= self.max_entries && !self.entries.contains_key(key) {
+            let evicted = self.entries.iter().next().map(|entry| entry.key().clone());
+            if let Some(evicted) = evicted {
+                self.entries.remove(&evicted);
+            }
+        }
+
+        self.entries.insert(key.to_string(), response);
+    }
+
+    async fn remove(&self, key: &str) {
+        self.entries.remove(key);
+    }
+}
+
+/**
+A client-side http cache, following a subset of [RFC
+9111](https://www.rfc-editor.org/rfc/rfc9111)
+
+Enable a cache for a [`Client`](crate::Client) with
+[`Client::with_cache`](crate::Client::with_cache). Only `GET` requests
+without a request body are cached. A response is stored if its status
+is cacheable, `Cache-Control` does not include `no-store`, it is not
+`Vary: *`, its body has a known length of at most the
+[maximum body length](HttpCache::with_max_body_len), and it either has
+explicit freshness information (`Cache-Control: max-age`, or
+`Expires`) or can be revalidated (`ETag` or `Last-Modified`).
+
+A fresh stored response is served without sending the request. A
+stale response with validators is revalidated with `If-None-Match`
+and `If-Modified-Since`, and served if the origin responds `304 Not
+Modified`. Stored responses are only served to requests with the same
+values for the request headers named in the response's `Vary` header,
+and for any headers added to the cache key with
+[`HttpCache::with_key_header`]. The request directives `no-store`,
+`no-cache`, and `max-age` are honored. Heuristic freshness is not
+implemented.
+
+```
+use trillium_client::{Client, HttpCache, MemoryStore};
+use trillium_smol::ClientConfig;
+
+let client = Client::new(ClientConfig::default()).with_cache(
+    HttpCache::new()
+        .with_store(MemoryStore::new(100))
+        .with_key_header("x-tenant-id"),
+);
+```
+*/
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+    store: Arc<dyn CacheStore>,
+    shared: bool,
+    key_headers: Vec<HeaderName<'static>>,
+    max_body_len: u64,
+}
+
+impl Default for HttpCache {
+    fn default() -> Self {
+        Self {
+            store: Arc::new(MemoryStore::default()),
+            shared: false,
+            key_headers: Vec::new(),
+            max_body_len: 1024 * 1024,
+        }
+    }
+}
+
+impl HttpCache {
+    /// builds a new private cache backed by a [`MemoryStore`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// chainable method to use this [`CacheStore`]
+    pub fn with_store(mut self, store: impl CacheStore) -> Self {
+        self.store = Arc::new(store);
+        self
+    }
+
+    /// chainable method to behave as a shared cache, as in a proxy
+    /// that serves many users. a shared cache does not store responses
+    /// marked `Cache-Control: private` or responses to requests with an
+    /// `Authorization` header unless they are marked `public`, and
+    /// prefers `s-maxage` to `max-age`
+    pub fn with_shared(mut self, shared: bool) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    /// chainable method to include this request header in the cache
+    /// key, in addition to the headers named by each response's `Vary`
+    /// header
+    pub fn with_key_header(mut self, name: impl Into<HeaderName<'static>>) -> Self {
+        self.key_headers.push(name.into());
+        self
+    }
+
+    /// chainable method to set the largest response body that will be
+    /// stored, in bytes. defaults to 1mb
+    pub fn with_max_body_len(mut self, max_body_len: u64) -> Self {
+        self.max_body_len = max_body_len;
+        self
+    }
+
+    /// borrow the [`CacheStore`] for this cache
+    pub fn store(&self) -> &Arc<dyn CacheStore> {
+        &self.store
+    }
+
+    fn key(&self, conn: &Conn) -> String {
+        let mut key = conn.url.to_string();
+        for name in &self.key_headers {
+            key.push('\n');
+            key.push_str(name.as_ref());
+            key.push(':');
+            if let Some(value) = header_string(&conn.request_headers, name.clone()) {
+                key.push_str(&value);
+            }
+        }
+        key
+    }
+
+    pub(crate) async fn exec(&self, conn: &mut Conn) -> Result<()> {
+        let request_directives = Directives::parse(&conn.request_headers);
+        if conn.method != Method::Get || conn.request_body.is_some() || request_directives.no_store
+        {
+            return conn.exec_network().await;
+        }
+
+        let key = self.key(conn);
+        let mut revalidating = None;
+
+        if let Some(cached) = self
+            .store
+            .get(&key)
+            .await
+            .filter(|cached| cached.matches_vary(&conn.request_headers))
+        {
+            let age = cached.age();
+            let fresh = age < cached.freshness_lifetime(self.shared)
+                && request_directives
+                    .max_age
+                    .is_none_or(|max_age| age <= max_age)
+                && !request_directives.no_cache
+                && !cached.cache_control().no_cache;
+
+            if fresh {
+                log::trace!("serving {} from cache", conn.url);
+                cached.serve(conn, CacheStatus::Hit);
+                return Ok(());
+            }
+
+            if cached.has_validators()
+                && !conn.request_headers.has_header(IfNoneMatch)
+                && !conn.request_headers.has_header(IfModifiedSince)
+            {
+                if let Some(etag) = cached.headers.get_values(Etag) {
+                    conn.request_headers.insert(IfNoneMatch, etag.clone());
+                }
+                if let Some(last_modified) = cached.headers.get_values(LastModified) {
+                    conn.request_headers
+                        .insert(IfModifiedSince, last_modified.clone());
+                }
+                revalidating = Some(cached);
+            }
+        }
+
+        let result = conn.exec_network().await;
+
+        if let Some(mut cached) = revalidating {
+            conn.request_headers.remove(IfNoneMatch);
+            conn.request_headers.remove(IfModifiedSince);
+            result?;
+
+            if conn.status == Some(Status::NotModified) {
+                log::trace!("revalidated cached response for {}", conn.url);
+                conn.recycle_transport().await;
+                cached.revalidated(&conn.response_headers);
+                cached.serve(conn, CacheStatus::Revalidated);
+                self.store.insert(&key, cached).await;
+                return Ok(());
+            }
+        } else {
+            result?;
+        }
+
+        conn.cache_status = Some(CacheStatus::Miss);
+
+        if let Some(vary) = self.storable(conn) {
+            let body = conn.response_body().read_bytes().await?;
+            conn.recycle_transport().await;
+            let mut headers = conn.response_headers.clone();
+            headers.remove(TransferEncoding);
+            headers.insert(ContentLength, body.len().to_string());
+
+            let cached = CachedResponse {
+                status: conn.status.unwrap_or(Status::Ok),
+                headers,
+                body: Arc::new(body),
+                stored_at: SystemTime::now(),
+                vary,
+            };
+
+            cached.serve(conn, CacheStatus::Miss);
+            self.store.insert(&key, cached).await;
+        } else if !matches!(conn.status, Some(status) if status.is_server_error()) {
+            // a response that cannot be stored replaces any stored
+            // response for this request
+            self.store.remove(&key).await;
+        }
+
+        Ok(())
+    }
+
+    // the request header values that this response varies on, if this
+    // response can be stored
+    fn storable(&self, conn: &Conn) -> Option<Vec<(HeaderName<'static>, Option<String>)>> {
+        let status = conn.status?;
+        if !matches!(
+            status,
+            Status::Ok
+                | Status::NonAuthoritativeInformation
+                | Status::MultipleChoice
+                | Status::MovedPermanently
+                | Status::PermanentRedirect
+                | Status::NotFound
+                | Status::Gone
+        ) {
+            return None;
+        }
+
+        let directives = Directives::parse(&conn.response_headers);
+        if directives.no_store
+            || (self.shared && directives.private)
+            || (self.shared && conn.request_headers.has_header(Authorization) && !directives.public)
+        {
+            return None;
+        }
+
+        if conn
+            .response_content_length()
+            .is_none_or(|len| len > self.max_body_len)
+        {
+            return None;
+        }
+
+        let has_freshness = (self.shared && directives.s_maxage.is_some())
+            || directives.max_age.is_some()
+            || conn.response_headers.has_header(Expires);
+        let has_validators = conn.response_headers.has_header(Etag)
+            || conn.response_headers.has_header(LastModified);
+        if !has_freshness && !has_validators {
+            return None;
+        }
+
+        let mut vary = Vec::new();
+        if let Some(values) = conn.response_headers.get_values(Vary) {
+            for name in values.iter().filter_map(|value| value.as_str()) {
+                for name in name
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                {
+                    if name == "*" {
+                        return None;
+                    }
+                    let name = HeaderName::from(name.to_ascii_lowercase());
+                    let value = header_string(&conn.request_headers, name.clone());
+                    vary.push((name, value));
+                }
+            }
+        }
+
+        Some(vary)
+    }
+}
+
+fn header_string(headers: &Headers, name: HeaderName<'_>) -> Option<String> {
+    headers.get_values(name).map(|values| {
+        values
+            .iter()
+            .filter_map(|value| value.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    })
+}
+
+#[derive(Debug, Default)]
+struct Directives {
+    no_store: bool,
+    no_cache: bool,
+    private: bool,
+    public: bool,
+    max_age: Option<Duration>,
+    s_maxage: Option<Duration>,
+}
+
+impl Directives {
+    fn parse(headers: &Headers) -> Self {
+        let mut directives = Self::default();
+        let Some(values) = headers.get_values(CacheControl) else {
+            return directives;
+        };
+
+        for directive in values
+            .iter()
+            .filter_map(|value| value.as_str())
+            .flat_map(|value| value.split(','))
+        {
+            let (name, argument) = match directive.split_once('=') {
+                Some((name, argument)) => (name.trim(), Some(argument.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+            let seconds = || {
+                argument
+                    .and_then(|argument| argument.parse().ok())
+                    .map(Duration::from_secs)
+            };
+
+            match &*name.to_ascii_lowercase() {
+                "no-store" => directives.no_store = true,
+                "no-cache" => directives.no_cache = true,
+                "private" => directives.private = true,
+                "public" => directives.public = true,
+                "max-age" => directives.max_age = seconds(),
+                "s-maxage" => directives.s_maxage = seconds(),
+                _ => {}
+            }
+        }
+
+        directives
+    }
+}
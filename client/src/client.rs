@@ -1,4 +1,4 @@
-use crate::{Conn, IntoUrl, Pool, USER_AGENT};
+use crate::{Conn, HttpCache, IntoUrl, Pool, ProxyConfig, RequestBuilder, USER_AGENT};
 use std::{fmt::Debug, sync::Arc};
 use trillium_http::{
     transport::BoxedTransport, HeaderName, HeaderValues, Headers, KnownHeaderName, Method,
@@ -20,6 +20,8 @@ pub struct Client {
     pool: Option<Pool<Origin, BoxedTransport>>,
     base: Option<Arc<Url>>,
     default_headers: Arc<Headers>,
+    proxy: Option<Arc<ProxyConfig>>,
+    cache: Option<HttpCache>,
 }
 
 macro_rules! method {
@@ -67,16 +69,55 @@ pub(crate) fn default_request_headers() -> Headers {
 }
 
 impl Client {
-    /// builds a new client from this `Connector`
+    /// builds a new client from this `Connector`, using any proxies
+    /// configured in the environment as described in
+    /// [`ProxyConfig::from_env`]
     pub fn new(config: impl Connector) -> Self {
+        let proxy = ProxyConfig::from_env();
         Self {
             config: config.arced(),
             pool: None,
             base: None,
             default_headers: Arc::new(default_request_headers()),
+            proxy: (!proxy.is_empty()).then(|| Arc::new(proxy)),
+            cache: None,
         }
     }
 
+    /**
+    chainable method to use the provided [`ProxyConfig`] instead of
+    proxies configured in the environment
+
+    ```
+    use trillium_client::{Client, ProxyConfig};
+    use trillium_smol::ClientConfig;
+
+    let client = Client::new(ClientConfig::default())
+        .with_proxy_config(ProxyConfig::new().with_proxy("http://proxy.internal:3128"));
+
+    assert_eq!(
+        client.proxy_config().unwrap().proxy_for(&"https://trillium.rs".parse().unwrap()).unwrap().as_str(),
+        "http://proxy.internal:3128/"
+    );
+    ```
+    */
+    pub fn with_proxy_config(mut self, proxy_config: ProxyConfig) -> Self {
+        self.proxy = (!proxy_config.is_empty()).then(|| Arc::new(proxy_config));
+        self
+    }
+
+    /// chainable method to connect directly, ignoring any proxies
+    /// configured in the environment
+    pub fn without_proxy(mut self) -> Self {
+        self.proxy = None;
+        self
+    }
+
+    /// borrow the [`ProxyConfig`] for this client, if it uses any proxies
+    pub fn proxy_config(&self) -> Option<&ProxyConfig> {
+        self.proxy.as_deref()
+    }
+
     /// chainable method to remove a header from default request headers
     pub fn without_default_header(mut self, name: impl Into<HeaderName<'static>>) -> Self {
         self.default_headers_mut().remove(name);
@@ -123,6 +164,32 @@ impl Client {
         self
     }
 
+    /**
+    chainable method to cache responses with the provided
+    [`HttpCache`]. clones of this client share the cache. see
+    [`HttpCache`] for details
+
+    ```
+    use trillium_client::{Client, HttpCache};
+    use trillium_smol::ClientConfig;
+
+    let client = Client::new(ClientConfig::default())
+        .with_default_pool()
+        .with_cache(HttpCache::new()); //<-
+
+    assert!(client.cache().is_some());
+    ```
+    */
+    pub fn with_cache(mut self, cache: HttpCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// borrow the [`HttpCache`] for this client, if it has one
+    pub fn cache(&self) -> Option<&HttpCache> {
+        self.cache.as_ref()
+    }
+
     /**
     builds a new conn.
 
@@ -147,9 +214,13 @@ impl Client {
         M: TryInto<Method>,
         <M as TryInto<Method>>::Error: Debug,
     {
+        self.conn(method.try_into().unwrap(), self.build_url(url).unwrap())
+    }
+
+    fn conn(&self, method: Method, url: Url) -> Conn {
         Conn {
-            url: self.build_url(url).unwrap(),
-            method: method.try_into().unwrap(),
+            url,
+            method,
             request_headers: Headers::clone(&self.default_headers),
             response_headers: Headers::new(),
             transport: None,
@@ -160,9 +231,47 @@ impl Client {
             response_body_state: ReceivedBodyState::Start,
             config: Arc::clone(&self.config),
             headers_finalized: false,
+            proxy: self.proxy.clone(),
+            cache: self.cache.clone(),
+            cache_status: None,
         }
     }
 
+    /**
+    builds a new conn from a [`RequestBuilder`], which can be awaited
+    to send it. the request's headers are applied after this client's
+    default headers, replacing any with the same name. the request's
+    url is used as-is, without regard to [`Client::base`].
+
+    ```
+    use trillium_smol::ClientConfig;
+    use trillium_client::{Client, RequestBuilder};
+    use trillium_testing::prelude::*;
+    let client = Client::new(ClientConfig::default());
+    let request = RequestBuilder::new("put", "http://trillium.rs/some/route")
+        .with_request_header("x-signature", "abc");
+
+    let conn = client.execute(request); //<-
+
+    assert_eq!(conn.method(), Method::Put);
+    assert_eq!(conn.url().as_str(), "http://trillium.rs/some/route");
+    assert_eq!(conn.request_headers().get_str("x-signature"), Some("abc"));
+    ```
+    */
+    pub fn execute(&self, request: RequestBuilder) -> Conn {
+        let RequestBuilder {
+            method,
+            url,
+            request_headers,
+            request_body,
+        } = request;
+
+        let mut conn = self.conn(method, url);
+        conn.request_headers.insert_all(request_headers);
+        conn.request_body = request_body;
+        conn
+    }
+
     /// borrow the connector for this client
     pub fn connector(&self) -> &Arc<dyn ObjectSafeConnector> {
         &self.config
@@ -1,4 +1,9 @@
-use crate::{pool::PoolEntry, util::encoding, Pool};
+use crate::{
+    pool::PoolEntry,
+    proxy::{proxy_authorization, tunnel},
+    util::encoding,
+    CacheStatus, HttpCache, Pool, ProxyConfig,
+};
 use encoding_rs::Encoding;
 use futures_lite::{future::poll_once, io, AsyncReadExt, AsyncWriteExt};
 use memchr::memmem::Finder;
@@ -15,11 +20,14 @@ use std::{
 use trillium_http::{
     transport::BoxedTransport,
     Body, Error, HeaderName, HeaderValue, HeaderValues, Headers,
-    KnownHeaderName::{Connection, ContentLength, Expect, Host, TransferEncoding},
+    KnownHeaderName::{
+        Connection, ContentLength, Expect, Host, ProxyAuthorization, TransferEncoding,
+        Upgrade as UpgradeHeader,
+    },
     Method, ReceivedBody, ReceivedBodyState, Result, StateSet, Status, Stopper, Upgrade,
 };
 use trillium_server_common::{
-    url::{Origin, Url},
+    url::{Origin, Position, Url},
     Connector, ObjectSafeConnector, Transport,
 };
 
@@ -61,6 +69,9 @@ pub struct Conn {
     pub(crate) response_body_state: ReceivedBodyState,
     pub(crate) config: Arc<dyn ObjectSafeConnector>,
     pub(crate) headers_finalized: bool,
+    pub(crate) proxy: Option<Arc<ProxyConfig>>,
+    pub(crate) cache: Option<HttpCache>,
+    pub(crate) cache_status: Option<CacheStatus>,
 }
 
 /// default http user-agent header
@@ -79,6 +90,9 @@ impl Debug for Conn {
             .field("buffer", &String::from_utf8_lossy(&self.buffer))
             .field("response_body_state", &self.response_body_state)
             .field("config", &self.config)
+            .field("proxy", &self.proxy)
+            .field("cache", &self.cache)
+            .field("cache_status", &self.cache_status)
             .finish()
     }
 }
@@ -512,6 +526,16 @@ impl Conn {
         }
     }
 
+    /**
+    Whether this response was served from the client's [`HttpCache`].
+    This is `None` for conns that have not been sent, and for conns
+    sent by a client without a cache or that the cache does not
+    apply to, such as requests with a method other than `GET`.
+    */
+    pub fn cache_status(&self) -> Option<CacheStatus> {
+        self.cache_status
+    }
+
     /// attempts to retrieve the connected peer address
     pub fn peer_addr(&self) -> Option<std::net::SocketAddr> {
         self.transport
@@ -584,7 +608,12 @@ impl Conn {
             )));
         }
 
-        let head = self.build_head().await?;
+        let route = self.proxy_route();
+        let forward_proxy = match &route {
+            ProxyRoute::Forward(proxy) => Some(proxy),
+            _ => None,
+        };
+        let head = self.build_head(forward_proxy).await?;
 
         let transport = match self.find_pool_candidate(&head).await? {
             Some(transport) => {
@@ -593,7 +622,14 @@ impl Conn {
             }
 
             None => {
-                let mut transport = Connector::connect(&self.config, &self.url).await?;
+                let mut transport = match &route {
+                    ProxyRoute::Direct => Connector::connect(&self.config, &self.url).await?,
+                    ProxyRoute::Forward(proxy) => Connector::connect(&self.config, proxy).await?,
+                    ProxyRoute::Tunnel(proxy) => {
+                        let tunnel = tunnel(&self.config, proxy, &self.url).await?;
+                        Connector::connect_over(&self.config, &self.url, tunnel).await?
+                    }
+                };
                 log::debug!("opened new connection to {:?}", transport.peer_addr()?);
                 transport.write_all(&head).await?;
                 transport
@@ -604,13 +640,31 @@ impl Conn {
         Ok(())
     }
 
-    async fn build_head(&mut self) -> Result<Vec<u8>> {
+    fn proxy_route(&self) -> ProxyRoute {
+        let Some(proxy) = self
+            .proxy
+            .as_ref()
+            .and_then(|proxy| proxy.proxy_for(&self.url))
+        else {
+            return ProxyRoute::Direct;
+        };
+
+        if self.url.scheme() == "https" || self.request_headers.has_header(UpgradeHeader) {
+            ProxyRoute::Tunnel(proxy.clone())
+        } else {
+            ProxyRoute::Forward(proxy.clone())
+        }
+    }
+
+    async fn build_head(&mut self, forward_proxy: Option<&Url>) -> Result<Vec<u8>> {
         let mut buf = Vec::with_capacity(128);
         let url = &self.url;
         let method = self.method;
         write!(buf, "{method} ")?;
 
-        if method == Method::Connect {
+        if forward_proxy.is_some() && method != Method::Connect {
+            write!(buf, "{}", &url[..Position::AfterQuery])?;
+        } else if method == Method::Connect {
             let host = url.host_str().ok_or(Error::UnexpectedUriFormat)?;
 
             let port = url
@@ -644,6 +698,10 @@ impl Conn {
             }
         }
 
+        if let Some(authorization) = forward_proxy.and_then(proxy_authorization) {
+            write!(buf, "{ProxyAuthorization}: {authorization}\r\n")?;
+        }
+
         write!(buf, "\r\n")?;
         log::trace!(
             "{}",
@@ -803,7 +861,30 @@ impl Conn {
         }
     }
 
+    // reads any remaining response body and returns the transport to
+    // the pool if possible, so that the response can be replaced with a
+    // cached response
+    pub(crate) async fn recycle_transport(&mut self) {
+        self.finish_reading_body().await;
+        let Some(transport) = self.transport.take() else {
+            return;
+        };
+
+        if self.is_keep_alive() && self.response_body_state == ReceivedBodyState::End {
+            if let (Some(pool), Ok(Some(_))) = (&self.pool, transport.peer_addr()) {
+                pool.insert(self.url.origin(), PoolEntry::new(transport, None));
+            }
+        }
+    }
+
     async fn exec(&mut self) -> Result<()> {
+        match self.cache.clone() {
+            Some(cache) => cache.exec(self).await,
+            None => self.exec_network().await,
+        }
+    }
+
+    pub(crate) async fn exec_network(&mut self) -> Result<()> {
         self.finalize_headers()?;
         self.connect_and_send_head().await?;
         self.send_body_and_parse_head().await?;
@@ -935,6 +1016,15 @@ impl<'conn> IntoFuture for &'conn mut Conn {
     }
 }
 
+// how a conn reaches its url
+enum ProxyRoute {
+    Direct,
+    // send the request to the proxy with the full url as the request target
+    Forward(Url),
+    // open a CONNECT tunnel through the proxy to the url
+    Tunnel(Url),
+}
+
 /// An unexpected http status code was received. Transform this back
 /// into the conn with [`From::from`]/[`Into::into`].
 ///
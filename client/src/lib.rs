@@ -25,6 +25,17 @@ tls crate such as
 [`trillium_rustls`](https://docs.trillium.rs/trillium_rustls) or
 [`trillium_native_tls`](https://docs.trillium.rs/trillium_native_tls).
 
+## Caching
+
+Clients can optionally cache responses according to http caching
+semantics. See [`HttpCache`] and [`Client::with_cache`].
+
+## Proxies
+
+By default, clients honor the `http_proxy`, `https_proxy`,
+`all_proxy`, and `no_proxy` environment variables. See
+[`ProxyConfig`] for details and for configuring proxies per client.
+
 See the documentation for [`Client`] and [`Conn`] for further usage
 examples.
 
@@ -66,3 +77,15 @@ pub fn client(connector: impl Connector) -> Client {
 
 mod into_url;
 pub use into_url::IntoUrl;
+
+mod url_template;
+pub use url_template::UrlTemplate;
+
+mod proxy;
+pub use proxy::ProxyConfig;
+
+mod request_builder;
+pub use request_builder::RequestBuilder;
+
+mod cache;
+pub use cache::{CacheStatus, CacheStore, CachedResponse, HttpCache, MemoryStore};
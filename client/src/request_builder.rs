@@ -0,0 +1,158 @@
+use crate::IntoUrl;
+use std::fmt::Debug;
+use trillium_http::{Body, HeaderName, HeaderValues, Headers, Method};
+use trillium_server_common::url::Url;
+
+/**
+A http request that is not yet associated with a [`Client`](crate::Client)
+
+A RequestBuilder can be constructed and passed around independently of
+any client, for example by sdk code that builds requests or by
+middleware that signs them, and then executed by any client with
+[`Client::execute`](crate::Client::execute).
+
+```
+use trillium_client::{Client, KnownHeaderName, RequestBuilder};
+use trillium_testing::prelude::*;
+
+fn sign(mut request: RequestBuilder) -> RequestBuilder {
+    let signature = format!("{} {}", request.method(), request.url().path());
+    request.request_headers_mut().insert("x-signature", signature);
+    request
+}
+
+let request = RequestBuilder::new("post", "http://example.com/items")
+    .with_request_header(KnownHeaderName::ContentType, "text/plain")
+    .with_body("new item");
+let request = sign(request);
+
+let handler = |conn: trillium::Conn| async move {
+    let signature = conn.request_headers().get_str("x-signature").unwrap_or_default().to_string();
+    conn.ok(signature)
+};
+
+let client = Client::new(trillium_testing::connector(handler));
+block_on(async move {
+    let mut conn = client.execute(request).await.unwrap();
+    assert_eq!(conn.response_body().read_string().await.unwrap(), "POST /items");
+});
+```
+*/
+#[derive(Debug)]
+pub struct RequestBuilder {
+    pub(crate) method: Method,
+    pub(crate) url: Url,
+    pub(crate) request_headers: Headers,
+    pub(crate) request_body: Option<Body>,
+}
+
+impl RequestBuilder {
+    /**
+    builds a new request with the provided method and absolute url
+
+    # Panics
+
+    Panics if the method is not a recognized http method or if the url
+    is not a valid absolute url
+    */
+    pub fn new<M>(method: M, url: impl IntoUrl) -> Self
+    where
+        M: TryInto<Method>,
+        <M as TryInto<Method>>::Error: Debug,
+    {
+        Self {
+            method: method.try_into().unwrap(),
+            url: url.into_url(None).unwrap(),
+            request_headers: Headers::new(),
+            request_body: None,
+        }
+    }
+
+    /// retrieves the method for this request
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// sets the method for this request
+    pub fn set_method(&mut self, method: Method) {
+        self.method = method;
+    }
+
+    /// retrieves the url for this request
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// mutably borrow the url for this request
+    pub fn url_mut(&mut self) -> &mut Url {
+        &mut self.url
+    }
+
+    /// borrow the request headers. these do not include the default
+    /// headers of the client that executes this request
+    pub fn request_headers(&self) -> &Headers {
+        &self.request_headers
+    }
+
+    /// mutably borrow the request headers
+    pub fn request_headers_mut(&mut self) -> &mut Headers {
+        &mut self.request_headers
+    }
+
+    /// chainable setter for [`inserting`](Headers::insert) a request header
+    pub fn with_request_header(
+        mut self,
+        name: impl Into<HeaderName<'static>>,
+        value: impl Into<HeaderValues>,
+    ) -> Self {
+        self.request_headers.insert(name, value);
+        self
+    }
+
+    /// chainable setter for `extending` request headers
+    pub fn with_request_headers<HN, HV, I>(mut self, headers: I) -> Self
+    where
+        I: IntoIterator<Item = (HN, HV)> + Send,
+        HN: Into<HeaderName<'static>>,
+        HV: Into<HeaderValues>,
+    {
+        self.request_headers.extend(headers);
+        self
+    }
+
+    /// chainable method to remove a request header if present
+    pub fn without_request_header(mut self, name: impl Into<HeaderName<'static>>) -> Self {
+        self.request_headers.remove(name);
+        self
+    }
+
+    /// borrow the request body, if one has been set. a static body can
+    /// be read with [`Body::static_bytes`], for example to compute a
+    /// content digest for a signature
+    pub fn request_body(&self) -> Option<&Body> {
+        self.request_body.as_ref()
+    }
+
+    /// sets the request body
+    pub fn set_request_body(&mut self, body: impl Into<Body>) {
+        self.request_body = Some(body.into());
+    }
+
+    /// chainable setter for the request body
+    pub fn with_body(mut self, body: impl Into<Body>) -> Self {
+        self.set_request_body(body);
+        self
+    }
+
+    /**
+    chainable setter for json body. this requires the `json` crate feature to be enabled.
+     */
+    #[cfg(feature = "json")]
+    pub fn with_json_body(self, body: &impl serde::Serialize) -> serde_json::Result<Self> {
+        use trillium_http::KnownHeaderName;
+
+        Ok(self
+            .with_body(serde_json::to_string(body)?)
+            .with_request_header(KnownHeaderName::ContentType, "application/json"))
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{Error, IntoUrl, Result};
+use std::{
+    borrow::Cow,
+    fmt::{Display, Write},
+};
+use trillium_server_common::url::Url;
+
+/**
+A url built from a template with `{name}` placeholders, each of which
+is replaced with a percent-encoded parameter value.
+
+Like a `&str`, the expanded template may be an absolute url or a
+reference relative to the [`Client::base`](crate::Client::base). A
+template can also carry its own base with [`UrlTemplate::with_base`],
+which takes precedence over the client's base for that request.
+
+```
+use trillium_client::{Client, UrlTemplate};
+use trillium_testing::ServerConnector;
+
+let client = Client::new(ServerConnector::new("ok")).with_base("https://api.example/v1/");
+
+let conn = client.get(
+    UrlTemplate::new("/users/{id}/files/{name}")
+        .with_param("id", 42)
+        .with_param("name", "a report?.pdf")
+        .with_query_param("download", true),
+);
+
+assert_eq!(
+    conn.url().as_str(),
+    "https://api.example/v1/users/42/files/a%20report%3F.pdf?download=true"
+);
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlTemplate {
+    template: Cow<'static, str>,
+    params: Vec<(Cow<'static, str>, String)>,
+    query: Vec<(String, String)>,
+    base: Option<Url>,
+}
+
+impl UrlTemplate {
+    /// Constructs a new url template. Placeholders are written as
+    /// `{name}`, and literal braces as `{{` and `}}`.
+    pub fn new(template: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            template: template.into(),
+            params: Vec::new(),
+            query: Vec::new(),
+            base: None,
+        }
+    }
+
+    /// Chainable method to provide the value for the `{name}`
+    /// placeholder. All characters other than ascii alphanumerics and
+    /// `-`, `.`, `_`, and `~` are percent-encoded, so a value always
+    /// occupies a single path segment.
+    pub fn with_param(mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        self.params.push((name.into(), value.to_string()));
+        self
+    }
+
+    /// Chainable method to append a form-urlencoded query parameter
+    /// after the template is expanded
+    pub fn with_query_param(mut self, name: impl Into<String>, value: impl Display) -> Self {
+        self.query.push((name.into(), value.to_string()));
+        self
+    }
+
+    /**
+    Chainable method to resolve this template against the provided
+    base instead of the client's base. As with
+    [`Client::with_base`](crate::Client::with_base), a trailing slash
+    is appended to the base if it does not have one.
+
+    # Panics
+
+    This will panic if the base cannot be parsed as a url.
+    */
+    pub fn with_base(mut self, base: impl IntoUrl) -> Self {
+        let mut base = base.into_url(None).unwrap();
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        self.base = Some(base);
+        self
+    }
+
+    /// Expands the placeholders of this template, returning an error
+    /// if the template is malformed or a placeholder has no value
+    pub fn expand(&self) -> Result<String> {
+        let mut expanded = String::with_capacity(self.template.len());
+        let mut chars = self.template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    expanded.push('{');
+                }
+
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    expanded.push('}');
+                }
+
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or(Error::UnexpectedUriFormat)?;
+                    let name = &rest[..end];
+                    let value = self
+                        .params
+                        .iter()
+                        .rev()
+                        .find_map(|(n, value)| (n == name).then_some(value))
+                        .ok_or(Error::UnexpectedUriFormat)?;
+                    encode(value, &mut expanded);
+                    chars = rest[end + 1..].chars();
+                }
+
+                '}' => return Err(Error::UnexpectedUriFormat),
+
+                c => expanded.push(c),
+            }
+        }
+
+        Ok(expanded)
+    }
+}
+
+fn encode(value: &str, out: &mut String) {
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(char::from(byte));
+        } else {
+            write!(out, "%{byte:02X}").unwrap();
+        }
+    }
+}
+
+impl IntoUrl for &UrlTemplate {
+    fn into_url(self, base: Option<&Url>) -> Result<Url> {
+        let mut url = self.expand()?.into_url(self.base.as_ref().or(base))?;
+        if !self.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&self.query);
+        }
+        Ok(url)
+    }
+}
+
+impl IntoUrl for UrlTemplate {
+    fn into_url(self, base: Option<&Url>) -> Result<Url> {
+        (&self).into_url(base)
+    }
+}
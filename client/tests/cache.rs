@@ -0,0 +1,120 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use test_harness::test;
+use trillium::{Conn, Handler, KnownHeaderName};
+use trillium_client::{CacheStatus, Client, HttpCache};
+use trillium_testing::{harness, ServerConnector, TestResult};
+
+fn counting(
+    handler: impl Fn(Conn) -> Conn + Send + Sync + 'static,
+) -> (Arc<AtomicUsize>, impl Handler) {
+    let count = Arc::new(AtomicUsize::new(0));
+    let counter = count.clone();
+    let handler = move |conn: Conn| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        let conn = handler(conn);
+        async move { conn }
+    };
+    (count, handler)
+}
+
+#[test(harness)]
+async fn serves_fresh_responses_from_cache() -> TestResult {
+    let (count, handler) = counting(|conn| {
+        conn.with_response_header(KnownHeaderName::CacheControl, "max-age=60")
+            .ok("cached body")
+    });
+    let client = Client::new(ServerConnector::new(handler)).with_cache(HttpCache::new());
+
+    let mut conn = client.get("http://example.com/").await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Miss));
+    assert_eq!(conn.response_body().await?, "cached body");
+
+    let mut conn = client.get("http://example.com/").await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Hit));
+    assert_eq!(
+        conn.response_headers().get_str(KnownHeaderName::Age),
+        Some("0")
+    );
+    assert_eq!(conn.response_body().await?, "cached body");
+    assert_eq!(count.load(Ordering::SeqCst), 1);
+
+    let conn = client
+        .get("http://example.com/")
+        .with_request_header(KnownHeaderName::CacheControl, "no-cache")
+        .await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Miss));
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    let conn = client.post("http://example.com/").await?;
+    assert_eq!(conn.cache_status(), None);
+    assert_eq!(count.load(Ordering::SeqCst), 3);
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn revalidates_with_etag() -> TestResult {
+    let (count, handler) = counting(|conn| {
+        let conn = conn.with_response_header(KnownHeaderName::Etag, "\"v1\"");
+        if conn.request_headers().get_str(KnownHeaderName::IfNoneMatch) == Some("\"v1\"") {
+            conn.with_status(304)
+        } else {
+            conn.ok("etagged body")
+        }
+    });
+    let client = Client::new(ServerConnector::new(handler)).with_cache(HttpCache::new());
+
+    let conn = client.get("http://example.com/").await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Miss));
+
+    let mut conn = client.get("http://example.com/").await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Revalidated));
+    assert_eq!(conn.status(), Some(trillium_client::Status::Ok));
+    assert!(!conn
+        .request_headers()
+        .has_header(KnownHeaderName::IfNoneMatch));
+    assert_eq!(conn.response_body().await?, "etagged body");
+    assert_eq!(count.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn respects_vary_and_no_store() -> TestResult {
+    let (count, handler) = counting(|conn| {
+        let language = conn
+            .request_headers()
+            .get_str(KnownHeaderName::AcceptLanguage)
+            .unwrap_or("none")
+            .to_string();
+        let cache_control = if conn.path() == "/secret" {
+            "no-store"
+        } else {
+            "max-age=60"
+        };
+        conn.with_response_header(KnownHeaderName::CacheControl, cache_control)
+            .with_response_header(KnownHeaderName::Vary, "Accept-Language")
+            .ok(language)
+    });
+    let client = Client::new(ServerConnector::new(handler)).with_cache(HttpCache::new());
+
+    for (language, expected_count) in [("en", 1), ("en", 1), ("fr", 2), ("fr", 2), ("en", 3)] {
+        let mut conn = client
+            .get("http://example.com/")
+            .with_request_header(KnownHeaderName::AcceptLanguage, language)
+            .await?;
+        assert_eq!(conn.response_body().await?, language);
+        assert_eq!(count.load(Ordering::SeqCst), expected_count);
+    }
+
+    let conn = client.get("http://example.com/secret").await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Miss));
+    let conn = client.get("http://example.com/secret").await?;
+    assert_eq!(conn.cache_status(), Some(CacheStatus::Miss));
+    assert_eq!(count.load(Ordering::SeqCst), 5);
+
+    Ok(())
+}
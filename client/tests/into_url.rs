@@ -3,7 +3,7 @@ use std::{
     str::FromStr,
 };
 
-use trillium_client::{IntoUrl, Url};
+use trillium_client::{IntoUrl, Url, UrlTemplate};
 
 #[test]
 fn socket_addr() {
@@ -78,3 +78,61 @@ fn ip_addr() {
         .into_url(Some(&Url::parse("http://_").unwrap()))
         .is_err());
 }
+
+#[test]
+fn url_template() {
+    let base = Url::parse("https://api.example/v1/").unwrap();
+
+    assert_eq!(
+        UrlTemplate::new("/users/{id}/posts/{post}")
+            .with_param("id", 10)
+            .with_param("post", "a/b c")
+            .into_url(Some(&base))
+            .unwrap()
+            .as_str(),
+        "https://api.example/v1/users/10/posts/a%2Fb%20c"
+    );
+
+    assert_eq!(
+        UrlTemplate::new("https://api.example/v1/search?q={q}")
+            .with_param("q", "&ü#")
+            .with_query_param("page", 2)
+            .into_url(Some(&base))
+            .unwrap()
+            .as_str(),
+        "https://api.example/v1/search?q=%26%C3%BC%23&page=2"
+    );
+
+    assert_eq!(
+        UrlTemplate::new("{{literal}}/{x}")
+            .with_param("x", "..")
+            .expand()
+            .unwrap(),
+        "{literal}/.."
+    );
+
+    let template = UrlTemplate::new("users/{id}")
+        .with_param("id", 1)
+        .with_base("https://other.example/v2");
+    assert_eq!(
+        (&template).into_url(Some(&base)).unwrap().as_str(),
+        "https://other.example/v2/users/1"
+    );
+    assert_eq!(
+        template.into_url(None).unwrap().as_str(),
+        "https://other.example/v2/users/1"
+    );
+
+    assert!(UrlTemplate::new("/users/{id}")
+        .into_url(Some(&base))
+        .is_err());
+    assert!(UrlTemplate::new("/users/{id").expand().is_err());
+    assert!(UrlTemplate::new("/users/id}").expand().is_err());
+    assert!(UrlTemplate::new("/users/{id}")
+        .with_param("id", 1)
+        .into_url(None)
+        .is_err());
+    assert!(UrlTemplate::new("https://elsewhere.example/")
+        .into_url(Some(&base))
+        .is_err());
+}
@@ -0,0 +1,173 @@
+use futures_lite::{future, io, AsyncWriteExt};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Method, Status, Upgrade};
+use trillium_client::{Client, Connector, ProxyConfig};
+use trillium_http::transport::BoxedTransport;
+use trillium_testing::{with_server, with_tls_server, ClientConfig, Url};
+
+// a forward proxy that responds to requests itself and tunnels CONNECT
+// requests to the requested authority
+#[derive(Debug, Default, Clone)]
+struct TestProxy {
+    connects: Arc<AtomicUsize>,
+}
+
+struct Tunnel(BoxedTransport);
+
+#[async_trait]
+impl Handler for TestProxy {
+    async fn run(&self, conn: Conn) -> Conn {
+        let authorization = conn
+            .request_headers()
+            .get_str(KnownHeaderName::ProxyAuthorization)
+            .unwrap_or("none")
+            .to_string();
+
+        if conn.method() != Method::Connect {
+            let body = format!("{} {authorization}", conn.inner().path_and_query());
+            return conn.ok(body);
+        }
+
+        self.connects.fetch_add(1, Ordering::SeqCst);
+        if authorization == "Basic cmVqZWN0Og==" {
+            return conn.with_status(Status::ProxyAuthenticationRequired).halt();
+        }
+
+        let url = Url::parse(&format!("http://{}", conn.path())).unwrap();
+        let upstream = Connector::connect(&ClientConfig::default(), &url)
+            .await
+            .unwrap();
+        conn.with_status(Status::Ok)
+            .with_state(Tunnel(BoxedTransport::new(upstream)))
+            .halt()
+    }
+
+    fn has_upgrade(&self, upgrade: &Upgrade) -> bool {
+        upgrade.state.contains::<Tunnel>()
+    }
+
+    async fn upgrade(&self, mut upgrade: Upgrade) {
+        let Tunnel(upstream) = upgrade.state.take().unwrap();
+        let (mut upstream_read, mut upstream_write) = io::split(upstream);
+        let (mut downstream_read, mut downstream_write) = io::split(upgrade);
+        future::race(
+            async {
+                io::copy(&mut upstream_read, &mut downstream_write)
+                    .await
+                    .ok();
+                downstream_write.close().await.ok();
+            },
+            async {
+                io::copy(&mut downstream_read, &mut upstream_write)
+                    .await
+                    .ok();
+                upstream_write.close().await.ok();
+            },
+        )
+        .await;
+    }
+}
+
+#[test]
+fn http_requests_are_forwarded_to_the_proxy() {
+    with_server(TestProxy::default(), |proxy_url| async move {
+        let client = Client::new(ClientConfig::new())
+            .with_proxy_config(ProxyConfig::new().with_http_proxy(proxy_url.clone()));
+
+        let mut conn = client.get("http://example.com/some/path?query").await?;
+        assert_eq!(
+            conn.response_body().read_string().await?,
+            "http://example.com/some/path?query none"
+        );
+
+        let mut authenticated_proxy = proxy_url.clone();
+        authenticated_proxy.set_username("user").unwrap();
+        authenticated_proxy.set_password(Some("p%40ss")).unwrap();
+        let client = Client::new(ClientConfig::new())
+            .with_proxy_config(ProxyConfig::new().with_http_proxy(authenticated_proxy));
+        let mut conn = client.get("http://example.com:8080/").await?;
+        assert_eq!(
+            conn.response_body().read_string().await?,
+            "http://example.com:8080/ Basic dXNlcjpwQHNz"
+        );
+
+        let client = Client::new(ClientConfig::new()).with_proxy_config(
+            ProxyConfig::new()
+                .with_http_proxy("http://unused.invalid")
+                .with_no_proxy("localhost"),
+        );
+        let mut conn = client.get(proxy_url).await?;
+        assert_eq!(conn.response_body().read_string().await?, "/ none");
+
+        Ok(())
+    });
+}
+
+#[test]
+fn https_requests_are_tunneled_through_the_proxy() {
+    let target = |conn: Conn| async move {
+        let body = format!("secure: {} {}", conn.is_secure(), conn.path());
+        conn.ok(body)
+    };
+
+    with_tls_server(target, |url, client_config| async move {
+        let proxy = TestProxy::default();
+        let connects = proxy.connects.clone();
+        let port = portpicker::pick_unused_port().unwrap();
+        let handle = trillium_testing::config()
+            .with_host("localhost")
+            .with_port(port)
+            .spawn(proxy);
+        handle.info().await;
+
+        let client = Client::new(client_config).with_proxy_config(
+            ProxyConfig::new().with_https_proxy(format!("http://localhost:{port}").as_str()),
+        );
+
+        let mut conn = client.get(url.join("/tunneled")?).await?;
+        assert_eq!(
+            conn.response_body().read_string().await?,
+            "secure: true /tunneled"
+        );
+        assert_eq!(connects.load(Ordering::SeqCst), 1);
+
+        let rejected = client.with_proxy_config(
+            ProxyConfig::new().with_https_proxy(format!("http://reject@localhost:{port}").as_str()),
+        );
+        let error = rejected.get(url).await.unwrap_err();
+        assert!(error.to_string().contains("407"), "{error}");
+        assert_eq!(connects.load(Ordering::SeqCst), 2);
+
+        handle.stop().await;
+        Ok(())
+    });
+}
+
+#[test]
+fn environment_variables() {
+    with_server(TestProxy::default(), |proxy_url| async move {
+        std::env::set_var("http_proxy", proxy_url.as_str());
+        std::env::set_var("no_proxy", "excluded.example.com");
+        let client = Client::new(ClientConfig::new());
+        std::env::remove_var("http_proxy");
+        std::env::remove_var("no_proxy");
+
+        let mut conn = client.get("http://example.com/").await?;
+        assert_eq!(
+            conn.response_body().read_string().await?,
+            "http://example.com/ none"
+        );
+
+        let config = client.proxy_config().unwrap();
+        assert!(config
+            .proxy_for(&"http://excluded.example.com".parse()?)
+            .is_none());
+        assert!(config.proxy_for(&"https://example.com".parse()?).is_none());
+
+        assert!(client.without_proxy().proxy_config().is_none());
+        Ok(())
+    });
+}
@@ -0,0 +1,71 @@
+use test_harness::test;
+use trillium_client::{
+    Client,
+    KnownHeaderName::{Accept, ContentLength, Host, UserAgent},
+    Method, RequestBuilder,
+};
+use trillium_testing::{harness, ServerConnector, TestResult};
+
+async fn echo(mut conn: trillium::Conn) -> trillium::Conn {
+    let body = conn.request_body_string().await.unwrap();
+    let response = format!("{} {} {body}", conn.method(), conn.path());
+    conn.ok(response)
+}
+
+#[test(harness)]
+async fn execute_request_builder() -> TestResult {
+    let client = Client::new(ServerConnector::new(echo)).with_default_header(UserAgent, "client");
+
+    let request = RequestBuilder::new(Method::Post, "http://example.com/items?page=2")
+        .with_request_header(Accept, "application/json")
+        .with_request_header("x-signature", "signed")
+        .with_body("new item");
+
+    assert_eq!(request.request_headers().len(), 2);
+    assert_eq!(
+        request.request_body().and_then(|body| body.static_bytes()),
+        Some(&b"new item"[..])
+    );
+
+    let mut conn = client.execute(request).await?;
+    let request_headers = conn.request_headers();
+    assert_eq!(request_headers.get_str(UserAgent), Some("client"));
+    assert_eq!(request_headers.get_str(Accept), Some("application/json"));
+    assert_eq!(request_headers.get_str("x-signature"), Some("signed"));
+    assert_eq!(request_headers.get_str(Host), Some("example.com"));
+    assert_eq!(request_headers.get_str(ContentLength), Some("8"));
+    assert_eq!(
+        conn.response_body().read_string().await?,
+        "POST /items new item"
+    );
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn request_builder_ignores_client_base() -> TestResult {
+    let client = Client::new(ServerConnector::new(echo)).with_base("http://api.example.com/v1/");
+    let request = RequestBuilder::new("get", "http://other.example.com/status");
+
+    let mut conn = client.execute(request).await?;
+    assert_eq!(conn.url().as_str(), "http://other.example.com/status");
+    assert_eq!(conn.response_body().read_string().await?, "GET /status ");
+    Ok(())
+}
+
+#[test(harness)]
+async fn reusable_across_clients() -> TestResult {
+    let build = || RequestBuilder::new("delete", "http://example.com/items/1");
+
+    for client in [
+        Client::new(ServerConnector::new(echo)),
+        Client::new(ServerConnector::new(echo)).with_default_pool(),
+    ] {
+        let mut conn = client.execute(build()).await?;
+        assert_eq!(
+            conn.response_body().read_string().await?,
+            "DELETE /items/1 "
+        );
+    }
+    Ok(())
+}
@@ -6,6 +6,11 @@ the zstd, brotli, and gzip algorithms (in order of preference),
 although more algorithms may be added in the future. The correct
 algorithm will be selected based on the Accept-Encoding header sent by
 the client, if one exists.
+
+Compression is cpu-intensive, and under load spikes can starve the
+executor. [`Compression::with_max_concurrent`] and
+[`Compression::with_max_size`] can be used to send some responses
+uncompressed instead, trading bandwidth for cpu time.
 */
 #![forbid(unsafe_code)]
 #![deny(
@@ -18,6 +23,7 @@ the client, if one exists.
 #![warn(missing_docs)]
 
 use async_compression::futures::bufread::{BrotliEncoder, GzipEncoder, ZstdEncoder};
+use async_lock::Semaphore;
 use futures_lite::{
     io::{BufReader, Cursor},
     AsyncReadExt,
@@ -26,6 +32,7 @@ use std::{
     collections::BTreeSet,
     fmt::{self, Display, Formatter},
     str::FromStr,
+    sync::Arc,
 };
 use trillium::{
     async_trait, conn_try, conn_unwrap, Body, Conn, Handler, HeaderValues,
@@ -94,6 +101,8 @@ Trillium handler for compression
 #[derive(Clone, Debug)]
 pub struct Compression {
     algorithms: BTreeSet<CompressionAlgorithm>,
+    semaphore: Option<Arc<Semaphore>>,
+    max_size: Option<u64>,
 }
 
 impl Default for Compression {
@@ -101,6 +110,8 @@ impl Default for Compression {
         use CompressionAlgorithm::*;
         Self {
             algorithms: [Zstd, Brotli, Gzip].into_iter().collect(),
+            semaphore: None,
+            max_size: None,
         }
     }
 }
@@ -125,6 +136,42 @@ impl Compression {
         self
     }
 
+    /**
+    limits the number of responses that may be compressed at the
+    same time. when this many responses are already being compressed,
+    additional responses are sent uncompressed instead of waiting.
+    a streaming response counts toward this limit until it has been
+    fully sent.
+
+    clones of this handler share the same limit.
+
+    ```
+    use trillium_compression::Compression;
+    use trillium::KnownHeaderName::{AcceptEncoding, ContentEncoding};
+    use trillium_testing::prelude::*;
+
+    let handler = (Compression::new().with_max_concurrent(0), "a".repeat(100));
+    assert_headers!(
+        get("/").with_request_header(AcceptEncoding, "gzip").on(&handler),
+        ContentEncoding => None
+    );
+    ```
+    */
+    pub fn with_max_concurrent(mut self, max_concurrent: usize) -> Self {
+        self.semaphore = Some(Arc::new(Semaphore::new(max_concurrent)));
+        self
+    }
+
+    /**
+    sends responses with a known length greater than `max_size` bytes
+    uncompressed. streaming bodies with an unknown length are not
+    affected by this limit.
+    */
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
     fn negotiate(&self, header: &str) -> Option<CompressionAlgorithm> {
         parse_accept_encoding(header)
             .into_iter()
@@ -182,6 +229,27 @@ impl Handler for Compression {
             let mut body = conn_unwrap!(conn.inner_mut().take_response_body(), conn);
             let mut compression_used = false;
 
+            if let Some(len) = body
+                .len()
+                .filter(|&len| self.max_size.is_some_and(|max| len > max))
+            {
+                log::trace!("not compressing {len} byte body because it exceeds max size");
+                return conn.with_body(body);
+            }
+
+            let permit = match &self.semaphore {
+                Some(semaphore) => match semaphore.try_acquire_arc() {
+                    Some(permit) => Some(permit),
+                    None => {
+                        log::debug!(
+                            "not compressing body because max concurrent compressions reached"
+                        );
+                        return conn.with_body(body);
+                    }
+                },
+                None => None,
+            };
+
             if body.is_static() {
                 match algo {
                     CompressionAlgorithm::Zstd => {
@@ -246,6 +314,10 @@ impl Handler for Compression {
                 }
             }
 
+            if let Some(permit) = permit.filter(|_| body.is_streaming()) {
+                conn.inner_mut().after_send(move |_| drop(permit));
+            }
+
             if compression_used {
                 let vary = conn
                     .response_headers()
@@ -87,3 +87,68 @@ fn test() {
         ContentEncoding => "zstd"
     );
 }
+
+#[test]
+fn max_size() {
+    let handler = (
+        trillium_compression::compression().with_max_size(499),
+        COMPRESSIBLE_CONTENT,
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentLength => "500",
+        ContentEncoding => None
+    );
+
+    let handler = (
+        trillium_compression::compression().with_max_size(500),
+        COMPRESSIBLE_CONTENT,
+    );
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentLength => "77",
+        ContentEncoding => "gzip"
+    );
+}
+
+#[test]
+fn max_concurrent() {
+    let handler = (
+        trillium_compression::compression().with_max_concurrent(1),
+        |conn: trillium::Conn| async move {
+            conn.ok(trillium::Body::new_streaming(
+                futures_lite::io::Cursor::new(COMPRESSIBLE_CONTENT),
+                None,
+            ))
+        },
+    );
+
+    // a streaming body holds its permit until the response is sent,
+    // which for a TestConn is when it is dropped
+    let held = get("/")
+        .with_request_header(AcceptEncoding, "gzip")
+        .on(&handler);
+    assert_headers!(&held, ContentEncoding => "gzip");
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentEncoding => None
+    );
+
+    drop(held);
+
+    assert_headers!(
+        get("/")
+            .with_request_header(AcceptEncoding, "gzip")
+            .on(&handler),
+        ContentEncoding => "gzip"
+    );
+}
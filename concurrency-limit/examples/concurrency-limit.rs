@@ -0,0 +1,18 @@
+use std::time::Duration;
+use trillium::Conn;
+use trillium_concurrency_limit::ConcurrencyLimit;
+
+async fn slow(conn: Conn) -> Conn {
+    trillium_smol::async_io::Timer::after(Duration::from_secs(1)).await;
+    conn.ok("made it!")
+}
+
+fn main() {
+    // try making more than five concurrent requests
+    trillium_smol::run((
+        ConcurrencyLimit::new(5)
+            .with_queue_timeout(Duration::from_millis(500))
+            .with_retry_after(Duration::from_secs(2)),
+        slow,
+    ));
+}
@@ -0,0 +1,243 @@
+/*!
+# Trillium handler to limit concurrent requests and shed excess load
+
+[`ConcurrencyLimit`] caps the number of requests that may be in flight
+at once. A request is considered to be in flight from the time that
+it reaches this handler until its response has been sent. When the
+limit is reached, additional requests are rejected with a `503 Service
+Unavailable` and a `Retry-After` header, or, if a queue timeout is
+configured with [`ConcurrencyLimit::with_queue_timeout`], wait up to
+that long for capacity before being rejected.
+
+Limits can be applied globally, or separately for each key returned by
+a function of the conn with [`ConcurrencyLimit::with_key`].
+
+```
+use trillium_concurrency_limit::ConcurrencyLimit;
+use std::time::Duration;
+
+let handler = (
+    ConcurrencyLimit::new(100)
+        .with_queue_timeout(Duration::from_millis(250))
+        .with_retry_after(Duration::from_secs(5)),
+    "ok",
+);
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/").on(&handler), "ok");
+```
+
+This is a more flexible alternative to
+[`Config::with_max_connections`](https://docs.trillium.rs/trillium_server_common/struct.config#method.with_max_connections),
+which counts connections instead of requests and always responds with
+the same pre-rendered response.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+use async_io::Timer;
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use futures_lite::future;
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName::RetryAfter, Status};
+
+type KeyFn = Box<dyn Fn(&Conn) -> Option<String> + Send + Sync + 'static>;
+
+/**
+Trillium handler that limits the number of concurrent in-flight requests
+
+See crate-level docs for an explanation
+*/
+pub struct ConcurrencyLimit {
+    max: usize,
+    global: Arc<Semaphore>,
+    key_fn: Option<KeyFn>,
+    keyed: Mutex<HashMap<String, Arc<Semaphore>>>,
+    queue_timeout: Option<Duration>,
+    retry_after: Option<Duration>,
+    response: Box<dyn Handler>,
+}
+
+impl Debug for ConcurrencyLimit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConcurrencyLimit")
+            .field("max", &self.max)
+            .field("keyed", &self.key_fn.is_some())
+            .field("queue_timeout", &self.queue_timeout)
+            .field("retry_after", &self.retry_after)
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+/// Alias for [`ConcurrencyLimit::new`]
+pub fn concurrency_limit(max: usize) -> ConcurrencyLimit {
+    ConcurrencyLimit::new(max)
+}
+
+impl ConcurrencyLimit {
+    /// Constructs a new ConcurrencyLimit that allows at most `max`
+    /// requests to be in flight at once.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            global: Arc::new(Semaphore::new(max)),
+            key_fn: None,
+            keyed: Mutex::new(HashMap::new()),
+            queue_timeout: None,
+            retry_after: Some(Duration::from_secs(1)),
+            response: Box::new(Status::ServiceUnavailable),
+        }
+    }
+
+    /**
+    Applies the limit separately to each distinct key returned by the
+    provided function, instead of to all requests. Requests for which
+    the function returns None are not limited.
+
+    ```
+    use trillium_concurrency_limit::ConcurrencyLimit;
+    // at most ten concurrent requests per path, and no limit on GETs
+    let handler = ConcurrencyLimit::new(10).with_key(|conn| {
+        (conn.method() != trillium::Method::Get).then(|| conn.path().to_string())
+    });
+    ```
+    */
+    pub fn with_key<F>(mut self, key_fn: F) -> Self
+    where
+        F: Fn(&Conn) -> Option<String> + Send + Sync + 'static,
+    {
+        self.key_fn = Some(Box::new(key_fn));
+        self
+    }
+
+    /// Instead of immediately rejecting requests when the limit is
+    /// reached, wait up to this long for another request to complete.
+    /// The default is to not wait.
+    pub fn with_queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = Some(queue_timeout);
+        self
+    }
+
+    /// Sets the duration sent in the `Retry-After` header of rejected
+    /// requests, rounded up to whole seconds. The default is one second.
+    pub fn with_retry_after(mut self, retry_after: Duration) -> Self {
+        self.retry_after = Some(retry_after);
+        self
+    }
+
+    /// Do not send a `Retry-After` header with rejected requests
+    pub fn without_retry_after(mut self) -> Self {
+        self.retry_after = None;
+        self
+    }
+
+    /**
+    Replaces the default `503 Service Unavailable` response with a
+    custom handler. The `Retry-After` header will already be set when
+    this handler is run, and the conn will be halted after it is run.
+
+    ```
+    use trillium::{Conn, Status};
+    use trillium_concurrency_limit::ConcurrencyLimit;
+    let handler = ConcurrencyLimit::new(0).with_response(|conn: Conn| async move {
+        conn.with_status(Status::TooManyRequests).with_body("slow down")
+    });
+
+    use trillium_testing::prelude::*;
+    assert_response!(
+        get("/").on(&handler),
+        Status::TooManyRequests,
+        "slow down",
+        "retry-after" => "1"
+    );
+    ```
+    */
+    pub fn with_response(mut self, response: impl Handler) -> Self {
+        self.response = Box::new(response);
+        self
+    }
+
+    /// The configured maximum number of concurrent requests
+    pub fn max(&self) -> usize {
+        self.max
+    }
+
+    fn semaphore(&self, conn: &Conn) -> Option<Arc<Semaphore>> {
+        let Some(key_fn) = &self.key_fn else {
+            return Some(Arc::clone(&self.global));
+        };
+
+        let key = key_fn(conn)?;
+        let mut keyed = self.keyed.lock().unwrap();
+        if let Some(semaphore) = keyed.get(&key) {
+            return Some(Arc::clone(semaphore));
+        }
+
+        // a semaphore that is only referenced by this map has no
+        // outstanding permits, so we can clean it up
+        keyed.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        let semaphore = Arc::new(Semaphore::new(self.max));
+        keyed.insert(key, Arc::clone(&semaphore));
+        Some(semaphore)
+    }
+
+    async fn acquire(&self, semaphore: Arc<Semaphore>) -> Option<SemaphoreGuardArc> {
+        if let Some(permit) = semaphore.try_acquire_arc() {
+            return Some(permit);
+        }
+
+        let queue_timeout = self.queue_timeout?;
+        future::or(async { Some(semaphore.acquire_arc().await) }, async {
+            Timer::after(queue_timeout).await;
+            None
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl Handler for ConcurrencyLimit {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        let Some(semaphore) = self.semaphore(&conn) else {
+            return conn;
+        };
+
+        if let Some(permit) = self.acquire(semaphore).await {
+            conn.inner_mut().after_send(move |_| drop(permit));
+            return conn;
+        }
+
+        log::debug!(
+            "shedding {} {}: concurrency limit of {} reached",
+            conn.method(),
+            conn.path(),
+            self.max
+        );
+
+        if let Some(retry_after) = self.retry_after {
+            let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+            conn.response_headers_mut()
+                .insert(RetryAfter, seconds.to_string());
+        }
+
+        self.response.run(conn).await.halt()
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        self.response.init(info).await;
+    }
+}
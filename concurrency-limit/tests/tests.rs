@@ -0,0 +1,100 @@
+use async_io::Timer;
+use std::time::Duration;
+use test_harness::test;
+use trillium::{Conn, Method, Status};
+use trillium_concurrency_limit::ConcurrencyLimit;
+use trillium_testing::{harness, prelude::*, TestConn};
+
+fn app(limit: ConcurrencyLimit) -> impl trillium::Handler {
+    (limit, "ok")
+}
+
+#[test]
+fn limits_in_flight_requests() {
+    let app = app(ConcurrencyLimit::new(2));
+
+    // the permit is held until the response is sent, which for a
+    // TestConn is when it is dropped
+    let first = get("/").on(&app);
+    let second = get("/").on(&app);
+    assert_response!(
+        get("/").on(&app),
+        Status::ServiceUnavailable,
+        "",
+        "retry-after" => "1"
+    );
+
+    assert_ok!(first, "ok");
+    let third = get("/").on(&app);
+    assert_status!(get("/").on(&app), 503);
+
+    drop((second, third));
+    assert_ok!(get("/").on(&app), "ok");
+}
+
+#[test]
+fn retry_after_configuration() {
+    let app = app(ConcurrencyLimit::new(0).with_retry_after(Duration::from_millis(1500)));
+    assert_response!(get("/").on(&app), 503, "", "retry-after" => "2");
+
+    let app = self::app(ConcurrencyLimit::new(0).without_retry_after());
+    let conn = get("/").on(&app);
+    assert_status!(&conn, 503);
+    assert!(conn.response_headers().get("retry-after").is_none());
+}
+
+#[test]
+fn keyed() {
+    let app = app(ConcurrencyLimit::new(1)
+        .with_key(|conn| (conn.method() == Method::Post).then(|| conn.path().to_string())));
+
+    let a = post("/a").on(&app);
+    assert_status!(&a, 200);
+    assert_status!(post("/a").on(&app), 503);
+    let b = post("/b").on(&app);
+    assert_status!(&b, 200);
+    assert_status!(post("/b").on(&app), 503);
+
+    // requests without a key are not limited
+    let gets = [get("/a").on(&app), get("/a").on(&app)];
+    for conn in gets {
+        assert_ok!(conn, "ok");
+    }
+
+    drop(a);
+    assert_ok!(post("/a").on(&app), "ok");
+    drop(b);
+}
+
+#[test(harness)]
+async fn queue_timeout() {
+    let app = app(ConcurrencyLimit::new(1).with_queue_timeout(Duration::from_millis(500)));
+
+    let held = get("/").run_async(&app).await;
+    let release = trillium_testing::spawn(async move {
+        Timer::after(Duration::from_millis(50)).await;
+        drop(held);
+    });
+
+    let conn = get("/").run_async(&app).await;
+    assert_ok!(conn, "ok");
+    release.await;
+
+    let app = self::app(ConcurrencyLimit::new(1).with_queue_timeout(Duration::from_millis(10)));
+    let _held = get("/").run_async(&app).await;
+    let conn = TestConn::build(Method::Get, "/", ()).run_async(&app).await;
+    assert_status!(conn, 503);
+}
+
+#[test]
+fn custom_response() {
+    let app = app(
+        ConcurrencyLimit::new(0).with_response(|conn: Conn| async move {
+            conn.with_status(Status::TooManyRequests)
+                .with_body("slow down")
+        }),
+    );
+    let conn = get("/").on(&app);
+    assert!(conn.is_halted());
+    assert_response!(conn, Status::TooManyRequests, "slow down", "retry-after" => "1");
+}
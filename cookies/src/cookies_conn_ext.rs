@@ -1,4 +1,6 @@
-use cookie::{Cookie, CookieJar};
+use crate::cookies_handler::CookiesKey;
+use cookie::{Cookie, CookieJar, Key, PrivateJar, SignedJar};
+use std::sync::Arc;
 use trillium::Conn;
 
 /**
@@ -14,6 +16,35 @@ pub trait CookiesConnExt {
     fn cookies(&self) -> &CookieJar;
     /// gets a mutable reference to the cookie jar
     fn cookies_mut(&mut self) -> &mut CookieJar;
+
+    /**
+    gets a signed view of the cookie jar. cookies retrieved from
+    this jar are only returned if their signature is valid, and
+    cookies added to it are signed, so they can be read by the
+    client but cannot be tampered with.
+
+    this requires a key to be configured with
+    [`CookiesHandler::with_key`](crate::CookiesHandler::with_key).
+    */
+    fn signed_cookies(&self) -> SignedJar<&CookieJar>;
+
+    /// gets a mutable signed view of the cookie jar. see
+    /// [`CookiesConnExt::signed_cookies`]
+    fn signed_cookies_mut(&mut self) -> SignedJar<&mut CookieJar>;
+
+    /**
+    gets a private view of the cookie jar. cookies added to this jar
+    are encrypted and authenticated, so they can neither be read nor
+    tampered with by the client.
+
+    this requires a key to be configured with
+    [`CookiesHandler::with_key`](crate::CookiesHandler::with_key).
+    */
+    fn private_cookies(&self) -> PrivateJar<&CookieJar>;
+
+    /// gets a mutable private view of the cookie jar. see
+    /// [`CookiesConnExt::private_cookies`]
+    fn private_cookies_mut(&mut self) -> PrivateJar<&mut CookieJar>;
 }
 
 impl CookiesConnExt for Conn {
@@ -31,4 +62,29 @@ impl CookiesConnExt for Conn {
         self.state_mut()
             .expect("Cookies handler must be executed before calling CookiesExt::cookies_mut")
     }
+
+    fn signed_cookies(&self) -> SignedJar<&CookieJar> {
+        self.cookies().signed(&cookies_key(self))
+    }
+
+    fn signed_cookies_mut(&mut self) -> SignedJar<&mut CookieJar> {
+        let key = cookies_key(self);
+        self.cookies_mut().signed_mut(&key)
+    }
+
+    fn private_cookies(&self) -> PrivateJar<&CookieJar> {
+        self.cookies().private(&cookies_key(self))
+    }
+
+    fn private_cookies_mut(&mut self) -> PrivateJar<&mut CookieJar> {
+        let key = cookies_key(self);
+        self.cookies_mut().private_mut(&key)
+    }
+}
+
+fn cookies_key(conn: &Conn) -> Arc<Key> {
+    let CookiesKey(key) = conn
+        .state()
+        .expect("CookiesHandler::with_key must be configured to use signed or private cookies");
+    Arc::clone(key)
 }
@@ -1,23 +1,145 @@
-use cookie::{Cookie, CookieJar};
-use trillium::{async_trait, Conn, Handler, HeaderValue, HeaderValues, KnownHeaderName};
+use cookie::{Cookie, CookieJar, Key, SameSite};
+use std::{borrow::Cow, sync::Arc};
+use trillium::{async_trait, Conn, Handler, HeaderValue, HeaderValues, Info, KnownHeaderName};
 
 /**
 The trillium cookie handler. See crate level docs for an example. This
 must run before any handlers access the cookie jar.
 */
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct CookiesHandler {
-    // this is in order to force users to call CookiesHandler::new or
-    // CookiesHandler::default, allowing us to add
-    // customization/settings later without breaking existing usage
-    _priv: (),
+    key: Option<Arc<Key>>,
+    defaults: CookieDefaults,
 }
 
+#[derive(Clone, Debug, Default)]
+struct CookieDefaults {
+    same_site: Option<SameSite>,
+    secure: Option<bool>,
+    http_only: Option<bool>,
+    path: Option<Cow<'static, str>>,
+    domain: Option<Cow<'static, str>>,
+}
+
+impl CookieDefaults {
+    fn apply(&self, cookie: &mut Cookie<'static>) {
+        if let (None, Some(same_site)) = (cookie.same_site(), self.same_site) {
+            cookie.set_same_site(same_site);
+        }
+
+        if let (None, Some(secure)) = (cookie.secure(), self.secure) {
+            cookie.set_secure(secure);
+        }
+
+        if let (None, Some(http_only)) = (cookie.http_only(), self.http_only) {
+            cookie.set_http_only(http_only);
+        }
+
+        if let (None, Some(path)) = (cookie.path(), &self.path) {
+            cookie.set_path(path.clone());
+        }
+
+        if let (None, Some(domain)) = (cookie.domain(), &self.domain) {
+            cookie.set_domain(domain.clone());
+        }
+    }
+}
+
+/// the key used for signed and private cookies, stored in conn state
+#[derive(Debug)]
+pub(crate) struct CookiesKey(pub(crate) Arc<Key>);
+
 impl CookiesHandler {
     /// constructs a new cookies handler
     pub fn new() -> Self {
         Self::default()
     }
+
+    /**
+    sets the [`Key`] used to sign and encrypt cookies with
+    [`CookiesConnExt::signed_cookies`](crate::CookiesConnExt::signed_cookies)
+    and
+    [`CookiesConnExt::private_cookies`](crate::CookiesConnExt::private_cookies).
+    the key should be derived from a cryptographically random
+    secret with [`Key::derive_from`] or generated with
+    [`Key::generate`], and must be the same across restarts and
+    servers for cookies to remain valid.
+    */
+    pub fn with_key(mut self, key: Key) -> Self {
+        self.key = Some(Arc::new(key));
+        self
+    }
+
+    /**
+    sets a default SameSite attribute for cookies added to the jar.
+    this and the other defaults below are applied when the response
+    is sent to any cookie that does not explicitly set the attribute.
+
+    ```
+    use trillium::Conn;
+    use trillium_cookies::{cookie::{Cookie, SameSite}, CookiesConnExt, CookiesHandler};
+
+    let handler = (
+        CookiesHandler::new()
+            .with_default_same_site(SameSite::Strict)
+            .with_default_secure(true)
+            .with_default_http_only(true)
+            .with_default_path("/"),
+        |conn: Conn| async move {
+            conn.with_cookie(("session", "a"))
+                .with_cookie(Cookie::build(("theme", "dark")).http_only(false))
+                .ok("ok")
+        },
+    );
+
+    use trillium_testing::prelude::*;
+    let conn = get("/").on(&handler);
+    let mut set_cookies = conn
+        .response_headers()
+        .get_values("set-cookie")
+        .unwrap()
+        .iter()
+        .filter_map(|value| value.as_str())
+        .map(String::from)
+        .collect::<Vec<_>>();
+    set_cookies.sort();
+    assert_eq!(
+        set_cookies,
+        [
+            "session=a; HttpOnly; SameSite=Strict; Secure; Path=/",
+            "theme=dark; SameSite=Strict; Secure; Path=/",
+        ]
+    );
+    ```
+    */
+    pub fn with_default_same_site(mut self, same_site: SameSite) -> Self {
+        self.defaults.same_site = Some(same_site);
+        self
+    }
+
+    /// sets whether cookies added to the jar are `Secure` by default
+    pub fn with_default_secure(mut self, secure: bool) -> Self {
+        self.defaults.secure = Some(secure);
+        self
+    }
+
+    /// sets whether cookies added to the jar are `HttpOnly` by default
+    pub fn with_default_http_only(mut self, http_only: bool) -> Self {
+        self.defaults.http_only = Some(http_only);
+        self
+    }
+
+    /// sets a default `Path` for cookies added to the jar
+    pub fn with_default_path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.defaults.path = Some(path.into());
+        self
+    }
+
+    /// sets a default `Domain` for cookies added to the jar
+    pub fn with_default_domain(mut self, domain: impl Into<Cow<'static, str>>) -> Self {
+        self.defaults.domain = Some(domain.into());
+        self
+    }
 }
 
 #[async_trait]
@@ -35,15 +157,27 @@ impl Handler for CookiesHandler {
             }
         }
 
+        if let Some(key) = &self.key {
+            conn.insert_state(CookiesKey(Arc::clone(key)));
+        }
+
         conn.with_state(jar)
     }
 
+    async fn init(&mut self, info: &mut Info) {
+        info.provide::<CookieJar>();
+    }
+
     async fn before_send(&self, mut conn: Conn) -> Conn {
         if let Some(jar) = conn.take_state::<CookieJar>() {
             conn.response_headers_mut().append(
                 KnownHeaderName::SetCookie,
                 jar.delta()
-                    .map(|cookie| cookie.encoded().to_string())
+                    .map(|cookie| {
+                        let mut cookie = cookie.clone();
+                        self.defaults.apply(&mut cookie);
+                        cookie.encoded().to_string()
+                    })
                     .collect::<HeaderValues>(),
             );
             conn.with_state(jar)
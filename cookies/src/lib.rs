@@ -43,6 +43,50 @@ assert_ok!(
     "set-cookie" => "some_cookie=some-cookie-value"
 );
 
+```
+
+## signed and private cookies
+
+when a [`Key`](cookie::Key) is configured with
+[`CookiesHandler::with_key`], cookies can be signed with
+[`CookiesConnExt::signed_cookies`] so that they cannot be tampered
+with, or encrypted with [`CookiesConnExt::private_cookies`] so that
+they can neither be read nor tampered with by the client.
+
+```
+use trillium::Conn;
+use trillium_cookies::{cookie::{Cookie, Key}, CookiesConnExt, CookiesHandler};
+
+let handler = (
+    CookiesHandler::new().with_key(Key::generate()),
+    |mut conn: Conn| async move {
+        let content = match conn.private_cookies().get("user_id") {
+            Some(cookie) => format!("user id: {}", cookie.value()),
+            None => String::from("not logged in"),
+        };
+
+        conn.private_cookies_mut().add(("user_id", "10"));
+        conn.ok(content)
+    },
+);
+
+use trillium_testing::prelude::*;
+
+let conn = get("/").on(&handler);
+let set_cookie = conn.response_headers().get_str("set-cookie").unwrap();
+let cookie = Cookie::parse_encoded(set_cookie).unwrap();
+assert_ne!(cookie.value(), "10");
+
+let cookie_header = cookie.encoded().stripped().to_string();
+assert_ok!(
+    get("/").with_request_header("cookie", cookie_header).on(&handler),
+    "user id: 10"
+);
+
+assert_ok!(
+    get("/").with_request_header("cookie", "user_id=10").on(&handler),
+    "not logged in"
+);
 ```
 */
 mod cookies_handler;
@@ -0,0 +1,94 @@
+use trillium::Conn;
+use trillium_cookies::{
+    cookie::{Cookie, Key},
+    CookiesConnExt, CookiesHandler,
+};
+use trillium_testing::prelude::*;
+
+#[test]
+fn signed_cookies() {
+    let handler = (
+        CookiesHandler::new().with_key(Key::derive_from(&[1; 32])),
+        |mut conn: Conn| async move {
+            let content = match conn.signed_cookies().get("role") {
+                Some(cookie) => format!("role: {}", cookie.value()),
+                None => String::from("no role"),
+            };
+
+            conn.signed_cookies_mut().add(("role", "admin"));
+            conn.ok(content)
+        },
+    );
+
+    let conn = get("/").on(&handler);
+    let set_cookie = conn.response_headers().get_str("set-cookie").unwrap();
+    let cookie = Cookie::parse_encoded(set_cookie).unwrap();
+    assert!(cookie.value().ends_with("admin"));
+
+    let signed = cookie.encoded().stripped().to_string();
+    assert_ok!(
+        get("/")
+            .with_request_header("cookie", signed.clone())
+            .on(&handler),
+        "role: admin"
+    );
+
+    let tampered = signed.replace("admin", "owner");
+    assert_ok!(
+        get("/")
+            .with_request_header("cookie", tampered)
+            .on(&handler),
+        "no role"
+    );
+}
+
+#[test]
+#[should_panic(expected = "CookiesHandler::with_key")]
+fn signed_cookies_without_key() {
+    let handler = (CookiesHandler::new(), |conn: Conn| async move {
+        let _ = conn.signed_cookies().get("role");
+        conn
+    });
+
+    get("/").on(&handler);
+}
+
+#[test]
+fn default_attributes() {
+    use trillium_cookies::cookie::SameSite;
+
+    let handler = (
+        CookiesHandler::new()
+            .with_default_same_site(SameSite::Strict)
+            .with_default_path("/app")
+            .with_default_domain("trillium.rs"),
+        |mut conn: Conn| async move {
+            conn.cookies_mut().remove(Cookie::from("old"));
+            conn.with_cookie(Cookie::build(("new", "value")).same_site(SameSite::Lax))
+                .ok("ok")
+        },
+    );
+
+    let conn = get("/")
+        .with_request_header("cookie", "old=value")
+        .on(&handler);
+    let set_cookies = conn
+        .response_headers()
+        .get_values("set-cookie")
+        .unwrap()
+        .iter()
+        .filter_map(|value| Cookie::parse(value.as_str()?.to_string()).ok())
+        .collect::<Vec<_>>();
+    assert_eq!(set_cookies.len(), 2);
+
+    for cookie in &set_cookies {
+        assert_eq!(cookie.path(), Some("/app"));
+        assert_eq!(cookie.domain(), Some("trillium.rs"));
+    }
+
+    let new = set_cookies.iter().find(|c| c.name() == "new").unwrap();
+    assert_eq!(new.same_site(), Some(SameSite::Lax));
+    let old = set_cookies.iter().find(|c| c.name() == "old").unwrap();
+    assert_eq!(old.same_site(), Some(SameSite::Strict));
+    assert_eq!(old.value(), "");
+}
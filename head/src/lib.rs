@@ -2,9 +2,10 @@
 # Trillium handler for HEAD requests
 
 This simple handler rewrites HEAD requests to be GET requests, and
-then before sending the response, removes the outbound body but sets a
-content length header. Any handlers subsequent to this one see a GET
-request.
+then before sending the response, restores the HEAD method. Any
+handlers subsequent to this one see a GET request. The response is
+framed as the GET response would be, including its content length,
+but the body is not sent.
 */
 #![forbid(unsafe_code)]
 #![deny(
@@ -16,7 +17,7 @@ request.
     unused_qualifications
 )]
 
-use trillium::{async_trait, conn_unwrap, Conn, Handler, KnownHeaderName::ContentLength, Method};
+use trillium::{async_trait, conn_unwrap, Conn, Handler, Method};
 
 /**
 Trillium handler for HEAD requests
@@ -52,13 +53,7 @@ impl Handler for Head {
     async fn before_send(&self, mut conn: Conn) -> Conn {
         conn_unwrap!(conn.state::<RequestWasHead>(), conn);
         conn.inner_mut().set_method(Method::Head);
-        let len = conn_unwrap!(
-            conn.inner_mut()
-                .take_response_body()
-                .and_then(|body| body.len()),
-            conn
-        );
-        conn.with_response_header(ContentLength, len.to_string())
+        conn
     }
 }
 
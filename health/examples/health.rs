@@ -0,0 +1,16 @@
+use std::time::Duration;
+use trillium_health::health;
+
+fn main() {
+    // try `curl http://localhost:8080/healthz` and
+    // `curl http://localhost:8080/readyz`
+    trillium_smol::run((
+        health()
+            .with_check("always", || async { Ok::<_, String>(()) })
+            .with_check_timeout("slow", Duration::from_millis(100), || async {
+                async_io::Timer::after(Duration::from_secs(1)).await;
+                Ok::<_, String>(())
+            }),
+        "hello",
+    ));
+}
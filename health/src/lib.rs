@@ -0,0 +1,337 @@
+/*!
+Liveness and readiness checks for trillium applications.
+
+[`Health`] is a handler that serves two endpoints, as expected by
+orchestrators such as kubernetes and by load balancers:
+
+* `/healthz`, the liveness endpoint, always responds `200 OK` while
+  the server is able to handle requests at all
+* `/readyz`, the readiness endpoint, runs every check registered with
+  [`Health::with_check`] concurrently, and responds `200 OK` if all of
+  them pass or `503 Service Unavailable` if any of them fail or time
+  out
+
+Both respond with a json body. The readiness body includes the status
+and duration of each check:
+
+```json
+{
+  "status": "unavailable",
+  "checks": {
+    "database": { "status": "ok", "duration_ms": 2 },
+    "upstream": { "status": "error", "error": "connection refused", "duration_ms": 14 }
+  }
+}
+```
+
+Checks are async functions that return a `Result`, and are typically
+clones of a database pool or http client that perform a trivial
+request. Each check has a timeout, which is five seconds by default.
+
+```
+use std::time::Duration;
+use trillium_health::health;
+
+let handler = health()
+    .with_check("database", || async { Ok::<_, std::io::Error>(()) })
+    .with_check_timeout("upstream", Duration::from_millis(500), || async {
+        Err::<(), _>("connection refused")
+    });
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/healthz").on(&handler), r#"{"status":"ok"}"#);
+assert_status!(get("/readyz").on(&handler), 503);
+```
+
+Requests to other paths are passed along, so [`Health`] is usually
+placed at the start of the application.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+use async_io::Timer;
+use futures_lite::future;
+use serde_json::{json, Map, Value};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Display, Formatter},
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::Arc,
+    task::Poll,
+    time::{Duration, Instant},
+};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Method, Status};
+
+type BoxedFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'static>>;
+type CheckFn = Arc<dyn Fn() -> BoxedFuture + Send + Sync + 'static>;
+
+struct Check {
+    name: Cow<'static, str>,
+    timeout: Option<Duration>,
+    check: CheckFn,
+}
+
+/// The outcome of a single readiness check
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// The check passed
+    Ok,
+
+    /// The check returned an error, with the error's display
+    /// representation
+    Error(String),
+
+    /// The check did not complete within its timeout
+    Timeout,
+}
+
+impl CheckStatus {
+    /// Whether the check passed
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Self::Ok)
+    }
+}
+
+/**
+The liveness and readiness handler. See the [crate-level docs](crate)
+for details.
+*/
+#[derive(Clone)]
+pub struct Health {
+    liveness_path: Cow<'static, str>,
+    readiness_path: Cow<'static, str>,
+    default_timeout: Duration,
+    checks: Vec<Arc<Check>>,
+}
+
+impl Debug for Health {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Health")
+            .field("liveness_path", &self.liveness_path)
+            .field("readiness_path", &self.readiness_path)
+            .field("default_timeout", &self.default_timeout)
+            .field(
+                "checks",
+                &self
+                    .checks
+                    .iter()
+                    .map(|check| &check.name)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self {
+            liveness_path: Cow::Borrowed("/healthz"),
+            readiness_path: Cow::Borrowed("/readyz"),
+            default_timeout: Duration::from_secs(5),
+            checks: Vec::new(),
+        }
+    }
+}
+
+impl Health {
+    /// Builds a new Health handler with no readiness checks
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves the liveness endpoint at this path instead of `/healthz`
+    pub fn with_liveness_path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.liveness_path = path.into();
+        self
+    }
+
+    /// Serves the readiness endpoint at this path instead of `/readyz`
+    pub fn with_readiness_path(mut self, path: impl Into<Cow<'static, str>>) -> Self {
+        self.readiness_path = path.into();
+        self
+    }
+
+    /// Sets the timeout for checks that are added without their own
+    /// timeout. Defaults to five seconds.
+    pub fn with_default_timeout(mut self, timeout: Duration) -> Self {
+        self.default_timeout = timeout;
+        self
+    }
+
+    /// Adds a readiness check with the default timeout. The check is
+    /// called for every readiness request, and fails if it returns an
+    /// error. A check with the same name as an existing check replaces
+    /// it.
+    pub fn with_check<F, Fut, E>(self, name: impl Into<Cow<'static, str>>, check: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        self.add_check(name.into(), None, check)
+    }
+
+    /// Adds a readiness check with its own timeout. See
+    /// [`Health::with_check`]
+    pub fn with_check_timeout<F, Fut, E>(
+        self,
+        name: impl Into<Cow<'static, str>>,
+        timeout: Duration,
+        check: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        self.add_check(name.into(), Some(timeout), check)
+    }
+
+    fn add_check<F, Fut, E>(
+        mut self,
+        name: Cow<'static, str>,
+        timeout: Option<Duration>,
+        check: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display,
+    {
+        let check: CheckFn = Arc::new(move || {
+            let fut = check();
+            Box::pin(async move { fut.await.map_err(|e| e.to_string()) })
+        });
+        let check = Arc::new(Check {
+            name,
+            timeout,
+            check,
+        });
+
+        match self.checks.iter_mut().find(|c| c.name == check.name) {
+            Some(existing) => *existing = check,
+            None => self.checks.push(check),
+        }
+        self
+    }
+
+    /// Runs every readiness check concurrently, returning the name,
+    /// status, and duration of each check in the order they were added
+    pub async fn check(&self) -> Vec<(Cow<'static, str>, CheckStatus, Duration)> {
+        let mut pending = self
+            .checks
+            .iter()
+            .map(|check| Some(Box::pin(self.run_check(Arc::clone(check)))))
+            .collect::<Vec<_>>();
+        let mut results = vec![None; pending.len()];
+
+        poll_fn(|cx| {
+            let mut done = true;
+            for (slot, result) in pending.iter_mut().zip(&mut results) {
+                if let Some(fut) = slot {
+                    match fut.as_mut().poll(cx) {
+                        Poll::Ready(output) => {
+                            *result = Some(output);
+                            *slot = None;
+                        }
+                        Poll::Pending => done = false,
+                    }
+                }
+            }
+            if done {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await;
+
+        results.into_iter().flatten().collect()
+    }
+
+    async fn run_check(&self, check: Arc<Check>) -> (Cow<'static, str>, CheckStatus, Duration) {
+        let timeout = check.timeout.unwrap_or(self.default_timeout);
+        let start = Instant::now();
+        let status = future::or(
+            async {
+                match (check.check)().await {
+                    Ok(()) => CheckStatus::Ok,
+                    Err(error) => CheckStatus::Error(error),
+                }
+            },
+            async {
+                Timer::after(timeout).await;
+                CheckStatus::Timeout
+            },
+        )
+        .await;
+        (check.name.clone(), status, start.elapsed())
+    }
+
+    async fn readiness(&self) -> (Status, Value) {
+        let results = self.check().await;
+        let ready = results.iter().all(|(_, status, _)| status.is_ok());
+
+        let checks = results
+            .into_iter()
+            .map(|(name, status, duration)| {
+                let duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+                let check = match status {
+                    CheckStatus::Ok => json!({ "status": "ok", "duration_ms": duration_ms }),
+                    CheckStatus::Error(error) => {
+                        json!({ "status": "error", "error": error, "duration_ms": duration_ms })
+                    }
+                    CheckStatus::Timeout => {
+                        json!({ "status": "timeout", "duration_ms": duration_ms })
+                    }
+                };
+                (name.into_owned(), check)
+            })
+            .collect::<Map<_, _>>();
+
+        if ready {
+            (Status::Ok, json!({ "status": "ok", "checks": checks }))
+        } else {
+            (
+                Status::ServiceUnavailable,
+                json!({ "status": "unavailable", "checks": checks }),
+            )
+        }
+    }
+}
+
+#[async_trait]
+impl Handler for Health {
+    async fn run(&self, conn: Conn) -> Conn {
+        if !matches!(conn.method(), Method::Get | Method::Head) {
+            return conn;
+        }
+
+        let (status, body) = if conn.path() == self.liveness_path {
+            (Status::Ok, json!({ "status": "ok" }))
+        } else if conn.path() == self.readiness_path {
+            self.readiness().await
+        } else {
+            return conn;
+        };
+
+        conn.with_status(status)
+            .with_response_header(KnownHeaderName::ContentType, "application/json")
+            .with_response_header(KnownHeaderName::CacheControl, "no-store")
+            .with_body(body.to_string())
+            .halt()
+    }
+}
+
+/// Convenience alias for [`Health::new`]
+pub fn health() -> Health {
+    Health::new()
+}
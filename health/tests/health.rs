@@ -0,0 +1,100 @@
+use serde_json::{json, Value};
+use std::time::Duration;
+use trillium_health::{health, CheckStatus};
+use trillium_testing::prelude::*;
+
+fn json_body(conn: &mut trillium_testing::TestConn) -> Value {
+    serde_json::from_str(&conn.take_response_body_string().unwrap()).unwrap()
+}
+
+#[test]
+fn liveness() {
+    let handler = (health(), "app");
+    assert_response!(
+        get("/healthz").on(&handler),
+        200,
+        r#"{"status":"ok"}"#,
+        "content-type" => "application/json",
+        "cache-control" => "no-store"
+    );
+    assert_ok!(get("/").on(&handler), "app");
+    assert_ok!(post("/healthz").on(&handler), "app");
+}
+
+#[test]
+fn ready_when_all_checks_pass() {
+    let handler = health()
+        .with_check("first", || async { Ok::<_, String>(()) })
+        .with_check("second", || async { Ok::<_, String>(()) });
+
+    let mut conn = get("/readyz").on(&handler);
+    assert_status!(&conn, 200);
+    let body = json_body(&mut conn);
+    assert_eq!(body["status"], "ok");
+    assert_eq!(body["checks"]["first"]["status"], "ok");
+    assert_eq!(body["checks"]["second"]["status"], "ok");
+}
+
+#[test]
+fn unavailable_when_a_check_fails_or_times_out() {
+    let handler = health()
+        .with_check("ok", || async { Ok::<_, String>(()) })
+        .with_check("failing", || async { Err("connection refused") })
+        .with_check_timeout("slow", Duration::from_millis(10), || async {
+            async_io::Timer::after(Duration::from_secs(5)).await;
+            Ok::<_, String>(())
+        });
+
+    let mut conn = get("/readyz").on(&handler);
+    assert_status!(&conn, 503);
+    let body = json_body(&mut conn);
+    assert_eq!(body["status"], "unavailable");
+    assert_eq!(body["checks"]["ok"]["status"], "ok");
+    assert_eq!(
+        body["checks"]["failing"],
+        json!({
+            "status": "error",
+            "error": "connection refused",
+            "duration_ms": body["checks"]["failing"]["duration_ms"]
+        })
+    );
+    assert_eq!(body["checks"]["slow"]["status"], "timeout");
+}
+
+#[test]
+fn checks_run_concurrently() {
+    let slow = || async {
+        async_io::Timer::after(Duration::from_millis(100)).await;
+        Ok::<_, String>(())
+    };
+    let handler = health()
+        .with_check("a", slow)
+        .with_check("b", slow)
+        .with_check("c", || async { Err("original") })
+        .with_check("c", || async { Err("replaced") });
+
+    let start = std::time::Instant::now();
+    let results = block_on(handler.check());
+    assert!(start.elapsed() < Duration::from_millis(190));
+    assert_eq!(
+        results
+            .iter()
+            .map(|(name, status, _)| (&**name, status.clone()))
+            .collect::<Vec<_>>(),
+        [
+            ("a", CheckStatus::Ok),
+            ("b", CheckStatus::Ok),
+            ("c", CheckStatus::Error("replaced".into()))
+        ]
+    );
+}
+
+#[test]
+fn custom_paths() {
+    let handler = health()
+        .with_liveness_path("/live")
+        .with_readiness_path("/ready");
+    assert_ok!(get("/live").on(&handler));
+    assert_ok!(get("/ready").on(&handler));
+    assert_not_handled!(get("/healthz").on(&handler));
+}
@@ -0,0 +1,109 @@
+/*!
+Counters of the heap allocations performed by trillium-http while
+parsing requests and rendering responses.
+
+These counters are process-wide, and are only updated when the
+`alloc-metrics` cargo feature is enabled. They account for the
+buffers and generated header values that trillium-http allocates in
+its own request and response path, not for allocations performed by
+handlers, bodies, or the header map itself.
+*/
+
+#[cfg(feature = "alloc-metrics")]
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+#[cfg(feature = "alloc-metrics")]
+static REQUESTS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "alloc-metrics")]
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "alloc-metrics")]
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "alloc-metrics")]
+static REUSED_BUFFERS: AtomicU64 = AtomicU64::new(0);
+
+/// A snapshot of the allocation counters, as returned by [`alloc_metrics`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AllocMetrics {
+    requests: u64,
+    allocations: u64,
+    allocated_bytes: u64,
+    reused_buffers: u64,
+}
+
+impl AllocMetrics {
+    /// the number of request heads that have been parsed
+    pub const fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// the number of heap allocations performed
+    pub const fn allocations(&self) -> u64 {
+        self.allocations
+    }
+
+    /// the total number of bytes allocated
+    pub const fn allocated_bytes(&self) -> u64 {
+        self.allocated_bytes
+    }
+
+    /// the number of response buffers that were reused instead of
+    /// allocated
+    pub const fn reused_buffers(&self) -> u64 {
+        self.reused_buffers
+    }
+
+    /// the mean number of allocations per request
+    #[allow(clippy::cast_precision_loss)]
+    pub fn allocations_per_request(&self) -> f64 {
+        self.allocations as f64 / self.requests.max(1) as f64
+    }
+
+    /// the mean number of bytes allocated per request
+    #[allow(clippy::cast_precision_loss)]
+    pub fn bytes_per_request(&self) -> f64 {
+        self.allocated_bytes as f64 / self.requests.max(1) as f64
+    }
+}
+
+/**
+Returns the current values of the process-wide allocation counters.
+
+This is only available with the `alloc-metrics` cargo feature. The
+counters account for the buffers and generated header values that
+trillium-http allocates while parsing requests and rendering
+responses, not for allocations performed by handlers, bodies, or the
+header map itself.
+*/
+#[cfg(feature = "alloc-metrics")]
+pub fn alloc_metrics() -> AllocMetrics {
+    AllocMetrics {
+        requests: REQUESTS.load(Relaxed),
+        allocations: ALLOCATIONS.load(Relaxed),
+        allocated_bytes: ALLOCATED_BYTES.load(Relaxed),
+        reused_buffers: REUSED_BUFFERS.load(Relaxed),
+    }
+}
+
+#[inline]
+pub(crate) fn record_request() {
+    #[cfg(feature = "alloc-metrics")]
+    REQUESTS.fetch_add(1, Relaxed);
+}
+
+#[inline]
+pub(crate) fn record_allocation(bytes: usize) {
+    #[cfg(feature = "alloc-metrics")]
+    {
+        ALLOCATIONS.fetch_add(1, Relaxed);
+        ALLOCATED_BYTES.fetch_add(bytes as u64, Relaxed);
+    }
+
+    #[cfg(not(feature = "alloc-metrics"))]
+    let _ = bytes;
+}
+
+#[inline]
+pub(crate) fn record_reuse() {
+    #[cfg(feature = "alloc-metrics")]
+    REUSED_BUFFERS.fetch_add(1, Relaxed);
+}
@@ -67,6 +67,7 @@ impl Buffer {
     pub fn expand(&mut self) {
         if self.1.len() == self.1.capacity() {
             self.1.reserve(32);
+            crate::alloc_metrics::record_allocation(self.1.capacity());
         }
         self.fill_capacity();
     }
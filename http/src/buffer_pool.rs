@@ -0,0 +1,38 @@
+use crate::alloc_metrics::{record_allocation, record_reuse};
+use std::cell::RefCell;
+
+// enough for a handful of concurrent responses per worker thread
+const MAX_POOLED_BUFFERS: usize = 8;
+
+thread_local! {
+    static POOL: RefCell<Vec<Vec<u8>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes an empty response buffer with at least `capacity` bytes of
+/// capacity from this thread's pool, allocating one if none are
+/// available
+pub(crate) fn take(capacity: usize) -> Vec<u8> {
+    match POOL.with_borrow_mut(Vec::pop) {
+        Some(mut buffer) if buffer.capacity() >= capacity => {
+            record_reuse();
+            buffer.clear();
+            buffer
+        }
+
+        _ => {
+            record_allocation(capacity);
+            Vec::with_capacity(capacity)
+        }
+    }
+}
+
+/// Returns a response buffer to this thread's pool. Because futures
+/// can move between threads, this may not be the thread it was taken
+/// from.
+pub(crate) fn give(buffer: Vec<u8>) {
+    POOL.with_borrow_mut(|pool| {
+        if pool.len() < MAX_POOLED_BUFFERS {
+            pool.push(buffer);
+        }
+    });
+}
@@ -24,6 +24,10 @@ impl<W: AsyncWrite + Unpin> BufWriter<W> {
         }
     }
 
+    pub(crate) fn into_buffer(self) -> Vec<u8> {
+        self.buffer
+    }
+
     fn poll_flush_buf(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<usize>> {
         let Self {
             inner,
@@ -1,20 +1,25 @@
 use crate::{
     after_send::{AfterSend, SendStatus},
-    copy,
+    alloc_metrics::{record_allocation, record_request},
+    buffer_pool, copy,
     http_config::DEFAULT_CONFIG,
+    http_date,
     liveness::{CancelOnDisconnect, LivenessFut},
     received_body::ReceivedBodyState,
     util::encoding,
-    Body, BufWriter, Buffer, ConnectionStatus, Error, HeaderName, HeaderValue, Headers, HttpConfig,
-    KnownHeaderName::{Connection, ContentLength, Date, Expect, Host, Server, TransferEncoding},
-    Method, ReceivedBody, Result, StateSet, Status, Stopper, Upgrade, Version,
+    Body, BufWriter, Buffer, ConnectionStats, ConnectionStatus, Error, HeaderName, HeaderValue,
+    HeaderValues, Headers, HttpConfig,
+    KnownHeaderName::{
+        Connection, ContentLength, Date, Expect, Host, Server, Trailer, TransferEncoding,
+    },
+    Method, RawHead, ReceivedBody, Result, StateSet, Status, Stopper, Upgrade, Version,
 };
 use encoding_rs::Encoding;
 use futures_lite::{
     future,
     io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
 };
-use httparse::{Request, EMPTY_HEADER};
+use httparse::{Header, Request, EMPTY_HEADER};
 use memchr::memmem::Finder;
 use std::{
     fmt::{self, Debug, Formatter},
@@ -22,9 +27,13 @@ use std::{
     net::IpAddr,
     pin::pin,
     str::FromStr,
-    time::{Instant, SystemTime},
+    time::Instant,
 };
 
+// request heads with up to this many headers are parsed without a
+// heap-allocated header array
+const STACK_HEADERS: usize = 64;
+
 /// Default Server header
 pub const SERVER: &str = concat!("trillium/", env!("CARGO_PKG_VERSION"));
 
@@ -50,8 +59,15 @@ pub struct Conn<Transport> {
     pub(crate) stopper: Stopper,
     pub(crate) after_send: AfterSend,
     pub(crate) start_time: Instant,
+    pub(crate) connection_stats: ConnectionStats,
     pub(crate) peer_ip: Option<IpAddr>,
     pub(crate) http_config: HttpConfig,
+    pub(crate) raw_head: Option<RawHead>,
+    pub(crate) request_trailers: Option<Headers>,
+    pub(crate) response_trailers: Option<Headers>,
+    pub(crate) sent_100_continue: bool,
+    #[cfg(feature = "http2")]
+    pub(crate) http2_response: Option<h2::server::SendResponse<bytes::Bytes>>,
 }
 
 impl<Transport> Debug for Conn<Transport> {
@@ -73,8 +89,13 @@ impl<Transport> Debug for Conn<Transport> {
             .field("stopper", &self.stopper)
             .field("after_send", &"..")
             .field("start_time", &self.start_time)
+            .field("connection_stats", &self.connection_stats)
             .field("peer_ip", &self.peer_ip)
-            .finish()
+            .field("raw_head", &self.raw_head)
+            .field("request_trailers", &self.request_trailers)
+            .field("response_trailers", &self.response_trailers)
+            .field("sent_100_continue", &self.sent_100_continue)
+            .finish_non_exhaustive()
     }
 }
 
@@ -145,29 +166,54 @@ where
         F: FnMut(Conn<Transport>) -> Fut,
         Fut: Future<Output = Conn<Transport>> + Send,
     {
-        let mut conn = Conn::new_with_config(
-            http_config,
-            transport,
-            Vec::with_capacity(http_config.request_buffer_initial_len).into(),
-            stopper,
-        )
-        .await?;
-        loop {
-            conn = match handler(conn).await.send().await? {
-                ConnectionStatus::Upgrade(upgrade) => return Ok(Some(upgrade)),
-                ConnectionStatus::Close => return Ok(None),
-                ConnectionStatus::Conn(next) => next,
+        record_allocation(http_config.request_buffer_initial_len);
+        let mut connection_stats = None;
+        let result = async {
+            let mut conn = Conn::new_with_config(
+                http_config,
+                transport,
+                Vec::with_capacity(http_config.request_buffer_initial_len).into(),
+                stopper,
+                ConnectionStats::new(Instant::now()),
+            )
+            .await?;
+            loop {
+                connection_stats = Some(conn.connection_stats);
+                conn = match handler(conn).await.send().await? {
+                    ConnectionStatus::Upgrade(upgrade) => return Ok(Some(upgrade)),
+                    ConnectionStatus::Close => return Ok(None),
+                    ConnectionStatus::Conn(next) => next,
+                }
             }
         }
+        .await;
+
+        if let (Some(hook), Some(connection_stats)) =
+            (http_config.connection_close_hook, connection_stats)
+        {
+            hook(&connection_stats);
+        }
+
+        result
     }
 
     async fn send(mut self) -> Result<ConnectionStatus<Transport>> {
-        let mut output_buffer = Vec::with_capacity(self.http_config.response_buffer_len);
-        self.write_headers(&mut output_buffer)?;
+        let mut output_buffer = buffer_pool::take(self.http_config.response_buffer_len);
+        self.write_headers(&mut output_buffer);
 
+        let sends_trailers = self.sends_response_trailers();
         let mut bufwriter = BufWriter::new_with_buffer(output_buffer, &mut self.transport);
 
-        if self.method != Method::Head
+        if sends_trailers {
+            let trailers = self.response_trailers.take().unwrap_or_default();
+            write_chunked_with_trailers(
+                self.response_body.take().unwrap_or_default(),
+                &trailers,
+                &mut bufwriter,
+                self.http_config.response_buffer_len,
+            )
+            .await?;
+        } else if self.method != Method::Head
             && !matches!(self.status, Some(Status::NotModified | Status::NoContent))
         {
             if let Some(body) = self.response_body.take() {
@@ -176,6 +222,7 @@ where
         }
 
         bufwriter.flush().await?;
+        buffer_pool::give(bufwriter.into_buffer());
         self.after_send.call(true.into());
         self.finish().await
     }
@@ -349,6 +396,45 @@ where
         self.response_body.take()
     }
 
+    /**
+    Sets a response trailer, replacing any previous trailer with the same name.
+
+    Trailers are sent after the response body. On http/1.1 this requires chunked transfer-encoding,
+    so a response with trailers is always sent chunked, and the `Trailer` header is set to the
+    names of the trailers. Trailers are not sent on http/1.0 responses, since http/1.0 does not
+    support chunked transfer-encoding.
+
+    ```
+    # use trillium_http::{Conn, Method};
+    # let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    conn.set_response_trailer("grpc-status", "0");
+    assert_eq!(conn.response_trailers().unwrap().get_str("grpc-status"), Some("0"));
+    ```
+    */
+    pub fn set_response_trailer(
+        &mut self,
+        name: impl Into<HeaderName<'static>>,
+        value: impl Into<HeaderValues>,
+    ) {
+        self.response_trailers_mut().insert(name, value);
+    }
+
+    /// Replaces any response trailers with the provided [`Headers`]. See
+    /// [`Conn::set_response_trailer`]
+    pub fn set_response_trailers(&mut self, trailers: Headers) {
+        self.response_trailers = Some(trailers);
+    }
+
+    /// returns a reference to the response trailers, if any have been set
+    pub fn response_trailers(&self) -> Option<&Headers> {
+        self.response_trailers.as_ref()
+    }
+
+    /// returns a mutable reference to the response trailers
+    pub fn response_trailers_mut(&mut self) -> &mut Headers {
+        self.response_trailers.get_or_insert_with(Headers::new)
+    }
+
     /**
     returns the http method for this conn's request.
     ```
@@ -444,8 +530,21 @@ where
         future::poll_once(LivenessFut::new(self)).await.is_some()
     }
 
+    /// Waits until the client disconnects from the conn's transport, by reading from it until the
+    /// read results in an error or empty read. If the client remains connected, this future never
+    /// resolves, so it is intended to be raced against other work with something like
+    /// [`futures_lite::future::or`].
+    ///
+    /// As with [`Conn::cancel_on_disconnect`], the use of this method is not advised if your
+    /// connected http client employs pipelining (rarely seen in the wild), as it will buffer an
+    /// unbounded number of requests
+    pub async fn on_client_disconnect(&mut self) {
+        LivenessFut::new(self).await;
+    }
+
     fn needs_100_continue(&self) -> bool {
-        self.request_body_state == ReceivedBodyState::Start
+        !self.sent_100_continue
+            && self.request_body_state == ReceivedBodyState::Start
             && self.version != Version::Http1_0
             && self
                 .request_headers
@@ -463,6 +562,7 @@ where
             encoding(&self.request_headers),
             &self.http_config,
         )
+        .with_trailers(&mut self.request_trailers)
     }
 
     /**
@@ -512,13 +612,100 @@ where
     ```
     */
     pub async fn request_body(&mut self) -> ReceivedBody<'_, Transport> {
-        if self.needs_100_continue() {
+        // a body that declares itself too long will be rejected, so there's no need to ask for it
+        let too_long = self
+            .request_content_length()
+            .ok()
+            .flatten()
+            .is_some_and(|len| len > self.http_config.received_body_max_len);
+
+        if self.http_config.automatic_100_continue && self.needs_100_continue() && !too_long {
             self.send_100_continue().await.ok();
         }
 
         self.build_request_body()
     }
 
+    /**
+    returns the trailers that were sent after a chunked request body, if any.
+
+    Trailers follow the request body, so if the request body has not been read to the end, the
+    remainder of the body is read and discarded before returning.
+
+    ```
+    # async_io::block_on(async {
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    assert_eq!(conn.request_body().await.read_string().await.unwrap(), "hello");
+    assert!(conn.request_trailers().await.unwrap().is_none());
+    # });
+    ```
+
+    # Errors
+
+    This will return an error if the remainder of the body or the trailers cannot be read.
+    */
+    pub async fn request_trailers(&mut self) -> Result<Option<&Headers>> {
+        if self.request_body_state != ReceivedBodyState::End {
+            self.request_body().await.drain().await?;
+        }
+        Ok(self.request_trailers.as_ref())
+    }
+
+    /**
+    reads the request body into memory and retains it, so that it
+    can be read again by a subsequent call to [`Conn::request_body`].
+    This allows a handler to inspect the body without preventing later
+    handlers from reading it.
+
+    Because the body is retained in decoded form, any
+    `Transfer-Encoding` request header is replaced with a
+    `Content-Length` header, and any `Expect` request header is
+    removed. Calling this again returns the same retained body.
+
+    ```
+    # async_io::block_on(async {
+    # use trillium_http::{Conn, Method};
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    assert_eq!(conn.buffer_request_body(1024).await.unwrap(), b"hello");
+    assert_eq!(conn.request_body().await.read_string().await.unwrap(), "hello");
+    # });
+    ```
+
+    # Errors
+
+    This will return an error if the body is longer than `max_len`
+    or cannot be read. The body cannot be read again after an error.
+
+    This will return [`Error::RequestBodyAlreadyRead`] if the body has
+    already been read in part or in full through
+    [`Conn::request_body`], unless it is known to be empty.
+    */
+    pub async fn buffer_request_body(&mut self, max_len: u64) -> Result<&[u8]> {
+        match self.request_body_state {
+            ReceivedBodyState::Start | ReceivedBodyState::ReadToEnd { total: 0 } => {}
+            ReceivedBodyState::End if self.request_body_is_known_empty() => return Ok(&[]),
+            _ => return Err(Error::RequestBodyAlreadyRead),
+        }
+
+        let body = self
+            .request_body()
+            .await
+            .with_max_len(max_len)
+            .read_bytes()
+            .await?;
+
+        let len = body.len();
+        let mut buffer = body;
+        buffer.extend_from_slice(&self.buffer);
+        self.buffer = buffer.into();
+        self.request_body_state = ReceivedBodyState::Start;
+        self.request_headers.remove(TransferEncoding);
+        self.request_headers.remove(Expect);
+        self.request_headers.insert(ContentLength, len.to_string());
+        Ok(&self.buffer[..len])
+    }
+
     /// returns a clone of the [`stopper::Stopper`] for this Conn. use
     /// this to gracefully stop long-running futures and streams
     /// inside of handler functions
@@ -526,7 +713,9 @@ where
         self.stopper.clone()
     }
 
-    fn validate_headers(request_headers: &Headers) -> Result<()> {
+    fn validate_headers(http_config: &HttpConfig, request_headers: &mut Headers) -> Result<()> {
+        http_config.normalize_duplicate_headers(request_headers)?;
+
         let content_length = request_headers.has_header(ContentLength);
         let transfer_encoding_chunked =
             request_headers.eq_ignore_ascii_case(TransferEncoding, "chunked");
@@ -556,9 +745,16 @@ where
     /// * the request is an unsupported http version
     /// * we cannot make sense of the headers, such as if there is a
     /// `content-length` header as well as a `transfer-encoding: chunked`
-    /// header.
+    /// header, or if there are several `content-length` or `host` headers
     pub async fn new(transport: Transport, bytes: Vec<u8>, stopper: Stopper) -> Result<Self> {
-        Self::new_with_config(DEFAULT_CONFIG, transport, bytes.into(), stopper).await
+        Self::new_with_config(
+            DEFAULT_CONFIG,
+            transport,
+            bytes.into(),
+            stopper,
+            ConnectionStats::new(Instant::now()),
+        )
+        .await
     }
 
     /// # Create a new `Conn`
@@ -585,12 +781,22 @@ where
         mut transport: Transport,
         mut buffer: Buffer,
         stopper: Stopper,
+        mut connection_stats: ConnectionStats,
     ) -> Result<Self> {
         let (head_size, start_time) =
             Self::head(&mut transport, &mut buffer, &stopper, &http_config).await?;
+        connection_stats.request_started(start_time);
 
-        let mut headers = vec![EMPTY_HEADER; http_config.max_headers];
-        let mut httparse_req = Request::new(&mut headers);
+        let mut stack_headers = [EMPTY_HEADER; STACK_HEADERS];
+        let mut heap_headers: Vec<Header<'_>>;
+        let headers = if http_config.max_headers <= STACK_HEADERS {
+            &mut stack_headers[..http_config.max_headers]
+        } else {
+            heap_headers = vec![EMPTY_HEADER; http_config.max_headers];
+            record_allocation(http_config.max_headers * size_of::<Header<'_>>());
+            &mut heap_headers[..]
+        };
+        let mut httparse_req = Request::new(headers);
 
         let status = httparse_req.parse(&buffer[..])?;
         if status.is_partial() {
@@ -615,11 +821,12 @@ where
         let mut request_headers = Headers::with_capacity(httparse_req.headers.len());
         for header in httparse_req.headers {
             let header_name = HeaderName::from_str(header.name)?;
-            let header_value = HeaderValue::from(header.value.to_owned());
+            let header_value = HeaderValue::from_slice(header.value);
             request_headers.append(header_name, header_value);
         }
 
-        Self::validate_headers(&request_headers)?;
+        Self::validate_headers(&http_config, &mut request_headers)?;
+        record_request();
 
         let path = httparse_req
             .path
@@ -631,6 +838,9 @@ where
             Headers::with_capacity(http_config.response_header_initial_capacity);
         response_headers.insert(Server, SERVER);
 
+        let raw_head = (http_config.raw_head_max_len > 0)
+            .then(|| RawHead::new(&buffer[..head_size], http_config.raw_head_max_len));
+
         buffer.ignore_front(head_size);
 
         Ok(Self {
@@ -649,8 +859,15 @@ where
             stopper,
             after_send: AfterSend::default(),
             start_time,
+            connection_stats,
             peer_ip: None,
             http_config,
+            raw_head,
+            request_trailers: None,
+            response_trailers: None,
+            sent_100_continue: false,
+            #[cfg(feature = "http2")]
+            http2_response: None,
         })
     }
 
@@ -675,27 +892,65 @@ where
 
     /**
     calculates any auto-generated headers for this conn prior to sending it
+
+    Framing headers are always derived from the response body at this
+    point, so handlers that replace the body do not need to keep
+    `Content-Length` and `Transfer-Encoding` in sync with it. Any
+    previously set `Content-Length` is replaced with the length of the
+    body, or removed in favor of chunked encoding if the body's length
+    is not known. Responses to HEAD requests are framed as the
+    equivalent GET response would be, but the body is not sent. A HEAD
+    response without a body retains any `Content-Length` that a handler
+    has set.
     */
     pub fn finalize_headers(&mut self) {
         if self.status == Some(Status::SwitchingProtocols) {
             return;
         }
 
-        self.response_headers
-            .try_insert_with(Date, || httpdate::fmt_http_date(SystemTime::now()));
+        self.response_headers.try_insert_with(Date, http_date::now);
+
+        match self.status {
+            Some(Status::NotModified) => {}
 
-        if !matches!(self.status, Some(Status::NotModified | Status::NoContent)) {
-            if let Some(len) = self.body_len() {
-                self.response_headers
-                    .try_insert(ContentLength, len.to_string());
+            Some(Status::NoContent) => {
+                self.response_headers.remove(ContentLength);
+                self.response_headers.remove(TransferEncoding);
             }
 
-            if self.version == Version::Http1_1 && !self.response_headers.has_header(ContentLength)
-            {
-                self.response_headers.insert(TransferEncoding, "chunked");
-            } else {
+            _ if self.method == Method::Head && self.response_body.is_none() => {
+                if !self.response_headers.has_header(ContentLength) {
+                    self.response_headers.insert(ContentLength, "0");
+                }
                 self.response_headers.remove(TransferEncoding);
             }
+
+            _ => {
+                if let Some(len) = self.body_len().filter(|_| !self.sends_response_trailers()) {
+                    self.response_headers
+                        .insert(ContentLength, HeaderValue::from_display(len));
+                    self.response_headers.remove(TransferEncoding);
+                } else {
+                    self.response_headers.remove(ContentLength);
+                    if self.version == Version::Http1_1 {
+                        self.response_headers.insert(TransferEncoding, "chunked");
+                    } else {
+                        self.response_headers.remove(TransferEncoding);
+                    }
+                }
+            }
+        }
+
+        if self.sends_response_trailers() {
+            if let Some(trailers) = &self.response_trailers {
+                let names = trailers
+                    .iter()
+                    .map(|(name, _)| name.as_ref().to_string())
+                    .collect::<Vec<_>>();
+                self.response_headers.insert(Trailer, names.join(", "));
+            }
+        } else {
+            self.response_headers.remove(Trailer);
         }
 
         if self.stopper.is_stopped() {
@@ -703,6 +958,19 @@ where
         }
     }
 
+    fn sends_response_trailers(&self) -> bool {
+        matches!(self.version, Version::Http1_1 | Version::Http2_0)
+            && self.method != Method::Head
+            && !matches!(
+                self.status,
+                Some(Status::NotModified | Status::NoContent | Status::SwitchingProtocols)
+            )
+            && self
+                .response_trailers
+                .as_ref()
+                .is_some_and(|trailers| !trailers.is_empty())
+    }
+
     /**
     Registers a function to call after the http response has been
     completely transferred. Please note that this is a sync function
@@ -726,8 +994,90 @@ where
         self.start_time
     }
 
+    /// keepalive statistics for the connection that this conn was
+    /// received on. see [`ConnectionStats`] for details.
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.connection_stats
+    }
+
+    /**
+    predicate function to indicate whether the client sent `Expect: 100-continue` and is waiting
+    for a `100 Continue` response before sending the request body.
+
+    By default, `100 Continue` is sent when the request body is read with [`Conn::request_body`].
+    To decide whether to accept the body before it is sent, either inspect the request before
+    calling `request_body`, or disable automatic `100 Continue` responses with
+    [`HttpConfig::with_automatic_100_continue`] and call [`Conn::send_informational`] with
+    [`Status::Continue`]. To deny the body, respond with a final status such as `417 Expectation
+    Failed` or `413 Payload Too Large` without reading the request body.
+    */
+    pub fn expects_100_continue(&self) -> bool {
+        self.needs_100_continue()
+    }
+
+    /**
+    sends an informational (1xx) response before the final response, such as `103 Early Hints`.
+    this can be called any number of times before the handler returns the conn.
+
+    Informational responses are only sent for http/1.1 requests. For http/1.0 requests, which
+    cannot receive them, and for http/2 requests, this does nothing. [`Status::Continue`] is only
+    sent if the client is waiting for it (see [`Conn::expects_100_continue`]), and the provided
+    headers are ignored for it.
+
+    ```
+    # use trillium_http::{transport::Transport, Conn, Headers, KnownHeaderName, Status};
+    async fn handler<T: Transport>(mut conn: Conn<T>) -> Conn<T> {
+        let early_hints =
+            Headers::from_iter([(KnownHeaderName::Link, "</style.css>; rel=preload; as=style")]);
+        if let Err(e) = conn.send_informational(Status::EarlyHints, early_hints).await {
+            log::error!("{e}");
+        }
+
+        conn.set_status(Status::Ok);
+        conn
+    }
+    ```
+
+    # Errors
+
+    This will return an error if the status is not informational, if it is `101 Switching
+    Protocols`, which is sent by setting it as the conn's status, or if the response cannot be
+    written to the transport.
+    */
+    pub async fn send_informational(&mut self, status: Status, headers: Headers) -> Result<()> {
+        if !status.is_informational() || status == Status::SwitchingProtocols {
+            return Err(Error::UnexpectedStatus(status));
+        }
+
+        if self.version != Version::Http1_1 {
+            return Ok(());
+        }
+
+        if status == Status::Continue {
+            if self.needs_100_continue() {
+                self.send_100_continue().await?;
+            }
+            return Ok(());
+        }
+
+        log::trace!("sending:\n{} {}\n{}", self.version, status, &headers);
+        let mut output_buffer = Vec::with_capacity(self.http_config.response_buffer_len);
+        output_buffer.extend_from_slice(self.version.as_str().as_bytes());
+        output_buffer.push(b' ');
+        write_status_code(&mut output_buffer, status as u16);
+        output_buffer.push(b' ');
+        output_buffer.extend_from_slice(status.canonical_reason().as_bytes());
+        output_buffer.extend_from_slice(b"\r\n");
+        write_fields(&mut output_buffer, &headers);
+        output_buffer.extend_from_slice(b"\r\n");
+        self.transport.write_all(&output_buffer).await?;
+        self.transport.flush().await?;
+        Ok(())
+    }
+
     async fn send_100_continue(&mut self) -> Result<()> {
         log::trace!("sending 100-continue");
+        self.sent_100_continue = true;
         Ok(self
             .transport
             .write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
@@ -792,7 +1142,14 @@ where
         if !self.needs_100_continue() || self.request_body_state != ReceivedBodyState::Start {
             self.build_request_body().drain().await?;
         }
-        Conn::new_with_config(self.http_config, self.transport, self.buffer, self.stopper).await
+        Conn::new_with_config(
+            self.http_config,
+            self.transport,
+            self.buffer,
+            self.stopper,
+            self.connection_stats,
+        )
+        .await
     }
 
     fn should_close(&self) -> bool {
@@ -814,7 +1171,8 @@ where
             || self.status == Some(Status::SwitchingProtocols)
     }
 
-    async fn finish(self) -> Result<ConnectionStatus<Transport>> {
+    async fn finish(mut self) -> Result<ConnectionStatus<Transport>> {
+        self.connection_stats.response_sent();
         if self.should_close() {
             Ok(ConnectionStatus::Close)
         } else if self.should_upgrade() {
@@ -831,10 +1189,18 @@ where
         }
     }
 
+    // a body without framing headers is empty for http/1.x, but an http/2 body without a
+    // content-length may have any length
+    fn request_body_is_known_empty(&self) -> bool {
+        matches!(self.request_content_length(), Ok(Some(0)))
+            && (self.version != Version::Http2_0 || self.request_headers.has_header(ContentLength))
+    }
+
     fn request_content_length(&self) -> Result<Option<u64>> {
-        if self
-            .request_headers
-            .eq_ignore_ascii_case(TransferEncoding, "chunked")
+        if matches!(self.request_body_state, ReceivedBodyState::ReadToEnd { .. })
+            || self
+                .request_headers
+                .eq_ignore_ascii_case(TransferEncoding, "chunked")
         {
             Ok(None)
         } else if let Some(cl) = self.request_headers.get_str(ContentLength) {
@@ -853,17 +1219,15 @@ where
         }
     }
 
-    fn write_headers(&mut self, output_buffer: &mut Vec<u8>) -> Result<()> {
-        use std::io::Write;
+    fn write_headers(&mut self, output_buffer: &mut Vec<u8>) {
         let status = self.status().unwrap_or(Status::NotFound);
 
-        write!(
-            output_buffer,
-            "{} {} {}\r\n",
-            self.version,
-            status as u16,
-            status.canonical_reason()
-        )?;
+        output_buffer.extend_from_slice(self.version.as_str().as_bytes());
+        output_buffer.push(b' ');
+        write_status_code(output_buffer, status as u16);
+        output_buffer.push(b' ');
+        output_buffer.extend_from_slice(status.canonical_reason().as_bytes());
+        output_buffer.extend_from_slice(b"\r\n");
 
         self.finalize_headers();
 
@@ -874,24 +1238,8 @@ where
             &self.response_headers
         );
 
-        for (name, values) in &self.response_headers {
-            if name.is_valid() {
-                for value in values {
-                    if value.is_valid() {
-                        write!(output_buffer, "{name}: ")?;
-                        output_buffer.extend_from_slice(value.as_ref());
-                        write!(output_buffer, "\r\n")?;
-                    } else {
-                        log::error!("skipping invalid header value {value:?} for header {name}");
-                    }
-                }
-            } else {
-                log::error!("skipping invalid header with name {name:?}");
-            }
-        }
-
-        write!(output_buffer, "\r\n")?;
-        Ok(())
+        write_fields(output_buffer, &self.response_headers);
+        output_buffer.extend_from_slice(b"\r\n");
     }
 
     /// applies a mapping function from one transport to another. This
@@ -918,8 +1266,15 @@ where
             stopper,
             after_send,
             start_time,
+            connection_stats,
             peer_ip,
             http_config,
+            raw_head,
+            request_trailers,
+            response_trailers,
+            sent_100_continue,
+            #[cfg(feature = "http2")]
+            http2_response,
         } = self;
 
         Conn {
@@ -938,8 +1293,15 @@ where
             stopper,
             after_send,
             start_time,
+            connection_stats,
             peer_ip,
             http_config,
+            raw_head,
+            request_trailers,
+            response_trailers,
+            sent_100_continue,
+            #[cfg(feature = "http2")]
+            http2_response,
         }
     }
 
@@ -958,6 +1320,18 @@ where
         &mut self.transport
     }
 
+    /// sets the maximum length of the request body for this conn, overriding
+    /// [`HttpConfig::with_received_body_max_len`]. This must be called before the request body is
+    /// read.
+    pub fn set_received_body_max_len(&mut self, max_len: u64) {
+        self.http_config.received_body_max_len = max_len;
+    }
+
+    /// returns the maximum length of the request body for this conn
+    pub fn received_body_max_len(&self) -> u64 {
+        self.http_config.received_body_max_len
+    }
+
     /// sets the remote ip address for this conn, if available.
     pub fn set_peer_ip(&mut self, peer_ip: Option<IpAddr>) {
         self.peer_ip = peer_ip;
@@ -967,4 +1341,67 @@ where
     pub fn peer_ip(&self) -> Option<IpAddr> {
         self.peer_ip
     }
+
+    /// the bytes of the request head exactly as received, if
+    /// [`raw_head_max_len`][HttpConfig#raw_head_max_len] is nonzero
+    pub fn raw_head(&self) -> Option<&RawHead> {
+        self.raw_head.as_ref()
+    }
+}
+
+fn write_fields(output_buffer: &mut Vec<u8>, headers: &Headers) {
+    for (name, values) in headers {
+        if name.is_valid() {
+            for value in values {
+                if value.is_valid() {
+                    output_buffer.extend_from_slice(name.as_ref().as_bytes());
+                    output_buffer.extend_from_slice(b": ");
+                    output_buffer.extend_from_slice(value.as_ref());
+                    output_buffer.extend_from_slice(b"\r\n");
+                } else {
+                    log::error!("skipping invalid header value {value:?} for header {name}");
+                }
+            }
+        } else {
+            log::error!("skipping invalid header with name {name:?}");
+        }
+    }
+}
+
+// the body is read without its own framing, so that the last chunk can be followed by the
+// trailer section
+async fn write_chunked_with_trailers(
+    body: Body,
+    trailers: &Headers,
+    writer: &mut (impl AsyncWrite + Unpin),
+    buffer_len: usize,
+) -> Result<()> {
+    let mut reader = body.into_reader();
+    let mut buf = vec![0; buffer_len];
+    loop {
+        let bytes = reader.read(&mut buf).await?;
+        if bytes == 0 {
+            break;
+        }
+        writer
+            .write_all(format!("{bytes:X}\r\n").as_bytes())
+            .await?;
+        writer.write_all(&buf[..bytes]).await?;
+        writer.write_all(b"\r\n").await?;
+    }
+
+    let mut trailer_section = b"0\r\n".to_vec();
+    write_fields(&mut trailer_section, trailers);
+    trailer_section.extend_from_slice(b"\r\n");
+    writer.write_all(&trailer_section).await?;
+    Ok(())
+}
+
+// status codes are always three digits
+fn write_status_code(output_buffer: &mut Vec<u8>, code: u16) {
+    output_buffer.extend_from_slice(&[
+        b'0' + (code / 100 % 10) as u8,
+        b'0' + (code / 10 % 10) as u8,
+        b'0' + (code % 10) as u8,
+    ]);
 }
@@ -0,0 +1,88 @@
+use std::time::{Duration, Instant};
+
+/**
+# Keepalive statistics for the connection that a [`Conn`](crate::Conn) was received on
+
+An http/1.x connection may carry any number of sequential requests.
+`ConnectionStats` describes the connection as of a given request, and
+is available with [`Conn::connection_stats`](crate::Conn::connection_stats).
+When the connection ends, the final stats are passed to the
+[`connection_close_hook`](crate::HttpConfig#connection_close_hook), if
+one is configured, which can be used to aggregate keepalive
+effectiveness across connections.
+
+Each http/2 stream and each synthetic conn is reported as the only
+request on its connection.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConnectionStats {
+    opened_at: Instant,
+    requests: u64,
+    idle: Option<Duration>,
+    total_idle: Duration,
+    last_response_at: Option<Instant>,
+}
+
+impl ConnectionStats {
+    pub(crate) fn new(opened_at: Instant) -> Self {
+        Self {
+            opened_at,
+            requests: 1,
+            idle: None,
+            total_idle: Duration::ZERO,
+            last_response_at: None,
+        }
+    }
+
+    // records that the response to the current request has been sent
+    pub(crate) fn response_sent(&mut self) {
+        self.last_response_at = Some(Instant::now());
+    }
+
+    // records the start of a request, which is the first request on
+    // this connection unless a previous response has been sent
+    pub(crate) fn request_started(&mut self, start_time: Instant) {
+        if let Some(last_response_at) = self.last_response_at.take() {
+            let idle = start_time.saturating_duration_since(last_response_at);
+            self.requests += 1;
+            self.idle = Some(idle);
+            self.total_idle += idle;
+        }
+    }
+
+    /// The time that this connection was opened
+    pub fn opened_at(&self) -> Instant {
+        self.opened_at
+    }
+
+    /// How long this connection has been open
+    pub fn age(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
+    /// The number of requests received on this connection so far,
+    /// including the current request. This is `1` for the first request
+    /// on a connection.
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    /// Whether at least one request preceded the current request on
+    /// this connection
+    pub fn is_reused(&self) -> bool {
+        self.requests > 1
+    }
+
+    /// How long the connection was idle between the previous response
+    /// and the start of the current request, or `None` for the first
+    /// request on a connection
+    pub fn idle_duration(&self) -> Option<Duration> {
+        self.idle
+    }
+
+    /// The total time that this connection has spent idle between
+    /// requests
+    pub fn total_idle_duration(&self) -> Duration {
+        self.total_idle
+    }
+}
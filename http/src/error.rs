@@ -10,7 +10,7 @@ use thiserror::Error;
 pub enum Error {
     /// [`std::io::Error`]
     #[error(transparent)]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
 
     /// this error describes a malformed request with a path that does
     /// not start with / or http:// or https://
@@ -80,6 +80,11 @@ pub enum Error {
     #[error("unexpected header: {0}")]
     UnexpectedHeader(&'static str),
 
+    /// this header must not appear more than once, and the
+    /// [`DuplicateHeaderPolicy`](crate::DuplicateHeaderPolicy) is to reject such requests
+    #[error("duplicate header: {0}")]
+    DuplicateHeader(crate::KnownHeaderName),
+
     /// to mitigate against malicious http clients, we do not allow request headers beyond this
     /// length.
     #[error("Headers were malformed or longer than allowed")]
@@ -90,6 +95,31 @@ pub enum Error {
     /// implementation on `ReceivedBody`
     #[error("Received body too long. Maximum {0} bytes")]
     ReceivedBodyTooLong(u64),
+
+    /// the request body was already read, in part or in full, so it can no longer be retained
+    /// with [`Conn::buffer_request_body`](crate::Conn::buffer_request_body)
+    #[error("request body has already been read")]
+    RequestBodyAlreadyRead,
+
+    /// this status cannot be used here, such as a non-informational status passed to
+    /// [`Conn::send_informational`](crate::Conn::send_informational)
+    #[error("unexpected status {0}")]
+    UnexpectedStatus(crate::Status),
+
+    /// [`h2::Error`]
+    #[cfg(feature = "http2")]
+    #[error(transparent)]
+    Http2(#[from] h2::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        // ReceivedBody reports its length limit through AsyncRead as an io error
+        match error.get_ref().and_then(|inner| inner.downcast_ref()) {
+            Some(Self::ReceivedBodyTooLong(max_len)) => Self::ReceivedBodyTooLong(*max_len),
+            _ => Self::Io(error),
+        }
+    }
 }
 
 /// this crate's result type
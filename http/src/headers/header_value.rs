@@ -1,8 +1,10 @@
+use crate::alloc_metrics::record_allocation;
 use smallvec::SmallVec;
 use smartcow::SmartCow;
+use smartstring::SmartString;
 use std::{
     borrow::Cow,
-    fmt::{Debug, Display, Formatter},
+    fmt::{Debug, Display, Formatter, Write},
 };
 use HeaderValueInner::{Bytes, Utf8};
 
@@ -81,6 +83,34 @@ impl Display for HeaderValue {
     }
 }
 
+impl HeaderValue {
+    /// builds a header value from a borrowed slice, only allocating
+    /// if it is too long to be stored inline
+    pub(crate) fn from_slice(bytes: &[u8]) -> Self {
+        if let Ok(s) = std::str::from_utf8(bytes) {
+            let s = SmartString::from(s);
+            if !s.is_inline() {
+                record_allocation(s.len());
+            }
+            Self(Utf8(SmartCow::Owned(s)))
+        } else {
+            let bytes = SmallVec::from_slice(bytes);
+            if bytes.spilled() {
+                record_allocation(bytes.len());
+            }
+            Self(Bytes(bytes))
+        }
+    }
+
+    /// builds a header value from a short display value, such as a
+    /// number, without an intermediate `String`
+    pub(crate) fn from_display(value: impl Display) -> Self {
+        let mut s = SmartString::new();
+        write!(s, "{value}").expect("writing to a string cannot fail");
+        Self(Utf8(SmartCow::Owned(s)))
+    }
+}
+
 impl From<Vec<u8>> for HeaderValue {
     fn from(v: Vec<u8>) -> Self {
         match String::from_utf8(v) {
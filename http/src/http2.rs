@@ -0,0 +1,417 @@
+use crate::{
+    after_send::AfterSend,
+    received_body::ReceivedBodyState,
+    transport::{PeerCertificates, PeerCredentials, Transport, TransportKind},
+    Body, Conn, ConnectionStats, Error, Headers, HttpConfig, KnownHeaderName, Method, Result,
+    StateSet, Status, Stopper, Version,
+};
+use bytes::Bytes;
+use futures_lite::{future::poll_fn, ready, AsyncRead, AsyncReadExt, AsyncWrite};
+use h2::{
+    server::{self, SendResponse},
+    RecvStream, SendStream,
+};
+use http1 as http;
+use std::{
+    fmt::{self, Debug, Formatter},
+    future::{Future, IntoFuture},
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Instant,
+};
+use Poll::{Pending, Ready};
+
+/**
+The transport for a single http/2 request stream.
+
+Reading from an `Http2Transport` yields the request body received on the stream. Writing to it
+is a no-op, as responses are sent through the http/2 connection once the handler has returned
+the [`Conn`]. The [`Transport`] accessors such as [`Transport::peer_addr`] and
+[`Transport::negotiated_alpn`] describe the underlying connection.
+*/
+pub struct Http2Transport {
+    recv: Option<RecvStream>,
+    buffer: Bytes,
+    connection: Arc<ConnectionInfo>,
+}
+
+/// the properties of the underlying transport, shared by every stream on an http/2 connection
+#[derive(Debug)]
+struct ConnectionInfo {
+    peer_addr: Option<SocketAddr>,
+    local_addr: Option<SocketAddr>,
+    transport_kind: TransportKind,
+    negotiated_alpn: Option<Vec<u8>>,
+    peer_credentials: Option<PeerCredentials>,
+    peer_certificates: Option<PeerCertificates>,
+}
+
+impl ConnectionInfo {
+    fn new(transport: &impl Transport) -> Self {
+        Self {
+            peer_addr: transport.peer_addr().ok().flatten(),
+            local_addr: transport.local_addr().ok().flatten(),
+            transport_kind: transport.transport_kind(),
+            negotiated_alpn: transport.negotiated_alpn().map(<[u8]>::to_vec),
+            peer_credentials: transport.peer_credentials().ok().flatten(),
+            peer_certificates: transport.peer_certificates(),
+        }
+    }
+}
+
+impl Debug for Http2Transport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Http2Transport")
+            .field("end_of_stream", &self.recv.is_none())
+            .field("buffered", &self.buffer.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Http2Transport {
+    fn new(recv: RecvStream, connection: Arc<ConnectionInfo>) -> Self {
+        Self {
+            recv: (!recv.is_end_stream()).then_some(recv),
+            buffer: Bytes::new(),
+            connection,
+        }
+    }
+}
+
+impl AsyncRead for Http2Transport {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        while self.buffer.is_empty() {
+            let Some(recv) = self.recv.as_mut() else {
+                return Ready(Ok(0));
+            };
+
+            match ready!(recv.poll_data(cx)) {
+                Some(Ok(data)) => {
+                    recv.flow_control()
+                        .release_capacity(data.len())
+                        .map_err(io::Error::other)?;
+                    self.buffer = data;
+                }
+                Some(Err(e)) => return Ready(Err(io::Error::other(e))),
+                None => self.recv = None,
+            }
+        }
+
+        let len = buf.len().min(self.buffer.len());
+        buf[..len].copy_from_slice(&self.buffer.split_to(len));
+        Ready(Ok(len))
+    }
+}
+
+impl AsyncWrite for Http2Transport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Ready(Ok(()))
+    }
+}
+
+impl Transport for Http2Transport {
+    fn peer_addr(&self) -> io::Result<Option<SocketAddr>> {
+        Ok(self.connection.peer_addr)
+    }
+
+    fn local_addr(&self) -> io::Result<Option<SocketAddr>> {
+        Ok(self.connection.local_addr)
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        self.connection.transport_kind
+    }
+
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.connection.negotiated_alpn.as_deref()
+    }
+
+    fn peer_credentials(&self) -> io::Result<Option<PeerCredentials>> {
+        Ok(self.connection.peer_credentials)
+    }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        self.connection.peer_certificates.clone()
+    }
+}
+
+type StreamFuture<'a> = Pin<Box<dyn Future<Output = ()> + Send + 'a>>;
+
+impl Conn<Http2Transport> {
+    /**
+    Serve http/2 on the provided transport, calling the handler function with a `Conn` for each
+    request stream until the connection is closed. Streams are handled concurrently on the task
+    that calls this function.
+
+    This is used for transports that negotiated `h2` through tls alpn (see
+    [`Transport::negotiated_alpn`]). Cleartext http/2 (h2c), whether through prior knowledge or
+    an `Upgrade: h2c` request, is not currently supported.
+
+    When the stopper is stopped, the connection is shut down gracefully: requests in progress are
+    completed and no new requests are accepted.
+
+    # Errors
+
+    This will return an error if the http/2 handshake fails, or if there is a connection-level
+    http/2 or io error.
+    */
+    pub async fn map_http2<T, F, Fut>(
+        http_config: HttpConfig,
+        transport: T,
+        stopper: Stopper,
+        handler: F,
+    ) -> Result<()>
+    where
+        T: Transport,
+        F: Fn(Conn<Http2Transport>) -> Fut + Sync,
+        Fut: Future<Output = Conn<Http2Transport>> + Send,
+    {
+        let connection_info = Arc::new(ConnectionInfo::new(&transport));
+        let mut connection = server::handshake(TokioIo(transport)).await?;
+        let mut stop = stopper.clone().into_future();
+        let mut shutting_down = false;
+        let mut accepting = true;
+        let mut streams: Vec<StreamFuture<'_>> = vec![];
+        let handler = &handler;
+
+        poll_fn(|cx| {
+            if !shutting_down && Pin::new(&mut stop).poll(cx).is_ready() {
+                log::debug!("shutting down http/2 connection");
+                connection.graceful_shutdown();
+                shutting_down = true;
+            }
+
+            while accepting {
+                match connection.poll_accept(cx) {
+                    Ready(Some(Ok((request, mut respond)))) => {
+                        let stopper = stopper.clone();
+                        let connection_info = Arc::clone(&connection_info);
+                        streams.push(Box::pin(async move {
+                            match Conn::new_http2(http_config, request, stopper, connection_info) {
+                                Ok(mut conn) => {
+                                    conn.http2_response = Some(respond);
+                                    send_http2(handler(conn).await).await;
+                                }
+                                Err(e) => {
+                                    log::debug!("{e}");
+                                    let mut response = http::Response::new(());
+                                    *response.status_mut() = http::StatusCode::NOT_IMPLEMENTED;
+                                    if let Err(e) = respond.send_response(response, true) {
+                                        log::debug!("http/2 stream error: {e}");
+                                    }
+                                }
+                            }
+                        }));
+                    }
+                    Ready(Some(Err(e))) => return Ready(Err(Error::from(e))),
+                    Ready(None) => accepting = false,
+                    Pending => break,
+                }
+            }
+
+            streams.retain_mut(|stream| stream.as_mut().poll(cx).is_pending());
+
+            if accepting || !streams.is_empty() {
+                Pending
+            } else {
+                Ready(Ok(()))
+            }
+        })
+        .await
+    }
+
+    fn new_http2(
+        http_config: HttpConfig,
+        request: http::Request<RecvStream>,
+        stopper: Stopper,
+        connection_info: Arc<ConnectionInfo>,
+    ) -> Result<Self> {
+        let (parts, body) = request.into_parts();
+        let method = Method::try_from(parts.method)?;
+        let mut request_headers = Headers::from(parts.headers);
+        if let Some(authority) = parts.uri.authority() {
+            request_headers.try_insert(KnownHeaderName::Host, authority.to_string());
+        }
+        http_config.normalize_duplicate_headers(&mut request_headers)?;
+
+        let request_body_state =
+            if body.is_end_stream() || request_headers.has_header(KnownHeaderName::ContentLength) {
+                ReceivedBodyState::Start
+            } else {
+                ReceivedBodyState::ReadToEnd { total: 0 }
+            };
+
+        Ok(Self {
+            transport: Http2Transport::new(body, connection_info),
+            request_headers,
+            response_headers: Headers::new(),
+            path: parts
+                .uri
+                .path_and_query()
+                .map_or_else(|| String::from("/"), ToString::to_string),
+            method,
+            status: None,
+            version: Version::Http2_0,
+            state: StateSet::new(),
+            response_body: None,
+            buffer: Vec::with_capacity(http_config.request_buffer_initial_len).into(),
+            request_body_state,
+            secure: false,
+            stopper,
+            after_send: AfterSend::default(),
+            start_time: Instant::now(),
+            connection_stats: ConnectionStats::new(Instant::now()),
+            peer_ip: None,
+            http_config,
+            raw_head: None,
+            request_trailers: None,
+            response_trailers: None,
+            sent_100_continue: false,
+            http2_response: None,
+        })
+    }
+}
+
+async fn send_http2(mut conn: Conn<Http2Transport>) {
+    let Some(mut respond) = conn.http2_response.take() else {
+        log::error!("http/2 conn returned without its response stream");
+        return;
+    };
+    let result = send_response(&mut conn, &mut respond).await;
+    if let Err(e) = &result {
+        log::debug!("http/2 stream error: {e}");
+    }
+    conn.after_send.call(result.is_ok().into());
+}
+
+async fn send_response(
+    conn: &mut Conn<Http2Transport>,
+    respond: &mut SendResponse<Bytes>,
+) -> Result<()> {
+    conn.finalize_headers();
+    let status = conn.status.unwrap_or(Status::NotFound);
+
+    let mut headers = std::mem::take(&mut conn.response_headers);
+    for connection_specific in [
+        KnownHeaderName::Connection,
+        KnownHeaderName::KeepAlive,
+        KnownHeaderName::ProxyConnection,
+        KnownHeaderName::TransferEncoding,
+        KnownHeaderName::Upgrade,
+    ] {
+        headers.remove(connection_specific);
+    }
+
+    let mut response = http::Response::new(());
+    *response.status_mut() =
+        http::StatusCode::try_from(status).map_err(|e| Error::Io(io::Error::other(e)))?;
+    *response.headers_mut() = headers
+        .try_into()
+        .map_err(|e| Error::Io(io::Error::other(e)))?;
+
+    let (body, trailers) = if conn.method == Method::Head
+        || matches!(status, Status::NotModified | Status::NoContent)
+    {
+        (None, None)
+    } else {
+        (
+            conn.response_body.take(),
+            conn.response_trailers
+                .take()
+                .filter(|trailers| !trailers.is_empty()),
+        )
+    };
+
+    let end_of_stream = body.is_none() && trailers.is_none();
+    let mut send = respond.send_response(response, end_of_stream)?;
+    if let Some(body) = body {
+        send_body(body, &mut send, conn.http_config.response_buffer_len).await?;
+    }
+
+    if let Some(trailers) = trailers {
+        send.send_trailers(
+            trailers
+                .try_into()
+                .map_err(|e| Error::Io(io::Error::other(e)))?,
+        )?;
+    } else if !end_of_stream {
+        send.send_data(Bytes::new(), true)?;
+    }
+
+    Ok(())
+}
+
+async fn send_body(body: Body, send: &mut SendStream<Bytes>, buffer_len: usize) -> Result<()> {
+    // http/2 has its own framing, so the body is read without chunked encoding
+    let mut reader = body.into_reader();
+    let mut buf = vec![0; buffer_len];
+    loop {
+        let bytes = reader.read(&mut buf).await?;
+        if bytes == 0 {
+            return Ok(());
+        }
+
+        let mut data = Bytes::copy_from_slice(&buf[..bytes]);
+        while !data.is_empty() {
+            send.reserve_capacity(data.len());
+            let capacity = poll_fn(|cx| send.poll_capacity(cx))
+                .await
+                .ok_or(Error::Closed)??;
+            if capacity > 0 {
+                send.send_data(data.split_to(capacity.min(data.len())), false)?;
+            }
+        }
+    }
+}
+
+// h2 is written in terms of tokio's io traits
+struct TokioIo<T>(T);
+
+impl<T: AsyncRead + Unpin> tokio::io::AsyncRead for TokioIo<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let bytes = ready!(Pin::new(&mut self.0).poll_read(cx, buf.initialize_unfilled()))?;
+        buf.advance(bytes);
+        Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> tokio::io::AsyncWrite for TokioIo<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 
+use crate::{ConnectionStats, Error, HeaderValue, HeaderValues, Headers, KnownHeaderName, Result};
+
 pub const DEFAULT_CONFIG: HttpConfig = HttpConfig {
     response_buffer_len: 512,
     request_buffer_initial_len: 128,
@@ -10,8 +12,19 @@ pub const DEFAULT_CONFIG: HttpConfig = HttpConfig {
     received_body_max_len: 500 * 1024 * 1024,
     received_body_initial_len: 128,
     received_body_max_preallocate: 1024 * 1024,
+    raw_head_max_len: 0,
+    automatic_100_continue: true,
+    duplicate_header_policy: DuplicateHeaderPolicy::Reject,
+    duplicate_header_hook: None,
+    connection_close_hook: None,
 };
 
+/// request headers that must not appear more than once, because
+/// intermediaries that disagree about which value applies can be used to
+/// smuggle requests
+const SINGLETON_HEADERS: [KnownHeaderName; 2] =
+    [KnownHeaderName::ContentLength, KnownHeaderName::Host];
+
 /**
 # Performance and security parameters for trillium-http.
 
@@ -31,7 +44,8 @@ on values seen at runtime.
 The initial buffer allocated for the response. Ideally this would be exactly the length of the
 combined response headers and body, if the body is short. If the value is shorter than the headers
 plus the body, multiple transport writes will be performed, and if the value is longer, unnecessary
-memory will be allocated for each conn. Although a tcp packet can be up to 64kb, it is probably
+memory will be held by each response buffer. Response buffers are reused across responses on the
+same thread. Although a tcp packet can be up to 64kb, it is probably
 better to use a value less than 1.5kb.
 
 **Default**: `512`
@@ -115,10 +129,77 @@ The maximum length allowed before the http body begins for a given request.
 The maximum length of a received body. This applies to both chunked and fixed-length request bodies,
 and the correct value will be application dependent.
 
+This can be overridden for an individual conn with `Conn::set_received_body_max_len`, and for an
+individual body with `ReceivedBody::set_max_len`. Reading a longer body results in
+`Error::ReceivedBodyTooLong`, which trillium-api responds to with `413 Payload Too Large`.
+
 **Default**: `500mb` in bytes
 
 **Unit**: Byte count
 
+## Auditing parameters
+
+### `raw_head_max_len`
+
+When this is nonzero, up to this many bytes of each request head are retained exactly as they were
+received, and are available as a [`RawHead`][crate::RawHead] through
+[`Conn::raw_head`][crate::Conn::raw_head]. This is intended for auditing and for handlers that need
+to inspect the request as sent rather than as parsed, and it costs one allocation per request. A
+value larger than `head_max_len` has no additional effect.
+
+**Default**: `0` (disabled)
+
+**Unit**: Byte count
+
+## Protocol parameters
+
+### `automatic_100_continue`
+
+When a client sends `Expect: 100-continue`, it waits for a `100 Continue` response before sending
+the request body. When this is true, `100 Continue` is sent as soon as the request body is read
+with [`Conn::request_body`][crate::Conn::request_body]. When this is false, the handler is
+responsible for sending `100 Continue` with
+[`Conn::send_informational`][crate::Conn::send_informational] before reading the body, or for
+denying the body by responding without reading it.
+
+**Default**: `true`
+
+**Unit**: boolean
+
+### `duplicate_header_policy`
+
+How to handle a request that contains more than one `Content-Length` or `Host` header. Servers and
+proxies that disagree about which of several values applies can be used to smuggle requests, so
+trillium applies a single [`DuplicateHeaderPolicy`] consistently. With
+[`DuplicateHeaderPolicy::Reject`], the connection is closed with
+[`Error::DuplicateHeader`][crate::Error::DuplicateHeader]. Otherwise, only the first or last value is
+retained and the request is handled normally.
+
+**Default**: [`DuplicateHeaderPolicy::Reject`]
+
+**Unit**: [`DuplicateHeaderPolicy`]
+
+### `duplicate_header_hook`
+
+A function that is called with a [`DuplicateHeader`] for every duplicated `Content-Length` or
+`Host` header, before `duplicate_header_policy` is applied, for example to log or count these
+requests.
+
+**Default**: `None`
+
+**Unit**: `fn(&DuplicateHeader<'_>)`
+
+### `connection_close_hook`
+
+A function that is called with the final [`ConnectionStats`] when an http/1.x connection that
+received at least one request is closed or upgraded, for example to record how many requests each
+connection carried and how long it was open. Connections that close before a request is received
+are not reported.
+
+**Default**: `None`
+
+**Unit**: `fn(&ConnectionStats)`
+
 */
 
 #[derive(Clone, Copy, Debug)]
@@ -132,6 +213,11 @@ pub struct HttpConfig {
     pub(crate) copy_loops_per_yield: usize,
     pub(crate) received_body_initial_len: usize,
     pub(crate) received_body_max_preallocate: usize,
+    pub(crate) raw_head_max_len: usize,
+    pub(crate) automatic_100_continue: bool,
+    pub(crate) duplicate_header_policy: DuplicateHeaderPolicy,
+    pub(crate) duplicate_header_hook: Option<fn(&DuplicateHeader<'_>)>,
+    pub(crate) connection_close_hook: Option<fn(&ConnectionStats)>,
 }
 
 #[allow(missing_docs)]
@@ -190,6 +276,188 @@ impl HttpConfig {
         self.received_body_max_preallocate = received_body_max_preallocate;
         self
     }
+
+    /// See [`raw_head_max_len`][HttpConfig#raw_head_max_len]
+    #[must_use]
+    pub fn with_raw_head_max_len(mut self, raw_head_max_len: usize) -> Self {
+        self.raw_head_max_len = raw_head_max_len;
+        self
+    }
+
+    /// See [`automatic_100_continue`][HttpConfig#automatic_100_continue]
+    #[must_use]
+    pub fn with_automatic_100_continue(mut self, automatic_100_continue: bool) -> Self {
+        self.automatic_100_continue = automatic_100_continue;
+        self
+    }
+
+    /// See [`duplicate_header_policy`][HttpConfig#duplicate_header_policy]
+    #[must_use]
+    pub fn with_duplicate_header_policy(
+        mut self,
+        duplicate_header_policy: DuplicateHeaderPolicy,
+    ) -> Self {
+        self.duplicate_header_policy = duplicate_header_policy;
+        self
+    }
+
+    /// See [`duplicate_header_hook`][HttpConfig#duplicate_header_hook]
+    #[must_use]
+    pub fn with_duplicate_header_hook(
+        mut self,
+        duplicate_header_hook: fn(&DuplicateHeader<'_>),
+    ) -> Self {
+        self.duplicate_header_hook = Some(duplicate_header_hook);
+        self
+    }
+
+    /// See [`connection_close_hook`][HttpConfig#connection_close_hook]
+    #[must_use]
+    pub fn with_connection_close_hook(
+        mut self,
+        connection_close_hook: fn(&ConnectionStats),
+    ) -> Self {
+        self.connection_close_hook = Some(connection_close_hook);
+        self
+    }
+}
+
+impl HttpConfig {
+    /// See [`response_buffer_len`][HttpConfig#response_buffer_len]
+    pub fn response_buffer_len(&self) -> usize {
+        self.response_buffer_len
+    }
+
+    /// See [`request_buffer_initial_len`][HttpConfig#request_buffer_initial_len]
+    pub fn request_buffer_initial_len(&self) -> usize {
+        self.request_buffer_initial_len
+    }
+
+    /// See [`head_max_len`][HttpConfig#head_max_len]
+    pub fn head_max_len(&self) -> usize {
+        self.head_max_len
+    }
+
+    /// The maximum number of headers accepted in a request
+    pub fn max_headers(&self) -> usize {
+        self.max_headers
+    }
+
+    /// See [`response_header_initial_capacity`][HttpConfig#response_header_initial_capacity]
+    pub fn response_header_initial_capacity(&self) -> usize {
+        self.response_header_initial_capacity
+    }
+
+    /// See [`copy_loops_per_yield`][HttpConfig#copy_loops_per_yield]
+    pub fn copy_loops_per_yield(&self) -> usize {
+        self.copy_loops_per_yield
+    }
+
+    /// See [`received_body_max_len`][HttpConfig#received_body_max_len]
+    pub fn received_body_max_len(&self) -> u64 {
+        self.received_body_max_len
+    }
+
+    /// See [`received_body_initial_len`][HttpConfig#received_body_initial_len]
+    pub fn received_body_initial_len(&self) -> usize {
+        self.received_body_initial_len
+    }
+
+    /// See [`received_body_max_preallocate`][HttpConfig#received_body_max_preallocate]
+    pub fn received_body_max_preallocate(&self) -> usize {
+        self.received_body_max_preallocate
+    }
+
+    /// See [`raw_head_max_len`][HttpConfig#raw_head_max_len]
+    pub fn raw_head_max_len(&self) -> usize {
+        self.raw_head_max_len
+    }
+
+    /// See [`automatic_100_continue`][HttpConfig#automatic_100_continue]
+    pub fn automatic_100_continue(&self) -> bool {
+        self.automatic_100_continue
+    }
+
+    /// See [`duplicate_header_policy`][HttpConfig#duplicate_header_policy]
+    pub fn duplicate_header_policy(&self) -> DuplicateHeaderPolicy {
+        self.duplicate_header_policy
+    }
+
+    /// applies the [`DuplicateHeaderPolicy`] to each singleton header that
+    /// appears more than once in these request headers
+    pub(crate) fn normalize_duplicate_headers(&self, request_headers: &mut Headers) -> Result<()> {
+        for name in SINGLETON_HEADERS {
+            let Some(values) = request_headers.get_values(name) else {
+                continue;
+            };
+
+            if values.len() < 2 {
+                continue;
+            }
+
+            if let Some(hook) = self.duplicate_header_hook {
+                hook(&DuplicateHeader {
+                    name,
+                    values,
+                    policy: self.duplicate_header_policy,
+                });
+            }
+
+            let value: Option<HeaderValue> = match self.duplicate_header_policy {
+                DuplicateHeaderPolicy::Reject => return Err(Error::DuplicateHeader(name)),
+                DuplicateHeaderPolicy::FirstWins => values.first().cloned(),
+                DuplicateHeaderPolicy::LastWins => values.last().cloned(),
+            };
+
+            if let Some(value) = value {
+                request_headers.insert(name, value);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// how a request that repeats a `Content-Length` or `Host` header is
+/// handled. See [`duplicate_header_policy`][HttpConfig#duplicate_header_policy]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DuplicateHeaderPolicy {
+    /// close the connection without handling the request
+    #[default]
+    Reject,
+
+    /// use the first value and discard the others
+    FirstWins,
+
+    /// use the last value and discard the others
+    LastWins,
+}
+
+/// a singleton request header that was received more than once, as
+/// provided to the [`duplicate_header_hook`][HttpConfig#duplicate_header_hook]
+#[derive(Clone, Copy, Debug)]
+pub struct DuplicateHeader<'a> {
+    name: KnownHeaderName,
+    values: &'a HeaderValues,
+    policy: DuplicateHeaderPolicy,
+}
+
+impl DuplicateHeader<'_> {
+    /// the name of the duplicated header
+    pub fn name(&self) -> KnownHeaderName {
+        self.name
+    }
+
+    /// every value received for this header, in the order received
+    pub fn values(&self) -> &HeaderValues {
+        self.values
+    }
+
+    /// the policy that will be applied to this request
+    pub fn policy(&self) -> DuplicateHeaderPolicy {
+        self.policy
+    }
 }
 
 impl Default for HttpConfig {
@@ -0,0 +1,33 @@
+use crate::{alloc_metrics::record_allocation, HeaderValue};
+use std::{
+    cell::RefCell,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+thread_local! {
+    static CACHED: RefCell<Option<(u64, HeaderValue)>> = const { RefCell::new(None) };
+}
+
+/// Returns the current time formatted as an http date, reformatting
+/// at most once per second per thread
+pub(crate) fn now() -> HeaderValue {
+    let now = SystemTime::now();
+    let seconds = now
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    CACHED.with_borrow_mut(|cached| {
+        let value = match cached {
+            Some((cached_seconds, value)) if *cached_seconds == seconds => value.clone(),
+            _ => {
+                let value = HeaderValue::from(httpdate::fmt_http_date(now));
+                *cached = Some((seconds, value.clone()));
+                value
+            }
+        };
+
+        // http dates are longer than the inline string capacity
+        record_allocation(value.as_ref().len());
+        value
+    })
+}
@@ -68,6 +68,12 @@ stopper.stop(); // stop the server after one request
 server_handle.await?; // wait for the server to shut down
 # Result::Ok(()) }) }
 ```
+
+## HTTP/2
+
+With the `http2` cargo feature, [`Conn::map_http2`] serves http/2 on a transport that negotiated
+`h2` through tls alpn, calling the handler with a [`Conn`] for each request stream. Runtime adapters
+expose this as an `http2` feature as well.
 */
 
 mod received_body;
@@ -85,6 +91,9 @@ pub use conn::{Conn, SERVER};
 mod connection_status;
 pub use connection_status::ConnectionStatus;
 
+mod connection_stats;
+pub use connection_stats::ConnectionStats;
+
 mod synthetic;
 pub use synthetic::Synthetic;
 
@@ -136,11 +145,19 @@ pub mod http_compat0;
 #[cfg(feature = "http-compat")]
 pub use http_compat0 as http_compat; // for semver
 
+#[cfg(feature = "http2")]
+mod http2;
+#[cfg(feature = "http2")]
+pub use http2::Http2Transport;
+
 mod bufwriter;
 pub(crate) use bufwriter::BufWriter;
 
 mod http_config;
-pub use http_config::HttpConfig;
+pub use http_config::{DuplicateHeader, DuplicateHeaderPolicy, HttpConfig};
+
+mod raw_head;
+pub use raw_head::RawHead;
 
 pub(crate) mod after_send;
 
@@ -157,3 +174,12 @@ pub use copy::copy;
 pub(crate) use copy::copy;
 
 mod liveness;
+
+#[cfg_attr(not(feature = "alloc-metrics"), allow(dead_code))]
+mod alloc_metrics;
+#[cfg(feature = "alloc-metrics")]
+pub use alloc_metrics::{alloc_metrics, AllocMetrics};
+
+mod buffer_pool;
+
+mod http_date;
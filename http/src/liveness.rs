@@ -21,6 +21,13 @@ where
     type Output = ();
 
     fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // an http/2 request stream's body ends independently of the client's interest in the
+        // response, so the client has only gone away once the stream has been reset
+        #[cfg(feature = "http2")]
+        if let Some(respond) = &mut self.0.http2_response {
+            return respond.poll_reset(cx).map(|_| ());
+        }
+
         let LivenessFut(Conn {
             buffer, transport, ..
         }) = &mut *self;
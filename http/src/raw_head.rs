@@ -0,0 +1,71 @@
+use std::fmt::{self, Debug, Formatter};
+
+/**
+The bytes of a request head exactly as they were received, before
+parsing or normalization, retained when
+[`raw_head_max_len`][crate::HttpConfig#raw_head_max_len] is nonzero.
+
+The request head is the request line and all header lines, including
+the terminating empty line. If the head was longer than
+`raw_head_max_len`, only that many bytes are retained and
+[`RawHead::is_truncated`] returns true.
+*/
+#[derive(Clone, PartialEq, Eq)]
+pub struct RawHead {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl Debug for RawHead {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RawHead")
+            .field("bytes", &String::from_utf8_lossy(&self.bytes))
+            .field("truncated", &self.truncated)
+            .finish()
+    }
+}
+
+impl RawHead {
+    pub(crate) fn new(head: &[u8], max_len: usize) -> Self {
+        let truncated = head.len() > max_len;
+        Self {
+            bytes: head[..head.len().min(max_len)].to_vec(),
+            truncated,
+        }
+    }
+
+    /// the retained bytes of the request head
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// returns true if the request head was longer than
+    /// `raw_head_max_len` and only part of it was retained
+    pub const fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// the request line, such as `GET / HTTP/1.1`, without its line
+    /// ending. Returns None if the request line was truncated.
+    pub fn request_line(&self) -> Option<&[u8]> {
+        self.lines().next()
+    }
+
+    /// each header line as received, without line endings, in the
+    /// order they were received. A header line that was cut off by
+    /// truncation is not included.
+    pub fn header_lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines().skip(1).filter(|line| !line.is_empty())
+    }
+
+    // complete lines only, split on \n with any trailing \r removed
+    fn lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.bytes
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map(|end| &self.bytes[..end])
+            .into_iter()
+            .flat_map(|complete| complete.split(|byte| *byte == b'\n'))
+            .map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{copy, http_config::DEFAULT_CONFIG, Body, Buffer, HttpConfig, MutCow};
+use crate::{copy, http_config::DEFAULT_CONFIG, Body, Buffer, Headers, HttpConfig, MutCow};
 use encoding_rs::Encoding;
 use futures_lite::{ready, AsyncRead, AsyncReadExt, AsyncWrite, Stream};
 use httparse::{InvalidChunkSize, Status};
@@ -11,10 +11,12 @@ use std::{
     task::{Context, Poll},
 };
 use Poll::{Pending, Ready};
-use ReceivedBodyState::{Chunked, End, FixedLength, PartialChunkSize, Start};
+use ReceivedBodyState::{Chunked, End, FixedLength, PartialChunkSize, ReadToEnd, Start, Trailers};
 
 mod chunked;
 mod fixed_length;
+mod read_to_end;
+mod trailers;
 
 /** A received http body
 
@@ -34,9 +36,15 @@ assert_eq!(body.read_string().await?, "hello");
 ## Bounds checking
 
 Every `ReceivedBody` has a maximum length beyond which it will return an error, expressed as a
-u64. To override this on the specific `ReceivedBody`, use [`ReceivedBody::with_max_len`] or
+u64. This is initially [`HttpConfig::with_received_body_max_len`], which can be overridden for a
+single conn with [`Conn::set_received_body_max_len`](crate::Conn::set_received_body_max_len). To
+override this on the specific `ReceivedBody`, use [`ReceivedBody::with_max_len`] or
 [`ReceivedBody::set_max_len`]
 
+Bodies that declare a longer content-length are rejected before any content is read. When reading
+through the `AsyncRead` implementation, exceeding the maximum length results in an
+[`io::Error`](std::io::Error) that converts into [`Error::ReceivedBodyTooLong`](crate::Error::ReceivedBodyTooLong).
+
 The default maximum length is currently set to 500mb. In the next semver-minor release, this value
 will decrease substantially.
 
@@ -51,12 +59,15 @@ pub struct ReceivedBody<'conn, Transport> {
     buffer: MutCow<'conn, Buffer>,
     transport: Option<MutCow<'conn, Transport>>,
     state: MutCow<'conn, ReceivedBodyState>,
+    trailers: MutCow<'conn, Option<Headers>>,
     on_completion: Option<Box<dyn Fn(Transport) + Send + Sync + 'static>>,
     encoding: &'static Encoding,
     max_len: u64,
     initial_len: usize,
     copy_loops_per_yield: usize,
     max_preallocate: usize,
+    trailers_max_len: usize,
+    max_trailers: usize,
 }
 
 fn slice_from(min: u64, buf: &[u8]) -> Option<&[u8]> {
@@ -105,15 +116,31 @@ where
             buffer: buffer.into(),
             transport: Some(transport.into()),
             state: state.into(),
+            trailers: MutCow::Owned(None),
             on_completion,
             encoding,
             max_len: config.received_body_max_len,
             initial_len: config.received_body_initial_len,
             copy_loops_per_yield: config.copy_loops_per_yield,
             max_preallocate: config.received_body_max_preallocate,
+            trailers_max_len: config.head_max_len,
+            max_trailers: config.max_headers,
         }
     }
 
+    pub(crate) fn with_trailers(mut self, trailers: &'conn mut Option<Headers>) -> Self {
+        self.trailers = MutCow::Borrowed(trailers);
+        self
+    }
+
+    /**
+    Returns the trailers that were sent after a chunked body, if any. Trailers are only
+    available once the body has been read to the end.
+    */
+    pub fn trailers(&self) -> Option<&Headers> {
+        self.trailers.as_ref()
+    }
+
     /**
     Returns the content-length of this body, if available. This
     usually is derived from the content-length header. If the http
@@ -292,6 +319,13 @@ where
     }
 }
 
+pub(crate) fn too_long(max_len: u64) -> io::Error {
+    io::Error::new(
+        ErrorKind::Unsupported,
+        crate::Error::ReceivedBodyTooLong(max_len),
+    )
+}
+
 const STREAM_READ_BUF_LENGTH: usize = 128;
 impl<'conn, Transport> Stream for ReceivedBody<'conn, Transport>
 where
@@ -335,17 +369,12 @@ where
             match self.content_length {
                 Some(0) => End,
 
-                Some(total_length) if total_length < self.max_len => FixedLength {
+                Some(total_length) if total_length <= self.max_len => FixedLength {
                     current_index: 0,
                     total: total_length,
                 },
 
-                Some(_) => {
-                    return Ready(Err(io::Error::new(
-                        ErrorKind::Unsupported,
-                        "content too long",
-                    )))
-                }
+                Some(_) => return Ready(Err(too_long(self.max_len))),
 
                 None => Chunked {
                     remaining: 0,
@@ -375,6 +404,8 @@ where
                     current_index,
                     total,
                 } => self.handle_fixed_length(cx, buf, current_index, total),
+                ReadToEnd { total } => self.handle_read_to_end(cx, buf, total),
+                Trailers => self.handle_trailers(cx, buf),
                 End => Ready(Ok((End, 0))),
             })?;
 
@@ -402,6 +433,7 @@ impl<'conn, Transport> Debug for ReceivedBody<'conn, Transport> {
         f.debug_struct("RequestBody")
             .field("state", &*self.state)
             .field("content_length", &self.content_length)
+            .field("trailers", &*self.trailers)
             .field("buffer", &"..")
             .field("on_completion", &self.on_completion.is_some())
             .finish()
@@ -442,6 +474,16 @@ pub enum ReceivedBodyState {
         total: u64,
     },
 
+    /// read state for a body that is not framed by http/1.x, such as an http/2 request body,
+    /// which ends when the transport has no more data to read.
+    ReadToEnd {
+        /// total indicates the absolute number of bytes read
+        total: u64,
+    },
+
+    /// read state for the trailer section that follows the last chunk of a chunked body
+    Trailers,
+
     /// the terminal read state
     End,
 }
@@ -1,6 +1,7 @@
 use super::{
-    io, ready, slice_from, AsyncRead, Buffer, Chunked, Context, End, ErrorKind, InvalidChunkSize,
-    PartialChunkSize, Pin, Ready, ReceivedBody, ReceivedBodyState, StateOutput, Status,
+    io, ready, slice_from, too_long, AsyncRead, Buffer, Chunked, Context, End, ErrorKind,
+    InvalidChunkSize, PartialChunkSize, Pin, Ready, ReceivedBody, ReceivedBodyState, StateOutput,
+    Status, Trailers,
 };
 
 impl<'conn, Transport> ReceivedBody<'conn, Transport>
@@ -50,7 +51,7 @@ where
                 self.buffer.ignore_front(framing_bytes);
                 Ready(Ok((
                     if remaining == 0 {
-                        End
+                        Trailers
                     } else {
                         Chunked {
                             remaining: remaining + 2,
@@ -94,7 +95,7 @@ pub(super) fn chunk_decode(
             let new_bytes = (keep_end - keep_start) as u64;
             total += new_bytes;
             if total > max_len {
-                return Err(io::Error::new(ErrorKind::Unsupported, "content too long"));
+                return Err(too_long(max_len));
             }
         }
         chunk_start = chunk_end;
@@ -121,10 +122,22 @@ pub(super) fn chunk_decode(
                     .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "chunk size too long"))?;
 
                 if chunk_size == 0 {
-                    if let Some(buf) = slice_from(chunk_end, buf) {
-                        self_buffer.extend_from_slice(buf);
+                    // the last chunk is followed by an optional trailer section and a blank line
+                    match slice_from(chunk_start, buf) {
+                        Some(rest) if rest.starts_with(b"\r\n") => {
+                            if let Some(buf) = slice_from(chunk_end, buf) {
+                                self_buffer.extend_from_slice(buf);
+                            }
+                            break End;
+                        }
+
+                        Some(rest) => {
+                            self_buffer.extend_from_slice(rest);
+                            break Trailers;
+                        }
+
+                        None => break Trailers,
                     }
-                    break End;
                 }
             }
 
@@ -324,6 +337,31 @@ mod tests {
         assert_decoded((7, "hello\r\n0\r\n\r\n"), (None, "hello", ""));
     }
 
+    #[test]
+    fn trailers() {
+        block_on(async {
+            for size in 1..50 {
+                let input = "5\r\nhello\r\n0\r\nx-checksum: abc\r\nx-other: d\r\n\r\nnext";
+                let mut rb = new_with_config(input.into(), &DEFAULT_CONFIG);
+                let output = read_with_buffers_of_size(&mut rb, size).await.unwrap();
+                assert_eq!(output, "hello", "size: {size}");
+                let trailers = rb.trailers().unwrap();
+                assert_eq!(trailers.get_str("x-checksum"), Some("abc"), "size: {size}");
+                assert_eq!(trailers.get_str("x-other"), Some("d"), "size: {size}");
+                assert!(b"next".starts_with(&rb.buffer), "size: {size}");
+
+                let input = "5\r\nhello\r\n0\r\n\r\nnext";
+                let mut rb = new_with_config(input.into(), &DEFAULT_CONFIG);
+                let output = read_with_buffers_of_size(&mut rb, size).await.unwrap();
+                assert_eq!(output, "hello", "size: {size}");
+                assert!(rb.trailers().is_none());
+
+                let input = "5\r\nhello\r\n0\r\nnot a trailer\r\n\r\n";
+                assert!(decode(input.into(), size).await.is_err(), "size: {size}");
+            }
+        });
+    }
+
     #[test]
     fn read_string_and_read_bytes() {
         block_on(async {
@@ -375,4 +413,26 @@ mod tests {
                 .is_err());
         });
     }
+
+    #[test]
+    fn max_len_errors() {
+        block_on(async {
+            let content = build_chunked_body("test ".repeat(100)).await;
+            assert!(matches!(
+                new_with_config(
+                    content.clone(),
+                    &DEFAULT_CONFIG.with_received_body_max_len(400)
+                )
+                .read_bytes()
+                .await,
+                Err(crate::Error::ReceivedBodyTooLong(400))
+            ));
+
+            assert!(matches!(
+                decode_with_config(content, 10, &DEFAULT_CONFIG.with_received_body_max_len(400))
+                    .await,
+                Err(crate::Error::ReceivedBodyTooLong(400))
+            ));
+        });
+    }
 }
@@ -154,4 +154,38 @@ mod tests {
                 .is_err());
         });
     }
+
+    #[test]
+    fn max_len_errors() {
+        block_on(async {
+            let content = "test ".repeat(10);
+            assert_eq!(
+                new_with_config(
+                    content.clone(),
+                    &DEFAULT_CONFIG.with_received_body_max_len(50)
+                )
+                .read_string()
+                .await
+                .unwrap(),
+                content
+            );
+
+            assert!(matches!(
+                new_with_config(
+                    content.clone(),
+                    &DEFAULT_CONFIG.with_received_body_max_len(49)
+                )
+                .read_bytes()
+                .await,
+                Err(crate::Error::ReceivedBodyTooLong(49))
+            ));
+
+            let mut received_body =
+                new_with_config(content, &DEFAULT_CONFIG.with_received_body_max_len(49));
+            assert!(matches!(
+                read_with_buffers_of_size(&mut received_body, 10).await,
+                Err(crate::Error::ReceivedBodyTooLong(49))
+            ));
+        });
+    }
 }
@@ -0,0 +1,26 @@
+use super::{
+    ready, too_long, AsyncRead, Context, End, ReadToEnd, Ready, ReceivedBody, StateOutput,
+};
+
+impl<Transport> ReceivedBody<'_, Transport>
+where
+    Transport: AsyncRead + Unpin + Send + Sync + 'static,
+{
+    #[inline]
+    pub(super) fn handle_read_to_end(
+        &mut self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+        total: u64,
+    ) -> StateOutput {
+        let bytes = ready!(self.read_raw(cx, buf)?);
+        let total = total + bytes as u64;
+        if bytes == 0 {
+            Ready(Ok((End, 0)))
+        } else if total > self.max_len {
+            Ready(Err(too_long(self.max_len)))
+        } else {
+            Ready(Ok((ReadToEnd { total }, bytes)))
+        }
+    }
+}
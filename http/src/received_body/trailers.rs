@@ -0,0 +1,68 @@
+use super::{
+    io, ready, AsyncRead, Context, End, ErrorKind, Pin, Ready, ReceivedBody, StateOutput, Trailers,
+};
+use crate::{HeaderName, HeaderValue, Headers};
+use httparse::{Status, EMPTY_HEADER};
+use memchr::memmem;
+use std::str::FromStr;
+
+impl<Transport> ReceivedBody<'_, Transport>
+where
+    Transport: AsyncRead + Unpin + Send + Sync + 'static,
+{
+    #[inline]
+    pub(super) fn handle_trailers(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> StateOutput {
+        if self.buffer.starts_with(b"\r\n") {
+            self.buffer.ignore_front(2);
+            return Ready(Ok((End, 0)));
+        }
+
+        if let Some(index) = memmem::find(&self.buffer, b"\r\n\r\n") {
+            let trailers = parse_trailers(&self.buffer[..index + 4], self.max_trailers)?;
+            self.buffer.ignore_front(index + 4);
+            *self.trailers = Some(trailers);
+            return Ready(Ok((End, 0)));
+        }
+
+        if self.buffer.len() >= self.trailers_max_len {
+            return Ready(Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "trailers too long",
+            )));
+        }
+
+        let transport = self
+            .transport
+            .as_deref_mut()
+            .ok_or_else(|| io::Error::from(ErrorKind::NotConnected))?;
+        let bytes = ready!(Pin::new(transport).poll_read(cx, buf))?;
+
+        if bytes == 0 {
+            // tolerate a body that ends without the final blank line
+            return if self.buffer.is_empty() {
+                Ready(Ok((End, 0)))
+            } else {
+                Ready(Err(io::Error::from(ErrorKind::ConnectionAborted)))
+            };
+        }
+
+        self.buffer.extend_from_slice(&buf[..bytes]);
+        Ready(Ok((Trailers, 0)))
+    }
+}
+
+fn parse_trailers(section: &[u8], max_trailers: usize) -> io::Result<Headers> {
+    let mut headers = vec![EMPTY_HEADER; max_trailers];
+    let Ok(Status::Complete((_, headers))) = httparse::parse_headers(section, &mut headers) else {
+        return Err(io::Error::new(ErrorKind::InvalidData, "invalid trailers"));
+    };
+
+    let mut trailers = Headers::with_capacity(headers.len());
+    for header in headers {
+        let name = HeaderName::from_str(header.name)
+            .map_err(|_| io::Error::new(ErrorKind::InvalidData, "invalid trailer name"))?;
+        trailers.append(name, HeaderValue::from_slice(header.value));
+    }
+
+    Ok(trailers)
+}
@@ -1,6 +1,7 @@
 use crate::{
     after_send::AfterSend, http_config::DEFAULT_CONFIG, received_body::ReceivedBodyState,
-    transport::Transport, Conn, Headers, KnownHeaderName, Method, StateSet, Stopper, Version,
+    transport::Transport, Conn, ConnectionStats, Headers, KnownHeaderName, Method, StateSet,
+    Stopper, Version,
 };
 use futures_lite::io::{AsyncRead, AsyncWrite, Cursor, Result};
 use std::{
@@ -160,8 +161,15 @@ impl Conn<Synthetic> {
             stopper: Stopper::new(),
             after_send: AfterSend::default(),
             start_time: Instant::now(),
+            connection_stats: ConnectionStats::new(Instant::now()),
             peer_ip: None,
             http_config: DEFAULT_CONFIG,
+            raw_head: None,
+            request_trailers: None,
+            response_trailers: None,
+            sent_100_continue: false,
+            #[cfg(feature = "http2")]
+            http2_response: None,
         }
     }
 
@@ -1,4 +1,4 @@
-use crate::transport::Transport;
+use crate::transport::{PeerCertificates, PeerCredentials, Transport, TransportKind};
 use futures_lite::io::{AsyncRead, AsyncWrite};
 use std::{
     any::Any,
@@ -137,4 +137,24 @@ impl Transport for BoxedTransport {
     fn peer_addr(&self) -> Result<Option<SocketAddr>> {
         self.0.peer_addr()
     }
+
+    fn local_addr(&self) -> Result<Option<SocketAddr>> {
+        self.0.local_addr()
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        self.0.transport_kind()
+    }
+
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.0.negotiated_alpn()
+    }
+
+    fn peer_credentials(&self) -> Result<Option<PeerCredentials>> {
+        self.0.peer_credentials()
+    }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        self.0.peer_certificates()
+    }
 }
@@ -1,7 +1,16 @@
 mod boxed_transport;
 pub use boxed_transport::BoxedTransport;
+
+mod peer_credentials;
 use futures_lite::{AsyncRead, AsyncWrite};
+pub use peer_credentials::PeerCredentials;
+
+mod peer_certificates;
+pub use peer_certificates::PeerCertificates;
+
+mod transport_kind;
 use std::{any::Any, io::Result, net::SocketAddr, time::Duration};
+pub use transport_kind::TransportKind;
 
 /**
 # The interface that the http protocol is communicated over.
@@ -70,6 +79,58 @@ pub trait Transport: Any + AsyncRead + AsyncWrite + Unpin + Send + Sync + 'stati
     fn peer_addr(&self) -> Result<Option<SocketAddr>> {
         Ok(None)
     }
+
+    /// # Returns the local socket address of this transport.
+    ///
+    /// This is the address that the remote peer connected to. For
+    /// transports that carry a proxy protocol header, this is the
+    /// destination address from that header.
+    /// Optional to implement.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if this transport supports retrieving the
+    /// local address but attempting to do so is unsuccessful.
+    fn local_addr(&self) -> Result<Option<SocketAddr>> {
+        Ok(None)
+    }
+
+    /// # Returns the kind of socket this transport communicates over.
+    ///
+    /// Transports that wrap another transport should return the kind of the inner transport.
+    /// Optional to implement, defaulting to [`TransportKind::Other`].
+    fn transport_kind(&self) -> TransportKind {
+        TransportKind::Other
+    }
+
+    /// # Returns the application protocol negotiated through tls alpn, if any
+    ///
+    /// For example, this returns `Some(b"h2")` for a tls transport that negotiated http/2.
+    /// Optional to implement.
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// # Returns the credentials of the remote peer of this transport.
+    ///
+    /// This is only available for unix domain sockets on some operating systems.
+    /// Optional to implement.
+    ///
+    /// # Errors
+    ///
+    /// Return an error if this transport supports retrieving peer
+    /// credentials but attempting to do so is unsuccessful.
+    fn peer_credentials(&self) -> Result<Option<PeerCredentials>> {
+        Ok(None)
+    }
+
+    /// # Returns the certificate chain presented by the remote peer of this transport.
+    ///
+    /// This is only available for tls transports that requested a client certificate and
+    /// received one. Optional to implement.
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        None
+    }
 }
 
 impl Transport for Box<dyn Transport> {
@@ -88,4 +149,24 @@ impl Transport for Box<dyn Transport> {
     fn peer_addr(&self) -> Result<Option<SocketAddr>> {
         (**self).peer_addr()
     }
+
+    fn local_addr(&self) -> Result<Option<SocketAddr>> {
+        (**self).local_addr()
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        (**self).transport_kind()
+    }
+
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        (**self).negotiated_alpn()
+    }
+
+    fn peer_credentials(&self) -> Result<Option<PeerCredentials>> {
+        (**self).peer_credentials()
+    }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        (**self).peer_certificates()
+    }
 }
@@ -0,0 +1,41 @@
+/**
+# The certificate chain presented by the remote peer of a tls connection
+
+Returned by [`Transport::peer_certificates`](crate::transport::Transport::peer_certificates)
+for tls transports that requested a certificate from the peer and received one. Each certificate
+is der-encoded, and the first certificate is the peer's own (end-entity) certificate, followed by
+any intermediate certificates that the peer sent.
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PeerCertificates(Vec<Vec<u8>>);
+
+impl PeerCertificates {
+    /// construct a new `PeerCertificates` from der-encoded certificates, starting with the
+    /// end-entity certificate. this is intended for
+    /// [`Transport`](crate::transport::Transport) implementations. Returns None if there are no
+    /// certificates.
+    pub fn new<I, C>(certificates: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<Vec<u8>>,
+    {
+        let certificates: Vec<Vec<u8>> = certificates.into_iter().map(Into::into).collect();
+        (!certificates.is_empty()).then_some(Self(certificates))
+    }
+
+    /// the der-encoded end-entity certificate presented by the peer
+    pub fn end_entity(&self) -> &[u8] {
+        &self.0[0]
+    }
+
+    /// the der-encoded intermediate certificates presented by the peer, if any
+    pub fn intermediates(&self) -> impl Iterator<Item = &[u8]> {
+        self.0[1..].iter().map(Vec::as_slice)
+    }
+
+    /// the full der-encoded certificate chain presented by the peer, starting with the end-entity
+    /// certificate
+    pub fn iter(&self) -> impl Iterator<Item = &[u8]> {
+        self.0.iter().map(Vec::as_slice)
+    }
+}
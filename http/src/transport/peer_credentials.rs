@@ -0,0 +1,36 @@
+/**
+# The credentials of the process on the other end of a unix domain socket
+
+Returned by [`Transport::peer_credentials`](crate::transport::Transport::peer_credentials)
+for transports that support it. These are determined by the operating system when the
+connection is established, and cannot be forged by the peer.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PeerCredentials {
+    pid: Option<u32>,
+    uid: u32,
+    gid: u32,
+}
+
+impl PeerCredentials {
+    /// construct a new `PeerCredentials`. this is intended for [`Transport`](crate::transport::Transport)
+    /// implementations
+    pub fn new(pid: Option<u32>, uid: u32, gid: u32) -> Self {
+        Self { pid, uid, gid }
+    }
+
+    /// the process id of the peer, if the operating system provides it
+    pub fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// the effective user id of the peer
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    /// the effective group id of the peer
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+}
@@ -0,0 +1,44 @@
+use std::fmt::{self, Display, Formatter};
+
+/**
+# The kind of socket that a [`Transport`](crate::transport::Transport) communicates over
+
+Returned by [`Transport::transport_kind`](crate::transport::Transport::transport_kind).
+Transports that wrap another transport, such as tls, report the kind of the
+underlying transport.
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum TransportKind {
+    /// a tcp socket
+    Tcp,
+
+    /// a unix domain socket
+    Unix,
+
+    /// any other transport, such as an in-memory transport for testing
+    #[default]
+    Other,
+}
+
+impl TransportKind {
+    /// returns true if this is [`TransportKind::Tcp`]
+    pub fn is_tcp(&self) -> bool {
+        matches!(self, Self::Tcp)
+    }
+
+    /// returns true if this is [`TransportKind::Unix`]
+    pub fn is_unix(&self) -> bool {
+        matches!(self, Self::Unix)
+    }
+}
+
+impl Display for TransportKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Tcp => "tcp",
+            Self::Unix => "unix",
+            Self::Other => "other",
+        })
+    }
+}
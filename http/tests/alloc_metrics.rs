@@ -0,0 +1,45 @@
+use futures_lite::future;
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{alloc_metrics, Conn, HttpConfig};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    conn.set_status(200);
+    conn.set_response_body("ok");
+    conn
+}
+
+const REQUEST: &str = "GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+
+#[test(harness)]
+async fn counts_requests_and_reuses_response_buffers() -> TestResult {
+    let before = alloc_metrics();
+    let (client, server) = TestTransport::new();
+
+    // the server and client run on the same task so that response
+    // buffers are returned to the same thread's pool
+    let server = async move {
+        Conn::map_with_config(HttpConfig::default(), server, Stopper::new(), handler)
+            .await
+            .unwrap();
+    };
+
+    let client = async move {
+        for _ in 0..3 {
+            client.write_all(REQUEST);
+            let response = client.read_available_string().await;
+            assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(response.ends_with("\r\n\r\nok"));
+        }
+    };
+
+    future::or(client, server).await;
+
+    let after = alloc_metrics();
+    assert!(after.requests() >= before.requests() + 3);
+    assert!(after.reused_buffers() >= before.reused_buffers() + 2);
+    assert!(after.allocations() > before.allocations());
+    assert!(after.allocations_per_request() > 0.0);
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use indoc::indoc;
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{Conn, Error, KnownHeaderName, Method};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    let buffered =
+        String::from_utf8(conn.buffer_request_body(1024).await.unwrap().to_vec()).unwrap();
+    let content_length = conn
+        .request_headers()
+        .get_str(KnownHeaderName::ContentLength)
+        .unwrap_or_default()
+        .to_string();
+    let body = conn.request_body().await.read_string().await.unwrap();
+    conn.set_status(200);
+    conn.response_headers_mut()
+        .insert(KnownHeaderName::Date, "now");
+    conn.set_response_body(format!("{buffered}|{body}|{content_length}"));
+    conn
+}
+
+#[test(harness)]
+async fn chunked_body_and_pipelined_request() -> TestResult {
+    let (client, server) = TestTransport::new();
+    trillium_testing::spawn(async move {
+        Conn::map(server, Stopper::new(), handler).await.unwrap();
+    });
+
+    client.write_all(indoc! {"
+        POST / HTTP/1.1\r
+        Host: example.com\r
+        Transfer-Encoding: chunked\r
+        \r
+        5\r
+        hello\r
+        6\r
+         world\r
+        0\r
+        \r
+        GET / HTTP/1.1\r
+        Host: example.com\r
+        Connection: close\r
+        \r
+    "});
+
+    let mut response = client.read_available_string().await;
+    if !response.ends_with("||0") {
+        response.push_str(&client.read_available_string().await);
+    }
+    assert!(
+        response.contains("hello world|hello world|11"),
+        "{response}"
+    );
+    assert!(response.ends_with("||0"), "{response}");
+    Ok(())
+}
+
+#[test(harness)]
+async fn already_read_body() {
+    let mut conn = Conn::new_synthetic(Method::Post, "/", "hello");
+    assert_eq!(
+        conn.request_body().await.read_string().await.unwrap(),
+        "hello"
+    );
+    assert!(matches!(
+        conn.buffer_request_body(1024).await,
+        Err(Error::RequestBodyAlreadyRead)
+    ));
+
+    let mut conn = Conn::new_synthetic(Method::Get, "/", ());
+    assert_eq!(conn.request_body().await.read_string().await.unwrap(), "");
+    assert_eq!(conn.buffer_request_body(1024).await.unwrap(), b"");
+}
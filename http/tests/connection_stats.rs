@@ -0,0 +1,49 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{Conn, ConnectionStats, HttpConfig};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    let stats = conn.connection_stats();
+    assert_eq!(stats.is_reused(), stats.requests() > 1);
+    assert_eq!(stats.idle_duration().is_some(), stats.is_reused());
+    assert!(stats.total_idle_duration() <= stats.age());
+    conn.set_status(200);
+    conn.set_response_body(stats.requests().to_string());
+    conn
+}
+
+static CLOSED_CONNECTIONS: AtomicU64 = AtomicU64::new(0);
+static CLOSED_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+fn record_close(stats: &ConnectionStats) {
+    CLOSED_CONNECTIONS.fetch_add(1, Ordering::SeqCst);
+    CLOSED_REQUESTS.fetch_add(stats.requests(), Ordering::SeqCst);
+}
+
+#[test(harness)]
+async fn requests_are_counted_per_connection() -> TestResult {
+    let http_config = HttpConfig::default().with_connection_close_hook(record_close);
+    let (client, server) = TestTransport::new();
+    let server = trillium_testing::spawn(async move {
+        Conn::map_with_config(http_config, server, Stopper::new(), handler)
+            .await
+            .map(|_| ())
+    });
+
+    for expected in ["1", "2"] {
+        client.write_all("GET / HTTP/1.1\r\nHost: example.com\r\n\r\n");
+        let response = client.read_available_string().await;
+        assert_eq!(response.split("\r\n\r\n").nth(1).unwrap(), expected);
+    }
+
+    client.write_all("GET / HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n");
+    let response = client.read_available_string().await;
+    assert_eq!(response.split("\r\n\r\n").nth(1).unwrap(), "3");
+
+    server.await.unwrap()?;
+    assert_eq!(CLOSED_CONNECTIONS.load(Ordering::SeqCst), 1);
+    assert_eq!(CLOSED_REQUESTS.load(Ordering::SeqCst), 3);
+    Ok(())
+}
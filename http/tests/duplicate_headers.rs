@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{
+    Conn, DuplicateHeader, DuplicateHeaderPolicy, Error, HttpConfig, KnownHeaderName,
+};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    let host = conn
+        .request_headers()
+        .get_str(KnownHeaderName::Host)
+        .unwrap_or("-")
+        .to_string();
+    let request_body = conn.request_body().await.read_string().await.unwrap();
+    let body = format!("{host}|{request_body}");
+    conn.set_status(200);
+    conn.set_response_body(body);
+    conn
+}
+
+async fn response_body(http_config: HttpConfig, request: &str) -> Result<String, Error> {
+    let (client, server) = TestTransport::new();
+    let server = trillium_testing::spawn(async move {
+        Conn::map_with_config(http_config, server, Stopper::new(), handler)
+            .await
+            .map(|_| ())
+    });
+
+    client.write_all(request);
+    let response = client.read_available_string().await;
+    server.await.unwrap()?;
+    Ok(response.split("\r\n\r\n").nth(1).unwrap().to_string())
+}
+
+const DUPLICATE_CONTENT_LENGTH: &str = "POST / HTTP/1.1\r\nHost: example.com\r\nContent-Length: 3\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello";
+
+const DUPLICATE_HOST: &str =
+    "GET / HTTP/1.1\r\nHost: first.example\r\nHost: last.example\r\nConnection: close\r\n\r\n";
+
+#[test(harness)]
+async fn rejects_by_default() -> TestResult {
+    assert!(matches!(
+        response_body(HttpConfig::default(), DUPLICATE_CONTENT_LENGTH).await,
+        Err(Error::DuplicateHeader(KnownHeaderName::ContentLength))
+    ));
+
+    assert!(matches!(
+        response_body(HttpConfig::default(), DUPLICATE_HOST).await,
+        Err(Error::DuplicateHeader(KnownHeaderName::Host))
+    ));
+    Ok(())
+}
+
+#[test(harness)]
+async fn first_wins() -> TestResult {
+    let http_config =
+        HttpConfig::default().with_duplicate_header_policy(DuplicateHeaderPolicy::FirstWins);
+    assert_eq!(
+        response_body(http_config, DUPLICATE_CONTENT_LENGTH).await?,
+        "example.com|hel"
+    );
+    assert_eq!(
+        response_body(http_config, DUPLICATE_HOST).await?,
+        "first.example|"
+    );
+    Ok(())
+}
+
+#[test(harness)]
+async fn last_wins() -> TestResult {
+    let http_config =
+        HttpConfig::default().with_duplicate_header_policy(DuplicateHeaderPolicy::LastWins);
+    assert_eq!(
+        response_body(http_config, DUPLICATE_CONTENT_LENGTH).await?,
+        "example.com|hello"
+    );
+    assert_eq!(
+        response_body(http_config, DUPLICATE_HOST).await?,
+        "last.example|"
+    );
+    Ok(())
+}
+
+static DUPLICATES: AtomicUsize = AtomicUsize::new(0);
+
+fn count_duplicates(duplicate: &DuplicateHeader<'_>) {
+    assert_eq!(duplicate.name(), KnownHeaderName::Host);
+    assert_eq!(duplicate.values().len(), 2);
+    assert_eq!(duplicate.policy(), DuplicateHeaderPolicy::LastWins);
+    DUPLICATES.fetch_add(1, Ordering::SeqCst);
+}
+
+#[test(harness)]
+async fn hook_observes_duplicates() -> TestResult {
+    let http_config = HttpConfig::default()
+        .with_duplicate_header_policy(DuplicateHeaderPolicy::LastWins)
+        .with_duplicate_header_hook(count_duplicates);
+
+    response_body(
+        http_config,
+        "GET / HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n",
+    )
+    .await?;
+    assert_eq!(DUPLICATES.load(Ordering::SeqCst), 0);
+
+    response_body(http_config, DUPLICATE_HOST).await?;
+    assert_eq!(DUPLICATES.load(Ordering::SeqCst), 1);
+    Ok(())
+}
@@ -0,0 +1,256 @@
+use async_compat::Compat;
+use bytes::Bytes;
+use h2::{client::SendRequest, RecvStream};
+use http1::{Request, Response};
+use std::future::Future;
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{Conn, Http2Transport, HttpConfig, KnownHeaderName, Version};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<Http2Transport>) -> Conn<Http2Transport> {
+    assert_eq!(conn.http_version(), Version::Http2_0);
+    let body = conn.request_body().await.read_string().await.unwrap();
+    let host = conn.host().unwrap_or_default().to_string();
+    let response_body = format!(
+        "{} {}?{} {host} {body}",
+        conn.method(),
+        conn.path(),
+        conn.querystring()
+    );
+    conn.set_status(200);
+    conn.response_headers_mut()
+        .insert(KnownHeaderName::Connection, "keep-alive");
+    conn.set_response_body(response_body);
+    conn
+}
+
+async fn connect<F, Fut>(stopper: Stopper, handler: F) -> Result<SendRequest<Bytes>, h2::Error>
+where
+    F: Fn(Conn<Http2Transport>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Conn<Http2Transport>> + Send,
+{
+    let (client, server) = TestTransport::new();
+    trillium_testing::spawn(async move {
+        Conn::map_http2(HttpConfig::default(), server, stopper, handler)
+            .await
+            .unwrap();
+    });
+
+    let (send_request, connection) = h2::client::handshake(Compat::new(client)).await?;
+    trillium_testing::spawn(async move { connection.await.unwrap() });
+    Ok(send_request)
+}
+
+async fn read_body(response: Response<RecvStream>) -> Result<String, h2::Error> {
+    let mut body = response.into_body();
+    let mut content = vec![];
+    while let Some(data) = body.data().await {
+        let data = data?;
+        body.flow_control().release_capacity(data.len())?;
+        content.extend_from_slice(&data);
+    }
+    Ok(String::from_utf8(content).unwrap())
+}
+
+#[test(harness)]
+async fn requests() -> TestResult {
+    let mut client = connect(Stopper::new(), handler).await?.ready().await?;
+
+    let request = Request::get("https://example.com/some/path?query").body(())?;
+    let (response, _) = client.send_request(request, true)?;
+    let response = response.await?;
+    assert_eq!(response.status(), 200);
+    assert!(response.headers().get("connection").is_none());
+    assert_eq!(
+        read_body(response).await?,
+        "GET /some/path?query example.com "
+    );
+
+    let request = Request::post("https://example.com/").body(())?;
+    let (response, mut send_stream) = client.send_request(request, false)?;
+    send_stream.send_data(Bytes::from_static(b"unknown "), false)?;
+    send_stream.send_data(Bytes::from_static(b"length"), true)?;
+    assert_eq!(
+        read_body(response.await?).await?,
+        "POST /? example.com unknown length"
+    );
+
+    let request = Request::put("https://example.com/")
+        .header("content-length", "5")
+        .body(())?;
+    let (response, mut send_stream) = client.send_request(request, false)?;
+    send_stream.send_data(Bytes::from_static(b"fixed"), true)?;
+    assert_eq!(
+        read_body(response.await?).await?,
+        "PUT /? example.com fixed"
+    );
+
+    let request = Request::head("https://example.com/").body(())?;
+    let (response, _) = client.send_request(request, true)?;
+    let response = response.await?;
+    assert_eq!(response.headers()["content-length"], "20");
+    assert!(response.into_body().is_end_stream());
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn concurrent_streams() -> TestResult {
+    let mut client = connect(Stopper::new(), handler).await?.ready().await?;
+
+    let mut responses = vec![];
+    for n in 0..10 {
+        let request = Request::get(format!("https://example.com/{n}")).body(())?;
+        responses.push(client.send_request(request, true)?.0);
+    }
+
+    for (n, response) in responses.into_iter().enumerate() {
+        assert_eq!(
+            read_body(response.await?).await?,
+            format!("GET /{n}? example.com ")
+        );
+    }
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn flow_control() -> TestResult {
+    let mut client = connect(Stopper::new(), handler).await?.ready().await?;
+    let request_body = "x".repeat(200_000);
+
+    let request = Request::post("https://example.com/").body(())?;
+    let (response, mut send_stream) = client.send_request(request, false)?;
+    send_stream.reserve_capacity(request_body.len());
+    let mut remaining = Bytes::from(request_body.clone());
+    while !remaining.is_empty() {
+        let capacity = std::future::poll_fn(|cx| send_stream.poll_capacity(cx))
+            .await
+            .unwrap()?;
+        let end_of_stream = capacity >= remaining.len();
+        send_stream.send_data(
+            remaining.split_to(capacity.min(remaining.len())),
+            end_of_stream,
+        )?;
+    }
+
+    assert_eq!(
+        read_body(response.await?).await?,
+        format!("POST /? example.com {request_body}")
+    );
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn graceful_shutdown() -> TestResult {
+    let stopper = Stopper::new();
+    let started = Stopper::new();
+    let mut client = connect(stopper.clone(), {
+        let started = started.clone();
+        move |conn| {
+            started.stop();
+            handler(conn)
+        }
+    })
+    .await?
+    .ready()
+    .await?;
+
+    let request = Request::post("https://example.com/").body(())?;
+    let (response, mut send_stream) = client.send_request(request, false)?;
+    send_stream.send_data(Bytes::from_static(b"in flight"), false)?;
+    started.await;
+    stopper.stop();
+    send_stream.send_data(Bytes::new(), true)?;
+
+    assert_eq!(
+        read_body(response.await?).await?,
+        "POST /? example.com in flight"
+    );
+
+    let request = Request::get("https://example.com/").body(())?;
+    assert!(client.send_request(request, true).is_err() || client.ready().await.is_err());
+    Ok(())
+}
+
+#[test(harness)]
+async fn response_trailers() -> TestResult {
+    let mut client = connect(
+        Stopper::new(),
+        |mut conn: Conn<Http2Transport>| async move {
+            conn.set_status(200);
+            conn.set_response_body("with trailers");
+            conn.set_response_trailer("x-checksum", "abc");
+            conn
+        },
+    )
+    .await?
+    .ready()
+    .await?;
+
+    let request = Request::get("https://example.com/").body(())?;
+    let (response, _) = client.send_request(request, true)?;
+    let response = response.await?;
+    assert!(response.headers().get("trailer").is_some());
+    let mut body = response.into_body();
+    let data = body.data().await.unwrap()?;
+    assert_eq!(&data[..], b"with trailers");
+    while body.data().await.transpose()?.is_some() {}
+    let trailers = body.trailers().await?.unwrap();
+    assert_eq!(trailers["x-checksum"], "abc");
+    Ok(())
+}
+
+#[test(harness)]
+async fn cancel_on_disconnect_without_request_body() -> TestResult {
+    let mut client = connect(
+        Stopper::new(),
+        |mut conn: Conn<Http2Transport>| async move {
+            assert!(!conn.is_disconnected().await);
+            let body = conn
+                .cancel_on_disconnect(async {
+                    futures_lite::future::yield_now().await;
+                    "not cancelled"
+                })
+                .await;
+            conn.set_status(200);
+            conn.set_response_body(body.unwrap_or("cancelled"));
+            conn
+        },
+    )
+    .await?
+    .ready()
+    .await?;
+
+    let request = Request::get("https://example.com/").body(())?;
+    let (response, _) = client.send_request(request, true)?;
+    assert_eq!(read_body(response.await?).await?, "not cancelled");
+    Ok(())
+}
+
+#[test(harness)]
+async fn stream_reset_is_a_disconnect() -> TestResult {
+    let disconnected = Stopper::new();
+    let mut client = connect(Stopper::new(), {
+        let disconnected = disconnected.clone();
+        move |mut conn: Conn<Http2Transport>| {
+            let disconnected = disconnected.clone();
+            async move {
+                conn.on_client_disconnect().await;
+                disconnected.stop();
+                conn
+            }
+        }
+    })
+    .await?
+    .ready()
+    .await?;
+
+    let request = Request::get("https://example.com/").body(())?;
+    let (_, mut send_stream) = client.send_request(request, true)?;
+    send_stream.send_reset(h2::Reason::CANCEL);
+    disconnected.await;
+    Ok(())
+}
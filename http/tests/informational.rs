@@ -0,0 +1,142 @@
+use indoc::{formatdoc, indoc};
+use pretty_assertions::assert_eq;
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{Conn, Headers, HttpConfig, KnownHeaderName, Status, SERVER};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+const TEST_DATE: &str = "Tue, 21 Nov 2023 21:27:21 GMT";
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    conn.response_headers_mut()
+        .insert(KnownHeaderName::Date, TEST_DATE);
+    conn.response_headers_mut()
+        .insert(KnownHeaderName::Connection, "close");
+
+    if conn.expects_100_continue() && conn.request_headers().has_header("x-deny") {
+        conn.set_status(Status::ExpectationFailed);
+        return conn;
+    }
+
+    conn.send_informational(
+        Status::EarlyHints,
+        Headers::from_iter([(KnownHeaderName::Link, "</style.css>; rel=preload")]),
+    )
+    .await
+    .unwrap();
+
+    conn.send_informational(Status::Continue, Headers::new())
+        .await
+        .unwrap();
+
+    let request_body = conn.request_body().await.read_string().await.unwrap();
+    conn.set_status(200);
+    conn.set_response_body(format!("response: {request_body}"));
+    conn
+}
+
+fn serve(http_config: HttpConfig) -> TestTransport {
+    let (client, server) = TestTransport::new();
+    trillium_testing::spawn(async move {
+        Conn::map_with_config(http_config, server, Stopper::new(), handler)
+            .await
+            .unwrap();
+    });
+    client
+}
+
+#[test(harness)]
+async fn early_hints_and_manual_continue() -> TestResult {
+    let client = serve(HttpConfig::default().with_automatic_100_continue(false));
+
+    client.write_all(indoc! {"
+        POST / HTTP/1.1\r
+        Expect: 100-continue\r
+        Host: example.com\r
+        Content-Length: 10\r
+        \r
+    "});
+
+    let expected_interim = indoc! {"
+        HTTP/1.1 103 Early Hints\r
+        Link: </style.css>; rel=preload\r
+        \r
+        HTTP/1.1 100 Continue\r
+        \r
+    "};
+
+    let mut interim = String::new();
+    while interim.len() < expected_interim.len() {
+        interim.push_str(&client.read_available_string().await);
+    }
+    assert_eq!(interim, expected_interim);
+
+    client.write_all(b"0123456789");
+
+    let expected_response = formatdoc! {"
+        HTTP/1.1 200 OK\r
+        Date: {TEST_DATE}\r
+        Server: {SERVER}\r
+        Connection: close\r
+        Content-Length: 20\r
+        \r
+        response: 0123456789\
+    "};
+
+    assert_eq!(client.read_available_string().await, expected_response);
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn deny_continue() -> TestResult {
+    let client = serve(HttpConfig::default().with_automatic_100_continue(false));
+
+    client.write_all(indoc! {"
+        POST / HTTP/1.1\r
+        Expect: 100-continue\r
+        X-Deny: true\r
+        Host: example.com\r
+        Content-Length: 10\r
+        \r
+    "});
+
+    let expected_response = formatdoc! {"
+        HTTP/1.1 417 Expectation Failed\r
+        Date: {TEST_DATE}\r
+        Server: {SERVER}\r
+        Connection: close\r
+        Content-Length: 0\r
+        \r
+    "};
+
+    assert_eq!(client.read_available_string().await, expected_response);
+
+    Ok(())
+}
+
+#[test(harness)]
+async fn no_informational_responses_for_http_1_0() -> TestResult {
+    let client = serve(HttpConfig::default());
+
+    client.write_all(indoc! {"
+        POST / HTTP/1.0\r
+        Host: example.com\r
+        Content-Length: 10\r
+        \r
+        0123456789"});
+
+    let expected_response = formatdoc! {"
+        HTTP/1.0 200 OK\r
+        Date: {TEST_DATE}\r
+        Server: {SERVER}\r
+        Connection: close\r
+        Content-Length: 20\r
+        \r
+        response: 0123456789\
+    "};
+
+    assert_eq!(client.read_available_string().await, expected_response);
+
+    Ok(())
+}
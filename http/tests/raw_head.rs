@@ -0,0 +1,67 @@
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{Conn, HttpConfig};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    let body = match conn.raw_head() {
+        Some(raw_head) => format!(
+            "{}|{}|{}",
+            String::from_utf8_lossy(raw_head.request_line().unwrap_or(b"-")),
+            raw_head
+                .header_lines()
+                .map(String::from_utf8_lossy)
+                .collect::<Vec<_>>()
+                .join(","),
+            raw_head.is_truncated()
+        ),
+        None => String::from("none"),
+    };
+    conn.set_status(200);
+    conn.set_response_body(body);
+    conn
+}
+
+async fn response_body(http_config: HttpConfig, request: &str) -> String {
+    let (client, server) = TestTransport::new();
+    trillium_testing::spawn(async move {
+        Conn::map_with_config(http_config, server, Stopper::new(), handler)
+            .await
+            .unwrap();
+    });
+
+    client.write_all(request);
+    let response = client.read_available_string().await;
+    response.split("\r\n\r\n").nth(1).unwrap().to_string()
+}
+
+const REQUEST: &str = "GET /a%2Fb?c HTTP/1.1\r\nhOST:   example.com  \r\nX-Thing: 1\r\n\r\n";
+
+#[test(harness)]
+async fn disabled_by_default() -> TestResult {
+    assert_eq!(response_body(HttpConfig::default(), REQUEST).await, "none");
+    Ok(())
+}
+
+#[test(harness)]
+async fn captures_head_as_received() -> TestResult {
+    let http_config = HttpConfig::default().with_raw_head_max_len(1024);
+    assert_eq!(
+        response_body(http_config, REQUEST).await,
+        "GET /a%2Fb?c HTTP/1.1|hOST:   example.com  ,X-Thing: 1|false"
+    );
+    Ok(())
+}
+
+#[test(harness)]
+async fn truncates_long_heads() -> TestResult {
+    let http_config = HttpConfig::default().with_raw_head_max_len(50);
+    assert_eq!(
+        response_body(http_config, REQUEST).await,
+        "GET /a%2Fb?c HTTP/1.1|hOST:   example.com  |true"
+    );
+
+    let http_config = HttpConfig::default().with_raw_head_max_len(10);
+    assert_eq!(response_body(http_config, REQUEST).await, "-||true");
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use indoc::indoc;
+use pretty_assertions::assert_eq;
+use stopper::Stopper;
+use test_harness::test;
+use trillium_http::{Conn, KnownHeaderName};
+use trillium_testing::{harness, TestResult, TestTransport};
+
+async fn handler(mut conn: Conn<TestTransport>) -> Conn<TestTransport> {
+    let body = if conn.path() == "/skip-body" {
+        String::new()
+    } else {
+        conn.request_body().await.read_string().await.unwrap()
+    };
+
+    let checksum = conn
+        .request_trailers()
+        .await
+        .unwrap()
+        .and_then(|trailers| trailers.get_str("x-checksum"))
+        .unwrap_or("none")
+        .to_string();
+
+    conn.set_status(200);
+    conn.response_headers_mut()
+        .insert(KnownHeaderName::Date, "now");
+    conn.set_response_body(format!("{body}|{checksum}"));
+    conn.set_response_trailer("x-response-checksum", checksum);
+    conn
+}
+
+#[test(harness)]
+async fn request_and_response_trailers() -> TestResult {
+    let (client, server) = TestTransport::new();
+    trillium_testing::spawn(async move {
+        Conn::map(server, Stopper::new(), handler).await.unwrap();
+    });
+
+    client.write_all(indoc! {"
+        POST / HTTP/1.1\r
+        Host: example.com\r
+        Transfer-Encoding: chunked\r
+        Trailer: x-checksum\r
+        \r
+        5\r
+        hello\r
+        0\r
+        x-checksum: abc\r
+        \r
+        POST /skip-body HTTP/1.1\r
+        Host: example.com\r
+        Transfer-Encoding: chunked\r
+        \r
+        5\r
+        hello\r
+        0\r
+        x-checksum: def\r
+        \r
+        GET / HTTP/1.1\r
+        Host: example.com\r
+        Connection: close\r
+        \r
+    "});
+
+    let expected = indoc! {"
+        HTTP/1.1 200 OK\r
+        Date: now\r
+        Server: trillium/0.3.17\r
+        Trailer: x-response-checksum\r
+        Transfer-Encoding: chunked\r
+        \r
+        9\r
+        hello|abc\r
+        0\r
+        x-response-checksum: abc\r
+        \r
+        HTTP/1.1 200 OK\r
+        Date: now\r
+        Server: trillium/0.3.17\r
+        Trailer: x-response-checksum\r
+        Transfer-Encoding: chunked\r
+        \r
+        4\r
+        |def\r
+        0\r
+        x-response-checksum: def\r
+        \r
+        HTTP/1.1 200 OK\r
+        Date: now\r
+        Server: trillium/0.3.17\r
+        Trailer: x-response-checksum\r
+        Transfer-Encoding: chunked\r
+        \r
+        5\r
+        |none\r
+        0\r
+        x-response-checksum: none\r
+        \r
+    "};
+
+    let mut response = String::new();
+    while response.len() < expected.len() {
+        response.push_str(&client.read_available_string().await);
+    }
+
+    assert_eq!(response, expected);
+    Ok(())
+}
+
+#[test(harness)]
+async fn no_trailers_for_http_1_0() -> TestResult {
+    let (client, server) = TestTransport::new();
+    trillium_testing::spawn(async move {
+        Conn::map(server, Stopper::new(), handler).await.unwrap();
+    });
+
+    client.write_all(indoc! {"
+        GET / HTTP/1.0\r
+        Host: example.com\r
+        \r
+    "});
+
+    assert_eq!(
+        client.read_available_string().await,
+        indoc! {"
+            HTTP/1.0 200 OK\r
+            Date: now\r
+            Server: trillium/0.3.17\r
+            Content-Length: 5\r
+            \r
+            |none"}
+    );
+    Ok(())
+}
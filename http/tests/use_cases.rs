@@ -3,7 +3,7 @@
 use std::{future::Future, marker::PhantomData, sync::Arc};
 use test_harness::test;
 use trillium_client::{Client, Connector, Url};
-use trillium_http::{Conn, KnownHeaderName};
+use trillium_http::{Body, Conn, KnownHeaderName, Method};
 use trillium_testing::{TestResult, TestTransport};
 
 #[test(harness = trillium_testing::harness)]
@@ -17,6 +17,94 @@ async fn send_no_server_header() -> TestResult {
     Ok(())
 }
 
+#[test(harness = trillium_testing::harness)]
+async fn replacing_the_body_recomputes_framing() -> TestResult {
+    let client = Client::new(ServerConnector::new(|mut conn| async move {
+        conn.response_headers_mut()
+            .insert(KnownHeaderName::ContentLength, "5");
+        conn.set_response_body("a longer replacement body");
+        conn
+    }));
+    let mut conn = client.get("http://_").await?;
+    assert_eq!(
+        conn.response_headers()
+            .get_str(KnownHeaderName::ContentLength),
+        Some("25")
+    );
+    assert_eq!(
+        conn.response_body().read_string().await?,
+        "a longer replacement body"
+    );
+    Ok(())
+}
+
+#[test(harness = trillium_testing::harness)]
+async fn streaming_bodies_of_unknown_length_are_chunked() -> TestResult {
+    let client = Client::new(ServerConnector::new(|mut conn| async move {
+        conn.response_headers_mut()
+            .insert(KnownHeaderName::ContentLength, "5");
+        conn.set_response_body(Body::new_streaming(
+            futures_lite::io::Cursor::new("streamed"),
+            None,
+        ));
+        conn
+    }));
+    let mut conn = client.get("http://_").await?;
+    assert!(!conn
+        .response_headers()
+        .has_header(KnownHeaderName::ContentLength));
+    assert_eq!(
+        conn.response_headers()
+            .get_str(KnownHeaderName::TransferEncoding),
+        Some("chunked")
+    );
+    assert_eq!(conn.response_body().read_string().await?, "streamed");
+    Ok(())
+}
+
+#[test(harness = trillium_testing::harness)]
+async fn head_responses_are_framed_like_get() -> TestResult {
+    let client = Client::new(ServerConnector::new(|mut conn| async move {
+        conn.set_response_body("this body is not sent");
+        conn
+    }))
+    .with_default_pool();
+
+    let mut conn = client.build_conn(Method::Head, "http://_").await?;
+    assert_eq!(
+        conn.response_headers()
+            .get_str(KnownHeaderName::ContentLength),
+        Some("21")
+    );
+    assert_eq!(conn.response_body().read_string().await?, "");
+    conn.recycle().await;
+
+    let mut conn = client.get("http://_").await?;
+    assert_eq!(
+        conn.response_body().read_string().await?,
+        "this body is not sent"
+    );
+    Ok(())
+}
+
+#[test(harness = trillium_testing::harness)]
+async fn no_content_responses_have_no_framing_headers() -> TestResult {
+    let client = Client::new(ServerConnector::new(|mut conn| async move {
+        conn.set_status(204);
+        conn.response_headers_mut()
+            .insert(KnownHeaderName::ContentLength, "10");
+        conn
+    }));
+    let conn = client.get("http://_").await?;
+    assert!(!conn
+        .response_headers()
+        .has_header(KnownHeaderName::ContentLength));
+    assert!(!conn
+        .response_headers()
+        .has_header(KnownHeaderName::TransferEncoding));
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct ServerConnector<F, Fut> {
     handler: Arc<F>,
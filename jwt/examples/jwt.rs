@@ -0,0 +1,24 @@
+use serde::Deserialize;
+use trillium::Conn;
+use trillium_jwt::{Jwt, JwtConnExt, Key};
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+async fn hello(conn: Conn) -> Conn {
+    let sub = conn.jwt_claims::<Claims>().unwrap().sub.clone();
+    conn.ok(format!("hello, {sub}"))
+}
+
+fn main() {
+    env_logger::init();
+    // try `curl -H "Authorization: Bearer $TOKEN" http://localhost:8080`
+    // with a token signed with HS256 using the secret "trillium"
+    trillium_smol::run((
+        trillium_logger::logger(),
+        Jwt::new(Key::hs256("trillium")).with_claims::<Claims>(),
+        hello,
+    ));
+}
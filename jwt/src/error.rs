@@ -0,0 +1,23 @@
+use std::error::Error as StdError;
+
+/// Reasons that a request could not be authenticated
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// the request did not include an `Authorization: Bearer` header
+    #[error("no bearer token was provided")]
+    MissingToken,
+
+    /// none of the configured keys match the token's algorithm and key id
+    #[error("no key matches the token's algorithm and key id")]
+    UnknownKey,
+
+    /// the token could not be decoded or validated, or a key could not
+    /// be built
+    #[error(transparent)]
+    Jwt(#[from] jsonwebtoken::errors::Error),
+
+    /// a json web key set could not be fetched or parsed
+    #[error("could not fetch json web key set: {0}")]
+    Jwks(#[source] Box<dyn StdError + Send + Sync + 'static>),
+}
@@ -0,0 +1,86 @@
+use crate::{Error, Keys};
+use async_lock::Mutex;
+use jsonwebtoken::{jwk::JwkSet, Header};
+use std::{
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+use trillium_client::{Client, Url};
+
+/// the minimum time between fetches that are triggered by a token with
+/// an unrecognized key id, to avoid refetching for every bad token
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A json web key set url that is periodically refetched
+#[derive(Debug)]
+pub(crate) struct Jwks {
+    client: Client,
+    url: Url,
+    refresh_interval: Duration,
+    last_fetch: RwLock<Option<Instant>>,
+    fetching: Mutex<()>,
+}
+
+impl Jwks {
+    pub(crate) fn new(client: Client, url: Url) -> Self {
+        Self {
+            client,
+            url,
+            refresh_interval: Duration::from_secs(5 * 60),
+            last_fetch: RwLock::new(None),
+            fetching: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn set_refresh_interval(&mut self, refresh_interval: Duration) {
+        self.refresh_interval = refresh_interval;
+    }
+
+    fn needs_refresh(&self, unknown_key: bool) -> bool {
+        match *self.last_fetch.read().unwrap() {
+            None => true,
+            Some(last_fetch) => {
+                let elapsed = last_fetch.elapsed();
+                elapsed >= self.refresh_interval || (unknown_key && elapsed >= MIN_REFRESH_INTERVAL)
+            }
+        }
+    }
+
+    /// refetches the key set if it is stale, or if no current key
+    /// matches the provided header. only one fetch happens at a time.
+    pub(crate) async fn refresh_if_needed(&self, keys: &Keys, header: &Header) {
+        let unknown_key = !keys.has_match(header);
+        if !self.needs_refresh(unknown_key) {
+            return;
+        }
+
+        let _guard = self.fetching.lock().await;
+        if self.needs_refresh(unknown_key && !keys.has_match(header)) {
+            if let Err(e) = self.refresh(keys).await {
+                log::error!("{e}");
+            }
+        }
+    }
+
+    pub(crate) async fn refresh(&self, keys: &Keys) -> Result<(), Error> {
+        *self.last_fetch.write().unwrap() = Some(Instant::now());
+        log::debug!("fetching json web key set from {}", self.url);
+        let jwks = self.fetch().await?;
+        keys.replace_from_jwks(&jwks);
+        Ok(())
+    }
+
+    async fn fetch(&self) -> Result<JwkSet, Error> {
+        let conn = self
+            .client
+            .get(self.url.clone())
+            .await
+            .map_err(|e| Error::Jwks(Box::new(e)))?;
+
+        conn.success()
+            .map_err(|e| Error::Jwks(Box::new(e)))?
+            .response_json()
+            .await
+            .map_err(|e| Error::Jwks(Box::new(e)))
+    }
+}
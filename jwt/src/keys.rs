@@ -0,0 +1,205 @@
+use crate::Error;
+use jsonwebtoken::{
+    decode,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, PublicKeyUse},
+    Algorithm, DecodingKey, Header, Validation,
+};
+use serde::de::DeserializeOwned;
+use std::{
+    fmt::{self, Debug, Formatter},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+/**
+A single key that can be used to verify tokens signed with a specific
+algorithm, and optionally a specific key id (`kid`).
+*/
+#[derive(Clone)]
+pub struct Key {
+    algorithm: Algorithm,
+    kid: Option<String>,
+    decoding_key: DecodingKey,
+}
+
+impl Debug for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Key")
+            .field("algorithm", &self.algorithm)
+            .field("kid", &self.kid)
+            .finish()
+    }
+}
+
+impl Key {
+    /// builds a key from any [`Algorithm`] and [`DecodingKey`]
+    pub fn new(algorithm: Algorithm, decoding_key: DecodingKey) -> Self {
+        Self {
+            algorithm,
+            kid: None,
+            decoding_key,
+        }
+    }
+
+    /// builds an HS256 key from a shared secret
+    pub fn hs256(secret: impl AsRef<[u8]>) -> Self {
+        Self::new(Algorithm::HS256, DecodingKey::from_secret(secret.as_ref()))
+    }
+
+    /// builds an RS256 key from a pem-encoded rsa public key
+    pub fn rs256_pem(pem: impl AsRef<[u8]>) -> Result<Self, Error> {
+        Ok(Self::new(
+            Algorithm::RS256,
+            DecodingKey::from_rsa_pem(pem.as_ref())?,
+        ))
+    }
+
+    /// builds an ES256 key from a pem-encoded ecdsa public key
+    pub fn es256_pem(pem: impl AsRef<[u8]>) -> Result<Self, Error> {
+        Ok(Self::new(
+            Algorithm::ES256,
+            DecodingKey::from_ec_pem(pem.as_ref())?,
+        ))
+    }
+
+    /**
+    builds a key from a json web key. if the jwk does not specify an
+    algorithm, it is inferred from the key type.
+    */
+    pub fn from_jwk(jwk: &Jwk) -> Result<Self, Error> {
+        let algorithm = match (&jwk.common.key_algorithm, &jwk.algorithm) {
+            (Some(key_algorithm), _) => Algorithm::from_str(&key_algorithm.to_string())?,
+            (None, AlgorithmParameters::RSA(_)) => Algorithm::RS256,
+            (None, AlgorithmParameters::EllipticCurve(params)) => match params.curve {
+                EllipticCurve::P384 => Algorithm::ES384,
+                _ => Algorithm::ES256,
+            },
+            (None, AlgorithmParameters::OctetKey(_)) => Algorithm::HS256,
+            (None, AlgorithmParameters::OctetKeyPair(_)) => Algorithm::EdDSA,
+        };
+
+        Ok(Self {
+            algorithm,
+            kid: jwk.common.key_id.clone(),
+            decoding_key: DecodingKey::from_jwk(jwk)?,
+        })
+    }
+
+    /// sets the key id (`kid`) for this key. tokens that specify a
+    /// different key id will not be verified with this key.
+    pub fn with_kid(mut self, kid: impl Into<String>) -> Self {
+        self.kid = Some(kid.into());
+        self
+    }
+
+    /// the algorithm for this key
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// the key id for this key, if one was provided
+    pub fn kid(&self) -> Option<&str> {
+        self.kid.as_deref()
+    }
+
+    fn matches(&self, header: &Header) -> bool {
+        self.algorithm == header.alg
+            && match (&header.kid, &self.kid) {
+                (Some(token_kid), Some(kid)) => token_kid == kid,
+                _ => true,
+            }
+    }
+}
+
+/**
+A reloadable set of [`Key`]s
+
+Cloning a Keys returns a handle to the same set of keys, so a clone
+can be retained after building a [`Jwt`](crate::Jwt) handler in order
+to rotate keys while the server is running.
+
+```
+use trillium_jwt::{Jwt, Key, Keys};
+let keys = Keys::from(Key::hs256("first secret"));
+let handler = Jwt::new(keys.clone());
+// later
+keys.replace([Key::hs256("second secret")]);
+assert_eq!(handler.keys().len(), 1);
+```
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Keys(Arc<RwLock<Vec<Key>>>);
+
+impl Keys {
+    /// builds a new Keys from any iterator of [`Key`]
+    pub fn new(keys: impl IntoIterator<Item = Key>) -> Self {
+        Self(Arc::new(RwLock::new(keys.into_iter().collect())))
+    }
+
+    /// replaces all keys in this set, for all clones of this Keys
+    pub fn replace(&self, keys: impl IntoIterator<Item = Key>) {
+        *self.0.write().unwrap() = keys.into_iter().collect();
+    }
+
+    /// adds a key to this set, for all clones of this Keys
+    pub fn push(&self, key: Key) {
+        self.0.write().unwrap().push(key);
+    }
+
+    /// the number of keys in this set
+    pub fn len(&self) -> usize {
+        self.0.read().unwrap().len()
+    }
+
+    /// returns true if there are no keys in this set
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub(crate) fn has_match(&self, header: &Header) -> bool {
+        self.0.read().unwrap().iter().any(|key| key.matches(header))
+    }
+
+    pub(crate) fn replace_from_jwks(&self, jwks: &JwkSet) {
+        self.replace(jwks.keys.iter().filter_map(|jwk| {
+            if matches!(jwk.common.public_key_use, Some(PublicKeyUse::Encryption)) {
+                return None;
+            }
+
+            Key::from_jwk(jwk)
+                .map_err(|e| log::warn!("skipping jwk {:?}: {e}", jwk.common.key_id))
+                .ok()
+        }));
+    }
+
+    pub(crate) fn decode<C: DeserializeOwned>(
+        &self,
+        token: &str,
+        header: &Header,
+        validation: &Validation,
+    ) -> Result<C, Error> {
+        let keys = self.0.read().unwrap();
+        let mut result = Err(Error::UnknownKey);
+        for key in keys.iter().filter(|key| key.matches(header)) {
+            let mut validation = validation.clone();
+            validation.algorithms = vec![key.algorithm];
+            match decode(token, &key.decoding_key, &validation) {
+                Ok(token_data) => return Ok(token_data.claims),
+                Err(e) => result = Err(e.into()),
+            }
+        }
+        result
+    }
+}
+
+impl From<Key> for Keys {
+    fn from(key: Key) -> Self {
+        Self::new([key])
+    }
+}
+
+impl From<Vec<Key>> for Keys {
+    fn from(keys: Vec<Key>) -> Self {
+        Self::new(keys)
+    }
+}
@@ -0,0 +1,328 @@
+/*!
+# Json web token authentication for trillium.rs
+
+[`Jwt`] validates `Authorization: Bearer` tokens signed with any
+algorithm supported by [`jsonwebtoken`], including HS256, RS256, and
+ES256. The `exp` claim is required, and `nbf`, `aud`, and `iss` are
+checked when present or configured. The deserialized claims are then
+available to subsequent handlers through [`JwtConnExt::jwt_claims`].
+
+```
+use serde::Deserialize;
+use trillium::Conn;
+use trillium_jwt::{Jwt, JwtConnExt, Key};
+
+#[derive(Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+let handler = (
+    Jwt::new(Key::hs256("secret"))
+        .with_claims::<Claims>()
+        .with_issuer(&["https://issuer.example"]),
+    |conn: Conn| async move {
+        let sub = conn.jwt_claims::<Claims>().unwrap().sub.clone();
+        conn.ok(format!("hello, {sub}"))
+    },
+);
+
+use trillium_testing::prelude::*;
+let conn = get("/").on(&handler);
+assert_status!(&conn, 401);
+assert_headers!(&conn, "www-authenticate" => "Bearer");
+```
+
+## Key rotation
+
+Keys are held in a [`Keys`] set that can be replaced while the server
+is running, either by retaining a clone of the [`Keys`] or by
+configuring the handler to fetch a json web key set (JWKS) from a url
+with [`Jwt::from_jwks`]. A json web key set is fetched when the
+handler is initialized, refetched periodically as requests arrive,
+and refetched early when a token names an unrecognized key id.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod error;
+pub use error::Error;
+
+mod keys;
+pub use keys::{Key, Keys};
+
+mod jwks;
+use jwks::Jwks;
+
+pub use jsonwebtoken;
+
+use jsonwebtoken::{decode_header, Validation};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    time::Duration,
+};
+use trillium::{
+    async_trait, Conn, Handler, Info,
+    KnownHeaderName::{Authorization, WwwAuthenticate},
+    StateSet, Status,
+};
+use trillium_client::{Client, Url};
+
+/**
+Trillium handler that authenticates requests with json web tokens
+
+See crate-level docs for an explanation
+*/
+pub struct Jwt<Claims = Value> {
+    keys: Keys,
+    jwks: Option<Jwks>,
+    validation: Validation,
+    response: Box<dyn Handler>,
+    claims: PhantomData<fn() -> Claims>,
+}
+
+impl<Claims> Debug for Jwt<Claims> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jwt")
+            .field("keys", &self.keys)
+            .field("jwks", &self.jwks)
+            .field("validation", &self.validation)
+            .field("response", &self.response)
+            .field("claims", &std::any::type_name::<Claims>())
+            .finish()
+    }
+}
+
+impl Jwt {
+    /**
+    Constructs a new Jwt handler that verifies tokens with the
+    provided [`Key`] or [`Keys`]. Claims are deserialized as a
+    [`serde_json::Value`] until [`Jwt::with_claims`] is called.
+    */
+    pub fn new(keys: impl Into<Keys>) -> Self {
+        let mut validation = Validation::default();
+        validation.validate_nbf = true;
+        Self {
+            keys: keys.into(),
+            jwks: None,
+            validation,
+            response: Box::new(Status::Unauthorized),
+            claims: PhantomData,
+        }
+    }
+
+    /**
+    Constructs a new Jwt handler that verifies tokens with keys from
+    the json web key set at the provided url, using the provided
+    client to fetch it. The key set is refetched every five minutes
+    by default, which can be configured with
+    [`Jwt::with_jwks_refresh_interval`].
+
+    ```
+    use trillium_client::Client;
+    use trillium_jwt::Jwt;
+    let handler = Jwt::from_jwks(
+        Client::new(trillium_smol::ClientConfig::default()),
+        "https://auth.example/.well-known/jwks.json".parse().unwrap(),
+    );
+    ```
+    */
+    pub fn from_jwks(client: Client, url: Url) -> Self {
+        let mut jwt = Self::new(Keys::default());
+        jwt.jwks = Some(Jwks::new(client, url));
+        jwt
+    }
+}
+
+/// Alias for [`Jwt::new`]
+pub fn jwt(keys: impl Into<Keys>) -> Jwt {
+    Jwt::new(keys)
+}
+
+impl<Claims> Jwt<Claims>
+where
+    Claims: DeserializeOwned + Send + Sync + 'static,
+{
+    /// Deserialize claims as the provided type instead of a
+    /// [`serde_json::Value`]
+    pub fn with_claims<T>(self) -> Jwt<T> {
+        Jwt {
+            keys: self.keys,
+            jwks: self.jwks,
+            validation: self.validation,
+            response: self.response,
+            claims: PhantomData,
+        }
+    }
+
+    /// Require the `aud` claim to be present and to contain one of
+    /// the provided audiences
+    pub fn with_audience(mut self, audience: &[impl ToString]) -> Self {
+        self.validation.set_audience(audience);
+        self.validation.required_spec_claims.insert("aud".into());
+        self
+    }
+
+    /// Require the `iss` claim to be present and to be one of the
+    /// provided issuers
+    pub fn with_issuer(mut self, issuer: &[impl ToString]) -> Self {
+        self.validation.set_issuer(issuer);
+        self.validation.required_spec_claims.insert("iss".into());
+        self
+    }
+
+    /// Allow this much clock skew when checking the `exp` and `nbf`
+    /// claims, rounded down to whole seconds. The default is one
+    /// minute.
+    pub fn with_leeway(mut self, leeway: Duration) -> Self {
+        self.validation.leeway = leeway.as_secs();
+        self
+    }
+
+    /// Replace the [`Validation`] used for all tokens. The
+    /// `algorithms` field will be ignored, as each token is validated
+    /// with the algorithm of the key that matches it.
+    pub fn with_validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Sets the interval at which the json web key set configured
+    /// with [`Jwt::from_jwks`] is refetched. This has no effect
+    /// for handlers built with [`Jwt::new`].
+    pub fn with_jwks_refresh_interval(mut self, refresh_interval: Duration) -> Self {
+        match &mut self.jwks {
+            Some(jwks) => jwks.set_refresh_interval(refresh_interval),
+            None => log::warn!("with_jwks_refresh_interval has no effect without from_jwks"),
+        }
+        self
+    }
+
+    /**
+    Replaces the default `401 Unauthorized` response with a custom
+    handler. The `WWW-Authenticate` header will already be set when
+    this handler is run, the reason for rejection is available with
+    [`JwtConnExt::jwt_error`], and the conn will be halted after it
+    is run.
+
+    ```
+    use trillium::{Conn, Status};
+    use trillium_jwt::{Jwt, JwtConnExt, Key};
+    let handler = Jwt::new(Key::hs256("secret")).with_response(|conn: Conn| async move {
+        let message = conn.jwt_error().unwrap().to_string();
+        conn.with_status(Status::Unauthorized).with_body(message)
+    });
+
+    use trillium_testing::prelude::*;
+    assert_response!(get("/").on(&handler), 401, "no bearer token was provided");
+    ```
+    */
+    pub fn with_response(mut self, response: impl Handler) -> Self {
+        self.response = Box::new(response);
+        self
+    }
+
+    /// Borrow the [`Keys`] used by this handler. Cloning these keys
+    /// returns a handle that can be used to replace them.
+    pub fn keys(&self) -> &Keys {
+        &self.keys
+    }
+
+    /// Validates a token with the keys and validation rules of this
+    /// handler, returning its claims. This is useful for tokens that are
+    /// not sent in an `Authorization` header.
+    pub async fn validate(&self, token: &str) -> Result<Claims, Error> {
+        let header = decode_header(token)?;
+        if let Some(jwks) = &self.jwks {
+            jwks.refresh_if_needed(&self.keys, &header).await;
+        }
+        self.keys.decode(token, &header, &self.validation)
+    }
+
+    async fn authenticate(&self, conn: &Conn) -> Result<Claims, Error> {
+        self.validate(bearer_token(conn).ok_or(Error::MissingToken)?)
+            .await
+    }
+}
+
+fn bearer_token(conn: &Conn) -> Option<&str> {
+    let (scheme, token) = conn
+        .request_headers()
+        .get_str(Authorization)?
+        .split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty()).then_some(token)
+}
+
+struct JwtClaims<Claims>(Claims);
+
+#[async_trait]
+impl<Claims> Handler for Jwt<Claims>
+where
+    Claims: DeserializeOwned + Send + Sync + 'static,
+{
+    async fn run(&self, conn: Conn) -> Conn {
+        let error = match self.authenticate(&conn).await {
+            Ok(claims) => return conn.with_state(JwtClaims(claims)),
+            Err(error) => error,
+        };
+
+        log::debug!("rejecting {} {}: {error}", conn.method(), conn.path());
+        let www_authenticate = match error {
+            Error::MissingToken => "Bearer",
+            _ => r#"Bearer error="invalid_token""#,
+        };
+
+        let conn = conn
+            .with_response_header(WwwAuthenticate, www_authenticate)
+            .with_state(error);
+        self.response.run(conn).await.halt()
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        if let Some(jwks) = &self.jwks {
+            if let Err(e) = jwks.refresh(&self.keys).await {
+                log::error!("{e}");
+            }
+        }
+
+        self.response.init(info).await;
+    }
+}
+
+/// Extension trait to retrieve the results of a [`Jwt`] handler
+pub trait JwtConnExt {
+    /// Returns the claims of a valid token, if the [`Jwt`] handler
+    /// deserialized them as `Claims`
+    fn jwt_claims<Claims: Send + Sync + 'static>(&self) -> Option<&Claims>;
+
+    /// Returns the reason that a [`Jwt`] handler rejected this conn,
+    /// if it did
+    fn jwt_error(&self) -> Option<&Error>;
+}
+
+impl<ConnLike> JwtConnExt for ConnLike
+where
+    ConnLike: AsRef<StateSet>,
+{
+    fn jwt_claims<Claims: Send + Sync + 'static>(&self) -> Option<&Claims> {
+        self.as_ref()
+            .get::<JwtClaims<Claims>>()
+            .map(|claims| &claims.0)
+    }
+
+    fn jwt_error(&self) -> Option<&Error> {
+        self.as_ref().get()
+    }
+}
@@ -0,0 +1,306 @@
+use crate::Targetable;
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Mutex,
+    },
+    thread,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/**
+When a [`FileTarget`] starts a new file
+
+Rotated files are renamed with a numeric suffix, so that `access.log`
+becomes `access.log.1`, the previous `access.log.1` becomes
+`access.log.2`, and so on, up to the number of files configured with
+[`FileTargetBuilder::with_max_files`].
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Rotation {
+    /// never rotate
+    #[default]
+    Never,
+
+    /// rotate before a line would increase the file beyond this many
+    /// bytes. a single line longer than this is still written to a new
+    /// file
+    Size(u64),
+
+    /// rotate at the first line written after each utc hour begins
+    Hourly,
+
+    /// rotate at the first line written after each utc day begins
+    Daily,
+}
+
+impl Rotation {
+    // the utc period that this time falls into, for time-based rotation
+    fn period(self, time: SystemTime) -> Option<u64> {
+        let seconds = match self {
+            Rotation::Hourly => 60 * 60,
+            Rotation::Daily => 24 * 60 * 60,
+            Rotation::Never | Rotation::Size(_) => return None,
+        };
+
+        let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+        Some(since_epoch.as_secs() / seconds)
+    }
+}
+
+/**
+Builder for a [`FileTarget`]
+
+```no_run
+use trillium_logger::{FileTarget, Logger, Rotation};
+
+let target = FileTarget::builder("access.log")
+    .with_rotation(Rotation::Size(10 * 1024 * 1024))
+    .with_max_files(7)
+    .open()
+    .unwrap();
+
+Logger::new().with_target(target);
+```
+*/
+#[derive(Clone, Debug)]
+pub struct FileTargetBuilder {
+    path: PathBuf,
+    rotation: Rotation,
+    max_files: usize,
+    flush_interval: Duration,
+}
+
+impl FileTargetBuilder {
+    /// specify when to start a new file. defaults to [`Rotation::Never`]
+    pub fn with_rotation(mut self, rotation: Rotation) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    /// specify how many rotated files to keep in addition to the
+    /// current file. the oldest files are deleted. defaults to 5
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// specify how long written lines may be buffered before they are
+    /// flushed to disk. defaults to one second
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /**
+    open the file, creating it if it does not exist and appending to
+    it if it does, and start the thread that writes to it
+
+    # Errors
+
+    returns an error if the file cannot be opened or the writer
+    thread cannot be spawned
+    */
+    pub fn open(self) -> io::Result<FileTarget> {
+        let writer = FileWriter::open(self.path.clone(), self.rotation, self.max_files)?;
+        let (sender, receiver) = mpsc::channel();
+        let flush_interval = self.flush_interval;
+        thread::Builder::new()
+            .name(String::from("trillium-logger-file"))
+            .spawn(move || writer.run(&receiver, flush_interval))?;
+
+        Ok(FileTarget {
+            path: self.path,
+            sender: Mutex::new(sender),
+        })
+    }
+}
+
+/**
+A [`Targetable`] that writes each line to a file, with optional
+[`Rotation`]
+
+Lines are sent to a dedicated thread that buffers and writes them, so
+writing a log line never blocks the async runtime on disk io. Buffered
+lines are flushed at the flush interval, when [`FileTarget::flush`] is
+called, and when the target is dropped.
+*/
+pub struct FileTarget {
+    path: PathBuf,
+    sender: Mutex<Sender<Message>>,
+}
+
+impl Debug for FileTarget {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileTarget")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+enum Message {
+    Line(String),
+    Flush(Sender<()>),
+}
+
+impl FileTarget {
+    /// build a file target that writes to this path. see
+    /// [`FileTargetBuilder`] for options
+    pub fn builder(path: impl AsRef<Path>) -> FileTargetBuilder {
+        FileTargetBuilder {
+            path: path.as_ref().to_path_buf(),
+            rotation: Rotation::Never,
+            max_files: 5,
+            flush_interval: Duration::from_secs(1),
+        }
+    }
+
+    /**
+    open a file target that writes to this path without rotation
+
+    # Errors
+
+    returns an error if the file cannot be opened
+    */
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::builder(path).open()
+    }
+
+    /// the path of the current log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// block until every line written before this call has been
+    /// flushed to disk. this is not necessary in normal use, but can be
+    /// useful before shutdown or in tests
+    pub fn flush(&self) {
+        let (sender, receiver) = mpsc::channel();
+        if self.send(Message::Flush(sender)) {
+            let _ = receiver.recv();
+        }
+    }
+
+    fn send(&self, message: Message) -> bool {
+        self.sender.lock().unwrap().send(message).is_ok()
+    }
+}
+
+impl Targetable for FileTarget {
+    fn write(&self, data: String) {
+        if !self.send(Message::Line(data)) {
+            log::error!("trillium-logger file target for {:?} is closed", self.path);
+        }
+    }
+}
+
+struct FileWriter {
+    path: PathBuf,
+    rotation: Rotation,
+    max_files: usize,
+    file: BufWriter<File>,
+    len: u64,
+    period: Option<u64>,
+}
+
+impl FileWriter {
+    fn open(path: PathBuf, rotation: Rotation, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        let modified = file
+            .metadata()?
+            .modified()
+            .unwrap_or_else(|_| SystemTime::now());
+        Ok(Self {
+            path,
+            rotation,
+            max_files,
+            file: BufWriter::new(file),
+            len,
+            period: rotation.period(modified),
+        })
+    }
+
+    fn run(mut self, receiver: &Receiver<Message>, flush_interval: Duration) {
+        loop {
+            match receiver.recv_timeout(flush_interval) {
+                Ok(Message::Line(line)) => {
+                    if let Err(e) = self.write_line(&line) {
+                        log::error!("unable to write to {:?}: {e}", self.path);
+                    }
+                }
+
+                Ok(Message::Flush(done)) => {
+                    self.flush();
+                    let _ = done.send(());
+                }
+
+                Err(RecvTimeoutError::Timeout) => self.flush(),
+
+                Err(RecvTimeoutError::Disconnected) => {
+                    self.flush();
+                    break;
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.file.flush() {
+            log::error!("unable to flush {:?}: {e}", self.path);
+        }
+    }
+
+    fn should_rotate(&self, line_len: u64) -> bool {
+        match self.rotation {
+            Rotation::Never => false,
+            Rotation::Size(max_len) => self.len > 0 && self.len + line_len > max_len,
+            Rotation::Hourly | Rotation::Daily => {
+                self.period != self.rotation.period(SystemTime::now())
+            }
+        }
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let line_len = line.len() as u64 + 1;
+        if self.should_rotate(line_len) {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.len += line_len;
+        Ok(())
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{n}"));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated_path(self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        *self = Self::open(self.path.clone(), self.rotation, self.max_files)?;
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
-use crate::LogFormatter;
+use crate::{LogFields, LogFormatter, LoggerConnExt};
 use colored::{ColoredString, Colorize};
 use size::{Base, Size};
 use std::{borrow::Cow, fmt::Display, sync::Arc, time::Instant};
 use trillium::{Conn, HeaderName, KnownHeaderName, Method, Status, Version};
 
+mod json;
+pub use json::{json_formatter, JsonFormatter, JsonOutput};
+
 /**
 [apache combined log format][apache]
 
@@ -303,6 +306,42 @@ pub fn bytes(conn: &Conn, _color: bool) -> u64 {
     conn.response_len().unwrap_or_default()
 }
 
+/**
+formatter for every field added to the conn with
+[`LoggerConnExt::log_field`], displayed as space-separated
+`key=value` pairs. see [`LogFields`] for details
+
+```
+# use trillium_logger::{Logger, dev_formatter, formatters::log_fields};
+Logger::new().with_formatter((dev_formatter, " ", log_fields));
+```
+*/
+pub fn log_fields(conn: &Conn, _color: bool) -> LogFields {
+    conn.log_fields().cloned().unwrap_or_default()
+}
+
+/**
+formatter-builder for a single field added to the conn with
+[`LoggerConnExt::log_field`]. `-` if the field is not present
+
+```
+# use trillium_logger::{Logger, apache_common, formatters::log_field};
+Logger::new().with_formatter(apache_common("-", log_field("user_id")));
+```
+
+**note**: this is not a formatter itself, but returns a formatter when
+called with a field name
+*/
+pub fn log_field(name: impl Into<Cow<'static, str>>) -> impl LogFormatter {
+    let name = name.into();
+    move |conn: &Conn, _color: bool| {
+        conn.log_fields()
+            .and_then(|fields| fields.get(&name))
+            .unwrap_or("-")
+            .to_string()
+    }
+}
+
 /**
 formatter that prints an emoji if the request is secure as determined
 by [`Conn::is_secure`]
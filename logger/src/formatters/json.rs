@@ -0,0 +1,182 @@
+use crate::{LogFields, LogFormatter, LoggerConnExt};
+use serde_json::{Map, Value};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Display, Formatter},
+    net::IpAddr,
+    time::Instant,
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+use trillium::{Conn, Method, Status};
+
+type DynFormatter = Box<dyn Fn(&Conn, bool) -> Box<dyn Display + Send + Sync> + Send + Sync>;
+
+/**
+structured formatter that writes one json object per request
+
+Rather than concatenating the display output of other formatters,
+this formatter collects named fields into a single json object,
+which is suitable for ingestion by log pipelines. Each line includes
+the following fields:
+
+| key           | value                                                   |
+|---------------|---------------------------------------------------------|
+| `timestamp`   | the time the log line was written, in rfc 3339 format   |
+| `method`      | the http method                                         |
+| `path`        | the request path, without the query                     |
+| `query`       | the querystring, only included if it is not empty       |
+| `status`      | the numeric http status                                 |
+| `duration_ms` | the time from the first bytes read to the response sent |
+| `bytes`       | the response body length, or `null` if unknown          |
+| `ip`          | the peer ip address, or `null` if unknown               |
+
+Fields added by handlers with
+[`LoggerConnExt::log_field`](crate::LoggerConnExt::log_field) are
+included as json strings. Additional fields can also be added from
+any other [`LogFormatter`] with [`JsonFormatter::with_field`]. Their
+display output is included as a json string. A field with the same
+name as a built-in field replaces it, and fields added with
+[`JsonFormatter::with_field`] take precedence over fields added by
+handlers.
+
+```
+use trillium_logger::{json_formatter, Logger};
+# use trillium::Conn;
+# struct User(String);
+fn user(conn: &Conn, _color: bool) -> String {
+    conn.state::<User>()
+        .map_or_else(|| String::from("guest"), |user| user.0.clone())
+}
+
+Logger::new().with_formatter(
+    json_formatter()
+        .with_field("user", user)
+        .with_field("user_agent", |conn: &Conn, _color: bool| {
+            conn.request_headers()
+                .get_str(trillium::KnownHeaderName::UserAgent)
+                .unwrap_or_default()
+                .to_string()
+        }),
+);
+```
+
+To include the conn id from the `trillium-conn-id` crate, add its
+formatter with `.with_field("conn_id", trillium_conn_id::log_formatter::conn_id)`.
+*/
+#[derive(Default)]
+pub struct JsonFormatter {
+    fields: Vec<(Cow<'static, str>, DynFormatter)>,
+}
+
+impl Debug for JsonFormatter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonFormatter")
+            .field(
+                "fields",
+                &self.fields.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl JsonFormatter {
+    /// Builds a new json formatter with only the built-in fields
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a field to every log line with the display output of
+    /// this formatter as a json string
+    pub fn with_field(
+        mut self,
+        name: impl Into<Cow<'static, str>>,
+        formatter: impl LogFormatter,
+    ) -> Self {
+        self.fields.push((
+            name.into(),
+            Box::new(move |conn, color| Box::new(formatter.format(conn, color))),
+        ));
+        self
+    }
+}
+
+/// Convenience alias for [`JsonFormatter::new`]
+pub fn json_formatter() -> JsonFormatter {
+    JsonFormatter::new()
+}
+
+/**
+display output for [`JsonFormatter`]
+*/
+pub struct JsonOutput {
+    method: Method,
+    path: String,
+    query: String,
+    status: Status,
+    bytes: Option<u64>,
+    ip: Option<IpAddr>,
+    start_time: Instant,
+    log_fields: Option<LogFields>,
+    fields: Vec<(Cow<'static, str>, Box<dyn Display + Send + Sync>)>,
+}
+
+impl LogFormatter for JsonFormatter {
+    type Output = JsonOutput;
+
+    fn format(&self, conn: &Conn, color: bool) -> Self::Output {
+        JsonOutput {
+            method: conn.method(),
+            path: conn.path().to_string(),
+            query: conn.querystring().to_string(),
+            status: conn.status().unwrap_or(Status::NotFound),
+            bytes: conn.response_len(),
+            ip: conn.peer_ip(),
+            start_time: conn.inner().start_time(),
+            log_fields: conn.log_fields().cloned(),
+            fields: self
+                .fields
+                .iter()
+                .map(|(name, formatter)| (name.clone(), formatter(conn, color)))
+                .collect(),
+        }
+    }
+}
+
+impl Display for JsonOutput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // like the response_time formatter, duration is calculated
+        // when the line is written, after the response has been sent
+        let duration = self.start_time.elapsed();
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .map_err(|_| fmt::Error)?;
+
+        let mut map = Map::new();
+        map.insert("timestamp".into(), timestamp.into());
+        map.insert("method".into(), self.method.as_ref().into());
+        map.insert("path".into(), self.path.as_str().into());
+        if !self.query.is_empty() {
+            map.insert("query".into(), self.query.as_str().into());
+        }
+        map.insert("status".into(), (self.status as u16).into());
+        map.insert(
+            "duration_ms".into(),
+            (duration.as_secs_f64() * 1000.0).into(),
+        );
+        map.insert("bytes".into(), self.bytes.into());
+        map.insert(
+            "ip".into(),
+            self.ip.map_or(Value::Null, |ip| ip.to_string().into()),
+        );
+
+        for (name, value) in self.log_fields.iter().flat_map(LogFields::iter) {
+            map.insert(name.to_string(), value.into());
+        }
+
+        for (name, value) in &self.fields {
+            map.insert(name.to_string(), value.to_string().into());
+        }
+
+        f.write_str(&Value::Object(map).to_string())
+    }
+}
@@ -9,14 +9,23 @@
 /*!
 Welcome to the trillium logger!
 */
-pub use crate::formatters::{apache_combined, apache_common, dev_formatter};
-use std::{fmt::Display, io::IsTerminal, sync::Arc};
+pub use crate::formatters::{apache_combined, apache_common, dev_formatter, json_formatter};
+use std::{fmt::Display, io::IsTerminal, sync::Arc, time::Duration};
+use summary::Summary;
 use trillium::{async_trait, Conn, Handler, Info};
 /**
 Components with which common log formats can be constructed
 */
 pub mod formatters;
 
+mod summary;
+
+mod log_fields;
+pub use log_fields::{LogFields, LoggerConnExt};
+
+mod file_target;
+pub use file_target::{FileTarget, FileTargetBuilder, Rotation};
+
 /**
 A configuration option that determines if format will be colorful.
 
@@ -77,8 +86,8 @@ pub enum Target {
     Stdout,
 }
 
-/// A trait for log targets. Implemented for [`Target`] and for all
-/// `Fn(String) + Send + Sync + 'static`.
+/// A trait for log targets. Implemented for [`Target`], [`FileTarget`],
+/// and for all `Fn(String) + Send + Sync + 'static`.
 pub trait Targetable: Send + Sync + 'static {
     /// write a log line
     fn write(&self, data: String);
@@ -192,8 +201,15 @@ pub struct Logger<F> {
     format: F,
     color_mode: ColorMode,
     target: Arc<dyn Targetable>,
+    slow_threshold: Option<Duration>,
+    slow_target: Arc<dyn Targetable>,
+    summary: Option<Arc<Summary>>,
+    filter: Option<Arc<Filter>>,
+    sample_rate: Option<f64>,
 }
 
+type Filter = dyn Fn(&Conn) -> bool + Send + Sync + 'static;
+
 impl Logger<()> {
     /**
     Builds a new logger
@@ -203,12 +219,22 @@ impl Logger<()> {
     * formatter: [`dev_formatter`]
     * color mode: [`ColorMode::Auto`]
     * target: [`Target::Stdout`]
+    * slow request threshold: none
+    * slow request target: [`Target::Logger`]`(`[`log::Level::Warn`]`)`
+    * summary interval: none
+    * filter: none
+    * sample rate: none (every request is logged)
     */
     pub fn new() -> Logger<impl LogFormatter> {
         Logger {
             format: dev_formatter,
             color_mode: ColorMode::Auto,
             target: Arc::new(Target::Stdout),
+            slow_threshold: None,
+            slow_target: Arc::new(Target::Logger(log::Level::Warn)),
+            summary: None,
+            filter: None,
+            sample_rate: None,
         }
     }
 }
@@ -233,6 +259,11 @@ impl<T> Logger<T> {
             format: formatter,
             color_mode: self.color_mode,
             target: self.target,
+            slow_threshold: self.slow_threshold,
+            slow_target: self.slow_target,
+            summary: self.summary,
+            filter: self.filter,
+            sample_rate: self.sample_rate,
         }
     }
 }
@@ -268,6 +299,113 @@ impl<F: LogFormatter> Logger<F> {
         self.target = Arc::new(target);
         self
     }
+
+    /**
+    flag requests that take longer than the provided duration, from
+    the first bytes read to the completion of the response. in
+    addition to the usual log line, the formatted output for each
+    slow request is written to the slow request target, which defaults
+    to [`Target::Logger`]`(`[`log::Level::Warn`]`)` and can be changed
+    with [`Logger::with_slow_request_target`].
+
+    ```
+    use std::time::Duration;
+    use trillium_logger::Logger;
+    Logger::new().with_slow_request_threshold(Duration::from_secs(1));
+    ```
+    */
+    pub fn with_slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_threshold = Some(threshold);
+        self
+    }
+
+    /**
+    specify where slow requests are flagged. this has no effect unless
+    a threshold is set with [`Logger::with_slow_request_threshold`]
+
+    ```
+    use std::time::Duration;
+    use trillium_logger::{Logger, Target};
+    Logger::new()
+        .with_slow_request_threshold(Duration::from_millis(500))
+        .with_slow_request_target(Target::Logger(log::Level::Error));
+    ```
+    */
+    pub fn with_slow_request_target(mut self, target: impl Targetable) -> Self {
+        self.slow_target = Arc::new(target);
+        self
+    }
+
+    /**
+    periodically write a summary of the requests completed since the
+    last summary to the logger target, including the number of
+    requests, counts by status class, and approximate latency
+    percentiles. latencies are aggregated into buckets from 1ms to
+    10s, so percentiles are reported as the upper bound of a bucket.
+
+    this does not spawn a task, so a summary is written after the
+    first request that completes once the interval has elapsed.
+
+    ```
+    use std::time::Duration;
+    use trillium_logger::Logger;
+    Logger::new().with_summary_interval(Duration::from_secs(60));
+    ```
+    */
+    pub fn with_summary_interval(mut self, interval: Duration) -> Self {
+        self.summary = Some(Arc::new(Summary::new(interval)));
+        self
+    }
+
+    /**
+    only log requests for which this predicate returns true. the
+    predicate is called before the response is sent, so the response
+    status and headers are available. requests that are filtered out
+    are not formatted, and are neither flagged as slow nor counted in
+    the summary.
+
+    ```
+    use trillium::Conn;
+    use trillium_logger::Logger;
+    Logger::new().with_filter(|conn: &Conn| conn.path() != "/healthz");
+    ```
+    */
+    pub fn with_filter(mut self, filter: impl Fn(&Conn) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Arc::new(filter));
+        self
+    }
+
+    /**
+    only log a random sample of requests, where `sample_rate` is the
+    proportion of requests to log between `0.0` (none) and `1.0`
+    (all). requests that are not sampled are not formatted and are not
+    flagged as slow, but are still counted in the summary. this is
+    applied after any filter provided with [`Logger::with_filter`],
+    which can be combined with separate loggers to sample only
+    high-volume routes.
+
+    ```
+    use trillium_logger::Logger;
+    Logger::new().with_sample_rate(0.1);
+    ```
+
+    # Panics
+
+    panics if `sample_rate` is not between `0.0` and `1.0`
+    */
+    pub fn with_sample_rate(mut self, sample_rate: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&sample_rate),
+            "sample rate must be between 0.0 and 1.0, but was {sample_rate}"
+        );
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    fn is_sampled(&self) -> bool {
+        self.sample_rate
+            .is_none_or(|sample_rate| fastrand::f64() < sample_rate)
+    }
 }
 
 struct LoggerWasRun;
@@ -296,13 +434,54 @@ Control-C to quit",
     }
 
     async fn before_send(&self, mut conn: Conn) -> Conn {
-        if conn.state::<LoggerWasRun>().is_some() {
-            let target = self.target.clone();
-            let output = self.format.format(&conn, self.color_mode.is_enabled());
-            conn.inner_mut()
-                .after_send(move |_| target.write(output.to_string()));
+        if conn.state::<LoggerWasRun>().is_none()
+            || self.filter.as_ref().is_some_and(|filter| !filter(&conn))
+        {
+            return conn;
+        }
+
+        if !self.is_sampled() {
+            if let Some(summary) = self.summary.clone() {
+                let target = self.target.clone();
+                let start_time = conn.inner().start_time();
+                let status = conn.status();
+                conn.inner_mut().after_send(move |_| {
+                    if let Some(line) = summary.record(start_time.elapsed(), status) {
+                        target.write(line);
+                    }
+                });
+            }
+            return conn;
         }
 
+        let target = self.target.clone();
+        let output = self.format.format(&conn, self.color_mode.is_enabled());
+        let start_time = conn.inner().start_time();
+        let status = conn.status();
+        let slow = self
+            .slow_threshold
+            .map(|threshold| (threshold, self.slow_target.clone()));
+        let summary = self.summary.clone();
+
+        conn.inner_mut().after_send(move |_| {
+            let output = output.to_string();
+            let duration = start_time.elapsed();
+
+            if let Some((threshold, slow_target)) = slow {
+                if duration > threshold {
+                    slow_target.write(format!(
+                        "slow request ({duration:?} > {threshold:?}): {output}"
+                    ));
+                }
+            }
+
+            target.write(output);
+
+            if let Some(line) = summary.and_then(|summary| summary.record(duration, status)) {
+                target.write(line);
+            }
+        });
+
         conn
     }
 }
@@ -0,0 +1,124 @@
+use std::{
+    borrow::Cow,
+    fmt::{self, Display, Formatter},
+};
+use trillium::Conn;
+
+/**
+Key/value pairs added to a conn by handlers with
+[`LoggerConnExt::log_field`], to be included in the access log line
+for that conn.
+
+These are collected when the logger formats the conn, so fields can
+be added by any handler that runs before the response is sent,
+including in `before_send`. They are included in every line written
+by the [`json_formatter`](crate::json_formatter), and can be added to
+other formats with [`formatters::log_fields`](crate::formatters::log_fields)
+or [`formatters::log_field`](crate::formatters::log_field).
+
+Fields are displayed in the order that they were first added, in a
+`key=value` format that quotes values containing whitespace.
+*/
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogFields(Vec<(Cow<'static, str>, String)>);
+
+impl LogFields {
+    /// Adds a field, replacing the value of any existing field with
+    /// the same name
+    pub fn insert(&mut self, name: impl Into<Cow<'static, str>>, value: impl Display) {
+        let name = name.into();
+        let value = value.to_string();
+        match self.0.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.0.push((name, value)),
+        }
+    }
+
+    /// Retrieves the value of a field by name
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find_map(|(n, value)| (n == name).then_some(value.as_str()))
+    }
+
+    /// Iterates over the names and values of these fields, in the order
+    /// that they were added
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_ref(), value.as_str()))
+    }
+
+    /// The number of fields
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether no fields have been added
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Display for LogFields {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, (name, value)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+
+            if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"') {
+                write!(f, "{name}={value:?}")?;
+            } else {
+                write!(f, "{name}={value}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Extension trait for adding fields to the access log line for a
+[`Conn`]. See [`LogFields`] for details.
+
+```
+use trillium::Conn;
+use trillium_logger::{json_formatter, logger, LoggerConnExt};
+
+let handler = (
+    logger().with_formatter(json_formatter()),
+    |mut conn: Conn| async move {
+        conn.log_field("user_id", 42).log_field("cache", "miss");
+        conn.with_log_field("tenant", "acme").ok("ok")
+    },
+);
+```
+*/
+pub trait LoggerConnExt {
+    /// Adds a field to the access log line for this conn, replacing
+    /// the value of any existing field with the same name
+    fn log_field(&mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> &mut Self;
+
+    /// Chainable variant of [`LoggerConnExt::log_field`]
+    fn with_log_field(self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self;
+
+    /// The fields that have been added to this conn, if any
+    fn log_fields(&self) -> Option<&LogFields>;
+}
+
+impl LoggerConnExt for Conn {
+    fn log_field(&mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> &mut Self {
+        self.mut_state_or_insert_with(LogFields::default)
+            .insert(name, value);
+        self
+    }
+
+    fn with_log_field(mut self, name: impl Into<Cow<'static, str>>, value: impl Display) -> Self {
+        self.log_field(name, value);
+        self
+    }
+
+    fn log_fields(&self) -> Option<&LogFields> {
+        self.state()
+    }
+}
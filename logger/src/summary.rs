@@ -0,0 +1,114 @@
+use std::{
+    fmt::Write,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use trillium::Status;
+
+const BUCKETS_MS: [u64; 13] = [1, 2, 5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000];
+
+#[derive(Debug)]
+pub(crate) struct Summary {
+    interval: Duration,
+    state: Mutex<SummaryState>,
+}
+
+#[derive(Debug)]
+struct SummaryState {
+    started: Instant,
+    count: u64,
+    max: Duration,
+    // the last bucket counts everything slower than the largest bound
+    histogram: [u64; BUCKETS_MS.len() + 1],
+    // indexed by the first digit of the status, with 0 for no status
+    statuses: [u64; 6],
+}
+
+impl SummaryState {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            count: 0,
+            max: Duration::ZERO,
+            histogram: [0; BUCKETS_MS.len() + 1],
+            statuses: [0; 6],
+        }
+    }
+
+    fn percentile(&self, percentile: u64) -> String {
+        let target = (self.count * percentile).div_ceil(100);
+        let mut seen = 0;
+        for (bucket, count) in self.histogram.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                return match BUCKETS_MS.get(bucket) {
+                    Some(bound) => format!("≤{:?}", Duration::from_millis(*bound)),
+                    None => format!(">{:?}", Duration::from_millis(BUCKETS_MS[bucket - 1])),
+                };
+            }
+        }
+        unreachable!()
+    }
+
+    fn format(&self) -> String {
+        let mut line = format!("{} requests in {:?}", self.count, self.started.elapsed());
+
+        for (class, count) in self.statuses.iter().enumerate() {
+            if *count > 0 {
+                if class == 0 {
+                    write!(line, ", no status: {count}").unwrap();
+                } else {
+                    write!(line, ", {class}xx: {count}").unwrap();
+                }
+            }
+        }
+
+        if self.count > 0 {
+            write!(
+                line,
+                ", p50 {}, p95 {}, p99 {}, max {:?}",
+                self.percentile(50),
+                self.percentile(95),
+                self.percentile(99),
+                self.max
+            )
+            .unwrap();
+        }
+
+        line
+    }
+}
+
+impl Summary {
+    pub(crate) fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            state: Mutex::new(SummaryState::new()),
+        }
+    }
+
+    /// records a completed request, returning a summary line if the
+    /// interval has elapsed since the last summary
+    pub(crate) fn record(&self, duration: Duration, status: Option<Status>) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        state.count += 1;
+        state.max = state.max.max(duration);
+
+        let bucket = BUCKETS_MS
+            .iter()
+            .position(|bound| duration <= Duration::from_millis(*bound))
+            .unwrap_or(BUCKETS_MS.len());
+        state.histogram[bucket] += 1;
+
+        let class = status.map_or(0, |status| usize::from(status as u16 / 100).min(5));
+        state.statuses[class] += 1;
+
+        if state.started.elapsed() >= self.interval {
+            let line = state.format();
+            *state = SummaryState::new();
+            Some(line)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use trillium::Conn;
+use trillium_logger::{formatters, logger, ColorMode};
+use trillium_testing::prelude::*;
+
+fn collector() -> (
+    Arc<Mutex<Vec<String>>>,
+    impl Fn(String) + Send + Sync + 'static,
+) {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let target = {
+        let lines = lines.clone();
+        move |line: String| lines.lock().unwrap().push(line)
+    };
+    (lines, target)
+}
+
+#[test]
+fn slow_requests_are_flagged() {
+    let (lines, target) = collector();
+    let (slow, slow_target) = collector();
+    let handler = (
+        logger()
+            .with_formatter((formatters::method, " ", formatters::url))
+            .with_color_mode(ColorMode::Off)
+            .with_target(target)
+            .with_slow_request_threshold(Duration::ZERO)
+            .with_slow_request_target(slow_target),
+        "ok",
+    );
+
+    get("/slow").on(&handler);
+    assert_eq!(*lines.lock().unwrap(), ["GET /slow"]);
+    let slow = slow.lock().unwrap();
+    assert_eq!(slow.len(), 1);
+    assert!(slow[0].starts_with("slow request ("));
+    assert!(slow[0].ends_with("> 0ns): GET /slow"));
+}
+
+#[test]
+fn fast_requests_are_not_flagged() {
+    let (slow, slow_target) = collector();
+    let handler = (
+        logger()
+            .with_target(|_| {})
+            .with_slow_request_threshold(Duration::from_secs(60))
+            .with_slow_request_target(slow_target),
+        "ok",
+    );
+
+    get("/").on(&handler);
+    assert!(slow.lock().unwrap().is_empty());
+}
+
+#[test]
+fn summaries() {
+    let (lines, target) = collector();
+    let handler = (
+        logger()
+            .with_formatter("request")
+            .with_target(target)
+            .with_summary_interval(Duration::from_secs(60)),
+        |conn: Conn| async move {
+            match conn.path() {
+                "/missing" => conn.with_status(404),
+                "/error" => conn.with_status(500),
+                _ => conn.ok("ok"),
+            }
+        },
+    );
+
+    get("/").on(&handler);
+    get("/missing").on(&handler);
+    assert_eq!(*lines.lock().unwrap(), ["request", "request"]);
+
+    let (lines, target) = collector();
+    let handler = (
+        logger()
+            .with_formatter("request")
+            .with_target(target)
+            .with_summary_interval(Duration::ZERO),
+        |conn: Conn| async move { conn.with_status(500) },
+    );
+
+    get("/error").on(&handler);
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "request");
+    assert!(lines[1].starts_with("1 requests in "), "{}", lines[1]);
+    assert!(lines[1].contains(", 5xx: 1, p50 ≤"), "{}", lines[1]);
+}
+
+#[test]
+fn filtered_requests_are_not_logged() {
+    let (lines, target) = collector();
+    let handler = (
+        logger()
+            .with_formatter((formatters::method, " ", formatters::url))
+            .with_target(target)
+            .with_filter(|conn: &Conn| conn.path() != "/healthz"),
+        "ok",
+    );
+
+    get("/healthz").on(&handler);
+    get("/widgets").on(&handler);
+    assert_eq!(*lines.lock().unwrap(), ["GET /widgets"]);
+}
+
+#[test]
+fn sampled_requests() {
+    let (lines, target) = collector();
+    let handler = (
+        logger()
+            .with_formatter("request")
+            .with_target(target)
+            .with_sample_rate(0.0),
+        "ok",
+    );
+    for _ in 0..10 {
+        get("/").on(&handler);
+    }
+    assert!(lines.lock().unwrap().is_empty());
+
+    let (lines, target) = collector();
+    let handler = (
+        logger()
+            .with_formatter("request")
+            .with_target(target)
+            .with_sample_rate(1.0),
+        "ok",
+    );
+    for _ in 0..10 {
+        get("/").on(&handler);
+    }
+    assert_eq!(lines.lock().unwrap().len(), 10);
+
+    let (lines, target) = collector();
+    let handler = (
+        logger()
+            .with_formatter("request")
+            .with_target(target)
+            .with_sample_rate(0.0)
+            .with_summary_interval(Duration::ZERO),
+        "ok",
+    );
+    get("/").on(&handler);
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].starts_with("1 requests in "), "{}", lines[0]);
+}
+
+#[test]
+#[should_panic(expected = "sample rate must be between 0.0 and 1.0")]
+fn invalid_sample_rate() {
+    let _ = logger().with_sample_rate(1.5);
+}
@@ -0,0 +1,84 @@
+use std::{fs, path::PathBuf};
+use trillium_logger::{logger, FileTarget, Rotation, Targetable};
+use trillium_testing::prelude::*;
+
+fn log_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("trillium-logger-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn writes_lines() {
+    let dir = log_dir("writes-lines");
+    let path = dir.join("access.log");
+    let target = FileTarget::open(&path).unwrap();
+    target.write(String::from("first"));
+    target.write(String::from("second"));
+    target.flush();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+    drop(target);
+
+    let target = FileTarget::open(&path).unwrap();
+    target.write(String::from("appended"));
+    drop(target);
+    // dropping the target flushes on the writer thread
+    let mut contents = String::new();
+    for _ in 0..100 {
+        contents = fs::read_to_string(&path).unwrap();
+        if contents.ends_with("appended\n") {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert_eq!(contents, "first\nsecond\nappended\n");
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn rotates_by_size() {
+    let dir = log_dir("rotates-by-size");
+    let path = dir.join("access.log");
+    let target = FileTarget::builder(&path)
+        .with_rotation(Rotation::Size(20))
+        .with_max_files(2)
+        .open()
+        .unwrap();
+
+    for line in ["line one", "line two", "line three", "line four"] {
+        target.write(String::from(line));
+    }
+    target.flush();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "line four\n");
+    assert_eq!(
+        fs::read_to_string(dir.join("access.log.1")).unwrap(),
+        "line three\n"
+    );
+    assert_eq!(
+        fs::read_to_string(dir.join("access.log.2")).unwrap(),
+        "line one\nline two\n"
+    );
+    assert!(!dir.join("access.log.3").exists());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn as_logger_target() {
+    let dir = log_dir("as-logger-target");
+    let path = dir.join("access.log");
+    let target = std::sync::Arc::new(FileTarget::open(&path).unwrap());
+    let handler = (
+        logger().with_formatter("request").with_target({
+            let target = target.clone();
+            move |line: String| target.write(line)
+        }),
+        "ok",
+    );
+    get("/").on(&handler);
+    get("/").on(&handler);
+    target.flush();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "request\nrequest\n");
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,79 @@
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use trillium::Conn;
+use trillium_conn_id::{conn_id, log_formatter};
+use trillium_logger::{json_formatter, logger};
+use trillium_testing::prelude::*;
+
+fn log_line(handler: impl trillium::Handler, conn: trillium_testing::TestConn) -> Value {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let handler = (
+        logger().with_formatter(json_formatter()).with_target({
+            let lines = lines.clone();
+            move |line: String| lines.lock().unwrap().push(line)
+        }),
+        handler,
+    );
+    conn.on(&handler);
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 1);
+    serde_json::from_str(&lines[0]).unwrap()
+}
+
+#[test]
+fn built_in_fields() {
+    let line = log_line(
+        |conn: Conn| async move { conn.with_status(201).with_body("created") },
+        post("/some/path?a=b").with_peer_ip("1.2.3.4".parse().unwrap()),
+    );
+
+    assert_eq!(line["method"], "POST");
+    assert_eq!(line["path"], "/some/path");
+    assert_eq!(line["query"], "a=b");
+    assert_eq!(line["status"], 201);
+    assert_eq!(line["bytes"], 7);
+    assert_eq!(line["ip"], "1.2.3.4");
+    assert!(line["duration_ms"].as_f64().unwrap() >= 0.0);
+    assert!(line["timestamp"].is_string());
+}
+
+#[test]
+fn missing_values() {
+    let line = log_line((), get("/"));
+    assert_eq!(line["status"], 404);
+    assert!(line.get("query").is_none());
+    assert!(line["ip"].is_null());
+}
+
+#[test]
+fn user_fields() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let handler = (
+        conn_id().with_seed(1000),
+        logger()
+            .with_formatter(
+                json_formatter()
+                    .with_field("conn_id", log_formatter::conn_id)
+                    .with_field("quote", |_: &Conn, _: bool| "\"quoted\"")
+                    .with_field("status", "overridden"),
+            )
+            .with_target({
+                let lines = lines.clone();
+                move |line: String| lines.lock().unwrap().push(line)
+            }),
+        "ok",
+    );
+
+    let conn = get("/").on(&handler);
+    let id = conn
+        .response_headers()
+        .get_str("x-request-id")
+        .unwrap()
+        .to_string();
+    drop(conn);
+    let lines = lines.lock().unwrap();
+    let line: Value = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(line["conn_id"], id);
+    assert_eq!(line["quote"], "\"quoted\"");
+    assert_eq!(line["status"], "overridden");
+}
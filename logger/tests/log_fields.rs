@@ -0,0 +1,68 @@
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use trillium::Conn;
+use trillium_logger::{
+    formatters::{log_field, log_fields, method},
+    json_formatter, logger, ColorMode, LogFormatter, LoggerConnExt,
+};
+use trillium_testing::prelude::*;
+
+fn log_line(formatter: impl LogFormatter) -> String {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let handler = (
+        logger()
+            .with_formatter(formatter)
+            .with_color_mode(ColorMode::Off)
+            .with_target({
+                let lines = lines.clone();
+                move |line: String| lines.lock().unwrap().push(line)
+            }),
+        |mut conn: Conn| async move {
+            conn.log_field("user_id", 42)
+                .log_field("tenant", "acme corp")
+                .log_field("user_id", 43);
+            conn.with_log_field("cache", "miss").ok("ok")
+        },
+    );
+    get("/").on(&handler);
+    let lines = lines.lock().unwrap();
+    assert_eq!(lines.len(), 1);
+    lines[0].clone()
+}
+
+#[test]
+fn all_fields() {
+    assert_eq!(
+        log_line((method, " ", log_fields)),
+        r#"GET user_id=43 tenant="acme corp" cache=miss"#
+    );
+}
+
+#[test]
+fn single_field() {
+    assert_eq!(
+        log_line((log_field("tenant"), " ", log_field("missing"))),
+        "acme corp -"
+    );
+}
+
+#[test]
+fn json() {
+    let line: Value = serde_json::from_str(&log_line(
+        json_formatter().with_field("cache", "overridden"),
+    ))
+    .unwrap();
+    assert_eq!(line["user_id"], "43");
+    assert_eq!(line["tenant"], "acme corp");
+    assert_eq!(line["cache"], "overridden");
+}
+
+#[test]
+fn conn_ext() {
+    let conn = get("/").on(&|conn: Conn| async move { conn.with_log_field("a", "") });
+    let fields = conn.log_fields().unwrap();
+    assert_eq!(fields.get("a"), Some(""));
+    assert_eq!(fields.len(), 1);
+    assert_eq!(fields.to_string(), r#"a="""#);
+    assert!(get("/").on(&()).log_fields().is_none());
+}
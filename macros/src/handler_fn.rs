@@ -0,0 +1,249 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Attribute, Error, Expr, FnArg, Ident, ItemFn, Meta, MetaNameValue, Pat, PatType, Token, Type,
+    TypeReference,
+};
+
+struct HandlerFnArgs {
+    name: Option<Ident>,
+}
+
+impl Parse for HandlerFnArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self { name: None });
+        }
+
+        let key: Ident = input.parse()?;
+        if key != "name" {
+            return Err(Error::new(
+                key.span(),
+                "unrecognized #[handler_fn] argument. the only valid option is name",
+            ));
+        }
+        input.parse::<Token![=]>()?;
+        let name = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after #[handler_fn(name = ...)]"));
+        }
+        Ok(Self { name: Some(name) })
+    }
+}
+
+struct ConfigField {
+    ident: Ident,
+    ty: Type,
+    default: Option<Expr>,
+}
+
+fn config_attribute(attrs: &mut Vec<Attribute>) -> syn::Result<Option<Option<Expr>>> {
+    let Some(index) = attrs.iter().position(|attr| attr.path().is_ident("config")) else {
+        return Ok(None);
+    };
+
+    match attrs.remove(index).meta {
+        Meta::Path(_) => Ok(Some(None)),
+        Meta::List(list) => {
+            let MetaNameValue { path, value, .. } = syn::parse2(list.tokens)?;
+            if path.is_ident("default") {
+                Ok(Some(Some(value)))
+            } else {
+                Err(Error::new(
+                    path.span(),
+                    "unrecognized #[config] argument. the only valid option is default",
+                ))
+            }
+        }
+        Meta::NameValue(nv) => Err(Error::new(
+            nv.span(),
+            "unrecognized #[config] attribute. use #[config] or #[config(default = ...)]",
+        )),
+    }
+}
+
+fn camel_case(ident: &Ident) -> Ident {
+    let camel = ident
+        .to_string()
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect::<String>();
+    Ident::new(&camel, ident.span())
+}
+
+pub fn handler_fn(args: TokenStream, item: TokenStream) -> TokenStream {
+    let HandlerFnArgs { name } = parse_macro_input!(args as HandlerFnArgs);
+    let item = parse_macro_input!(item as ItemFn);
+    match expand(name, item) {
+        Ok(tokens) => tokens.into(),
+        Err(error) => error.into_compile_error().into(),
+    }
+}
+
+fn expand(name: Option<Ident>, item: ItemFn) -> syn::Result<proc_macro2::TokenStream> {
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+
+    if sig.asyncness.is_none() {
+        return Err(Error::new(
+            sig.fn_token.span(),
+            "#[handler_fn] can only be applied to async fns",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "#[handler_fn] does not support generic fns",
+        ));
+    }
+
+    let mut fields = vec![];
+    let mut conn = None;
+
+    for input in sig.inputs {
+        let FnArg::Typed(PatType {
+            mut attrs, pat, ty, ..
+        }) = input
+        else {
+            return Err(Error::new(
+                input.span(),
+                "#[handler_fn] fns cannot take self",
+            ));
+        };
+
+        if let Some(default) = config_attribute(&mut attrs)? {
+            let Pat::Ident(pat_ident) = *pat else {
+                return Err(Error::new(
+                    pat.span(),
+                    "#[config] arguments must be named with a single identifier",
+                ));
+            };
+
+            let Type::Reference(TypeReference {
+                mutability: None,
+                elem,
+                ..
+            }) = *ty
+            else {
+                return Err(Error::new(
+                    ty.span(),
+                    "#[config] arguments must be shared references, as they are borrowed from the handler",
+                ));
+            };
+
+            fields.push(ConfigField {
+                ident: pat_ident.ident,
+                ty: *elem,
+                default,
+            });
+        } else if conn.is_some() {
+            return Err(Error::new(
+                pat.span(),
+                "#[handler_fn] fns take exactly one argument without #[config], the conn",
+            ));
+        } else {
+            conn = Some((pat, ty));
+        }
+    }
+
+    let Some((conn_pat, conn_ty)) = conn else {
+        return Err(Error::new(
+            Span::call_site(),
+            "#[handler_fn] fns take exactly one argument without #[config], the conn",
+        ));
+    };
+
+    let fn_name = sig.ident;
+    let struct_name = name.unwrap_or_else(|| camel_case(&fn_name));
+
+    let field_defs = fields
+        .iter()
+        .map(|ConfigField { ident, ty, .. }| quote!(#ident: #ty));
+
+    let required = fields
+        .iter()
+        .filter(|field| field.default.is_none())
+        .collect::<Vec<_>>();
+    let required_args = required
+        .iter()
+        .map(|ConfigField { ident, ty, .. }| quote!(#ident: #ty))
+        .collect::<Vec<_>>();
+    let required_idents = required
+        .iter()
+        .map(|field| &field.ident)
+        .collect::<Vec<_>>();
+
+    let field_inits = fields.iter().map(|ConfigField { ident, default, .. }| {
+        default
+            .as_ref()
+            .map_or_else(|| quote!(#ident), |default| quote!(#ident: #default))
+    });
+
+    let builders = fields.iter().map(|ConfigField { ident, ty, .. }| {
+        let method = format_ident!("with_{}", ident);
+        let doc = format!("Sets the `{ident}` config for this handler");
+        quote! {
+            #[doc = #doc]
+            #vis fn #method(mut self, #ident: #ty) -> Self {
+                self.#ident = #ident;
+                self
+            }
+        }
+    });
+
+    let bindings = fields
+        .iter()
+        .map(|ConfigField { ident, .. }| quote!(let #ident = &self.#ident;));
+
+    let new_doc = format!("Builds a new `{struct_name}` handler");
+    let alias_doc = format!("Convenience alias for [`{struct_name}::new`]");
+
+    Ok(quote! {
+        #(#attrs)*
+        #vis struct #struct_name {
+            #(#field_defs,)*
+        }
+
+        impl #struct_name {
+            #[doc = #new_doc]
+            #[allow(clippy::new_without_default)]
+            #vis fn new(#(#required_args),*) -> Self {
+                Self {
+                    #(#field_inits,)*
+                }
+            }
+
+            #(#builders)*
+        }
+
+        #[doc = #alias_doc]
+        #vis fn #fn_name(#(#required_args),*) -> #struct_name {
+            #struct_name::new(#(#required_idents),*)
+        }
+
+        #[trillium::async_trait]
+        impl trillium::Handler for #struct_name {
+            async fn run(&self, conn: trillium::Conn) -> trillium::Conn {
+                #(#bindings)*
+                let #conn_pat: #conn_ty = conn;
+                #block
+            }
+        }
+    })
+}
@@ -18,6 +18,16 @@ pub fn derive_handler(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     handler::derive_handler(input)
 }
 
+mod handler_fn;
+/// see crate docs
+#[proc_macro_attribute]
+pub fn handler_fn(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    handler_fn::handler_fn(args, input)
+}
+
 mod transport;
 ///
 #[proc_macro_derive(Transport, attributes(transport))]
@@ -46,6 +46,11 @@ enum Override {
     SetNodelay,
     SetIpTtl,
     PeerAddr,
+    LocalAddr,
+    TransportKind,
+    NegotiatedAlpn,
+    PeerCredentials,
+    PeerCertificates,
 }
 
 impl TryFrom<&Path> for Override {
@@ -59,6 +64,16 @@ impl TryFrom<&Path> for Override {
             Ok(Self::SetIpTtl)
         } else if path.is_ident("peer_addr") {
             Ok(Self::PeerAddr)
+        } else if path.is_ident("local_addr") {
+            Ok(Self::LocalAddr)
+        } else if path.is_ident("transport_kind") {
+            Ok(Self::TransportKind)
+        } else if path.is_ident("negotiated_alpn") {
+            Ok(Self::NegotiatedAlpn)
+        } else if path.is_ident("peer_credentials") {
+            Ok(Self::PeerCredentials)
+        } else if path.is_ident("peer_certificates") {
+            Ok(Self::PeerCertificates)
         } else {
             Err(Error::new(
                 path.span(),
@@ -78,7 +93,7 @@ struct DeriveOptions {
 fn overrides<'a, I: Iterator<Item = &'a Expr>>(iter: I) -> syn::Result<Vec<Override>> {
     iter.map(|expr| match expr {
         Expr::Path(ExprPath { path, .. }) => path.try_into(),
-        _ => Err(Error::new(expr.span(), "unrecognized override. valid options are set_linger, set_nodelay, set_ip_ttl, and peer_addr")),
+        _ => Err(Error::new(expr.span(), "unrecognized override. valid options are set_linger, set_nodelay, set_ip_ttl, peer_addr, local_addr, transport_kind, negotiated_alpn, peer_credentials, and peer_certificates")),
     })
     .collect()
 }
@@ -240,12 +255,47 @@ pub fn derive_transport(input: TokenStream) -> TokenStream {
         quote!(trillium_server_common::Transport::peer_addr(&#transport))
     };
 
+    let local_addr = if overrides.contains(&Override::LocalAddr) {
+        quote!(Self::local_addr(self))
+    } else {
+        quote!(trillium_server_common::Transport::local_addr(&#transport))
+    };
+
+    let transport_kind = if overrides.contains(&Override::TransportKind) {
+        quote!(Self::transport_kind(self))
+    } else {
+        quote!(trillium_server_common::Transport::transport_kind(&#transport))
+    };
+
+    let negotiated_alpn = if overrides.contains(&Override::NegotiatedAlpn) {
+        quote!(Self::negotiated_alpn(self))
+    } else {
+        quote!(trillium_server_common::Transport::negotiated_alpn(&#transport))
+    };
+
+    let peer_credentials = if overrides.contains(&Override::PeerCredentials) {
+        quote!(Self::peer_credentials(self))
+    } else {
+        quote!(trillium_server_common::Transport::peer_credentials(&#transport))
+    };
+
+    let peer_certificates = if overrides.contains(&Override::PeerCertificates) {
+        quote!(Self::peer_certificates(self))
+    } else {
+        quote!(trillium_server_common::Transport::peer_certificates(&#transport))
+    };
+
     quote! {
         impl #impl_generics trillium_server_common::Transport for #struct_name #ty_generics #where_clause {
             fn set_linger(&mut self, linger: Option<core::time::Duration>) -> std::io::Result<()> { #set_linger }
             fn set_nodelay(&mut self, nodelay: bool) -> std::io::Result<()> { #set_nodelay }
             fn set_ip_ttl(&mut self, ttl: u32) -> std::io::Result<()> { #set_ip_ttl }
             fn peer_addr(&self) -> std::io::Result<Option<std::net::SocketAddr>> { #peer_addr }
+            fn local_addr(&self) -> std::io::Result<Option<std::net::SocketAddr>> { #local_addr }
+            fn transport_kind(&self) -> trillium_server_common::TransportKind { #transport_kind }
+            fn negotiated_alpn(&self) -> Option<&[u8]> { #negotiated_alpn }
+            fn peer_credentials(&self) -> std::io::Result<Option<trillium_server_common::PeerCredentials>> { #peer_credentials }
+            fn peer_certificates(&self) -> Option<trillium_server_common::PeerCertificates> { #peer_certificates }
         }
     }
     .into()
@@ -0,0 +1,42 @@
+use trillium::{Conn, Handler};
+use trillium_macros::handler_fn;
+use trillium_testing::prelude::*;
+
+fn assert_handler(_: impl Handler) {}
+
+#[handler_fn]
+async fn greet(
+    #[config] greeting: &String,
+    #[config(default = 1)] times: &usize,
+    conn: Conn,
+) -> Conn {
+    conn.ok(greeting.repeat(*times))
+}
+
+#[handler_fn(name = Teapot)]
+#[derive(Clone, Copy, Debug)]
+async fn short_and_stout(mut conn: Conn) -> Conn {
+    conn.set_status(418);
+    conn.halt()
+}
+
+#[test]
+fn config_and_defaults() {
+    assert_ok!(get("/").on(&greet("hi".into())), "hi");
+    assert_ok!(
+        get("/").on(&Greet::new("hi".into()).with_times(3)),
+        "hihihi"
+    );
+    assert_ok!(
+        get("/").on(&greet("hi".into()).with_greeting("hey".into())),
+        "hey"
+    );
+}
+
+#[test]
+fn named_without_config() {
+    let handler = Teapot::new();
+    assert_handler(handler);
+    assert_status!(get("/").on(&(handler, "not reached")), 418);
+    assert_eq!(format!("{:?}", short_and_stout()), "Teapot");
+}
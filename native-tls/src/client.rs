@@ -8,7 +8,9 @@ use std::{
     sync::Arc,
     task::{Context, Poll},
 };
-use trillium_server_common::{async_trait, AsyncRead, AsyncWrite, Connector, Transport, Url};
+use trillium_server_common::{
+    async_trait, AsyncRead, AsyncWrite, BoxedTransport, Connector, Transport, Url,
+};
 
 /**
 Configuration for the native tls client connector
@@ -103,6 +105,21 @@ impl<T: Connector> Connector for NativeTlsConfig<T> {
     fn spawn<Fut: Future<Output = ()> + Send + 'static>(&self, fut: Fut) {
         self.tcp_config.spawn(fut)
     }
+
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        match url.scheme() {
+            "https" => self
+                .tls_connector
+                .connect(url, transport)
+                .await
+                .map_err(|e| Error::other(e.to_string()))
+                .map(|tls| {
+                    BoxedTransport::new(NativeTlsClientTransport::<BoxedTransport>::from(tls))
+                }),
+
+            _ => self.tcp_config.connect_over(url, transport).await,
+        }
+    }
 }
 
 /**
@@ -18,6 +18,8 @@ pub use async_native_tls;
 pub use native_tls;
 pub use native_tls::Identity;
 
+mod pkcs8;
+
 mod server;
 pub use server::{NativeTlsAcceptor, NativeTlsServerTransport};
 
@@ -0,0 +1,108 @@
+use pem::Pem;
+
+// DER encodings of the algorithm identifiers that a pkcs8 PrivateKeyInfo
+// uses to describe the key it wraps
+const RSA_ENCRYPTION: &[u8] = &[
+    0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01, 0x05, 0x00,
+];
+const EC_PUBLIC_KEY: &[u8] = &[0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+
+const SEQUENCE: u8 = 0x30;
+const OCTET_STRING: u8 = 0x04;
+const EC_PARAMETERS: u8 = 0xa0;
+
+/// native-tls only accepts pkcs8 private keys, so this rewrites the
+/// first private key in the pem as pkcs8 if it is a pkcs1 rsa key or a
+/// sec1 ec key. Anything else is returned unchanged for native-tls to
+/// accept or reject.
+pub(crate) fn private_key_to_pkcs8(key: &[u8]) -> Vec<u8> {
+    let Ok(pems) = pem::parse_many(key) else {
+        return key.to_vec();
+    };
+
+    let Some(private_key) = pems.iter().find(|pem| pem.tag().ends_with("PRIVATE KEY")) else {
+        return key.to_vec();
+    };
+
+    let algorithm = match private_key.tag() {
+        "RSA PRIVATE KEY" => RSA_ENCRYPTION.to_vec(),
+        "EC PRIVATE KEY" => match sec1_curve(private_key.contents()) {
+            Some(curve) => [EC_PUBLIC_KEY, curve].concat(),
+            None => return key.to_vec(),
+        },
+        _ => return key.to_vec(),
+    };
+
+    let private_key_info = der(
+        SEQUENCE,
+        &[
+            &[0x02, 0x01, 0x00][..],
+            &der(SEQUENCE, &algorithm),
+            &der(OCTET_STRING, private_key.contents()),
+        ]
+        .concat(),
+    );
+
+    pem::encode(&Pem::new("PRIVATE KEY", private_key_info)).into_bytes()
+}
+
+// returns the named curve oid from the parameters of a sec1
+// ECPrivateKey, which is a sequence of a version, the private key
+// octets, and then optionally [0] parameters and [1] public key
+fn sec1_curve(sec1: &[u8]) -> Option<&[u8]> {
+    let (SEQUENCE, mut fields, _) = read_der(sec1)? else {
+        return None;
+    };
+
+    while !fields.is_empty() {
+        let (tag, contents, rest) = read_der(fields)?;
+        if tag == EC_PARAMETERS {
+            return Some(contents);
+        }
+        fields = rest;
+    }
+
+    None
+}
+
+// splits a single der tag-length-value from the front of the input,
+// returning the tag, the value, and the remaining input
+fn read_der(input: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, input) = input.split_first()?;
+    let (&first, mut input) = input.split_first()?;
+
+    let len = if first < 0x80 {
+        usize::from(first)
+    } else {
+        let len_bytes = usize::from(first & 0x7f);
+        if len_bytes > size_of::<usize>() || input.len() < len_bytes {
+            return None;
+        }
+        let (len, rest) = input.split_at(len_bytes);
+        input = rest;
+        len.iter()
+            .fold(0, |len, byte| len << 8 | usize::from(*byte))
+    };
+
+    if input.len() < len {
+        return None;
+    }
+
+    let (contents, rest) = input.split_at(len);
+    Some((tag, contents, rest))
+}
+
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut der = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        der.push(len as u8);
+    } else {
+        let len_bytes = len.to_be_bytes();
+        let skip = len_bytes.iter().take_while(|byte| **byte == 0).count();
+        der.push(0x80 | (len_bytes.len() - skip) as u8);
+        der.extend_from_slice(&len_bytes[skip..]);
+    }
+    der.extend_from_slice(contents);
+    der
+}
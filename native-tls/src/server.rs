@@ -2,19 +2,28 @@ use std::{
     io::{self, IoSlice, IoSliceMut},
     net::SocketAddr,
     pin::Pin,
+    sync::{Arc, RwLock},
     task::{Context, Poll},
 };
 
-use crate::Identity;
+use crate::{pkcs8::private_key_to_pkcs8, Identity};
 use async_native_tls::{Error, TlsAcceptor, TlsStream};
-use trillium_server_common::{async_trait, Acceptor, AsyncRead, AsyncWrite, Transport};
+use trillium_server_common::{
+    async_trait, Acceptor, AsyncRead, AsyncWrite, PeerCredentials, Transport, TransportKind,
+};
 
 /**
 trillium [`Acceptor`] for native-tls
+
+Clones of a NativeTlsAcceptor share an identity, so the identity can
+be replaced at runtime with [`NativeTlsAcceptor::set_identity`] or
+[`NativeTlsAcceptor::set_pem`], for example to rotate certificates
+without restarting the server. Connections that have already been
+accepted are unaffected.
 */
 
 #[derive(Clone, Debug)]
-pub struct NativeTlsAcceptor(TlsAcceptor);
+pub struct NativeTlsAcceptor(Arc<RwLock<TlsAcceptor>>);
 
 impl NativeTlsAcceptor {
     /**
@@ -44,6 +53,59 @@ impl NativeTlsAcceptor {
             .expect("could not build Identity from provided pem and key")
             .into()
     }
+
+    /**
+    constructs a NativeTlsAcceptor from a pem certificate chain and a
+    pem private key. In addition to pkcs8 keys, this accepts pkcs1 rsa
+    keys (`BEGIN RSA PRIVATE KEY`) and sec1 ec keys (`BEGIN EC PRIVATE
+    KEY`), which are converted to pkcs8.
+
+    # Panics
+
+    Panics if the certificate chain and key are not valid. See
+    [`NativeTlsAcceptor::try_from_pem`] for a fallible alternative.
+    */
+    pub fn from_pem(cert_chain: &[u8], key: &[u8]) -> Self {
+        Self::try_from_pem(cert_chain, key)
+            .expect("could not build NativeTlsAcceptor from provided pem and key")
+    }
+
+    /**
+    constructs a NativeTlsAcceptor from a pem certificate chain and a
+    pem private key, returning an error if they are not valid. See
+    [`NativeTlsAcceptor::from_pem`]
+    */
+    pub fn try_from_pem(cert_chain: &[u8], key: &[u8]) -> Result<Self, Error> {
+        identity_from_pem(cert_chain, key)
+            .and_then(native_tls::TlsAcceptor::new)
+            .map(Self::from)
+    }
+
+    /**
+    replaces the identity presented to new connections for this
+    acceptor and every clone of it. If the identity cannot be used,
+    an error is returned and the current identity is retained.
+    */
+    pub fn set_identity(&self, identity: Identity) -> Result<(), Error> {
+        let acceptor = native_tls::TlsAcceptor::new(identity)?;
+        *self.0.write().unwrap() = acceptor.into();
+        Ok(())
+    }
+
+    /**
+    replaces the identity presented to new connections for this
+    acceptor and every clone of it with a pem certificate chain and
+    private key, as accepted by [`NativeTlsAcceptor::from_pem`]. If
+    they are not valid, an error is returned and the current identity
+    is retained.
+    */
+    pub fn set_pem(&self, cert_chain: &[u8], key: &[u8]) -> Result<(), Error> {
+        self.set_identity(identity_from_pem(cert_chain, key)?)
+    }
+}
+
+fn identity_from_pem(cert_chain: &[u8], key: &[u8]) -> Result<Identity, Error> {
+    Identity::from_pkcs8(cert_chain, &private_key_to_pkcs8(key))
 }
 
 impl From<Identity> for NativeTlsAcceptor {
@@ -54,13 +116,13 @@ impl From<Identity> for NativeTlsAcceptor {
 
 impl From<native_tls::TlsAcceptor> for NativeTlsAcceptor {
     fn from(i: native_tls::TlsAcceptor) -> Self {
-        Self(i.into())
+        Self::from(TlsAcceptor::from(i))
     }
 }
 
 impl From<TlsAcceptor> for NativeTlsAcceptor {
     fn from(i: TlsAcceptor) -> Self {
-        Self(i)
+        Self(Arc::new(RwLock::new(i)))
     }
 }
 
@@ -78,7 +140,12 @@ where
     type Output = NativeTlsServerTransport<Input>;
     type Error = Error;
     async fn accept(&self, input: Input) -> Result<Self::Output, Self::Error> {
-        self.0.accept(input).await.map(NativeTlsServerTransport)
+        let acceptor = self.0.read().unwrap().clone();
+        acceptor.accept(input).await.map(NativeTlsServerTransport)
+    }
+
+    fn is_secure(&self) -> bool {
+        true
     }
 }
 
@@ -158,4 +225,16 @@ impl<T: Transport> Transport for NativeTlsServerTransport<T> {
     fn peer_addr(&self) -> io::Result<Option<SocketAddr>> {
         self.0.get_ref().peer_addr()
     }
+
+    fn local_addr(&self) -> io::Result<Option<SocketAddr>> {
+        self.0.get_ref().local_addr()
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        self.0.get_ref().transport_kind()
+    }
+
+    fn peer_credentials(&self) -> io::Result<Option<PeerCredentials>> {
+        self.0.get_ref().peer_credentials()
+    }
 }
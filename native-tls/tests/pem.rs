@@ -0,0 +1,62 @@
+use futures_lite::future::{block_on, zip};
+use trillium_native_tls::{
+    async_native_tls::TlsConnector, native_tls::Certificate, NativeTlsAcceptor,
+};
+use trillium_server_common::Acceptor;
+use trillium_testing::TestTransport;
+
+const RSA_CERT: &[u8] = include_bytes!("fixtures/rsa.pem");
+const RSA_PKCS1_KEY: &[u8] = include_bytes!("fixtures/rsa-key.pem");
+const EC_CERT: &[u8] = include_bytes!("fixtures/ec.pem");
+const EC_SEC1_KEY: &[u8] = include_bytes!("fixtures/ec-key.pem");
+
+fn presented_certificate(acceptor: &NativeTlsAcceptor) -> Vec<u8> {
+    block_on(async {
+        let (client, server) = TestTransport::new();
+        let connector = TlsConnector::new().danger_accept_invalid_certs(true);
+        let (server, client) = zip(
+            acceptor.accept(server),
+            connector.connect("localhost", client),
+        )
+        .await;
+        server.unwrap();
+        client
+            .unwrap()
+            .peer_certificate()
+            .unwrap()
+            .unwrap()
+            .to_der()
+            .unwrap()
+    })
+}
+
+fn der(pem: &[u8]) -> Vec<u8> {
+    Certificate::from_pem(pem).unwrap().to_der().unwrap()
+}
+
+#[test]
+fn pkcs1_and_sec1_keys() {
+    let rsa = NativeTlsAcceptor::from_pem(RSA_CERT, RSA_PKCS1_KEY);
+    assert_eq!(presented_certificate(&rsa), der(RSA_CERT));
+
+    let ec = NativeTlsAcceptor::from_pem(EC_CERT, EC_SEC1_KEY);
+    assert_eq!(presented_certificate(&ec), der(EC_CERT));
+}
+
+#[test]
+fn invalid_pem() {
+    assert!(NativeTlsAcceptor::try_from_pem(RSA_CERT, b"not a key").is_err());
+    assert!(NativeTlsAcceptor::try_from_pem(b"not a cert", RSA_PKCS1_KEY).is_err());
+}
+
+#[test]
+fn set_pem() {
+    let acceptor = NativeTlsAcceptor::from_pem(RSA_CERT, RSA_PKCS1_KEY);
+    let clone = acceptor.clone();
+
+    acceptor.set_pem(EC_CERT, EC_SEC1_KEY).unwrap();
+    assert_eq!(presented_certificate(&clone), der(EC_CERT));
+
+    assert!(acceptor.set_pem(EC_CERT, b"not a key").is_err());
+    assert_eq!(presented_certificate(&clone), der(EC_CERT));
+}
@@ -0,0 +1,33 @@
+use trillium::Conn;
+use trillium_client::Client;
+use trillium_cookies::CookiesHandler;
+use trillium_oidc::{Oidc, OidcConnExt};
+use trillium_rustls::RustlsConfig;
+use trillium_sessions::{MemoryStore, SessionHandler};
+use trillium_smol::ClientConfig;
+
+async fn hello(conn: Conn) -> Conn {
+    let identity = conn.oidc_identity().unwrap();
+    let name = identity.name().unwrap_or(identity.subject()).to_string();
+    conn.ok(format!("hello, {name}"))
+}
+
+fn main() {
+    env_logger::init();
+    // register http://localhost:8080/oidc/callback as a redirect url
+    // with your provider, then visit http://localhost:8080
+    let env = |name| std::env::var(name).unwrap_or_else(|_| panic!("{name} must be set"));
+    trillium_smol::run((
+        trillium_logger::logger(),
+        CookiesHandler::new(),
+        SessionHandler::new(MemoryStore::new(), env("TRILLIUM_SESSION_SECRET")),
+        Oidc::new(
+            Client::new(RustlsConfig::<ClientConfig>::default()),
+            env("OIDC_ISSUER"),
+            env("OIDC_CLIENT_ID"),
+            "http://localhost:8080/oidc/callback".parse().unwrap(),
+        )
+        .with_client_secret(env("OIDC_CLIENT_SECRET")),
+        hello,
+    ));
+}
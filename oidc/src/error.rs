@@ -0,0 +1,64 @@
+use std::error::Error as StdError;
+
+/// Reasons that an openid connect login could not be completed
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// the provider's configuration could not be discovered
+    #[error("could not discover openid provider configuration: {0}")]
+    Discovery(#[source] Box<dyn StdError + Send + Sync + 'static>),
+
+    /// the authorization code could not be exchanged for tokens
+    #[error("could not exchange authorization code: {0}")]
+    TokenExchange(#[source] Box<dyn StdError + Send + Sync + 'static>),
+
+    /// the provider redirected back with an error instead of a code
+    #[error("openid provider returned {error}: {}", description.as_deref().unwrap_or("no description"))]
+    Provider {
+        /// the `error` parameter sent by the provider
+        error: String,
+        /// the `error_description` parameter sent by the provider, if any
+        description: Option<String>,
+    },
+
+    /// a callback was received without a login in progress for this session
+    #[error("no login is in progress for this session")]
+    NoLoginInProgress,
+
+    /// the `state` parameter did not match the login in progress
+    #[error("state parameter did not match")]
+    StateMismatch,
+
+    /// the callback did not include a `code` parameter
+    #[error("no authorization code was provided")]
+    MissingCode,
+
+    /// the id token could not be validated
+    #[error("invalid id token: {0}")]
+    IdToken(#[from] trillium_jwt::Error),
+
+    /// the id token's `nonce` claim did not match the login in progress
+    #[error("id token nonce did not match")]
+    NonceMismatch,
+
+    /// the id token did not include a `sub` claim
+    #[error("id token did not include a subject")]
+    MissingSubject,
+}
+
+impl Error {
+    pub(crate) fn discovery(error: impl StdError + Send + Sync + 'static) -> Self {
+        Self::Discovery(Box::new(error))
+    }
+
+    pub(crate) fn token_exchange(error: impl StdError + Send + Sync + 'static) -> Self {
+        Self::TokenExchange(Box::new(error))
+    }
+
+    /// Returns true if this error was caused by a failure to
+    /// communicate with the openid provider, as opposed to an invalid
+    /// or forged callback
+    pub fn is_provider_unavailable(&self) -> bool {
+        matches!(self, Self::Discovery(_) | Self::TokenExchange(_))
+    }
+}
@@ -0,0 +1,70 @@
+use crate::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/**
+The identity of a user who has logged in with an openid connect
+provider, built from the claims of a validated id token
+*/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OidcIdentity {
+    issuer: String,
+    subject: String,
+    claims: Map<String, Value>,
+}
+
+impl OidcIdentity {
+    pub(crate) fn from_claims(claims: Value) -> Result<Self, Error> {
+        let Value::Object(claims) = claims else {
+            return Err(Error::MissingSubject);
+        };
+
+        let subject = claims
+            .get("sub")
+            .and_then(Value::as_str)
+            .ok_or(Error::MissingSubject)?
+            .to_string();
+
+        let issuer = claims
+            .get("iss")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+
+        Ok(Self {
+            issuer,
+            subject,
+            claims,
+        })
+    }
+
+    /// the `sub` claim, which uniquely identifies this user at the issuer
+    pub fn subject(&self) -> &str {
+        &self.subject
+    }
+
+    /// the `iss` claim, which identifies the provider
+    pub fn issuer(&self) -> &str {
+        &self.issuer
+    }
+
+    /// the `email` claim, if the provider included it
+    pub fn email(&self) -> Option<&str> {
+        self.claim("email").and_then(Value::as_str)
+    }
+
+    /// the `name` claim, if the provider included it
+    pub fn name(&self) -> Option<&str> {
+        self.claim("name").and_then(Value::as_str)
+    }
+
+    /// borrow any claim of the id token by name
+    pub fn claim(&self, name: &str) -> Option<&Value> {
+        self.claims.get(name)
+    }
+
+    /// borrow all claims of the id token
+    pub fn claims(&self) -> &Map<String, Value> {
+        &self.claims
+    }
+}
@@ -0,0 +1,410 @@
+/*!
+# OpenID Connect login for trillium.rs
+
+[`Oidc`] logs users in with an openid connect provider using the
+authorization code flow with PKCE. It depends on
+[`trillium_sessions`], so it must run after a
+[`SessionHandler`](trillium_sessions::SessionHandler), which itself
+must run after a `CookiesHandler`.
+
+When a request arrives without an authenticated session, the user is
+redirected to the provider. When the provider redirects back to the
+configured redirect url, the authorization code is exchanged for an
+id token, the id token is validated against the provider's json web
+key set, and the resulting [`OidcIdentity`] is stored in the
+session. Subsequent handlers can retrieve it with
+[`OidcConnExt::oidc_identity`].
+
+```
+use trillium::Conn;
+use trillium_client::Client;
+use trillium_cookies::CookiesHandler;
+use trillium_oidc::{Oidc, OidcConnExt};
+use trillium_sessions::{MemoryStore, SessionHandler};
+
+let client = Client::new(trillium_smol::ClientConfig::default());
+let handler = (
+    CookiesHandler::new(),
+    SessionHandler::new(MemoryStore::new(), "01234567890123456789012345678901123"),
+    Oidc::new(
+        client,
+        "https://accounts.example",
+        "client-id",
+        "https://app.example/oidc/callback".parse().unwrap(),
+    )
+    .with_client_secret("client-secret"),
+    |conn: Conn| async move {
+        let subject = conn.oidc_identity().unwrap().subject().to_string();
+        conn.ok(format!("hello, {subject}"))
+    },
+);
+```
+
+## Provider discovery
+
+The provider's endpoints are discovered from
+`{issuer}/.well-known/openid-configuration` when the handler is
+initialized, and discovery is retried as requests arrive if it
+fails. To skip discovery, provide the endpoints with
+[`Oidc::with_provider_metadata`].
+
+## Login paths
+
+By default, every request without an authenticated session is
+redirected to the provider. To allow anonymous requests and only
+start a login at a specific path, use [`Oidc::with_login_path`].
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod error;
+pub use error::Error;
+
+mod identity;
+pub use identity::OidcIdentity;
+
+mod provider;
+use provider::Provider;
+pub use provider::ProviderMetadata;
+
+use async_lock::OnceCell;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+};
+use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName::Location, Status};
+use trillium_client::Client;
+use trillium_sessions::{Session, SessionConnExt};
+use url::{form_urlencoded, Url};
+
+const IDENTITY_KEY: &str = "trillium-oidc:identity";
+const LOGIN_KEY: &str = "trillium-oidc:login";
+
+/**
+Trillium handler that logs users in with an openid connect provider
+
+See crate-level docs for an explanation
+*/
+pub struct Oidc {
+    client: Client,
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_url: Url,
+    scopes: Vec<String>,
+    login_path: Option<String>,
+    metadata: Option<ProviderMetadata>,
+    provider: OnceCell<Provider>,
+    response: Box<dyn Handler>,
+}
+
+impl Debug for Oidc {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Oidc")
+            .field("client", &self.client)
+            .field("issuer", &self.issuer)
+            .field("client_id", &self.client_id)
+            .field("client_secret", &self.client_secret.as_ref().map(|_| ".."))
+            .field("redirect_url", &self.redirect_url)
+            .field("scopes", &self.scopes)
+            .field("login_path", &self.login_path)
+            .field("provider", &self.provider.get())
+            .field("response", &self.response)
+            .finish()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct LoginAttempt {
+    state: String,
+    nonce: String,
+    code_verifier: String,
+    return_to: String,
+}
+
+impl Oidc {
+    /**
+    Constructs a new Oidc handler for the provider at `issuer`.
+
+    The provided client is used for discovery, fetching keys, and
+    exchanging authorization codes. `redirect_url` must be registered
+    with the provider, and requests to its path are handled as
+    callbacks by this handler.
+    */
+    pub fn new(
+        client: Client,
+        issuer: impl Into<String>,
+        client_id: impl Into<String>,
+        redirect_url: Url,
+    ) -> Self {
+        Self {
+            client,
+            issuer: issuer.into(),
+            client_id: client_id.into(),
+            client_secret: None,
+            redirect_url,
+            scopes: vec!["openid".into(), "profile".into(), "email".into()],
+            login_path: None,
+            metadata: None,
+            provider: OnceCell::new(),
+            response: Box::new(()),
+        }
+    }
+
+    /// Authenticates with the token endpoint using this client
+    /// secret. Without a client secret, this handler acts as a public
+    /// client and relies on PKCE alone.
+    pub fn with_client_secret(mut self, client_secret: impl Into<String>) -> Self {
+        self.client_secret = Some(client_secret.into());
+        self
+    }
+
+    /// Replaces the default scopes of `openid profile email`. The
+    /// `openid` scope is always requested.
+    pub fn with_scopes(mut self, scopes: &[impl ToString]) -> Self {
+        self.scopes = vec!["openid".into()];
+        self.scopes.extend(
+            scopes
+                .iter()
+                .map(ToString::to_string)
+                .filter(|scope| scope != "openid"),
+        );
+        self
+    }
+
+    /**
+    Only start a login for requests to this path, allowing other
+    requests without an authenticated session to pass through
+    unauthenticated. After logging in, the user is redirected to `/`.
+
+    ```
+    use trillium_client::Client;
+    use trillium_oidc::Oidc;
+    let handler = Oidc::new(
+        Client::new(trillium_smol::ClientConfig::default()),
+        "https://accounts.example",
+        "client-id",
+        "https://app.example/oidc/callback".parse().unwrap(),
+    )
+    .with_login_path("/login");
+    ```
+    */
+    pub fn with_login_path(mut self, login_path: impl Into<String>) -> Self {
+        self.login_path = Some(login_path.into());
+        self
+    }
+
+    /// Use these provider endpoints instead of discovering them from
+    /// the issuer
+    pub fn with_provider_metadata(mut self, metadata: ProviderMetadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Replaces the default empty `401 Unauthorized` or `502 Bad
+    /// Gateway` response for failed logins with a custom handler. The
+    /// status will already be set when this handler is run, the reason
+    /// for failure is available with [`OidcConnExt::oidc_error`], and
+    /// the conn will be halted after it is run.
+    pub fn with_response(mut self, response: impl Handler) -> Self {
+        self.response = Box::new(response);
+        self
+    }
+
+    async fn provider(&self) -> Result<&Provider, Error> {
+        self.provider
+            .get_or_try_init(|| async {
+                let metadata = match &self.metadata {
+                    Some(metadata) => metadata.clone(),
+                    None => ProviderMetadata::discover(&self.client, &self.issuer).await?,
+                };
+                Ok(Provider::new(metadata, &self.client, &self.client_id))
+            })
+            .await
+    }
+
+    async fn login(&self, conn: Conn) -> Conn {
+        let provider = match self.provider().await {
+            Ok(provider) => provider,
+            Err(error) => return self.fail(conn, error).await,
+        };
+
+        let return_to = if self.login_path.is_some() {
+            String::from("/")
+        } else {
+            match conn.querystring() {
+                "" => conn.path().to_string(),
+                querystring => format!("{}?{querystring}", conn.path()),
+            }
+        };
+
+        let attempt = LoginAttempt {
+            state: random_token(),
+            nonce: random_token(),
+            code_verifier: random_token(),
+            return_to,
+        };
+
+        let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(&attempt.code_verifier));
+        let mut url = provider.metadata.authorization_endpoint.clone();
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", self.redirect_url.as_str())
+            .append_pair("scope", &self.scopes.join(" "))
+            .append_pair("state", &attempt.state)
+            .append_pair("nonce", &attempt.nonce)
+            .append_pair("code_challenge", &code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        conn.with_session(LOGIN_KEY, attempt)
+            .with_status(Status::Found)
+            .with_response_header(Location, url.to_string())
+            .halt()
+    }
+
+    async fn callback(&self, mut conn: Conn) -> Conn {
+        match self.complete_login(&mut conn).await {
+            Ok((identity, return_to)) => {
+                // the session is now authenticated, so a session id
+                // obtained before login must not remain valid
+                conn.regenerate_session_id();
+                conn.with_session(IDENTITY_KEY, &identity)
+                    .with_state(identity)
+                    .with_status(Status::Found)
+                    .with_response_header(Location, return_to)
+                    .halt()
+            }
+
+            Err(error) => self.fail(conn, error).await,
+        }
+    }
+
+    async fn complete_login(&self, conn: &mut Conn) -> Result<(OidcIdentity, String), Error> {
+        let attempt: LoginAttempt = conn
+            .session()
+            .get(LOGIN_KEY)
+            .ok_or(Error::NoLoginInProgress)?;
+        conn.session_mut().remove(LOGIN_KEY);
+
+        let mut params: HashMap<String, String> =
+            form_urlencoded::parse(conn.querystring().as_bytes())
+                .into_owned()
+                .collect();
+
+        if let Some(error) = params.remove("error") {
+            return Err(Error::Provider {
+                error,
+                description: params.remove("error_description"),
+            });
+        }
+
+        if params.get("state") != Some(&attempt.state) {
+            return Err(Error::StateMismatch);
+        }
+
+        let code = params.get("code").ok_or(Error::MissingCode)?;
+        let provider = self.provider().await?;
+        let id_token = provider
+            .exchange_code(
+                &self.client,
+                (&self.client_id, self.client_secret.as_deref()),
+                &self.redirect_url,
+                code,
+                &attempt.code_verifier,
+            )
+            .await?;
+
+        let claims: Value = provider.jwt.validate(&id_token).await?;
+        if claims.get("nonce").and_then(Value::as_str) != Some(&attempt.nonce) {
+            return Err(Error::NonceMismatch);
+        }
+
+        Ok((OidcIdentity::from_claims(claims)?, attempt.return_to))
+    }
+
+    async fn fail(&self, conn: Conn, error: Error) -> Conn {
+        log::warn!("oidc login failed: {error}");
+        let status = if error.is_provider_unavailable() {
+            Status::BadGateway
+        } else {
+            Status::Unauthorized
+        };
+
+        let conn = conn.with_status(status).with_state(error);
+        self.response.run(conn).await.halt()
+    }
+}
+
+fn random_token() -> String {
+    let mut bytes = [0; 32];
+    getrandom::getrandom(&mut bytes).expect("could not generate random bytes");
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[async_trait]
+impl Handler for Oidc {
+    async fn run(&self, conn: Conn) -> Conn {
+        if conn.path() == self.redirect_url.path() {
+            return self.callback(conn).await;
+        }
+
+        if let Some(identity) = conn.session().get::<OidcIdentity>(IDENTITY_KEY) {
+            return conn.with_state(identity);
+        }
+
+        match &self.login_path {
+            Some(login_path) if conn.path() != login_path => conn,
+            _ => self.login(conn).await,
+        }
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        info.require::<Session>("Oidc");
+        if let Err(e) = self.provider().await {
+            log::error!("{e}");
+        }
+        self.response.init(info).await;
+    }
+}
+
+/// Extension trait to retrieve the results of an [`Oidc`] handler
+pub trait OidcConnExt {
+    /// Returns the identity of the logged in user, if there is one
+    fn oidc_identity(&self) -> Option<&OidcIdentity>;
+
+    /// Returns the reason that an [`Oidc`] handler could not complete
+    /// a login, if it failed
+    fn oidc_error(&self) -> Option<&Error>;
+
+    /// Forgets the identity of the logged in user, removing it from
+    /// the session. This does not log the user out of the provider.
+    fn oidc_logout(&mut self);
+}
+
+impl OidcConnExt for Conn {
+    fn oidc_identity(&self) -> Option<&OidcIdentity> {
+        self.state()
+    }
+
+    fn oidc_error(&self) -> Option<&Error> {
+        self.state()
+    }
+
+    fn oidc_logout(&mut self) {
+        self.take_state::<OidcIdentity>();
+        self.session_mut().remove(IDENTITY_KEY);
+    }
+}
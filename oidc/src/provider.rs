@@ -0,0 +1,157 @@
+use crate::Error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use trillium::KnownHeaderName::{Accept, Authorization, ContentType};
+use trillium_client::Client;
+use trillium_jwt::Jwt;
+use url::{form_urlencoded::Serializer, Url};
+
+/**
+The subset of an openid provider's configuration that is needed to
+log users in, as published at
+`{issuer}/.well-known/openid-configuration`
+*/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProviderMetadata {
+    /// the issuer identifier, which must match the `iss` claim of id tokens
+    pub issuer: String,
+
+    /// the url that users are redirected to in order to log in
+    pub authorization_endpoint: Url,
+
+    /// the url that authorization codes are exchanged at
+    pub token_endpoint: Url,
+
+    /// the url of the json web key set used to sign id tokens
+    pub jwks_uri: Url,
+
+    /// the url that users can be sent to in order to log out of the
+    /// provider, if it supports rp-initiated logout
+    #[serde(default)]
+    pub end_session_endpoint: Option<Url>,
+}
+
+impl ProviderMetadata {
+    /// Builds provider metadata from its required endpoints
+    pub fn new(
+        issuer: impl Into<String>,
+        authorization_endpoint: Url,
+        token_endpoint: Url,
+        jwks_uri: Url,
+    ) -> Self {
+        Self {
+            issuer: issuer.into(),
+            authorization_endpoint,
+            token_endpoint,
+            jwks_uri,
+            end_session_endpoint: None,
+        }
+    }
+
+    /// Sets the end session endpoint
+    pub fn with_end_session_endpoint(mut self, end_session_endpoint: Url) -> Self {
+        self.end_session_endpoint = Some(end_session_endpoint);
+        self
+    }
+
+    pub(crate) async fn discover(client: &Client, issuer: &str) -> Result<Self, Error> {
+        let url = format!(
+            "{}/.well-known/openid-configuration",
+            issuer.trim_end_matches('/')
+        );
+
+        client
+            .get(url.as_str())
+            .with_request_header(Accept, "application/json")
+            .await
+            .map_err(Error::discovery)?
+            .success()
+            .map_err(Error::discovery)?
+            .response_json()
+            .await
+            .map_err(Error::discovery)
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Provider {
+    pub(crate) metadata: ProviderMetadata,
+    pub(crate) jwt: Jwt,
+}
+
+impl Provider {
+    pub(crate) fn new(metadata: ProviderMetadata, client: &Client, client_id: &str) -> Self {
+        let jwt = Jwt::from_jwks(client.clone(), metadata.jwks_uri.clone())
+            .with_audience(&[client_id])
+            .with_issuer(&[&metadata.issuer]);
+        Self { metadata, jwt }
+    }
+
+    pub(crate) async fn exchange_code(
+        &self,
+        client: &Client,
+        credentials: (&str, Option<&str>),
+        redirect_uri: &Url,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String, Error> {
+        let (client_id, client_secret) = credentials;
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+
+        let mut conn = client
+            .post(self.metadata.token_endpoint.clone())
+            .with_request_header(ContentType, "application/x-www-form-urlencoded")
+            .with_request_header(Accept, "application/json");
+
+        match client_secret {
+            Some(client_secret) => {
+                let credentials = STANDARD.encode(format!(
+                    "{}:{}",
+                    encode_credential(client_id),
+                    encode_credential(client_secret)
+                ));
+                conn = conn.with_request_header(Authorization, format!("Basic {credentials}"));
+            }
+
+            None => {
+                params.push(("client_id", client_id));
+            }
+        }
+
+        let body = Serializer::new(String::new()).extend_pairs(params).finish();
+
+        let response: TokenResponse = conn
+            .with_body(body)
+            .await
+            .map_err(Error::token_exchange)?
+            .success()
+            .map_err(Error::token_exchange)?
+            .response_json()
+            .await
+            .map_err(Error::token_exchange)?;
+
+        response.id_token.ok_or_else(|| {
+            Error::token_exchange(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "token response did not include an id_token",
+            ))
+        })
+    }
+}
+
+// client_secret_basic requires the client id and secret to be
+// form-urlencoded before they are joined and base64 encoded
+fn encode_credential(credential: &str) -> String {
+    url::form_urlencoded::byte_serialize(credential.as_bytes()).collect()
+}
@@ -0,0 +1,286 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{encode, get_current_timestamp, Algorithm, EncodingKey, Header};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use trillium::{
+    Conn, Handler, Info,
+    KnownHeaderName::{Authorization, Cookie, Location, SetCookie},
+};
+use trillium_client::Client;
+use trillium_cookies::CookiesHandler;
+use trillium_oidc::{Error, Oidc, OidcConnExt};
+use trillium_sessions::{MemoryStore, SessionHandler};
+use trillium_testing::{prelude::*, ServerConnector, TestConn};
+use url::Url;
+
+type Params = Arc<Mutex<HashMap<String, String>>>;
+
+/// a minimal openid provider that remembers the parameters of the
+/// most recent authorization request
+fn provider(authorization: Params, id_token_nonce: Option<&'static str>) -> impl Handler {
+    move |mut conn: Conn| {
+        let authorization = Arc::clone(&authorization);
+        async move {
+            match conn.path() {
+                "/.well-known/openid-configuration" => conn.ok(json!({
+                    "issuer": "http://provider.test",
+                    "authorization_endpoint": "http://provider.test/authorize",
+                    "token_endpoint": "http://provider.test/token",
+                    "jwks_uri": "http://provider.test/jwks",
+                }).to_string()),
+
+                "/jwks" => conn.ok(json!({
+                    "keys": [{ "kty": "oct", "alg": "HS256", "k": URL_SAFE_NO_PAD.encode("secret") }]
+                }).to_string()),
+
+                "/token" => {
+                    let expected_auth = format!(
+                        "Basic {}",
+                        base64::engine::general_purpose::STANDARD.encode("client-id:client-secret")
+                    );
+                    if conn.request_headers().get_str(Authorization) != Some(&expected_auth) {
+                        return conn.with_status(401);
+                    }
+
+                    let body = conn.request_body_string().await.unwrap();
+                    let params: HashMap<String, String> =
+                        url::form_urlencoded::parse(body.as_bytes()).into_owned().collect();
+                    let authorization = authorization.lock().unwrap().clone();
+                    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(&params["code_verifier"]));
+                    if params["code"] != "the-code"
+                        || params["grant_type"] != "authorization_code"
+                        || params["redirect_uri"] != authorization["redirect_uri"]
+                        || challenge != authorization["code_challenge"]
+                    {
+                        return conn.with_status(400);
+                    }
+
+                    let nonce = id_token_nonce.unwrap_or(&authorization["nonce"]);
+                    let id_token = encode(
+                        &Header::new(Algorithm::HS256),
+                        &json!({
+                            "iss": "http://provider.test",
+                            "aud": "client-id",
+                            "sub": "user-1",
+                            "email": "user@example.com",
+                            "nonce": nonce,
+                            "exp": get_current_timestamp() + 60,
+                        }),
+                        &EncodingKey::from_secret(b"secret"),
+                    )
+                    .unwrap();
+
+                    conn.ok(json!({ "id_token": id_token, "token_type": "Bearer" }).to_string())
+                }
+
+                _ => conn,
+            }
+        }
+    }
+}
+
+fn app(
+    oidc: impl Fn(Oidc) -> Oidc,
+    id_token_nonce: Option<&'static str>,
+) -> (impl Handler, Params) {
+    let authorization = Params::default();
+    let client = Client::new(ServerConnector::new(provider(
+        Arc::clone(&authorization),
+        id_token_nonce,
+    )));
+
+    let mut handler = (
+        CookiesHandler::new(),
+        SessionHandler::new(MemoryStore::new(), "01234567890123456789012345678901123"),
+        oidc(
+            Oidc::new(
+                client,
+                "http://provider.test",
+                "client-id",
+                "http://app.test/callback".parse().unwrap(),
+            )
+            .with_client_secret("client-secret"),
+        ),
+        |mut conn: Conn| async move {
+            if conn.path() == "/logout" {
+                conn.oidc_logout();
+            }
+
+            let body = match conn.oidc_identity() {
+                Some(identity) => format!("hello, {}", identity.subject()),
+                None => String::from("anonymous"),
+            };
+            conn.ok(body)
+        },
+    );
+    trillium_testing::init(&mut handler);
+    (handler, authorization)
+}
+
+fn session_cookie(conn: &TestConn) -> String {
+    let set_cookie = conn.response_headers().get_str(SetCookie).unwrap();
+    set_cookie.split(';').next().unwrap().to_string()
+}
+
+/// follows the redirect to the provider, recording the authorization
+/// request parameters as the provider would
+fn authorize(conn: &TestConn, authorization: &Params) -> HashMap<String, String> {
+    assert_status!(conn, 302);
+    let location: Url = conn
+        .response_headers()
+        .get_str(Location)
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(location.path(), "/authorize");
+    let params: HashMap<String, String> = location.query_pairs().into_owned().collect();
+    *authorization.lock().unwrap() = params.clone();
+    params
+}
+
+#[test]
+fn login_flow() {
+    let (handler, authorization) = app(|oidc| oidc, None);
+
+    let conn = get("/private?page=2").on(&handler);
+    let cookie = session_cookie(&conn);
+    let params = authorize(&conn, &authorization);
+    assert_eq!(params["response_type"], "code");
+    assert_eq!(params["client_id"], "client-id");
+    assert_eq!(params["redirect_uri"], "http://app.test/callback");
+    assert_eq!(params["scope"], "openid profile email");
+    assert_eq!(params["code_challenge_method"], "S256");
+
+    let conn = get(format!("/callback?code=the-code&state={}", params["state"]))
+        .with_request_header(Cookie, cookie.clone())
+        .on(&handler);
+    assert_status!(&conn, 302);
+    assert_headers!(&conn, "location" => "/private?page=2");
+    let identity = conn.oidc_identity().unwrap();
+    assert_eq!(identity.subject(), "user-1");
+    assert_eq!(identity.issuer(), "http://provider.test");
+    assert_eq!(identity.email(), Some("user@example.com"));
+
+    // the session id is rotated on login, so the pre-login cookie is not authenticated
+    let authenticated_cookie = session_cookie(&conn);
+    assert_ne!(authenticated_cookie, cookie);
+    assert_ok!(
+        get("/private")
+            .with_request_header(Cookie, authenticated_cookie.clone())
+            .on(&handler),
+        "hello, user-1"
+    );
+    assert_status!(
+        get("/private")
+            .with_request_header(Cookie, cookie)
+            .on(&handler),
+        302
+    );
+
+    // the login attempt is consumed by the first callback
+    let conn = get(format!("/callback?code=the-code&state={}", params["state"]))
+        .with_request_header(Cookie, authenticated_cookie)
+        .on(&handler);
+    assert_status!(&conn, 401);
+    assert!(matches!(conn.oidc_error(), Some(Error::NoLoginInProgress)));
+}
+
+#[test]
+fn invalid_callbacks() {
+    let (handler, authorization) = app(|oidc| oidc, None);
+
+    let conn = get("/callback?code=the-code&state=anything").on(&handler);
+    assert_status!(&conn, 401);
+    assert!(matches!(conn.oidc_error(), Some(Error::NoLoginInProgress)));
+
+    let conn = get("/").on(&handler);
+    let cookie = session_cookie(&conn);
+    authorize(&conn, &authorization);
+    let conn = get("/callback?code=the-code&state=forged")
+        .with_request_header(Cookie, cookie)
+        .on(&handler);
+    assert_status!(&conn, 401);
+    assert!(matches!(conn.oidc_error(), Some(Error::StateMismatch)));
+
+    let conn = get("/").on(&handler);
+    let cookie = session_cookie(&conn);
+    let params = authorize(&conn, &authorization);
+    let conn = get(format!(
+        "/callback?error=access_denied&error_description=nope&state={}",
+        params["state"]
+    ))
+    .with_request_header(Cookie, cookie)
+    .on(&handler);
+    assert_status!(&conn, 401);
+    assert!(matches!(
+        conn.oidc_error(),
+        Some(Error::Provider { error, description: Some(description) })
+            if error == "access_denied" && description == "nope"
+    ));
+}
+
+#[test]
+fn nonce_mismatch() {
+    let (handler, authorization) = app(|oidc| oidc, Some("replayed"));
+
+    let conn = get("/").on(&handler);
+    let cookie = session_cookie(&conn);
+    let params = authorize(&conn, &authorization);
+    let conn = get(format!("/callback?code=the-code&state={}", params["state"]))
+        .with_request_header(Cookie, cookie)
+        .on(&handler);
+    assert_status!(&conn, 401);
+    assert!(matches!(conn.oidc_error(), Some(Error::NonceMismatch)));
+    assert!(conn.oidc_identity().is_none());
+}
+
+#[test]
+fn login_path_and_logout() {
+    let (handler, authorization) = app(|oidc| oidc.with_login_path("/login"), None);
+    assert_ok!(get("/").on(&handler), "anonymous");
+
+    let conn = get("/login").on(&handler);
+    let cookie = session_cookie(&conn);
+    let params = authorize(&conn, &authorization);
+    let conn = get(format!("/callback?code=the-code&state={}", params["state"]))
+        .with_request_header(Cookie, cookie)
+        .on(&handler);
+    assert_headers!(&conn, "location" => "/");
+    let cookie = session_cookie(&conn);
+
+    assert_ok!(
+        get("/")
+            .with_request_header(Cookie, cookie.clone())
+            .on(&handler),
+        "hello, user-1"
+    );
+
+    assert_ok!(
+        get("/logout")
+            .with_request_header(Cookie, cookie.clone())
+            .on(&handler),
+        "anonymous"
+    );
+    assert_ok!(
+        get("/").with_request_header(Cookie, cookie).on(&handler),
+        "anonymous"
+    );
+}
+
+#[test]
+fn requires_sessions() {
+    let client = Client::new(ServerConnector::new(provider(Params::default(), None)));
+    let mut oidc = Oidc::new(
+        client,
+        "http://provider.test",
+        "client-id",
+        "http://app.test/callback".parse().unwrap(),
+    );
+    let mut info = Info::default();
+    trillium_testing::block_on(oidc.init(&mut info));
+    assert_eq!(info.missing_dependencies()[0].handler(), "Oidc");
+}
@@ -0,0 +1,20 @@
+use trillium::Conn;
+use trillium_prometheus::prometheus;
+use trillium_router::{router, RouterConnExt};
+
+fn main() {
+    let prometheus = prometheus().with_namespace("example");
+
+    // try `curl http://localhost:8080/hello/trillium` and then
+    // `curl http://localhost:8080/metrics`
+    trillium_smol::run((
+        prometheus.clone(),
+        router().get("/metrics", prometheus.endpoint()).get(
+            "/hello/:name",
+            |conn: Conn| async move {
+                let name = conn.param("name").unwrap_or("world").to_string();
+                conn.ok(format!("hello, {name}"))
+            },
+        ),
+    ));
+}
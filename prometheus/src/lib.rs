@@ -0,0 +1,254 @@
+/*!
+Prometheus metrics for trillium applications.
+
+[`Prometheus`] is a handler that records http server metrics in
+process, and [`MetricsEndpoint`] renders them in the prometheus text
+exposition format for a prometheus server to scrape. No metrics
+collector other than prometheus itself is needed.
+
+The following metrics are recorded, each labeled with the request
+`method`, the `route`, and the response `status`:
+
+* `http_server_requests_total`, a counter of requests
+* `http_server_request_duration_seconds`, a histogram of the time
+  from receiving each request until its response is sent
+* `http_server_response_size_bytes`, a histogram of the size of each
+  response body that has a known length
+
+as well as `http_server_requests_in_flight`, an unlabeled gauge of the
+requests currently being handled.
+
+The `route` label is the route specification matched by
+trillium-router, such as `/users/:id`, when the `router` crate feature
+is enabled, as it is by default. Requests that were not routed have
+an empty route. The request path is never used as a label, as it would
+create a separate series for every distinct path. A different route
+label can be provided with [`Prometheus::with_route`].
+
+Place [`Prometheus`] early in the application, so that it sees the
+final status of every response, and mount the endpoint with
+trillium-router:
+
+```
+use trillium_prometheus::Prometheus;
+use trillium_router::router;
+
+let prometheus = Prometheus::new();
+let app = (
+    prometheus.clone(),
+    router()
+        .get("/metrics", prometheus.endpoint())
+        .get("/users/:id", "user"),
+);
+```
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod registry;
+
+use registry::{Labels, Registry};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+    sync::{atomic::Ordering, Arc},
+};
+use trillium::{async_trait, Conn, Handler, KnownHeaderName, Method, Status};
+
+type RouteFn = dyn Fn(&Conn) -> Option<Cow<'_, str>> + Send + Sync + 'static;
+
+/**
+The metrics recording handler. See the [crate-level docs](crate) for
+details.
+
+Clones of this handler and the [`MetricsEndpoint`]s built from it share
+their metrics, so the namespace and buckets must be configured before
+it is cloned or an endpoint is built.
+*/
+#[derive(Clone)]
+pub struct Prometheus {
+    registry: Arc<Registry>,
+    route: Arc<RouteFn>,
+}
+
+impl Debug for Prometheus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Prometheus")
+            .field("registry", &self.registry)
+            .field("route", &"..")
+            .finish()
+    }
+}
+
+impl Default for Prometheus {
+    fn default() -> Self {
+        Self {
+            registry: Arc::new(Registry::default()),
+            route: Arc::new(default_route),
+        }
+    }
+}
+
+#[cfg(feature = "router")]
+fn default_route(conn: &Conn) -> Option<Cow<'_, str>> {
+    trillium_router::RouterConnExt::route(conn).map(Cow::Borrowed)
+}
+
+#[cfg(not(feature = "router"))]
+fn default_route(_conn: &Conn) -> Option<Cow<'_, str>> {
+    None
+}
+
+impl Prometheus {
+    /// Builds a new Prometheus handler with no recorded metrics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn registry_mut(&mut self) -> &mut Registry {
+        Arc::get_mut(&mut self.registry)
+            .expect("Prometheus must be configured before it is cloned or an endpoint is built")
+    }
+
+    /// Prefixes the name of every metric with this namespace and an
+    /// underscore, such as `myapp_http_server_requests_total`
+    ///
+    /// # Panics
+    ///
+    /// Panics if this handler has already been cloned or an endpoint
+    /// has been built
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.registry_mut().namespace = Some(namespace.into());
+        self
+    }
+
+    /// Sets the upper bounds of the request duration histogram buckets,
+    /// in seconds. The default buckets range from 5ms to 10s.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this handler has already been cloned or an endpoint
+    /// has been built
+    pub fn with_duration_buckets(mut self, buckets: impl IntoIterator<Item = f64>) -> Self {
+        self.registry_mut().duration_buckets = sorted(buckets);
+        self
+    }
+
+    /// Sets the upper bounds of the response size histogram buckets,
+    /// in bytes. The default buckets range from 100 bytes to 10mb.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this handler has already been cloned or an endpoint
+    /// has been built
+    pub fn with_response_size_buckets(mut self, buckets: impl IntoIterator<Item = f64>) -> Self {
+        self.registry_mut().response_size_buckets = sorted(buckets);
+        self
+    }
+
+    /// Determines the `route` label from the conn with this function
+    /// instead of the trillium-router route. The route should have a
+    /// small number of distinct values. Conns for which it returns
+    /// `None` have an empty route.
+    pub fn with_route(
+        mut self,
+        route: impl Fn(&Conn) -> Option<Cow<'_, str>> + Send + Sync + 'static,
+    ) -> Self {
+        self.route = Arc::new(route);
+        self
+    }
+
+    /// Builds a [`MetricsEndpoint`] that renders the metrics recorded
+    /// by this handler and its clones
+    pub fn endpoint(&self) -> MetricsEndpoint {
+        MetricsEndpoint(Arc::clone(&self.registry))
+    }
+
+    /// The current metrics in the prometheus text exposition format
+    pub fn render(&self) -> String {
+        self.registry.render()
+    }
+}
+
+fn sorted(buckets: impl IntoIterator<Item = f64>) -> Vec<f64> {
+    let mut buckets = buckets
+        .into_iter()
+        .filter(|bucket| bucket.is_finite())
+        .collect::<Vec<_>>();
+    buckets.sort_by(f64::total_cmp);
+    buckets.dedup();
+    buckets
+}
+
+// decrements the in-flight gauge when the conn's response is sent or
+// the conn is dropped
+struct InFlight(Arc<Registry>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[async_trait]
+impl Handler for Prometheus {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        self.registry.in_flight.fetch_add(1, Ordering::Relaxed);
+        conn.insert_state(InFlight(Arc::clone(&self.registry)));
+        conn
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        if conn.take_state::<InFlight>().is_none() {
+            return conn;
+        }
+
+        let labels = Labels {
+            method: conn.method().as_str(),
+            route: (self.route)(&conn).map(Cow::into_owned).unwrap_or_default(),
+            status: conn.status().unwrap_or(Status::NotFound) as u16,
+        };
+        let duration = conn.start_time().elapsed().as_secs_f64();
+        self.registry.record(labels, duration, conn.response_len());
+        conn
+    }
+}
+
+/// Convenience alias for [`Prometheus::new`]
+pub fn prometheus() -> Prometheus {
+    Prometheus::new()
+}
+
+/**
+A handler that responds to `GET` requests with the metrics recorded by
+a [`Prometheus`] handler, in the prometheus text exposition format.
+Build one with [`Prometheus::endpoint`].
+
+This handler does not perform any authentication or routing of its
+own, so it is usually mounted at `/metrics` with trillium-router.
+*/
+#[derive(Clone, Debug)]
+pub struct MetricsEndpoint(Arc<Registry>);
+
+#[async_trait]
+impl Handler for MetricsEndpoint {
+    async fn run(&self, conn: Conn) -> Conn {
+        if conn.method() != Method::Get {
+            return conn;
+        }
+
+        conn.with_response_header(
+            KnownHeaderName::ContentType,
+            "text/plain; version=0.0.4; charset=utf-8",
+        )
+        .with_response_header(KnownHeaderName::CacheControl, "no-store")
+        .ok(self.0.render())
+    }
+}
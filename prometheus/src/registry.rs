@@ -0,0 +1,218 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicI64, Ordering},
+        Mutex,
+    },
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Labels {
+    pub(crate) method: &'static str,
+    pub(crate) route: String,
+    pub(crate) status: u16,
+}
+
+#[derive(Debug)]
+struct Histogram {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new(len: usize) -> Self {
+        Self {
+            buckets: vec![0; len],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(&mut self, bounds: &[f64], value: f64) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(bounds) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug)]
+struct Series {
+    duration: Histogram,
+    response_size: Histogram,
+}
+
+#[derive(Debug)]
+pub(crate) struct Registry {
+    pub(crate) namespace: Option<String>,
+    pub(crate) duration_buckets: Vec<f64>,
+    pub(crate) response_size_buckets: Vec<f64>,
+    pub(crate) in_flight: AtomicI64,
+    series: Mutex<BTreeMap<Labels, Series>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self {
+            namespace: None,
+            duration_buckets: vec![
+                0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+            ],
+            response_size_buckets: vec![
+                100.0,
+                1_000.0,
+                10_000.0,
+                100_000.0,
+                1_000_000.0,
+                10_000_000.0,
+            ],
+            in_flight: AtomicI64::new(0),
+            series: Mutex::new(BTreeMap::new()),
+        }
+    }
+}
+
+impl Registry {
+    pub(crate) fn record(&self, labels: Labels, duration: f64, response_size: Option<u64>) {
+        let mut series = self.series.lock().unwrap();
+        let series = series.entry(labels).or_insert_with(|| Series {
+            duration: Histogram::new(self.duration_buckets.len()),
+            response_size: Histogram::new(self.response_size_buckets.len()),
+        });
+        series.duration.observe(&self.duration_buckets, duration);
+        if let Some(response_size) = response_size {
+            series
+                .response_size
+                .observe(&self.response_size_buckets, response_size as f64);
+        }
+    }
+
+    fn name(&self, name: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{namespace}_{name}"),
+            None => name.to_string(),
+        }
+    }
+
+    pub(crate) fn render(&self) -> String {
+        let series = self.series.lock().unwrap();
+        let mut output = String::new();
+
+        let requests = self.name("http_server_requests_total");
+        header(
+            &mut output,
+            &requests,
+            "counter",
+            "Total number of http requests handled.",
+        );
+        for (labels, series) in series.iter() {
+            let _ = writeln!(
+                output,
+                "{requests}{{{}}} {}",
+                label_string(labels),
+                series.duration.count
+            );
+        }
+
+        let duration = self.name("http_server_request_duration_seconds");
+        header(
+            &mut output,
+            &duration,
+            "histogram",
+            "Time from receiving an http request until its response is sent, in seconds.",
+        );
+        for (labels, series) in series.iter() {
+            render_histogram(
+                &mut output,
+                &duration,
+                labels,
+                &self.duration_buckets,
+                &series.duration,
+            );
+        }
+
+        let response_size = self.name("http_server_response_size_bytes");
+        header(
+            &mut output,
+            &response_size,
+            "histogram",
+            "Size of http response bodies with a known length, in bytes.",
+        );
+        for (labels, series) in series.iter() {
+            render_histogram(
+                &mut output,
+                &response_size,
+                labels,
+                &self.response_size_buckets,
+                &series.response_size,
+            );
+        }
+
+        let in_flight = self.name("http_server_requests_in_flight");
+        header(
+            &mut output,
+            &in_flight,
+            "gauge",
+            "Number of http requests currently being handled.",
+        );
+        let _ = writeln!(
+            output,
+            "{in_flight} {}",
+            self.in_flight.load(Ordering::Relaxed)
+        );
+
+        output
+    }
+}
+
+fn header(output: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(output, "# HELP {name} {help}");
+    let _ = writeln!(output, "# TYPE {name} {kind}");
+}
+
+fn render_histogram(
+    output: &mut String,
+    name: &str,
+    labels: &Labels,
+    bounds: &[f64],
+    histogram: &Histogram,
+) {
+    let labels = label_string(labels);
+    for (bound, count) in bounds.iter().zip(&histogram.buckets) {
+        let _ = writeln!(output, "{name}_bucket{{{labels},le=\"{bound}\"}} {count}");
+    }
+    let _ = writeln!(
+        output,
+        "{name}_bucket{{{labels},le=\"+Inf\"}} {}",
+        histogram.count
+    );
+    let _ = writeln!(output, "{name}_sum{{{labels}}} {}", histogram.sum);
+    let _ = writeln!(output, "{name}_count{{{labels}}} {}", histogram.count);
+}
+
+fn label_string(labels: &Labels) -> String {
+    format!(
+        "method=\"{}\",route=\"{}\",status=\"{}\"",
+        labels.method,
+        escape(&labels.route),
+        labels.status
+    )
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,78 @@
+use std::borrow::Cow;
+use trillium::Conn;
+use trillium_prometheus::prometheus;
+use trillium_router::router;
+use trillium_testing::prelude::*;
+
+#[test]
+fn records_requests_by_route() {
+    let prometheus = prometheus();
+    let handler = (
+        prometheus.clone(),
+        router()
+            .get("/metrics", prometheus.endpoint())
+            .get("/users/:id", "user")
+            .get("/fail", |conn: Conn| async move { conn.with_status(500) }),
+    );
+
+    assert_ok!(get("/users/1").on(&handler), "user");
+    assert_ok!(get("/users/2").on(&handler), "user");
+    assert_status!(get("/fail").on(&handler), 500);
+    assert_not_handled!(get("/missing").on(&handler));
+
+    let mut conn = get("/metrics").on(&handler);
+    assert_headers!(&conn, "content-type" => "text/plain; version=0.0.4; charset=utf-8");
+    let body = conn.take_response_body_string().unwrap();
+
+    assert!(body.contains("# TYPE http_server_requests_total counter\n"));
+    assert!(body.contains(
+        "http_server_requests_total{method=\"GET\",route=\"/users/:id\",status=\"200\"} 2\n"
+    ));
+    assert!(body
+        .contains("http_server_requests_total{method=\"GET\",route=\"/fail\",status=\"500\"} 1\n"));
+    assert!(
+        body.contains("http_server_requests_total{method=\"GET\",route=\"\",status=\"404\"} 1\n")
+    );
+    assert!(body.contains(
+        "http_server_request_duration_seconds_bucket{method=\"GET\",route=\"/users/:id\",status=\"200\",le=\"+Inf\"} 2\n"
+    ));
+    assert!(body.contains(
+        "http_server_response_size_bytes_bucket{method=\"GET\",route=\"/users/:id\",status=\"200\",le=\"100\"} 2\n"
+    ));
+    assert!(body.contains(
+        "http_server_response_size_bytes_sum{method=\"GET\",route=\"/users/:id\",status=\"200\"} 8\n"
+    ));
+    assert!(body.contains("http_server_requests_in_flight 1\n"));
+    assert!(!body.contains("/users/1"));
+
+    assert!(prometheus
+        .render()
+        .contains("http_server_requests_in_flight 0\n"));
+}
+
+#[test]
+fn namespace_buckets_and_custom_route() {
+    let prometheus = prometheus()
+        .with_namespace("app")
+        .with_duration_buckets([1.0, 0.5])
+        .with_route(|conn| {
+            Some(Cow::Owned(
+                conn.path().split('/').take(2).collect::<Vec<_>>().join("/"),
+            ))
+        });
+    let handler = (prometheus.clone(), "ok");
+
+    assert_ok!(get("/a/b").on(&handler), "ok");
+    let body = prometheus.render();
+    assert!(body.contains(
+        "app_http_server_requests_total{method=\"GET\",route=\"/a\",status=\"200\"} 1\n"
+    ));
+    let first = body.find("le=\"0.5\"").unwrap();
+    let second = body.find("le=\"1\"").unwrap();
+    assert!(first < second);
+}
+
+#[test]
+fn endpoint_only_handles_get() {
+    assert_not_handled!(post("/").on(&prometheus().endpoint()));
+}
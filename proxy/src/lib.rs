@@ -17,12 +17,13 @@ http reverse and forward proxy trillium handler
 mod body_streamer;
 mod forward_proxy_connect;
 pub mod upstream;
+pub mod upstream_auth;
 
 use body_streamer::stream_body;
 use full_duplex_async_copy::full_duplex_copy;
 use futures_lite::future::zip;
 use size::{Base, Size};
-use std::{borrow::Cow, fmt::Debug, future::IntoFuture};
+use std::{borrow::Cow, collections::HashMap, fmt::Debug, future::IntoFuture};
 use trillium::{
     async_trait, Conn, Handler, KnownHeaderName,
     Status::{NotFound, SwitchingProtocols},
@@ -31,6 +32,8 @@ use trillium::{
 use trillium_forwarding::Forwarded;
 use trillium_http::{HeaderName, HeaderValue, Headers, Status, Version};
 use upstream::{IntoUpstreamSelector, UpstreamSelector};
+use upstream_auth::UpstreamAuth;
+use url::Origin;
 
 pub use forward_proxy_connect::ForwardProxyConnect;
 pub use trillium_client::{Client, Connector};
@@ -44,6 +47,39 @@ where
     Proxy::new(client, upstream)
 }
 
+/// constructs a new [`Proxy`] for a single [`trillium_router`] route,
+/// sending requests to an upstream built from the route's params. See
+/// [`RouteTemplate`](upstream::RouteTemplate) for the template syntax.
+///
+/// ```
+/// use trillium::Conn;
+/// use trillium_client::Client;
+/// use trillium_proxy::proxy_route;
+/// use trillium_router::router;
+/// use trillium_testing::prelude::*;
+///
+/// let upstream = |conn: Conn| async move {
+///     let host = conn.inner().host().unwrap_or_default().to_string();
+///     let path = conn.inner().path_and_query().to_string();
+///     conn.ok(format!("{host} {path}"))
+/// };
+/// let client = Client::new(trillium_testing::connector(upstream));
+///
+/// let app = router()
+///     .get("/api/:service/*", proxy_route(client.clone(), "http://{service}.internal/{*}"))
+///     .get("/users/:id", proxy_route(client, "http://users.internal/v2/users/{id}"));
+///
+/// assert_ok!(
+///     get("/api/billing/invoices/10?expand=lines").on(&app),
+///     "billing.internal /invoices/10?expand=lines"
+/// );
+/// assert_ok!(get("/users/10").on(&app), "users.internal /v2/users/10");
+/// ```
+#[cfg(feature = "router")]
+pub fn proxy_route(client: impl Into<Client>, template: &str) -> Proxy<upstream::RouteTemplate> {
+    Proxy::new(client, upstream::RouteTemplate::new(template))
+}
+
 /**
 the proxy handler
 */
@@ -55,6 +91,9 @@ pub struct Proxy<U> {
     halt: bool,
     via_pseudonym: Option<Cow<'static, str>>,
     allow_websocket_upgrade: bool,
+    upstream_auth: Option<UpstreamAuth>,
+    upstream_auth_by_origin: HashMap<Origin, UpstreamAuth>,
+    body_buffer_limit: Option<u64>,
 }
 
 impl<U: UpstreamSelector> Proxy<U> {
@@ -81,6 +120,9 @@ impl<U: UpstreamSelector> Proxy<U> {
             halt: true,
             via_pseudonym: None,
             allow_websocket_upgrade: false,
+            upstream_auth: None,
+            upstream_auth_by_origin: HashMap::new(),
+            body_buffer_limit: None,
         }
     }
 
@@ -144,6 +186,87 @@ impl<U: UpstreamSelector> Proxy<U> {
         self
     }
 
+    /// attach these credentials to requests to any upstream that does
+    /// not have credentials configured with
+    /// [`Proxy::with_upstream_auth_for`]. See [`UpstreamAuth`] for
+    /// examples.
+    pub fn with_upstream_auth(mut self, upstream_auth: UpstreamAuth) -> Self {
+        self.upstream_auth = Some(upstream_auth);
+        self
+    }
+
+    /**
+    attach these credentials to requests to upstreams with the same
+    origin (scheme, host, and port) as the provided url
+
+    ```
+    use trillium::Conn;
+    use trillium_client::Client;
+    use trillium_proxy::{upstream::RoundRobin, upstream_auth::UpstreamAuth, Proxy};
+    use trillium_testing::prelude::*;
+
+    let upstream = |conn: Conn| async move {
+        let host = conn.inner().host().unwrap_or_default().to_string();
+        let authorization = conn.request_headers().get_str("authorization").unwrap_or_default().to_string();
+        conn.ok(format!("{host}: {authorization}"))
+    };
+    let client = Client::new(trillium_testing::connector(upstream));
+
+    let proxy = Proxy::new(client, RoundRobin::new(["http://billing.internal", "http://users.internal"]))
+        .with_upstream_auth_for("http://billing.internal", UpstreamAuth::bearer("billing-token"))
+        .with_upstream_auth_for("http://users.internal", UpstreamAuth::bearer("users-token"));
+
+    assert_ok!(get("/").on(&proxy), "billing.internal: Bearer billing-token");
+    assert_ok!(get("/").on(&proxy), "users.internal: Bearer users-token");
+    ```
+
+    # Panics
+
+    This will panic if the upstream is not a valid url
+    */
+    pub fn with_upstream_auth_for(mut self, upstream: &str, upstream_auth: UpstreamAuth) -> Self {
+        let origin = Url::parse(upstream)
+            .expect("could not parse upstream url")
+            .origin();
+        self.upstream_auth_by_origin.insert(origin, upstream_auth);
+        self
+    }
+
+    /**
+    buffer request bodies with a content-length of at most this many
+    bytes in memory instead of streaming them to the upstream, and
+    retry requests with a buffered body or no body once against a
+    newly selected upstream if the first upstream request fails.
+
+    Chunked request bodies and bodies larger than this limit are
+    streamed as usual, and are never retried. By default, no request
+    bodies are buffered and no requests are retried.
+
+    Note that a failed upstream request may have been received by the
+    upstream before the failure, so this is only appropriate if
+    upstream requests are safe to repeat.
+
+    ```
+    # use trillium_smol::ClientConfig;
+    # use trillium_proxy::{upstream::RoundRobin, Proxy};
+    let proxy = Proxy::new(
+        ClientConfig::default(),
+        RoundRobin::new(["http://primary.internal", "http://secondary.internal"]),
+    )
+    .with_body_buffer_limit(64 * 1024);
+    ```
+    */
+    pub fn with_body_buffer_limit(mut self, bytes: u64) -> Self {
+        self.body_buffer_limit = Some(bytes);
+        self
+    }
+
+    fn upstream_auth(&self, url: &Url) -> Option<&UpstreamAuth> {
+        self.upstream_auth_by_origin
+            .get(&url.origin())
+            .or(self.upstream_auth.as_ref())
+    }
+
     fn set_via_pseudonym(&self, headers: &mut Headers, version: Version) {
         if let Some(via) = &self.via_pseudonym {
             let via = match headers.get_values(KnownHeaderName::Via) {
@@ -242,44 +365,107 @@ impl<U: UpstreamSelector> Handler for Proxy<U> {
         }
 
         self.set_via_pseudonym(&mut request_headers, conn.inner().http_version());
-        let content_length = !matches!(
-            conn.request_headers()
-                .get_str(KnownHeaderName::ContentLength),
-            Some("0") | None
-        );
+        let content_length = conn
+            .request_headers()
+            .get_str(KnownHeaderName::ContentLength);
 
         let chunked = conn
             .request_headers()
             .eq_ignore_ascii_case(KnownHeaderName::TransferEncoding, "chunked");
+        let has_body = chunked || !matches!(content_length, Some("0") | None);
+        let content_length = content_length.and_then(|s| s.parse::<u64>().ok());
+
+        let buffered_body = match (self.body_buffer_limit, content_length) {
+            (Some(limit), Some(len)) if has_body && !chunked && len <= limit => {
+                match conn
+                    .request_body()
+                    .await
+                    .with_max_len(limit)
+                    .read_bytes()
+                    .await
+                {
+                    Ok(body) => Some(body),
+                    Err(e) => {
+                        log::error!("could not buffer request body: {e}");
+                        return conn.with_status(Status::BadRequest).halt();
+                    }
+                }
+            }
+            _ => None,
+        };
+
+        let mut retries =
+            usize::from(self.body_buffer_limit.is_some() && (buffered_body.is_some() || !has_body));
+        let mut request_url = request_url;
         let method = conn.method();
-        let conn_result = if chunked || content_length {
-            let (body_fut, request_body) = stream_body(&mut conn);
 
-            let client_fut = self
-                .client
-                .build_conn(method, request_url)
-                .with_request_headers(request_headers)
-                .with_body(request_body)
-                .into_future();
+        let (mut client_conn, upstream_auth) = loop {
+            let mut request_headers = request_headers.clone();
+            let upstream_auth = self.upstream_auth(&request_url);
+            if let Some(upstream_auth) = upstream_auth {
+                match upstream_auth.authorization().await {
+                    Ok(authorization) => {
+                        request_headers.insert(KnownHeaderName::Authorization, authorization);
+                    }
+
+                    Err(e) => {
+                        log::error!(
+                            "could not fetch credentials for {}: {e}",
+                            request_url.origin().ascii_serialization()
+                        );
+                        return conn.with_status(Status::ServiceUnavailable).halt();
+                    }
+                }
+            }
 
-            zip(body_fut, client_fut).await.1
-        } else {
-            self.client
-                .build_conn(method, request_url)
-                .with_request_headers(request_headers)
-                .await
-        };
+            let client_conn = self
+                .client
+                .build_conn(method, request_url.clone())
+                .with_request_headers(request_headers);
+
+            let conn_result = if let Some(body) = &buffered_body {
+                client_conn.with_body(body.clone()).await
+            } else if has_body {
+                let (body_fut, request_body) = stream_body(&mut conn);
+                zip(body_fut, client_conn.with_body(request_body).into_future())
+                    .await
+                    .1
+            } else {
+                client_conn.await
+            };
 
-        let mut client_conn = match conn_result {
-            Ok(client_conn) => client_conn,
-            Err(e) => {
-                return conn
-                    .with_status(Status::ServiceUnavailable)
-                    .halt()
-                    .with_state(e);
+            match conn_result {
+                Ok(client_conn) => break (client_conn, upstream_auth),
+
+                Err(e) if retries > 0 => {
+                    retries -= 1;
+                    log::warn!("upstream request to {request_url} failed, retrying: {e}");
+                    match self.upstream.determine_upstream(&mut conn) {
+                        Some(url) => request_url = url,
+                        None => {
+                            return conn
+                                .with_status(Status::ServiceUnavailable)
+                                .halt()
+                                .with_state(e);
+                        }
+                    }
+                }
+
+                Err(e) => {
+                    return conn
+                        .with_status(Status::ServiceUnavailable)
+                        .halt()
+                        .with_state(e);
+                }
             }
         };
 
+        if let (Some(upstream_auth), Some(Status::Unauthorized)) =
+            (upstream_auth, client_conn.status())
+        {
+            upstream_auth.invalidate().await;
+        }
+
         let mut conn = match client_conn.status() {
             Some(SwitchingProtocols) => {
                 conn.response_headers_mut()
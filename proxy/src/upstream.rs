@@ -8,12 +8,16 @@ mod connection_counting;
 #[cfg(feature = "upstream-random")]
 mod random;
 mod round_robin;
+#[cfg(feature = "router")]
+mod route_template;
 
 #[cfg(feature = "upstream-connection-counting")]
 pub use connection_counting::ConnectionCounting;
 #[cfg(feature = "upstream-random")]
 pub use random::RandomSelector;
 pub use round_robin::RoundRobin;
+#[cfg(feature = "router")]
+pub use route_template::RouteTemplate;
 
 /// a trait for selecting the correct upstream
 pub trait UpstreamSelector: Debug + Send + Sync + 'static {
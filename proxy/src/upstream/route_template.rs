@@ -0,0 +1,132 @@
+use super::UpstreamSelector;
+use std::fmt::Write;
+use trillium::Conn;
+use trillium_router::RouterConnExt;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Wildcard,
+}
+
+/**
+an upstream selector that builds the upstream url from the params of
+the matched [`trillium_router`] route
+
+The template is written as a url with `{name}` placeholders for route
+params and `{*}` for the route's wildcard. Literal braces are written
+as `{{` and `}}`. The request's query string is appended to the
+upstream url. If a placeholder has no value for the current route, or
+the expanded template is not a valid url, the conn is passed along
+without proxying.
+
+Param values are substituted with any characters other than ascii
+alphanumerics, `-`, `.`, `_`, `~`, and `%` percent-encoded, so a param
+cannot change the structure of the upstream url. The wildcard is
+substituted as-is, and may contain slashes.
+
+```
+use trillium_proxy::upstream::RouteTemplate;
+let upstream = RouteTemplate::new("http://{service}.internal/{*}");
+```
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RouteTemplate(Vec<Segment>);
+
+impl RouteTemplate {
+    /**
+    construct a new route template
+
+    # Panics
+
+    This will panic if the template has an unmatched brace
+    */
+    pub fn new(template: &str) -> Self {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+
+                '{' => {
+                    let rest = chars.as_str();
+                    let Some(end) = rest.find('}') else {
+                        panic!("unmatched {{ in route template {template}");
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(match &rest[..end] {
+                        "*" => Segment::Wildcard,
+                        name => Segment::Param(name.to_string()),
+                    });
+                    chars = rest[end + 1..].chars();
+                }
+
+                '}' => panic!("unmatched }} in route template {template}"),
+
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Self(segments)
+    }
+
+    fn expand(&self, conn: &Conn) -> Option<String> {
+        let mut expanded = String::new();
+        for segment in &self.0 {
+            match segment {
+                Segment::Literal(literal) => expanded.push_str(literal),
+                Segment::Param(name) => encode(conn.param(name)?, &mut expanded),
+                Segment::Wildcard => expanded.push_str(conn.wildcard()?),
+            }
+        }
+        Some(expanded)
+    }
+}
+
+fn encode(value: &str, out: &mut String) {
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~' | b'%') {
+            out.push(char::from(byte));
+        } else {
+            write!(out, "%{byte:02X}").unwrap();
+        }
+    }
+}
+
+impl UpstreamSelector for RouteTemplate {
+    fn determine_upstream(&self, conn: &mut Conn) -> Option<Url> {
+        let mut url = Url::parse(&self.expand(conn)?).ok()?;
+        let querystring = conn.querystring();
+        if !querystring.is_empty() {
+            let query = match url.query() {
+                Some(query) if !query.is_empty() => format!("{query}&{querystring}"),
+                _ => querystring.to_string(),
+            };
+            url.set_query(Some(&query));
+        }
+        Some(url)
+    }
+}
+
+impl From<&str> for RouteTemplate {
+    fn from(template: &str) -> Self {
+        Self::new(template)
+    }
+}
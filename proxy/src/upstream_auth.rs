@@ -0,0 +1,226 @@
+//! Credentials for upstream requests
+use async_lock::RwLock;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::{
+    error::Error,
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    time::{Duration, Instant},
+};
+use trillium::async_trait;
+
+/**
+credentials that a [`Proxy`](crate::Proxy) attaches to requests to an
+upstream as an `Authorization` header
+
+Any `Authorization` header sent by the client is replaced, and the
+credentials are never included in the response to the client.
+
+```
+use trillium::Conn;
+use trillium_client::Client;
+use trillium_proxy::{upstream_auth::UpstreamAuth, Proxy};
+use trillium_testing::prelude::*;
+
+let upstream = |conn: Conn| async move {
+    let authorization = conn.request_headers().get_str("authorization").unwrap_or_default().to_string();
+    conn.ok(authorization)
+};
+let client = Client::new(trillium_testing::connector(upstream));
+
+let proxy = Proxy::new(client.clone(), "http://internal.example")
+    .with_upstream_auth(UpstreamAuth::bearer("internal-token"));
+assert_ok!(
+    get("/").with_request_header("authorization", "Bearer client").on(&proxy),
+    "Bearer internal-token"
+);
+
+let proxy = Proxy::new(client, "http://internal.example")
+    .with_upstream_auth(UpstreamAuth::basic("proxy", "secret"));
+assert_ok!(get("/").on(&proxy), "Basic cHJveHk6c2VjcmV0");
+```
+*/
+pub struct UpstreamAuth(Credentials);
+
+enum Credentials {
+    Bearer(String),
+    Basic(String),
+    TokenSource(CachedToken),
+}
+
+impl Debug for UpstreamAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let kind = match &self.0 {
+            Credentials::Bearer(_) => "Bearer",
+            Credentials::Basic(_) => "Basic",
+            Credentials::TokenSource(_) => "TokenSource",
+        };
+        f.debug_tuple("UpstreamAuth")
+            .field(&format_args!("{kind}"))
+            .finish()
+    }
+}
+
+impl UpstreamAuth {
+    /// a static bearer token
+    pub fn bearer(token: impl AsRef<str>) -> Self {
+        Self(Credentials::Bearer(format!("Bearer {}", token.as_ref())))
+    }
+
+    /// a static http basic auth username and password
+    pub fn basic(username: impl AsRef<str>, password: impl AsRef<str>) -> Self {
+        let credentials = format!("{}:{}", username.as_ref(), password.as_ref());
+        Self(Credentials::Basic(format!(
+            "Basic {}",
+            STANDARD.encode(credentials)
+        )))
+    }
+
+    /**
+    bearer tokens fetched from a [`TokenSource`], such as an async
+    closure that requests a token from an oauth2 token endpoint
+
+    The token is cached until it expires, and is fetched again when it
+    expires or when the upstream responds with `401 Unauthorized`. If a
+    token cannot be fetched, the proxy responds with `503 Service
+    Unavailable` instead of sending an unauthenticated request.
+
+    ```
+    use std::{sync::atomic::{AtomicUsize, Ordering}, time::Duration};
+    use trillium::Conn;
+    use trillium_client::Client;
+    use trillium_proxy::{upstream_auth::{Token, UpstreamAuth}, Proxy};
+    use trillium_testing::prelude::*;
+
+    let upstream = |conn: Conn| async move {
+        let authorization = conn.request_headers().get_str("authorization").unwrap_or_default().to_string();
+        conn.ok(authorization)
+    };
+
+    static FETCHES: AtomicUsize = AtomicUsize::new(0);
+    let proxy = Proxy::new(Client::new(trillium_testing::connector(upstream)), "http://internal.example")
+        .with_upstream_auth(UpstreamAuth::token_source(|| async {
+            let fetches = FETCHES.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok::<_, String>(Token::new(format!("token-{fetches}")).with_expires_in(Duration::from_secs(60)))
+        }));
+
+    assert_ok!(get("/").on(&proxy), "Bearer token-1");
+    assert_ok!(get("/").on(&proxy), "Bearer token-1");
+    ```
+    */
+    pub fn token_source(token_source: impl TokenSource) -> Self {
+        Self(Credentials::TokenSource(CachedToken {
+            token_source: Box::new(token_source),
+            token: RwLock::new(None),
+        }))
+    }
+
+    /// the value of the authorization header
+    pub(crate) async fn authorization(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        match &self.0 {
+            Credentials::Bearer(value) | Credentials::Basic(value) => Ok(value.clone()),
+            Credentials::TokenSource(cached_token) => {
+                Ok(format!("Bearer {}", cached_token.token().await?))
+            }
+        }
+    }
+
+    /// discard any cached token so that the next request fetches a new one
+    pub(crate) async fn invalidate(&self) {
+        if let Credentials::TokenSource(cached_token) = &self.0 {
+            *cached_token.token.write().await = None;
+        }
+    }
+}
+
+/// a bearer token provided by a [`TokenSource`]
+#[derive(Clone)]
+pub struct Token {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl Debug for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("value", &"..")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+impl Token {
+    /// a token that does not expire until the upstream rejects it
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            expires_at: None,
+        }
+    }
+
+    /// fetch a new token after this duration, such as the
+    /// `expires_in` of an oauth2 token response
+    pub fn with_expires_in(mut self, expires_in: Duration) -> Self {
+        self.expires_at = Some(Instant::now() + expires_in);
+        self
+    }
+
+    fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+    }
+}
+
+/**
+an async source of bearer tokens for [`UpstreamAuth::token_source`]
+
+This is implemented for any `Fn() -> impl Future<Output =
+Result<Token, E>>` where E can be converted into a boxed error, such
+as a `String`.
+*/
+#[async_trait]
+pub trait TokenSource: Send + Sync + 'static {
+    /// fetch a new token
+    async fn fetch_token(&self) -> Result<Token, Box<dyn Error + Send + Sync>>;
+}
+
+#[async_trait]
+impl<F, Fut, E> TokenSource for F
+where
+    F: Fn() -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Token, E>> + Send,
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    async fn fetch_token(&self) -> Result<Token, Box<dyn Error + Send + Sync>> {
+        self().await.map_err(Into::into)
+    }
+}
+
+struct CachedToken {
+    token_source: Box<dyn TokenSource>,
+    token: RwLock<Option<Token>>,
+}
+
+impl CachedToken {
+    async fn token(&self) -> Result<String, Box<dyn Error + Send + Sync>> {
+        if let Some(token) = &*self.token.read().await {
+            if !token.is_expired() {
+                return Ok(token.value.clone());
+            }
+        }
+
+        let mut cached = self.token.write().await;
+        // another request may have refreshed the token while this one
+        // waited for the lock
+        if let Some(token) = &*cached {
+            if !token.is_expired() {
+                return Ok(token.value.clone());
+            }
+        }
+
+        let token = self.token_source.fetch_token().await?;
+        let value = token.value.clone();
+        *cached = Some(token);
+        Ok(value)
+    }
+}
@@ -0,0 +1,66 @@
+use std::io::{Error, ErrorKind};
+use trillium::{Conn, Handler};
+use trillium_client::Client;
+use trillium_proxy::{upstream::RoundRobin, Proxy, Url};
+use trillium_testing::{prelude::*, ServerConnector, TestTransport};
+
+struct FailingConnector<H>(ServerConnector<H>);
+
+#[trillium::async_trait]
+impl<H: Handler> trillium_testing::Connector for FailingConnector<H> {
+    type Transport = TestTransport;
+    async fn connect(&self, url: &Url) -> std::io::Result<TestTransport> {
+        if url.host_str() == Some("down.internal") {
+            Err(Error::new(
+                ErrorKind::ConnectionRefused,
+                "connection refused",
+            ))
+        } else {
+            Ok(self.0.connect(false).await)
+        }
+    }
+
+    fn spawn<Fut: std::future::Future<Output = ()> + Send + 'static>(&self, fut: Fut) {
+        trillium_testing::spawn(fut);
+    }
+}
+
+fn proxy() -> Proxy<RoundRobin<Url>> {
+    let upstream = |mut conn: Conn| async move {
+        let body = conn.request_body_string().await.unwrap();
+        let host = conn.inner().host().unwrap_or_default().to_string();
+        conn.ok(format!("{host}: {body}"))
+    };
+    let client = Client::new(FailingConnector(ServerConnector::new(upstream)));
+    Proxy::new(
+        client,
+        RoundRobin::new(["http://down.internal", "http://up.internal"]),
+    )
+}
+
+#[test]
+fn buffered_bodies_are_retried() {
+    let proxy = proxy().with_body_buffer_limit(1024);
+    assert_ok!(
+        post("/").with_request_body("hello").on(&proxy),
+        "up.internal: hello"
+    );
+    assert_ok!(get("/").on(&proxy), "up.internal: ");
+}
+
+#[test]
+fn bodies_over_the_limit_are_not_retried() {
+    let proxy = proxy().with_body_buffer_limit(2);
+    assert_status!(post("/").with_request_body("hello").on(&proxy), 503);
+    assert_ok!(
+        post("/").with_request_body("hello").on(&proxy),
+        "up.internal: hello"
+    );
+}
+
+#[test]
+fn requests_are_not_retried_by_default() {
+    let proxy = proxy();
+    assert_status!(post("/").with_request_body("hello").on(&proxy), 503);
+    assert_status!(get("/").on(&proxy), 200);
+}
@@ -0,0 +1,215 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeSet,
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use trillium::{Conn, HeaderName, HeaderValue};
+
+/**
+# A condition that a request must satisfy for a route to match
+
+Routes registered within [`Router::when`](crate::Router::when) only
+match requests for which [`RouteConstraint::matches`] returns true.
+Otherwise the router continues looking for a matching route as if the
+constrained route had not been registered.
+
+This is implemented for all `Fn(&Conn) -> bool + Send + Sync +
+'static`, as well as for [`Host`] and [`Header`].
+
+```
+# use trillium::Conn;
+# use trillium_router::Router;
+let router = Router::new()
+    .when(
+        |conn: &Conn| conn.querystring().contains("beta"),
+        |beta| beta.get("/", "beta"),
+    )
+    .get("/", "stable");
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/?beta").on(&router), "beta");
+assert_ok!(get("/").on(&router), "stable");
+```
+*/
+pub trait RouteConstraint: Send + Sync + 'static {
+    /// returns true if routes with this constraint can match the conn
+    fn matches(&self, conn: &Conn) -> bool;
+}
+
+impl<F> RouteConstraint for F
+where
+    F: Fn(&Conn) -> bool + Send + Sync + 'static,
+{
+    fn matches(&self, conn: &Conn) -> bool {
+        self(conn)
+    }
+}
+
+/**
+A [`RouteConstraint`] on the host of the request, ignoring any port.
+The host is compared case-insensitively, and a host that starts with
+`*.` matches any subdomain of the rest of the host, but not the host
+itself.
+
+```
+# use trillium_router::{Host, Router};
+let router = Router::new()
+    .when(Host::new("api.example.com"), |api| api.get("/", "api"))
+    .when(Host::new("*.example.com"), |subdomain| subdomain.get("/", "subdomain"))
+    .get("/", "default");
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/").with_request_header("host", "API.example.com:8080").on(&router), "api");
+assert_ok!(get("/").with_request_header("host", "www.example.com").on(&router), "subdomain");
+assert_ok!(get("/").with_request_header("host", "example.com").on(&router), "default");
+assert_ok!(get("/").on(&router), "default");
+```
+*/
+#[derive(Debug, Clone)]
+pub struct Host(Cow<'static, str>);
+
+impl Host {
+    /// constructs a new host constraint
+    pub fn new(host: impl Into<Cow<'static, str>>) -> Self {
+        Self(host.into())
+    }
+}
+
+impl RouteConstraint for Host {
+    fn matches(&self, conn: &Conn) -> bool {
+        conn.inner()
+            .host()
+            .is_some_and(|host| host_matches(&self.0, hostname(host)))
+    }
+}
+
+// the host header without a port
+pub(crate) fn hostname(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+        _ => host,
+    }
+}
+
+// whether a hostname matches an exact or `*.`-prefixed wildcard pattern,
+// case-insensitively
+pub(crate) fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .len()
+            .checked_sub(domain.len() + 1)
+            .and_then(|split| host.get(split..))
+            .and_then(|suffix| suffix.strip_prefix('.'))
+            .is_some_and(|suffix| suffix.eq_ignore_ascii_case(domain)),
+        None => host.eq_ignore_ascii_case(pattern),
+    }
+}
+
+/**
+A [`RouteConstraint`] on a request header, which matches if the header
+is present or, if constructed with [`Header::equals`], if the header
+has the provided value, compared case-insensitively.
+
+```
+# use trillium_router::{Header, Router};
+let router = Router::new()
+    .when(Header::equals("accept", "application/json"), |json| json.get("/", "{}"))
+    .when(Header::present("x-requested-with"), |xhr| xhr.get("/", "xhr"))
+    .get("/", "html");
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/").with_request_header("accept", "application/json").on(&router), "{}");
+assert_ok!(get("/").with_request_header("x-requested-with", "fetch").on(&router), "xhr");
+assert_ok!(get("/").on(&router), "html");
+```
+*/
+#[derive(Debug, Clone)]
+pub struct Header {
+    name: HeaderName<'static>,
+    value: Option<HeaderValue>,
+}
+
+impl Header {
+    /// constructs a constraint that matches if the header is present
+    pub fn present(name: impl Into<HeaderName<'static>>) -> Self {
+        Self {
+            name: name.into(),
+            value: None,
+        }
+    }
+
+    /// constructs a constraint that matches if the header has the provided value
+    pub fn equals(name: impl Into<HeaderName<'static>>, value: impl Into<HeaderValue>) -> Self {
+        Self {
+            name: name.into(),
+            value: Some(value.into()),
+        }
+    }
+}
+
+impl RouteConstraint for Header {
+    fn matches(&self, conn: &Conn) -> bool {
+        match (
+            &self.value,
+            conn.request_headers().get_str(self.name.clone()),
+        ) {
+            (_, None) => false,
+            (None, Some(_)) => true,
+            (Some(expected), Some(value)) => expected
+                .as_str()
+                .is_some_and(|expected| expected.eq_ignore_ascii_case(value)),
+        }
+    }
+}
+
+// a constraint attached to a route, with an id that is recorded on the
+// conn when the route matches so that before_send and upgrade can
+// identify the same route without evaluating the constraint again
+#[derive(Clone)]
+pub(crate) struct Constraint {
+    id: usize,
+    constraint: Arc<dyn RouteConstraint>,
+}
+
+impl Debug for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Constraint").field("id", &self.id).finish()
+    }
+}
+
+impl Constraint {
+    pub(crate) fn new(constraint: impl RouteConstraint) -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            constraint: Arc::new(constraint),
+        }
+    }
+
+    pub(crate) fn matches(&self, conn: &Conn) -> bool {
+        self.constraint.matches(conn)
+    }
+
+    pub(crate) fn and(&self, other: Constraint) -> Self {
+        let this = self.constraint.clone();
+        let other = other.constraint;
+        Self::new(move |conn: &Conn| this.matches(conn) && other.matches(conn))
+    }
+
+    pub(crate) fn id(&self) -> usize {
+        self.id
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct MatchedConstraints(pub(crate) BTreeSet<usize>);
+
+impl MatchedConstraints {
+    pub(crate) fn contains(&self, constraint: &Constraint) -> bool {
+        self.0.contains(&constraint.id)
+    }
+}
@@ -0,0 +1,30 @@
+/**
+# How a [`Router`](crate::Router) treats duplicate routes
+
+A route is a duplicate if it is registered for a method and a path
+that an earlier route in the same router already handles, such as
+`/users/:id` after `/users/:user_id`, or `get("/")` after
+`all("/")`. Without a policy, the earlier route silently shadows the
+later one.
+
+Routes registered within [`Router::when`](crate::Router::when) are
+only duplicates of other routes within the same constraint.
+
+Configure this with
+[`Router::with_duplicate_route_policy`](crate::Router::with_duplicate_route_policy).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateRoutePolicy {
+    /// Log a warning for each duplicate route when the router is
+    /// initialized. The earlier route is used. This is the default.
+    #[default]
+    Warn,
+
+    /// Panic when the router is initialized if there are any
+    /// duplicate routes, listing all of them.
+    Panic,
+
+    /// The later route replaces the earlier route for the methods
+    /// that they have in common.
+    LastWins,
+}
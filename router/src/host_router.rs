@@ -0,0 +1,179 @@
+use crate::constraint::{host_matches, hostname};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+};
+use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName, Upgrade};
+
+/**
+# A handler that dispatches on the request host
+
+A `HostRouter` runs a different handler depending on the `Host`
+header of the request, so that one server can serve several
+applications. Host patterns are either exact, like `"example.com"`,
+or match any subdomain of a domain, like `"*.example.com"`, which
+matches `api.example.com` and `a.b.example.com` but not `example.com`
+itself. Hosts are compared case-insensitively and without the port.
+
+An exact pattern takes precedence over any wildcard pattern, and a
+wildcard pattern for a longer domain takes precedence over one for a
+shorter domain, regardless of the order they were added in. Requests
+that do not match any pattern run the handler provided to
+[`HostRouter::with_default`], or are not handled if there is none.
+
+For [`Router`](crate::Router) routes that depend on the host, see
+[`Host`](crate::Host).
+
+## Proxies
+
+When trillium runs behind a reverse proxy, the `Host` header may be
+that of the proxy. Place `trillium-forwarding`'s `Forwarding` handler
+before the `HostRouter` to route on the host provided in a trusted
+`Forwarded` or `X-Forwarded-Host` header.
+
+```
+# use trillium_router::{HostRouter, Router};
+let app = HostRouter::new()
+    .with_host("api.example.com", Router::new().get("/", "api"))
+    .with_host("*.example.com", "some subdomain")
+    .with_host("example.com", "www")
+    .with_default("unknown host");
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/").with_request_header("host", "API.example.com:8080").on(&app), "api");
+assert_ok!(get("/").with_request_header("host", "docs.example.com").on(&app), "some subdomain");
+assert_ok!(get("/").with_request_header("host", "example.com").on(&app), "www");
+assert_ok!(get("/").with_request_header("host", "example.org").on(&app), "unknown host");
+```
+*/
+#[derive(Default)]
+pub struct HostRouter {
+    hosts: Vec<(Cow<'static, str>, Box<dyn Handler>)>,
+    default: Option<Box<dyn Handler>>,
+}
+
+impl HostRouter {
+    /// Constructs a new HostRouter with no hosts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Run the provided handler for requests with a host that matches the
+    provided pattern, which is either an exact host like
+    `"example.com"` or a wildcard like `"*.example.com"`.
+
+    # Panics
+
+    This will panic if the pattern is empty or contains a port.
+    */
+    pub fn with_host(
+        mut self,
+        pattern: impl Into<Cow<'static, str>>,
+        handler: impl Handler,
+    ) -> Self {
+        let pattern = pattern.into();
+        assert!(
+            !pattern.is_empty() && !pattern.contains(':'),
+            "host patterns must not be empty or contain a port, but was `{pattern}`"
+        );
+        self.hosts.push((pattern, Box::new(handler)));
+        self
+    }
+
+    /// Run the provided handler for requests that do not match any host
+    /// pattern, including requests without a `Host` header.
+    pub fn with_default(mut self, handler: impl Handler) -> Self {
+        self.default = Some(Box::new(handler));
+        self
+    }
+
+    fn handler_for(&self, host: Option<&str>) -> Option<&dyn Handler> {
+        host.map(hostname)
+            .and_then(|host| {
+                self.hosts
+                    .iter()
+                    .filter(|(pattern, _)| host_matches(pattern, host))
+                    .min_by_key(|(pattern, _)| precedence(pattern))
+            })
+            .map(|(_, handler)| &**handler)
+            .or(self.default.as_deref())
+    }
+}
+
+// lower is more specific. the first of several equally specific
+// patterns is chosen, since min_by_key returns the first minimum
+fn precedence(pattern: &str) -> usize {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => usize::MAX - domain.len(),
+        None => 0,
+    }
+}
+
+#[async_trait]
+impl Handler for HostRouter {
+    async fn run(&self, conn: Conn) -> Conn {
+        match self.handler_for(conn.inner().host()) {
+            Some(handler) => handler.run(conn).await,
+            None => {
+                log::debug!("{:?} did not match any host", conn.inner().host());
+                conn
+            }
+        }
+    }
+
+    async fn before_send(&self, conn: Conn) -> Conn {
+        match self.handler_for(conn.inner().host()) {
+            Some(handler) => handler.before_send(conn).await,
+            None => conn,
+        }
+    }
+
+    fn has_upgrade(&self, upgrade: &Upgrade) -> bool {
+        self.handler_for(upgrade.request_headers.get_str(KnownHeaderName::Host))
+            .is_some_and(|handler| handler.has_upgrade(upgrade))
+    }
+
+    async fn upgrade(&self, upgrade: Upgrade) {
+        if let Some(handler) =
+            self.handler_for(upgrade.request_headers.get_str(KnownHeaderName::Host))
+        {
+            handler.upgrade(upgrade).await;
+        }
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        for (_, handler) in &mut self.hosts {
+            handler.init(info).await;
+        }
+
+        if let Some(default) = &mut self.default {
+            default.init(info).await;
+        }
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        "HostRouter".into()
+    }
+}
+
+impl Debug for HostRouter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("HostRouter ")?;
+        let mut map = f.debug_map();
+        for (pattern, handler) in &self.hosts {
+            map.entry(
+                &format_args!("{pattern}"),
+                &format_args!("{}", handler.name()),
+            );
+        }
+
+        if let Some(default) = &self.default {
+            map.entry(
+                &format_args!("default"),
+                &format_args!("{}", default.name()),
+            );
+        }
+        map.finish()
+    }
+}
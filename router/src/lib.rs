@@ -42,6 +42,41 @@ crate! It should be possible to nest different types of routers (and
 different versions of router crates) within each other as long as they
 all depend on the same version of the `trillium` crate.
 
+## Named routes
+
+Routes can be registered with a name, with [`Router::get_named`] and
+related methods, and paths for those routes can be built with
+[`Router::url_for`] or [`RouterConnExt::url_for`] instead of
+hard-coding them in templates and redirects.
+
+## Route constraints
+
+Routes registered within [`Router::when`] only match requests that
+satisfy a [`RouteConstraint`], such as a [`Host`] or [`Header`], or
+any `Fn(&Conn) -> bool`. Requests that do not satisfy the constraint
+fall through to later routes.
+
+## Host routing
+
+To run a different handler, such as a different router, for each
+request host, use a [`HostRouter`].
+
+## Trailing slashes
+
+By default, the router ignores trailing slashes. To redirect
+requests with a trailing slash or repeated slashes to the canonical
+path, to treat them as equivalent to the canonical path, or to only
+match canonical paths, use [`Router::with_slash_policy`] or
+[`RouterRef::set_slash_policy`].
+
+## Duplicate routes
+
+By default, the router logs a warning when it is initialized for each
+route that is shadowed by an earlier route for the same method and an
+equivalent path. To panic at startup instead, or to let later routes
+replace earlier ones, use [`Router::with_duplicate_route_policy`] or
+[`RouterRef::set_duplicate_route_policy`].
+
 ## Options handling
 
 By default, the trillium router will reply to an OPTIONS request with
@@ -55,6 +90,22 @@ or an `any` handler.
 To disable the default OPTIONS behavior, use
 [`Router::without_options_handling`] or
 [`RouterRef::set_options_handling`]
+
+To customize the OPTIONS response, for example to respond to CORS
+preflight requests, use [`Router::with_options_handler`] or
+[`RouterRef::set_options_handler`]. The options handler is run
+after the `Allow` header is set, and can retrieve the methods
+supported at the requested path with
+[`RouterConnExt::allowed_methods`].
+
+## Method not allowed
+
+By default, a request for a path that matches routes in this router,
+but not for the request method, is not handled by the router. To
+respond to these requests with `405 Method Not Allowed` and an `Allow`
+header listing the supported methods, use
+[`Router::with_method_not_allowed`] or
+[`RouterRef::set_method_not_allowed`].
 */
 
 mod router;
@@ -63,9 +114,28 @@ pub use router::Router;
 mod router_ref;
 pub use router_ref::RouterRef;
 
+mod named_routes;
+
+mod constraint;
+pub use constraint::{Header, Host, RouteConstraint};
+
+mod host_router;
+pub use host_router::HostRouter;
+
+mod slash_policy;
+pub use slash_policy::SlashPolicy;
+
+mod duplicate_route_policy;
+pub use duplicate_route_policy::DuplicateRoutePolicy;
+
 mod router_conn_ext;
 pub use router_conn_ext::RouterConnExt;
 
+#[cfg(feature = "serde")]
+mod params;
+#[cfg(feature = "serde")]
+pub use params::ParamsError;
+
 /**
 The routes macro represents an experimental macro for defining
 routers.
@@ -109,3 +179,4 @@ pub fn router() -> Router {
 
 pub(crate) struct CapturesNewType<'a, 'b>(routefinder::Captures<'a, 'b>);
 pub(crate) struct RouteSpecNewType(routefinder::RouteSpec);
+pub(crate) struct AllowedMethodsNewType(Vec<trillium::Method>);
@@ -0,0 +1,53 @@
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use routefinder::{RouteSpec, Segment};
+use std::{collections::BTreeMap, sync::Arc};
+
+// everything other than the unreserved characters of rfc 3986
+const PARAM: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+#[derive(Debug, Default, Clone)]
+pub(crate) struct NamedRoutes(Arc<BTreeMap<String, RouteSpec>>);
+
+impl NamedRoutes {
+    pub(crate) fn insert(&mut self, name: &str, route: RouteSpec) {
+        if Arc::make_mut(&mut self.0)
+            .insert(name.to_string(), route)
+            .is_some()
+        {
+            panic!("route name {name} is already in use");
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &RouteSpec)> {
+        self.0.iter()
+    }
+
+    pub(crate) fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Option<String> {
+        let route = self.0.get(name)?;
+        let get = |key: &str| {
+            params
+                .iter()
+                .find_map(|(name, value)| (*name == key).then_some(*value))
+        };
+
+        let mut url = String::from("/");
+        for segment in route.segments() {
+            match segment {
+                Segment::Slash => url.push('/'),
+                Segment::Dot => url.push('.'),
+                Segment::Exact(exact) => url.push_str(exact),
+                Segment::Param(param) => url.extend(utf8_percent_encode(get(param)?, PARAM)),
+                Segment::Wildcard => url.push_str(get("*")?),
+            }
+        }
+        Some(url)
+    }
+}
@@ -0,0 +1,269 @@
+use serde::de::{
+    value::{MapDeserializer, SeqDeserializer},
+    Deserializer, Error as _, IntoDeserializer, Visitor,
+};
+use serde::forward_to_deserialize_any;
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/**
+An error returned by [`RouterConnExt::params`](crate::RouterConnExt::params)
+when the route's params cannot be deserialized into the requested type
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParamsError {
+    param: Option<String>,
+    message: String,
+}
+
+impl ParamsError {
+    /// the name of the param that could not be deserialized, if the
+    /// error is specific to a single param
+    pub fn param(&self) -> Option<&str> {
+        self.param.as_deref()
+    }
+
+    /// a description of the error, without the param name
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn for_param(mut self, name: &str) -> Self {
+        if self.param.is_none() {
+            self.param = Some(name.to_string());
+        }
+        self
+    }
+}
+
+impl Display for ParamsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.param {
+            Some(param) => write!(f, "{param}: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl Error for ParamsError {}
+
+impl serde::de::Error for ParamsError {
+    fn custom<T: Display>(message: T) -> Self {
+        Self {
+            param: None,
+            message: message.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self {
+            param: Some(field.to_string()),
+            message: String::from("missing param"),
+        }
+    }
+}
+
+// a single param value, which deserializes primitives by parsing them
+pub(crate) struct Param<'a> {
+    name: &'a str,
+    value: Cow<'a, str>,
+}
+
+impl<'a> Param<'a> {
+    pub(crate) fn new(name: &'a str, value: Cow<'a, str>) -> Self {
+        Self { name, value }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, ParamsError> for Param<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+macro_rules! parse_param {
+    ($($method:ident => $visit:ident,)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+            let name = self.name;
+            match self.value.parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(ParamsError::custom(format_args!(
+                    "could not parse {:?} as {}",
+                    self.value,
+                    &stringify!($method)["deserialize_".len()..]
+                ))),
+            }
+            .map_err(|e: ParamsError| e.for_param(name))
+        })*
+    };
+}
+
+impl<'de> Deserializer<'de> for Param<'de> {
+    type Error = ParamsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        let name = self.name;
+        match self.value {
+            Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+            Cow::Owned(value) => visitor.visit_string(value),
+        }
+        .map_err(|e: ParamsError| e.for_param(name))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        let name = self.name;
+        visitor
+            .visit_enum(self.value.into_deserializer())
+            .map_err(|e: ParamsError| e.for_param(name))
+    }
+
+    parse_param! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
+    }
+}
+
+// all of the params for a route, which deserialize as a map by name, as
+// a sequence in route order, or as a single value
+pub(crate) struct Params<'a>(pub(crate) Vec<Param<'a>>);
+
+impl<'de> Params<'de> {
+    fn single(self) -> Result<Param<'de>, ParamsError> {
+        let len = self.0.len();
+        match <[_; 1]>::try_from(self.0) {
+            Ok([param]) => Ok(param),
+            Err(_) => Err(ParamsError::invalid_length(len, &"exactly one param")),
+        }
+    }
+}
+
+macro_rules! single_param {
+    ($($method:ident)*) => {
+        $(fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+            self.single()?.$method(visitor)
+        })*
+    };
+}
+
+impl<'de> Deserializer<'de> for Params<'de> {
+    type Error = ParamsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        MapDeserializer::new(self.0.into_iter().map(|param| (param.name, param)))
+            .deserialize_map(visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        SeqDeserializer::new(self.0.into_iter()).deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParamsError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParamsError> {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    single_param! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_i128 deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64
+        deserialize_u128 deserialize_f32 deserialize_f64 deserialize_char deserialize_str
+        deserialize_string deserialize_bytes deserialize_byte_buf deserialize_option
+        deserialize_identifier
+    }
+}
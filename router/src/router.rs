@@ -1,11 +1,20 @@
-use crate::{CapturesNewType, RouteSpecNewType, RouterRef};
-use routefinder::{Match, RouteSpec, Router as Routefinder};
+use crate::{
+    constraint::{Constraint, MatchedConstraints},
+    named_routes::NamedRoutes,
+    slash_policy::canonicalize,
+    AllowedMethodsNewType, CapturesNewType, DuplicateRoutePolicy, RouteConstraint,
+    RouteSpecNewType, RouterRef, SlashPolicy,
+};
+use routefinder::{Match, RouteSpec, Router as Routefinder, Segment};
 use std::{
+    borrow::Cow,
     collections::BTreeSet,
     fmt::{self, Debug, Display, Formatter},
     mem,
 };
-use trillium::{async_trait, Conn, Handler, Info, KnownHeaderName, Method, Upgrade};
+use trillium::{
+    async_trait, Conn, Handler, Info, KnownHeaderName, Method, StateSet, Status, Upgrade,
+};
 
 const ALL_METHODS: [Method; 5] = [
     Method::Delete,
@@ -20,6 +29,58 @@ enum MethodSelection {
     Just(Method),
     All,
     Any(Vec<Method>),
+    AllExcept(Vec<Method>),
+}
+
+impl MethodSelection {
+    // the methods selected, if this is a finite set
+    fn finite(&self) -> Option<&[Method]> {
+        match self {
+            MethodSelection::Just(method) => Some(std::slice::from_ref(method)),
+            MethodSelection::Any(methods) => Some(methods),
+            MethodSelection::All | MethodSelection::AllExcept(_) => None,
+        }
+    }
+
+    fn overlaps(&self, other: &MethodSelection) -> bool {
+        match (self.finite(), other.finite()) {
+            (Some(methods), _) => methods.iter().any(|method| other == method),
+            (None, Some(methods)) => methods.iter().any(|method| self == method),
+            (None, None) => true,
+        }
+    }
+
+    // this selection with any methods in the other selection removed
+    fn without(&self, other: &MethodSelection) -> MethodSelection {
+        match (self, other) {
+            (_, MethodSelection::All) => MethodSelection::Any(vec![]),
+            (MethodSelection::All, _) => {
+                MethodSelection::AllExcept(other.finite().unwrap().to_vec())
+            }
+            (MethodSelection::AllExcept(excluded), MethodSelection::AllExcept(other_excluded)) => {
+                MethodSelection::Any(
+                    other_excluded
+                        .iter()
+                        .filter(|method| !excluded.contains(method))
+                        .copied()
+                        .collect(),
+                )
+            }
+            (MethodSelection::AllExcept(excluded), _) => {
+                let mut excluded = excluded.clone();
+                excluded.extend(other.finite().unwrap());
+                MethodSelection::AllExcept(excluded)
+            }
+            _ => MethodSelection::Any(
+                self.finite()
+                    .unwrap()
+                    .iter()
+                    .filter(|method| other != *method)
+                    .copied()
+                    .collect(),
+            ),
+        }
+    }
 }
 
 impl Display for MethodSelection {
@@ -30,6 +91,10 @@ impl Display for MethodSelection {
             MethodSelection::Any(v) => {
                 f.write_str(&v.iter().map(|m| m.as_ref()).collect::<Vec<_>>().join(", "))
             }
+            MethodSelection::AllExcept(v) => f.write_fmt(format_args!(
+                "* except {}",
+                v.iter().map(|m| m.as_ref()).collect::<Vec<_>>().join(", ")
+            )),
         }
     }
 }
@@ -40,6 +105,7 @@ impl PartialEq<Method> for MethodSelection {
             MethodSelection::Just(m) => m == other,
             MethodSelection::All => true,
             MethodSelection::Any(v) => v.contains(other),
+            MethodSelection::AllExcept(v) => !v.contains(other),
         }
     }
 }
@@ -67,21 +133,50 @@ impl From<Vec<Method>> for MethodSelection {
     }
 }
 
+type Route = (MethodSelection, Box<dyn Handler>, Option<Constraint>);
+
 #[derive(Debug, Default)]
-struct MethodRoutefinder(Routefinder<(MethodSelection, Box<dyn Handler>)>);
+struct MethodRoutefinder(Routefinder<Route>);
 impl MethodRoutefinder {
-    fn add<R>(
-        &mut self,
-        method_selection: impl Into<MethodSelection>,
-        path: R,
-        handler: impl Handler,
-    ) where
+    // returns a description of each earlier route that the new route
+    // duplicates, removing the duplicated methods from those routes
+    // if the policy is last-wins
+    fn add_route<R>(&mut self, path: R, route: Route, policy: DuplicateRoutePolicy) -> Vec<String>
+    where
         R: TryInto<RouteSpec>,
         R::Error: Debug,
     {
-        self.0
-            .add(path, (method_selection.into(), Box::new(handler)))
-            .expect("could not add route")
+        let route_spec = path.try_into().expect("could not add route");
+        let (methods, _, constraint) = &route;
+        let constraint_id = constraint.as_ref().map(Constraint::id);
+        let mut duplicates = vec![];
+        for (existing_spec, (existing_methods, _, existing_constraint)) in &mut self.0 {
+            if existing_constraint.as_ref().map(Constraint::id) != constraint_id
+                || !equivalent(existing_spec, &route_spec)
+                || !existing_methods.overlaps(methods)
+            {
+                continue;
+            }
+
+            duplicates.push(format!(
+                "{methods} {route_spec} duplicates {existing_methods} {existing_spec}"
+            ));
+
+            if policy == DuplicateRoutePolicy::LastWins {
+                *existing_methods = existing_methods.without(methods);
+            }
+        }
+
+        self.insert(route_spec, route);
+        duplicates
+    }
+
+    fn insert<R>(&mut self, path: R, route: Route)
+    where
+        R: TryInto<RouteSpec>,
+        R::Error: Debug,
+    {
+        self.0.add(path, route).expect("could not add route")
     }
 
     fn methods_matching(&self, path: &str) -> BTreeSet<Method> {
@@ -98,11 +193,18 @@ impl MethodRoutefinder {
                 MethodSelection::Any(methods) => {
                     set.extend(methods);
                 }
+                MethodSelection::AllExcept(methods) => {
+                    set.extend(
+                        ALL_METHODS
+                            .iter()
+                            .filter(|method| !methods.contains(method)),
+                    );
+                }
             }
         }
 
         if path == "*" {
-            for ms in self.0.iter().map(|(_, (m, _))| m) {
+            for ms in self.0.iter().map(|(_, (m, _, _))| m) {
                 extend(ms, &mut set);
             }
         } else {
@@ -119,8 +221,11 @@ impl MethodRoutefinder {
         &'a self,
         method: Method,
         path: &'b str,
-    ) -> Option<Match<'a, 'b, (MethodSelection, Box<dyn Handler>)>> {
-        self.0.match_iter(path).find(|m| m.0 == method)
+        constraint_matches: impl Fn(&Constraint) -> bool,
+    ) -> Option<Match<'a, 'b, Route>> {
+        self.0
+            .match_iter(path)
+            .find(|m| m.0 == method && m.2.as_ref().is_none_or(&constraint_matches))
     }
 }
 
@@ -133,6 +238,13 @@ See crate level docs for more, as this is the primary type in this crate.
 pub struct Router {
     routefinder: MethodRoutefinder,
     handle_options: bool,
+    handle_method_not_allowed: bool,
+    options_handler: Option<Box<dyn Handler>>,
+    handlers_before: Vec<Box<dyn Handler>>,
+    named_routes: NamedRoutes,
+    slash_policy: Option<SlashPolicy>,
+    duplicate_route_policy: DuplicateRoutePolicy,
+    duplicate_routes: Vec<String>,
 }
 
 impl Default for Router {
@@ -140,6 +252,13 @@ impl Default for Router {
         Self {
             routefinder: MethodRoutefinder::default(),
             handle_options: true,
+            handle_method_not_allowed: false,
+            options_handler: None,
+            handlers_before: Vec::new(),
+            named_routes: NamedRoutes::default(),
+            slash_policy: None,
+            duplicate_route_policy: DuplicateRoutePolicy::default(),
+            duplicate_routes: Vec::new(),
         }
     }
 }
@@ -192,6 +311,45 @@ assert_not_handled!(",
     };
 }
 
+macro_rules! named_method {
+    ($fn_name:ident, $method:ident) => {
+        named_method!(
+            $fn_name,
+            $method,
+            concat!(
+                "Registers a named handler for the ",
+                stringify!($method),
+                " http method. See [`Router::url_for`] for usage of the name.
+
+```
+# use trillium::Conn;
+# use trillium_router::Router;
+let router = Router::new().",
+                stringify!($fn_name),
+                "(\"/some/:param\", \"some_route\", |conn: Conn| async move {
+  conn.ok(\"success\")
+});
+
+assert_eq!(router.url_for(\"some_route\", &[(\"param\", \"value\")]).unwrap(), \"/some/value\");
+```
+"
+            )
+        );
+    };
+
+    ($fn_name:ident, $method:ident, $doc_comment:expr) => {
+        #[doc = $doc_comment]
+        pub fn $fn_name<R>(mut self, path: R, name: &str, handler: impl Handler) -> Self
+        where
+            R: TryInto<RouteSpec>,
+            R::Error: Debug,
+        {
+            self.add_named(path, name, Method::$method, handler);
+            self
+        }
+    };
+}
+
 impl Router {
     /**
     Constructs a new Router. This is often used with [`Router::get`],
@@ -237,6 +395,364 @@ impl Router {
         self.handle_options = options_enabled;
     }
 
+    /**
+    Respond with `405 Method Not Allowed` to requests for a path that
+    matches routes in this router but not for the request method,
+    instead of leaving the conn unhandled for later handlers. The
+    response includes an `Allow` header with the methods supported at
+    that path, as in the default options handling.
+
+    This applies to the routes of this router only, so it must be
+    enabled separately on a router that is nested within another
+    router or [`Router::scope`].
+
+    ```
+    # use trillium_router::Router;
+    let router = Router::new()
+        .with_method_not_allowed()
+        .get("/some/route", "ok")
+        .put("/some/route", "ok");
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/some/route").on(&router), "ok");
+    assert_response!(
+        delete("/some/route").on(&router),
+        405,
+        "",
+        "allow" => "GET, PUT"
+    );
+    assert_not_handled!(delete("/other/route").on(&router));
+    ```
+    */
+    pub fn with_method_not_allowed(mut self) -> Self {
+        self.set_method_not_allowed(true);
+        self
+    }
+
+    pub(crate) fn set_method_not_allowed(&mut self, enabled: bool) {
+        self.handle_method_not_allowed = enabled;
+    }
+
+    /**
+    Run the provided handler on OPTIONS requests that do not match an
+    explicit OPTIONS route, after the `Allow` header has been set. The
+    supported methods at the requested path are available to this
+    handler with
+    [`RouterConnExt::allowed_methods`](crate::RouterConnExt::allowed_methods),
+    which allows a CORS handler to respond to preflight requests. If
+    the handler does not set a status, the router responds with a
+    `200 Ok`. The options handler does not run if options handling has
+    been disabled with [`Router::without_options_handling`].
+
+    ```
+    # use trillium::Conn;
+    # use trillium_router::{Router, RouterConnExt};
+    let router = Router::new()
+        .get("/some/route", "ok")
+        .post("/some/route", "ok")
+        .with_options_handler(|conn: Conn| async move {
+            let methods = conn
+                .allowed_methods()
+                .unwrap()
+                .iter()
+                .map(|method| method.as_ref())
+                .collect::<Vec<_>>()
+                .join(", ");
+            conn.with_response_header("access-control-allow-methods", methods)
+                .with_status(204)
+        });
+
+    use trillium_testing::{prelude::*, TestConn};
+    let conn = TestConn::build("options", "/some/route", ()).on(&router);
+    assert_status!(&conn, 204);
+    assert_headers!(
+        &conn,
+        "allow" => "GET, POST",
+        "access-control-allow-methods" => "GET, POST"
+    );
+    ```
+    */
+    pub fn with_options_handler(mut self, handler: impl Handler) -> Self {
+        self.set_options_handler(handler);
+        self
+    }
+
+    pub(crate) fn set_options_handler(&mut self, handler: impl Handler) {
+        self.options_handler = Some(Box::new(handler));
+    }
+
+    /**
+    Run the provided handler before the handler of any route that
+    matches in this router. This handler does not run if no route
+    matches, including for OPTIONS requests that are answered by the
+    router's default options handling. If the handler halts the conn,
+    the route's handler is not run. Multiple handlers run in the order
+    they were added.
+
+    This is most useful in combination with [`Router::scope`], to
+    apply authentication or logging to a group of routes.
+
+    ```
+    # use trillium::Conn;
+    # use trillium_router::Router;
+    let router = Router::new()
+        .with_handler_before(|conn: Conn| async move {
+            conn.with_response_header("x-before", "true")
+        })
+        .get("/", "index");
+
+    use trillium_testing::prelude::*;
+    assert_headers!(get("/").on(&router), "x-before" => "true");
+    assert_not_handled!(get("/other").on(&router));
+    ```
+    */
+    pub fn with_handler_before(mut self, handler: impl Handler) -> Self {
+        self.add_handler_before(handler);
+        self
+    }
+
+    pub(crate) fn add_handler_before(&mut self, handler: impl Handler) {
+        self.handlers_before.push(Box::new(handler));
+    }
+
+    /**
+    Configure how this router treats paths with a trailing slash or
+    repeated slashes. See [`SlashPolicy`] for the available policies.
+    By default, trailing slashes are ignored, as are repeated slashes
+    at the start or end of the path, but repeated slashes elsewhere
+    are not.
+
+    A slash policy applies to the path this router sees, so a policy
+    on a router nested within another router or [`Router::scope`]
+    applies to the path relative to the nested router's prefix.
+    Redirects are always to the full canonical request path.
+
+    ```
+    # use trillium::Status;
+    # use trillium_router::{Router, SlashPolicy};
+    let redirecting = Router::new()
+        .with_slash_policy(SlashPolicy::Redirect(Status::PermanentRedirect))
+        .get("/pages/:page", "page");
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/pages/about").on(&redirecting), "page");
+    assert_response!(
+        get("/pages//about/?lang=en").on(&redirecting),
+        308,
+        "",
+        "location" => "/pages/about?lang=en"
+    );
+    assert_not_handled!(get("/unknown/").on(&redirecting));
+
+    let strict = Router::new()
+        .with_slash_policy(SlashPolicy::Strict)
+        .get("/pages/:page", "page");
+    assert_ok!(get("/pages/about").on(&strict), "page");
+    assert_not_handled!(get("/pages/about/").on(&strict));
+
+    let equivalent = Router::new()
+        .with_slash_policy(SlashPolicy::Equivalent)
+        .get("/pages/:page", "page");
+    assert_ok!(get("/pages//about/").on(&equivalent), "page");
+    ```
+    */
+    pub fn with_slash_policy(mut self, slash_policy: SlashPolicy) -> Self {
+        self.set_slash_policy(slash_policy);
+        self
+    }
+
+    pub(crate) fn set_slash_policy(&mut self, slash_policy: SlashPolicy) {
+        self.slash_policy = Some(slash_policy);
+    }
+
+    /**
+    Configures how this router treats a route that is registered for
+    a method and path that an earlier route already handles. See
+    [`DuplicateRoutePolicy`] for the available policies. The default
+    is [`DuplicateRoutePolicy::Warn`]. Routers built with
+    [`Router::scope`] and [`Router::when`] inherit this policy, so it
+    should be set before registering them.
+
+    ```
+    use trillium_router::{DuplicateRoutePolicy, Router};
+    let router = Router::new()
+        .with_duplicate_route_policy(DuplicateRoutePolicy::LastWins)
+        .get("/users/:id", "first")
+        .get("/users/:user_id", "second");
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/users/1").on(&router), "second");
+    ```
+    */
+    pub fn with_duplicate_route_policy(
+        mut self,
+        duplicate_route_policy: DuplicateRoutePolicy,
+    ) -> Self {
+        self.set_duplicate_route_policy(duplicate_route_policy);
+        self
+    }
+
+    pub(crate) fn set_duplicate_route_policy(
+        &mut self,
+        duplicate_route_policy: DuplicateRoutePolicy,
+    ) {
+        self.duplicate_route_policy = duplicate_route_policy;
+    }
+
+    // a new router for a scope or constraint within this router
+    pub(crate) fn nested(&self) -> Router {
+        Router::new().with_duplicate_route_policy(self.duplicate_route_policy)
+    }
+
+    /**
+    Registers a nested router at the provided path prefix. The closure
+    receives a new [`Router`] and returns it with routes relative to
+    the prefix, and with any handlers that should apply to the whole
+    group added with [`Router::with_handler_before`]. Those handlers
+    only run for requests that match one of the scope's routes. Note
+    that a nested scope is itself a route of the enclosing scope, so
+    the enclosing scope's handlers run for any request within the
+    nested scope's prefix.
+
+    The prefix may contain route params, which are available to the
+    handlers within the scope.
+
+    ```
+    # use trillium::{Conn, Status};
+    # use trillium_router::{Router, RouterConnExt};
+    let router = Router::new()
+        .get("/", "public")
+        .scope("/admin", |admin| {
+            admin
+                .with_handler_before(|conn: Conn| async move {
+                    if conn.request_headers().has_header("authorization") {
+                        conn
+                    } else {
+                        conn.with_status(Status::Unauthorized).halt()
+                    }
+                })
+                .get("/users", "users")
+                .get("/users/:id", |conn: Conn| async move {
+                    let id = conn.param("id").unwrap().to_string();
+                    conn.ok(id)
+                })
+        });
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/").on(&router), "public");
+    assert_status!(get("/admin/users").on(&router), 401);
+    assert_ok!(
+        get("/admin/users")
+            .with_request_header("authorization", "secret")
+            .on(&router),
+        "users"
+    );
+    assert_ok!(
+        get("/admin/users/10")
+            .with_request_header("authorization", "secret")
+            .on(&router),
+        "10"
+    );
+    assert_not_handled!(get("/admin/unknown").on(&router));
+    ```
+    */
+    pub fn scope(mut self, prefix: &str, builder: impl FnOnce(Router) -> Router) -> Self {
+        let nested = self.nested();
+        self.add_scope(prefix, builder(nested));
+        self
+    }
+
+    pub(crate) fn add_scope(&mut self, prefix: &str, mut router: Router) {
+        let prefix = prefix.trim_end_matches('/');
+        for (name, route) in mem::take(&mut router.named_routes).iter() {
+            self.named_routes
+                .insert(name, prefixed(prefix, route).expect("could not add route"));
+        }
+        self.add_all(format!("{prefix}/*"), router);
+    }
+
+    /**
+    Registers the routes built by the provided closure so that they
+    only match requests for which the provided [`RouteConstraint`]
+    holds, such as a [`Host`](crate::Host), a
+    [`Header`](crate::Header), or any `Fn(&Conn) -> bool`. When the
+    constraint does not hold, the router continues to later routes as
+    if these routes had not been registered. Constraints can be nested,
+    in which case all of them must hold.
+
+    The closure's router is only used to register routes, and it is
+    not supported to add handlers with
+    [`Router::with_handler_before`], an options handler, or a slash
+    policy to it. The
+    default options handling ignores constraints when determining the
+    methods supported at a path.
+
+    ```
+    # use trillium::Conn;
+    # use trillium_router::{Header, Host, Router};
+    let router = Router::new()
+        .when(Host::new("admin.example.com"), |admin| {
+            admin
+                .get("/", "admin index")
+                .when(Header::present("authorization"), |authorized| {
+                    authorized.delete("/users/:id", "deleted")
+                })
+        })
+        .get("/", "index")
+        .delete("/users/:id", |conn: Conn| async move { conn.with_status(403) });
+
+    use trillium_testing::{prelude::*, TestConn};
+    let admin = |conn: TestConn| conn.with_request_header("host", "admin.example.com");
+    assert_ok!(admin(get("/")).on(&router), "admin index");
+    assert_ok!(get("/").on(&router), "index");
+    assert_ok!(
+        admin(delete("/users/1"))
+            .with_request_header("authorization", "token")
+            .on(&router),
+        "deleted"
+    );
+    assert_status!(admin(delete("/users/1")).on(&router), 403);
+    ```
+
+    # Panics
+
+    This will panic if the closure's router has handlers before, an
+    options handler, or a slash policy.
+    */
+    pub fn when(
+        mut self,
+        constraint: impl RouteConstraint,
+        builder: impl FnOnce(Router) -> Router,
+    ) -> Self {
+        let nested = self.nested();
+        self.add_constrained(Constraint::new(constraint), builder(nested));
+        self
+    }
+
+    pub(crate) fn add_constrained(&mut self, constraint: Constraint, mut router: Router) {
+        assert!(
+            router.handlers_before.is_empty()
+                && router.options_handler.is_none()
+                && router.slash_policy.is_none(),
+            "handlers before, options handlers, and slash policies are not supported within Router::when"
+        );
+
+        for (name, route) in router.named_routes.iter() {
+            self.named_routes.insert(name, route.clone());
+        }
+
+        self.duplicate_routes.append(&mut router.duplicate_routes);
+
+        for (route, (methods, handler, inner)) in router.routefinder.0 {
+            let constraint = match inner {
+                Some(inner) => constraint.and(inner),
+                None => constraint.clone(),
+            };
+            self.routefinder
+                .insert(route, (methods, handler, Some(constraint)));
+        }
+    }
+
     /**
     Another way to build a router, if you don't like the chainable
     interface described in [`Router::new`]. Note that the argument to
@@ -276,8 +792,24 @@ impl Router {
         &'a self,
         method: Method,
         path: &'b str,
-    ) -> Option<Match<'a, 'b, (MethodSelection, Box<dyn Handler>)>> {
-        self.routefinder.best_match(method, path)
+        constraint_matches: impl Fn(&Constraint) -> bool,
+    ) -> Option<Match<'a, 'b, Route>> {
+        self.routefinder
+            .best_match(method, path, constraint_matches)
+    }
+
+    // the routes that matched with a constraint are recorded on the
+    // conn, so the same route is found after the handler has run
+    fn matched_route<'a, 'b>(
+        &'a self,
+        method: Method,
+        path: &'b str,
+        state: &StateSet,
+    ) -> Option<Match<'a, 'b, Route>> {
+        let matched = state.get::<MatchedConstraints>();
+        self.best_match(method, path, |constraint| {
+            matched.is_some_and(|matched| matched.contains(constraint))
+        })
     }
 
     /**
@@ -311,7 +843,92 @@ impl Router {
         R: TryInto<RouteSpec>,
         R::Error: Debug,
     {
-        self.routefinder.add(method, path, handler);
+        self.add_route(path, (method.into(), Box::new(handler), None));
+    }
+
+    fn add_route<R>(&mut self, path: R, route: Route)
+    where
+        R: TryInto<RouteSpec>,
+        R::Error: Debug,
+    {
+        let duplicates = self
+            .routefinder
+            .add_route(path, route, self.duplicate_route_policy);
+        self.duplicate_routes.extend(duplicates);
+    }
+
+    pub(crate) fn add_named<R>(
+        &mut self,
+        path: R,
+        name: &str,
+        method: Method,
+        handler: impl Handler,
+    ) where
+        R: TryInto<RouteSpec>,
+        R::Error: Debug,
+    {
+        let route = path.try_into().expect("could not add route");
+        self.named_routes.insert(name, route.clone());
+        self.add(route, method, handler);
+    }
+
+    /**
+    Registers a named handler for a method other than get, put, post,
+    patch, or delete. See [`Router::url_for`] for usage of the name.
+
+    ```
+    # use trillium::{Conn, Method};
+    # use trillium_router::Router;
+    let router = Router::new()
+        .with_named_route(Method::Checkin, "/some/route", "checkin", "checkin??");
+
+    assert_eq!(router.url_for("checkin", &[]).unwrap(), "/some/route");
+    ```
+    */
+    pub fn with_named_route<M, R>(
+        mut self,
+        method: M,
+        path: R,
+        name: &str,
+        handler: impl Handler,
+    ) -> Self
+    where
+        M: TryInto<Method>,
+        <M as TryInto<Method>>::Error: Debug,
+        R: TryInto<RouteSpec>,
+        R::Error: Debug,
+    {
+        self.add_named(path, name, method.try_into().unwrap(), handler);
+        self
+    }
+
+    /// Builds a path for the route registered with the provided name,
+    /// substituting the provided params. Param values are
+    /// percent-encoded, and the wildcard is provided with the name `"*"`
+    /// and substituted as-is. This returns None if no route has the
+    /// provided name or if a param is missing. Params that are not part
+    /// of the route are ignored.
+    ///
+    /// Routes named within a [`Router::scope`] are available from the
+    /// enclosing router with the scope's prefix. Within a handler, use
+    /// [`RouterConnExt::url_for`](crate::RouterConnExt::url_for).
+    ///
+    /// ```
+    /// # use trillium_router::Router;
+    /// let router = Router::new()
+    ///     .get_named("/users/:id", "user_show", "user")
+    ///     .get_named("/files/*", "file", "file")
+    ///     .scope("/orgs/:org", |org| org.get_named("/", "org_show", "org"));
+    ///
+    /// assert_eq!(router.url_for("user_show", &[("id", "10")]).unwrap(), "/users/10");
+    /// assert_eq!(router.url_for("user_show", &[("id", "a b/c")]).unwrap(), "/users/a%20b%2Fc");
+    /// assert_eq!(router.url_for("file", &[("*", "a/b.txt")]).unwrap(), "/files/a/b.txt");
+    /// assert_eq!(router.url_for("org_show", &[("org", "trillium")]).unwrap(), "/orgs/trillium");
+    /// assert!(router.url_for("user_show", &[]).is_none());
+    /// assert!(router.url_for("unknown", &[]).is_none());
+    /// ```
+    pub fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Option<String> {
+        self.named_routes.url_for(name, params)
     }
 
     pub(crate) fn add_any<R>(&mut self, methods: &[Method], path: R, handler: impl Handler)
@@ -319,7 +936,7 @@ impl Router {
         R: TryInto<RouteSpec>,
         R::Error: Debug,
     {
-        self.routefinder.add(methods, path, handler)
+        self.add_route(path, (methods.into(), Box::new(handler), None));
     }
 
     pub(crate) fn add_all<R>(&mut self, path: R, handler: impl Handler)
@@ -327,7 +944,7 @@ impl Router {
         R: TryInto<RouteSpec>,
         R::Error: Debug,
     {
-        self.routefinder.add((), path, handler);
+        self.add_route(path, (().into(), Box::new(handler), None));
     }
 
     /**
@@ -404,18 +1021,94 @@ impl Router {
     method!(put, Put);
     method!(delete, Delete);
     method!(patch, Patch);
+    named_method!(get_named, Get);
+    named_method!(post_named, Post);
+    named_method!(put_named, Put);
+    named_method!(delete_named, Delete);
+    named_method!(patch_named, Patch);
 }
 
-#[async_trait]
-impl Handler for Router {
-    async fn run(&self, mut conn: Conn) -> Conn {
+// two route specs are equivalent if they differ only in param names
+fn equivalent(a: &RouteSpec, b: &RouteSpec) -> bool {
+    a.segments().len() == b.segments().len()
+        && a.segments()
+            .iter()
+            .zip(b.segments())
+            .all(|segments| match segments {
+                (Segment::Param(_), Segment::Param(_)) => true,
+                (a, b) => a == b,
+            })
+}
+
+fn prefixed(prefix: &str, route: &RouteSpec) -> Result<RouteSpec, String> {
+    format!("{prefix}{route}").parse()
+}
+
+fn allow(methods: &[Method]) -> String {
+    methods
+        .iter()
+        .map(|m| m.as_ref())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Router {
+    // the methods supported at this path if it matches routes in this
+    // router but not for this method, when 405 responses are enabled
+    fn method_not_allowed(&self, method: Method, path: &str) -> Option<Vec<Method>> {
+        if !self.handle_method_not_allowed || path == "*" {
+            return None;
+        }
+
+        let allowed_methods = self.routefinder.methods_matching(path);
+        if allowed_methods.is_empty() || allowed_methods.contains(&method) {
+            None
+        } else {
+            Some(allowed_methods.into_iter().collect())
+        }
+    }
+
+    // the path to route, or None if a non-canonical path should not be
+    // routed under this router's slash policy
+    fn routing_path<'a>(&self, path: &'a str) -> Option<Cow<'a, str>> {
+        match (self.slash_policy, canonicalize(path)) {
+            (Some(SlashPolicy::Equivalent), Some(canonical)) => Some(Cow::Owned(canonical)),
+            (Some(_), Some(_)) => None,
+            _ => Some(Cow::Borrowed(path)),
+        }
+    }
+
+    fn redirect(&self, conn: Conn, canonical: &str, status: Status) -> Conn {
+        if self
+            .best_match(conn.method(), canonical, |constraint| {
+                constraint.matches(&conn)
+            })
+            .is_none()
+        {
+            return conn;
+        }
+
+        let path = conn.inner().path();
+        let mut location = canonicalize(path).unwrap_or_else(|| path.to_string());
+        if !conn.querystring().is_empty() {
+            location.push('?');
+            location.push_str(conn.querystring());
+        }
+
+        conn.with_status(status)
+            .with_response_header(KnownHeaderName::Location, location)
+            .halt()
+    }
+
+    async fn route(&self, mut conn: Conn) -> Conn {
         let method = conn.method();
         let original_captures = conn.take_state();
         let path = conn.path();
         let mut has_path = false;
 
-        if let Some(m) = self.best_match(conn.method(), path) {
+        if let Some(m) = self.best_match(method, path, |constraint| constraint.matches(&conn)) {
             let mut captures = m.captures().into_owned();
+            let constraint = m.2.as_ref().map(Constraint::id);
 
             let route = m.route().clone();
 
@@ -425,70 +1118,174 @@ impl Handler for Router {
             }
 
             log::debug!("running {}: {}", m.route(), m.1.name());
-            let mut new_conn = m
-                .handler()
-                .1
-                .run({
-                    if let Some(wildcard) = captures.wildcard() {
-                        conn.push_path(String::from(wildcard));
-                        has_path = true;
-                    }
+            let handler = &m.handler().1;
+            if let Some(wildcard) = captures.wildcard() {
+                conn.push_path(String::from(wildcard));
+                has_path = true;
+            }
 
-                    conn.with_state(CapturesNewType(captures))
-                        .with_state(RouteSpecNewType(route))
-                })
-                .await;
+            if let Some(constraint) = constraint {
+                let mut matched = conn.take_state::<MatchedConstraints>().unwrap_or_default();
+                matched.0.insert(constraint);
+                conn.insert_state(matched);
+            }
+
+            let mut new_conn = conn
+                .with_state(CapturesNewType(captures))
+                .with_state(RouteSpecNewType(route));
+
+            for handler in &self.handlers_before {
+                if new_conn.is_halted() {
+                    break;
+                }
+                new_conn = handler.run(new_conn).await;
+            }
+
+            if !new_conn.is_halted() {
+                new_conn = handler.run(new_conn).await;
+            }
 
             if has_path {
                 new_conn.pop_path();
             }
             new_conn
         } else if method == Method::Options && self.handle_options {
-            let allow = self
+            let allowed_methods = self
                 .routefinder
                 .methods_matching(path)
-                .iter()
-                .map(|m| m.as_ref())
-                .collect::<Vec<_>>()
-                .join(", ");
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            let mut conn =
+                conn.with_response_header(KnownHeaderName::Allow, allow(&allowed_methods));
+
+            if let Some(options_handler) = &self.options_handler {
+                conn = options_handler
+                    .run(conn.with_state(AllowedMethodsNewType(allowed_methods)))
+                    .await;
+            }
 
-            return conn
-                .with_response_header(KnownHeaderName::Allow, allow)
-                .with_status(200)
-                .halt();
+            if conn.status().is_none() {
+                conn.set_status(200);
+            }
+
+            conn.halt()
+        } else if let Some(allowed_methods) = self.method_not_allowed(method, path) {
+            log::debug!("{} did not match any route for {method}", conn.path());
+            conn.with_response_header(KnownHeaderName::Allow, allow(&allowed_methods))
+                .with_status(Status::MethodNotAllowed)
+                .halt()
         } else {
             log::debug!("{} did not match any route", conn.path());
             conn
         }
     }
 
-    async fn before_send(&self, conn: Conn) -> Conn {
+    async fn route_before_send(&self, mut conn: Conn) -> Conn {
         let path = conn.path();
-        if let Some(m) = self.best_match(conn.method(), path) {
-            m.handler().1.before_send(conn).await
+        if let Some(m) = self.matched_route(conn.method(), path, conn.as_ref()) {
+            let handler = &m.handler().1;
+            let wildcard = m.captures().wildcard().map(String::from);
+            let has_path = wildcard.is_some();
+            if let Some(wildcard) = wildcard {
+                conn.push_path(wildcard);
+            }
+
+            conn = handler.before_send(conn).await;
+            for before in self.handlers_before.iter().rev() {
+                conn = before.before_send(conn).await;
+            }
+
+            if has_path {
+                conn.pop_path();
+            }
+            conn
+        } else if let Some(options_handler) = self
+            .options_handler
+            .as_ref()
+            .filter(|_| conn.method() == Method::Options && self.handle_options)
+        {
+            options_handler.before_send(conn).await
         } else {
             conn
         }
     }
+}
+
+#[async_trait]
+impl Handler for Router {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        if !self.named_routes.is_empty() && conn.state::<NamedRoutes>().is_none() {
+            conn.insert_state(self.named_routes.clone());
+        }
+
+        match (self.slash_policy, canonicalize(conn.path())) {
+            (Some(SlashPolicy::Equivalent), Some(canonical)) => {
+                conn.push_path(canonical);
+                let mut conn = self.route(conn).await;
+                conn.pop_path();
+                conn
+            }
+
+            (Some(SlashPolicy::Redirect(status)), Some(canonical)) => {
+                self.redirect(conn, &canonical, status)
+            }
+
+            (Some(SlashPolicy::Strict), Some(_)) => conn,
+
+            _ => self.route(conn).await,
+        }
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        match (self.slash_policy, canonicalize(conn.path())) {
+            (Some(SlashPolicy::Equivalent), Some(canonical)) => {
+                conn.push_path(canonical);
+                let mut conn = self.route_before_send(conn).await;
+                conn.pop_path();
+                conn
+            }
+
+            (Some(_), Some(_)) => conn,
+
+            _ => self.route_before_send(conn).await,
+        }
+    }
 
     fn has_upgrade(&self, upgrade: &Upgrade) -> bool {
-        if let Some(m) = self.best_match(*upgrade.method(), upgrade.path()) {
-            m.1.has_upgrade(upgrade)
+        let Some(path) = self.routing_path(upgrade.path()) else {
+            return false;
+        };
+
+        if let Some(m) = self.matched_route(*upgrade.method(), &path, upgrade.state()) {
+            self.handlers_before
+                .iter()
+                .any(|handler| handler.has_upgrade(upgrade))
+                || m.1.has_upgrade(upgrade)
         } else {
             false
         }
     }
 
     async fn upgrade(&self, upgrade: Upgrade) {
-        self.best_match(*upgrade.method(), upgrade.path())
+        let path = self.routing_path(upgrade.path()).unwrap();
+        let handler = &self
+            .matched_route(*upgrade.method(), &path, upgrade.state())
             .unwrap()
             .handler()
-            .1
-            .upgrade(upgrade)
-            .await
+            .1;
+
+        match self
+            .handlers_before
+            .iter()
+            .find(|handler| handler.has_upgrade(&upgrade))
+        {
+            Some(before) => before.upgrade(upgrade).await,
+            None => handler.upgrade(upgrade).await,
+        }
     }
 
-    fn name(&self) -> std::borrow::Cow<'static, str> {
+    fn name(&self) -> Cow<'static, str> {
         "Router".into()
     }
 
@@ -510,10 +1307,32 @@ impl Handler for Router {
         // contents into this future and then replace it, and the
         // performance impacts of doing so are unimportant as it is
         // part of app boot.
+        let duplicate_routes = mem::take(&mut self.duplicate_routes);
+        match self.duplicate_route_policy {
+            DuplicateRoutePolicy::Warn => {
+                for duplicate_route in &duplicate_routes {
+                    log::warn!("duplicate route: {duplicate_route}");
+                }
+            }
+            DuplicateRoutePolicy::Panic if !duplicate_routes.is_empty() => {
+                panic!("duplicate routes: {}", duplicate_routes.join("; "));
+            }
+            _ => {}
+        }
+
+        for handler in &mut self.handlers_before {
+            handler.init(info).await;
+        }
+
         let routefinder = mem::take(&mut self.routefinder);
-        for (route, (methods, mut handler)) in routefinder.0 {
+        for (route, (methods, mut handler, constraint)) in routefinder.0 {
             handler.init(info).await;
-            self.routefinder.add(methods, route, handler);
+            self.routefinder
+                .insert(route, (methods, handler, constraint));
+        }
+
+        if let Some(options_handler) = &mut self.options_handler {
+            options_handler.init(info).await;
         }
     }
 }
@@ -523,8 +1342,23 @@ impl Debug for Router {
         f.write_str("Router ")?;
         let mut set = f.debug_set();
 
-        for (route, (methods, handler)) in &self.routefinder.0 {
-            set.entry(&format_args!("{} {} -> {}", methods, route, handler.name()));
+        for handler in &self.handlers_before {
+            set.entry(&format_args!("BEFORE -> {}", handler.name()));
+        }
+
+        for (route, (methods, handler, constraint)) in &self.routefinder.0 {
+            let when = if constraint.is_some() { " (when)" } else { "" };
+            set.entry(&format_args!(
+                "{} {}{} -> {}",
+                methods,
+                route,
+                when,
+                handler.name()
+            ));
+        }
+
+        if let Some(options_handler) = &self.options_handler {
+            set.entry(&format_args!("OPTIONS -> {}", options_handler.name()));
         }
         set.finish()
     }
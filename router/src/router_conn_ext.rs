@@ -1,5 +1,7 @@
-use crate::{CapturesNewType, RouteSpecNewType};
-use trillium::Conn;
+use crate::{named_routes::NamedRoutes, AllowedMethodsNewType, CapturesNewType, RouteSpecNewType};
+use percent_encoding::percent_decode_str;
+use std::borrow::Cow;
+use trillium::{Conn, Method};
 
 /**
 Extends trillium::Conn with accessors for router params.
@@ -32,6 +34,98 @@ pub trait RouterConnExt {
 
     fn param<'a>(&'a self, param: &str) -> Option<&'a str>;
 
+    /**
+    Retrieves a captured param from the conn, percent-decoded. Any
+    invalid utf-8 is replaced with the unicode replacement character.
+
+    ```
+    use trillium::{conn_unwrap, Conn};
+    use trillium_router::{Router, RouterConnExt};
+
+    let router = Router::new().get("/pages/:page_name", |conn: Conn| async move {
+        let page_name = conn_unwrap!(conn.param_decoded("page_name"), conn);
+        let content = format!("you have reached the page named {}", page_name);
+        conn.ok(content)
+    });
+
+    use trillium_testing::prelude::*;
+    assert_ok!(
+        get("/pages/trillium%20rs").on(&router),
+        "you have reached the page named trillium rs"
+    );
+    ```
+    */
+    fn param_decoded<'a>(&'a self, param: &str) -> Option<Cow<'a, str>>;
+
+    /**
+    Retrieves all captured params from the conn as raw name-value
+    pairs, in the order they appear in the matched route. This will be
+    empty if the matched route has no params.
+
+    ```
+    use trillium::Conn;
+    use trillium_router::{Router, RouterConnExt};
+
+    let router = Router::new().get("/:org/:repo", |conn: Conn| async move {
+        let content = conn
+            .raw_params()
+            .into_iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        conn.ok(content)
+    });
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/trillium-rs/trillium").on(&router), "org=trillium-rs repo=trillium");
+    ```
+    */
+    fn raw_params(&self) -> Vec<(&str, &str)>;
+
+    /**
+    Deserializes all captured params, percent-decoded, into `T`.
+    Requires the `serde` cargo feature. Params are deserialized as:
+
+    * a struct or map, keyed by param name
+    * a tuple or sequence, in the order they appear in the route
+    * any other type, such as a number or string, if the route has
+      exactly one param
+
+    ```
+    use trillium::{Conn, Status};
+    use trillium_router::{Router, RouterConnExt};
+
+    #[derive(serde::Deserialize)]
+    struct Issue {
+        repo: String,
+        number: u64,
+    }
+
+    let router = Router::new()
+        .get("/:repo/issues/:number", |conn: Conn| async move {
+            match conn.params::<Issue>() {
+                Ok(issue) => conn.ok(format!("{} issue {}", issue.repo, issue.number)),
+                Err(e) => conn.with_status(Status::BadRequest).with_body(e.to_string()).halt(),
+            }
+        })
+        .get("/issues/:id", |conn: Conn| async move {
+            let id: u64 = conn.params().unwrap();
+            conn.ok(format!("issue {id}"))
+        });
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/trillium%20rs/issues/10").on(&router), "trillium rs issue 10");
+    assert_ok!(get("/issues/10").on(&router), "issue 10");
+    assert_response!(
+        get("/trillium/issues/ten").on(&router),
+        Status::BadRequest,
+        r#"number: could not parse "ten" as u64"#
+    );
+    ```
+    */
+    #[cfg(feature = "serde")]
+    fn params<T: serde::de::DeserializeOwned>(&self) -> Result<T, crate::ParamsError>;
+
     /// Retrieves the wildcard match from the conn. Note that this will
     /// only be Some if the matched route contains a wildcard, as
     /// expressed by a "*" in the routefinder route spec.
@@ -72,6 +166,33 @@ pub trait RouterConnExt {
     /// );
     /// ```
     fn route(&self) -> Option<&str>;
+
+    /// Retrieves the http methods supported at the requested path.
+    /// This is only available to an options handler configured with
+    /// [`Router::with_options_handler`](crate::Router::with_options_handler).
+    fn allowed_methods(&self) -> Option<&[Method]>;
+
+    /**
+    Builds a path for the route registered with the provided name on
+    the outermost router that handled this conn. See
+    [`Router::url_for`](crate::Router::url_for) for details.
+
+    ```
+    use trillium::Conn;
+    use trillium_router::{Router, RouterConnExt};
+
+    let router = Router::new()
+        .get_named("/users/:id", "user_show", "user")
+        .post("/users", |conn: Conn| async move {
+            let location = conn.url_for("user_show", &[("id", "10")]).unwrap();
+            conn.with_status(303).with_response_header("location", location)
+        });
+
+    use trillium_testing::prelude::*;
+    assert_response!(post("/users").on(&router), 303, "", "location" => "/users/10");
+    ```
+    */
+    fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Option<String>;
 }
 
 impl RouterConnExt for Conn {
@@ -79,6 +200,29 @@ impl RouterConnExt for Conn {
         self.state().and_then(|CapturesNewType(p)| p.get(param))
     }
 
+    fn param_decoded<'a>(&'a self, param: &str) -> Option<Cow<'a, str>> {
+        self.param(param)
+            .map(|value| percent_decode_str(value).decode_utf8_lossy())
+    }
+
+    fn raw_params(&self) -> Vec<(&str, &str)> {
+        self.state()
+            .map(|CapturesNewType(p)| p.iter().collect())
+            .unwrap_or_default()
+    }
+
+    #[cfg(feature = "serde")]
+    fn params<T: serde::de::DeserializeOwned>(&self) -> Result<T, crate::ParamsError> {
+        let params = self
+            .raw_params()
+            .into_iter()
+            .map(|(name, value)| {
+                crate::params::Param::new(name, percent_decode_str(value).decode_utf8_lossy())
+            })
+            .collect();
+        T::deserialize(crate::params::Params(params))
+    }
+
     fn wildcard(&self) -> Option<&str> {
         self.state().and_then(|CapturesNewType(p)| p.wildcard())
     }
@@ -86,6 +230,15 @@ impl RouterConnExt for Conn {
     fn route(&self) -> Option<&str> {
         self.state().and_then(|RouteSpecNewType(r)| r.source())
     }
+
+    fn allowed_methods(&self) -> Option<&[Method]> {
+        self.state()
+            .map(|AllowedMethodsNewType(methods)| methods.as_slice())
+    }
+
+    fn url_for(&self, name: &str, params: &[(&str, &str)]) -> Option<String> {
+        self.state::<NamedRoutes>()?.url_for(name, params)
+    }
 }
 
 // ```
@@ -1,4 +1,4 @@
-use crate::Router;
+use crate::{constraint::Constraint, DuplicateRoutePolicy, RouteConstraint, Router, SlashPolicy};
 use routefinder::RouteSpec;
 use std::fmt::Debug;
 use trillium::{Handler, Method};
@@ -52,6 +52,46 @@ assert_not_handled!(",
     };
 }
 
+macro_rules! named_method_ref {
+    ($fn_name:ident, $method:ident) => {
+        named_method_ref!(
+            $fn_name,
+            $method,
+            concat!(
+                "Registers a named handler for the ",
+                stringify!($method),
+                " http method. See [`Router::url_for`] for usage of the name.
+
+```
+# use trillium::Conn;
+# use trillium_router::Router;
+let router = Router::build(|mut router| {
+    router.",
+                stringify!($fn_name),
+                "(\"/some/:param\", \"some_route\", |conn: Conn| async move {
+        conn.ok(\"success\")
+    });
+});
+
+assert_eq!(router.url_for(\"some_route\", &[(\"param\", \"value\")]).unwrap(), \"/some/value\");
+```
+"
+            )
+        );
+    };
+
+    ($fn_name:ident, $method:ident, $doc_comment:expr) => {
+        #[doc = $doc_comment]
+        pub fn $fn_name<R>(&mut self, path: R, name: &str, handler: impl Handler)
+        where
+            R: TryInto<RouteSpec>,
+            R::Error: Debug,
+        {
+            self.0.add_named(path, name, Method::$method, handler);
+        }
+    };
+}
+
 /**
 # A `&mut Router` for use with `Router::build`
 
@@ -66,6 +106,11 @@ impl<'r> RouterRef<'r> {
     method_ref!(put, Put);
     method_ref!(delete, Delete);
     method_ref!(patch, Patch);
+    named_method_ref!(get_named, Get);
+    named_method_ref!(post_named, Post);
+    named_method_ref!(put_named, Put);
+    named_method_ref!(delete_named, Delete);
+    named_method_ref!(patch_named, Patch);
 
     /**
     Appends the handler to all (get, post, put, delete, and patch) methods.
@@ -180,4 +225,104 @@ impl<'r> RouterRef<'r> {
     pub fn set_options_handling(&mut self, options_enabled: bool) {
         self.0.set_options_handling(options_enabled);
     }
+
+    /**
+    enable or disable responding with `405 Method Not Allowed` to
+    requests for a path that matches routes in this router but not for
+    the request method. see [`Router::with_method_not_allowed`] for
+    further explanation.
+
+    default: disabled
+     */
+    pub fn set_method_not_allowed(&mut self, enabled: bool) {
+        self.0.set_method_not_allowed(enabled);
+    }
+
+    /**
+    run the provided handler on OPTIONS requests that do not match an
+    explicit OPTIONS route. see [`Router::with_options_handler`] for
+    further explanation.
+     */
+    pub fn set_options_handler(&mut self, handler: impl Handler) {
+        self.0.set_options_handler(handler);
+    }
+
+    /**
+    run the provided handler before the handler of any route that
+    matches in this router. see [`Router::with_handler_before`] for
+    further explanation.
+     */
+    pub fn add_handler_before(&mut self, handler: impl Handler) {
+        self.0.add_handler_before(handler);
+    }
+
+    /**
+    configure how this router treats paths with a trailing slash or
+    repeated slashes. see [`Router::with_slash_policy`] for further
+    explanation.
+     */
+    pub fn set_slash_policy(&mut self, slash_policy: SlashPolicy) {
+        self.0.set_slash_policy(slash_policy);
+    }
+
+    /**
+    configure how this router treats a route that is registered for a
+    method and path that an earlier route already handles. see
+    [`Router::with_duplicate_route_policy`] for further explanation.
+     */
+    pub fn set_duplicate_route_policy(&mut self, duplicate_route_policy: DuplicateRoutePolicy) {
+        self.0.set_duplicate_route_policy(duplicate_route_policy);
+    }
+
+    /**
+    register a nested router at the provided path prefix, built with
+    the provided closure. see [`Router::scope`] for further
+    explanation.
+
+    ```
+    # use trillium::Conn;
+    # use trillium_router::Router;
+    let router = Router::build(|mut router| {
+        router.scope("/admin", |mut admin| {
+            admin.add_handler_before(|conn: Conn| async move {
+                conn.with_response_header("x-admin", "true")
+            });
+            admin.get("/users", "users");
+        });
+    });
+
+    use trillium_testing::prelude::*;
+    assert_response!(get("/admin/users").on(&router), 200, "users", "x-admin" => "true");
+    ```
+     */
+    pub fn scope(&mut self, prefix: &str, builder: impl Fn(RouterRef)) {
+        let mut router = self.0.nested();
+        builder(RouterRef::new(&mut router));
+        self.0.add_scope(prefix, router);
+    }
+
+    /**
+    register the routes built with the provided closure so that they
+    only match requests for which the provided [`RouteConstraint`]
+    holds. see [`Router::when`] for further explanation.
+
+    ```
+    # use trillium_router::{Host, Router};
+    let router = Router::build(|mut router| {
+        router.when(Host::new("api.example.com"), |mut api| {
+            api.get("/", "api");
+        });
+        router.get("/", "index");
+    });
+
+    use trillium_testing::prelude::*;
+    assert_ok!(get("/").with_request_header("host", "api.example.com").on(&router), "api");
+    assert_ok!(get("/").on(&router), "index");
+    ```
+     */
+    pub fn when(&mut self, constraint: impl RouteConstraint, builder: impl Fn(RouterRef)) {
+        let mut router = self.0.nested();
+        builder(RouterRef::new(&mut router));
+        self.0.add_constrained(Constraint::new(constraint), router);
+    }
 }
@@ -0,0 +1,55 @@
+use trillium::Status;
+
+/**
+# How a [`Router`](crate::Router) treats non-canonical paths
+
+A path is canonical if it does not end with a slash (other than the
+root path `/`) and does not contain repeated slashes. Without a slash
+policy, the router matches paths as routefinder does, which ignores
+trailing slashes but not repeated slashes within a path.
+
+Configure this with
+[`Router::with_slash_policy`](crate::Router::with_slash_policy).
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlashPolicy {
+    /**
+    Match non-canonical paths as if they were canonical, so `/foo/`
+    and `//foo` are equivalent to `/foo`. The route handler, and any
+    nested router, sees the canonical path.
+    */
+    Equivalent,
+
+    /**
+    Redirect non-canonical paths to the canonical path with the
+    provided status if a route matches the canonical path, preserving
+    the query string. This is usually [`Status::MovedPermanently`]
+    (301), or [`Status::PermanentRedirect`] (308) to require clients
+    to preserve the request method and body.
+    */
+    Redirect(Status),
+
+    /// Only match canonical paths.
+    Strict,
+}
+
+/// returns the canonical form of this path, or None if it is already canonical
+pub(crate) fn canonicalize(path: &str) -> Option<String> {
+    let has_trailing_slash = path.len() > 1 && path.ends_with('/');
+    if !has_trailing_slash && !path.contains("//") {
+        return None;
+    }
+
+    let mut canonical = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c != '/' || !canonical.ends_with('/') {
+            canonical.push(c);
+        }
+    }
+
+    if canonical.len() > 1 && canonical.ends_with('/') {
+        canonical.pop();
+    }
+
+    Some(canonical)
+}
@@ -0,0 +1,114 @@
+use trillium::{async_trait, Conn, Handler};
+use trillium_router::{Header, Host, Router};
+use trillium_testing::prelude::*;
+
+#[test]
+fn falls_through_to_later_routes() {
+    let router = Router::new()
+        .when(Header::equals("x-version", "2"), |v2| {
+            v2.get("/widgets", "v2 widgets")
+        })
+        .when(
+            |conn: &Conn| conn.querystring() == "preview",
+            |preview| {
+                preview
+                    .get("/widgets", "preview widgets")
+                    .post("/widgets", "created")
+            },
+        )
+        .get("/widgets", "widgets");
+
+    assert_ok!(get("/widgets").on(&router), "widgets");
+    assert_ok!(
+        get("/widgets")
+            .with_request_header("x-version", "2")
+            .on(&router),
+        "v2 widgets"
+    );
+    assert_ok!(
+        get("/widgets")
+            .with_request_header("x-version", "3")
+            .on(&router),
+        "widgets"
+    );
+    assert_ok!(get("/widgets?preview").on(&router), "preview widgets");
+    assert_ok!(post("/widgets?preview").on(&router), "created");
+    assert_not_handled!(post("/widgets").on(&router));
+}
+
+#[test]
+fn host_wildcards() {
+    let router = Router::new()
+        .when(Host::new("*.example.com"), |sub| sub.get("/", "subdomain"))
+        .get("/", "default");
+
+    let on_host = |host| get("/").with_request_header("host", host).on(&router);
+    assert_ok!(on_host("a.example.com"), "subdomain");
+    assert_ok!(on_host("a.b.example.com:443"), "subdomain");
+    assert_ok!(on_host("example.com"), "default");
+    assert_ok!(on_host("badexample.com"), "default");
+    assert_ok!(on_host("example.com.evil"), "default");
+}
+
+#[test]
+fn nested_constraints_must_all_hold() {
+    let router = Router::build(|mut router| {
+        router.when(Header::present("x-a"), |mut a| {
+            a.when(Header::present("x-b"), |mut b| {
+                b.get("/", "a and b");
+            });
+            a.get("/", "a");
+        });
+        router.get("/", "neither");
+    });
+
+    assert_ok!(get("/").on(&router), "neither");
+    assert_ok!(
+        get("/").with_request_header("x-b", "").on(&router),
+        "neither"
+    );
+    assert_ok!(get("/").with_request_header("x-a", "").on(&router), "a");
+    assert_ok!(
+        get("/")
+            .with_request_header("x-a", "")
+            .with_request_header("x-b", "")
+            .on(&router),
+        "a and b"
+    );
+}
+
+struct RemovesHeader(&'static str);
+#[async_trait]
+impl Handler for RemovesHeader {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        conn.request_headers_mut().remove("x-beta");
+        conn.ok(self.0)
+    }
+
+    async fn before_send(&self, conn: Conn) -> Conn {
+        conn.with_response_header("x-before-send", self.0)
+    }
+}
+
+#[test]
+fn before_send_uses_the_matched_route() {
+    let router = Router::new()
+        .when(Header::present("x-beta"), |beta| {
+            beta.get("/", RemovesHeader("beta"))
+        })
+        .get("/", RemovesHeader("stable"));
+
+    assert_response!(
+        get("/").with_request_header("x-beta", "").on(&router),
+        200,
+        "beta",
+        "x-before-send" => "beta"
+    );
+    assert_response!(get("/").on(&router), 200, "stable", "x-before-send" => "stable");
+}
+
+#[test]
+#[should_panic(expected = "not supported within Router::when")]
+fn handlers_before_within_when() {
+    Router::new().when(Header::present("x-a"), |a| a.with_handler_before("nope"));
+}
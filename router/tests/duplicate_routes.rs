@@ -0,0 +1,83 @@
+use trillium::Conn;
+use trillium_router::{DuplicateRoutePolicy, Header, Router, RouterConnExt};
+use trillium_testing::prelude::*;
+
+#[test]
+fn earlier_route_wins_by_default() {
+    let mut router = Router::new()
+        .get("/users/:id", "first")
+        .get("/users/:user_id", "second")
+        .all("/users/:id", "all");
+    init(&mut router);
+
+    assert_ok!(get("/users/1").on(&router), "first");
+    assert_ok!(post("/users/1").on(&router), "all");
+}
+
+#[test]
+fn last_wins() {
+    let router = Router::new()
+        .with_duplicate_route_policy(DuplicateRoutePolicy::LastWins)
+        .any(&["get", "post"], "/widgets", "first")
+        .get("/widgets", "second")
+        .all("/pages/:page", "all")
+        .put("/pages/:slug", |conn: Conn| async move {
+            let slug = conn.param("slug").unwrap().to_string();
+            conn.ok(slug)
+        });
+
+    assert_ok!(get("/widgets").on(&router), "second");
+    assert_ok!(post("/widgets").on(&router), "first");
+    assert_ok!(put("/pages/about").on(&router), "about");
+    assert_ok!(delete("/pages/about").on(&router), "all");
+}
+
+#[test]
+fn last_wins_is_inherited() {
+    let router = Router::new()
+        .with_duplicate_route_policy(DuplicateRoutePolicy::LastWins)
+        .scope("/admin", |admin| admin.get("/", "first").get("/", "second"));
+
+    assert_ok!(get("/admin/").on(&router), "second");
+}
+
+#[test]
+#[should_panic(expected = "GET /users/:user_id duplicates GET /users/:id")]
+fn panic() {
+    let mut router = Router::new()
+        .with_duplicate_route_policy(DuplicateRoutePolicy::Panic)
+        .get("/users/:id", "first")
+        .get("/users/:user_id", "second");
+    init(&mut router);
+}
+
+#[test]
+#[should_panic(expected = "duplicate routes")]
+fn panic_within_constraint() {
+    let mut router = Router::new()
+        .with_duplicate_route_policy(DuplicateRoutePolicy::Panic)
+        .when(Header::present("authorization"), |authorized| {
+            authorized.get("/", "first").all("/", "second")
+        });
+    init(&mut router);
+}
+
+#[test]
+fn distinct_routes_do_not_panic() {
+    let mut router = Router::new()
+        .with_duplicate_route_policy(DuplicateRoutePolicy::Panic)
+        .get("/users/:id", "user")
+        .post("/users/:id", "update")
+        .get("/users/:id/edit", "edit")
+        .get("/users/new", "new")
+        .when(Header::present("authorization"), |authorized| {
+            authorized.get("/users/:id", "authorized")
+        })
+        .when(Header::present("x-admin"), |admin| {
+            admin.get("/users/:id", "admin")
+        });
+    init(&mut router);
+
+    assert_ok!(get("/users/1").on(&router), "user");
+    assert_ok!(get("/users/new").on(&router), "new");
+}
@@ -0,0 +1,87 @@
+use trillium::{Conn, Handler};
+use trillium_forwarding::Forwarding;
+use trillium_router::{HostRouter, Router};
+use trillium_testing::prelude::*;
+
+fn app() -> HostRouter {
+    HostRouter::new()
+        .with_host("*.example.com", "subdomain")
+        .with_host("*.api.example.com", "api subdomain")
+        .with_host("api.example.com", Router::new().get("/users", "users"))
+}
+
+fn host(host: &'static str) -> trillium_testing::TestConn {
+    get("/users").with_request_header("host", host)
+}
+
+#[test]
+fn precedence() {
+    let app = app();
+    assert_ok!(host("api.example.com").on(&app), "users");
+    assert_ok!(host("API.EXAMPLE.COM:443").on(&app), "users");
+    assert_ok!(host("v1.api.example.com").on(&app), "api subdomain");
+    assert_ok!(host("www.example.com").on(&app), "subdomain");
+    assert_ok!(host("a.b.example.com").on(&app), "subdomain");
+}
+
+#[test]
+fn unmatched_hosts() {
+    let app = app();
+    assert_not_handled!(host("example.com").on(&app));
+    assert_not_handled!(host("notexample.com").on(&app));
+    assert_not_handled!(get("/").on(&app));
+
+    // the nested router does not handle this path
+    assert_not_handled!(get("/other")
+        .with_request_header("host", "api.example.com")
+        .on(&app));
+
+    let app = self::app().with_default("default");
+    assert_ok!(host("example.com").on(&app), "default");
+    assert_ok!(get("/").on(&app), "default");
+}
+
+#[test]
+fn before_send() {
+    struct BeforeSend;
+    #[trillium::async_trait]
+    impl Handler for BeforeSend {
+        async fn run(&self, conn: Conn) -> Conn {
+            conn.ok("ok")
+        }
+
+        async fn before_send(&self, conn: Conn) -> Conn {
+            conn.with_response_header("x-before-send", "true")
+        }
+    }
+
+    let app = HostRouter::new()
+        .with_host("example.com", BeforeSend)
+        .with_default("default");
+    assert_headers!(host("example.com").on(&app), "x-before-send" => "true");
+    assert_headers!(host("example.org").on(&app), "x-before-send" => None);
+}
+
+#[test]
+fn forwarded_host() {
+    let app = (Forwarding::trust_always(), app());
+    assert_ok!(
+        host("proxy.internal")
+            .with_request_header("x-forwarded-host", "api.example.com")
+            .on(&app),
+        "users"
+    );
+
+    assert_ok!(
+        host("proxy.internal")
+            .with_request_header("forwarded", "host=www.example.com")
+            .on(&app),
+        "subdomain"
+    );
+}
+
+#[test]
+#[should_panic(expected = "must not be empty or contain a port")]
+fn patterns_with_ports() {
+    let _ = HostRouter::new().with_host("example.com:8080", "ok");
+}
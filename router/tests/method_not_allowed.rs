@@ -0,0 +1,102 @@
+use trillium::Conn;
+use trillium_router::{Header, Router, RouterConnExt};
+use trillium_testing::{prelude::*, TestConn};
+
+fn app() -> Router {
+    Router::new()
+        .with_method_not_allowed()
+        .get("/users", "users")
+        .post("/users", "created")
+        .get("/users/:id", |conn: Conn| async move {
+            let id = conn.param("id").unwrap().to_string();
+            conn.ok(id)
+        })
+        .delete("/users/:id", "deleted")
+}
+
+#[test]
+fn disabled_by_default() {
+    let router = Router::new().get("/users", "users");
+    assert_not_handled!(post("/users").on(&router));
+}
+
+#[test]
+fn method_not_allowed() {
+    let app = app();
+    assert_ok!(get("/users").on(&app), "users");
+    assert_ok!(get("/users/1").on(&app), "1");
+
+    assert_response!(put("/users").on(&app), 405, "", "allow" => "GET, POST");
+    assert_response!(
+        patch("/users/1").on(&app),
+        405,
+        "",
+        "allow" => "DELETE, GET"
+    );
+    assert_response!(
+        TestConn::build("checkin", "/users", ()).on(&app),
+        405,
+        "",
+        "allow" => "GET, POST"
+    );
+
+    assert_not_handled!(put("/unknown").on(&app));
+}
+
+#[test]
+fn options_handling_is_unchanged() {
+    let app = app();
+    assert_response!(
+        TestConn::build("options", "/users/1", ()).on(&app),
+        200,
+        "",
+        "allow" => "DELETE, GET"
+    );
+
+    let app = Router::new()
+        .with_method_not_allowed()
+        .without_options_handling()
+        .get("/users", "users");
+    assert_response!(
+        TestConn::build("options", "/users", ()).on(&app),
+        405,
+        "",
+        "allow" => "GET"
+    );
+}
+
+#[test]
+fn unsatisfied_constraints_are_not_handled() {
+    let app = Router::new()
+        .with_method_not_allowed()
+        .when(Header::present("authorization"), |authorized| {
+            authorized.delete("/users/:id", "deleted")
+        })
+        .get("/users/:id", "user");
+
+    assert_ok!(
+        delete("/users/1")
+            .with_request_header("authorization", "token")
+            .on(&app),
+        "deleted"
+    );
+    assert_not_handled!(delete("/users/1").on(&app));
+    assert_response!(put("/users/1").on(&app), 405, "", "allow" => "DELETE, GET");
+}
+
+#[test]
+fn nested_routers() {
+    let app = Router::new().get("/", "index").scope("/api", |api| {
+        api.with_method_not_allowed().get("/users", "users")
+    });
+
+    assert_ok!(get("/api/users").on(&app), "users");
+    assert_response!(post("/api/users").on(&app), 405, "", "allow" => "GET");
+    assert_not_handled!(post("/").on(&app));
+
+    let app = Router::build(|mut router| {
+        router.set_method_not_allowed(true);
+        router.get("/", "index");
+    });
+    assert_response!(post("/").on(&app), 405, "", "allow" => "GET");
+}
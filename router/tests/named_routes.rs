@@ -0,0 +1,66 @@
+use trillium::Conn;
+use trillium_router::{Router, RouterConnExt};
+use trillium_testing::prelude::*;
+
+fn link_to(
+    name: &'static str,
+    params: &'static [(&'static str, &'static str)],
+) -> impl trillium::Handler {
+    move |conn: Conn| async move {
+        let url = conn.url_for(name, params).unwrap_or_default();
+        conn.ok(url)
+    }
+}
+
+#[test]
+fn url_for_within_nested_scopes() {
+    let router = Router::new()
+        .get_named(
+            "/",
+            "index",
+            link_to("member", &[("org", "trillium"), ("id", "10")]),
+        )
+        .scope("/orgs/:org", |org| {
+            org.get_named("/", "org", link_to("index", &[]))
+                .scope("/members", |members| {
+                    members.get_named("/:id", "member", link_to("org", &[("org", "other")]))
+                })
+        });
+
+    assert_ok!(get("/").on(&router), "/orgs/trillium/members/10");
+    assert_ok!(get("/orgs/trillium").on(&router), "/");
+    assert_ok!(get("/orgs/trillium/members/10").on(&router), "/orgs/other");
+}
+
+#[test]
+fn url_for_with_router_ref() {
+    let router = Router::build(|mut router| {
+        router.post_named("/widgets/:id", "widget", "ok");
+        router.scope("/admin", |mut admin| {
+            admin.delete_named("/widgets/:id", "admin_widget", "ok");
+        });
+    });
+
+    assert_eq!(
+        router.url_for("widget", &[("id", "1")]).unwrap(),
+        "/widgets/1"
+    );
+    assert_eq!(
+        router.url_for("admin_widget", &[("id", "1")]).unwrap(),
+        "/admin/widgets/1"
+    );
+}
+
+#[test]
+fn url_for_without_named_routes() {
+    let router = Router::new().get("/", link_to("index", &[]));
+    assert_ok!(get("/").on(&router), "");
+}
+
+#[test]
+#[should_panic(expected = "route name index is already in use")]
+fn duplicate_names() {
+    Router::new()
+        .get_named("/", "index", "ok")
+        .get_named("/other", "index", "ok");
+}
@@ -65,3 +65,45 @@ fn nested_router() {
     assert_headers!(TestConn::build("options", "/nested/here", ()).on(&router), "allow" => "GET, POST");
     assert_headers!(TestConn::build("options", "*", ()).on(&router), "allow" => "DELETE, GET, PATCH, POST, PUT");
 }
+
+#[test]
+fn options_handler() {
+    let router = Router::new()
+        .get("/some/route", "ok")
+        .delete("/some/:anything", "ok")
+        .with_options_handler(|conn: trillium::Conn| async move {
+            let methods = conn
+                .allowed_methods()
+                .unwrap()
+                .iter()
+                .map(|method| method.as_ref())
+                .collect::<Vec<_>>()
+                .join(", ");
+            conn.with_response_header("access-control-allow-methods", methods)
+        });
+
+    let mut conn = TestConn::build("options", "/some/route", ()).on(&router);
+    assert_status!(&conn, 200);
+    assert_headers!(
+        &mut conn,
+        "allow" => "DELETE, GET",
+        "access-control-allow-methods" => "DELETE, GET"
+    );
+
+    let mut conn = TestConn::build("options", "/other", ()).on(&router);
+    assert_headers!(&mut conn, "access-control-allow-methods" => "");
+
+    let router = Router::new()
+        .get("/some/route", "ok")
+        .any(&["options"], "/some/route", "explicit")
+        .with_options_handler(|conn: trillium::Conn| async move { conn.with_status(204) });
+    assert_ok!(
+        TestConn::build("options", "/some/route", ()).on(&router),
+        "explicit"
+    );
+
+    let router = Router::new()
+        .without_options_handling()
+        .with_options_handler(|conn: trillium::Conn| async move { conn.with_status(204) });
+    assert_not_handled!(TestConn::build("options", "/", ()).on(&router));
+}
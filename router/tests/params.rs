@@ -0,0 +1,112 @@
+use serde::Deserialize;
+use trillium::{Conn, Handler};
+use trillium_router::{Router, RouterConnExt};
+use trillium_testing::prelude::*;
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Sort {
+    Newest,
+    Oldest,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Listing {
+    org: String,
+    sort: Sort,
+    page: Option<u8>,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+struct Missing {
+    id: u64,
+}
+
+fn respond<T: std::fmt::Debug>(
+    conn: Conn,
+    params: Result<T, trillium_router::ParamsError>,
+) -> Conn {
+    match params {
+        Ok(params) => conn.ok(format!("{params:?}")),
+        Err(e) => conn
+            .with_status(400)
+            .with_body(format!("{:?} {}", e.param(), e.message()))
+            .halt(),
+    }
+}
+
+fn app() -> impl Handler {
+    Router::new()
+        .get("/listing/:org/:sort", |conn: Conn| async move {
+            let params = conn.params::<Listing>();
+            respond(conn, params)
+        })
+        .get("/missing/:name", |conn: Conn| async move {
+            let params = conn.params::<Missing>();
+            respond(conn, params)
+        })
+        .get("/pair/:a/:b", |conn: Conn| async move {
+            let params = conn.params::<(String, u8)>();
+            respond(conn, params)
+        })
+        .get("/single/:a/:b", |conn: Conn| async move {
+            let params = conn.params::<u8>();
+            respond(conn, params)
+        })
+        .get("/list/:a/:b/:c", |conn: Conn| async move {
+            let params = conn.params::<Vec<String>>();
+            respond(conn, params)
+        })
+}
+
+#[test]
+fn structs() {
+    assert_ok!(
+        get("/listing/trillium%2Frs/oldest").on(&app()),
+        r#"Listing { org: "trillium/rs", sort: Oldest, page: None }"#
+    );
+
+    assert_response!(
+        get("/listing/trillium/sideways").on(&app()),
+        400,
+        r#"Some("sort") unknown variant `sideways`, expected `newest` or `oldest`"#
+    );
+
+    assert_response!(
+        get("/missing/trillium").on(&app()),
+        400,
+        r#"Some("id") missing param"#
+    );
+}
+
+#[test]
+fn sequences() {
+    assert_ok!(get("/pair/a%20b/10").on(&app()), r#"("a b", 10)"#);
+    assert_response!(
+        get("/pair/a/256").on(&app()),
+        400,
+        r#"Some("b") could not parse "256" as u8"#
+    );
+    assert_ok!(get("/list/x/y/z").on(&app()), r#"["x", "y", "z"]"#);
+}
+
+#[test]
+fn single_values() {
+    assert_response!(
+        get("/single/1/2").on(&app()),
+        400,
+        "None invalid length 2, expected exactly one param"
+    );
+}
+
+#[test]
+fn decoded() {
+    let app = Router::new().get("/:name", |conn: Conn| async move {
+        let name = conn.param_decoded("name").unwrap().into_owned();
+        let raw = conn.param("name").unwrap().to_string();
+        conn.ok(format!("{raw} {name}"))
+    });
+    assert_ok!(get("/caf%C3%A9").on(&app), "caf%C3%A9 café");
+}
@@ -0,0 +1,119 @@
+use trillium::{Conn, Handler, KnownHeaderName, Status};
+use trillium_router::{Router, RouterConnExt};
+use trillium_testing::{prelude::*, TestConn};
+
+fn tag(value: &'static str) -> impl Handler {
+    move |conn: Conn| async move {
+        let tags = conn
+            .response_headers()
+            .get_str("x-tags")
+            .map_or_else(|| value.to_string(), |tags| format!("{tags},{value}"));
+        conn.with_response_header("x-tags", tags)
+    }
+}
+
+fn app() -> Router {
+    Router::new().get("/", "index").scope("/orgs/:org", |org| {
+        org.with_handler_before(tag("org"))
+            .get("/", |conn: Conn| async move {
+                let org = conn.param("org").unwrap().to_string();
+                conn.ok(org)
+            })
+            .scope("/admin", |admin| {
+                admin
+                    .with_handler_before(tag("admin"))
+                    .with_handler_before(|conn: Conn| async move {
+                        if conn
+                            .request_headers()
+                            .has_header(KnownHeaderName::Authorization)
+                        {
+                            conn
+                        } else {
+                            conn.with_status(Status::Forbidden).halt()
+                        }
+                    })
+                    .post("/users/:id", |conn: Conn| async move {
+                        let response = format!(
+                            "{} {}",
+                            conn.param("org").unwrap(),
+                            conn.param("id").unwrap()
+                        );
+                        conn.ok(response)
+                    })
+            })
+    })
+}
+
+#[test]
+fn handlers_before_run_in_order_for_matched_routes() {
+    let app = app();
+    assert_ok!(get("/").on(&app), "index");
+    assert!(get("/").on(&app).response_headers().get("x-tags").is_none());
+
+    assert_response!(get("/orgs/trillium").on(&app), 200, "trillium", "x-tags" => "org");
+
+    assert_response!(
+        post("/orgs/trillium/admin/users/10")
+            .with_request_header(KnownHeaderName::Authorization, "token")
+            .on(&app),
+        200,
+        "trillium 10",
+        "x-tags" => "org,admin"
+    );
+}
+
+#[test]
+fn halting_skips_route_handler() {
+    let app = app();
+    assert_response!(
+        post("/orgs/trillium/admin/users/10").on(&app),
+        403,
+        "",
+        "x-tags" => "org,admin"
+    );
+}
+
+#[test]
+fn unmatched_routes_skip_handlers_before() {
+    let app = app();
+    assert_not_handled!(get("/orgs/trillium/unknown").on(&app));
+    assert_not_handled!(get("/orgs/trillium/admin/users/10").on(&app));
+
+    let conn = TestConn::build("options", "/orgs/trillium/admin/users/10", ()).on(&app);
+    assert_status!(&conn, 200);
+    assert_headers!(&conn, "allow" => "POST");
+    // the nested admin scope is a route of the org scope
+    assert_headers!(&conn, "x-tags" => "org");
+
+    let conn = TestConn::build("options", "/orgs/trillium", ()).on(&app);
+    assert_headers!(&conn, "allow" => "GET");
+    assert!(conn.response_headers().get("x-tags").is_none());
+}
+
+#[test]
+fn before_send_runs_within_scope() {
+    struct BeforeSend;
+    #[trillium::async_trait]
+    impl Handler for BeforeSend {
+        async fn run(&self, conn: Conn) -> Conn {
+            conn
+        }
+
+        async fn before_send(&self, conn: Conn) -> Conn {
+            let route = conn.route().unwrap().to_string();
+            conn.with_response_header("x-before-send", route)
+        }
+    }
+
+    let app = Router::new().scope("/api", |api| {
+        api.with_handler_before(BeforeSend)
+            .get("/widgets", "widgets")
+    });
+
+    assert_response!(
+        get("/api/widgets").on(&app),
+        200,
+        "widgets",
+        "x-before-send" => "/widgets"
+    );
+}
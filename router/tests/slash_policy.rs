@@ -0,0 +1,137 @@
+use trillium::{Conn, Handler, Status};
+use trillium_router::{Router, RouterConnExt, SlashPolicy};
+use trillium_testing::{prelude::*, TestConn};
+
+async fn path_and_wildcard(conn: Conn) -> Conn {
+    let body = format!("{} {}", conn.path(), conn.wildcard().unwrap_or("-"));
+    conn.ok(body)
+}
+
+fn app(policy: Option<SlashPolicy>) -> Router {
+    let router = Router::new()
+        .get("/", "index")
+        .get("/pages/:page", |conn: Conn| async move {
+            let page = conn.param("page").unwrap().to_string();
+            conn.ok(page)
+        })
+        .get("/files/*", path_and_wildcard)
+        .post("/form", "posted");
+
+    match policy {
+        Some(policy) => router.with_slash_policy(policy),
+        None => router,
+    }
+}
+
+#[test]
+fn default_policy() {
+    let app = app(None);
+    assert_ok!(get("/pages/about/").on(&app), "about");
+    assert_ok!(get("//pages/about").on(&app), "about");
+    assert_not_handled!(get("/pages//about").on(&app));
+    assert_ok!(get("/files/a//b").on(&app), "a//b a//b");
+}
+
+#[test]
+fn equivalent() {
+    let app = app(Some(SlashPolicy::Equivalent));
+    assert_ok!(get("/").on(&app), "index");
+    assert_ok!(get("//").on(&app), "index");
+    assert_ok!(get("/pages/about/").on(&app), "about");
+    assert_ok!(get("/pages//about//").on(&app), "about");
+    assert_ok!(get("/files//a//b/").on(&app), "a/b a/b");
+    assert_not_handled!(get("/unknown/").on(&app));
+}
+
+#[test]
+fn redirect() {
+    let app = app(Some(SlashPolicy::Redirect(Status::MovedPermanently)));
+    assert_ok!(get("/pages/about").on(&app), "about");
+    assert_ok!(get("/").on(&app), "index");
+
+    assert_response!(
+        get("/pages/about/").on(&app),
+        301,
+        "",
+        "location" => "/pages/about"
+    );
+
+    assert_response!(
+        get("//pages//about?a=b&c").on(&app),
+        301,
+        "",
+        "location" => "/pages/about?a=b&c"
+    );
+
+    assert_not_handled!(get("/unknown/").on(&app));
+    assert_not_handled!(post("/pages/about/").on(&app));
+
+    let app = self::app(Some(SlashPolicy::Redirect(Status::PermanentRedirect)));
+    assert_response!(post("/form/").on(&app), 308, "", "location" => "/form");
+}
+
+#[test]
+fn strict() {
+    let app = app(Some(SlashPolicy::Strict));
+    assert_ok!(get("/").on(&app), "index");
+    assert_ok!(get("/pages/about").on(&app), "about");
+    assert_not_handled!(get("/pages/about/").on(&app));
+    assert_not_handled!(get("//pages/about").on(&app));
+    assert_not_handled!(get("/files/a//b").on(&app));
+    assert_not_handled!(TestConn::build("options", "/pages/about/", ()).on(&app));
+}
+
+#[test]
+fn nested_routers() {
+    let app = Router::new()
+        .with_slash_policy(SlashPolicy::Equivalent)
+        .scope("/api", |api| api.get("/users/:id", "user"))
+        .get("/nested/*", app(Some(SlashPolicy::Strict)));
+
+    assert_ok!(get("/api//users/1/").on(&app), "user");
+
+    // the outer router canonicalizes the path before the nested router sees it
+    assert_ok!(get("/nested/pages/about/").on(&app), "about");
+
+    let app = Router::new()
+        .get("/nested/*", self::app(Some(SlashPolicy::Strict)))
+        .get(
+            "/redirect/*",
+            self::app(Some(SlashPolicy::Redirect(Status::MovedPermanently))),
+        );
+    assert_ok!(get("/nested/pages/about").on(&app), "about");
+    assert_not_handled!(get("/nested/pages//about").on(&app));
+    assert_response!(
+        get("/redirect/pages//about?x").on(&app),
+        301,
+        "",
+        "location" => "/redirect/pages/about?x"
+    );
+}
+
+#[test]
+fn before_send_with_equivalent_paths() {
+    struct BeforeSend;
+    #[trillium::async_trait]
+    impl Handler for BeforeSend {
+        async fn run(&self, conn: Conn) -> Conn {
+            conn.ok("ok")
+        }
+
+        async fn before_send(&self, conn: Conn) -> Conn {
+            conn.with_response_header("x-before-send", "true")
+        }
+    }
+
+    let app = Router::new()
+        .with_slash_policy(SlashPolicy::Equivalent)
+        .get("/some/route", BeforeSend);
+    assert_headers!(get("/some//route/").on(&app), "x-before-send" => "true");
+
+    let app = Router::build(|mut router| {
+        router.set_slash_policy(SlashPolicy::Strict);
+        router.get("/some/route", BeforeSend);
+    });
+    assert_headers!(get("/some/route").on(&app), "x-before-send" => "true");
+    assert_headers!(get("/some/route/").on(&app), "x-before-send" => None);
+}
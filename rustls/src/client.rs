@@ -1,4 +1,4 @@
-use crate::crypto_provider;
+use crate::{crypto_provider, KeyExchange};
 use futures_rustls::{
     client::TlsStream,
     rustls::{
@@ -16,7 +16,9 @@ use std::{
     sync::Arc,
     task::{Context, Poll},
 };
-use trillium_server_common::{async_trait, AsyncRead, AsyncWrite, Connector, Transport, Url};
+use trillium_server_common::{
+    async_trait, AsyncRead, AsyncWrite, BoxedTransport, Connector, Transport, Url,
+};
 use RustlsClientTransportInner::{Tcp, Tls};
 
 #[derive(Clone, Debug)]
@@ -66,7 +68,10 @@ fn verifier(provider: Arc<CryptoProvider>) -> Arc<dyn ServerCertVerifier> {
 }
 
 fn default_client_config() -> ClientConfig {
-    let provider = crypto_provider();
+    client_config_with_provider(crypto_provider())
+}
+
+fn client_config_with_provider(provider: Arc<CryptoProvider>) -> ClientConfig {
     let verifier = verifier(Arc::clone(&provider));
 
     ClientConfig::builder_with_provider(provider)
@@ -95,6 +100,57 @@ impl<C: Connector> RustlsConfig<C> {
         self.tcp_config = config;
         self
     }
+
+    /// advertise these alpn protocols, in order of preference, retaining
+    /// any other customization of the rustls config
+    pub fn with_alpn_protocols<P: Into<Vec<u8>>>(
+        mut self,
+        alpn_protocols: impl IntoIterator<Item = P>,
+    ) -> Self {
+        Arc::make_mut(&mut self.rustls_config.0).alpn_protocols =
+            alpn_protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /**
+    replace the rustls config with this crate's default client config,
+    using this [`KeyExchange`] policy with the crate's crypto provider.
+    Any alpn protocols that have already been configured are retained.
+    To combine a key exchange policy with other customizations, build a
+    [`ClientConfig`] with a provider from [`KeyExchange::apply`].
+
+    ```
+    use trillium_rustls::{KeyExchange, RustlsConfig};
+    use trillium_smol::ClientConfig;
+
+    let config = RustlsConfig::<ClientConfig>::default()
+        .with_key_exchange(KeyExchange::PreferPostQuantum)
+        .with_alpn_protocols(["http/1.1"]);
+    ```
+
+    # Panics
+
+    Panics if the crypto provider does not support the key exchange
+    policy. See [`RustlsConfig::try_with_key_exchange`] for a fallible
+    alternative.
+    */
+    pub fn with_key_exchange(self, key_exchange: KeyExchange) -> Self {
+        self.try_with_key_exchange(key_exchange)
+            .expect("could not apply the key exchange policy to the crypto provider")
+    }
+
+    /**
+    replace the rustls config with this crate's default client config
+    using this [`KeyExchange`] policy, returning an error if the crypto
+    provider does not support it. See
+    [`RustlsConfig::with_key_exchange`]
+    */
+    pub fn try_with_key_exchange(mut self, key_exchange: KeyExchange) -> Result<Self> {
+        let mut client_config = client_config_with_provider(key_exchange.apply(crypto_provider())?);
+        client_config.alpn_protocols = self.rustls_config.0.alpn_protocols.clone();
+        self.rustls_config = client_config.into();
+        Ok(self)
+    }
 }
 
 impl<Config: Debug> Debug for RustlsConfig<Config> {
@@ -142,6 +198,28 @@ impl<C: Connector> Connector for RustlsConfig<C> {
     fn spawn<Fut: Future<Output = ()> + Send + 'static>(&self, fut: Fut) {
         self.tcp_config.spawn(fut)
     }
+
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        match url.scheme() {
+            "https" => {
+                let connector: TlsConnector = Arc::clone(&self.rustls_config.0).into();
+                let domain = url
+                    .domain()
+                    .and_then(|dns_name| ServerName::try_from(dns_name.to_string()).ok())
+                    .ok_or_else(|| Error::other("missing domain"))?;
+
+                connector
+                    .connect(domain, transport)
+                    .await
+                    .map_err(|e| Error::other(e.to_string()))
+                    .map(|tls| {
+                        BoxedTransport::new(RustlsClientTransport::<BoxedTransport>::from(tls))
+                    })
+            }
+
+            _ => self.tcp_config.connect_over(url, transport).await,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,89 @@
+use crate::{crypto_provider, rustls::RootCertStore};
+use futures_rustls::rustls::server::{danger::ClientCertVerifier, WebPkiClientVerifier};
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, Cursor},
+    sync::Arc,
+};
+
+/**
+Client certificate (mutual tls) authentication for a
+[`RustlsAcceptor`](crate::RustlsAcceptor)
+
+Client certificates are verified against one or more trusted
+certificate authorities. See
+[`RustlsAcceptor::from_single_cert_with_client_auth`](crate::RustlsAcceptor::from_single_cert_with_client_auth)
+*/
+#[derive(Clone)]
+pub struct ClientAuth {
+    roots: RootCertStore,
+    required: bool,
+}
+
+impl Debug for ClientAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientAuth")
+            .field("roots", &self.roots.len())
+            .field("required", &self.required)
+            .finish()
+    }
+}
+
+impl ClientAuth {
+    /**
+    require every client to present a certificate issued by one of the
+    certificate authorities in this pem bundle. Connections from
+    clients that do not are closed during the tls handshake.
+    */
+    pub fn required(client_ca_pem: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            roots: roots(client_ca_pem)?,
+            required: true,
+        })
+    }
+
+    /**
+    request a certificate issued by one of the certificate authorities
+    in this pem bundle, but also accept clients that do not present
+    one. A certificate that is presented must be valid.
+    */
+    pub fn optional(client_ca_pem: &[u8]) -> io::Result<Self> {
+        Ok(Self {
+            roots: roots(client_ca_pem)?,
+            required: false,
+        })
+    }
+
+    pub(crate) fn verifier(&self) -> io::Result<Arc<dyn ClientCertVerifier>> {
+        let builder = WebPkiClientVerifier::builder_with_provider(
+            Arc::new(self.roots.clone()),
+            crypto_provider(),
+        );
+        let builder = if self.required {
+            builder
+        } else {
+            builder.allow_unauthenticated()
+        };
+        builder
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+    }
+}
+
+fn roots(client_ca_pem: &[u8]) -> io::Result<RootCertStore> {
+    let mut roots = RootCertStore::empty();
+    for certificate in rustls_pemfile::certs(&mut Cursor::new(client_ca_pem)) {
+        roots
+            .add(certificate?)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+
+    if roots.is_empty() {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no certificates found in client ca pem",
+        ))
+    } else {
+        Ok(roots)
+    }
+}
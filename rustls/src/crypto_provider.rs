@@ -2,7 +2,11 @@ use futures_rustls::rustls::crypto::CryptoProvider;
 use std::sync::Arc;
 
 #[cfg(feature = "aws-lc-rs")]
-pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
+/// The rustls [`CryptoProvider`] selected by this crate's cargo
+/// features, for building a `ServerConfig` or `ClientConfig` that
+/// matches the ones this crate builds. See the crate-level docs for
+/// how the provider is selected.
+pub fn crypto_provider() -> Arc<CryptoProvider> {
     #[cfg(any(feature = "ring", feature = "custom-crypto-provider"))]
     log::error!("multiple crypto provider features enabled, choosing aws-lc-rs");
 
@@ -10,7 +14,11 @@ pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
 }
 
 #[cfg(all(not(feature = "aws-lc-rs"), feature = "ring"))]
-pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
+/// The rustls [`CryptoProvider`] selected by this crate's cargo
+/// features, for building a `ServerConfig` or `ClientConfig` that
+/// matches the ones this crate builds. See the crate-level docs for
+/// how the provider is selected.
+pub fn crypto_provider() -> Arc<CryptoProvider> {
     #[cfg(feature = "custom-crypto-provider")]
     log::error!("multiple crypto provider features enabled, choosing ring");
     futures_rustls::rustls::crypto::ring::default_provider().into()
@@ -20,7 +28,11 @@ pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
     not(any(feature = "aws-lc-rs", feature = "ring")),
     feature = "custom-crypto-provider"
 ))]
-pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
+/// The rustls [`CryptoProvider`] selected by this crate's cargo
+/// features, for building a `ServerConfig` or `ClientConfig` that
+/// matches the ones this crate builds. See the crate-level docs for
+/// how the provider is selected.
+pub fn crypto_provider() -> Arc<CryptoProvider> {
     CryptoProvider::get_default()
         .expect(concat!(
             "`custom-crypto-provider` feature was enabled, but no default crypto ",
@@ -35,7 +47,11 @@ pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
     feature = "aws-lc-rs",
     feature = "custom-crypto-provider"
 )))]
-pub(crate) fn crypto_provider() -> Arc<CryptoProvider> {
+/// The rustls [`CryptoProvider`] selected by this crate's cargo
+/// features, for building a `ServerConfig` or `ClientConfig` that
+/// matches the ones this crate builds. See the crate-level docs for
+/// how the provider is selected.
+pub fn crypto_provider() -> Arc<CryptoProvider> {
     compile_error!(
         "\n\n`trillium-rustls` cannot compile without a crypto provider feature enabled.
 Please enable `ring`, `aws-lc-rs`, or `custom-crypto-provider`.
@@ -0,0 +1,123 @@
+use futures_rustls::rustls::{
+    crypto::{CryptoProvider, SupportedKxGroup},
+    NamedGroup,
+};
+use std::{
+    io::{Error, ErrorKind, Result},
+    sync::Arc,
+};
+
+/**
+Key exchange policy for the tls configurations built by this crate
+
+Hybrid post-quantum key exchange groups such as `X25519MLKEM768`
+combine a classical key exchange with a post-quantum key encapsulation
+mechanism, protecting recorded traffic against future decryption by a
+quantum computer. Whether any such groups are available depends on the
+[`CryptoProvider`]: providers that do not offer them can be combined
+with a provider that does with the `custom-crypto-provider` feature.
+
+Rustls sends a key share for only the first group that the client
+prefers, so the order of the groups determines which key exchange is
+attempted first. Peers that do not support a post-quantum group fall
+back to a classical group with an additional round trip, unless the
+policy is [`KeyExchange::RequirePostQuantum`].
+
+Note that post-quantum key shares are large enough that a client hello
+may span more than one tcp segment. This is independent of the alpn
+protocols that are advertised, which are retained when a key exchange
+policy is applied.
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyExchange {
+    /// use the key exchange groups of the crypto provider in the
+    /// provider's order of preference
+    #[default]
+    ProviderDefault,
+
+    /// prefer any post-quantum key exchange groups offered by the crypto
+    /// provider, falling back to the provider's other groups. if the
+    /// provider offers no post-quantum groups, a warning is logged and
+    /// the provider's groups are used as-is
+    PreferPostQuantum,
+
+    /// only use post-quantum key exchange groups. applying this policy
+    /// to a crypto provider that offers no post-quantum groups is an
+    /// error, and peers that do not support any of them will fail to
+    /// connect
+    RequirePostQuantum,
+}
+
+impl KeyExchange {
+    /**
+    apply this policy to a [`CryptoProvider`], returning a provider that
+    can be used to build a rustls `ServerConfig` or `ClientConfig`
+    directly
+
+    ```
+    use trillium_rustls::{crypto_provider, KeyExchange};
+    let provider = KeyExchange::PreferPostQuantum.apply(crypto_provider()).unwrap();
+    ```
+    */
+    pub fn apply(self, provider: Arc<CryptoProvider>) -> Result<Arc<CryptoProvider>> {
+        let (post_quantum, classical): (Vec<_>, Vec<_>) = provider
+            .kx_groups
+            .iter()
+            .partition(|group| is_post_quantum(**group));
+
+        let kx_groups = match self {
+            Self::ProviderDefault => return Ok(provider),
+
+            Self::PreferPostQuantum if post_quantum.is_empty() => {
+                log::warn!(
+                    "post-quantum key exchange was preferred, but the crypto provider does not \
+                     offer any post-quantum key exchange groups"
+                );
+                return Ok(provider);
+            }
+
+            Self::PreferPostQuantum => post_quantum.into_iter().chain(classical).collect(),
+
+            Self::RequirePostQuantum if post_quantum.is_empty() => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "post-quantum key exchange was required, but the crypto provider does not \
+                     offer any post-quantum key exchange groups",
+                ));
+            }
+
+            Self::RequirePostQuantum => post_quantum,
+        };
+
+        Ok(Arc::new(CryptoProvider {
+            kx_groups,
+            ..CryptoProvider::clone(&provider)
+        }))
+    }
+
+    /**
+    whether the [`CryptoProvider`] offers any post-quantum key exchange
+    groups
+    */
+    pub fn is_supported_by(provider: &CryptoProvider) -> bool {
+        provider
+            .kx_groups
+            .iter()
+            .any(|group| is_post_quantum(*group))
+    }
+}
+
+fn is_post_quantum(group: &dyn SupportedKxGroup) -> bool {
+    matches!(
+        group.name(),
+        NamedGroup::X25519MLKEM768
+            | NamedGroup::secp256r1MLKEM768
+            | NamedGroup::MLKEM512
+            | NamedGroup::MLKEM768
+            | NamedGroup::MLKEM1024
+    ) || matches!(
+        u16::from(group.name()),
+        // SecP384r1MLKEM1024 and the pre-standard X25519Kyber768Draft00
+        0x11ed | 0x6399
+    )
+}
@@ -59,6 +59,12 @@ In order to use a crypto provider other than the above two options, enable the
 [`trillium_rustls::rustls::crypto::CryptoProvider::install_default`][rustls::crypto::CryptoProvider::install_default]
 prior to executing trillium-rustls code.
 
+## Post-quantum key exchange
+
+Hybrid post-quantum key exchange can be preferred or required with a [`KeyExchange`] policy on both
+[`RustlsAcceptor::builder`] and [`RustlsConfig::with_key_exchange`], provided that the selected
+crypto provider offers post-quantum key exchange groups.
+
 ## Client verifier
 
 This crate offers a `platform-verifier` feature for client usage that builds a ClientConfig with the
@@ -75,11 +81,21 @@ pub use client::{RustlsClientTransport, RustlsConfig};
 #[cfg(feature = "server")]
 mod server;
 #[cfg(feature = "server")]
-pub use server::{RustlsAcceptor, RustlsServerTransport};
+pub use server::{RustlsAcceptor, RustlsAcceptorBuilder, RustlsServerTransport};
+
+#[cfg(feature = "server")]
+mod client_auth;
+#[cfg(feature = "server")]
+pub use client_auth::ClientAuth;
 
 pub use futures_rustls;
 pub use futures_rustls::rustls;
 
+#[cfg(any(feature = "client", feature = "server"))]
+mod key_exchange;
+#[cfg(any(feature = "client", feature = "server"))]
+pub use key_exchange::KeyExchange;
+
 #[cfg(any(feature = "client", feature = "server"))]
 mod crypto_provider;
-pub(crate) use crypto_provider::crypto_provider;
+pub use crypto_provider::crypto_provider;
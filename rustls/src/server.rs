@@ -7,19 +7,100 @@ use std::{
     fmt::{Debug, Formatter},
     io,
     pin::Pin,
-    sync::Arc,
+    sync::{Arc, RwLock},
     task::{Context, Poll},
 };
-use trillium_server_common::{async_trait, Acceptor, AsyncRead, AsyncWrite, Transport};
+use trillium_server_common::{
+    async_trait, Acceptor, AsyncRead, AsyncWrite, PeerCertificates, PeerCredentials, Transport,
+    TransportKind,
+};
 
-use crate::crypto_provider;
+use crate::{crypto_provider, ClientAuth, KeyExchange};
 
 /**
 trillium [`Acceptor`] for Rustls
+
+## HTTP/2
+
+To serve http/2, enable the `http2` cargo feature on the runtime adapter (eg `trillium-smol`) and
+advertise `h2` with alpn by setting
+[`alpn_protocols`][`crate::rustls::ServerConfig::alpn_protocols`] on the [`ServerConfig`]. Clients
+that do not negotiate `h2` will continue to be served http/1.1.
+
+```rust,ignore
+use trillium_rustls::{rustls::ServerConfig, RustlsAcceptor};
+let mut server_config = ServerConfig::builder()
+    .with_no_client_auth()
+    .with_single_cert(certs, private_key)
+    .expect("could not build rustls ServerConfig");
+server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+let rustls_acceptor = RustlsAcceptor::new(server_config);
+```
+
+## Client certificates
+
+To request or require a certificate from each client (mutual tls), build the acceptor with
+[`RustlsAcceptor::from_single_cert_with_client_auth`] and a [`ClientAuth`], or provide a
+[`ServerConfig`] with a client certificate verifier. The verified certificate chain presented by the
+client is available to handlers through `trillium::Conn::peer_certificates`.
+
+```rust,no_run
+use trillium_rustls::{ClientAuth, RustlsAcceptor};
+const KEY: &[u8] = include_bytes!("../examples/key.pem");
+const CERT: &[u8] = include_bytes!("../examples/cert.pem");
+const CLIENT_CA: &[u8] = include_bytes!("../examples/cert.pem");
+let rustls_acceptor = RustlsAcceptor::from_single_cert_with_client_auth(
+    CERT,
+    KEY,
+    ClientAuth::required(CLIENT_CA).unwrap(),
+);
+```
+
+## Post-quantum key exchange
+
+To prefer or require hybrid post-quantum key exchange, build the acceptor with
+[`RustlsAcceptor::builder`] and a [`KeyExchange`] policy. Alpn protocols can be set on the same
+builder, so neither requires constructing a [`ServerConfig`].
+
+```rust,no_run
+use trillium_rustls::{KeyExchange, RustlsAcceptor};
+const KEY: &[u8] = include_bytes!("../examples/key.pem");
+const CERT: &[u8] = include_bytes!("../examples/cert.pem");
+let rustls_acceptor = RustlsAcceptor::builder(CERT, KEY)
+    .with_key_exchange(KeyExchange::PreferPostQuantum)
+    .with_alpn_protocols(["h2", "http/1.1"])
+    .build()
+    .unwrap();
+```
+
+## Certificate rotation
+
+Clones of a RustlsAcceptor share a [`ServerConfig`], so the certificate can be replaced at runtime
+with [`RustlsAcceptor::set_server_config`] or [`RustlsAcceptor::set_single_cert`], for example after
+renewing certificates, without restarting the server. Connections that have already been accepted
+are unaffected.
+
+```rust,no_run
+use trillium_rustls::RustlsAcceptor;
+let rustls_acceptor = RustlsAcceptor::from_single_cert(
+    &std::fs::read("cert.pem").unwrap(),
+    &std::fs::read("key.pem").unwrap(),
+);
+
+let handle = rustls_acceptor.clone();
+std::thread::spawn(move || loop {
+    std::thread::sleep(std::time::Duration::from_secs(60 * 60 * 24));
+    let cert = std::fs::read("cert.pem").unwrap();
+    let key = std::fs::read("key.pem").unwrap();
+    if let Err(e) = handle.set_single_cert(&cert, &key) {
+        eprintln!("could not reload certificate: {e}");
+    }
+});
+```
 */
 
 #[derive(Clone)]
-pub struct RustlsAcceptor(TlsAcceptor);
+pub struct RustlsAcceptor(Arc<RwLock<TlsAcceptor>>);
 impl Debug for RustlsAcceptor {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_tuple("Rustls").field(&"<<TlsAcceptor>>").finish()
@@ -61,35 +142,178 @@ impl RustlsAcceptor {
     ```
     */
     pub fn from_single_cert(cert: &[u8], key: &[u8]) -> Self {
+        Self::try_from_single_cert(cert, key)
+            .expect("could not create a rustls ServerConfig from the supplied cert and key")
+    }
+
+    /**
+    build a new RustlsAcceptor from a cert chain (pem) and private key,
+    returning an error if they are not valid. See
+    [`RustlsAcceptor::from_single_cert`]
+    */
+    pub fn try_from_single_cert(cert: &[u8], key: &[u8]) -> io::Result<Self> {
+        Self::builder(cert, key).build()
+    }
+
+    /**
+    start building a RustlsAcceptor from a cert chain (pem) and private
+    key, as accepted by [`RustlsAcceptor::from_single_cert`], in order to
+    configure client authentication, key exchange, or alpn protocols
+    without constructing a [`ServerConfig`]. See
+    [`RustlsAcceptorBuilder`]
+    */
+    pub fn builder<'a>(cert: &'a [u8], key: &'a [u8]) -> RustlsAcceptorBuilder<'a> {
+        RustlsAcceptorBuilder {
+            cert,
+            key,
+            client_auth: None,
+            key_exchange: KeyExchange::default(),
+            alpn_protocols: Vec::new(),
+        }
+    }
+
+    /**
+    build a new RustlsAcceptor from a cert chain (pem) and private key,
+    as accepted by [`RustlsAcceptor::from_single_cert`], that requests
+    or requires client certificates as specified by the [`ClientAuth`]
+
+    # Panics
+
+    Panics if the cert chain and key are not valid. See
+    [`RustlsAcceptor::try_from_single_cert_with_client_auth`] for a
+    fallible alternative.
+    */
+    pub fn from_single_cert_with_client_auth(
+        cert: &[u8],
+        key: &[u8],
+        client_auth: ClientAuth,
+    ) -> Self {
+        Self::try_from_single_cert_with_client_auth(cert, key, client_auth)
+            .expect("could not create a rustls ServerConfig from the supplied cert and key")
+    }
+
+    /**
+    build a new RustlsAcceptor that requests or requires client
+    certificates, returning an error if the cert chain and key are not
+    valid. See [`RustlsAcceptor::from_single_cert_with_client_auth`]
+    */
+    pub fn try_from_single_cert_with_client_auth(
+        cert: &[u8],
+        key: &[u8],
+        client_auth: ClientAuth,
+    ) -> io::Result<Self> {
+        Self::builder(cert, key)
+            .with_client_auth(client_auth)
+            .build()
+    }
+
+    /**
+    replaces the [`ServerConfig`] used for new connections by this
+    acceptor and every clone of it
+    */
+    pub fn set_server_config(&self, server_config: impl Into<Arc<ServerConfig>>) {
+        *self.0.write().unwrap() = TlsAcceptor::from(server_config.into());
+    }
+
+    /**
+    replaces the certificate presented to new connections by this
+    acceptor and every clone of it with a cert chain (pem) and private
+    key, as accepted by [`RustlsAcceptor::from_single_cert`]. If they
+    are not valid, an error is returned and the current certificate is
+    retained.
+
+    Note that this replaces the entire [`ServerConfig`] with the
+    defaults used by [`RustlsAcceptor::from_single_cert`]. To retain
+    other customizations such as alpn protocols, build a new
+    [`ServerConfig`] and use [`RustlsAcceptor::set_server_config`].
+    */
+    pub fn set_single_cert(&self, cert: &[u8], key: &[u8]) -> io::Result<()> {
+        self.set_server_config(Self::builder(cert, key).server_config()?);
+        Ok(())
+    }
+}
+
+/**
+Builder for a [`RustlsAcceptor`] from a cert chain and private key. See
+[`RustlsAcceptor::builder`]
+*/
+#[derive(Debug)]
+pub struct RustlsAcceptorBuilder<'a> {
+    cert: &'a [u8],
+    key: &'a [u8],
+    client_auth: Option<ClientAuth>,
+    key_exchange: KeyExchange,
+    alpn_protocols: Vec<Vec<u8>>,
+}
+
+impl RustlsAcceptorBuilder<'_> {
+    /// request or require client certificates as specified by the
+    /// [`ClientAuth`]
+    pub fn with_client_auth(mut self, client_auth: ClientAuth) -> Self {
+        self.client_auth = Some(client_auth);
+        self
+    }
+
+    /// use this [`KeyExchange`] policy with the crate's crypto provider
+    pub fn with_key_exchange(mut self, key_exchange: KeyExchange) -> Self {
+        self.key_exchange = key_exchange;
+        self
+    }
+
+    /// advertise these alpn protocols, in order of preference
+    pub fn with_alpn_protocols<P: Into<Vec<u8>>>(
+        mut self,
+        alpn_protocols: impl IntoIterator<Item = P>,
+    ) -> Self {
+        self.alpn_protocols = alpn_protocols.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// build the [`RustlsAcceptor`], returning an error if the cert
+    /// chain or key are not valid, or if the key exchange policy is not
+    /// supported by the crypto provider
+    pub fn build(self) -> io::Result<RustlsAcceptor> {
+        self.server_config().map(RustlsAcceptor::from)
+    }
+
+    fn server_config(self) -> io::Result<ServerConfig> {
         use std::io::Cursor;
 
-        let cert_chain = rustls_pemfile::certs(&mut Cursor::new(cert))
-            .collect::<Result<_, _>>()
-            .expect("could not read certificate");
+        let cert_chain =
+            rustls_pemfile::certs(&mut Cursor::new(self.cert)).collect::<Result<_, _>>()?;
 
-        let key_der = rustls_pemfile::private_key(&mut Cursor::new(key))
-            .expect("could not read key pemfile")
-            .expect("no private key found in `key`");
+        let key_der =
+            rustls_pemfile::private_key(&mut Cursor::new(self.key))?.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "no private key found in `key`")
+            })?;
 
-        ServerConfig::builder_with_provider(crypto_provider())
+        let provider = self.key_exchange.apply(crypto_provider())?;
+        let builder = ServerConfig::builder_with_provider(provider)
             .with_safe_default_protocol_versions()
-            .expect("crypto provider did not support safe default protocol versions")
-            .with_no_client_auth()
+            .map_err(|e| io::Error::new(io::ErrorKind::Unsupported, e))?;
+
+        let builder = match &self.client_auth {
+            Some(client_auth) => builder.with_client_cert_verifier(client_auth.verifier()?),
+            None => builder.with_no_client_auth(),
+        };
+
+        let mut server_config = builder
             .with_single_cert(cert_chain, key_der)
-            .expect("could not create a rustls ServerConfig from the supplied cert and key")
-            .into()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        server_config.alpn_protocols = self.alpn_protocols;
+        Ok(server_config)
     }
 }
 
 impl From<ServerConfig> for RustlsAcceptor {
     fn from(sc: ServerConfig) -> Self {
-        Self(Arc::new(sc).into())
+        Self::from(TlsAcceptor::from(Arc::new(sc)))
     }
 }
 
 impl From<TlsAcceptor> for RustlsAcceptor {
     fn from(ta: TlsAcceptor) -> Self {
-        Self(ta)
+        Self(Arc::new(RwLock::new(ta)))
     }
 }
 
@@ -137,6 +361,27 @@ impl<T: Transport> Transport for RustlsServerTransport<T> {
     fn peer_addr(&self) -> io::Result<Option<std::net::SocketAddr>> {
         self.inner_transport().peer_addr()
     }
+
+    fn local_addr(&self) -> io::Result<Option<std::net::SocketAddr>> {
+        self.inner_transport().local_addr()
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        self.inner_transport().transport_kind()
+    }
+
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.0.get_ref().1.alpn_protocol()
+    }
+
+    fn peer_credentials(&self) -> io::Result<Option<PeerCredentials>> {
+        self.inner_transport().peer_credentials()
+    }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        let certificates = self.0.get_ref().1.peer_certificates()?;
+        PeerCertificates::new(certificates.iter().map(|certificate| certificate.to_vec()))
+    }
 }
 
 impl<T> RustlsServerTransport<T> {
@@ -183,6 +428,11 @@ where
     type Output = RustlsServerTransport<Input>;
     type Error = io::Error;
     async fn accept(&self, input: Input) -> Result<Self::Output, Self::Error> {
-        self.0.accept(input).await.map(RustlsServerTransport)
+        let acceptor = self.0.read().unwrap().clone();
+        acceptor.accept(input).await.map(RustlsServerTransport)
+    }
+
+    fn is_secure(&self) -> bool {
+        true
     }
 }
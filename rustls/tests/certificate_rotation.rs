@@ -0,0 +1,88 @@
+use std::sync::Arc;
+use trillium_rustls::{
+    crypto_provider,
+    futures_rustls::TlsConnector,
+    rustls::{
+        pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName},
+        ServerConfig,
+    },
+    RustlsAcceptor,
+};
+use trillium_server_common::Acceptor;
+use trillium_testing::{
+    futures_lite::future::{block_on, zip},
+    TestCertificate, TestTransport,
+};
+
+fn handshake(acceptor: &RustlsAcceptor, trusted: &TestCertificate) -> bool {
+    block_on(async {
+        let (client, server) = TestTransport::new();
+        let connector = TlsConnector::from(Arc::new(trusted.rustls_client_config()));
+        let server_name = ServerName::try_from("localhost").unwrap();
+        let (server, client) = zip(
+            acceptor.accept(server),
+            connector.connect(server_name, client),
+        )
+        .await;
+        server.is_ok() && client.is_ok()
+    })
+}
+
+fn server_config(certificate: &TestCertificate) -> ServerConfig {
+    ServerConfig::builder_with_provider(crypto_provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![CertificateDer::from(certificate.cert_der().to_vec())],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(certificate.key_der().to_vec())),
+        )
+        .unwrap()
+}
+
+#[test]
+fn set_single_cert() {
+    let first = TestCertificate::generate();
+    let second = TestCertificate::generate();
+
+    let acceptor =
+        RustlsAcceptor::from_single_cert(first.cert_pem().as_bytes(), first.key_pem().as_bytes());
+    let clone = acceptor.clone();
+    assert!(handshake(&clone, &first));
+    assert!(!handshake(&clone, &second));
+
+    acceptor
+        .set_single_cert(second.cert_pem().as_bytes(), second.key_pem().as_bytes())
+        .unwrap();
+    assert!(handshake(&clone, &second));
+    assert!(!handshake(&clone, &first));
+
+    assert!(acceptor
+        .set_single_cert(first.cert_pem().as_bytes(), b"not a key")
+        .is_err());
+    assert!(handshake(&clone, &second));
+}
+
+#[test]
+fn set_server_config() {
+    let first = TestCertificate::generate();
+    let second = TestCertificate::generate();
+
+    let acceptor = first.acceptor();
+    let clone = acceptor.clone();
+    assert!(handshake(&clone, &first));
+
+    acceptor.set_server_config(server_config(&second));
+    assert!(handshake(&clone, &second));
+    assert!(!handshake(&clone, &first));
+}
+
+#[test]
+fn invalid_pem() {
+    let certificate = TestCertificate::generate();
+    assert!(RustlsAcceptor::try_from_single_cert(certificate.cert_pem().as_bytes(), b"").is_err());
+    assert!(
+        RustlsAcceptor::try_from_single_cert(b"not a cert", certificate.key_pem().as_bytes())
+            .is_err()
+    );
+}
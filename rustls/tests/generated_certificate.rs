@@ -0,0 +1,30 @@
+use trillium::Conn;
+use trillium_client::Client;
+use trillium_testing::{with_server, with_tls_server};
+
+async fn handler(conn: Conn) -> Conn {
+    let secure = conn.is_secure();
+    conn.ok(if secure { "secure" } else { "insecure" })
+}
+
+#[test]
+fn tls_server_conns_are_secure() {
+    with_tls_server(handler, |url, client_config| async move {
+        assert_eq!(url.scheme(), "https");
+        let mut conn = Client::new(client_config).get(url).await?.success()?;
+        assert_eq!(conn.response_body().read_string().await?, "secure");
+        Ok(())
+    });
+}
+
+#[test]
+fn plaintext_server_conns_are_not_secure() {
+    with_server(handler, |url| async move {
+        let mut conn = Client::new(trillium_testing::client_config())
+            .get(url)
+            .await?
+            .success()?;
+        assert_eq!(conn.response_body().read_string().await?, "insecure");
+        Ok(())
+    });
+}
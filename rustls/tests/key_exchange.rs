@@ -0,0 +1,132 @@
+use std::{io::ErrorKind, sync::Arc};
+use trillium_rustls::{
+    crypto_provider,
+    futures_rustls::TlsConnector,
+    rustls::{
+        crypto::{ActiveKeyExchange, CryptoProvider, SupportedKxGroup},
+        pki_types::ServerName,
+        Error, NamedGroup,
+    },
+    KeyExchange, RustlsAcceptor, RustlsConfig,
+};
+use trillium_server_common::Acceptor;
+use trillium_smol::ClientConfig;
+use trillium_testing::{
+    futures_lite::future::{block_on, zip},
+    TestCertificate, TestTransport,
+};
+
+// stands in for a hybrid group from a provider that offers one
+#[derive(Debug)]
+struct Hybrid;
+impl SupportedKxGroup for Hybrid {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        Err(Error::General("not a real key exchange".into()))
+    }
+
+    fn name(&self) -> NamedGroup {
+        NamedGroup::X25519MLKEM768
+    }
+}
+
+fn provider_with_hybrid() -> Arc<CryptoProvider> {
+    let mut provider = CryptoProvider::clone(&crypto_provider());
+    provider.kx_groups.push(&Hybrid);
+    Arc::new(provider)
+}
+
+fn group_names(provider: &CryptoProvider) -> Vec<NamedGroup> {
+    provider
+        .kx_groups
+        .iter()
+        .map(|group| group.name())
+        .collect()
+}
+
+#[test]
+fn orders_post_quantum_groups() {
+    let provider = provider_with_hybrid();
+    assert!(KeyExchange::is_supported_by(&provider));
+    let classical = group_names(&crypto_provider());
+
+    let default = KeyExchange::ProviderDefault
+        .apply(provider.clone())
+        .unwrap();
+    assert_eq!(
+        group_names(&default).last(),
+        Some(&NamedGroup::X25519MLKEM768)
+    );
+
+    let preferred = KeyExchange::PreferPostQuantum
+        .apply(provider.clone())
+        .unwrap();
+    assert_eq!(
+        group_names(&preferred),
+        [NamedGroup::X25519MLKEM768]
+            .into_iter()
+            .chain(classical)
+            .collect::<Vec<_>>()
+    );
+
+    let required = KeyExchange::RequirePostQuantum.apply(provider).unwrap();
+    assert_eq!(group_names(&required), [NamedGroup::X25519MLKEM768]);
+}
+
+#[test]
+fn unsupported_by_provider() {
+    let provider = crypto_provider();
+    assert!(!KeyExchange::is_supported_by(&provider));
+
+    let preferred = KeyExchange::PreferPostQuantum
+        .apply(provider.clone())
+        .unwrap();
+    assert_eq!(group_names(&preferred), group_names(&provider));
+
+    let err = KeyExchange::RequirePostQuantum.apply(provider).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+
+    let certificate = TestCertificate::generate();
+    assert!(RustlsAcceptor::builder(
+        certificate.cert_pem().as_bytes(),
+        certificate.key_pem().as_bytes()
+    )
+    .with_key_exchange(KeyExchange::RequirePostQuantum)
+    .build()
+    .is_err());
+
+    assert!(RustlsConfig::<ClientConfig>::default()
+        .try_with_key_exchange(KeyExchange::RequirePostQuantum)
+        .is_err());
+}
+
+#[test]
+fn builder_with_alpn_protocols() {
+    let certificate = TestCertificate::generate();
+    let acceptor = RustlsAcceptor::builder(
+        certificate.cert_pem().as_bytes(),
+        certificate.key_pem().as_bytes(),
+    )
+    .with_key_exchange(KeyExchange::PreferPostQuantum)
+    .with_alpn_protocols(["h2", "http/1.1"])
+    .build()
+    .unwrap();
+
+    let mut client_config = certificate.rustls_client_config();
+    client_config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let connector = TlsConnector::from(Arc::new(client_config));
+
+    block_on(async {
+        let (client, server) = TestTransport::new();
+        let server_name = ServerName::try_from("localhost").unwrap();
+        let (server, client) = zip(
+            acceptor.accept(server),
+            connector.connect(server_name, client),
+        )
+        .await;
+        assert!(server.is_ok());
+        assert_eq!(
+            client.unwrap().get_ref().1.alpn_protocol(),
+            Some(&b"http/1.1"[..])
+        );
+    });
+}
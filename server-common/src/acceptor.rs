@@ -1,5 +1,10 @@
 use crate::{async_trait, Transport};
-use std::{convert::Infallible, fmt::Debug};
+use std::{
+    convert::Infallible,
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+use trillium_http::transport::BoxedTransport;
 
 /**
 This trait provides the common interface for server-side tls
@@ -44,6 +49,13 @@ where
     ```
     */
     async fn accept(&self, input: Input) -> Result<Self::Output, Self::Error>;
+
+    /// Indicates whether transports produced by this acceptor are
+    /// encrypted. Conns accepted by a secure acceptor will return true
+    /// from `Conn::is_secure`. The default is false.
+    fn is_secure(&self) -> bool {
+        false
+    }
 }
 
 #[async_trait]
@@ -57,3 +69,56 @@ where
         Ok(input)
     }
 }
+
+/// An object-safe [`Acceptor`] over [`BoxedTransport`], allowing a
+/// config to hold acceptors of differing types
+#[async_trait]
+pub(crate) trait ObjectSafeAcceptor: Send + Sync + 'static {
+    async fn accept(&self, input: BoxedTransport) -> Result<BoxedTransport, String>;
+    fn is_secure(&self) -> bool;
+}
+
+#[async_trait]
+impl<A: Acceptor<BoxedTransport>> ObjectSafeAcceptor for A {
+    async fn accept(&self, input: BoxedTransport) -> Result<BoxedTransport, String> {
+        Acceptor::accept(self, input)
+            .await
+            .map(BoxedTransport::new)
+            .map_err(|e| format!("{e:?}"))
+    }
+
+    fn is_secure(&self) -> bool {
+        Acceptor::is_secure(self)
+    }
+}
+
+/// A cloneable [`Acceptor`] that wraps any [`ObjectSafeAcceptor`]
+#[derive(Clone, Debug)]
+pub(crate) struct BoxedAcceptor(Arc<dyn ObjectSafeAcceptor>);
+
+impl BoxedAcceptor {
+    pub(crate) fn new(acceptor: impl Acceptor<BoxedTransport>) -> Self {
+        Self(Arc::new(acceptor))
+    }
+}
+
+#[async_trait]
+impl Acceptor<BoxedTransport> for BoxedAcceptor {
+    type Output = BoxedTransport;
+    type Error = String;
+    async fn accept(&self, input: BoxedTransport) -> Result<Self::Output, Self::Error> {
+        ObjectSafeAcceptor::accept(&*self.0, input).await
+    }
+
+    fn is_secure(&self) -> bool {
+        ObjectSafeAcceptor::is_secure(&*self.0)
+    }
+}
+
+impl Debug for dyn ObjectSafeAcceptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("dyn ObjectSafeAcceptor")
+            .field("is_secure", &self.is_secure())
+            .finish()
+    }
+}
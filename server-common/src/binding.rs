@@ -211,4 +211,24 @@ where
     fn peer_addr(&self) -> Result<Option<std::net::SocketAddr>> {
         self.as_transport().peer_addr()
     }
+
+    fn local_addr(&self) -> Result<Option<std::net::SocketAddr>> {
+        self.as_transport().local_addr()
+    }
+
+    fn transport_kind(&self) -> crate::TransportKind {
+        self.as_transport().transport_kind()
+    }
+
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.as_transport().negotiated_alpn()
+    }
+
+    fn peer_credentials(&self) -> Result<Option<crate::PeerCredentials>> {
+        self.as_transport().peer_credentials()
+    }
+
+    fn peer_certificates(&self) -> Option<crate::PeerCertificates> {
+        self.as_transport().peer_certificates()
+    }
 }
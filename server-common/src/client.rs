@@ -4,7 +4,7 @@ use crate::{async_trait, Transport, Url};
 use std::{
     fmt::{self, Debug},
     future::Future,
-    io::Result,
+    io::{Error, ErrorKind, Result},
     pin::Pin,
     sync::Arc,
 };
@@ -31,6 +31,28 @@ pub trait Connector: Send + Sync + 'static {
 
     ///
     fn spawn<Fut: Future<Output = ()> + Send + 'static>(&self, fut: Fut);
+
+    /**
+    Establish a connection to the provided url over an already-open
+    transport, such as a tunnel through an http proxy. Connectors that
+    provide tls should perform the tls handshake for `https` urls.
+
+    The default implementation returns the transport unchanged for
+    `http` urls and returns an error for any other scheme.
+     */
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        connect_over_plaintext(url, transport)
+    }
+}
+
+fn connect_over_plaintext(url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+    match url.scheme() {
+        "http" => Ok(transport),
+        unknown => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("unable to connect to {unknown} over an existing transport"),
+        )),
+    }
 }
 
 ///
@@ -40,6 +62,10 @@ pub trait ObjectSafeConnector: Send + Sync + 'static {
     async fn connect(&self, url: &Url) -> Result<BoxedTransport>;
     ///
     fn spawn(&self, fut: Pin<Box<dyn Future<Output = ()> + Send + 'static>>);
+    /// see [`Connector::connect_over`]
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        connect_over_plaintext(url, transport)
+    }
     ///
     fn boxed(self) -> Box<dyn ObjectSafeConnector>
     where
@@ -66,6 +92,10 @@ impl<T: Connector> ObjectSafeConnector for T {
     fn spawn(&self, fut: Pin<Box<dyn Future<Output = ()> + Send + 'static>>) {
         T::spawn(self, fut)
     }
+
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        T::connect_over(self, url, transport).await
+    }
 }
 
 #[async_trait]
@@ -78,6 +108,10 @@ impl Connector for Box<dyn ObjectSafeConnector> {
     fn spawn<Fut: Future<Output = ()> + Send + 'static>(&self, fut: Fut) {
         ObjectSafeConnector::spawn(self.as_ref(), Box::pin(fut))
     }
+
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        ObjectSafeConnector::connect_over(self.as_ref(), url, transport).await
+    }
 }
 
 #[async_trait]
@@ -90,6 +124,10 @@ impl Connector for Arc<dyn ObjectSafeConnector> {
     fn spawn<Fut: Future<Output = ()> + Send + 'static>(&self, fut: Fut) {
         ObjectSafeConnector::spawn(self.as_ref(), Box::pin(fut))
     }
+
+    async fn connect_over(&self, url: &Url, transport: BoxedTransport) -> Result<BoxedTransport> {
+        ObjectSafeConnector::connect_over(self.as_ref(), url, transport).await
+    }
 }
 
 impl Debug for dyn ObjectSafeConnector {
@@ -1,13 +1,20 @@
 use crate::{
-    server_handle::CompletionFuture, Acceptor, CloneCounterObserver, Server, ServerHandle, Stopper,
+    acceptor::BoxedAcceptor, connections::Connections, listener::AdditionalListener,
+    server_handle::CompletionFuture, socket_options::SocketOptions, Acceptor, CloneCounterObserver,
+    HttpsRedirect, Listener, Server, ServerHandle, StartupError, Stopper,
 };
 use async_cell::sync::AsyncCell;
 use std::{
+    fmt::{self, Debug, Formatter},
     marker::PhantomData,
+    mem,
     net::SocketAddr,
-    sync::{Arc, RwLock},
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
 };
 use trillium::{Handler, HttpConfig, Info};
+use trillium_http::transport::BoxedTransport;
 
 /**
 # Primary entrypoint for configuring and running a trillium server
@@ -29,9 +36,18 @@ trillium_smol::config() // or trillium_async_std, trillium_tokio
 
 The socket binding logic is as follows:
 
+* If a listener was provided with [`Config::with_prebound_listener`],
+  that will be used, overriding host, port, and unix socket settings
+* On `cfg(unix)` systems, if sockets were passed with systemd socket
+  activation (`LISTEN_FDS` and `LISTEN_PID`), the first of them will
+  be used, overriding host, port, and unix socket settings. The others
+  are available through [`Listener::systemd`]
 * If a LISTEN_FD environment variable is available on `cfg(unix)`
   systems, that will be used, overriding host and port settings
 * Otherwise:
+  * On `cfg(unix)` systems only: If a path was provided with
+    [`Config::with_unix_socket`], trillium will bind to it as a unix
+    domain socket, ignoring host and port.
   * Host will be selected from explicit configuration using
     [`Config::with_host`] or else the `HOST` environment variable,
     or else a default of "localhost".
@@ -43,6 +59,18 @@ The socket binding logic is as follows:
   * Port will be selected from explicit configuration using
     [`Config::with_port`] or else the `PORT` environment variable,
     or else a default of 8080.
+* Each [`Listener`] provided with [`Config::with_additional_listener`]
+  or [`Config::with_https_redirect`] is bound in addition to the above.
+* Socket options such as [`Config::with_reuse_port`],
+  [`Config::with_backlog`], and [`Config::with_tcp_keepalive`] are
+  applied to each tcp listener before it is bound.
+
+If the listener cannot be bound, or if a handler declares a dependency
+with [`Info::require`](trillium::Info::require) that is not provided by
+an earlier handler, [`Config::run`], [`Config::run_async`], and
+[`Config::spawn`] will panic. To handle a [`StartupError`] instead,
+use [`Config::try_run`] or [`Config::try_run_async`], or validate the
+binding ahead of time with [`Config::try_bind`].
 
 ## Signals
 
@@ -63,16 +91,42 @@ pub struct Config<ServerType, AcceptorType> {
     pub(crate) acceptor: AcceptorType,
     pub(crate) port: Option<u16>,
     pub(crate) host: Option<String>,
+    pub(crate) unix_socket: Option<PathBuf>,
     pub(crate) nodelay: bool,
+    pub(crate) socket_options: SocketOptions,
     pub(crate) stopper: Stopper,
     pub(crate) observer: CloneCounterObserver,
     pub(crate) register_signals: bool,
     pub(crate) max_connections: Option<usize>,
+    pub(crate) accept_loops: usize,
     pub(crate) info: Arc<AsyncCell<Info>>,
     pub(crate) completion_future: CompletionFuture,
     pub(crate) binding: RwLock<Option<ServerType>>,
+    pub(crate) primary_listener: Option<Listener>,
     pub(crate) server: PhantomData<ServerType>,
-    pub(crate) http_config: HttpConfig,
+    pub(crate) http_config: Arc<RwLock<HttpConfig>>,
+    pub(crate) additional_listeners: Vec<AdditionalListener>,
+    pub(crate) drain_timeout: Option<Duration>,
+    pub(crate) drain_hook: Option<DrainHook>,
+    pub(crate) force_close: Stopper,
+    pub(crate) connections: Connections,
+}
+
+/// A function that is called with the number of open connections
+/// when a server starts draining them
+#[derive(Clone)]
+pub(crate) struct DrainHook(Arc<dyn Fn(usize) + Send + Sync + 'static>);
+
+impl DrainHook {
+    pub(crate) fn call(&self, open_connections: usize) {
+        (self.0)(open_connections);
+    }
+}
+
+impl Debug for DrainHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("DrainHook(..)")
+    }
 }
 
 impl<ServerType, AcceptorType> Config<ServerType, AcceptorType>
@@ -101,6 +155,71 @@ where
         completion_future.notify()
     }
 
+    /**
+    Like [`Config::run`], but returns a [`StartupError`] instead of
+    panicking if the server cannot be started, for example because the
+    port is already in use or the host cannot be resolved.
+
+    ```rust,no_run
+    let result = trillium_smol::config()
+        .with_port(8080)
+        .try_run(|conn: trillium::Conn| async move { conn.ok("hello") });
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    ```
+    */
+    pub fn try_run<H: Handler>(self, handler: H) -> Result<(), StartupError> {
+        let result = Arc::new(Mutex::new(Ok(())));
+        let result_clone = Arc::clone(&result);
+        ServerType::block_on(async move {
+            *result_clone.lock().unwrap() = self.try_run_async(handler).await;
+        });
+        let mut result = result.lock().unwrap();
+        mem::replace(&mut *result, Ok(()))
+    }
+
+    /// Like [`Config::run_async`], but returns a [`StartupError`]
+    /// instead of panicking if the server cannot be started.
+    pub async fn try_run_async(self, handler: impl Handler) -> Result<(), StartupError> {
+        let completion_future = self.completion_future.clone();
+        let result = ServerType::try_run_async(self, handler).await;
+        completion_future.notify();
+        result
+    }
+
+    /**
+    Binds the listener described by this config immediately, returning
+    a [`StartupError`] if that is not possible. The bound listener is
+    retained and will be used when this config is run, so a successful
+    `try_bind` means that the server will not fail to start due to
+    its host or port.
+
+    Some runtime adapters (such as tokio) require this to be called
+    from within a running runtime.
+
+    ```
+    use std::net::TcpListener;
+    let taken = TcpListener::bind("127.0.0.1:0")?;
+    let port = taken.local_addr()?.port();
+
+    let result = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(port)
+        .try_bind();
+
+    assert!(result.is_err_and(|e| e.is_address_in_use()));
+    # Ok::<(), std::io::Error>(())
+    ```
+    */
+    pub fn try_bind(self) -> Result<Self, StartupError> {
+        let server = ServerType::try_build_listener(&self)?;
+        *self.binding.write().unwrap() = Some(server);
+        Ok(self)
+    }
+
     /// Spawns the server onto the async runtime, returning a
     /// ServerHandle that can be awaited directly to return an
     /// [`Info`] or used with [`ServerHandle::info`] and
@@ -119,6 +238,9 @@ where
             info: self.info.clone(),
             completion: self.completion_future.clone(),
             observer: self.observer.clone(),
+            http_config: self.http_config.clone(),
+            force_close: self.force_close.clone(),
+            connections: self.connections.clone(),
         }
     }
 
@@ -143,6 +265,32 @@ where
         self
     }
 
+    /**
+    Configures the server to listen on a unix domain socket at this
+    path instead of tcp. Host and port will be ignored.
+
+    The socket file is deleted when the server shuts down
+    cleanly. If a socket file already exists at this path but no
+    process is listening on it, such as after a crash, it will be
+    replaced. The credentials of the connecting process are
+    available through [`Conn::peer_credentials`](trillium::Conn::peer_credentials)
+    on linux and android.
+
+    ```rust,no_run
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_unix_socket("/run/app/app.sock")
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    #[cfg(unix)]
+    pub fn with_unix_socket(mut self, path: impl Into<PathBuf>) -> Self {
+        if self.has_binding() {
+            eprintln!("constructing a config with both a unix socket and a pre-bound listener will ignore the unix socket. this may be a panic in the future");
+        }
+        self.unix_socket = Some(path.into());
+        self
+    }
+
     /// Configures the server to NOT register for graceful-shutdown
     /// signals with the operating system. Default behavior is for the
     /// server to listen for SIGINT and SIGTERM and perform a graceful
@@ -160,6 +308,73 @@ where
         self
     }
 
+    /**
+    Configures whether tcp listeners set `SO_REUSEADDR` before
+    binding. This is enabled by default on `cfg(unix)` systems, which
+    allows a restarted server to bind to a port that still has
+    connections in `TIME_WAIT`.
+
+    Like the other socket options on this config, this applies to
+    every tcp listener that trillium binds, including additional
+    listeners, but not to pre-bound listeners, sockets passed with
+    systemd socket activation, or a `LISTEN_FD`.
+    */
+    pub fn with_reuse_address(mut self, reuse_address: bool) -> Self {
+        self.socket_options.reuse_address = Some(reuse_address);
+        self
+    }
+
+    /**
+    Configures tcp listeners to set `SO_REUSEPORT` before binding,
+    which allows several processes to listen on the same host and
+    port. On linux, the kernel balances incoming connections across
+    those processes.
+
+    ```rust,no_run
+    // run this in each worker process
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_port(8080)
+        .with_reuse_port()
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    #[cfg(unix)]
+    pub fn with_reuse_port(mut self) -> Self {
+        self.socket_options.reuse_port = true;
+        self
+    }
+
+    /// Configures the maximum length of the queue of pending
+    /// connections for tcp listeners. The default is 128, and the
+    /// operating system may silently limit this value.
+    pub fn with_backlog(mut self, backlog: u32) -> Self {
+        self.socket_options.backlog = Some(backlog);
+        self
+    }
+
+    /// Enables tcp keepalive on accepted connections, sending the
+    /// first keepalive probe after a connection has been idle for this
+    /// duration. Accepted connections inherit this from the listener
+    /// on linux and the bsds.
+    pub fn with_tcp_keepalive(mut self, idle: Duration) -> Self {
+        self.socket_options.tcp_keepalive = Some(idle);
+        self
+    }
+
+    /// Configures the size of the `SO_RCVBUF` socket receive buffer
+    /// for tcp listeners and the connections they accept
+    pub fn with_recv_buffer_size(mut self, size: usize) -> Self {
+        self.socket_options.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Configures the size of the `SO_SNDBUF` socket send buffer for
+    /// tcp listeners and the connections they accept
+    pub fn with_send_buffer_size(mut self, size: usize) -> Self {
+        self.socket_options.send_buffer_size = Some(size);
+        self
+    }
+
     /// Configures the server to listen on the ip and port specified
     /// by the provided socketaddr. This is identical to
     /// `self.with_host(&socketaddr.ip().to_string()).with_port(socketaddr.port())`
@@ -177,19 +392,101 @@ where
             acceptor,
             host: self.host,
             port: self.port,
+            unix_socket: self.unix_socket,
             nodelay: self.nodelay,
+            socket_options: self.socket_options,
             server: PhantomData,
             stopper: self.stopper,
             observer: self.observer,
             register_signals: self.register_signals,
             max_connections: self.max_connections,
+            accept_loops: self.accept_loops,
             info: self.info,
             completion_future: self.completion_future,
             binding: self.binding,
+            primary_listener: self.primary_listener,
             http_config: self.http_config,
+            additional_listeners: self.additional_listeners,
+            drain_timeout: self.drain_timeout,
+            drain_hook: self.drain_hook,
+            force_close: self.force_close,
+            connections: self.connections,
         }
     }
 
+    /**
+    Configures the server to also listen on the provided
+    [`Listener`], using the provided acceptor for connections to that
+    listener. This may be called more than once.
+
+    All listeners share the same handler, [`HttpConfig`], connection
+    limit, and [`Stopper`], so stopping the server stops every
+    listener, and graceful shutdown waits for connections on every
+    listener. Additional listeners are bound when the server starts,
+    even if the primary listener was bound with [`Config::try_bind`].
+
+    ```rust,no_run
+    use trillium_smol::Listener; // or trillium_async_std, trillium_tokio
+    # let tls_acceptor = ();
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_host("0.0.0.0")
+        .with_port(80)
+        .with_additional_listener(Listener::tcp("0.0.0.0", 443), tls_acceptor)
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    pub fn with_additional_listener<A: Acceptor<BoxedTransport>>(
+        mut self,
+        listener: Listener,
+        acceptor: A,
+    ) -> Self {
+        self.additional_listeners.push(AdditionalListener {
+            listener,
+            acceptor: BoxedAcceptor::new(acceptor),
+            https_redirect: None,
+        });
+        self
+    }
+
+    /**
+    Configures the server to also listen for plaintext http on the
+    provided [`Listener`], redirecting every request to https with
+    the provided [`HttpsRedirect`] instead of running the application
+    handler. The `HttpsRedirect` can also answer acme http-01
+    challenges, which must be served over plaintext http.
+
+    This is usually combined with a tls acceptor for the primary
+    listener.
+
+    ```rust,no_run
+    use trillium_smol::{AcmeChallenges, HttpsRedirect, Listener}; // or trillium_async_std, trillium_tokio
+    # let tls_acceptor = ();
+    let acme_challenges = AcmeChallenges::new();
+    // provide acme_challenges to an acme client
+    trillium_smol::config()
+        .with_host("0.0.0.0")
+        .with_port(443)
+        .with_acceptor(tls_acceptor)
+        .with_https_redirect(
+            Listener::tcp("0.0.0.0", 80),
+            HttpsRedirect::new().with_acme_challenges(acme_challenges),
+        )
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    pub fn with_https_redirect(
+        mut self,
+        listener: Listener,
+        https_redirect: HttpsRedirect,
+    ) -> Self {
+        self.additional_listeners.push(AdditionalListener {
+            listener,
+            acceptor: BoxedAcceptor::new(()),
+            https_redirect: Some(Arc::new(https_redirect)),
+        });
+        self
+    }
+
     /// use the specific [`Stopper`] provided
     pub fn with_stopper(mut self, stopper: Stopper) -> Self {
         self.stopper = stopper;
@@ -203,6 +500,48 @@ where
         self
     }
 
+    /**
+    Configures the maximum duration to wait for open connections to
+    close after the server is stopped. Any connections that are
+    still open when this duration elapses are closed, even if a
+    response is in progress. The default is to wait indefinitely.
+
+    While the server is draining, responses on open connections are
+    sent with `Connection: close`, and idle keep-alive connections
+    are closed.
+
+    ```rust,no_run
+    use std::time::Duration;
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_drain_timeout(Duration::from_secs(30))
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    pub fn with_drain_timeout(mut self, drain_timeout: Duration) -> Self {
+        self.drain_timeout = Some(drain_timeout);
+        self
+    }
+
+    /**
+    Registers a function to be called when the server is stopped and
+    starts draining open connections, with the number of connections
+    that are open at that time. This is called before waiting for
+    connections to close, and is called even if no connections are
+    open.
+
+    ```rust,no_run
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_drain_hook(|open_connections| {
+            log::info!("draining {open_connections} connections");
+        })
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    pub fn with_drain_hook(mut self, drain_hook: impl Fn(usize) + Send + Sync + 'static) -> Self {
+        self.drain_hook = Some(DrainHook(Arc::new(drain_hook)));
+        self
+    }
+
     /**
     Configures the maximum number of connections to accept. The
     default is 75% of the soft rlimit_nofile (`ulimit -n`) on unix
@@ -213,11 +552,73 @@ where
         self
     }
 
+    /**
+    Configures the number of accept loops to run for each listener.
+    The default is one.
+
+    Each accept loop is spawned onto the runtime's executor and
+    accepts from the same listening socket, so that on a
+    multi-threaded executor, accepting new connections is spread
+    across threads instead of being bound by a single task. Setting
+    this to the number of available cores is a reasonable starting
+    point for servers that accept many short-lived connections.
+
+    To instead run several processes that each accept from their own
+    socket, see [`Config::with_reuse_port`].
+
+    ```rust,no_run
+    let cores = std::thread::available_parallelism().map_or(1, usize::from);
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_accept_loops(cores)
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    pub fn with_accept_loops(mut self, accept_loops: usize) -> Self {
+        self.accept_loops = accept_loops.max(1);
+        self
+    }
+
     /// configures trillium-http performance and security tuning parameters.
     ///
-    /// See [`HttpConfig`] for documentation
+    /// See [`HttpConfig`] for documentation. These parameters can be
+    /// changed while the server is running with
+    /// [`ServerHandle::set_http_config`].
     pub fn with_http_config(mut self, http_config: HttpConfig) -> Self {
-        self.http_config = http_config;
+        self.http_config = Arc::new(RwLock::new(http_config));
+        self
+    }
+
+    /**
+    Uses a listener that was bound outside of trillium as the primary
+    listener, instead of binding to a host and port or unix socket.
+    This accepts a [`std::net::TcpListener`], a
+    [`std::os::unix::net::UnixListener`] on `cfg(unix)` systems, or
+    any [`Listener`], including one passed with systemd socket
+    activation through [`Listener::systemd_named`].
+
+    Unlike [`Config::with_prebound_server`], this does not require the
+    runtime's listener type and is not consumed by binding, so a
+    cloned config will share the same socket. The listener is set to
+    nonblocking when the server starts, and unix sockets passed this
+    way are not deleted on shutdown.
+
+    ```rust,no_run
+    let listener = std::net::TcpListener::bind("0.0.0.0:8080").unwrap();
+    trillium_smol::config() // or trillium_async_std, trillium_tokio
+        .with_prebound_listener(listener)
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+    */
+    pub fn with_prebound_listener(mut self, listener: impl Into<Listener>) -> Self {
+        if self.host.is_some() {
+            eprintln!("constructing a config with both a host and a pre-bound listener will ignore the host. this may be a panic in the future");
+        }
+
+        if self.port.is_some() {
+            eprintln!("constructing a config with both a port and a pre-bound listener will ignore the port. this may be a panic in the future");
+        }
+
+        self.primary_listener = Some(listener.into());
         self
     }
 
@@ -245,6 +646,25 @@ where
         self
     }
 
+    /// whether the primary listener was bound outside of trillium, in
+    /// which case it should not be cleaned up on shutdown
+    pub(crate) fn primary_is_prebound(&self) -> bool {
+        if self.has_binding() {
+            return false;
+        }
+
+        if let Some(listener) = &self.primary_listener {
+            return listener.is_prebound();
+        }
+
+        #[cfg(unix)]
+        if !crate::socket_activation::listeners().is_empty() {
+            return true;
+        }
+
+        false
+    }
+
     fn has_binding(&self) -> bool {
         self.binding
             .read()
@@ -274,16 +694,25 @@ where
             acceptor: self.acceptor.clone(),
             port: self.port,
             host: self.host.clone(),
+            unix_socket: self.unix_socket.clone(),
             server: PhantomData,
             nodelay: self.nodelay,
+            socket_options: self.socket_options,
             stopper: self.stopper.clone(),
             observer: self.observer.clone(),
             register_signals: self.register_signals,
             max_connections: self.max_connections,
+            accept_loops: self.accept_loops,
             info: AsyncCell::shared(),
             completion_future: CompletionFuture::new(),
             binding: RwLock::new(None),
-            http_config: self.http_config,
+            primary_listener: self.primary_listener.clone(),
+            http_config: Arc::new(RwLock::new(*self.http_config.read().unwrap())),
+            additional_listeners: self.additional_listeners.clone(),
+            drain_timeout: self.drain_timeout,
+            drain_hook: self.drain_hook.clone(),
+            force_close: self.force_close.clone(),
+            connections: self.connections.clone(),
         }
     }
 }
@@ -307,16 +736,25 @@ impl<ServerType: Server> Default for Config<ServerType, ()> {
             acceptor: (),
             port: None,
             host: None,
+            unix_socket: None,
             server: PhantomData,
             nodelay: false,
+            socket_options: SocketOptions::default(),
             stopper: Stopper::new(),
             observer: CloneCounterObserver::new(),
             register_signals: cfg!(unix),
             max_connections,
+            accept_loops: 1,
             info: AsyncCell::shared(),
             completion_future: CompletionFuture::new(),
             binding: RwLock::new(None),
-            http_config: HttpConfig::default(),
+            primary_listener: None,
+            http_config: Arc::new(RwLock::new(HttpConfig::default())),
+            additional_listeners: vec![],
+            drain_timeout: None,
+            drain_hook: None,
+            force_close: Stopper::new(),
+            connections: Connections::default(),
         }
     }
 }
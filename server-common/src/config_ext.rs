@@ -1,10 +1,14 @@
-use crate::{Acceptor, CloneCounterObserver, Config, Server, Stopper, Transport};
-use futures_lite::prelude::*;
+use crate::{
+    connections::ConnectionGuard, Acceptor, CloneCounterObserver, Config, Server, Stopper,
+    Transport,
+};
+use async_io::Timer;
+use futures_lite::{future, prelude::*};
 use std::{
     io::ErrorKind,
     net::{SocketAddr, TcpListener, ToSocketAddrs},
 };
-use trillium::Handler;
+use trillium::{Handler, Spawner};
 use trillium_http::{transport::BoxedTransport, Conn as HttpConn, Error, SERVICE_UNAVAILABLE};
 /// # Server-implementer interfaces to Config
 ///
@@ -132,18 +136,106 @@ where
 
     async fn graceful_shutdown(&self) {
         let current = self.observer.current();
+        if let Some(drain_hook) = &self.drain_hook {
+            drain_hook.call(current);
+        }
+
         if current > 0 {
             log::info!(
                 "waiting for {} open connection{} to close",
                 current,
                 if current == 1 { "" } else { "s" }
             );
-            self.observer.clone().await;
+
+            let drained = match self.drain_timeout {
+                Some(drain_timeout) => {
+                    future::or(
+                        async {
+                            self.observer.clone().await;
+                            true
+                        },
+                        async {
+                            Timer::after(drain_timeout).await;
+                            false
+                        },
+                    )
+                    .await
+                }
+                None => {
+                    self.observer.clone().await;
+                    true
+                }
+            };
+
+            if !drained {
+                let current = self.observer.current();
+                log::warn!(
+                    "closing {} connection{} after drain timeout",
+                    current,
+                    if current == 1 { "" } else { "s" }
+                );
+                self.force_close.stop();
+                self.observer.clone().await;
+            }
+
             log::info!("all done!")
         }
     }
 
-    async fn handle_stream(&self, mut stream: ServerType::Transport, handler: impl Handler) {
+    async fn handle_stream(&self, stream: ServerType::Transport, handler: impl Handler) {
+        self.serve_stream(stream, &self.acceptor, handler).await;
+    }
+
+    fn build_listener<Listener>(&self) -> Listener
+    where
+        Listener: TryFrom<TcpListener>,
+        <Listener as TryFrom<TcpListener>>::Error: std::fmt::Debug,
+    {
+        #[cfg(unix)]
+        let listener = {
+            use std::os::unix::prelude::FromRawFd;
+
+            if let Some(fd) = std::env::var("LISTEN_FD")
+                .ok()
+                .and_then(|fd| fd.parse().ok())
+            {
+                log::debug!("using fd {} from LISTEN_FD", fd);
+                unsafe { TcpListener::from_raw_fd(fd) }
+            } else {
+                TcpListener::bind((self.host(), self.port())).unwrap()
+            }
+        };
+
+        #[cfg(not(unix))]
+        let listener = TcpListener::bind((self.host(), self.port())).unwrap();
+
+        listener.set_nonblocking(true).unwrap();
+        listener.try_into().unwrap()
+    }
+
+    fn over_capacity(&self) -> bool {
+        self.max_connections
+            .map_or(false, |m| self.observer.current() >= m)
+    }
+}
+
+impl<ServerType, AcceptorType> Config<ServerType, AcceptorType>
+where
+    ServerType: Server,
+    AcceptorType: Acceptor<<ServerType as Server>::Transport>,
+{
+    /// the implementation of [`ConfigExt::handle_stream`], generic over
+    /// the transport and acceptor so that it can also be used for
+    /// additional listeners
+    pub(crate) async fn serve_stream<T, A>(
+        &self,
+        mut stream: T,
+        acceptor: &A,
+        handler: impl Handler,
+    ) where
+        T: Transport,
+        A: Acceptor<T>,
+    {
         if self.over_capacity() {
             let mut byte = [0u8]; // wait for the client to start requesting
             trillium::log_error!(stream.read(&mut byte).await);
@@ -152,12 +244,35 @@ where
         }
 
         let counter = self.observer.counter();
+        let peer_ip = stream.peer_addr().ok().flatten().map(|addr| addr.ip());
+        let connection = self.connections.open(peer_ip);
+
+        let serve = self.serve_connection(stream, acceptor, handler, &connection);
+        let serve = connection.stopper().stop_future(serve);
+        match self.force_close.stop_future(serve).await {
+            None => log::debug!("closing connection after drain timeout"),
+            Some(None) => log::debug!("closing connection from server handle"),
+            Some(Some(())) => {}
+        }
 
-        trillium::log_error!(stream.set_nodelay(self.nodelay));
+        drop(connection);
+        drop(counter);
+    }
 
-        let peer_ip = stream.peer_addr().ok().flatten().map(|addr| addr.ip());
+    /// accepts and serves a single connection until it is closed
+    async fn serve_connection<T, A>(
+        &self,
+        mut stream: T,
+        acceptor: &A,
+        handler: impl Handler,
+        connection: &ConnectionGuard,
+    ) where
+        T: Transport,
+        A: Acceptor<T>,
+    {
+        trillium::log_error!(stream.set_nodelay(self.nodelay));
 
-        let stream = match self.acceptor.accept(stream).await {
+        let stream = match acceptor.accept(stream).await {
             Ok(stream) => stream,
             Err(e) => {
                 log::error!("acceptor error: {:?}", e);
@@ -165,13 +280,53 @@ where
             }
         };
 
+        // read from the accepted transport so that acceptors such as
+        // ProxyProtocolAcceptor can provide the original peer address
+        let peer_ip = stream.peer_addr().ok().flatten().map(|addr| addr.ip());
+        connection.set_peer_ip(peer_ip);
+
         let handler = &handler;
+        let secure = acceptor.is_secure();
+        let http_config = *self.http_config.read().unwrap();
+        let spawner = || Spawner::new(|fut| ServerType::spawn(fut));
+
+        #[cfg(feature = "http2")]
+        if stream.negotiated_alpn() == Some(b"h2") {
+            let result = HttpConn::map_http2(
+                http_config,
+                stream,
+                self.stopper.clone(),
+                move |mut conn| async move {
+                    conn.set_peer_ip(peer_ip);
+                    conn.set_secure(secure);
+                    conn.state_mut().insert(spawner());
+                    let request = connection.request();
+                    conn.after_send(move |_| drop(request));
+                    let conn = handler.run(conn.into()).await;
+                    let conn = handler.before_send(conn).await;
+
+                    conn.into_inner()
+                },
+            )
+            .await;
+
+            if let Err(e) = result {
+                log::error!("http/2 error: {:?}", e);
+            }
+
+            return;
+        }
+
         let result = HttpConn::map_with_config(
-            self.http_config,
+            http_config,
             stream,
             self.stopper.clone(),
             |mut conn| async {
                 conn.set_peer_ip(peer_ip);
+                conn.set_secure(secure);
+                conn.state_mut().insert(spawner());
+                let request = connection.request();
+                conn.after_send(move |_| drop(request));
                 let conn = handler.run(conn.into()).await;
                 let conn = handler.before_send(conn).await;
 
@@ -205,39 +360,5 @@ where
                 log::error!("http error: {:?}", e);
             }
         };
-
-        drop(counter);
-    }
-
-    fn build_listener<Listener>(&self) -> Listener
-    where
-        Listener: TryFrom<TcpListener>,
-        <Listener as TryFrom<TcpListener>>::Error: std::fmt::Debug,
-    {
-        #[cfg(unix)]
-        let listener = {
-            use std::os::unix::prelude::FromRawFd;
-
-            if let Some(fd) = std::env::var("LISTEN_FD")
-                .ok()
-                .and_then(|fd| fd.parse().ok())
-            {
-                log::debug!("using fd {} from LISTEN_FD", fd);
-                unsafe { TcpListener::from_raw_fd(fd) }
-            } else {
-                TcpListener::bind((self.host(), self.port())).unwrap()
-            }
-        };
-
-        #[cfg(not(unix))]
-        let listener = TcpListener::bind((self.host(), self.port())).unwrap();
-
-        listener.set_nonblocking(true).unwrap();
-        listener.try_into().unwrap()
-    }
-
-    fn over_capacity(&self) -> bool {
-        self.max_connections
-            .map_or(false, |m| self.observer.current() >= m)
     }
 }
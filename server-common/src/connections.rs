@@ -0,0 +1,187 @@
+use std::{
+    collections::BTreeMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+use trillium_http::Stopper;
+
+/// The registry of connections that are currently open on a server,
+/// which is shared between the server and its
+/// [`ServerHandle`](crate::ServerHandle)s
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Connections(Arc<ConnectionsInner>);
+
+#[derive(Debug, Default)]
+struct ConnectionsInner {
+    next_id: AtomicU64,
+    open: Mutex<BTreeMap<u64, Arc<ConnectionState>>>,
+}
+
+#[derive(Debug)]
+struct ConnectionState {
+    peer_ip: RwLock<Option<IpAddr>>,
+    opened_at: Instant,
+    requests_in_flight: AtomicUsize,
+    stopper: Stopper,
+}
+
+impl ConnectionState {
+    fn info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            peer_ip: *self.peer_ip.read().unwrap(),
+            opened_at: self.opened_at,
+            requests_in_flight: self.requests_in_flight.load(Ordering::SeqCst),
+        }
+    }
+}
+
+/**
+A snapshot of a connection that is open on a server, as returned by
+[`ServerHandle::connections`](crate::ServerHandle::connections) and
+passed to the predicate for
+[`ServerHandle::close_connections`](crate::ServerHandle::close_connections)
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    peer_ip: Option<IpAddr>,
+    opened_at: Instant,
+    requests_in_flight: usize,
+}
+
+impl ConnectionInfo {
+    /// The remote ip address of this connection, if available. For
+    /// connections accepted with a
+    /// [`ProxyProtocolAcceptor`](crate::ProxyProtocolAcceptor), this is
+    /// the original client address once the header has been read.
+    pub fn peer_ip(&self) -> Option<IpAddr> {
+        self.peer_ip
+    }
+
+    /// The time that this connection was accepted
+    pub fn opened_at(&self) -> Instant {
+        self.opened_at
+    }
+
+    /// How long this connection has been open
+    pub fn age(&self) -> Duration {
+        self.opened_at.elapsed()
+    }
+
+    /// The number of requests on this connection whose responses have
+    /// not been completely sent. This is at most one for http/1.x
+    /// connections.
+    pub fn requests_in_flight(&self) -> usize {
+        self.requests_in_flight
+    }
+}
+
+/// A connection's membership in [`Connections`], which removes the
+/// connection when dropped
+#[derive(Debug)]
+pub(crate) struct ConnectionGuard {
+    id: u64,
+    connections: Connections,
+    state: Arc<ConnectionState>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.connections.0.open.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl ConnectionGuard {
+    pub(crate) fn set_peer_ip(&self, peer_ip: Option<IpAddr>) {
+        *self.state.peer_ip.write().unwrap() = peer_ip;
+    }
+
+    /// The stopper that closes only this connection
+    pub(crate) fn stopper(&self) -> &Stopper {
+        &self.state.stopper
+    }
+
+    /// Counts a request as in flight until the returned guard is
+    /// dropped
+    pub(crate) fn request(&self) -> RequestGuard {
+        self.state.requests_in_flight.fetch_add(1, Ordering::SeqCst);
+        RequestGuard(Arc::clone(&self.state))
+    }
+}
+
+/// A request that is in flight on a connection, which is no longer
+/// counted once dropped
+#[derive(Debug)]
+pub(crate) struct RequestGuard(Arc<ConnectionState>);
+
+impl Drop for RequestGuard {
+    fn drop(&mut self) {
+        self.0.requests_in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl Connections {
+    pub(crate) fn open(&self, peer_ip: Option<IpAddr>) -> ConnectionGuard {
+        let id = self.0.next_id.fetch_add(1, Ordering::Relaxed);
+        let state = Arc::new(ConnectionState {
+            peer_ip: RwLock::new(peer_ip),
+            opened_at: Instant::now(),
+            requests_in_flight: AtomicUsize::new(0),
+            stopper: Stopper::new(),
+        });
+        self.0.open.lock().unwrap().insert(id, Arc::clone(&state));
+
+        ConnectionGuard {
+            id,
+            connections: self.clone(),
+            state,
+        }
+    }
+
+    pub(crate) fn snapshot(&self) -> Vec<ConnectionInfo> {
+        self.0
+            .open
+            .lock()
+            .unwrap()
+            .values()
+            .map(|state| state.info())
+            .collect()
+    }
+
+    pub(crate) fn requests_in_flight(&self) -> usize {
+        self.0
+            .open
+            .lock()
+            .unwrap()
+            .values()
+            .map(|state| state.requests_in_flight.load(Ordering::SeqCst))
+            .sum()
+    }
+
+    /// Closes every open connection that matches the predicate,
+    /// returning the number of connections closed
+    pub(crate) fn close(&self, predicate: impl Fn(&ConnectionInfo) -> bool) -> usize {
+        // the predicate is called after releasing the registry so that
+        // it can use the server handle without deadlocking
+        let open = self
+            .0
+            .open
+            .lock()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut closed = 0;
+        for state in open {
+            if !state.stopper.is_stopped() && predicate(&state.info()) {
+                state.stopper.stop();
+                closed += 1;
+            }
+        }
+        closed
+    }
+}
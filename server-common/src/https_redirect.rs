@@ -0,0 +1,165 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use trillium::{
+    async_trait, Conn, Handler,
+    KnownHeaderName::{ContentType, Location},
+    Status,
+};
+
+const ACME_CHALLENGE_PREFIX: &str = "/.well-known/acme-challenge/";
+
+/**
+A shared set of pending [ACME
+HTTP-01](https://www.rfc-editor.org/rfc/rfc8555#section-8.3) challenges,
+to be answered by an [`HttpsRedirect`].
+
+Clones share the same set, so an acme client can insert a challenge
+into a clone while the server is running.
+
+```
+use trillium_server_common::AcmeChallenges;
+let challenges = AcmeChallenges::new();
+challenges.insert("token", "token.key-thumbprint");
+assert_eq!(challenges.get("token").as_deref(), Some("token.key-thumbprint"));
+challenges.remove("token");
+assert!(challenges.get("token").is_none());
+```
+*/
+#[derive(Clone, Debug, Default)]
+pub struct AcmeChallenges(Arc<RwLock<HashMap<String, String>>>);
+
+impl AcmeChallenges {
+    /// builds a new empty set of challenges
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// respond to requests for `/.well-known/acme-challenge/{token}`
+    /// with the provided key authorization
+    pub fn insert(&self, token: impl Into<String>, key_authorization: impl Into<String>) {
+        self.0
+            .write()
+            .unwrap()
+            .insert(token.into(), key_authorization.into());
+    }
+
+    /// stop responding to requests for this token
+    pub fn remove(&self, token: &str) -> Option<String> {
+        self.0.write().unwrap().remove(token)
+    }
+
+    /// the key authorization for this token, if any
+    pub fn get(&self, token: &str) -> Option<String> {
+        self.0.read().unwrap().get(token).cloned()
+    }
+}
+
+/**
+A handler that redirects every request to the same host and path over
+https with `301 Moved Permanently`, except for requests to
+`/.well-known/acme-challenge/{token}` that match an entry in its
+[`AcmeChallenges`].
+
+This is usually used with
+[`Config::with_https_redirect`](crate::Config::with_https_redirect),
+but it is also an ordinary handler.
+
+```
+use trillium_server_common::{AcmeChallenges, HttpsRedirect};
+use trillium_testing::prelude::*;
+
+let challenges = AcmeChallenges::new();
+challenges.insert("token", "token.key-thumbprint");
+let handler = HttpsRedirect::new()
+    .with_https_port(8443)
+    .with_acme_challenges(challenges);
+
+assert_response!(
+    get("/some/path?query")
+        .with_request_header("host", "example.com")
+        .on(&handler),
+    301,
+    "",
+    "location" => "https://example.com:8443/some/path?query"
+);
+
+assert_ok!(
+    get("/.well-known/acme-challenge/token")
+        .with_request_header("host", "example.com")
+        .on(&handler),
+    "token.key-thumbprint"
+);
+```
+*/
+#[derive(Clone, Debug, Default)]
+pub struct HttpsRedirect {
+    https_port: Option<u16>,
+    acme_challenges: AcmeChallenges,
+}
+
+impl HttpsRedirect {
+    /// builds a new HttpsRedirect to the default https port, with no
+    /// acme challenges
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// redirect to this port instead of the default of 443
+    pub fn with_https_port(mut self, https_port: u16) -> Self {
+        self.https_port = Some(https_port);
+        self
+    }
+
+    /// answer acme http-01 challenges from this [`AcmeChallenges`]
+    pub fn with_acme_challenges(mut self, acme_challenges: AcmeChallenges) -> Self {
+        self.acme_challenges = acme_challenges;
+        self
+    }
+
+    /// borrow the [`AcmeChallenges`] for this redirect
+    pub fn acme_challenges(&self) -> &AcmeChallenges {
+        &self.acme_challenges
+    }
+
+    fn location(&self, conn: &Conn) -> Option<String> {
+        let host = conn.inner().host()?;
+        let hostname = match host.rsplit_once(':') {
+            Some((hostname, port)) if !port.contains(']') => hostname,
+            _ => host,
+        };
+
+        let port = match self.https_port {
+            Some(443) | None => String::new(),
+            Some(port) => format!(":{port}"),
+        };
+
+        Some(format!(
+            "https://{hostname}{port}{}",
+            conn.inner().path_and_query()
+        ))
+    }
+}
+
+#[async_trait]
+impl Handler for HttpsRedirect {
+    async fn run(&self, conn: Conn) -> Conn {
+        if let Some(token) = conn.path().strip_prefix(ACME_CHALLENGE_PREFIX) {
+            return match self.acme_challenges.get(token) {
+                Some(key_authorization) => conn
+                    .with_response_header(ContentType, "text/plain")
+                    .ok(key_authorization),
+                None => conn.with_status(Status::NotFound).halt(),
+            };
+        }
+
+        match self.location(&conn) {
+            Some(location) => conn
+                .with_status(Status::MovedPermanently)
+                .with_response_header(Location, location)
+                .halt(),
+            None => conn.with_status(Status::BadRequest).halt(),
+        }
+    }
+}
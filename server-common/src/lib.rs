@@ -25,7 +25,10 @@ issue.
 */
 pub use async_trait::async_trait;
 pub use futures_lite::{AsyncRead, AsyncWrite};
-pub use trillium_http::{transport::Transport, Stopper};
+pub use trillium_http::{
+    transport::{BoxedTransport, PeerCertificates, PeerCredentials, Transport, TransportKind},
+    Stopper,
+};
 pub use url;
 pub use url::Url;
 
@@ -50,5 +53,30 @@ pub use client::{Connector, ObjectSafeConnector};
 mod acceptor;
 pub use acceptor::Acceptor;
 
+mod listener;
+pub use listener::Listener;
+
+mod https_redirect;
+pub use https_redirect::{AcmeChallenges, HttpsRedirect};
+
+mod proxy_protocol;
+pub use proxy_protocol::{ProxyProtocolAcceptor, ProxyProtocolError, ProxyProtocolTransport};
+
+mod connections;
+pub use connections::ConnectionInfo;
+
 mod server_handle;
 pub use server_handle::ServerHandle;
+
+mod socket_options;
+
+#[cfg(unix)]
+mod socket_activation;
+
+#[cfg(unix)]
+mod peer_credentials;
+#[cfg(unix)]
+pub use peer_credentials::unix_peer_credentials;
+
+mod startup_error;
+pub use startup_error::{MissingDependencies, StartupError};
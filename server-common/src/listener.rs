@@ -0,0 +1,159 @@
+use crate::{acceptor::BoxedAcceptor, HttpsRedirect};
+use std::{
+    fmt::{self, Debug, Formatter},
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+};
+#[cfg(unix)]
+use std::{os::unix::net::UnixListener, path::PathBuf};
+
+/**
+An address or already-bound socket for a server to listen on
+
+This can be used as the primary listener with
+[`Config::with_prebound_listener`](crate::Config::with_prebound_listener),
+or in addition to the primary listener with
+[`Config::with_additional_listener`](crate::Config::with_additional_listener).
+
+Already-bound std listeners can be converted into a `Listener` with
+[`From`], which allows a supervisor process to bind sockets and pass
+them to a trillium server. On `cfg(unix)` systems, sockets passed with
+systemd socket activation are available with [`Listener::systemd`].
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listener(pub(crate) ListenerKind);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ListenerKind {
+    Tcp {
+        host: String,
+        port: u16,
+    },
+    #[cfg(unix)]
+    Unix(PathBuf),
+    PreboundTcp(Prebound<TcpListener>),
+    #[cfg(unix)]
+    PreboundUnix(Prebound<UnixListener>),
+}
+
+/// A shared already-bound listener. Servers bind a duplicate of the
+/// file descriptor, so the listener can be used again if the server
+/// is restarted.
+pub(crate) struct Prebound<T>(pub(crate) Arc<T>);
+
+impl<T> Clone for Prebound<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T: Debug> Debug for Prebound<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+impl<T> PartialEq for Prebound<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Eq for Prebound<T> {}
+
+impl From<TcpListener> for Listener {
+    fn from(listener: TcpListener) -> Self {
+        Self(ListenerKind::PreboundTcp(Prebound(Arc::new(listener))))
+    }
+}
+
+#[cfg(unix)]
+impl From<UnixListener> for Listener {
+    fn from(listener: UnixListener) -> Self {
+        Self(ListenerKind::PreboundUnix(Prebound(Arc::new(listener))))
+    }
+}
+
+impl Listener {
+    /// listen on the provided host or ip address and port
+    pub fn tcp(host: impl Into<String>, port: u16) -> Self {
+        Self(ListenerKind::Tcp {
+            host: host.into(),
+            port,
+        })
+    }
+
+    /// listen on the ip and port of the provided socketaddr
+    pub fn socketaddr(socketaddr: SocketAddr) -> Self {
+        Self::tcp(socketaddr.ip().to_string(), socketaddr.port())
+    }
+
+    /// listen on a unix domain socket at this path. The socket file
+    /// is deleted when the server shuts down cleanly.
+    #[cfg(unix)]
+    pub fn unix(path: impl Into<PathBuf>) -> Self {
+        Self(ListenerKind::Unix(path.into()))
+    }
+
+    /// whether this listener was bound by another process or by the
+    /// application, rather than by trillium
+    pub(crate) fn is_prebound(&self) -> bool {
+        match &self.0 {
+            ListenerKind::PreboundTcp(_) => true,
+            #[cfg(unix)]
+            ListenerKind::PreboundUnix(_) => true,
+            _ => false,
+        }
+    }
+
+    /**
+    Returns every socket passed to this process with [systemd socket
+    activation][socket-activation], in the order they were passed, or
+    an empty vec if this process was not socket activated.
+
+    When a server has no other listener configured, the first
+    socket-activated socket is used automatically, so this is only
+    needed to use more than one of them. Sockets are taken from the
+    environment the first time this is called, and subsequent calls
+    return the same sockets.
+
+    ```rust,no_run
+    use trillium_smol::Listener; // or trillium_async_std, trillium_tokio
+    # let tls_acceptor = ();
+    let mut listeners = Listener::systemd().into_iter();
+    trillium_smol::config()
+        .with_prebound_listener(listeners.next().expect("socket activated"))
+        .with_additional_listener(listeners.next().expect("second socket"), tls_acceptor)
+        .run(|conn: trillium::Conn| async move { conn.ok("hello") });
+    ```
+
+    [socket-activation]: https://www.freedesktop.org/software/systemd/man/latest/sd_listen_fds.html
+    */
+    #[cfg(unix)]
+    pub fn systemd() -> Vec<Self> {
+        crate::socket_activation::listeners()
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect()
+    }
+
+    /// Returns the socket passed with systemd socket activation that
+    /// has this name, as configured with `FileDescriptorName=` in the
+    /// systemd socket unit. See [`Listener::systemd`].
+    #[cfg(unix)]
+    pub fn systemd_named(name: &str) -> Option<Self> {
+        crate::socket_activation::listeners()
+            .iter()
+            .find(|(listener_name, _)| listener_name.as_deref() == Some(name))
+            .map(|(_, listener)| listener.clone())
+    }
+}
+
+/// A listener bound in addition to the primary listener, along with
+/// how to handle its connections
+#[derive(Debug, Clone)]
+pub(crate) struct AdditionalListener {
+    pub(crate) listener: Listener,
+    pub(crate) acceptor: BoxedAcceptor,
+    pub(crate) https_redirect: Option<Arc<HttpsRedirect>>,
+}
@@ -0,0 +1,34 @@
+use crate::PeerCredentials;
+use std::{io::Result, os::fd::AsFd};
+
+/**
+Retrieves the [`PeerCredentials`] for a connected unix domain
+socket. This is intended for runtime adapters to use in their
+[`Transport::peer_credentials`](crate::Transport::peer_credentials)
+implementation for unix streams.
+
+This is currently only supported on linux and android, and returns
+`Ok(None)` on other platforms.
+
+# Errors
+
+Returns an error if the operating system is unable to provide the
+credentials for this socket.
+*/
+pub fn unix_peer_credentials(socket: impl AsFd) -> Result<Option<PeerCredentials>> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let ucred = rustix::net::sockopt::socket_peercred(socket)?;
+        Ok(Some(PeerCredentials::new(
+            u32::try_from(ucred.pid.as_raw_nonzero().get()).ok(),
+            ucred.uid.as_raw(),
+            ucred.gid.as_raw(),
+        )))
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        let _ = socket;
+        Ok(None)
+    }
+}
@@ -0,0 +1,342 @@
+use crate::{async_trait, Acceptor, PeerCertificates, PeerCredentials, Transport, TransportKind};
+use futures_lite::{AsyncRead, AsyncReadExt, AsyncWrite};
+use std::{
+    io::{self, ErrorKind, IoSlice, IoSliceMut},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+const V1_MAX_LEN: usize = 107;
+
+/**
+An [`Acceptor`] that reads a [PROXY protocol][spec] header (version
+1 or 2) from each connection before passing it to the wrapped
+acceptor
+
+Tcp load balancers such as haproxy, aws network load balancers, and
+many kubernetes ingress controllers can send this header to describe
+the original client connection. The source address from the header
+is used as the conn's peer ip, and the destination address is
+available as [`Transport::local_addr`], so that applications see the
+actual client instead of the load balancer without relying on http
+headers.
+
+Every connection to a listener with this acceptor must begin with a
+proxy protocol header, and connections that do not are closed. Only
+use this acceptor on listeners that are exclusively reachable through
+a proxy that sends the header, since any client that can connect
+directly is able to claim an arbitrary address.
+
+Connections with a version 2 `LOCAL` command or a version 1
+`UNKNOWN` protocol, which load balancers use for health checks, keep
+the addresses of the underlying transport.
+
+```rust,no_run
+use trillium_server_common::ProxyProtocolAcceptor;
+# let tls_acceptor = ();
+trillium_smol::config() // or trillium_async_std, trillium_tokio
+    .with_acceptor(ProxyProtocolAcceptor::new(tls_acceptor))
+    .run(|conn: trillium::Conn| async move {
+        let peer_ip = conn.peer_ip().map(|ip| ip.to_string()).unwrap_or_default();
+        conn.ok(format!("hello, {peer_ip}"))
+    });
+```
+
+[spec]: https://www.haproxy.org/download/3.0/doc/proxy-protocol.txt
+*/
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProxyProtocolAcceptor<A>(A);
+
+impl<A> ProxyProtocolAcceptor<A> {
+    /// wraps the provided acceptor, which may be `()` for plaintext
+    /// connections
+    pub fn new(acceptor: A) -> Self {
+        Self(acceptor)
+    }
+
+    /// borrow the wrapped acceptor
+    pub fn inner(&self) -> &A {
+        &self.0
+    }
+}
+
+/// Reasons that a [`ProxyProtocolAcceptor`] could not accept a connection
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ProxyProtocolError<E> {
+    /// the proxy protocol header was missing, malformed, or could not be read
+    Header(io::Error),
+
+    /// the wrapped acceptor returned an error
+    Acceptor(E),
+}
+
+#[async_trait]
+impl<Input, A> Acceptor<Input> for ProxyProtocolAcceptor<A>
+where
+    Input: Transport,
+    A: Acceptor<ProxyProtocolTransport<Input>>,
+{
+    type Output = A::Output;
+    type Error = ProxyProtocolError<A::Error>;
+
+    async fn accept(&self, mut input: Input) -> Result<Self::Output, Self::Error> {
+        let addresses = read_header(&mut input)
+            .await
+            .map_err(ProxyProtocolError::Header)?;
+
+        let transport = ProxyProtocolTransport {
+            inner: input,
+            source: addresses.map(|(source, _)| source),
+            destination: addresses.map(|(_, destination)| destination),
+        };
+
+        self.0
+            .accept(transport)
+            .await
+            .map_err(ProxyProtocolError::Acceptor)
+    }
+
+    fn is_secure(&self) -> bool {
+        self.0.is_secure()
+    }
+}
+
+/**
+A [`Transport`] that has had a proxy protocol header read from it by
+a [`ProxyProtocolAcceptor`]
+
+[`Transport::peer_addr`] and [`Transport::local_addr`] return the
+source and destination addresses from the header, if it included
+them, and otherwise are delegated to the underlying transport.
+*/
+#[derive(Debug)]
+pub struct ProxyProtocolTransport<T> {
+    inner: T,
+    source: Option<SocketAddr>,
+    destination: Option<SocketAddr>,
+}
+
+impl<T> ProxyProtocolTransport<T> {
+    /// the source address from the proxy protocol header, if any
+    pub fn source(&self) -> Option<SocketAddr> {
+        self.source
+    }
+
+    /// the destination address from the proxy protocol header, if any
+    pub fn destination(&self) -> Option<SocketAddr> {
+        self.destination
+    }
+
+    /// borrow the underlying transport
+    pub fn inner_transport(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T: Transport> Transport for ProxyProtocolTransport<T> {
+    fn set_linger(&mut self, linger: Option<Duration>) -> io::Result<()> {
+        self.inner.set_linger(linger)
+    }
+
+    fn set_nodelay(&mut self, nodelay: bool) -> io::Result<()> {
+        self.inner.set_nodelay(nodelay)
+    }
+
+    fn set_ip_ttl(&mut self, ttl: u32) -> io::Result<()> {
+        self.inner.set_ip_ttl(ttl)
+    }
+
+    fn peer_addr(&self) -> io::Result<Option<SocketAddr>> {
+        match self.source {
+            Some(source) => Ok(Some(source)),
+            None => self.inner.peer_addr(),
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Option<SocketAddr>> {
+        match self.destination {
+            Some(destination) => Ok(Some(destination)),
+            None => self.inner.local_addr(),
+        }
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        self.inner.transport_kind()
+    }
+
+    fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.inner.negotiated_alpn()
+    }
+
+    fn peer_credentials(&self) -> io::Result<Option<PeerCredentials>> {
+        self.inner.peer_credentials()
+    }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        self.inner.peer_certificates()
+    }
+}
+
+impl<T: Transport> AsyncRead for ProxyProtocolTransport<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read(cx, buf)
+    }
+
+    fn poll_read_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &mut [IoSliceMut<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_read_vectored(cx, bufs)
+    }
+}
+
+impl<T: Transport> AsyncWrite for ProxyProtocolTransport<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+// reads exactly the header and nothing after it, so that the
+// remaining bytes can be read by the wrapped acceptor. both versions
+// are at least as long as the version 2 signature
+async fn read_header(
+    transport: &mut impl Transport,
+) -> io::Result<Option<(SocketAddr, SocketAddr)>> {
+    let mut header = vec![0; V2_SIGNATURE.len()];
+    transport.read_exact(&mut header).await?;
+
+    if header == V2_SIGNATURE {
+        let mut rest = [0; 4];
+        transport.read_exact(&mut rest).await?;
+        let [version_command, family, len @ ..] = rest;
+        let mut addresses = vec![0; usize::from(u16::from_be_bytes(len))];
+        transport.read_exact(&mut addresses).await?;
+        parse_v2(version_command, family, &addresses)
+    } else if header.starts_with(b"PROXY ") {
+        while !header.ends_with(b"\r\n") {
+            if header.len() >= V1_MAX_LEN {
+                return Err(invalid("proxy protocol v1 header is too long"));
+            }
+            let mut byte = [0];
+            transport.read_exact(&mut byte).await?;
+            header.push(byte[0]);
+        }
+        parse_v1(&header[..header.len() - 2])
+    } else {
+        Err(invalid(
+            "connection did not begin with a proxy protocol header",
+        ))
+    }
+}
+
+fn parse_v1(header: &[u8]) -> io::Result<Option<(SocketAddr, SocketAddr)>> {
+    let header =
+        std::str::from_utf8(header).map_err(|_| invalid("invalid proxy protocol v1 header"))?;
+    let mut parts = header.split(' ').skip(1);
+    match parts.next() {
+        Some("UNKNOWN") => return Ok(None),
+        Some("TCP4" | "TCP6") => {}
+        _ => return Err(invalid("unsupported proxy protocol v1 protocol")),
+    }
+
+    let (Some(source_ip), Some(destination_ip), Some(source_port), Some(destination_port), None) = (
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+        parts.next(),
+    ) else {
+        return Err(invalid("invalid proxy protocol v1 header"));
+    };
+
+    let ip = |ip: &str| {
+        ip.parse::<IpAddr>()
+            .map_err(|_| invalid("invalid proxy protocol v1 address"))
+    };
+    let port = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| invalid("invalid proxy protocol v1 port"))
+    };
+
+    Ok(Some((
+        SocketAddr::new(ip(source_ip)?, port(source_port)?),
+        SocketAddr::new(ip(destination_ip)?, port(destination_port)?),
+    )))
+}
+
+fn parse_v2(
+    version_command: u8,
+    family: u8,
+    addresses: &[u8],
+) -> io::Result<Option<(SocketAddr, SocketAddr)>> {
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported proxy protocol version"));
+    }
+
+    match version_command & 0x0f {
+        // LOCAL
+        0 => return Ok(None),
+        // PROXY
+        1 => {}
+        _ => return Err(invalid("unsupported proxy protocol v2 command")),
+    }
+
+    let port = |bytes: &[u8]| u16::from_be_bytes([bytes[0], bytes[1]]);
+    match family >> 4 {
+        // AF_INET
+        1 if addresses.len() >= 12 => {
+            let ip = |bytes: &[u8]| Ipv4Addr::new(bytes[0], bytes[1], bytes[2], bytes[3]);
+            Ok(Some((
+                SocketAddr::new(ip(&addresses[0..4]).into(), port(&addresses[8..10])),
+                SocketAddr::new(ip(&addresses[4..8]).into(), port(&addresses[10..12])),
+            )))
+        }
+
+        // AF_INET6
+        2 if addresses.len() >= 36 => {
+            let ip = |bytes: &[u8]| Ipv6Addr::from(<[u8; 16]>::try_from(bytes).unwrap());
+            Ok(Some((
+                SocketAddr::new(ip(&addresses[0..16]).into(), port(&addresses[32..34])),
+                SocketAddr::new(ip(&addresses[16..32]).into(), port(&addresses[34..36])),
+            )))
+        }
+
+        1 | 2 => Err(invalid("proxy protocol v2 address block is too short")),
+
+        // AF_UNSPEC and AF_UNIX do not describe an ip address
+        _ => Ok(None),
+    }
+}
@@ -1,11 +1,19 @@
-use crate::{Acceptor, Config, ConfigExt, Stopper, Transport};
+use crate::{
+    listener::{AdditionalListener, ListenerKind, Prebound},
+    socket_options::SocketOptions,
+    Acceptor, CloneCounterObserver, Config, ConfigExt, Listener, MissingDependencies, StartupError,
+    Stopper, Transport,
+};
 use std::{
-    future::{ready, Future},
-    io::Result,
+    future::{poll_fn, ready, Future},
+    io::{self, ErrorKind},
+    net::{TcpListener, ToSocketAddrs},
     pin::Pin,
     sync::Arc,
+    task::Poll,
 };
 use trillium::{Handler, Info};
+use trillium_http::transport::BoxedTransport;
 
 /**
 The server trait, for standard network-based server implementations.
@@ -21,7 +29,7 @@ pub trait Server: Sized + Send + Sync + 'static {
 
     /// Asynchronously return a single `Self::Transport` from a
     /// `Self::Listener`. Must be implemented.
-    fn accept(&mut self) -> Pin<Box<dyn Future<Output = Result<Self::Transport>> + Send + '_>>;
+    fn accept(&mut self) -> Pin<Box<dyn Future<Output = io::Result<Self::Transport>> + Send + '_>>;
 
     /// Build an [`Info`] from the Self::Listener type. See [`Info`]
     /// for more details.
@@ -33,70 +41,118 @@ pub trait Server: Sized + Send + Sync + 'static {
         Box::pin(ready(()))
     }
 
-    /// Build a listener from the config. The default logic for this
-    /// is described elsewhere. To override the default logic, server
-    /// implementations could potentially implement this directly.  To
-    /// use this default logic, implement
+    /// Create another handle to the same listening socket, so that
+    /// several accept loops can accept from it concurrently. This is
+    /// used by [`Config::with_accept_loops`]. The default
+    /// implementation returns an [`ErrorKind::Unsupported`] error, in
+    /// which case a single accept loop is run.
+    fn try_clone(&self) -> io::Result<Self> {
+        Err(io::Error::new(
+            ErrorKind::Unsupported,
+            "this server does not support multiple accept loops",
+        ))
+    }
+
+    /// Build a listener from the config, panicking if the listener
+    /// cannot be built. This calls [`Server::try_build_listener`].
+    ///
+    /// **Breaking change:** [`Config`] no longer calls this, so
+    /// overriding it no longer has any effect. Server implementations
+    /// that build their own listeners should override
+    /// [`Server::try_build_listener`] instead.
+    fn build_listener<A>(config: &Config<Self, A>) -> Self
+    where
+        A: Acceptor<Self::Transport>,
+    {
+        Self::try_build_listener(config).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Attempt to build a listener from the config. The default logic
+    /// for this is described elsewhere. To override the default logic,
+    /// server implementations could potentially implement this
+    /// directly.  To use this default logic, implement
     /// [`Server::listener_from_tcp`] and
     /// [`Server::listener_from_unix`].
     #[cfg(unix)]
-    fn build_listener<A>(config: &Config<Self, A>) -> Self
+    fn try_build_listener<A>(config: &Config<Self, A>) -> Result<Self, StartupError>
     where
         A: Acceptor<Self::Transport>,
     {
         if let Some(listener) = config.binding.write().unwrap().take() {
             log::debug!("taking prebound listener");
-            return listener;
+            return Ok(listener);
+        }
+
+        if let Some(listener) = &config.primary_listener {
+            return bind_listener(listener, &config.socket_options);
+        }
+
+        if let Some((_, listener)) = crate::socket_activation::listeners().first() {
+            return bind_listener(listener, &config.socket_options);
         }
 
         use std::os::unix::prelude::FromRawFd;
         let host = config.host();
-        if host.starts_with(|c| c == '/' || c == '.' || c == '~') {
-            Self::listener_from_unix(std::os::unix::net::UnixListener::bind(host).unwrap())
+        let unix_socket = config.unix_socket.clone().or_else(|| {
+            host.starts_with(['/', '.', '~'])
+                .then(|| host.clone().into())
+        });
+
+        if let Some(path) = unix_socket {
+            Ok(Self::listener_from_unix(bind_unix(&path)?))
         } else {
             let tcp_listener = if let Some(fd) = std::env::var("LISTEN_FD")
                 .ok()
                 .and_then(|fd| fd.parse().ok())
             {
                 log::debug!("using fd {} from LISTEN_FD", fd);
-                unsafe { std::net::TcpListener::from_raw_fd(fd) }
+                unsafe { TcpListener::from_raw_fd(fd) }
             } else {
-                std::net::TcpListener::bind((host, config.port())).unwrap()
+                bind_tcp(&host, config.port(), &config.socket_options)?
             };
 
-            tcp_listener.set_nonblocking(true).unwrap();
-            Self::listener_from_tcp(tcp_listener)
+            tcp_listener
+                .set_nonblocking(true)
+                .map_err(StartupError::Listener)?;
+            Ok(Self::listener_from_tcp(tcp_listener))
         }
     }
 
-    /// Build a listener from the config. The default logic for this
-    /// is described elsewhere. To override the default logic, server
-    /// implementations could potentially implement this directly.  To
-    /// use this default logic, implement [`Server::listener_from_tcp`]
+    /// Attempt to build a listener from the config. The default logic
+    /// for this is described elsewhere. To override the default logic,
+    /// server implementations could potentially implement this
+    /// directly.  To use this default logic, implement
+    /// [`Server::listener_from_tcp`]
     #[cfg(not(unix))]
-    fn build_listener<A>(config: &Config<Self, A>) -> Self
+    fn try_build_listener<A>(config: &Config<Self, A>) -> Result<Self, StartupError>
     where
         A: Acceptor<Self::Transport>,
     {
         if let Some(listener) = config.binding.write().unwrap().take() {
             log::debug!("taking prebound listener");
-            return listener;
+            return Ok(listener);
         }
 
-        let tcp_listener = std::net::TcpListener::bind((config.host(), config.port())).unwrap();
-        tcp_listener.set_nonblocking(true).unwrap();
-        Self::listener_from_tcp(tcp_listener)
+        if let Some(listener) = &config.primary_listener {
+            return bind_listener(listener, &config.socket_options);
+        }
+
+        let tcp_listener = bind_tcp(&config.host(), config.port(), &config.socket_options)?;
+        tcp_listener
+            .set_nonblocking(true)
+            .map_err(StartupError::Listener)?;
+        Ok(Self::listener_from_tcp(tcp_listener))
     }
 
     /// Build a Self::Listener from a tcp listener. This is called by
-    /// the [`Server::build_listener`] default implementation, and
+    /// the [`Server::try_build_listener`] default implementation, and
     /// is mandatory if the default implementation is used.
-    fn listener_from_tcp(_tcp: std::net::TcpListener) -> Self {
+    fn listener_from_tcp(_tcp: TcpListener) -> Self {
         unimplemented!()
     }
 
     /// Build a Self::Listener from a tcp listener. This is called by
-    /// the [`Server::build_listener`] default implementation. You
+    /// the [`Server::try_build_listener`] default implementation. You
     /// will want to tag an implementation of this with #[cfg(unix)].
     #[cfg(unix)]
     fn listener_from_unix(_tcp: std::os::unix::net::UnixListener) -> Self {
@@ -125,18 +181,60 @@ pub trait Server: Sized + Send + Sync + 'static {
         Self::block_on(Self::run_async(config, handler))
     }
 
-    /// Run a trillium application from an async context. The default
+    /// Run a trillium application from an async context, panicking if
+    /// the server cannot be started. This calls [`Server::try_run_async`].
+    ///
+    /// **Breaking change:** [`Config::try_run`] and
+    /// [`Config::try_run_async`] call [`Server::try_run_async`]
+    /// directly, so overriding this no longer has any effect on them.
+    /// Server implementations should override
+    /// [`Server::try_run_async`] instead.
+    fn run_async<A, H>(
+        config: Config<Self, A>,
+        handler: H,
+    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    where
+        A: Acceptor<Self::Transport>,
+        H: Handler,
+    {
+        Box::pin(async move {
+            if let Err(e) = Self::try_run_async(config, handler).await {
+                panic!("{e}");
+            }
+        })
+    }
+
+    /// Run a trillium application from an async context, returning a
+    /// [`StartupError`] if the listener cannot be built. The default
     /// implementation of this method contains the core logic of this
     /// Trait.
-    fn run_async<A, H>(
+    fn try_run_async<A, H>(
         config: Config<Self, A>,
         mut handler: H,
-    ) -> Pin<Box<dyn Future<Output = ()> + Send + 'static>>
+    ) -> Pin<Box<dyn Future<Output = Result<(), StartupError>> + Send + 'static>>
     where
         A: Acceptor<Self::Transport>,
         H: Handler,
     {
         Box::pin(async move {
+            let primary_is_prebound = config.primary_is_prebound();
+            let listener = Self::try_build_listener(&config)?;
+            let mut additional_listeners = Vec::with_capacity(config.additional_listeners.len());
+            for additional_listener in &config.additional_listeners {
+                match bind_listener::<Self>(&additional_listener.listener, &config.socket_options) {
+                    Ok(additional) => {
+                        additional_listeners.push((additional, additional_listener.clone()))
+                    }
+                    Err(e) => {
+                        clean_up(listener, primary_is_prebound).await;
+                        for (additional, additional_listener) in additional_listeners {
+                            clean_up(additional, additional_listener.listener.is_prebound()).await;
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+
             if config.should_register_signals() {
                 #[cfg(unix)]
                 Self::spawn(Self::handle_signals(config.stopper()));
@@ -145,31 +243,269 @@ pub trait Server: Sized + Send + Sync + 'static {
                 log::error!("signals handling not supported on windows yet");
             }
 
-            let mut listener = Self::build_listener(&config);
             let mut info = Self::info(&listener);
+            info.set_secure(config.acceptor.is_secure());
+            for (additional, _) in &additional_listeners {
+                let additional = Self::info(additional);
+                info.listener_description_mut()
+                    .push_str(&format!(", {}", additional.listener_description()));
+            }
             info.server_description_mut().push_str(Self::DESCRIPTION);
             handler.init(&mut info).await;
+            if !info.missing_dependencies().is_empty() {
+                let missing = info.missing_dependencies().to_vec();
+                clean_up(listener, primary_is_prebound).await;
+                for (additional, additional_listener) in additional_listeners {
+                    clean_up(additional, additional_listener.listener.is_prebound()).await;
+                }
+                return Err(StartupError::MissingDependencies(MissingDependencies(
+                    missing,
+                )));
+            }
             config.info.set(info);
             let config = Arc::new(config);
             let handler = Arc::new(handler);
 
-            while let Some(stream) = config
-                .stopper
-                .stop_future(Self::accept(&mut listener))
-                .await
+            let mut prebound = vec![primary_is_prebound];
+            prebound.extend(
+                additional_listeners
+                    .iter()
+                    .map(|(_, additional_listener)| additional_listener.listener.is_prebound()),
+            );
+
+            let main_handler = Arc::clone(&handler);
+            let mut accept_loops = vec![accept_loop(
+                listener,
+                Arc::clone(&config),
+                move |stream, config| {
+                    let handler = Arc::clone(&main_handler);
+                    Self::spawn(async move { config.handle_stream(stream, handler).await })
+                },
+            )];
+
+            for (
+                listener,
+                AdditionalListener {
+                    acceptor,
+                    https_redirect,
+                    ..
+                },
+            ) in additional_listeners
             {
-                match stream {
-                    Ok(stream) => {
-                        let config = Arc::clone(&config);
-                        let handler = Arc::clone(&handler);
-                        Self::spawn(async move { config.handle_stream(stream, handler).await })
-                    }
-                    Err(e) => log::error!("tcp error: {}", e),
-                }
+                let handler = Arc::clone(&handler);
+                accept_loops.push(accept_loop(
+                    listener,
+                    Arc::clone(&config),
+                    move |stream, config| {
+                        let stream = BoxedTransport::new(stream);
+                        let acceptor = acceptor.clone();
+                        if let Some(https_redirect) = &https_redirect {
+                            let https_redirect = Arc::clone(https_redirect);
+                            Self::spawn(async move {
+                                config.serve_stream(stream, &acceptor, https_redirect).await
+                            })
+                        } else {
+                            let handler = Arc::clone(&handler);
+                            Self::spawn(async move {
+                                config.serve_stream(stream, &acceptor, handler).await
+                            })
+                        }
+                    },
+                ));
             }
 
+            let listeners = join_all(accept_loops).await;
             config.graceful_shutdown().await;
-            Self::clean_up(listener).await;
+            for (listener, prebound) in listeners.into_iter().zip(prebound) {
+                clean_up(listener, prebound).await;
+            }
+            Ok(())
         })
     }
 }
+
+/// Clean up a listener after the server has shut down, unless it was
+/// bound by another process, such as a unix socket passed with systemd
+/// socket activation, which must outlive this server
+async fn clean_up<S: Server>(listener: S, prebound: bool) {
+    if !prebound {
+        S::clean_up(listener).await;
+    }
+}
+
+/// Accept streams from the listener until the server is stopped,
+/// returning the listener for clean up. When more than one accept
+/// loop is configured, the others are spawned with clones of the
+/// listener, and are complete before this returns.
+fn accept_loop<S, A>(
+    listener: S,
+    config: Arc<Config<S, A>>,
+    handle_stream: impl Fn(S::Transport, Arc<Config<S, A>>) + Clone + Send + 'static,
+) -> Pin<Box<dyn Future<Output = S> + Send + 'static>>
+where
+    S: Server,
+    A: Acceptor<S::Transport>,
+{
+    Box::pin(async move {
+        let spawned_loops = CloneCounterObserver::new();
+        for _ in 1..config.accept_loops {
+            match listener.try_clone() {
+                Ok(listener) => {
+                    let counter = spawned_loops.counter();
+                    let config = Arc::clone(&config);
+                    let handle_stream = handle_stream.clone();
+                    S::spawn(async move {
+                        accept(listener, &config, handle_stream).await;
+                        drop(counter);
+                    });
+                }
+
+                Err(e) => {
+                    log::warn!("running a single accept loop: {e}");
+                    break;
+                }
+            }
+        }
+
+        let listener = accept(listener, &config, handle_stream).await;
+        spawned_loops.await;
+        listener
+    })
+}
+
+async fn accept<S, A>(
+    mut listener: S,
+    config: &Arc<Config<S, A>>,
+    handle_stream: impl Fn(S::Transport, Arc<Config<S, A>>),
+) -> S
+where
+    S: Server,
+    A: Acceptor<S::Transport>,
+{
+    while let Some(stream) = config.stopper.stop_future(S::accept(&mut listener)).await {
+        match stream {
+            Ok(stream) => handle_stream(stream, Arc::clone(config)),
+            Err(e) => log::error!("tcp error: {}", e),
+        }
+    }
+    listener
+}
+
+/// Poll every future concurrently, returning their outputs in order
+/// once all of them are complete
+async fn join_all<T>(futures: Vec<Pin<Box<dyn Future<Output = T> + Send>>>) -> Vec<T> {
+    let mut futures = futures.into_iter().map(Some).collect::<Vec<_>>();
+    let mut outputs = futures.iter().map(|_| None).collect::<Vec<_>>();
+    poll_fn(|cx| {
+        for (future, output) in futures.iter_mut().zip(&mut outputs) {
+            if let Some(fut) = future {
+                if let Poll::Ready(value) = fut.as_mut().poll(cx) {
+                    *output = Some(value);
+                    *future = None;
+                }
+            }
+        }
+
+        if futures.iter().all(Option::is_none) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    outputs.into_iter().flatten().collect()
+}
+
+/// Bind an additional [`Listener`]
+fn bind_listener<S: Server>(
+    listener: &Listener,
+    socket_options: &SocketOptions,
+) -> Result<S, StartupError> {
+    match &listener.0 {
+        ListenerKind::Tcp { host, port } => {
+            let tcp_listener = bind_tcp(host, *port, socket_options)?;
+            tcp_listener
+                .set_nonblocking(true)
+                .map_err(StartupError::Listener)?;
+            Ok(S::listener_from_tcp(tcp_listener))
+        }
+        #[cfg(unix)]
+        ListenerKind::Unix(path) => Ok(S::listener_from_unix(bind_unix(path)?)),
+        ListenerKind::PreboundTcp(Prebound(tcp_listener)) => {
+            let tcp_listener = tcp_listener.try_clone().map_err(StartupError::Listener)?;
+            tcp_listener
+                .set_nonblocking(true)
+                .map_err(StartupError::Listener)?;
+            Ok(S::listener_from_tcp(tcp_listener))
+        }
+        #[cfg(unix)]
+        ListenerKind::PreboundUnix(Prebound(unix_listener)) => {
+            let unix_listener = unix_listener.try_clone().map_err(StartupError::Listener)?;
+            unix_listener
+                .set_nonblocking(true)
+                .map_err(StartupError::Listener)?;
+            Ok(S::listener_from_unix(unix_listener))
+        }
+    }
+}
+
+/// bind a unix socket at path, replacing a stale socket file that refuses connections
+#[cfg(unix)]
+fn bind_unix(path: &std::path::Path) -> Result<std::os::unix::net::UnixListener, StartupError> {
+    use std::os::unix::net::{UnixListener, UnixStream};
+    let bind_error = |source| StartupError::Bind {
+        address: path.display().to_string(),
+        source,
+    };
+
+    let listener = match UnixListener::bind(path) {
+        Err(e)
+            if e.kind() == ErrorKind::AddrInUse
+                && UnixStream::connect(path)
+                    .is_err_and(|e| e.kind() == ErrorKind::ConnectionRefused) =>
+        {
+            log::info!("replacing stale unix socket {}", path.display());
+            std::fs::remove_file(path).map_err(bind_error)?;
+            UnixListener::bind(path)
+        }
+        result => result,
+    }
+    .map_err(bind_error)?;
+
+    listener
+        .set_nonblocking(true)
+        .map_err(StartupError::Listener)?;
+    Ok(listener)
+}
+
+/// Resolve the host and port and bind to the first address that
+/// succeeds, distinguishing resolution failures from bind failures
+fn bind_tcp(
+    host: &str,
+    port: u16,
+    socket_options: &SocketOptions,
+) -> Result<TcpListener, StartupError> {
+    let addrs = (host, port)
+        .to_socket_addrs()
+        .map_err(|source| StartupError::Resolve {
+            host: host.to_string(),
+            port,
+            source,
+        })?
+        .collect::<Vec<_>>();
+
+    if addrs.is_empty() {
+        return Err(StartupError::Resolve {
+            host: host.to_string(),
+            port,
+            source: io::Error::new(ErrorKind::NotFound, "no addresses found"),
+        });
+    }
+
+    socket_options
+        .bind(&addrs)
+        .map_err(|source| StartupError::Bind {
+            address: format!("{host}:{port}"),
+            source,
+        })
+}
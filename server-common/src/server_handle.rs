@@ -1,17 +1,21 @@
-use crate::CloneCounterObserver;
+use crate::{connections::Connections, CloneCounterObserver, ConnectionInfo};
 use async_cell::sync::AsyncCell;
+use async_io::Timer;
 use event_listener::{Event, EventListener};
+use futures_lite::future;
 use std::{
     fmt::{Debug, Formatter, Result},
     future::{Future, IntoFuture},
+    net::SocketAddr,
     pin::Pin,
     sync::{
         atomic::{AtomicBool, Ordering},
-        Arc,
+        Arc, RwLock,
     },
     task::{Context, Poll},
+    time::Duration,
 };
-use trillium::Info;
+use trillium::{HttpConfig, Info};
 use trillium_http::Stopper;
 
 /// A handle for a spawned trillium server. Returned by
@@ -23,6 +27,9 @@ pub struct ServerHandle {
     pub(crate) info: Arc<AsyncCell<Info>>,
     pub(crate) completion: CompletionFuture,
     pub(crate) observer: CloneCounterObserver,
+    pub(crate) http_config: Arc<RwLock<HttpConfig>>,
+    pub(crate) force_close: Stopper,
+    pub(crate) connections: Connections,
 }
 
 pub struct CompletionFuture(Arc<CompletionFutureInner>, Pin<Box<EventListener>>);
@@ -107,12 +114,117 @@ impl ServerHandle {
         self.info.get().await
     }
 
+    /**
+    await server start and retrieve the tcp socket address that the
+    server is listening on. This is the address that was actually
+    bound, so when the server was configured with port 0 it includes
+    the port assigned by the operating system.
+
+    Returns None for servers that are not listening on a tcp socket,
+    such as unix domain socket servers.
+
+    ```
+    # trillium_testing::block_on(async {
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn("ok");
+
+    let socket_addr = handle.socket_addr().await.unwrap();
+    assert_ne!(socket_addr.port(), 0);
+    handle.stop().await;
+    # });
+    ```
+    */
+    pub async fn socket_addr(&self) -> Option<SocketAddr> {
+        self.info().await.tcp_socket_addr().copied()
+    }
+
     /// stop server and wait for it to shut down gracefully
     pub async fn stop(&self) {
         self.stopper.stop();
         self.completion.clone().await
     }
 
+    /**
+    stop server and wait up to `deadline` for open connections to
+    close gracefully. Any connections that are still open after the
+    deadline are closed, and then this waits for the server to shut
+    down.
+
+    Returns true if every connection closed before the deadline.
+    */
+    pub async fn stop_with_deadline(&self, deadline: Duration) -> bool {
+        self.stopper.stop();
+        let drained = future::or(
+            async {
+                self.completion.clone().await;
+                true
+            },
+            async {
+                Timer::after(deadline).await;
+                false
+            },
+        )
+        .await;
+
+        if !drained {
+            self.force_close.stop();
+            self.completion.clone().await;
+        }
+
+        drained
+    }
+
+    /// the number of connections that are currently open on this
+    /// server
+    pub fn open_connections(&self) -> usize {
+        self.observer.current()
+    }
+
+    /// the number of requests that are currently being handled by this
+    /// server, from when the request head has been read until the
+    /// response has been completely sent
+    pub fn requests_in_flight(&self) -> usize {
+        self.connections.requests_in_flight()
+    }
+
+    /// a snapshot of every connection that is currently open on this
+    /// server
+    pub fn connections(&self) -> Vec<ConnectionInfo> {
+        self.connections.snapshot()
+    }
+
+    /**
+    immediately closes every open connection for which the predicate
+    returns true, without stopping the server. Any requests in flight
+    on those connections are abandoned without a response. Returns the
+    number of connections that were closed.
+
+    ```
+    use std::{net::IpAddr, time::Duration};
+    # trillium_testing::block_on(async {
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn("ok");
+
+    // close connections from a misbehaving client
+    let ip: IpAddr = "10.0.0.1".parse().unwrap();
+    handle.close_connections(|connection| connection.peer_ip() == Some(ip));
+
+    // close connections that have been open for more than an hour
+    handle.close_connections(|connection| connection.age() > Duration::from_secs(60 * 60));
+    handle.stop().await;
+    # });
+    ```
+    */
+    pub fn close_connections(&self, predicate: impl Fn(&ConnectionInfo) -> bool) -> usize {
+        self.connections.close(predicate)
+    }
+
     /// retrieves a clone of the [`Stopper`] used by this server
     pub fn stopper(&self) -> Stopper {
         self.stopper.clone()
@@ -125,6 +237,30 @@ impl ServerHandle {
         self.observer.clone()
     }
 
+    /// the [`HttpConfig`] that will be used for newly accepted connections
+    pub fn http_config(&self) -> HttpConfig {
+        *self.http_config.read().unwrap()
+    }
+
+    /**
+    replaces the [`HttpConfig`] for this server while it is running.
+    the new config applies to connections accepted after this call,
+    and connections that are already open continue to use the config
+    they were accepted with.
+
+    ```
+    use trillium::HttpConfig;
+    let handle = trillium_smol::config()
+        .with_http_config(HttpConfig::default().with_received_body_max_len(1024))
+        .handle();
+
+    handle.set_http_config(handle.http_config().with_received_body_max_len(512));
+    ```
+    */
+    pub fn set_http_config(&self, http_config: HttpConfig) {
+        *self.http_config.write().unwrap() = http_config;
+    }
+
     /// checks whether this server has shut down. It's preferable to await
     /// this [`ServerHandle`] instead of polling this.
     pub fn is_running(&self) -> bool {
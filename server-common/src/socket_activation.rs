@@ -0,0 +1,81 @@
+use crate::Listener;
+use socket2::{Domain, Socket};
+use std::{
+    env,
+    net::TcpListener,
+    os::{
+        fd::{FromRawFd, OwnedFd, RawFd},
+        unix::net::UnixListener,
+    },
+    sync::OnceLock,
+};
+
+/// the first file descriptor passed with socket activation
+const SD_LISTEN_FDS_START: RawFd = 3;
+
+static LISTENERS: OnceLock<Vec<(Option<String>, Listener)>> = OnceLock::new();
+
+/// the sockets passed to this process with systemd socket activation,
+/// along with their names. these are only taken from the environment
+/// once, since each file descriptor can only be owned once.
+pub(crate) fn listeners() -> &'static [(Option<String>, Listener)] {
+    LISTENERS.get_or_init(take_listeners)
+}
+
+fn take_listeners() -> Vec<(Option<String>, Listener)> {
+    let pid = env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok());
+    let count = env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|count| count.parse::<usize>().ok());
+    let names = env::var("LISTEN_FDNAMES").unwrap_or_default();
+
+    // these are only intended for this process, and should not be
+    // inherited by any child processes
+    env::remove_var("LISTEN_PID");
+    env::remove_var("LISTEN_FDS");
+    env::remove_var("LISTEN_FDNAMES");
+
+    let (Some(pid), Some(count)) = (pid, count) else {
+        return vec![];
+    };
+
+    if pid != std::process::id() {
+        log::debug!("ignoring LISTEN_FDS for pid {pid}");
+        return vec![];
+    }
+
+    let mut names = names.split(':').map(String::from);
+    (SD_LISTEN_FDS_START..)
+        .take(count)
+        .filter_map(|fd| {
+            let name = names.next().filter(|name| !name.is_empty());
+            match listener_from_fd(fd) {
+                Ok(listener) => {
+                    log::debug!("using fd {fd} ({name:?}) from systemd socket activation");
+                    Some((name, listener))
+                }
+                Err(e) => {
+                    log::error!("could not use fd {fd} from systemd socket activation: {e}");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn listener_from_fd(fd: RawFd) -> std::io::Result<Listener> {
+    // SAFETY: systemd passes ownership of LISTEN_FDS file descriptors
+    // starting at SD_LISTEN_FDS_START to the process named by
+    // LISTEN_PID, and they are only taken once
+    let socket = unsafe { Socket::from_raw_fd(fd) };
+    socket.set_cloexec(true)?;
+    let domain = socket.local_addr()?.domain();
+    let fd = OwnedFd::from(socket);
+    if domain == Domain::UNIX {
+        Ok(UnixListener::from(fd).into())
+    } else {
+        Ok(TcpListener::from(fd).into())
+    }
+}
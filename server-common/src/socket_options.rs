@@ -0,0 +1,84 @@
+use socket2::{Domain, Protocol, Socket, TcpKeepalive, Type};
+use std::{
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener},
+    time::Duration,
+};
+
+/// the backlog used by [`TcpListener::bind`]
+const DEFAULT_BACKLOG: u32 = 128;
+
+/// Options that are applied to tcp sockets before they are bound
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SocketOptions {
+    pub(crate) reuse_address: Option<bool>,
+    pub(crate) reuse_port: bool,
+    pub(crate) backlog: Option<u32>,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) recv_buffer_size: Option<usize>,
+    pub(crate) send_buffer_size: Option<usize>,
+}
+
+impl SocketOptions {
+    /// binds to the first of these addresses that succeeds, as
+    /// [`TcpListener::bind`] does
+    pub(crate) fn bind(&self, addrs: &[SocketAddr]) -> io::Result<TcpListener> {
+        if *self == Self::default() {
+            return TcpListener::bind(addrs);
+        }
+
+        let mut last_error = None;
+        for addr in addrs {
+            match self.bind_addr(addr) {
+                Ok(listener) => return Ok(listener),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                "could not resolve to any addresses",
+            )
+        }))
+    }
+
+    fn bind_addr(&self, addr: &SocketAddr) -> io::Result<TcpListener> {
+        let socket = Socket::new(
+            Domain::for_address(*addr),
+            Type::STREAM,
+            Some(Protocol::TCP),
+        )?;
+
+        // std sets SO_REUSEADDR on unix listeners so that restarted
+        // servers can bind while old connections are in TIME_WAIT
+        if let Some(reuse_address) = self.reuse_address.or(cfg!(unix).then_some(true)) {
+            socket.set_reuse_address(reuse_address)?;
+        }
+
+        if self.reuse_port {
+            #[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+            socket.set_reuse_port(true)?;
+
+            #[cfg(not(all(unix, not(any(target_os = "solaris", target_os = "illumos")))))]
+            log::warn!("SO_REUSEPORT is not supported on this platform");
+        }
+
+        if let Some(time) = self.tcp_keepalive {
+            socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(time))?;
+        }
+
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+
+        socket.bind(&(*addr).into())?;
+        let backlog = self.backlog.unwrap_or(DEFAULT_BACKLOG);
+        socket.listen(i32::try_from(backlog).unwrap_or(i32::MAX))?;
+        Ok(socket.into())
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{Error, ErrorKind},
+};
+use trillium::MissingDependency;
+
+/**
+Errors that can occur while starting a trillium server, before any
+connections are accepted.
+
+These are returned by [`Config::try_bind`](crate::Config::try_bind),
+[`Config::try_run`](crate::Config::try_run), and
+[`Config::try_run_async`](crate::Config::try_run_async). The
+non-`try_` variants of those functions panic with the same errors.
+*/
+#[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
+pub enum StartupError {
+    /// the configured host and port could not be resolved to any
+    /// socket addresses
+    #[error("could not resolve {host}:{port}: {source}")]
+    Resolve {
+        /// the host that was being resolved
+        host: String,
+        /// the port that was being resolved
+        port: u16,
+        /// the underlying io error
+        #[source]
+        source: Error,
+    },
+
+    /// the listener could not be bound, for example because the
+    /// address is already in use or the process does not have
+    /// permission to bind to it
+    #[error("could not bind to {address}: {source}")]
+    Bind {
+        /// a description of the address, which may be a socket address
+        /// or a unix socket path
+        address: String,
+        /// the underlying io error
+        #[source]
+        source: Error,
+    },
+
+    /// the bound listener could not be configured for use by the
+    /// runtime adapter
+    #[error("could not configure listener: {0}")]
+    Listener(#[source] Error),
+
+    /// one or more handlers declared a dependency with
+    /// [`Info::require`](trillium::Info::require) that was not
+    /// provided by an earlier handler
+    #[error("{0}")]
+    MissingDependencies(MissingDependencies),
+}
+
+/// The [`MissingDependency`] errors recorded while initializing a handler
+#[derive(Debug, Clone)]
+pub struct MissingDependencies(pub Vec<MissingDependency>);
+
+impl Display for MissingDependencies {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, missing_dependency) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            Display::fmt(missing_dependency, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl StartupError {
+    /// borrow the underlying [`std::io::Error`], if this error was
+    /// caused by one
+    pub fn io_error(&self) -> Option<&Error> {
+        match self {
+            StartupError::Resolve { source, .. } | StartupError::Bind { source, .. } => {
+                Some(source)
+            }
+            StartupError::Listener(source) => Some(source),
+            StartupError::MissingDependencies(_) => None,
+        }
+    }
+
+    /// the [`ErrorKind`] of the underlying [`std::io::Error`], if
+    /// this error was caused by one
+    pub fn kind(&self) -> Option<ErrorKind> {
+        self.io_error().map(Error::kind)
+    }
+
+    /// convenience predicate for the common case of attempting to bind
+    /// to a port that is already in use
+    pub fn is_address_in_use(&self) -> bool {
+        matches!(self, StartupError::Bind { .. }) && self.kind() == Some(ErrorKind::AddrInUse)
+    }
+}
@@ -0,0 +1,39 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    thread,
+};
+
+fn get(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn accept_loops() {
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .with_accept_loops(4)
+        .without_signals()
+        .spawn("ok");
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.socket_addr().await.unwrap();
+        let requests = (0..32)
+            .map(|_| thread::spawn(move || get(addr)))
+            .collect::<Vec<_>>();
+        for request in requests {
+            assert!(request.join().unwrap().ends_with("\r\n\r\nok"));
+        }
+
+        // every accept loop has stopped and released the socket
+        handle.stop().await;
+        assert!(TcpStream::connect(addr).is_err());
+    });
+}
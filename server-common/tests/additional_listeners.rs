@@ -0,0 +1,50 @@
+#![cfg(unix)]
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    os::unix::net::UnixStream,
+};
+use trillium::Conn;
+use trillium_smol::Listener;
+
+fn get(mut stream: impl Read + Write) -> String {
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn tcp_and_unix_socket_listeners() {
+    let path = std::env::temp_dir().join(format!(
+        "trillium-additional-listener-{}.sock",
+        std::process::id()
+    ));
+
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .with_additional_listener(Listener::unix(&path), ())
+        .without_signals()
+        .spawn(|conn: Conn| async move { conn.ok("hello") });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let info = handle.info().await;
+        let addr = info.tcp_socket_addr().copied().unwrap();
+        assert!(info
+            .listener_description()
+            .starts_with(&format!("{addr}, ")));
+        assert!(info
+            .listener_description()
+            .contains(&*path.to_string_lossy()));
+
+        assert!(get(TcpStream::connect(addr).unwrap()).ends_with("\r\n\r\nhello"));
+        assert!(get(UnixStream::connect(&path).unwrap()).ends_with("\r\n\r\nhello"));
+
+        handle.stop().await;
+        assert!(TcpStream::connect(addr).is_err());
+        assert!(!path.exists());
+    });
+}
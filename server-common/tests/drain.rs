@@ -0,0 +1,88 @@
+use async_io::Timer;
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use trillium::Conn;
+
+// sends a keep-alive request and reads until the server closes the connection
+fn get(addr: SocketAddr) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response
+    })
+}
+
+#[test]
+fn in_flight_responses_close_the_connection() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let open_connections = Arc::new(AtomicUsize::new(0));
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .with_drain_hook({
+            let open_connections = Arc::clone(&open_connections);
+            move |open| open_connections.store(open, Ordering::SeqCst)
+        })
+        .spawn(move |conn: Conn| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(()).unwrap();
+                Timer::after(Duration::from_millis(200)).await;
+                conn.ok("hello")
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.info().await.tcp_socket_addr().copied().unwrap();
+        let response = get(addr);
+        started_rx.recv().unwrap();
+        handle.stop().await;
+
+        let response = response.join().unwrap();
+        assert!(response.contains("Connection: close\r\n"));
+        assert!(response.ends_with("\r\n\r\nhello"));
+        assert_eq!(open_connections.load(Ordering::SeqCst), 1);
+    });
+}
+
+#[test]
+fn drain_timeout_closes_open_connections() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .with_drain_timeout(Duration::from_millis(100))
+        .spawn(move |conn: Conn| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(()).unwrap();
+                Timer::after(Duration::from_secs(60)).await;
+                conn.ok("too late")
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.info().await.tcp_socket_addr().copied().unwrap();
+        let response = get(addr);
+        started_rx.recv().unwrap();
+
+        let start = Instant::now();
+        handle.stop().await;
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(response.join().unwrap(), "");
+    });
+}
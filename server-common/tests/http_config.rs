@@ -0,0 +1,45 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+};
+use trillium::{Conn, HttpConfig};
+
+fn post(addr: SocketAddr, body: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "POST / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn set_http_config_applies_to_new_connections() {
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .with_http_config(HttpConfig::default().with_received_body_max_len(5))
+        .spawn(|mut conn: Conn| async move {
+            match conn.request_body_string().await {
+                Ok(body) => conn.ok(body),
+                Err(_) => conn.with_status(413),
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.info().await.tcp_socket_addr().copied().unwrap();
+        assert_eq!(handle.http_config().received_body_max_len(), 5);
+        assert!(post(addr, "0123456789").starts_with("HTTP/1.1 413"));
+
+        handle.set_http_config(handle.http_config().with_received_body_max_len(10));
+        assert_eq!(handle.http_config().received_body_max_len(), 10);
+        assert!(post(addr, "0123456789").ends_with("\r\n\r\n0123456789"));
+
+        handle.stop().await;
+    });
+}
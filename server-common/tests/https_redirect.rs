@@ -0,0 +1,56 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+use trillium::Conn;
+use trillium_smol::{AcmeChallenges, HttpsRedirect, Listener};
+
+fn get(addr: SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: example.com:{}\r\nConnection: close\r\n\r\n",
+        addr.port()
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn https_redirect_listener() {
+    let redirect_addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let acme_challenges = AcmeChallenges::new();
+
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .with_https_redirect(
+            Listener::socketaddr(redirect_addr),
+            HttpsRedirect::new().with_acme_challenges(acme_challenges.clone()),
+        )
+        .without_signals()
+        .spawn(|conn: Conn| async move { conn.ok("application") });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.info().await.tcp_socket_addr().copied().unwrap();
+        assert!(get(addr, "/").ends_with("\r\n\r\napplication"));
+
+        let response = get(redirect_addr, "/some/path?query");
+        assert!(response.starts_with("HTTP/1.1 301 Moved Permanently\r\n"));
+        assert!(response.contains("\r\nLocation: https://example.com/some/path?query\r\n"));
+
+        assert!(get(redirect_addr, "/.well-known/acme-challenge/token")
+            .starts_with("HTTP/1.1 404 Not Found\r\n"));
+        acme_challenges.insert("token", "token.thumbprint");
+        assert!(get(redirect_addr, "/.well-known/acme-challenge/token")
+            .ends_with("\r\n\r\ntoken.thumbprint"));
+
+        handle.stop().await;
+        assert!(TcpStream::connect(redirect_addr).is_err());
+    });
+}
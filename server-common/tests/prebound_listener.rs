@@ -0,0 +1,66 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+};
+use trillium_smol::Listener;
+
+fn get(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn prebound_listener() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let additional = TcpListener::bind("127.0.0.1:0").unwrap();
+    let additional_addr = additional.local_addr().unwrap();
+
+    let handle = trillium_smol::config()
+        .with_prebound_listener(listener)
+        .with_additional_listener(Listener::from(additional), ())
+        .without_signals()
+        .spawn("ok");
+
+    trillium_smol::async_global_executor::block_on(async move {
+        assert_eq!(handle.socket_addr().await, Some(addr));
+        assert!(get(addr).ends_with("\r\n\r\nok"));
+        assert!(get(additional_addr).ends_with("\r\n\r\nok"));
+        handle.stop().await;
+    });
+}
+
+#[cfg(unix)]
+#[test]
+fn prebound_unix_listener_is_not_removed() {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = std::env::temp_dir().join(format!("trillium-prebound-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let listener = UnixListener::bind(&path).unwrap();
+
+    let handle = trillium_smol::config()
+        .with_prebound_listener(listener)
+        .without_signals()
+        .spawn("ok");
+
+    trillium_smol::async_global_executor::block_on(async {
+        handle.info().await;
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.ends_with("\r\n\r\nok"));
+        handle.stop().await;
+    });
+
+    assert!(path.exists());
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,79 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+};
+use trillium::Conn;
+use trillium_smol::ProxyProtocolAcceptor;
+
+fn get(addr: SocketAddr, header: &[u8]) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream.write_all(header).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    response
+}
+
+fn v2_header(command: u8, family: u8, addresses: &[u8]) -> Vec<u8> {
+    let mut header = b"\r\n\r\n\0\r\nQUIT\n".to_vec();
+    header.extend_from_slice(&[0x20 | command, family]);
+    header.extend_from_slice(&u16::try_from(addresses.len()).unwrap().to_be_bytes());
+    header.extend_from_slice(addresses);
+    header
+}
+
+#[test]
+fn proxy_protocol() {
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .with_acceptor(ProxyProtocolAcceptor::new(()))
+        .spawn(|conn: Conn| async move {
+            let peer_ip = conn.peer_ip().unwrap();
+            let local_addr = conn.local_addr().unwrap();
+            conn.ok(format!("{peer_ip} -> {local_addr}"))
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.info().await.tcp_socket_addr().copied().unwrap();
+
+        assert!(
+            get(addr, b"PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n")
+                .ends_with("\r\n\r\n192.0.2.1 -> 198.51.100.1:443")
+        );
+
+        assert!(
+            get(addr, b"PROXY TCP6 2001:db8::1 2001:db8::2 56324 443\r\n")
+                .ends_with("\r\n\r\n2001:db8::1 -> [2001:db8::2]:443")
+        );
+
+        let mut addresses = vec![192, 0, 2, 1, 198, 51, 100, 1];
+        addresses.extend_from_slice(&56324_u16.to_be_bytes());
+        addresses.extend_from_slice(&443_u16.to_be_bytes());
+        // type-length-value extensions after the addresses are ignored
+        addresses.extend_from_slice(&[0x04, 0x00, 0x01, 0x00]);
+        assert!(get(addr, &v2_header(1, 0x11, &addresses))
+            .ends_with("\r\n\r\n192.0.2.1 -> 198.51.100.1:443"));
+
+        let mut addresses = [0; 36];
+        addresses[15] = 1;
+        addresses[31] = 2;
+        addresses[32..34].copy_from_slice(&56324_u16.to_be_bytes());
+        addresses[34..36].copy_from_slice(&443_u16.to_be_bytes());
+        assert!(get(addr, &v2_header(1, 0x21, &addresses)).ends_with("\r\n\r\n::1 -> [::2]:443"));
+
+        // health checks keep the addresses of the connection
+        let unproxied = format!("\r\n\r\n127.0.0.1 -> {addr}");
+        assert!(get(addr, &v2_header(0, 0x00, &[])).ends_with(&unproxied));
+        assert!(get(addr, b"PROXY UNKNOWN\r\n").ends_with(&unproxied));
+
+        // connections without a header are closed
+        assert_eq!(get(addr, b""), "");
+        assert_eq!(get(addr, b"PROXY TCP4 192.0.2.1\r\n"), "");
+
+        handle.stop().await;
+    });
+}
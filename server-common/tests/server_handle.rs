@@ -0,0 +1,180 @@
+use async_io::Timer;
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant},
+};
+use trillium::Conn;
+
+fn get(addr: SocketAddr) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        let _ = stream.read_to_string(&mut response);
+        response
+    })
+}
+
+#[test]
+fn socket_addr_and_open_connections() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn(move |conn: Conn| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(()).unwrap();
+                Timer::after(Duration::from_millis(100)).await;
+                conn.ok("hello")
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.socket_addr().await.unwrap();
+        assert_ne!(addr.port(), 0);
+        assert_eq!(handle.open_connections(), 0);
+
+        let response = get(addr);
+        started_rx.recv().unwrap();
+        assert_eq!(handle.open_connections(), 1);
+
+        assert!(handle.stop_with_deadline(Duration::from_secs(10)).await);
+        assert!(response.join().unwrap().ends_with("\r\n\r\nhello"));
+        assert_eq!(handle.open_connections(), 0);
+        assert!(!handle.is_running());
+    });
+}
+
+#[test]
+fn stop_with_deadline_closes_open_connections() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn(move |conn: Conn| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(()).unwrap();
+                Timer::after(Duration::from_secs(60)).await;
+                conn.ok("too late")
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.socket_addr().await.unwrap();
+        let response = get(addr);
+        started_rx.recv().unwrap();
+
+        let start = Instant::now();
+        assert!(!handle.stop_with_deadline(Duration::from_millis(100)).await);
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(response.join().unwrap(), "");
+        assert!(!handle.is_running());
+    });
+}
+
+#[test]
+fn requests_in_flight_and_connections() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn(move |conn: Conn| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(()).unwrap();
+                Timer::after(Duration::from_millis(100)).await;
+                conn.ok("hello")
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.socket_addr().await.unwrap();
+        assert_eq!(handle.requests_in_flight(), 0);
+        assert!(handle.connections().is_empty());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .unwrap();
+        started_rx.recv().unwrap();
+        assert_eq!(handle.requests_in_flight(), 1);
+        let connections = handle.connections();
+        assert_eq!(connections.len(), 1);
+        assert_eq!(connections[0].peer_ip(), Some(addr.ip()));
+        assert_eq!(connections[0].requests_in_flight(), 1);
+        assert!(connections[0].age() < Duration::from_secs(10));
+
+        let mut response = Vec::new();
+        let mut buf = [0; 1024];
+        while !response.ends_with(b"hello") {
+            let bytes = stream.read(&mut buf).unwrap();
+            response.extend_from_slice(&buf[..bytes]);
+        }
+
+        // the keep-alive connection stays open after the response
+        // has been sent, but the request is no longer in flight
+        let start = Instant::now();
+        while handle.requests_in_flight() > 0 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            Timer::after(Duration::from_millis(10)).await;
+        }
+        assert_eq!(handle.connections().len(), 1);
+        assert_eq!(handle.connections()[0].requests_in_flight(), 0);
+
+        drop(stream);
+        handle.stop().await;
+        assert!(handle.connections().is_empty());
+    });
+}
+
+#[test]
+fn close_connections() {
+    let (started_tx, started_rx) = mpsc::channel();
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn(move |conn: Conn| {
+            let started_tx = started_tx.clone();
+            async move {
+                started_tx.send(()).unwrap();
+                Timer::after(Duration::from_secs(60)).await;
+                conn.ok("too late")
+            }
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.socket_addr().await.unwrap();
+        let response = get(addr);
+        started_rx.recv().unwrap();
+
+        assert_eq!(
+            handle.close_connections(|connection| connection.peer_ip() != Some(addr.ip())),
+            0
+        );
+        assert_eq!(
+            handle.close_connections(|connection| connection.age() > Duration::from_secs(60)),
+            0
+        );
+        assert_eq!(handle.close_connections(|_| true), 1);
+        assert_eq!(response.join().unwrap(), "");
+
+        // the server continues to accept connections
+        assert!(handle.is_running());
+        let response = get(addr);
+        started_rx.recv().unwrap();
+        assert_eq!(handle.open_connections(), 1);
+        assert!(!handle.stop_with_deadline(Duration::from_millis(100)).await);
+        assert_eq!(response.join().unwrap(), "");
+    });
+}
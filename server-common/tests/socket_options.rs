@@ -0,0 +1,70 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    time::Duration,
+};
+use trillium_smol::Listener;
+
+fn get(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[cfg(unix)]
+#[test]
+fn reuse_port() {
+    let first = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .with_reuse_port()
+        .without_signals()
+        .spawn("ok");
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let port = first.socket_addr().await.unwrap().port();
+
+        let second = trillium_smol::config()
+            .with_host("127.0.0.1")
+            .with_port(port)
+            .with_reuse_port()
+            .try_bind();
+        assert!(second.is_ok());
+
+        let Err(error) = trillium_smol::config()
+            .with_host("127.0.0.1")
+            .with_port(port)
+            .try_bind()
+        else {
+            panic!("expected binding without SO_REUSEPORT to fail");
+        };
+        assert!(error.is_address_in_use());
+
+        first.stop().await;
+    });
+}
+
+#[test]
+fn socket_options() {
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .with_reuse_address(true)
+        .with_backlog(16)
+        .with_tcp_keepalive(Duration::from_secs(60))
+        .with_recv_buffer_size(64 * 1024)
+        .with_send_buffer_size(64 * 1024)
+        .with_additional_listener(Listener::tcp("127.0.0.1", 0), ())
+        .without_signals()
+        .spawn("ok");
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let addr = handle.socket_addr().await.unwrap();
+        assert!(get(addr).ends_with("\r\n\r\nok"));
+        handle.stop().await;
+    });
+}
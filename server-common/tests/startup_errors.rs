@@ -0,0 +1,116 @@
+use std::net::TcpListener;
+use trillium_server_common::StartupError;
+
+fn taken_port() -> (TcpListener, u16) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    (listener, port)
+}
+
+#[test]
+fn try_bind_port_in_use() {
+    let (_taken, port) = taken_port();
+    let Err(error) = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(port)
+        .try_bind()
+    else {
+        panic!("expected binding to an in-use port to fail");
+    };
+
+    assert!(error.is_address_in_use());
+    assert!(
+        matches!(&error, StartupError::Bind { address, .. } if *address == format!("127.0.0.1:{port}"))
+    );
+}
+
+#[test]
+fn try_bind_unresolvable_host() {
+    let Err(error) = trillium_smol::config()
+        .with_host("host.invalid")
+        .with_port(0)
+        .try_bind()
+    else {
+        panic!("expected an invalid host to fail");
+    };
+
+    assert!(matches!(error, StartupError::Resolve { ref host, .. } if host == "host.invalid"));
+    assert!(!error.is_address_in_use());
+}
+
+#[cfg(unix)]
+#[test]
+fn try_bind_bad_unix_socket_path() {
+    let Err(error) = trillium_smol::config()
+        .with_host("/this/directory/does/not/exist.sock")
+        .try_bind()
+    else {
+        panic!("expected binding to a missing directory to fail");
+    };
+
+    assert!(matches!(error, StartupError::Bind { .. }));
+    assert_eq!(error.kind(), Some(std::io::ErrorKind::NotFound));
+}
+
+#[test]
+fn try_run_returns_startup_error() {
+    let (_taken, port) = taken_port();
+    let result = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(port)
+        .without_signals()
+        .try_run(|conn: trillium::Conn| async move { conn.ok("unreachable") });
+
+    assert!(result.is_err_and(|e| e.is_address_in_use()));
+}
+
+#[test]
+fn try_bind_retains_listener() {
+    let config = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .try_bind()
+        .unwrap();
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let handle = config.spawn("ok");
+        let info = handle.info().await;
+        let addr = info.tcp_socket_addr().copied().unwrap();
+        assert_ne!(addr.port(), 0);
+        assert!(TcpListener::bind(addr).is_err());
+        handle.stop().await;
+    });
+}
+
+#[test]
+fn try_run_missing_dependency() {
+    struct Requires;
+    #[trillium::async_trait]
+    impl trillium::Handler for Requires {
+        async fn run(&self, conn: trillium::Conn) -> trillium::Conn {
+            conn
+        }
+
+        async fn init(&mut self, info: &mut trillium::Info) {
+            info.require::<String>("Requires");
+        }
+    }
+
+    let result = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .try_run(Requires);
+
+    let Err(StartupError::MissingDependencies(missing)) = result else {
+        panic!("expected a missing dependency error");
+    };
+
+    assert_eq!(missing.0.len(), 1);
+    assert_eq!(missing.0[0].handler(), "Requires");
+    assert_eq!(missing.0[0].dependency(), "alloc::string::String");
+    assert!(missing
+        .to_string()
+        .starts_with("Requires requires alloc::string::String"));
+}
@@ -0,0 +1,56 @@
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    sync::{Arc, Mutex},
+};
+use trillium::{Conn, Info, Init, TransportKind};
+
+fn get(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn tcp_transport_kind() {
+    let init_info = Arc::new(Mutex::new(None));
+    let handle = trillium_smol::config()
+        .with_host("127.0.0.1")
+        .with_port(0)
+        .without_signals()
+        .spawn((
+            Init::new({
+                let init_info = Arc::clone(&init_info);
+                move |info: Info| {
+                    let init_info = Arc::clone(&init_info);
+                    async move {
+                        *init_info.lock().unwrap() = Some(info);
+                    }
+                }
+            }),
+            |conn: Conn| async move {
+                let transport_kind = conn.transport_kind();
+                let alpn = conn.negotiated_alpn().is_some();
+                let secure = conn.is_secure();
+                conn.ok(format!("{transport_kind} alpn: {alpn} secure: {secure}"))
+            },
+        ));
+
+    trillium_smol::async_global_executor::block_on(async move {
+        let info = handle.info().await;
+        assert_eq!(info.transport_kind(), TransportKind::Tcp);
+        assert!(!info.is_secure());
+
+        let init_info = init_info.lock().unwrap().take().unwrap();
+        assert_eq!(init_info.transport_kind(), TransportKind::Tcp);
+        assert!(!init_info.is_secure());
+
+        let addr = *info.tcp_socket_addr().unwrap();
+        assert!(get(addr).ends_with("\r\n\r\ntcp alpn: false secure: false"));
+        handle.stop().await;
+    });
+}
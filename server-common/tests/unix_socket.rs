@@ -0,0 +1,55 @@
+#![cfg(unix)]
+use std::{
+    io::{Read, Write},
+    os::unix::{
+        fs::MetadataExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::PathBuf,
+};
+use trillium::{Conn, TransportKind};
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("trillium-{name}-{}.sock", std::process::id()))
+}
+
+fn get(path: &PathBuf) -> String {
+    let mut stream = UnixStream::connect(path).unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn unix_socket_with_peer_credentials() {
+    let path = socket_path("peer-credentials");
+    // a socket file left behind by a listener that is no longer running
+    drop(UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let handle = trillium_smol::config()
+        .with_unix_socket(&path)
+        .without_signals()
+        .spawn(|conn: Conn| async move {
+            let uid = conn.peer_credentials().map(|credentials| credentials.uid());
+            let transport_kind = conn.transport_kind();
+            conn.ok(format!("{transport_kind} {uid:?}"))
+        });
+
+    trillium_smol::async_global_executor::block_on(async move {
+        assert_eq!(handle.info().await.transport_kind(), TransportKind::Unix);
+        let response = get(&path);
+        let owner = std::fs::metadata(&path).unwrap().uid();
+        if cfg!(any(target_os = "linux", target_os = "android")) {
+            assert!(response.ends_with(&format!("\r\n\r\nunix Some({owner})")));
+        } else {
+            assert!(response.ends_with("\r\n\r\nunix None"));
+        }
+
+        handle.stop().await;
+        assert!(!path.exists());
+    });
+}
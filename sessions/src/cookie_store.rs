@@ -0,0 +1,44 @@
+use crate::{Session, SessionStore, StoreError};
+use async_session::base64;
+use trillium::async_trait;
+
+/**
+A session store that serializes the entire session into the cookie.
+
+Because the cookie is signed but not encrypted, session data is
+visible to the client, and because browsers limit cookie sizes, this
+store is only suitable for small sessions. Destroying a session
+removes the cookie, but a copy of the cookie retained by the client
+remains valid until it expires.
+*/
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CookieStore;
+
+impl CookieStore {
+    /// Constructs a new CookieStore
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl SessionStore for CookieStore {
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>, StoreError> {
+        let serialized = base64::decode(cookie_value)?;
+        let session: Session = bincode::deserialize(&serialized)?;
+        Ok(session.validate())
+    }
+
+    async fn store_session(&self, session: Session) -> Result<Option<String>, StoreError> {
+        let serialized = bincode::serialize(&session)?;
+        Ok(Some(base64::encode(serialized)))
+    }
+
+    async fn destroy_session(&self, _session: Session) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    async fn clear_store(&self) -> Result<(), StoreError> {
+        Ok(())
+    }
+}
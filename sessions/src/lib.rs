@@ -27,11 +27,14 @@ that client's session yet.
 
 ## Stores
 
-Although this crate provides two bundled session stores, it is highly
-recommended that trillium applications use an
-external-datastore-backed session storage. For a list of currently
-available session stores, see [the documentation for
-async-session](https://github.com/http-rs/async-session).
+Sessions are persisted by a [`SessionStore`]. This crate provides two
+bundled session stores, [`MemoryStore`] and [`CookieStore`], but it is
+highly recommended that trillium applications use an
+external-datastore-backed session storage such as redis or a sql
+database. Any store written for
+[async-session](https://github.com/http-rs/async-session) can be used
+directly, and custom stores can be built by implementing
+[`SessionStore`].
 
 ## Security
 
@@ -64,12 +67,26 @@ adversary were able to tamper with the expiry of a cookie, trillium
 sessions would still check the expiry on the contained session before
 using it
 
+### Session fixation
+
+Applications should call [`SessionConnExt::regenerate_session_id`]
+whenever the privilege level of a session changes, such as after
+login. This issues a new cookie value for the session's data and
+removes the previous session from the store.
+
 ### If anything goes wrong with the above process
 
 If there are any failures in the above session retrieval process, a
 new empty session is generated for the request, which proceeds through
 the application as normal.
 
+## Typed session data
+
+Types that implement [`TypedSession`] can be retrieved and stored with
+[`SessionConnExt::typed_session`] and
+[`SessionConnExt::set_typed_session`], with a versioned migration
+hook for data stored by previous versions of the type.
+
 ## Stale/expired session cleanup
 
 Any session store other than the cookie store will accumulate stale
@@ -116,7 +133,19 @@ assert_ok!(make_request(), "count: 4");
 mod session_conn_ext;
 pub use session_conn_ext::SessionConnExt;
 
+mod typed_session;
+pub use typed_session::TypedSession;
+
 mod session_handler;
 pub use session_handler::{sessions, SessionHandler};
 
-pub use async_session::{CookieStore, MemoryStore, Session};
+mod session_store;
+pub use session_store::{SessionStore, StoreError};
+
+mod memory_store;
+pub use memory_store::MemoryStore;
+
+mod cookie_store;
+pub use cookie_store::CookieStore;
+
+pub use async_session::{self, Session};
@@ -0,0 +1,78 @@
+use crate::{Session, SessionStore, StoreError};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+use trillium::async_trait;
+
+/**
+An in-memory session store, intended for testing and development.
+
+Sessions are lost when the process exits and are not shared between
+processes. Expired sessions are not removed until
+[`MemoryStore::cleanup`] is called.
+*/
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    inner: Arc<RwLock<HashMap<String, Session>>>,
+}
+
+impl MemoryStore {
+    /// Constructs a new empty MemoryStore
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Removes all expired sessions from this store
+    pub async fn cleanup(&self) {
+        log::trace!("cleaning up memory store...");
+        self.inner
+            .write()
+            .unwrap()
+            .retain(|_, session| !session.is_expired());
+    }
+
+    /// Returns the number of sessions in this store, including any
+    /// expired sessions that have not been cleaned up
+    pub async fn count(&self) -> usize {
+        self.inner.read().unwrap().len()
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemoryStore {
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>, StoreError> {
+        let id = Session::id_from_cookie_value(&cookie_value)?;
+        log::trace!("loading session by id `{id}`");
+        Ok(self
+            .inner
+            .read()
+            .unwrap()
+            .get(&id)
+            .cloned()
+            .and_then(Session::validate))
+    }
+
+    async fn store_session(&self, session: Session) -> Result<Option<String>, StoreError> {
+        log::trace!("storing session by id `{}`", session.id());
+        self.inner
+            .write()
+            .unwrap()
+            .insert(session.id().to_string(), session.clone());
+
+        session.reset_data_changed();
+        Ok(session.into_cookie_value())
+    }
+
+    async fn destroy_session(&self, session: Session) -> Result<(), StoreError> {
+        log::trace!("destroying session by id `{}`", session.id());
+        self.inner.write().unwrap().remove(session.id());
+        Ok(())
+    }
+
+    async fn clear_store(&self) -> Result<(), StoreError> {
+        log::trace!("clearing memory store");
+        self.inner.write().unwrap().clear();
+        Ok(())
+    }
+}
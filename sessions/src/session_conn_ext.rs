@@ -1,3 +1,4 @@
+use crate::{typed_session, TypedSession};
 use async_session::{serde::Serialize, Session};
 use trillium::Conn;
 
@@ -23,8 +24,52 @@ pub trait SessionConnExt {
     retrieve a mutable reference to the current session
     */
     fn session_mut(&mut self) -> &mut Session;
+
+    /**
+    rotate the current session's id and cookie value while retaining
+    its data. the previous session will be removed from the store
+    when the response is sent. this should be called whenever the
+    privilege level of a session changes, such as after login, to
+    prevent session fixation.
+    */
+    fn regenerate_session_id(&mut self);
+
+    /**
+    mark the current session for destruction. the session will be
+    removed from the store and the session cookie will be removed
+    when the response is sent.
+    */
+    fn destroy_session(&mut self);
+
+    /**
+    retrieve a [`TypedSession`] from the current session, migrating
+    it from a previous version if needed. returns None if the key is
+    not present, or if the stored data cannot be deserialized or
+    migrated.
+    */
+    fn typed_session<T: TypedSession>(&self) -> Option<T>;
+
+    /**
+    store a [`TypedSession`] in the current session under its key,
+    replacing any previous value
+    */
+    fn set_typed_session<T: TypedSession>(&mut self, value: T);
+
+    /**
+    chainable variant of [`SessionConnExt::set_typed_session`]
+    */
+    fn with_typed_session<T: TypedSession>(self, value: T) -> Self;
+
+    /**
+    remove a [`TypedSession`] from the current session
+    */
+    fn remove_typed_session<T: TypedSession>(&mut self);
 }
 
+/// the session as it was before the first call to
+/// [`SessionConnExt::regenerate_session_id`] on this conn
+pub(crate) struct RegeneratedSession(pub(crate) Session);
+
 impl SessionConnExt for Conn {
     fn session(&self) -> &Session {
         self.state()
@@ -40,4 +85,34 @@ impl SessionConnExt for Conn {
         self.state_mut()
             .expect("SessionHandler must be executed before calling SessionConnExt::sessions_mut")
     }
+
+    fn regenerate_session_id(&mut self) {
+        let session = self.session_mut();
+        let previous = session.clone();
+        session.regenerate();
+        if self.state::<RegeneratedSession>().is_none() {
+            self.insert_state(RegeneratedSession(previous));
+        }
+    }
+
+    fn destroy_session(&mut self) {
+        self.session_mut().destroy();
+    }
+
+    fn typed_session<T: TypedSession>(&self) -> Option<T> {
+        typed_session::get(self.session())
+    }
+
+    fn set_typed_session<T: TypedSession>(&mut self, value: T) {
+        typed_session::insert(self.session_mut(), &value);
+    }
+
+    fn with_typed_session<T: TypedSession>(mut self, value: T) -> Self {
+        self.set_typed_session(value);
+        self
+    }
+
+    fn remove_typed_session<T: TypedSession>(&mut self) {
+        self.session_mut().remove(T::KEY);
+    }
 }
@@ -1,18 +1,18 @@
 const BASE64_DIGEST_LEN: usize = 44;
+use crate::{session_conn_ext::RegeneratedSession, Session, SessionStore};
 use async_session::{
     base64,
     hmac::{Hmac, Mac, NewMac},
     sha2::Sha256,
-    Session, SessionStore,
 };
 use std::{
     fmt::{self, Debug, Formatter},
     iter,
     time::{Duration, SystemTime},
 };
-use trillium::{async_trait, Conn, Handler};
+use trillium::{async_trait, Conn, Handler, Info};
 use trillium_cookies::{
-    cookie::{Cookie, Key, SameSite},
+    cookie::{Cookie, CookieJar, Key, SameSite},
     CookiesConnExt,
 };
 
@@ -21,6 +21,9 @@ use trillium_cookies::{
 
 See crate-level docs for an overview of this crate's approach to
 sessions and security.
+
+A [`CookiesHandler`](trillium_cookies::CookiesHandler) must run
+before this handler. Servers will refuse to start if it does not.
 */
 
 pub struct SessionHandler<Store> {
@@ -30,6 +33,7 @@ pub struct SessionHandler<Store> {
     cookie_domain: Option<String>,
     session_ttl: Option<Duration>,
     save_unchanged: bool,
+    rolling_expiry: bool,
     same_site_policy: SameSite,
     key: Key,
     older_keys: Vec<Key>,
@@ -44,6 +48,7 @@ impl<Store: SessionStore> Debug for SessionHandler<Store> {
             .field("cookie_domain", &self.cookie_domain)
             .field("session_ttl", &self.session_ttl)
             .field("save_unchanged", &self.save_unchanged)
+            .field("rolling_expiry", &self.rolling_expiry)
             .field("same_site_policy", &self.same_site_policy)
             .field("key", &"<<secret>>")
             .field("older_keys", &"<<secret>>")
@@ -54,7 +59,7 @@ impl<Store: SessionStore> Debug for SessionHandler<Store> {
 impl<Store: SessionStore> SessionHandler<Store> {
     /**
     Constructs a SessionHandler from the given
-    [`async_session::SessionStore`] and secret. The `secret` MUST be
+    [`SessionStore`] and secret. The `secret` MUST be
     at least 32 bytes long, and MUST be cryptographically random to be
     secure. It is recommended to retrieve this at runtime from the
     environment instead of compiling it into your application.
@@ -72,6 +77,7 @@ impl<Store: SessionStore> SessionHandler<Store> {
     * session ttl: one day
     * same site: strict
     * save unchanged: enabled
+    * rolling expiry: disabled
     * older secrets: none
 
     # Customization
@@ -99,6 +105,7 @@ impl<Store: SessionStore> SessionHandler<Store> {
             .with_session_ttl(Some(Duration::from_secs(1)))
             .with_older_secrets(&session_secrets[1..])
             .without_save_unchanged()
+            .with_rolling_expiry()
     );
 
     ```
@@ -107,6 +114,7 @@ impl<Store: SessionStore> SessionHandler<Store> {
         Self {
             store,
             save_unchanged: true,
+            rolling_expiry: false,
             cookie_path: "/".into(),
             cookie_name: "trillium.sid".into(),
             cookie_domain: None,
@@ -156,6 +164,17 @@ impl<Store: SessionStore> SessionHandler<Store> {
         self
     }
 
+    /// Enables rolling expiry. With rolling expiry, every request
+    /// persists the session and reissues the session cookie, so
+    /// that both the store ttl and the cookie expiry are refreshed
+    /// and a session only expires after a period of inactivity as
+    /// long as the session ttl. This persists sessions regardless of
+    /// the `save_unchanged` setting.
+    pub fn with_rolling_expiry(mut self) -> Self {
+        self.rolling_expiry = true;
+        self
+    }
+
     /// Sets the same site policy for the session cookie. Defaults to
     /// SameSite::Strict. See [incrementally better
     /// cookies](https://tools.ietf.org/html/draft-west-cookie-incrementalism-01)
@@ -267,6 +286,10 @@ impl<Store: SessionStore> SessionHandler<Store> {
     }
 }
 
+// the verified cookie value this request's session was loaded with,
+// retained so that the cookie can be reissued with rolling expiry
+struct SessionCookieValue(String);
+
 #[async_trait]
 impl<Store: SessionStore> Handler for SessionHandler<Store> {
     async fn run(&self, mut conn: Conn) -> Conn {
@@ -286,25 +309,56 @@ impl<Store: SessionStore> Handler for SessionHandler<Store> {
             session.expire_in(ttl);
         }
 
+        if self.rolling_expiry {
+            if let Some(cookie_value) = cookie_value {
+                let cookie_value = SessionCookieValue(cookie_value.to_string());
+                conn.insert_state(cookie_value);
+            }
+        }
+
         conn.with_state(session)
     }
 
+    async fn init(&mut self, info: &mut Info) {
+        info.require::<CookieJar>("SessionHandler");
+        info.provide::<Session>();
+    }
+
     async fn before_send(&self, mut conn: Conn) -> Conn {
         if let Some(session) = conn.take_state::<Session>() {
             let session_to_keep = session.clone();
             let secure = conn.is_secure();
+
+            let regenerated = conn.take_state::<RegeneratedSession>();
+            let previous_cookie_value = conn.take_state::<SessionCookieValue>();
+
+            if let Some(RegeneratedSession(previous)) = &regenerated {
+                if let Err(e) = self.store.destroy_session(previous.clone()).await {
+                    log::error!("could not destroy regenerated session:\n\n{e}")
+                }
+            }
+
             if session.is_destroyed() {
                 self.store.destroy_session(session).await.ok();
                 conn.cookies_mut()
                     .remove(Cookie::from(self.cookie_name.clone()));
-            } else if self.save_unchanged || session.data_changed() {
+            } else if self.save_unchanged
+                || self.rolling_expiry
+                || regenerated.is_some()
+                || session.data_changed()
+            {
                 match self.store.store_session(session).await {
                     Ok(Some(cookie_value)) => {
                         conn.cookies_mut()
                             .add(self.build_cookie(secure, cookie_value));
                     }
 
-                    Ok(None) => {}
+                    Ok(None) => {
+                        if let Some(SessionCookieValue(cookie_value)) = previous_cookie_value {
+                            conn.cookies_mut()
+                                .add(self.build_cookie(secure, cookie_value));
+                        }
+                    }
 
                     Err(e) => {
                         log::error!("could not store session:\n\n{e}")
@@ -0,0 +1,114 @@
+use crate::Session;
+use std::{error::Error, fmt::Debug};
+use trillium::async_trait;
+
+/// The error type returned by [`SessionStore`] operations
+pub type StoreError = Box<dyn Error + Send + Sync + 'static>;
+
+/**
+An async backend for persisting sessions.
+
+Stores are responsible for turning a [`Session`] into a cookie value
+and back. Most stores persist the session in an external datastore
+keyed by [`Session::id`] and return [`Session::into_cookie_value`] as
+the cookie value, loading it again with
+[`Session::id_from_cookie_value`]. The [`MemoryStore`](crate::MemoryStore)
+in this crate is a small reference implementation of that approach.
+
+## Existing stores
+
+Every [`async_session::SessionStore`] is also a `SessionStore`, so
+stores published for async-session, such as redis or sql stores, can
+be passed directly to [`SessionHandler::new`](crate::SessionHandler::new).
+
+## Implementing a store
+
+```
+use std::{collections::HashMap, sync::{Arc, Mutex}};
+use trillium::async_trait;
+use trillium_sessions::{Session, SessionStore, StoreError};
+
+// a stand-in for a database table of id -> serialized session
+#[derive(Debug, Default)]
+struct TableStore(Arc<Mutex<HashMap<String, String>>>);
+
+#[async_trait]
+impl SessionStore for TableStore {
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>, StoreError> {
+        let id = Session::id_from_cookie_value(&cookie_value)?;
+        let Some(row) = self.0.lock().unwrap().get(&id).cloned() else {
+            return Ok(None);
+        };
+        let session: Session = serde_json::from_str(&row)?;
+        Ok(session.validate())
+    }
+
+    async fn store_session(&self, session: Session) -> Result<Option<String>, StoreError> {
+        let row = serde_json::to_string(&session)?;
+        self.0.lock().unwrap().insert(session.id().to_string(), row);
+        session.reset_data_changed();
+        Ok(session.into_cookie_value())
+    }
+
+    async fn destroy_session(&self, session: Session) -> Result<(), StoreError> {
+        self.0.lock().unwrap().remove(session.id());
+        Ok(())
+    }
+
+    async fn clear_store(&self) -> Result<(), StoreError> {
+        self.0.lock().unwrap().clear();
+        Ok(())
+    }
+}
+
+# trillium_testing::block_on(async {
+let store = TableStore::default();
+let mut session = Session::new();
+session.insert("key", "value").unwrap();
+let cookie_value = store.store_session(session).await.unwrap().unwrap();
+let session = store.load_session(cookie_value).await.unwrap().unwrap();
+assert_eq!(session.get::<String>("key").unwrap(), "value");
+# });
+```
+*/
+#[async_trait]
+pub trait SessionStore: Debug + Send + Sync + 'static {
+    /// Get a session from the storage backend. The cookie value is
+    /// the value that was returned by a previous call to
+    /// [`SessionStore::store_session`]. Returns `Ok(None)` if there
+    /// is no valid session for this cookie value.
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>, StoreError>;
+
+    /// Store a session in the storage backend, returning a cookie
+    /// value if the cookie needs to be set or updated. Returning
+    /// `Ok(None)` leaves the client's existing cookie unchanged.
+    async fn store_session(&self, session: Session) -> Result<Option<String>, StoreError>;
+
+    /// Remove a session from the storage backend
+    async fn destroy_session(&self, session: Session) -> Result<(), StoreError>;
+
+    /// Remove all sessions from the storage backend
+    async fn clear_store(&self) -> Result<(), StoreError>;
+}
+
+#[async_trait]
+impl<Store> SessionStore for Store
+where
+    Store: async_session::SessionStore,
+{
+    async fn load_session(&self, cookie_value: String) -> Result<Option<Session>, StoreError> {
+        Ok(async_session::SessionStore::load_session(self, cookie_value).await?)
+    }
+
+    async fn store_session(&self, session: Session) -> Result<Option<String>, StoreError> {
+        Ok(async_session::SessionStore::store_session(self, session).await?)
+    }
+
+    async fn destroy_session(&self, session: Session) -> Result<(), StoreError> {
+        Ok(async_session::SessionStore::destroy_session(self, session).await?)
+    }
+
+    async fn clear_store(&self) -> Result<(), StoreError> {
+        Ok(async_session::SessionStore::clear_store(self).await?)
+    }
+}
@@ -0,0 +1,98 @@
+use async_session::{
+    serde::{de::DeserializeOwned, Deserialize, Serialize},
+    serde_json::{self, Value},
+    Session,
+};
+
+/**
+# A serde-serializable type stored under a fixed session key
+
+Implementing `TypedSession` associates a type with a session key and a
+schema version, allowing it to be retrieved with
+[`SessionConnExt::typed_session`](crate::SessionConnExt::typed_session)
+and stored with
+[`SessionConnExt::set_typed_session`](crate::SessionConnExt::set_typed_session)
+instead of by string key.
+
+Typed session data is stored alongside its [`VERSION`](Self::VERSION).
+When the stored version differs from the current version, or when the
+key contains a value that was inserted directly with
+[`Session::insert`] (treated as version 0), the stored data is passed
+to [`migrate`](Self::migrate). The default implementation discards
+data from any other version.
+
+```
+use trillium_sessions::{
+    async_session::{serde::{Deserialize, Serialize}, serde_json::{from_value, Value}},
+    TypedSession,
+};
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(crate = "trillium_sessions::async_session::serde")]
+struct Cart {
+    items: Vec<(String, u32)>,
+}
+
+impl TypedSession for Cart {
+    const KEY: &'static str = "cart";
+    const VERSION: u32 = 2;
+
+    fn migrate(from_version: u32, data: Value) -> Option<Self> {
+        match from_version {
+            // version 1 stored a list of item names with no quantities
+            1 => from_value::<Vec<String>>(data)
+                .ok()
+                .map(|items| Cart { items: items.into_iter().map(|item| (item, 1)).collect() }),
+            _ => None,
+        }
+    }
+}
+```
+*/
+pub trait TypedSession: Serialize + DeserializeOwned {
+    /// the session key this type is stored under
+    const KEY: &'static str;
+
+    /// the current schema version of this type. defaults to 1
+    const VERSION: u32 = 1;
+
+    /**
+    convert data stored by a previous version of this type into the
+    current version, returning None if it cannot be converted.
+    `from_version` is 0 for values that were not stored as a
+    `TypedSession`.
+    */
+    fn migrate(from_version: u32, data: Value) -> Option<Self> {
+        let _ = (from_version, data);
+        None
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "async_session::serde")]
+struct Versioned<T> {
+    version: u32,
+    data: T,
+}
+
+pub(crate) fn get<T: TypedSession>(session: &Session) -> Option<T> {
+    let value: Value = session.get(T::KEY)?;
+    match serde_json::from_value::<Versioned<Value>>(value.clone()) {
+        Ok(Versioned { version, data }) if version == T::VERSION => {
+            serde_json::from_value(data).ok()
+        }
+        Ok(Versioned { version, data }) => T::migrate(version, data),
+        Err(_) => T::migrate(0, value),
+    }
+}
+
+pub(crate) fn insert<T: TypedSession>(session: &mut Session, data: &T) {
+    let versioned = Versioned {
+        version: T::VERSION,
+        data,
+    };
+
+    if let Err(e) = session.insert(T::KEY, versioned) {
+        log::error!("could not serialize {}: {e}", T::KEY);
+    }
+}
@@ -0,0 +1,171 @@
+use trillium::Conn;
+use trillium_cookies::{cookie::Cookie, CookiesHandler};
+use trillium_sessions::{
+    async_session::{
+        serde::{Deserialize, Serialize},
+        serde_json::{from_value, Value},
+    },
+    MemoryStore, SessionConnExt, SessionHandler, TypedSession,
+};
+use trillium_testing::{prelude::*, TestConn};
+
+const SECRET: &[u8] = b"this is just for testing and you should not do this";
+
+fn app(session_handler: SessionHandler<MemoryStore>) -> impl trillium::Handler {
+    (
+        CookiesHandler::new(),
+        session_handler,
+        |mut conn: Conn| async move {
+            match conn.path() {
+                "/login" => {
+                    conn.regenerate_session_id();
+                    conn.with_session("user", "jbr").ok("logged in")
+                }
+
+                "/logout" => {
+                    conn.destroy_session();
+                    conn.ok("logged out")
+                }
+
+                _ => {
+                    let user: Option<String> = conn.session().get("user");
+                    conn.ok(user.unwrap_or_else(|| "guest".into()))
+                }
+            }
+        },
+    )
+}
+
+fn session_cookie(conn: &TestConn) -> Option<Cookie<'static>> {
+    conn.response_headers()
+        .get_values("set-cookie")?
+        .iter()
+        .filter_map(|value| value.as_str())
+        .filter_map(|value| Cookie::parse_encoded(value.to_string()).ok())
+        .find(|cookie| cookie.name() == "trillium.sid")
+}
+
+fn request(handler: &impl trillium::Handler, path: &str, cookie: &Cookie<'_>) -> TestConn {
+    get(path)
+        .with_request_header("cookie", format!("{}={}", cookie.name(), cookie.value()))
+        .on(handler)
+}
+
+#[test]
+fn regenerate_session_id() {
+    let store = MemoryStore::new();
+    let handler = app(SessionHandler::new(store.clone(), SECRET));
+
+    let mut conn = get("/").on(&handler);
+    assert_ok!(&mut conn, "guest");
+    let guest_cookie = session_cookie(&conn).unwrap();
+
+    let mut conn = request(&handler, "/login", &guest_cookie);
+    assert_ok!(&mut conn, "logged in");
+    let login_cookie = session_cookie(&conn).unwrap();
+    assert_ne!(guest_cookie.value(), login_cookie.value());
+    assert_eq!(trillium_testing::block_on(store.count()), 1);
+
+    assert_ok!(request(&handler, "/", &login_cookie), "jbr");
+    assert_ok!(request(&handler, "/", &guest_cookie), "guest");
+}
+
+#[test]
+fn destroy_session() {
+    let store = MemoryStore::new();
+    let handler = app(SessionHandler::new(store.clone(), SECRET));
+
+    let conn = get("/login").on(&handler);
+    let cookie = session_cookie(&conn).unwrap();
+    assert_ok!(request(&handler, "/", &cookie), "jbr");
+
+    let mut conn = request(&handler, "/logout", &cookie);
+    assert_ok!(&mut conn, "logged out");
+    let removal = session_cookie(&conn).unwrap();
+    assert_eq!(removal.value(), "");
+    assert_eq!(trillium_testing::block_on(store.count()), 0);
+
+    assert_ok!(request(&handler, "/", &cookie), "guest");
+}
+
+#[test]
+fn rolling_expiry() {
+    let handler = app(SessionHandler::new(MemoryStore::new(), SECRET).without_save_unchanged());
+    let cookie = session_cookie(&get("/login").on(&handler)).unwrap();
+    let conn = request(&handler, "/", &cookie);
+    assert!(session_cookie(&conn).is_none());
+
+    let handler = app(SessionHandler::new(MemoryStore::new(), SECRET)
+        .without_save_unchanged()
+        .with_rolling_expiry());
+    let cookie = session_cookie(&get("/login").on(&handler)).unwrap();
+    let mut conn = request(&handler, "/", &cookie);
+    assert_ok!(&mut conn, "jbr");
+    let reissued = session_cookie(&conn).unwrap();
+    assert_eq!(reissued.value(), cookie.value());
+    assert!(reissued.expires_datetime().unwrap() >= cookie.expires_datetime().unwrap());
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Default)]
+#[serde(crate = "trillium_sessions::async_session::serde")]
+struct Cart {
+    items: Vec<(String, u32)>,
+}
+
+impl TypedSession for Cart {
+    const KEY: &'static str = "cart";
+    const VERSION: u32 = 2;
+
+    fn migrate(from_version: u32, data: Value) -> Option<Self> {
+        match from_version {
+            0 | 1 => from_value::<Vec<String>>(data).ok().map(|items| Cart {
+                items: items.into_iter().map(|item| (item, 1)).collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn typed_session() {
+    let handler = (
+        CookiesHandler::new(),
+        SessionHandler::new(MemoryStore::new(), SECRET),
+        |mut conn: Conn| async move {
+            match conn.path() {
+                "/legacy" => conn.with_session("cart", ["apple", "pear"]).ok("legacy"),
+                "/add" => {
+                    let mut cart = conn.typed_session::<Cart>().unwrap_or_default();
+                    cart.items.push(("plum".into(), 3));
+                    conn.with_typed_session(cart).ok("added")
+                }
+                "/clear" => {
+                    conn.remove_typed_session::<Cart>();
+                    conn.ok("cleared")
+                }
+                _ => {
+                    let cart = conn.typed_session::<Cart>();
+                    conn.ok(format!("{cart:?}"))
+                }
+            }
+        },
+    );
+
+    let cookie = session_cookie(&get("/").on(&handler)).unwrap();
+    assert_ok!(request(&handler, "/", &cookie), "None");
+
+    assert_ok!(request(&handler, "/legacy", &cookie), "legacy");
+    assert_ok!(
+        request(&handler, "/", &cookie),
+        r#"Some(Cart { items: [("apple", 1), ("pear", 1)] })"#
+    );
+
+    assert_ok!(request(&handler, "/add", &cookie), "added");
+    assert_ok!(
+        request(&handler, "/", &cookie),
+        r#"Some(Cart { items: [("apple", 1), ("pear", 1), ("plum", 3)] })"#
+    );
+
+    assert_ok!(request(&handler, "/clear", &cookie), "cleared");
+    assert_ok!(request(&handler, "/", &cookie), "None");
+}
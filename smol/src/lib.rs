@@ -60,7 +60,10 @@ trillium_testing::with_server("ok", |url| async move {
 */
 
 use trillium::Handler;
-pub use trillium_server_common::{Binding, CloneCounterObserver, Stopper};
+pub use trillium_server_common::{
+    AcmeChallenges, Binding, CloneCounterObserver, HttpsRedirect, Listener, ProxyProtocolAcceptor,
+    Stopper,
+};
 
 mod client;
 pub use client::ClientConfig;
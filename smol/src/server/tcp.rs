@@ -36,6 +36,10 @@ impl Server for SmolServer {
         self.0.local_addr().unwrap().into()
     }
 
+    fn try_clone(&self) -> Result<Self> {
+        Ok(Self(self.0.clone()))
+    }
+
     fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         spawn(fut).detach();
     }
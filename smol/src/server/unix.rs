@@ -77,6 +77,10 @@ impl Server for SmolServer {
         }
     }
 
+    fn try_clone(&self) -> Result<Self> {
+        Ok(self.clone())
+    }
+
     fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         spawn(fut).detach();
     }
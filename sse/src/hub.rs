@@ -0,0 +1,327 @@
+use crate::{Event, Eventable};
+use futures_lite::Stream;
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::{self, Debug, Formatter},
+    pin::Pin,
+    sync::{Arc, Mutex, Weak},
+    task::{Context, Poll, Waker},
+};
+
+/**
+What an [`SseHub`] does when an event is published to a subscriber
+whose buffer is full.
+*/
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LagPolicy {
+    /// discard the oldest buffered event to make room for the new
+    /// event. this is the default.
+    #[default]
+    DropOldest,
+
+    /// discard the new event, retaining the buffered events
+    DropNewest,
+
+    /// end the subscriber's stream once its buffered events have
+    /// been sent, closing the response
+    Disconnect,
+}
+
+/**
+A broadcast hub that fans events published to a topic out to any
+number of subscriber streams.
+
+Each subscriber has a bounded buffer, so publishing never waits on
+slow clients. When a subscriber's buffer is full, the hub's
+[`LagPolicy`] determines what happens. Subscribers are removed from
+the hub when their [`Subscription`] is dropped, which happens when
+the client disconnects.
+
+An `SseHub` is cheap to clone, and clones share the same topics and
+subscribers.
+
+```
+use trillium::{conn_try, Conn, Method, State};
+use trillium_sse::{SseConnExt, SseHub};
+
+let hub = SseHub::<String>::new().with_buffer_size(16);
+
+let handler = (State::new(hub), |mut conn: Conn| async move {
+    let hub = conn.state::<SseHub<String>>().unwrap().clone();
+    let topic = conn.path().trim_start_matches('/').to_string();
+    match conn.method() {
+        Method::Get => conn.with_sse_stream(hub.subscribe(&topic)),
+        Method::Post => {
+            let body = conn_try!(conn.request_body_string().await, conn);
+            let subscribers = hub.publish(&topic, body);
+            conn.ok(format!("sent to {subscribers} subscribers"))
+        }
+        _ => conn,
+    }
+});
+
+use trillium_testing::prelude::*;
+assert_ok!(post("/news").with_request_body("hello").on(&handler), "sent to 0 subscribers");
+```
+*/
+pub struct SseHub<E = Event> {
+    inner: Arc<Mutex<HubInner<E>>>,
+    buffer_size: usize,
+    lag_policy: LagPolicy,
+}
+
+type Subscribers<E> = HashMap<u64, Arc<Mutex<Buffer<E>>>>;
+
+struct HubInner<E> {
+    topics: HashMap<String, Subscribers<E>>,
+    next_id: u64,
+}
+
+struct Buffer<E> {
+    events: VecDeque<E>,
+    capacity: usize,
+    lag_policy: LagPolicy,
+    waker: Option<Waker>,
+    dropped: u64,
+    disconnected: bool,
+}
+
+impl<E> Clone for SseHub<E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            buffer_size: self.buffer_size,
+            lag_policy: self.lag_policy,
+        }
+    }
+}
+
+impl<E> Default for SseHub<E> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(HubInner {
+                topics: HashMap::new(),
+                next_id: 0,
+            })),
+            buffer_size: 64,
+            lag_policy: LagPolicy::default(),
+        }
+    }
+}
+
+impl<E> Debug for SseHub<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        let topics = inner
+            .topics
+            .iter()
+            .map(|(topic, subscribers)| (topic, subscribers.len()))
+            .collect::<HashMap<_, _>>();
+        f.debug_struct("SseHub")
+            .field("topics", &topics)
+            .field("buffer_size", &self.buffer_size)
+            .field("lag_policy", &self.lag_policy)
+            .finish()
+    }
+}
+
+impl<E> SseHub<E>
+where
+    E: Eventable + Clone,
+{
+    /// Constructs a new SseHub with a buffer of 64 events per
+    /// subscriber and the [`LagPolicy::DropOldest`] policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of events that can be buffered for each
+    /// subscriber before the [`LagPolicy`] applies. This only affects
+    /// subsequent subscriptions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer_size` is zero
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        assert!(buffer_size > 0, "buffer_size must be greater than zero");
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// Sets the [`LagPolicy`] for subscribers whose buffer is
+    /// full. This only affects subsequent subscriptions.
+    pub fn with_lag_policy(mut self, lag_policy: LagPolicy) -> Self {
+        self.lag_policy = lag_policy;
+        self
+    }
+
+    /// Subscribes to a topic, returning a stream of events published
+    /// to that topic after this call. The returned [`Subscription`]
+    /// can be passed directly to
+    /// [`SseConnExt::with_sse_stream`](crate::SseConnExt::with_sse_stream).
+    pub fn subscribe(&self, topic: &str) -> Subscription<E> {
+        let buffer = Arc::new(Mutex::new(Buffer {
+            events: VecDeque::new(),
+            capacity: self.buffer_size,
+            lag_policy: self.lag_policy,
+            waker: None,
+            dropped: 0,
+            disconnected: false,
+        }));
+
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner
+            .topics
+            .entry(topic.to_string())
+            .or_default()
+            .insert(id, Arc::clone(&buffer));
+
+        Subscription {
+            hub: Arc::downgrade(&self.inner),
+            topic: topic.to_string(),
+            id,
+            buffer,
+        }
+    }
+
+    /// Publishes an event to every current subscriber of a topic,
+    /// returning the number of subscribers that the event was
+    /// buffered for. This never waits for subscribers to receive the
+    /// event.
+    pub fn publish(&self, topic: &str, event: impl Into<E>) -> usize {
+        let event = event.into();
+        let inner = self.inner.lock().unwrap();
+        let Some(subscribers) = inner.topics.get(topic) else {
+            return 0;
+        };
+
+        let mut buffered = 0;
+        for buffer in subscribers.values() {
+            let mut buffer = buffer.lock().unwrap();
+            if buffer.push(event.clone()) {
+                buffered += 1;
+            }
+
+            if let Some(waker) = buffer.waker.take() {
+                waker.wake();
+            }
+        }
+        buffered
+    }
+
+    /// Returns the number of current subscribers to a topic
+    pub fn subscriber_count(&self, topic: &str) -> usize {
+        self.inner
+            .lock()
+            .unwrap()
+            .topics
+            .get(topic)
+            .map_or(0, HashMap::len)
+    }
+
+    /// Returns the topics that currently have subscribers
+    pub fn topics(&self) -> Vec<String> {
+        self.inner.lock().unwrap().topics.keys().cloned().collect()
+    }
+}
+
+impl<E> Buffer<E> {
+    fn push(&mut self, event: E) -> bool {
+        if self.disconnected {
+            return false;
+        }
+
+        if self.events.len() < self.capacity {
+            self.events.push_back(event);
+            return true;
+        }
+
+        self.dropped += 1;
+        match self.lag_policy {
+            LagPolicy::DropOldest => {
+                self.events.pop_front();
+                self.events.push_back(event);
+                true
+            }
+
+            LagPolicy::DropNewest => false,
+
+            LagPolicy::Disconnect => {
+                self.disconnected = true;
+                false
+            }
+        }
+    }
+}
+
+/**
+A stream of events published to a single topic of an [`SseHub`].
+
+The subscription is removed from the hub when this is dropped.
+*/
+pub struct Subscription<E = Event> {
+    hub: Weak<Mutex<HubInner<E>>>,
+    topic: String,
+    id: u64,
+    buffer: Arc<Mutex<Buffer<E>>>,
+}
+
+impl<E> Subscription<E> {
+    /// the topic this subscription receives events for
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    /// the number of events that have not been delivered to this
+    /// subscriber because its buffer was full
+    pub fn dropped_events(&self) -> u64 {
+        self.buffer.lock().unwrap().dropped
+    }
+}
+
+impl<E> Debug for Subscription<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let buffer = self.buffer.lock().unwrap();
+        f.debug_struct("Subscription")
+            .field("topic", &self.topic)
+            .field("buffered_events", &buffer.events.len())
+            .field("dropped_events", &buffer.dropped)
+            .field("disconnected", &buffer.disconnected)
+            .finish()
+    }
+}
+
+impl<E> Stream for Subscription<E> {
+    type Item = E;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        let mut buffer = self.buffer.lock().unwrap();
+        if let Some(event) = buffer.events.pop_front() {
+            Poll::Ready(Some(event))
+        } else if buffer.disconnected {
+            Poll::Ready(None)
+        } else {
+            buffer.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<E> Drop for Subscription<E> {
+    fn drop(&mut self) {
+        let Some(hub) = self.hub.upgrade() else {
+            return;
+        };
+
+        let mut inner = hub.lock().unwrap();
+        if let Some(subscribers) = inner.topics.get_mut(&self.topic) {
+            subscribers.remove(&self.id);
+            if subscribers.is_empty() {
+                inner.topics.remove(&self.topic);
+            }
+        }
+    }
+}
@@ -11,6 +11,10 @@ Often, you will want this stream to be something like a channel, but
 the specifics of that are dependent on the event fanout
 characteristics of your application.
 
+For the common case of fanning events out to many subscribers, this
+crate provides [`SseHub`], a broadcast hub of topics with bounded
+per-subscriber buffers.
+
 This crate implements [`Eventable`] for an [`Event`] type that you can
 use in your application, for `String`, and for `&'static str`. You can
 also implement [`Eventable`] for any type in your application.
@@ -65,6 +69,9 @@ fn main() {
 )]
 #![warn(missing_docs)]
 
+mod hub;
+pub use hub::{LagPolicy, SseHub, Subscription};
+
 use futures_lite::{stream::Stream, AsyncRead};
 use std::{
     borrow::Cow,
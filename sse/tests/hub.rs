@@ -0,0 +1,66 @@
+use futures_lite::{future::block_on, StreamExt};
+use trillium_sse::{Event, LagPolicy, SseHub};
+
+#[test]
+fn fans_out_to_topic_subscribers() {
+    let hub = SseHub::<Event>::new();
+    let mut first = hub.subscribe("news");
+    let mut second = hub.subscribe("news");
+    let mut other = hub.subscribe("weather");
+    assert_eq!(hub.subscriber_count("news"), 2);
+
+    assert_eq!(hub.publish("news", "extra extra"), 2);
+    assert_eq!(hub.publish("sports", "nobody is listening"), 0);
+
+    block_on(async {
+        assert_eq!(first.next().await.unwrap().data(), "extra extra");
+        assert_eq!(second.next().await.unwrap().data(), "extra extra");
+    });
+
+    hub.publish("weather", Event::new("sunny").with_type("forecast"));
+    let forecast = block_on(other.next()).unwrap();
+    assert_eq!(forecast.event_type(), Some("forecast"));
+}
+
+#[test]
+fn dropped_subscriptions_are_removed() {
+    let hub = SseHub::<String>::new();
+    let subscription = hub.subscribe("news");
+    assert_eq!(hub.topics(), vec![String::from("news")]);
+    drop(subscription);
+    assert_eq!(hub.subscriber_count("news"), 0);
+    assert!(hub.topics().is_empty());
+    assert_eq!(hub.publish("news", "hello"), 0);
+}
+
+#[test]
+fn lag_policies() {
+    let hub = SseHub::<String>::new().with_buffer_size(2);
+    let mut subscription = hub.subscribe("topic");
+    for n in 0..4 {
+        hub.publish("topic", n.to_string());
+    }
+    assert_eq!(subscription.dropped_events(), 2);
+    block_on(async {
+        assert_eq!(subscription.next().await.unwrap(), "2");
+        assert_eq!(subscription.next().await.unwrap(), "3");
+    });
+
+    let hub = hub.with_lag_policy(LagPolicy::DropNewest);
+    let mut subscription = hub.subscribe("topic");
+    for n in 0..4 {
+        hub.publish("topic", n.to_string());
+    }
+    block_on(async {
+        assert_eq!(subscription.next().await.unwrap(), "0");
+        assert_eq!(subscription.next().await.unwrap(), "1");
+    });
+
+    let hub = hub.with_lag_policy(LagPolicy::Disconnect);
+    let subscription = hub.subscribe("topic");
+    for n in 0..4 {
+        hub.publish("topic", n.to_string());
+    }
+    let events = block_on(subscription.collect::<Vec<_>>());
+    assert_eq!(events, ["0", "1"]);
+}
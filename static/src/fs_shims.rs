@@ -29,7 +29,15 @@ cfg_if::cfg_if! {
             }
         }
 
-
+        impl futures_lite::AsyncSeek for File {
+            fn poll_seek(
+                self: std::pin::Pin<&mut Self>,
+                _cx: &mut std::task::Context<'_>,
+                _pos: std::io::SeekFrom,
+            ) -> std::task::Poll<std::io::Result<u64>> {
+                unimplemented!("please enable the tokio, async-std, or smol runtime feature")
+            }
+        }
 
         pub(crate) mod fs {
             pub(crate) async fn canonicalize(_path: impl AsRef<std::path::Path>) -> std::io::Result<std::path::PathBuf> {
@@ -1,10 +1,14 @@
 use crate::{
     fs_shims::{fs, File},
+    image_variant::ImageVariant,
     options::StaticOptions,
     StaticConnExt,
 };
 use std::path::{Path, PathBuf};
-use trillium::{async_trait, conn_unwrap, Conn, Handler};
+use trillium::{
+    async_trait, conn_unwrap, Conn, Handler,
+    KnownHeaderName::{Accept, Vary},
+};
 
 /**
 trillium handler to serve static files from the filesystem
@@ -14,6 +18,7 @@ pub struct StaticFileHandler {
     fs_root: PathBuf,
     index_file: Option<String>,
     root_is_file: bool,
+    image_variants: bool,
     options: StaticOptions,
 }
 
@@ -67,6 +72,52 @@ impl StaticFileHandler {
         }
     }
 
+    /// serves the most preferred pre-generated variant of this image
+    /// that the client accepts, adding `Vary: Accept` if any variants
+    /// exist so that caches store each format separately
+    async fn send_image(&self, mut conn: Conn, path: PathBuf, file: File) -> Conn {
+        let mut variants = vec![];
+        for variant in ImageVariant::ALL {
+            let variant_path = variant.path_for(&path);
+            if fs::metadata(&variant_path)
+                .await
+                .is_ok_and(|metadata| metadata.is_file())
+            {
+                variants.push((variant, variant_path));
+            }
+        }
+
+        if !variants.is_empty() {
+            let vary = conn
+                .response_headers()
+                .get_str(Vary)
+                .map_or_else(|| String::from("Accept"), |vary| format!("{vary}, Accept"));
+            conn.response_headers_mut().insert(Vary, vary);
+
+            let accepted = conn
+                .request_headers()
+                .get_str(Accept)
+                .map(ImageVariant::accepted)
+                .unwrap_or_default();
+
+            for variant in accepted {
+                let Some((_, variant_path)) = variants.iter().find(|(v, _)| *v == variant) else {
+                    continue;
+                };
+
+                if let Ok(variant_file) = File::open(variant_path).await {
+                    log::trace!("serving {:?} for {:?}", variant_path, path);
+                    return conn
+                        .with_mime_from_path(variant_path)
+                        .send_file(variant_file)
+                        .await;
+                }
+            }
+        }
+
+        conn.with_mime_from_path(path).send_file(file).await
+    }
+
     /**
     builds a new StaticFileHandler
 
@@ -99,6 +150,7 @@ impl StaticFileHandler {
             fs_root,
             index_file: None,
             root_is_file: false,
+            image_variants: false,
             options: StaticOptions::default(),
         }
     }
@@ -141,6 +193,51 @@ impl StaticFileHandler {
         self.index_file = Some(file.to_string());
         self
     }
+
+    /**
+    serves pre-generated avif or webp variants of images to clients
+    that accept them
+
+    When an image such as `photo.jpg` is requested and the `Accept`
+    header explicitly includes `image/avif` or `image/webp`, a sibling
+    file named `photo.jpg.avif` or `photo.jpg.webp` is served in its
+    place if it exists, preferring avif unless the header gives webp a
+    higher quality value. Wildcard media ranges do not select a
+    variant. Responses for images that have any variants include
+    `Vary: Accept`, whether or not a variant was served.
+
+    This does not generate variants, which should be created ahead of
+    time by a build step.
+
+    ```
+    # #[cfg(not(unix))] fn main() {}
+    # #[cfg(unix)] fn main() {
+    # use trillium::Handler;
+    # trillium_testing::block_on(async {
+    use trillium_static::{StaticFileHandler, crate_relative_path};
+    use trillium_testing::prelude::*;
+
+    // tests/images contains photo.jpg, photo.jpg.avif, and photo.jpg.webp
+    let mut handler = StaticFileHandler::new(crate_relative_path!("tests/images"))
+        .with_image_variants();
+    # handler.init(&mut "testing".into()).await;
+
+    assert_ok!(
+        get("/photo.jpg")
+            .with_request_header("accept", "image/avif,image/webp")
+            .run_async(&handler)
+            .await,
+        "avif\n",
+        "content-type" => "image/avif",
+        "vary" => "Accept"
+    );
+    # }); }
+    ```
+    */
+    pub fn with_image_variants(mut self) -> Self {
+        self.image_variants = true;
+        self
+    }
 }
 
 #[async_trait]
@@ -169,18 +266,28 @@ impl Handler for StaticFileHandler {
 
     async fn run(&self, conn: Conn) -> Conn {
         match self.resolve(conn.path()).await {
-            Some(Record::File(path, file)) => conn.send_file(file).await.with_mime_from_path(path),
+            Some(Record::File(path, file)) if self.image_variants && is_image(&path) => {
+                self.send_image(conn, path, file).await
+            }
+
+            Some(Record::File(path, file)) => conn.with_mime_from_path(path).send_file(file).await,
 
             Some(Record::Dir(path)) => {
                 let index = conn_unwrap!(self.index_file.as_ref(), conn);
                 let path = path.join(index);
                 let file = conn_unwrap!(File::open(path.to_str().unwrap()).await.ok(), conn);
-                conn.send_file_with_options(file, &self.options)
+                conn.with_mime_from_path(path)
+                    .send_file_with_options(file, &self.options)
                     .await
-                    .with_mime_from_path(path)
             }
 
             _ => conn,
         }
     }
 }
+
+fn is_image(path: &Path) -> bool {
+    mime_guess::from_path(path)
+        .first()
+        .is_some_and(|mime| mime.type_() == mime_guess::mime::IMAGE)
+}
@@ -0,0 +1,61 @@
+use std::path::{Path, PathBuf};
+
+/// a pre-generated image format that can be served in place of the
+/// requested image, in order of preference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ImageVariant {
+    Avif,
+    Webp,
+}
+
+impl ImageVariant {
+    pub(crate) const ALL: [Self; 2] = [Self::Avif, Self::Webp];
+
+    fn media_type(self) -> &'static str {
+        match self {
+            Self::Avif => "image/avif",
+            Self::Webp => "image/webp",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Avif => "avif",
+            Self::Webp => "webp",
+        }
+    }
+
+    /// the sibling path for this variant, which appends the variant's
+    /// extension to the full file name, eg `photo.jpg.avif`
+    pub(crate) fn path_for(self, path: &Path) -> PathBuf {
+        let mut file_name = path.as_os_str().to_owned();
+        file_name.push(".");
+        file_name.push(self.extension());
+        file_name.into()
+    }
+
+    /// the variants that are explicitly accepted by this `Accept`
+    /// header, most preferred first. wildcards like `image/*` are
+    /// ignored, since browsers send them regardless of which formats
+    /// they support.
+    pub(crate) fn accepted(accept: &str) -> Vec<Self> {
+        let mut accepted = accept
+            .split(',')
+            .filter_map(|media_range| {
+                let mut iter = media_range.trim().split(';');
+                let media_type = iter.next()?.trim();
+                let variant = Self::ALL
+                    .into_iter()
+                    .find(|variant| variant.media_type().eq_ignore_ascii_case(media_type))?;
+                let q = iter
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .and_then(|q| q.parse::<f32>().map(|f| (f * 1000.0) as u16).ok())
+                    .unwrap_or(1000);
+                (q > 0).then_some((variant, q))
+            })
+            .collect::<Vec<_>>();
+
+        accepted.sort_by(|(variant_a, a), (variant_b, b)| b.cmp(a).then(variant_a.cmp(variant_b)));
+        accepted.into_iter().map(|(variant, _)| variant).collect()
+    }
+}
@@ -83,13 +83,18 @@ need to specify the runtime through feature flags.
 ## stability note
 
 Please note that this crate is fairly incomplete, while functional. It
-does not include any notion of range requests or cache headers. It
-serves all files from disk every time, with no in-memory caching.
+supports range requests, including multiple ranges and `If-Range`, but
+does not include any notion of cache headers. Pre-generated avif and
+webp variants of images can be served with
+[`StaticFileHandler::with_image_variants`]. It serves all files from
+disk every time, with no in-memory caching.
 */
 
 mod fs_shims;
 mod handler;
+mod image_variant;
 mod options;
+mod range;
 mod static_conn_ext;
 
 pub use handler::StaticFileHandler;
@@ -0,0 +1,288 @@
+use etag::EntityTag;
+use futures_lite::{ready, AsyncRead, AsyncSeek};
+use std::{
+    collections::{hash_map::RandomState, VecDeque},
+    fs::Metadata,
+    hash::{BuildHasher, Hasher},
+    io::{self, SeekFrom},
+    ops::RangeInclusive,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use trillium::{Conn, KnownHeaderName, Method};
+
+// requests for more ranges than this are served in full rather than as
+// a multipart response, since many small ranges are more expensive to
+// serve than the whole file
+const MAX_RANGES: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RangeRequest {
+    /// serve the whole file
+    Full,
+    /// none of the requested ranges can be satisfied
+    Unsatisfiable,
+    /// serve these ranges, which are sorted and do not overlap
+    Ranges(Vec<RangeInclusive<u64>>),
+}
+
+impl RangeRequest {
+    pub(crate) fn from_conn(conn: &Conn, metadata: &Metadata) -> Self {
+        if conn.method() != Method::Get {
+            return Self::Full;
+        }
+
+        let headers = conn.request_headers();
+        let Some(range) = headers.get_str(KnownHeaderName::Range) else {
+            return Self::Full;
+        };
+
+        match headers.get_str(KnownHeaderName::IfRange) {
+            Some(if_range) if !if_range_matches(if_range, metadata) => Self::Full,
+            _ => Self::parse(range, metadata.len()),
+        }
+    }
+
+    pub(crate) fn parse(header: &str, len: u64) -> Self {
+        let Some(specs) = header.trim().strip_prefix("bytes=") else {
+            return Self::Full;
+        };
+
+        let mut ranges = vec![];
+        for spec in specs.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let Some((start, end)) = spec.split_once('-') else {
+                return Self::Full;
+            };
+
+            let range = match (start.trim(), end.trim()) {
+                ("", suffix) => match suffix.parse::<u64>() {
+                    Ok(0) => None,
+                    Ok(suffix) if len > 0 => Some(len.saturating_sub(suffix)..=len - 1),
+                    Ok(_) => None,
+                    Err(_) => return Self::Full,
+                },
+
+                (start, "") => match start.parse::<u64>() {
+                    Ok(start) if start < len => Some(start..=len - 1),
+                    Ok(_) => None,
+                    Err(_) => return Self::Full,
+                },
+
+                (start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+                    (Ok(start), Ok(end)) if start > end => return Self::Full,
+                    (Ok(start), Ok(end)) if start < len => Some(start..=end.min(len - 1)),
+                    (Ok(_), Ok(_)) => None,
+                    _ => return Self::Full,
+                },
+            };
+
+            ranges.extend(range);
+        }
+
+        if ranges.is_empty() {
+            return Self::Unsatisfiable;
+        }
+
+        if ranges.len() > MAX_RANGES {
+            return Self::Full;
+        }
+
+        ranges.sort_by_key(|range| *range.start());
+        let mut coalesced: Vec<RangeInclusive<u64>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match coalesced.last_mut() {
+                Some(last) if *range.start() <= last.end().saturating_add(1) => {
+                    *last = *last.start()..=*range.end().max(last.end());
+                }
+                _ => coalesced.push(range),
+            }
+        }
+
+        Self::Ranges(coalesced)
+    }
+}
+
+// an if-range validator is either a strong entity tag or an http date
+// that must exactly match the representation for ranges to be served
+fn if_range_matches(if_range: &str, metadata: &Metadata) -> bool {
+    let if_range = if_range.trim();
+    if if_range.starts_with('"') || if_range.starts_with("W/") {
+        if_range
+            .parse::<EntityTag>()
+            .is_ok_and(|tag| tag.strong_eq(&EntityTag::from_file_meta(metadata)))
+    } else {
+        match (httpdate::parse_http_date(if_range), metadata.modified()) {
+            (Ok(if_range), Ok(modified)) => {
+                let seconds =
+                    |time: SystemTime| time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs());
+                seconds(if_range).is_some() && seconds(if_range) == seconds(modified)
+            }
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn content_range(range: &RangeInclusive<u64>, len: u64) -> String {
+    format!("bytes {}-{}/{}", range.start(), range.end(), len)
+}
+
+pub(crate) fn boundary() -> String {
+    let random = || RandomState::new().build_hasher().finish();
+    format!("{:016x}{:016x}", random(), random())
+}
+
+#[derive(Debug)]
+enum Segment {
+    Bytes(Vec<u8>),
+    File(RangeInclusive<u64>),
+}
+
+impl Segment {
+    fn len(&self) -> u64 {
+        match self {
+            Segment::Bytes(bytes) => bytes.len() as u64,
+            Segment::File(range) => range.end() - range.start() + 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum State {
+    Idle,
+    Bytes(Vec<u8>, usize),
+    Seeking(u64, u64),
+    Reading(u64),
+}
+
+/// an AsyncRead that streams the requested ranges of a file,
+/// interleaved with multipart boundaries and part headers if there is
+/// more than one range
+#[derive(Debug)]
+pub(crate) struct RangeBody<R> {
+    file: R,
+    segments: VecDeque<Segment>,
+    state: State,
+}
+
+impl<R> RangeBody<R> {
+    pub(crate) fn single(file: R, range: RangeInclusive<u64>) -> Self {
+        Self {
+            file,
+            segments: VecDeque::from([Segment::File(range)]),
+            state: State::Idle,
+        }
+    }
+
+    pub(crate) fn multipart(
+        file: R,
+        ranges: Vec<RangeInclusive<u64>>,
+        len: u64,
+        content_type: Option<&str>,
+        boundary: &str,
+    ) -> Self {
+        let mut segments = VecDeque::with_capacity(ranges.len() * 2 + 1);
+        for range in ranges {
+            let mut headers = format!("\r\n--{boundary}\r\n");
+            if let Some(content_type) = content_type {
+                headers.push_str(&format!("Content-Type: {content_type}\r\n"));
+            }
+            headers.push_str(&format!(
+                "Content-Range: {}\r\n\r\n",
+                content_range(&range, len)
+            ));
+            segments.push_back(Segment::Bytes(headers.into_bytes()));
+            segments.push_back(Segment::File(range));
+        }
+        segments.push_back(Segment::Bytes(
+            format!("\r\n--{boundary}--\r\n").into_bytes(),
+        ));
+
+        Self {
+            file,
+            segments,
+            state: State::Idle,
+        }
+    }
+
+    pub(crate) fn len(&self) -> u64 {
+        self.segments.iter().map(Segment::len).sum()
+    }
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncRead for RangeBody<R> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+        loop {
+            match &mut this.state {
+                State::Idle => match this.segments.pop_front() {
+                    None => return Poll::Ready(Ok(0)),
+                    Some(Segment::Bytes(bytes)) => this.state = State::Bytes(bytes, 0),
+                    Some(Segment::File(range)) => {
+                        this.state = State::Seeking(*range.start(), range.end() - range.start() + 1)
+                    }
+                },
+
+                State::Bytes(bytes, position) => {
+                    let remaining = &bytes[*position..];
+                    let len = remaining.len().min(buf.len());
+                    buf[..len].copy_from_slice(&remaining[..len]);
+                    *position += len;
+                    if *position == bytes.len() {
+                        this.state = State::Idle;
+                    }
+                    return Poll::Ready(Ok(len));
+                }
+
+                State::Seeking(start, len) => {
+                    let len = *len;
+                    ready!(Pin::new(&mut this.file).poll_seek(cx, SeekFrom::Start(*start)))?;
+                    this.state = State::Reading(len);
+                }
+
+                State::Reading(0) => this.state = State::Idle,
+
+                State::Reading(remaining) => {
+                    let max = usize::try_from(*remaining)
+                        .unwrap_or(usize::MAX)
+                        .min(buf.len());
+                    let bytes = ready!(Pin::new(&mut this.file).poll_read(cx, &mut buf[..max]))?;
+                    if bytes == 0 && max > 0 {
+                        return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+                    }
+                    *remaining -= bytes as u64;
+                    return Poll::Ready(Ok(bytes));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeRequest::{self, *};
+
+    #[test]
+    fn parse() {
+        let parse = |header| RangeRequest::parse(header, 100);
+        assert_eq!(parse("bytes=0-9"), Ranges(vec![0..=9]));
+        assert_eq!(parse("bytes=90-"), Ranges(vec![90..=99]));
+        assert_eq!(parse("bytes=-10"), Ranges(vec![90..=99]));
+        assert_eq!(parse("bytes=-1000"), Ranges(vec![0..=99]));
+        assert_eq!(parse("bytes=95-1000"), Ranges(vec![95..=99]));
+        assert_eq!(parse("bytes=0-0, -1"), Ranges(vec![0..=0, 99..=99]));
+        assert_eq!(parse("bytes=20-29,0-9"), Ranges(vec![0..=9, 20..=29]));
+        assert_eq!(parse("bytes=0-9,5-14,15-19"), Ranges(vec![0..=19]));
+        assert_eq!(parse("bytes=100-"), Unsatisfiable);
+        assert_eq!(parse("bytes=-0"), Unsatisfiable);
+        assert_eq!(parse("bytes=100-200, 150-"), Unsatisfiable);
+        assert_eq!(parse("bytes=10-5"), Full);
+        assert_eq!(parse("bytes=a-b"), Full);
+        assert_eq!(parse("items=0-9"), Full);
+        assert_eq!(RangeRequest::parse("bytes=0-9", 0), Unsatisfiable);
+    }
+}
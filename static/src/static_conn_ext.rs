@@ -1,9 +1,14 @@
-use crate::{fs_shims::File, options::StaticOptions};
+use crate::{
+    fs_shims::File,
+    options::StaticOptions,
+    range::{boundary, content_range, RangeBody, RangeRequest},
+};
 use etag::EntityTag;
 use std::path::Path;
 use trillium::{
     Body, Conn,
     KnownHeaderName::{self, ContentType},
+    Status,
 };
 
 /// conn extension trait to facilitate sending individual files and
@@ -20,6 +25,11 @@ pub trait StaticConnExt {
 
     /// Send the file at the provided path. Will send a 404 if the
     /// file cannot be resolved or if it is a directory.
+    ///
+    /// Range requests for GET are served as a `206 Partial Content`,
+    /// using a `multipart/byteranges` body if more than one range is
+    /// requested, and honoring `If-Range`. Any content-type set before
+    /// calling this is used for each part of a multipart response.
     async fn send_file_with_options(self, file: File, options: &StaticOptions) -> Self;
 
     /// Send the file at the provided path. Will send a 404 if the
@@ -32,7 +42,8 @@ pub trait StaticConnExt {
 
     /// Guess the mime type for this fs path using
     /// [`mime_guess`](https://docs.rs/mime_guess/) and set the
-    /// content-type header
+    /// content-type header. This does not replace the content-type
+    /// of a `multipart/byteranges` response.
     fn with_mime_from_path(self, path: impl AsRef<Path>) -> Self;
 }
 
@@ -55,9 +66,9 @@ impl StaticConnExt for Conn {
     ) -> Self {
         let path = path.as_ref().to_path_buf();
         let file = trillium::conn_try!(File::open(&path).await, self.with_status(404));
-        self.send_file_with_options(file, options)
+        self.with_mime_from_path(path)
+            .send_file_with_options(file, options)
             .await
-            .with_mime_from_path(path)
     }
 
     async fn send_file_with_options(mut self, file: File, options: &StaticOptions) -> Self {
@@ -78,13 +89,61 @@ impl StaticConnExt for Conn {
                 .try_insert(KnownHeaderName::Etag, etag.to_string());
         }
 
+        self.response_headers_mut()
+            .try_insert(KnownHeaderName::AcceptRanges, "bytes");
+
         #[cfg(feature = "tokio")]
         let file = async_compat::Compat::new(file);
 
-        self.ok(Body::new_streaming(file, Some(metadata.len())))
+        let len = metadata.len();
+        match RangeRequest::from_conn(&self, &metadata) {
+            RangeRequest::Full => self.ok(Body::new_streaming(file, Some(len))),
+
+            RangeRequest::Unsatisfiable => self
+                .with_response_header(KnownHeaderName::ContentRange, format!("bytes */{len}"))
+                .with_status(Status::RequestedRangeNotSatisfiable)
+                .halt(),
+
+            RangeRequest::Ranges(mut ranges) if ranges.len() == 1 => {
+                let range = ranges.pop().unwrap();
+                let content_range = content_range(&range, len);
+                let body = RangeBody::single(file, range);
+                let body_len = body.len();
+                self.with_response_header(KnownHeaderName::ContentRange, content_range)
+                    .with_status(Status::PartialContent)
+                    .with_body(Body::new_streaming(body, Some(body_len)))
+                    .halt()
+            }
+
+            RangeRequest::Ranges(ranges) => {
+                let boundary = boundary();
+                let content_type = self
+                    .response_headers_mut()
+                    .remove(ContentType)
+                    .and_then(|content_type| content_type.as_str().map(String::from));
+                let body =
+                    RangeBody::multipart(file, ranges, len, content_type.as_deref(), &boundary);
+                let body_len = body.len();
+                self.with_response_header(
+                    ContentType,
+                    format!("multipart/byteranges; boundary={boundary}"),
+                )
+                .with_status(Status::PartialContent)
+                .with_body(Body::new_streaming(body, Some(body_len)))
+                .halt()
+            }
+        }
     }
 
     fn with_mime_from_path(self, path: impl AsRef<Path>) -> Self {
+        if self
+            .response_headers()
+            .get_str(ContentType)
+            .is_some_and(|content_type| content_type.starts_with("multipart/byteranges"))
+        {
+            return self;
+        }
+
         if let Some(mime) = mime_guess::from_path(path).first() {
             use mime_guess::mime::{APPLICATION, HTML, JAVASCRIPT, TEXT};
             let is_text = matches!(
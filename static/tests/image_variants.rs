@@ -0,0 +1,92 @@
+#![cfg(unix)]
+use trillium::Handler;
+use trillium_static::{crate_relative_path, StaticFileHandler};
+use trillium_testing::prelude::*;
+
+async fn handler() -> StaticFileHandler {
+    let mut handler =
+        StaticFileHandler::new(crate_relative_path!("tests/images")).with_image_variants();
+    handler.init(&mut "testing".into()).await;
+    handler
+}
+
+#[test]
+fn serves_accepted_variants() {
+    block_on(async {
+        let handler = handler().await;
+        let with_accept = |accept| get("/photo.jpg").with_request_header("accept", accept);
+
+        assert_ok!(
+            with_accept("image/avif,image/webp,*/*").run_async(&handler).await,
+            "avif\n",
+            "content-type" => "image/avif",
+            "vary" => "Accept"
+        );
+
+        assert_ok!(
+            with_accept("image/webp,image/png,image/*;q=0.8").run_async(&handler).await,
+            "webp\n",
+            "content-type" => "image/webp",
+            "vary" => "Accept"
+        );
+
+        assert_ok!(
+            with_accept("image/avif;q=0.5, image/webp").run_async(&handler).await,
+            "webp\n",
+            "content-type" => "image/webp"
+        );
+
+        assert_ok!(
+            with_accept("image/avif;q=0, image/png").run_async(&handler).await,
+            "jpg\n",
+            "content-type" => "image/jpeg",
+            "vary" => "Accept"
+        );
+    });
+}
+
+#[test]
+fn wildcards_and_missing_accept_serve_the_original() {
+    block_on(async {
+        let handler = handler().await;
+
+        assert_ok!(
+            get("/photo.jpg")
+                .with_request_header("accept", "image/*,*/*;q=0.8")
+                .run_async(&handler)
+                .await,
+            "jpg\n",
+            "content-type" => "image/jpeg",
+            "vary" => "Accept"
+        );
+
+        assert_ok!(
+            get("/photo.jpg").run_async(&handler).await,
+            "jpg\n",
+            "vary" => "Accept"
+        );
+    });
+}
+
+#[test]
+fn images_without_variants_do_not_vary() {
+    block_on(async {
+        let handler = handler().await;
+        let mut conn = get("/logo.png")
+            .with_request_header("accept", "image/avif,image/webp")
+            .run_async(&handler)
+            .await;
+        assert_ok!(&mut conn, "png\n", "content-type" => "image/png");
+        assert_headers!(&conn, "vary" => None);
+
+        // variants are only served when enabled
+        let mut handler = StaticFileHandler::new(crate_relative_path!("tests/images"));
+        handler.init(&mut "testing".into()).await;
+        let mut conn = get("/photo.jpg")
+            .with_request_header("accept", "image/avif")
+            .run_async(&handler)
+            .await;
+        assert_ok!(&mut conn, "jpg\n");
+        assert_headers!(&conn, "vary" => None);
+    });
+}
@@ -0,0 +1,197 @@
+#![cfg(unix)]
+use trillium::Handler;
+use trillium_static::{crate_relative_path, StaticFileHandler};
+use trillium_testing::{prelude::*, TestConn};
+
+const PATH: &str = "/subdir_with_no_index/plaintext.txt";
+
+async fn handler() -> StaticFileHandler {
+    let mut handler = StaticFileHandler::new(crate_relative_path!("examples/files"));
+    handler.init(&mut "testing".into()).await;
+    handler
+}
+
+#[test]
+fn single_range() {
+    block_on(async {
+        let handler = handler().await;
+
+        assert_ok!(
+            get(PATH).run_async(&handler).await,
+            "plaintext file\n",
+            "accept-ranges" => "bytes"
+        );
+
+        assert_response!(
+            get(PATH)
+                .with_request_header("range", "bytes=0-8")
+                .run_async(&handler)
+                .await,
+            206,
+            "plaintext",
+            "content-range" => "bytes 0-8/15",
+            "content-length" => "9",
+            "content-type" => "text/plain; charset=utf-8"
+        );
+
+        assert_response!(
+            get(PATH)
+                .with_request_header("range", "bytes=-5")
+                .run_async(&handler)
+                .await,
+            206,
+            "file\n",
+            "content-range" => "bytes 10-14/15"
+        );
+
+        assert_response!(
+            get(PATH)
+                .with_request_header("range", "bytes=10-")
+                .run_async(&handler)
+                .await,
+            206,
+            "file\n",
+            "content-range" => "bytes 10-14/15"
+        );
+    });
+}
+
+#[test]
+fn unsatisfiable_and_ignored_ranges() {
+    block_on(async {
+        let handler = handler().await;
+
+        let conn = get(PATH)
+            .with_request_header("range", "bytes=15-")
+            .run_async(&handler)
+            .await;
+        assert_status!(&conn, 416);
+        assert_headers!(&conn, "content-range" => "bytes */15");
+
+        assert_ok!(
+            get(PATH)
+                .with_request_header("range", "bytes=9-0")
+                .run_async(&handler)
+                .await,
+            "plaintext file\n"
+        );
+
+        assert_status!(
+            TestConn::build("POST", PATH, ())
+                .with_request_header("range", "bytes=0-8")
+                .run_async(&handler)
+                .await,
+            200
+        );
+    });
+}
+
+#[test]
+fn multiple_ranges() {
+    block_on(async {
+        let handler = handler().await;
+
+        let mut conn = get(PATH)
+            .with_request_header("range", "bytes=10-13, 0-4")
+            .run_async(&handler)
+            .await;
+
+        assert_status!(&conn, 206);
+        let content_type = conn
+            .inner()
+            .response_headers()
+            .get_str("content-type")
+            .unwrap()
+            .to_string();
+        let boundary = content_type
+            .strip_prefix("multipart/byteranges; boundary=")
+            .unwrap();
+
+        let body = conn.take_response_body_string().unwrap();
+        assert_eq!(
+            body,
+            format!(
+                "\r\n--{boundary}\r\n\
+                 Content-Type: text/plain; charset=utf-8\r\n\
+                 Content-Range: bytes 0-4/15\r\n\r\n\
+                 plain\
+                 \r\n--{boundary}\r\n\
+                 Content-Type: text/plain; charset=utf-8\r\n\
+                 Content-Range: bytes 10-13/15\r\n\r\n\
+                 file\
+                 \r\n--{boundary}--\r\n"
+            )
+        );
+        let content_length = body.len().to_string();
+        assert_headers!(&conn, "content-length" => (&*content_length));
+
+        assert_response!(
+            get(PATH)
+                .with_request_header("range", "bytes=0-4, 3-8")
+                .run_async(&handler)
+                .await,
+            206,
+            "plaintext",
+            "content-range" => "bytes 0-8/15"
+        );
+    });
+}
+
+#[test]
+fn if_range() {
+    block_on(async {
+        let handler = handler().await;
+
+        let conn = get(PATH).run_async(&handler).await;
+        let last_modified = conn
+            .inner()
+            .response_headers()
+            .get_str("last-modified")
+            .unwrap()
+            .to_string();
+        let etag = conn
+            .inner()
+            .response_headers()
+            .get_str("etag")
+            .unwrap()
+            .to_string();
+
+        assert_response!(
+            get(PATH)
+                .with_request_header("range", "bytes=0-8")
+                .with_request_header("if-range", last_modified)
+                .run_async(&handler)
+                .await,
+            206,
+            "plaintext"
+        );
+
+        assert_ok!(
+            get(PATH)
+                .with_request_header("range", "bytes=0-8")
+                .with_request_header("if-range", "Wed, 21 Oct 2015 07:28:00 GMT")
+                .run_async(&handler)
+                .await,
+            "plaintext file\n"
+        );
+
+        // weak entity tags never match an if-range validator
+        assert_ok!(
+            get(PATH)
+                .with_request_header("range", "bytes=0-8")
+                .with_request_header("if-range", etag)
+                .run_async(&handler)
+                .await,
+            "plaintext file\n"
+        );
+
+        assert_ok!(
+            get(PATH)
+                .with_request_header("range", "bytes=0-8")
+                .with_request_header("if-range", "\"some-other-etag\"")
+                .run_async(&handler)
+                .await,
+            "plaintext file\n"
+        );
+    });
+}
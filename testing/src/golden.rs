@@ -0,0 +1,236 @@
+use crate::{block_on, TestTransport};
+use std::{
+    fmt::{self, Debug, Formatter},
+    fs,
+    path::{Path, PathBuf},
+};
+use trillium::{Handler, Info, KnownHeaderName};
+use trillium_http::{Conn as HttpConn, Error, HttpConfig, Stopper};
+
+type Normalizer = Box<dyn Fn(String) -> String + Send + Sync + 'static>;
+
+/**
+A golden-file conformance suite for trillium handlers
+
+Each `.request` file in the suite directory contains one or more raw
+http/1.1 requests. The requests are replayed through trillium-http
+against a handler and the raw response is compared to the
+corresponding `.response` file, or to an `.error` file if
+trillium-http returns an error.
+
+Because the exact bytes of each request matter, line breaks in these
+files are ignored and `\r` and `\n` are written as escapes. Responses
+are normalized before they are compared, which by default replaces the
+value of any `Date` header with `<date>`.
+
+To create or update the expected files, run the suite with the
+`TRILLIUM_GOLDEN_WRITE` environment variable set, and review the
+changes. To run a subset of the suite, set `TRILLIUM_GOLDEN_FILTER`
+to a substring of the file paths to run.
+
+```no_run
+use trillium::Conn;
+use trillium_testing::GoldenSuite;
+
+GoldenSuite::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+    .with_header_normalized("server", "<server>")
+    .run(|conn: Conn| async move { conn.ok("hello") });
+```
+
+A `.request` file might look like this:
+
+```text
+GET / HTTP/1.1\r\n
+Host: example.com\r\n
+Connection: close\r\n
+\r\n
+```
+*/
+pub struct GoldenSuite {
+    dir: PathBuf,
+    http_config: HttpConfig,
+    normalizers: Vec<Normalizer>,
+}
+
+impl Debug for GoldenSuite {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GoldenSuite")
+            .field("dir", &self.dir)
+            .field("http_config", &self.http_config)
+            .field("normalizers", &self.normalizers.len())
+            .finish()
+    }
+}
+
+impl GoldenSuite {
+    /// Builds a new suite from the `.request` files in this directory
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            http_config: HttpConfig::default(),
+            normalizers: vec![],
+        }
+        .with_header_normalized(KnownHeaderName::Date, "<date>")
+    }
+
+    /// Replays requests with this [`HttpConfig`] instead of the default
+    pub fn with_http_config(mut self, http_config: HttpConfig) -> Self {
+        self.http_config = http_config;
+        self
+    }
+
+    /// Replaces the value of every response header with this name
+    /// (case-insensitively) with the provided replacement before
+    /// comparison. This applies to each line of the output that starts
+    /// with the header name, so it also applies to every response of a
+    /// pipelined request file.
+    pub fn with_header_normalized(
+        self,
+        name: impl Into<trillium::HeaderName<'static>>,
+        replacement: impl Into<String>,
+    ) -> Self {
+        let prefix = format!("{}:", name.into()).to_ascii_lowercase();
+        let replacement = replacement.into();
+        self.with_normalizer(move |output| {
+            output
+                .split("\r\n")
+                .map(|line| match line.get(..prefix.len()) {
+                    Some(name) if name.eq_ignore_ascii_case(&prefix) => {
+                        format!("{name} {replacement}")
+                    }
+                    _ => line.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\r\n")
+        })
+    }
+
+    /// Applies an arbitrary normalization to each response before
+    /// comparison. Normalizations are applied in the order they are
+    /// added.
+    pub fn with_normalizer(
+        mut self,
+        normalizer: impl Fn(String) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.normalizers.push(Box::new(normalizer));
+        self
+    }
+
+    /**
+    Replays every request file against this handler
+
+    # Panics
+
+    Panics after all request files have been replayed if any response
+    did not match its expected file, or if the suite directory cannot
+    be read.
+    */
+    #[track_caller]
+    pub fn run(self, mut handler: impl Handler) {
+        let write = std::env::var_os("TRILLIUM_GOLDEN_WRITE").is_some();
+        let filter = std::env::var("TRILLIUM_GOLDEN_FILTER").unwrap_or_default();
+        let mut failures = vec![];
+
+        let mut request_files = fs::read_dir(&self.dir)
+            .unwrap_or_else(|e| panic!("could not read {}: {e}", self.dir.display()))
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "request")
+            })
+            .filter(|path| path.to_string_lossy().contains(&filter))
+            .collect::<Vec<_>>();
+        request_files.sort();
+
+        block_on(async {
+            let mut info = Info::from("golden");
+            handler.init(&mut info).await;
+
+            for request_file in &request_files {
+                let (actual, extension) = self.replay(request_file, &handler).await;
+                let expected_file = request_file.with_extension(extension);
+
+                if write {
+                    for stale in ["response", "error"] {
+                        let _ = fs::remove_file(request_file.with_extension(stale));
+                    }
+                    fs::write(&expected_file, escape(&actual)).unwrap_or_else(|e| {
+                        panic!("could not write {}: {e}", expected_file.display())
+                    });
+                    continue;
+                }
+
+                match fs::read_to_string(&expected_file) {
+                    Ok(expected) if unescape(&expected) == actual => {}
+                    Ok(expected) => failures.push(format!(
+                        "{} did not match\n--- expected ---\n{}\n--- actual ---\n{}",
+                        expected_file.display(),
+                        expected.trim_end(),
+                        escape(&actual).trim_end()
+                    )),
+                    Err(_) => failures.push(format!(
+                        "{} does not exist. the {extension} was:\n{}\nset TRILLIUM_GOLDEN_WRITE to write it",
+                        expected_file.display(),
+                        escape(&actual).trim_end()
+                    )),
+                }
+            }
+        });
+
+        assert!(
+            failures.is_empty(),
+            "{} of {} golden files did not match:\n\n{}",
+            failures.len(),
+            request_files.len(),
+            failures.join("\n\n")
+        );
+    }
+
+    async fn replay(&self, request_file: &Path, handler: &impl Handler) -> (String, &'static str) {
+        let request = fs::read_to_string(request_file)
+            .unwrap_or_else(|e| panic!("could not read {}: {e}", request_file.display()));
+
+        let (client, server) = TestTransport::new();
+        client.write_all(unescape(&request));
+        // the client sends nothing further, so the server sees the end of
+        // the input once it has read every request
+        client.write.close();
+
+        let result = HttpConn::map_with_config(
+            self.http_config,
+            server,
+            Stopper::new(),
+            |conn| async move {
+                let conn = handler.run(conn.into()).await;
+                handler.before_send(conn).await.into_inner()
+            },
+        )
+        .await;
+
+        let (output, extension) = match result {
+            Ok(_) | Err(Error::Closed) => (
+                String::from_utf8_lossy(&client.snapshot()).into_owned(),
+                "response",
+            ),
+            Err(e) => (e.to_string(), "error"),
+        };
+
+        let output = self
+            .normalizers
+            .iter()
+            .fold(output, |output, normalizer| normalizer(output));
+
+        (output, extension)
+    }
+}
+
+fn unescape(contents: &str) -> String {
+    contents
+        .replace(['\r', '\n'], "")
+        .replace("\\r", "\r")
+        .replace("\\n", "\n")
+}
+
+fn escape(contents: &str) -> String {
+    contents.replace('\r', "\\r").replace('\n', "\\n\n")
+}
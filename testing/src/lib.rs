@@ -59,6 +59,19 @@ trillium-testing = { version = "0.2", features = ["async-std"] }
 trillium-testing = { version = "0.2", features = ["smol"] }
 ```
 
+### Tls:
+
+The `tls` feature provides [`with_tls_server`] and
+[`TestCertificate`], which serve a handler over https with a
+certificate generated at test time and a client connector that trusts
+it. This is useful for testing behavior that depends on
+[`Conn::is_secure`](trillium::Conn::is_secure), like secure cookies.
+
+```toml
+[dev-dependencies]
+# ...
+trillium-testing = { version = "0.2", features = ["smol", "tls"] }
+```
 
 */
 
@@ -73,6 +86,12 @@ pub use test_transport::TestTransport;
 mod test_conn;
 pub use test_conn::TestConn;
 
+mod golden;
+pub use golden::GoldenSuite;
+
+mod multipart;
+pub use multipart::Multipart;
+
 pub mod methods;
 pub mod prelude {
     /*!
@@ -91,9 +110,18 @@ pub use trillium::{Method, Status};
 pub use url::Url;
 
 /// initialize a handler
+///
+/// # Panics
+///
+/// This will panic if any handler declares a dependency with
+/// [`Info::require`](trillium::Info::require) that is not provided by
+/// an earlier handler.
 pub fn init(handler: &mut impl trillium::Handler) {
-    let mut info = "testing".into();
-    block_on(handler.init(&mut info))
+    let mut info: trillium::Info = "testing".into();
+    block_on(handler.init(&mut info));
+    if let Some(missing_dependency) = info.missing_dependencies().first() {
+        panic!("{missing_dependency}");
+    }
 }
 
 // these exports are used by macros
@@ -237,6 +265,11 @@ pub use with_server::{with_server, with_transport};
 mod runtimeless;
 pub use runtimeless::{RuntimelessClientConfig, RuntimelessServer};
 
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(feature = "tls")]
+pub use tls::{with_tls_server, TestCertificate};
+
 /// a sponge Result
 pub type TestResult = Result<(), Box<dyn std::error::Error>>;
 
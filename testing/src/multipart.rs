@@ -0,0 +1,161 @@
+use std::fmt::{self, Debug, Formatter};
+
+/**
+A builder for a `multipart/form-data` request body, as a browser would
+submit for a form with `enctype="multipart/form-data"`. Use with
+[`TestConn::with_multipart`](crate::TestConn::with_multipart).
+
+```
+use trillium_testing::{prelude::*, Multipart};
+
+let mut conn = post("/upload").with_multipart(
+    Multipart::new()
+        .with_field("title", "greeting")
+        .with_file("upload", "hello.txt", "text/plain", "hello"),
+);
+
+let content_type = conn.request_headers().get_str("content-type").unwrap();
+assert!(content_type.starts_with("multipart/form-data; boundary="));
+assert!(conn.take_request_body_string().contains("filename=\"hello.txt\""));
+
+let multipart = Multipart::new()
+    .with_boundary("BOUNDARY")
+    .with_field("title", "greeting")
+    .with_file("upload", "hello.txt", "text/plain", "hello");
+
+assert_eq!(
+    String::from_utf8(multipart.into_body()).unwrap(),
+    "--BOUNDARY\r\n\
+     Content-Disposition: form-data; name=\"title\"\r\n\r\n\
+     greeting\r\n\
+     --BOUNDARY\r\n\
+     Content-Disposition: form-data; name=\"upload\"; filename=\"hello.txt\"\r\n\
+     Content-Type: text/plain\r\n\r\n\
+     hello\r\n\
+     --BOUNDARY--\r\n"
+);
+```
+*/
+#[derive(Clone)]
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<Part>,
+}
+
+#[derive(Clone)]
+struct Part {
+    name: String,
+    file: Option<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Debug for Multipart {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut list = f.debug_list();
+        for part in &self.parts {
+            list.entry(&part.name);
+        }
+        list.finish()
+    }
+}
+
+impl Default for Multipart {
+    fn default() -> Self {
+        Self {
+            boundary: format!("trillium-testing-boundary-{:016x}", fastrand::u64(..)),
+            parts: vec![],
+        }
+    }
+}
+
+impl Multipart {
+    /// constructs an empty multipart body with a random boundary
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// chainable setter to use a specific boundary instead of a random one
+    pub fn with_boundary(mut self, boundary: impl Into<String>) -> Self {
+        self.boundary = boundary.into();
+        self
+    }
+
+    /// chainable constructor to append a text field
+    pub fn with_field(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file: None,
+            body: value.into().into_bytes(),
+        });
+        self
+    }
+
+    /// chainable constructor to append a file with the provided file
+    /// name and content type
+    pub fn with_file(
+        mut self,
+        name: impl Into<String>,
+        file_name: impl Into<String>,
+        content_type: impl Into<String>,
+        body: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.parts.push(Part {
+            name: name.into(),
+            file: Some((file_name.into(), content_type.into())),
+            body: body.into(),
+        });
+        self
+    }
+
+    /// the boundary that separates parts of this body
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// the content-type header value for this body, including the boundary
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// serializes this multipart body
+    pub fn into_body(self) -> Vec<u8> {
+        let mut body = vec![];
+        for Part {
+            name,
+            file,
+            body: part_body,
+        } in self.parts
+        {
+            body.extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+            match file {
+                Some((file_name, content_type)) => body.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+                         Content-Type: {content_type}\r\n\r\n",
+                        escape(&name),
+                        escape(&file_name)
+                    )
+                    .as_bytes(),
+                ),
+                None => body.extend_from_slice(
+                    format!(
+                        "Content-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                        escape(&name)
+                    )
+                    .as_bytes(),
+                ),
+            }
+            body.extend_from_slice(&part_body);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body
+    }
+}
+
+// browsers percent-encode quotes and newlines in field and file names
+fn escape(name: &str) -> String {
+    name.replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
@@ -8,7 +8,7 @@ use std::{
     pin::Pin,
 };
 use trillium::Info;
-use trillium_server_common::{Acceptor, Config, ConfigExt, Connector, Server};
+use trillium_server_common::{Acceptor, Config, ConfigExt, Connector, Server, StartupError};
 use url::Url;
 
 type Servers = Lazy<DashMap<(String, u16), (Sender<TestTransport>, Receiver<TestTransport>)>>;
@@ -34,7 +34,7 @@ impl Server for RuntimelessServer {
         })
     }
 
-    fn build_listener<A>(config: &Config<Self, A>) -> Self
+    fn try_build_listener<A>(config: &Config<Self, A>) -> std::result::Result<Self, StartupError>
     where
         A: Acceptor<Self::Transport>,
     {
@@ -55,11 +55,11 @@ impl Server for RuntimelessServer {
 
         let (_, channel) = entry.value();
 
-        Self {
+        Ok(Self {
             host,
             channel: channel.clone(),
             port,
-        }
+        })
     }
 
     fn info(&self) -> Info {
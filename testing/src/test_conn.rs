@@ -1,9 +1,10 @@
+use crate::Multipart;
 use std::{
     fmt::Debug,
     net::IpAddr,
     ops::{Deref, DerefMut},
 };
-use trillium::{Conn, Handler, HeaderName, HeaderValues, Method};
+use trillium::{Conn, Handler, HeaderName, HeaderValues, KnownHeaderName, Method, Status};
 use trillium_http::{Conn as HttpConn, Synthetic};
 
 type SyntheticConn = HttpConn<Synthetic>;
@@ -79,6 +80,56 @@ impl TestConn {
         Self(inner.into())
     }
 
+    /**
+    chainable constructor to submit form fields as an
+    `application/x-www-form-urlencoded` request body, as a browser
+    would for a form without an `enctype`. this replaces the request
+    body and content-type.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = post("/").with_form([("name", "trillium"), ("greeting", "hello & welcome")]);
+    assert_eq!(
+        conn.request_headers().get_str("content-type"),
+        Some("application/x-www-form-urlencoded")
+    );
+    assert_eq!(
+        conn.take_request_body_string(),
+        "name=trillium&greeting=hello+%26+welcome"
+    );
+    ```
+    */
+    pub fn with_form<K, V>(self, fields: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(fields)
+            .finish();
+        self.with_request_body(body)
+            .with_content_type("application/x-www-form-urlencoded")
+    }
+
+    /**
+    chainable constructor to submit a [`Multipart`] form as the
+    request body. this replaces the request body and content-type.
+    See [`Multipart`] for an example.
+    */
+    pub fn with_multipart(self, multipart: Multipart) -> Self {
+        let content_type = multipart.content_type();
+        self.with_request_body(multipart.into_body())
+            .with_content_type(content_type)
+    }
+
+    fn with_content_type(self, content_type: impl Into<HeaderValues>) -> Self {
+        let mut inner: SyntheticConn = self.into();
+        inner
+            .request_headers_mut()
+            .insert(KnownHeaderName::ContentType, content_type);
+        Self(inner.into())
+    }
+
     /// sets the peer ip for this test conn
     pub fn with_peer_ip(mut self, ip: IpAddr) -> Self {
         self.inner_mut().set_peer_ip(Some(ip));
@@ -140,6 +191,12 @@ impl TestConn {
         let conn = handler.run(self.into()).await;
         let mut conn = handler.before_send(conn).await;
         conn.inner_mut().finalize_headers();
+        // as when sending, the body is framed but not transmitted
+        if conn.method() == Method::Head
+            || matches!(conn.status(), Some(Status::NotModified | Status::NoContent))
+        {
+            conn.inner_mut().take_response_body();
+        }
         Self(conn)
     }
 
@@ -0,0 +1,20 @@
+use trillium::{Conn, Handler};
+use trillium_testing::GoldenSuite;
+
+fn handler() -> impl Handler {
+    |mut conn: Conn| async move {
+        if conn.path() == "/echo" {
+            let body = conn.request_body_string().await.unwrap();
+            conn.ok(body)
+        } else {
+            conn.ok("hello")
+        }
+    }
+}
+
+#[test]
+fn golden() {
+    GoldenSuite::new(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden"))
+        .with_header_normalized("server", "<server>")
+        .run(handler());
+}
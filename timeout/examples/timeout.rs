@@ -0,0 +1,19 @@
+use async_io::Timer;
+use std::time::Duration;
+use trillium::Conn;
+use trillium_timeout::Timeout;
+
+async fn handler(conn: Conn) -> Conn {
+    let millis = conn.querystring().parse().unwrap_or(0);
+    Timer::after(Duration::from_millis(millis)).await;
+    conn.ok(format!("slept for {millis}ms"))
+}
+
+fn main() {
+    env_logger::init();
+    // try http://localhost:8080/?500 and http://localhost:8080/?1500
+    trillium_smol::run((
+        trillium_logger::logger(),
+        Timeout::new(Duration::from_secs(1), handler),
+    ));
+}
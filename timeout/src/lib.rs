@@ -0,0 +1,297 @@
+/*!
+# Trillium handler to limit the time spent responding to a request
+
+This crate provides [`Timeout`], a handler that races a wrapped
+handler against a deadline. If the wrapped handler has not returned
+a conn before the deadline elapses, its future is dropped (cancelling
+any in-progress work) and a `504 Gateway Timeout` response is sent
+instead. The timeout response can be customized with
+[`Timeout::with_response`].
+
+To declare timeouts inline in a handler tree, [`TimeoutHandlerExt`]
+adds a `with_timeout` combinator to every handler:
+
+```
+use std::time::Duration;
+use trillium::{Conn, Status};
+use trillium_timeout::TimeoutHandlerExt;
+
+async fn slow(conn: Conn) -> Conn {
+    async_io::Timer::after(Duration::from_secs(5)).await;
+    conn.ok("this will not be sent")
+}
+
+let handler = (
+    |conn: Conn| async move { conn.with_response_header("x-section", "reports") },
+    slow.with_timeout(Duration::from_millis(10), Status::ServiceUnavailable),
+);
+
+use trillium_testing::prelude::*;
+assert_status!(get("/").on(&handler), 503);
+```
+
+```
+use std::time::Duration;
+use trillium::Conn;
+use trillium_timeout::Timeout;
+
+async fn slow(conn: Conn) -> Conn {
+    async_io::Timer::after(Duration::from_secs(5)).await;
+    conn.ok("this will not be sent")
+}
+
+let handler = Timeout::new(Duration::from_millis(10), slow);
+
+use trillium_testing::prelude::*;
+assert_status!(get("/").on(&handler), 504);
+```
+
+## Conn ownership
+
+Because the wrapped handler takes ownership of the [`Conn`], a
+timed-out conn cannot be recovered. When a timeout occurs, a new conn
+is built from the original request's method, path, headers, peer ip,
+and security, and the original transport is returned to it. State
+that was set on the conn before the timeout handler, as well as
+anything that downstream handlers did before being cancelled, is not
+available to the timeout response. Since the request body may have
+been partially read, the connection is always closed after a
+timeout response is sent.
+
+## Runtimes
+
+The deadline uses [`async_io::Timer`], which drives itself and is
+therefore usable with any of the trillium runtime adapters.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod shared_transport;
+
+use async_io::Timer;
+use futures_lite::future;
+use shared_transport::SharedTransport;
+use std::{borrow::Cow, mem, net::IpAddr, time::Duration};
+use trillium::{
+    async_trait, Conn, Handler, Headers, Info, KnownHeaderName, Method, StateSet, Status, Upgrade,
+};
+use trillium_http::transport::BoxedTransport;
+
+/**
+Trillium handler that cancels a wrapped handler after a deadline
+
+See crate-level docs for an explanation
+*/
+#[derive(Debug)]
+pub struct Timeout<H> {
+    duration: Duration,
+    handler: H,
+    response: Box<dyn Handler>,
+}
+
+/// Alias for [`Timeout::new`]
+pub fn timeout<H: Handler>(duration: Duration, handler: H) -> Timeout<H> {
+    Timeout::new(duration, handler)
+}
+
+impl<H: Handler> Timeout<H> {
+    /// Constructs a new Timeout handler that will run the provided
+    /// handler for at most `duration`
+    pub fn new(duration: Duration, handler: H) -> Self {
+        Self {
+            duration,
+            handler,
+            response: Box::new(Status::GatewayTimeout),
+        }
+    }
+
+    /**
+    Replaces the default `504 Gateway Timeout` response with a custom
+    handler. This handler will be run on the conn that is constructed
+    after a timeout, as described in the crate-level docs.
+
+    ```
+    use std::time::Duration;
+    use trillium::{Conn, Status};
+    use trillium_timeout::Timeout;
+
+    let handler = Timeout::new(Duration::from_millis(10), |conn: Conn| async move {
+        async_io::Timer::after(Duration::from_secs(5)).await;
+        conn
+    })
+    .with_response(|conn: Conn| async move {
+        conn.with_status(Status::ServiceUnavailable)
+            .with_body("please try again later")
+    });
+
+    use trillium_testing::prelude::*;
+    assert_response!(
+        get("/").on(&handler),
+        Status::ServiceUnavailable,
+        "please try again later",
+        "connection" => "close"
+    );
+    ```
+    */
+    pub fn with_response(mut self, response: impl Handler) -> Self {
+        self.response = Box::new(response);
+        self
+    }
+
+    /// Sets the duration after which the wrapped handler is cancelled
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    /// Returns the duration after which the wrapped handler is cancelled
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+/**
+Extension trait that adds a timeout combinator to every [`Handler`]
+
+This is a shorthand for [`Timeout::new`] followed by
+[`Timeout::with_response`].
+*/
+pub trait TimeoutHandlerExt: Handler + Sized {
+    /**
+    Runs this handler for at most `duration`. If it has not returned a
+    conn before then, its future is dropped and `fallback` is run on a
+    replacement conn, as described in the crate-level docs.
+    */
+    fn with_timeout(self, duration: Duration, fallback: impl Handler) -> Timeout<Self> {
+        Timeout::new(duration, self).with_response(fallback)
+    }
+}
+
+impl<H: Handler> TimeoutHandlerExt for H {}
+
+/// The parts of a request that are retained in order to build a
+/// replacement conn if the wrapped handler times out
+#[derive(Debug)]
+struct RequestSnapshot {
+    method: Method,
+    path: String,
+    request_headers: Headers,
+    secure: bool,
+    peer_ip: Option<IpAddr>,
+}
+
+impl RequestSnapshot {
+    fn new(conn: &Conn) -> Self {
+        Self {
+            method: conn.method(),
+            path: conn.inner().path_and_query().to_string(),
+            request_headers: conn.request_headers().clone(),
+            secure: conn.is_secure(),
+            peer_ip: conn.peer_ip(),
+        }
+    }
+
+    fn into_conn(self, transport: BoxedTransport) -> Conn {
+        let mut inner = trillium_http::Conn::new_synthetic(self.method, self.path, ());
+        *inner.request_headers_mut() = self.request_headers;
+        inner.set_secure(self.secure);
+        inner.set_peer_ip(self.peer_ip);
+        let mut conn = Conn::from(inner);
+        *conn.inner_mut().transport_mut() = transport;
+        conn.with_response_header(KnownHeaderName::Connection, "close")
+            .with_state(TimedOut)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TimedOut;
+
+#[async_trait]
+impl<H: Handler> Handler for Timeout<H> {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        let snapshot = RequestSnapshot::new(&conn);
+        let placeholder = BoxedTransport::new(trillium_http::Synthetic::from(()));
+        let original = mem::replace(conn.inner_mut().transport_mut(), placeholder);
+        let shared = SharedTransport::new(original);
+        *conn.inner_mut().transport_mut() = BoxedTransport::new(shared.clone());
+
+        let result = future::or(async { Some(self.handler.run(conn).await) }, async {
+            Timer::after(self.duration).await;
+            None
+        })
+        .await;
+
+        let original = shared
+            .take()
+            .expect("the shared transport is only reclaimed by this handler");
+
+        match result {
+            Some(mut conn) => {
+                *conn.inner_mut().transport_mut() = original;
+                conn
+            }
+
+            None => {
+                log::warn!(
+                    "{} {} timed out after {:?}",
+                    snapshot.method,
+                    snapshot.path,
+                    self.duration
+                );
+                let conn = snapshot.into_conn(original);
+                self.response.run(conn).await.halt()
+            }
+        }
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        self.handler.init(info).await;
+        self.response.init(info).await;
+    }
+
+    async fn before_send(&self, conn: Conn) -> Conn {
+        if conn.timed_out() {
+            self.response.before_send(conn).await
+        } else {
+            self.handler.before_send(conn).await
+        }
+    }
+
+    fn has_upgrade(&self, upgrade: &Upgrade) -> bool {
+        self.handler.has_upgrade(upgrade)
+    }
+
+    async fn upgrade(&self, upgrade: Upgrade) {
+        self.handler.upgrade(upgrade).await;
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        format!("Timeout({:?}, {})", self.duration, self.handler.name()).into()
+    }
+}
+
+/// Extension trait to determine whether a conn was produced by a [`Timeout`]
+pub trait TimeoutConnExt {
+    /// Returns true if this conn was constructed after a timeout
+    /// cancelled the handler wrapped by a [`Timeout`]. This is useful
+    /// for loggers and other handlers that run after the Timeout or
+    /// in `before_send`.
+    fn timed_out(&self) -> bool;
+}
+
+impl<ConnLike> TimeoutConnExt for ConnLike
+where
+    ConnLike: AsRef<StateSet>,
+{
+    fn timed_out(&self) -> bool {
+        self.as_ref().get::<TimedOut>().is_some()
+    }
+}
@@ -0,0 +1,126 @@
+use futures_lite::io::{AsyncRead, AsyncWrite};
+use std::{
+    io::{Error, ErrorKind, Result},
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll},
+    time::Duration,
+};
+use trillium_http::transport::{
+    BoxedTransport, PeerCertificates, PeerCredentials, Transport, TransportKind,
+};
+
+/**
+A transport that is lent to the downstream handler while the
+[`Timeout`](crate::Timeout) retains the ability to take the original
+transport back if the downstream future is dropped.
+*/
+#[derive(Debug, Clone)]
+pub(crate) struct SharedTransport(Arc<Mutex<Option<BoxedTransport>>>);
+
+impl SharedTransport {
+    pub(crate) fn new(transport: BoxedTransport) -> Self {
+        Self(Arc::new(Mutex::new(Some(transport))))
+    }
+
+    /// takes the original transport back out. After this is called,
+    /// any other clone of this SharedTransport will return
+    /// [`ErrorKind::NotConnected`] for all io.
+    pub(crate) fn take(&self) -> Option<BoxedTransport> {
+        self.lock().take()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<BoxedTransport>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn with_transport<T>(
+        &self,
+        f: impl FnOnce(Pin<&mut BoxedTransport>) -> Poll<Result<T>>,
+    ) -> Poll<Result<T>> {
+        match &mut *self.lock() {
+            Some(transport) => f(Pin::new(transport)),
+            None => Poll::Ready(Err(not_connected())),
+        }
+    }
+}
+
+fn not_connected() -> Error {
+    Error::new(
+        ErrorKind::NotConnected,
+        "transport was reclaimed by trillium_timeout::Timeout",
+    )
+}
+
+impl AsyncRead for SharedTransport {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<Result<usize>> {
+        self.with_transport(|t| t.poll_read(cx, buf))
+    }
+}
+
+impl AsyncWrite for SharedTransport {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<Result<usize>> {
+        self.with_transport(|t| t.poll_write(cx, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.with_transport(|t| t.poll_flush(cx))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        self.with_transport(|t| t.poll_close(cx))
+    }
+}
+
+impl Transport for SharedTransport {
+    fn set_linger(&mut self, linger: Option<Duration>) -> Result<()> {
+        self.lock()
+            .as_mut()
+            .ok_or_else(not_connected)?
+            .set_linger(linger)
+    }
+
+    fn set_nodelay(&mut self, nodelay: bool) -> Result<()> {
+        self.lock()
+            .as_mut()
+            .ok_or_else(not_connected)?
+            .set_nodelay(nodelay)
+    }
+
+    fn set_ip_ttl(&mut self, ttl: u32) -> Result<()> {
+        self.lock()
+            .as_mut()
+            .ok_or_else(not_connected)?
+            .set_ip_ttl(ttl)
+    }
+
+    fn peer_addr(&self) -> Result<Option<SocketAddr>> {
+        self.lock().as_ref().ok_or_else(not_connected)?.peer_addr()
+    }
+
+    fn local_addr(&self) -> Result<Option<SocketAddr>> {
+        self.lock().as_ref().ok_or_else(not_connected)?.local_addr()
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        self.lock()
+            .as_ref()
+            .map_or(TransportKind::Other, |transport| transport.transport_kind())
+    }
+
+    fn peer_credentials(&self) -> Result<Option<PeerCredentials>> {
+        self.lock()
+            .as_ref()
+            .ok_or_else(not_connected)?
+            .peer_credentials()
+    }
+
+    fn peer_certificates(&self) -> Option<PeerCertificates> {
+        self.lock().as_ref()?.peer_certificates()
+    }
+}
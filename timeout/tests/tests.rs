@@ -0,0 +1,127 @@
+use async_io::Timer;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use test_harness::test;
+use trillium::{Conn, Status};
+use trillium_client::Client;
+use trillium_testing::{harness, prelude::*, ServerConnector, TestResult};
+use trillium_timeout::{Timeout, TimeoutConnExt, TimeoutHandlerExt};
+
+async fn slow(conn: Conn) -> Conn {
+    Timer::after(Duration::from_secs(5)).await;
+    conn.ok("slow")
+}
+
+async fn fast(conn: Conn) -> Conn {
+    conn.ok("fast")
+}
+
+#[test]
+fn completes_before_deadline() {
+    let handler = Timeout::new(Duration::from_secs(5), fast);
+    let conn = get("/").on(&handler);
+    assert!(!conn.timed_out());
+    assert_ok!(conn, "fast");
+}
+
+#[test]
+fn times_out() {
+    let handler = Timeout::new(Duration::from_millis(10), slow);
+    let conn = get("/")
+        .with_request_header("x-request", "value")
+        .with_peer_ip([10, 1, 1, 1].into())
+        .secure()
+        .on(&handler);
+
+    assert!(conn.timed_out());
+    assert!(conn.is_halted());
+    assert!(conn.is_secure());
+    assert_eq!(conn.peer_ip(), Some([10, 1, 1, 1].into()));
+    assert_eq!(conn.request_headers().get_str("x-request"), Some("value"));
+    assert_response!(conn, Status::GatewayTimeout, "", "connection" => "close");
+}
+
+#[test]
+fn custom_response() {
+    let handler =
+        Timeout::new(Duration::from_millis(10), slow).with_response(|conn: Conn| async move {
+            conn.with_status(Status::ServiceUnavailable)
+                .with_body("try again")
+        });
+    assert_response!(
+        get("/").on(&handler),
+        Status::ServiceUnavailable,
+        "try again"
+    );
+}
+
+#[test]
+fn with_timeout_drops_the_inner_future() {
+    struct SetOnDrop(Arc<AtomicBool>);
+    impl Drop for SetOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::SeqCst);
+        }
+    }
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let inner = {
+        let dropped = dropped.clone();
+        move |conn: Conn| {
+            let guard = SetOnDrop(dropped.clone());
+            async move {
+                let _guard = guard;
+                slow(conn).await
+            }
+        }
+    };
+
+    let handler = inner.with_timeout(Duration::from_millis(10), "fallback");
+    let conn = get("/").on(&handler);
+    assert!(conn.timed_out());
+    assert!(dropped.load(Ordering::SeqCst));
+    assert_ok!(conn, "fallback");
+
+    let handler = fast.with_timeout(Duration::from_secs(5), "fallback");
+    assert_ok!(get("/").on(&handler), "fast");
+}
+
+#[test]
+fn preserves_path_and_query() {
+    let handler = (
+        Timeout::new(Duration::from_millis(10), slow),
+        |conn: Conn| async move { conn.ok("not reached") },
+    );
+
+    let conn = get("/some/path?query=string").on(&handler);
+    assert_eq!(conn.path(), "/some/path");
+    assert_eq!(conn.querystring(), "query=string");
+    assert_status!(conn, Status::GatewayTimeout);
+}
+
+#[test(harness)]
+async fn over_a_transport() -> TestResult {
+    let client = Client::new(ServerConnector::new(Timeout::new(
+        Duration::from_millis(50),
+        |conn: Conn| async move {
+            if conn.path() == "/slow" {
+                slow(conn).await
+            } else {
+                fast(conn).await
+            }
+        },
+    )));
+
+    let mut conn = client.get("http://localhost/fast").await?;
+    assert_eq!(conn.status(), Some(Status::Ok));
+    assert_eq!(conn.response_body().read_string().await?, "fast");
+
+    let conn = client.get("http://localhost/slow").await?;
+    assert_eq!(conn.status(), Some(Status::GatewayTimeout));
+    assert_eq!(conn.response_headers().get_str("connection"), Some("close"));
+
+    Ok(())
+}
@@ -45,6 +45,14 @@ impl Server for TokioServer {
         self.0.local_addr().unwrap().into()
     }
 
+    fn try_clone(&self) -> Result<Self> {
+        #[cfg(unix)]
+        let owned = std::os::fd::AsFd::as_fd(&self.0).try_clone_to_owned()?;
+        #[cfg(windows)]
+        let owned = std::os::windows::io::AsSocket::as_socket(&self.0).try_clone_to_owned()?;
+        Ok(Self(std::net::TcpListener::from(owned).try_into()?))
+    }
+
     fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         spawn(fut);
     }
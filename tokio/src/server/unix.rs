@@ -79,6 +79,21 @@ impl Server for TokioServer {
         }
     }
 
+    fn try_clone(&self) -> Result<Self> {
+        use std::os::fd::AsFd;
+        match &self.0 {
+            Tcp(t) => {
+                let tcp = std::net::TcpListener::from(t.as_fd().try_clone_to_owned()?);
+                Ok(Self(Tcp(tcp.try_into()?)))
+            }
+
+            Unix(u) => {
+                let unix = std::os::unix::net::UnixListener::from(u.as_fd().try_clone_to_owned()?);
+                Ok(Self(Unix(unix.try_into()?)))
+            }
+        }
+    }
+
     fn spawn(fut: impl Future<Output = ()> + Send + 'static) {
         spawn(fut);
     }
@@ -2,7 +2,7 @@ use async_compat::Compat;
 use std::{io::Result, net::SocketAddr};
 use tokio::net::{TcpStream, ToSocketAddrs};
 use trillium_macros::{AsyncRead, AsyncWrite};
-use trillium_server_common::{AsyncRead, AsyncWrite, Transport};
+use trillium_server_common::{AsyncRead, AsyncWrite, Transport, TransportKind};
 
 /// A transport newtype for tokio
 #[derive(Debug, Clone, AsyncRead, AsyncWrite)]
@@ -35,6 +35,14 @@ impl Transport for TokioTransport<Compat<TcpStream>> {
         self.0.get_ref().peer_addr().map(Some)
     }
 
+    fn local_addr(&self) -> Result<Option<SocketAddr>> {
+        self.0.get_ref().local_addr().map(Some)
+    }
+
+    fn transport_kind(&self) -> TransportKind {
+        TransportKind::Tcp
+    }
+
     fn set_ip_ttl(&mut self, ttl: u32) -> Result<()> {
         self.0.get_mut().set_ttl(ttl)
     }
@@ -45,4 +53,12 @@ impl Transport for TokioTransport<Compat<TcpStream>> {
 }
 
 #[cfg(unix)]
-impl Transport for TokioTransport<Compat<tokio::net::UnixStream>> {}
+impl Transport for TokioTransport<Compat<tokio::net::UnixStream>> {
+    fn transport_kind(&self) -> TransportKind {
+        TransportKind::Unix
+    }
+
+    fn peer_credentials(&self) -> Result<Option<trillium_server_common::PeerCredentials>> {
+        trillium_server_common::unix_peer_credentials(self.0.get_ref())
+    }
+}
@@ -18,3 +18,29 @@ fn smoke() {
     let app = app();
     assert_ok!(get("/").on(&app), "successfully spawned a task");
 }
+
+#[test]
+fn accept_loops() {
+    use std::io::{Read, Write};
+
+    trillium_tokio::block_on(async move {
+        let handle = trillium_tokio::config()
+            .with_host("127.0.0.1")
+            .with_port(0)
+            .with_accept_loops(4)
+            .without_signals()
+            .spawn("ok");
+
+        let addr = handle.socket_addr().await.unwrap();
+        for _ in 0..16 {
+            let mut stream = std::net::TcpStream::connect(addr).unwrap();
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                .unwrap();
+            let mut response = String::new();
+            stream.read_to_string(&mut response).unwrap();
+            assert!(response.ends_with("\r\n\r\nok"));
+        }
+        handle.stop().await;
+    });
+}
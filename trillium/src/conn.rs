@@ -1,10 +1,11 @@
+use crate::{RequestScope, Spawner};
 use std::{
     fmt::{self, Debug, Formatter},
     future::Future,
-    net::IpAddr,
+    net::{IpAddr, SocketAddr},
 };
 use trillium_http::{
-    transport::{BoxedTransport, Transport},
+    transport::{BoxedTransport, PeerCertificates, PeerCredentials, Transport, TransportKind},
     Body, HeaderName, HeaderValues, Headers, Method, ReceivedBody, StateSet, Status,
 };
 
@@ -337,6 +338,30 @@ impl Conn {
         self.request_body().await.read_string().await
     }
 
+    /**
+    Reads the request body into memory and retains it, so that later
+    handlers can read it again. See
+    [`trillium_http::Conn::buffer_request_body`] for details.
+
+    ```
+    use trillium_testing::prelude::*;
+    let mut conn = post("/").with_request_body("request body").on(&());
+
+    # trillium_testing::block_on(async {
+    assert_eq!(conn.buffer_request_body(1024).await.unwrap(), b"request body");
+    assert_eq!(conn.request_body_string().await.unwrap(), "request body");
+    # });
+    ```
+
+    # Errors
+
+    This will return an error if the body is longer than `max_len`,
+    cannot be read, or has already been read.
+    */
+    pub async fn buffer_request_body(&mut self, max_len: u64) -> trillium_http::Result<&[u8]> {
+        self.inner.buffer_request_body(max_len).await
+    }
+
     /**
     if there is a response body for this conn and it has a known
     fixed length, it is returned from this function
@@ -577,6 +602,79 @@ impl Conn {
         self.inner_mut().set_peer_ip(peer_ip);
     }
 
+    /// retrieves the local address that the client connected to, if
+    /// available. For connections accepted with a proxy protocol
+    /// acceptor, this is the destination address sent by the proxy.
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.inner().transport().local_addr().ok().flatten()
+    }
+
+    /// the kind of socket that this conn was received on. tls
+    /// transports report the kind of the underlying socket, and
+    /// conns that were not received from a server, such as in tests,
+    /// report [`TransportKind::Other`].
+    pub fn transport_kind(&self) -> TransportKind {
+        self.inner().transport().transport_kind()
+    }
+
+    /// the application protocol negotiated with tls alpn, such as
+    /// `b"h2"` or `b"http/1.1"`, if any
+    pub fn negotiated_alpn(&self) -> Option<&[u8]> {
+        self.inner().transport().negotiated_alpn()
+    }
+
+    /// retrieves the credentials of the process on the other end of
+    /// this conn's transport, if available. this is currently only
+    /// supported for unix domain sockets on linux and android.
+    pub fn peer_credentials(&self) -> Option<PeerCredentials> {
+        self.inner().transport().peer_credentials().ok().flatten()
+    }
+
+    /// retrieves the certificate chain presented by the client, if
+    /// this conn's transport is tls and a client certificate was
+    /// requested and received. See
+    /// [`trillium_rustls::RustlsAcceptor`](https://docs.rs/trillium-rustls/latest/trillium_rustls/struct.RustlsAcceptor.html#client-certificates)
+    pub fn peer_certificates(&self) -> Option<PeerCertificates> {
+        self.inner().transport().peer_certificates()
+    }
+
+    /// sets the maximum length of the request body for this conn,
+    /// overriding the server's
+    /// [`HttpConfig`](crate::HttpConfig). This must be called before
+    /// the request body is read.
+    pub fn set_received_body_max_len(&mut self, max_len: u64) {
+        self.inner_mut().set_received_body_max_len(max_len);
+    }
+
+    /// chainable setter for the maximum length of the request body
+    /// for this conn. See [`Conn::set_received_body_max_len`].
+    ///
+    /// ```
+    /// use trillium_testing::prelude::*;
+    /// let handler = |conn: Conn| async move {
+    ///     let mut conn = conn.with_received_body_max_len(5);
+    ///     match conn.request_body_string().await {
+    ///         Ok(body) => conn.ok(body),
+    ///         Err(_) => conn.with_status(413),
+    ///     }
+    /// };
+    ///
+    /// assert_ok!(post("/").with_request_body("hello").on(&handler), "hello");
+    /// assert_status!(post("/").with_request_body("hello!").on(&handler), 413);
+    /// ```
+    #[must_use]
+    pub fn with_received_body_max_len(mut self, max_len: u64) -> Self {
+        self.set_received_body_max_len(max_len);
+        self
+    }
+
+    /// retrieves the bytes of the request head exactly as received,
+    /// if the server was configured to retain them. See
+    /// [`HttpConfig`](crate::HttpConfig) for details.
+    pub fn raw_head(&self) -> Option<&crate::RawHead> {
+        self.inner().raw_head()
+    }
+
     /// for router implementations. pushes a route segment onto the path
     pub fn push_path(&mut self, path: String) {
         self.path.push(path);
@@ -619,7 +717,11 @@ impl Conn {
     where
         Fut: Future + Send + 'a,
     {
-        self.inner_mut().cancel_on_disconnect(fut).await
+        let output = self.inner_mut().cancel_on_disconnect(fut).await;
+        if output.is_none() {
+            self.cancel_request_scope();
+        }
+        output
     }
 
     /// Check if the transport is connected by testing attempting to read from the transport
@@ -642,7 +744,121 @@ impl Conn {
     /// }
     /// ```
     pub async fn is_disconnected(&mut self) -> bool {
-        self.inner_mut().is_disconnected().await
+        let disconnected = self.inner_mut().is_disconnected().await;
+        if disconnected {
+            self.cancel_request_scope();
+        }
+        disconnected
+    }
+
+    /// Waits until the client disconnects from this conn's transport, and then cancels this conn's
+    /// [`RequestScope`]. If the client remains connected, this future never resolves, so it is
+    /// intended to be raced against other work that does not borrow the conn, such as a
+    /// long-running report or a stream of server-sent events that is produced elsewhere.
+    ///
+    /// The use of this method is not advised if your connected http client employs pipelining
+    /// (rarely seen in the wild), as it will buffer an unbounded number of requests
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use trillium::Conn;
+    /// # async fn generate_report() -> String { String::from("report") }
+    /// async fn handler(mut conn: Conn) -> Conn {
+    ///     let (sender, receiver) = async_channel::bounded(1);
+    ///     conn.spawn_scoped(async move {
+    ///         let _ = sender.send(generate_report().await).await;
+    ///     });
+    ///
+    ///     let report = futures_lite::future::or(
+    ///         async { receiver.recv().await.ok() },
+    ///         async {
+    ///             conn.on_client_disconnect().await;
+    ///             None
+    ///         },
+    ///     )
+    ///     .await;
+    ///
+    ///     match report {
+    ///         Some(report) => conn.ok(report),
+    ///         None => conn,
+    ///     }
+    /// }
+    /// ```
+    pub async fn on_client_disconnect(&mut self) {
+        self.inner_mut().on_client_disconnect().await;
+        self.cancel_request_scope();
+    }
+
+    /// Returns the [`RequestScope`] for this conn, creating it if this is the first call.
+    ///
+    /// The scope is cancelled when the response has been sent, when the conn is dropped without
+    /// a response, or when [`Conn::cancel_on_disconnect`], [`Conn::is_disconnected`] or
+    /// [`Conn::on_client_disconnect`] finds that the client has disconnected.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use trillium::Conn;
+    /// async fn handler(mut conn: Conn) -> Conn {
+    ///     let scope = conn.request_scope();
+    ///     assert!(!scope.is_cancelled());
+    ///     let output = scope.run(async { "done" }).await;
+    ///     conn.ok(output.unwrap_or("cancelled"))
+    /// }
+    /// ```
+    pub fn request_scope(&mut self) -> RequestScope {
+        if let Some(scope) = self.state::<RequestScope>() {
+            return scope.clone();
+        }
+
+        let scope = RequestScope::default();
+        self.insert_state(scope.clone());
+        self.inner_mut().after_send({
+            let scope = scope.clone();
+            move |_| scope.cancel()
+        });
+        scope
+    }
+
+    /// Spawns a task on the runtime that is serving this conn, which is cancelled along with
+    /// this conn's [`RequestScope`]. Unlike [`Conn::cancel_on_disconnect`], the task runs
+    /// concurrently with the rest of the handler and does not need to be awaited.
+    ///
+    /// This requires a [`Spawner`], which the runtime adapters provide for every conn they
+    /// serve. Returns false without running the future if no spawner is available, as is the
+    /// case for conns built with `trillium-testing`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use trillium::Conn;
+    /// # async fn warm_cache() {}
+    /// async fn handler(mut conn: Conn) -> Conn {
+    ///     conn.spawn_scoped(warm_cache());
+    ///     conn.ok("ok!")
+    /// }
+    /// ```
+    pub fn spawn_scoped<Fut>(&mut self, fut: Fut) -> bool
+    where
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let Some(spawner) = self.state::<Spawner>().cloned() else {
+            log::error!("no spawner available for Conn::spawn_scoped");
+            return false;
+        };
+
+        let scope = self.request_scope();
+        spawner.spawn(async move {
+            scope.run(fut).await;
+        });
+        true
+    }
+
+    fn cancel_request_scope(&self) {
+        if let Some(scope) = self.state::<RequestScope>() {
+            scope.cancel();
+        }
     }
 }
 
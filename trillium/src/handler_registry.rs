@@ -0,0 +1,297 @@
+use crate::{async_trait, Conn, Handler, Info, Upgrade};
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Debug, Display, Formatter},
+};
+
+/// A type-erased [`Handler`], as stored in a [`HandlerRegistry`]
+pub type BoxedHandler = Box<dyn Handler>;
+
+/**
+# A pipeline of named handlers that can be assembled at runtime
+
+Handler tuples are the usual way to compose a trillium application,
+but their order is fixed at compile time. A `HandlerRegistry` holds
+an ordered list of [`BoxedHandler`]s, each identified by a unique
+name, which can be inserted, removed, replaced and reordered until
+the registry is passed to a server. This is useful for plugin
+architectures and for applications that build their middleware stack
+from configuration.
+
+Once the server starts, the registry behaves exactly like a `Vec` of
+its handlers: they are initialized and run in order, `run` stops at
+the first handler that halts the conn, and `before_send` runs in
+reverse order.
+
+```
+use trillium::{Conn, HandlerRegistry};
+
+let mut registry = HandlerRegistry::new()
+    .with("powered-by", |conn: Conn| async move {
+        conn.with_response_header("x-powered-by", "trillium")
+    })
+    .with("app", "hello");
+
+registry
+    .insert_before("app", "cache-control", |conn: Conn| async move {
+        conn.with_response_header("cache-control", "no-cache")
+    })
+    .unwrap();
+registry.remove("powered-by");
+
+assert_eq!(registry.names().collect::<Vec<_>>(), ["cache-control", "app"]);
+
+use trillium_testing::prelude::*;
+assert_ok!(
+    get("/").on(&registry),
+    "hello",
+    "cache-control" => "no-cache"
+);
+```
+*/
+#[derive(Debug, Default)]
+pub struct HandlerRegistry {
+    handlers: Vec<(Cow<'static, str>, BoxedHandler)>,
+}
+
+/**
+An error returned by [`HandlerRegistry`] when a handler is referred
+to by a name that has not been registered
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownHandler(String);
+
+impl UnknownHandler {
+    /// The name that was not found in the registry
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for UnknownHandler {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "no handler named {} has been registered", self.0)
+    }
+}
+
+impl Error for UnknownHandler {}
+
+impl HandlerRegistry {
+    /// Builds a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chainable constructor to register a handler with
+    /// [`HandlerRegistry::insert`]
+    #[must_use]
+    pub fn with(mut self, name: impl Into<Cow<'static, str>>, handler: impl Handler) -> Self {
+        self.insert(name, handler);
+        self
+    }
+
+    /// Registers a handler after all other handlers. If a handler was
+    /// already registered with this name, it is replaced in its
+    /// current position and returned.
+    pub fn insert(
+        &mut self,
+        name: impl Into<Cow<'static, str>>,
+        handler: impl Handler,
+    ) -> Option<BoxedHandler> {
+        let name = name.into();
+        let handler = Box::new(handler) as BoxedHandler;
+        if let Some(index) = self.position(&name) {
+            Some(std::mem::replace(&mut self.handlers[index].1, handler))
+        } else {
+            self.handlers.push((name, handler));
+            None
+        }
+    }
+
+    /// Registers a handler immediately before the handler named
+    /// `existing`. Any handler that was already registered with this
+    /// name is removed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnknownHandler`] if no handler is named `existing`,
+    /// in which case the registry is not modified.
+    pub fn insert_before(
+        &mut self,
+        existing: &str,
+        name: impl Into<Cow<'static, str>>,
+        handler: impl Handler,
+    ) -> Result<(), UnknownHandler> {
+        self.insert_relative(existing, name.into(), Box::new(handler), 0)
+    }
+
+    /// Registers a handler immediately after the handler named
+    /// `existing`. Any handler that was already registered with this
+    /// name is removed first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnknownHandler`] if no handler is named `existing`,
+    /// in which case the registry is not modified.
+    pub fn insert_after(
+        &mut self,
+        existing: &str,
+        name: impl Into<Cow<'static, str>>,
+        handler: impl Handler,
+    ) -> Result<(), UnknownHandler> {
+        self.insert_relative(existing, name.into(), Box::new(handler), 1)
+    }
+
+    /// Moves the handler named `name` immediately before the handler
+    /// named `existing`
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnknownHandler`] if either handler has not been
+    /// registered, in which case the registry is not modified.
+    pub fn move_before(&mut self, name: &str, existing: &str) -> Result<(), UnknownHandler> {
+        self.move_relative(name, existing, 0)
+    }
+
+    /// Moves the handler named `name` immediately after the handler
+    /// named `existing`
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`UnknownHandler`] if either handler has not been
+    /// registered, in which case the registry is not modified.
+    pub fn move_after(&mut self, name: &str, existing: &str) -> Result<(), UnknownHandler> {
+        self.move_relative(name, existing, 1)
+    }
+
+    /// Removes and returns the handler registered with this name
+    pub fn remove(&mut self, name: &str) -> Option<BoxedHandler> {
+        self.position(name)
+            .map(|index| self.handlers.remove(index).1)
+    }
+
+    /// Borrows the handler registered with this name
+    pub fn get(&self, name: &str) -> Option<&BoxedHandler> {
+        self.position(name).map(|index| &self.handlers[index].1)
+    }
+
+    /// Mutably borrows the handler registered with this name
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut BoxedHandler> {
+        self.position(name).map(|index| &mut self.handlers[index].1)
+    }
+
+    /// Whether a handler has been registered with this name
+    pub fn contains(&self, name: &str) -> bool {
+        self.position(name).is_some()
+    }
+
+    /// The registered names, in the order that their handlers run
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.handlers.iter().map(|(name, _)| name.as_ref())
+    }
+
+    /// The number of registered handlers
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Whether no handlers have been registered
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.handlers.iter().position(|(n, _)| n == name)
+    }
+
+    fn insert_relative(
+        &mut self,
+        existing: &str,
+        name: Cow<'static, str>,
+        handler: BoxedHandler,
+        offset: usize,
+    ) -> Result<(), UnknownHandler> {
+        let mut index = self
+            .position(existing)
+            .ok_or_else(|| UnknownHandler(existing.to_string()))?
+            + offset;
+
+        if let Some(previous) = self.position(&name) {
+            self.handlers.remove(previous);
+            if previous < index {
+                index -= 1;
+            }
+        }
+
+        self.handlers.insert(index, (name, handler));
+        Ok(())
+    }
+
+    fn move_relative(
+        &mut self,
+        name: &str,
+        existing: &str,
+        offset: usize,
+    ) -> Result<(), UnknownHandler> {
+        let mut index = self
+            .position(existing)
+            .ok_or_else(|| UnknownHandler(existing.to_string()))?
+            + offset;
+        let previous = self
+            .position(name)
+            .ok_or_else(|| UnknownHandler(name.to_string()))?;
+
+        let entry = self.handlers.remove(previous);
+        if previous < index {
+            index -= 1;
+        }
+        self.handlers.insert(index, entry);
+        Ok(())
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = &BoxedHandler> {
+        self.handlers.iter().map(|(_, handler)| handler)
+    }
+}
+
+#[async_trait]
+impl Handler for HandlerRegistry {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        for (name, handler) in &self.handlers {
+            log::debug!("running {name}");
+            conn = handler.run(conn).await;
+            if conn.is_halted() {
+                break;
+            }
+        }
+        conn
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        for (_, handler) in &mut self.handlers {
+            handler.init(info).await;
+        }
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        for handler in self.iter().rev() {
+            conn = handler.before_send(conn).await;
+        }
+        conn
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        self.names().collect::<Vec<_>>().join(",").into()
+    }
+
+    fn has_upgrade(&self, upgrade: &Upgrade) -> bool {
+        self.iter().any(|handler| handler.has_upgrade(upgrade))
+    }
+
+    async fn upgrade(&self, upgrade: Upgrade) {
+        if let Some(handler) = self.iter().find(|handler| handler.has_upgrade(&upgrade)) {
+            handler.upgrade(upgrade).await;
+        }
+    }
+}
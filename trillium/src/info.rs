@@ -1,7 +1,12 @@
 use std::{
+    any::{type_name, TypeId},
+    borrow::Cow,
+    collections::BTreeSet,
+    error::Error,
     fmt::{Display, Formatter, Result},
     net::SocketAddr,
 };
+use trillium_http::transport::TransportKind;
 
 const DEFAULT_SERVER_DESCRIPTION: &str = concat!("trillium v", env!("CARGO_PKG_VERSION"));
 
@@ -10,6 +15,60 @@ This struct represents information about the currently connected
 server.
 
 It is passed to [`Handler::init`](crate::Handler::init) and the [`Init`](crate::Init) handler.
+
+## Dependencies between handlers
+
+Because handlers are initialized in the same order that they run,
+`Info` can also be used to declare that a handler depends on state
+that is inserted into the conn by some earlier handler. A handler
+that inserts state calls [`Info::provide`] in its init, and a handler
+that relies on it calls [`Info::require`]. Any requirement that was
+not provided by an earlier handler is recorded as a
+[`MissingDependency`], which servers report at startup instead of
+failing while handling a request.
+
+```
+use trillium::{async_trait, Conn, Handler, Info};
+
+struct CurrentUser(String);
+
+struct Authenticate;
+#[async_trait]
+impl Handler for Authenticate {
+    async fn run(&self, conn: Conn) -> Conn {
+        conn.with_state(CurrentUser("jbr".into()))
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        info.provide::<CurrentUser>();
+    }
+}
+
+struct Greet;
+#[async_trait]
+impl Handler for Greet {
+    async fn run(&self, conn: Conn) -> Conn {
+        let name = conn.state::<CurrentUser>().unwrap().0.clone();
+        conn.ok(format!("hello, {name}"))
+    }
+
+    async fn init(&mut self, info: &mut Info) {
+        info.require::<CurrentUser>("Greet");
+    }
+}
+
+# trillium_testing::block_on(async {
+let mut info = Info::default();
+(Authenticate, Greet).init(&mut info).await;
+assert!(info.missing_dependencies().is_empty());
+
+let mut info = Info::default();
+Greet.init(&mut info).await;
+let missing = &info.missing_dependencies()[0];
+assert_eq!(missing.handler(), "Greet");
+assert!(missing.dependency().ends_with("CurrentUser"));
+# });
+```
 */
 
 #[derive(Debug, Clone)]
@@ -17,14 +76,56 @@ pub struct Info {
     server_description: String,
     listener_description: String,
     tcp_socket_addr: Option<SocketAddr>,
+    transport_kind: TransportKind,
+    secure: bool,
+    provided: BTreeSet<TypeId>,
+    missing_dependencies: Vec<MissingDependency>,
+}
+
+/**
+A dependency declared with [`Info::require`] that was not
+provided by an earlier handler with [`Info::provide`]
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MissingDependency {
+    handler: Cow<'static, str>,
+    dependency: &'static str,
+}
+
+impl MissingDependency {
+    /// The name of the handler that declared this dependency
+    pub fn handler(&self) -> &str {
+        &self.handler
+    }
+
+    /// The type name of the missing dependency
+    pub const fn dependency(&self) -> &'static str {
+        self.dependency
+    }
+}
+
+impl Display for MissingDependency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        write!(
+            f,
+            "{} requires {}, which is not provided by any earlier handler",
+            self.handler, self.dependency
+        )
+    }
 }
 
+impl Error for MissingDependency {}
+
 impl Default for Info {
     fn default() -> Self {
         Self {
             server_description: DEFAULT_SERVER_DESCRIPTION.into(),
             listener_description: String::new(),
             tcp_socket_addr: None,
+            transport_kind: TransportKind::Other,
+            secure: false,
+            provided: BTreeSet::new(),
+            missing_dependencies: Vec::new(),
         }
     }
 }
@@ -63,6 +164,26 @@ impl Info {
         self.tcp_socket_addr.as_ref()
     }
 
+    /// Returns the kind of socket the server's primary listener is
+    /// bound to. Each conn's [`Conn::transport_kind`](crate::Conn::transport_kind)
+    /// describes the connection it was received on, which may differ
+    /// for servers with additional listeners.
+    pub const fn transport_kind(&self) -> TransportKind {
+        self.transport_kind
+    }
+
+    /// Returns true if the server's primary listener accepts
+    /// connections with a tls acceptor
+    pub const fn is_secure(&self) -> bool {
+        self.secure
+    }
+
+    /// sets whether the server's primary listener is secure. This is
+    /// intended for server implementations.
+    pub const fn set_secure(&mut self, secure: bool) {
+        self.secure = secure;
+    }
+
     /// obtain a mutable borrow of the server description, suitable
     /// for appending information or replacing it
     pub fn server_description_mut(&mut self) -> &mut String {
@@ -74,6 +195,39 @@ impl Info {
     pub fn listener_description_mut(&mut self) -> &mut String {
         &mut self.listener_description
     }
+
+    /// Declare that the handler being initialized inserts `T` into
+    /// the state of each conn, satisfying any later
+    /// [`Info::require`] for `T`
+    pub fn provide<T: 'static>(&mut self) {
+        self.provided.insert(TypeId::of::<T>());
+    }
+
+    /// Returns true if an earlier handler has called [`Info::provide`]
+    /// for `T`
+    pub fn provides<T: 'static>(&self) -> bool {
+        self.provided.contains(&TypeId::of::<T>())
+    }
+
+    /// Declare that the handler being initialized, identified by
+    /// `handler` in error messages, depends on an earlier handler
+    /// inserting `T` into the state of each conn. If no earlier
+    /// handler has called [`Info::provide`] for `T`, a
+    /// [`MissingDependency`] is recorded.
+    pub fn require<T: 'static>(&mut self, handler: impl Into<Cow<'static, str>>) {
+        if !self.provides::<T>() {
+            self.missing_dependencies.push(MissingDependency {
+                handler: handler.into(),
+                dependency: type_name::<T>(),
+            });
+        }
+    }
+
+    /// Returns all dependencies that were required but not provided
+    /// while initializing the handler tree
+    pub fn missing_dependencies(&self) -> &[MissingDependency] {
+        &self.missing_dependencies
+    }
 }
 
 impl From<&str> for Info {
@@ -92,6 +246,8 @@ impl From<SocketAddr> for Info {
             server_description: String::from(DEFAULT_SERVER_DESCRIPTION),
             listener_description: socket_addr.to_string(),
             tcp_socket_addr: Some(socket_addr),
+            transport_kind: TransportKind::Tcp,
+            ..Self::default()
         }
     }
 }
@@ -102,7 +258,8 @@ impl From<std::os::unix::net::SocketAddr> for Info {
         Self {
             server_description: String::from(DEFAULT_SERVER_DESCRIPTION),
             listener_description: format!("{s:?}"),
-            tcp_socket_addr: None,
+            transport_kind: TransportKind::Unix,
+            ..Self::default()
         }
     }
 }
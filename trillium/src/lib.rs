@@ -41,9 +41,11 @@ pub use async_trait::async_trait;
 
 pub use trillium_http::{
     Body, Error, HeaderName, HeaderValue, HeaderValues, Headers, HttpConfig, KnownHeaderName,
-    Method, StateSet, Status, Version,
+    Method, RawHead, StateSet, Status, Version,
 };
 
+pub use trillium_http::transport::{PeerCertificates, PeerCredentials, TransportKind};
+
 /**
 # A HTTP protocol upgrade
 
@@ -58,7 +60,16 @@ mod macros;
 pub use log;
 
 mod info;
-pub use info::Info;
+pub use info::{Info, MissingDependency};
 
 mod init;
 pub use init::{init, Init};
+
+mod handler_registry;
+pub use handler_registry::{BoxedHandler, HandlerRegistry, UnknownHandler};
+
+mod request_scope;
+pub use request_scope::{RequestScope, Spawner};
+
+mod transform;
+pub use transform::{Transform, Transformer};
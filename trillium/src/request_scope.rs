@@ -0,0 +1,79 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+};
+use trillium_http::Stopper;
+
+/**
+# A cancellation scope that lasts as long as a request
+
+A `RequestScope` is obtained with [`Conn::request_scope`](crate::Conn::request_scope), and is
+cancelled when the response has been sent, when the conn is dropped without sending a response,
+or when the client is found to have disconnected with
+[`Conn::cancel_on_disconnect`](crate::Conn::cancel_on_disconnect),
+[`Conn::is_disconnected`](crate::Conn::is_disconnected) or
+[`Conn::on_client_disconnect`](crate::Conn::on_client_disconnect).
+
+Any future passed to [`RequestScope::run`] is dropped at the next await point after the scope is
+cancelled, so work started on behalf of a request does not outlive it. Because a `RequestScope`
+is cheap to clone and does not borrow the conn, it can be moved into spawned tasks. To spawn
+a task on the current runtime within the scope, see
+[`Conn::spawn_scoped`](crate::Conn::spawn_scoped).
+*/
+#[derive(Clone, Debug, Default)]
+pub struct RequestScope(Stopper);
+
+impl RequestScope {
+    /// Runs this future until it completes or the scope is cancelled, whichever happens
+    /// first. Returns `None` if the scope was cancelled.
+    pub fn run<Fut>(&self, fut: Fut) -> impl Future<Output = Option<Fut::Output>> + Send
+    where
+        Fut: Future + Send,
+    {
+        self.0.stop_future(fut)
+    }
+
+    /// Cancels this scope and every future running within it
+    pub fn cancel(&self) {
+        self.0.stop();
+    }
+
+    /// Whether this scope has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.is_stopped()
+    }
+}
+
+type SpawnFn = dyn Fn(Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static;
+
+/**
+# Spawns tasks on the runtime that is serving a conn
+
+Runtime adapters insert a `Spawner` into the state of every conn they serve, which is used by
+[`Conn::spawn_scoped`](crate::Conn::spawn_scoped). Handlers are not usually expected to construct
+or interact with this type directly.
+*/
+#[derive(Clone)]
+pub struct Spawner(Arc<SpawnFn>);
+
+impl Debug for Spawner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Spawner").field(&"..").finish()
+    }
+}
+
+impl Spawner {
+    /// Builds a new `Spawner` from a function that spawns a boxed future
+    pub fn new(
+        spawn: impl Fn(Pin<Box<dyn Future<Output = ()> + Send + 'static>>) + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(spawn))
+    }
+
+    /// Spawns this future
+    pub fn spawn(&self, fut: impl Future<Output = ()> + Send + 'static) {
+        (self.0)(Box::pin(fut));
+    }
+}
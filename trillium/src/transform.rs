@@ -0,0 +1,136 @@
+use crate::{async_trait, Conn, Handler};
+use std::{
+    borrow::Cow,
+    fmt::{self, Debug, Formatter},
+};
+
+/**
+# A lightweight trait for request and response mutation
+
+Many middlewares only need to modify the request before later
+handlers run, or modify the response before it is sent, such as
+adding or removing headers. Implementing [`Handler`] for these is more
+involved than necessary, and it is easy to accidentally halt the conn
+or to respond from `run` when the intent was to modify the response in
+`before_send`.
+
+A `Transform` has two synchronous hooks, both of which default to
+doing nothing:
+
+* [`Transform::request`] runs in the position of [`Handler::run`],
+  before the handlers that follow it. It does not run if an earlier
+  handler halted the conn.
+* [`Transform::response`] runs in the position of
+  [`Handler::before_send`], after all handlers have run. It runs for
+  every conn, including conns halted before this transform was
+  reached, and transforms in a handler tuple see the response in
+  reverse order, as with any other `before_send`.
+
+Transforms cannot halt a conn. If [`Transform::request`] halts the
+conn, the halt is reverted. To use a transform as a handler, convert
+it into a [`Transformer`] with [`Transform::into_handler`] or
+[`Transformer::new`].
+
+```
+use trillium::{Conn, Transform};
+
+struct PoweredBy;
+impl Transform for PoweredBy {
+    fn request(&self, conn: &mut Conn) {
+        conn.request_headers_mut().remove("x-internal-only");
+    }
+
+    fn response(&self, conn: &mut Conn) {
+        conn.response_headers_mut()
+            .insert("x-powered-by", "trillium");
+    }
+}
+
+let handler = (PoweredBy.into_handler(), |conn: Conn| async move {
+    let internal = conn.request_headers().has_header("x-internal-only");
+    conn.ok(format!("internal: {internal}"))
+});
+
+use trillium_testing::prelude::*;
+assert_ok!(
+    get("/")
+        .with_request_header("x-internal-only", "true")
+        .on(&handler),
+    "internal: false",
+    "x-powered-by" => "trillium"
+);
+```
+*/
+pub trait Transform: Send + Sync + 'static {
+    /// Modify the request before the handlers that follow this transform.
+    #[allow(unused_variables)]
+    fn request(&self, conn: &mut Conn) {}
+
+    /// Modify the response before it is sent.
+    #[allow(unused_variables)]
+    fn response(&self, conn: &mut Conn) {}
+
+    /// A human-readable name for this transform, used in the name of
+    /// the [`Transformer`] handler.
+    fn name(&self) -> Cow<'static, str> {
+        std::any::type_name::<Self>().into()
+    }
+
+    /// Convert this transform into a [`Handler`].
+    fn into_handler(self) -> Transformer<Self>
+    where
+        Self: Sized,
+    {
+        Transformer::new(self)
+    }
+}
+
+/**
+# A [`Handler`] that applies a [`Transform`]
+
+See [`Transform`] for the order in which transforms run.
+*/
+pub struct Transformer<T>(T);
+
+impl<T: Transform> Transformer<T> {
+    /// Constructs a new Transformer handler from a [`Transform`]
+    pub const fn new(transform: T) -> Self {
+        Self(transform)
+    }
+
+    /// Borrow the transform
+    pub const fn transform(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Transform> From<T> for Transformer<T> {
+    fn from(transform: T) -> Self {
+        Self::new(transform)
+    }
+}
+
+impl<T: Transform> Debug for Transformer<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Transformer").field(&self.0.name()).finish()
+    }
+}
+
+#[async_trait]
+impl<T: Transform> Handler for Transformer<T> {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        let halted = conn.is_halted();
+        self.0.request(&mut conn);
+        conn.set_halted(halted);
+        conn
+    }
+
+    async fn before_send(&self, mut conn: Conn) -> Conn {
+        self.0.response(&mut conn);
+        conn
+    }
+
+    fn name(&self) -> Cow<'static, str> {
+        format!("Transformer({})", self.0.name()).into()
+    }
+}
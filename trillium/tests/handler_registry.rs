@@ -0,0 +1,79 @@
+use trillium::{Conn, HandlerRegistry};
+use trillium_testing::prelude::*;
+
+fn append(name: &'static str) -> impl trillium::Handler {
+    move |mut conn: Conn| async move {
+        let body = conn.take_state::<String>().unwrap_or_default() + name;
+        conn.with_body(body.clone()).with_state(body)
+    }
+}
+
+fn registry() -> HandlerRegistry {
+    HandlerRegistry::new()
+        .with("a", append("a"))
+        .with("b", append("b"))
+        .with("c", append("c"))
+}
+
+fn names(registry: &HandlerRegistry) -> Vec<&str> {
+    registry.names().collect()
+}
+
+#[test]
+fn runs_in_order() {
+    let registry = registry();
+    assert_eq!(registry.len(), 3);
+    assert_body!(get("/").on(&registry), "abc");
+}
+
+#[test]
+fn insert_replaces_in_place() {
+    let mut registry = registry();
+    assert!(registry.insert("b", append("B")).is_some());
+    assert_eq!(names(&registry), ["a", "b", "c"]);
+    assert_body!(get("/").on(&registry), "aBc");
+}
+
+#[test]
+fn insert_relative() {
+    let mut registry = registry();
+    registry.insert_before("a", "first", append("1")).unwrap();
+    registry.insert_after("c", "last", append("2")).unwrap();
+    registry.insert_after("a", "c", append("C")).unwrap();
+    assert_eq!(names(&registry), ["first", "a", "c", "b", "last"]);
+    assert_body!(get("/").on(&registry), "1aCb2");
+
+    let error = registry
+        .insert_before("missing", "d", append("d"))
+        .unwrap_err();
+    assert_eq!(error.name(), "missing");
+    assert!(!registry.contains("d"));
+}
+
+#[test]
+fn reorder_and_remove() {
+    let mut registry = registry();
+    registry.move_before("c", "a").unwrap();
+    assert_eq!(names(&registry), ["c", "a", "b"]);
+    registry.move_after("c", "b").unwrap();
+    assert_eq!(names(&registry), ["a", "b", "c"]);
+    registry.move_after("a", "a").unwrap();
+    assert_eq!(names(&registry), ["a", "b", "c"]);
+    assert_eq!(
+        registry.move_before("missing", "a").unwrap_err().name(),
+        "missing"
+    );
+
+    assert!(registry.remove("b").is_some());
+    assert!(registry.remove("b").is_none());
+    assert_body!(get("/").on(&registry), "ac");
+}
+
+#[test]
+fn halts() {
+    let mut registry = registry();
+    registry
+        .insert_after("a", "halt", |conn: Conn| async move { conn.halt() })
+        .unwrap();
+    assert_body!(get("/").on(&registry), "a");
+}
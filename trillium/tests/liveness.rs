@@ -99,6 +99,45 @@ async fn is_disconnected() -> TestResult {
     Ok(())
 }
 
+#[test(harness)]
+async fn on_client_disconnect() -> TestResult {
+    let (disconnected_sender, disconnected_receiver) = async_channel::unbounded();
+    let handle = config()
+        .with_host("localhost")
+        .with_port(0)
+        .spawn(move |mut conn: Conn| {
+            let disconnected_sender = disconnected_sender.clone();
+            async move {
+                let scope = conn.request_scope();
+                conn.on_client_disconnect().await;
+                disconnected_sender
+                    .send(scope.is_cancelled())
+                    .await
+                    .unwrap();
+                conn
+            }
+        });
+
+    let info = handle.info().await;
+
+    let url = format!("http://{}", info.listener_description())
+        .parse()
+        .unwrap();
+    let mut client = Connector::connect(&ClientConfig::default().boxed(), &url).await?;
+
+    client
+        .write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n")
+        .await?;
+    assert!(poll_once(disconnected_receiver.recv()).await.is_none());
+
+    client.close().await?;
+    assert!(disconnected_receiver.recv().await?);
+
+    handle.stop().await;
+
+    Ok(())
+}
+
 struct ReadAvailable<T>(T);
 impl<T: AsyncRead + Unpin> Future for ReadAvailable<T> {
     type Output = io::Result<Vec<u8>>;
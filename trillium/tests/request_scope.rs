@@ -0,0 +1,122 @@
+use async_channel::Sender;
+use futures_lite::{AsyncReadExt, AsyncWriteExt};
+use std::future::pending;
+use test_harness::test;
+use trillium::Conn;
+use trillium_testing::{config, harness, ClientConfig, Connector, ObjectSafeConnector, TestResult};
+
+struct DropSignal(Sender<()>);
+impl Drop for DropSignal {
+    fn drop(&mut self) {
+        let _ = self.0.try_send(());
+    }
+}
+
+#[test(harness)]
+async fn cancelled_after_send() -> TestResult {
+    let (sender, receiver) = async_channel::unbounded();
+    let handle = config()
+        .with_host("localhost")
+        .with_port(0)
+        .spawn(move |mut conn: Conn| {
+            let signal = DropSignal(sender.clone());
+            async move {
+                assert!(conn.spawn_scoped(async move {
+                    let _signal = signal;
+                    pending::<()>().await;
+                }));
+                conn.ok("ok")
+            }
+        });
+
+    let info = handle.info().await;
+    let url = format!("http://{}", info.listener_description())
+        .parse()
+        .unwrap();
+    let mut client = Connector::connect(&ClientConfig::default().boxed(), &url).await?;
+    client
+        .write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n")
+        .await?;
+    let mut response = String::new();
+    client.read_to_string(&mut response).await?;
+    assert!(response.ends_with("ok"));
+
+    receiver.recv().await?;
+    handle.stop().await;
+    Ok(())
+}
+
+#[test(harness)]
+async fn cancelled_on_disconnect() -> TestResult {
+    let (sender, receiver) = async_channel::unbounded();
+    let handle = config()
+        .with_host("localhost")
+        .with_port(0)
+        .spawn(move |mut conn: Conn| {
+            let signal = DropSignal(sender.clone());
+            async move {
+                assert!(conn.spawn_scoped(async move {
+                    let _signal = signal;
+                    pending::<()>().await;
+                }));
+                conn.cancel_on_disconnect(pending::<()>()).await;
+                assert!(conn.request_scope().is_cancelled());
+                conn
+            }
+        });
+
+    let info = handle.info().await;
+    let url = format!("http://{}", info.listener_description())
+        .parse()
+        .unwrap();
+    let mut client = Connector::connect(&ClientConfig::default().boxed(), &url).await?;
+    client
+        .write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\n\r\n")
+        .await?;
+    client.close().await?;
+
+    receiver.recv().await?;
+    handle.stop().await;
+    Ok(())
+}
+
+#[test(harness)]
+async fn completes_within_scope() -> TestResult {
+    let handle = config()
+        .with_host("localhost")
+        .with_port(0)
+        .spawn(|mut conn: Conn| async move {
+            let (sender, receiver) = async_channel::bounded(1);
+            assert!(conn.spawn_scoped(async move {
+                sender.send("from a scoped task").await.unwrap();
+            }));
+            let body = receiver.recv().await.unwrap();
+            let scope = conn.request_scope();
+            assert_eq!(scope.run(async { "run" }).await, Some("run"));
+            conn.ok(body)
+        });
+
+    let info = handle.info().await;
+    let url = format!("http://{}", info.listener_description())
+        .parse()
+        .unwrap();
+    let mut client = Connector::connect(&ClientConfig::default().boxed(), &url).await?;
+    client
+        .write_all(b"GET / HTTP/1.1\r\nHost: example.com\r\nConnection: close\r\n\r\n")
+        .await?;
+    let mut response = String::new();
+    client.read_to_string(&mut response).await?;
+    assert!(response.ends_with("from a scoped task"));
+
+    handle.stop().await;
+    Ok(())
+}
+
+#[test]
+fn no_spawner_without_runtime() {
+    let conn = trillium_testing::TestConn::build("GET", "/", ()).on(&|mut conn: Conn| async move {
+        assert!(!conn.spawn_scoped(async {}));
+        conn.ok("ok")
+    });
+    assert_eq!(conn.status(), Some(trillium::Status::Ok));
+}
@@ -0,0 +1,63 @@
+use trillium::{Conn, Transform, Transformer};
+use trillium_testing::prelude::*;
+
+struct Tag(&'static str);
+impl Transform for Tag {
+    fn request(&self, conn: &mut Conn) {
+        let seen = conn.take_state::<String>().unwrap_or_default();
+        conn.insert_state(format!("{seen}{}", self.0));
+    }
+
+    fn response(&self, conn: &mut Conn) {
+        let order = match conn.response_headers().get_str("x-order") {
+            Some(order) => format!("{order},{}", self.0),
+            None => self.0.to_string(),
+        };
+        conn.response_headers_mut().insert("x-order", order);
+    }
+}
+
+struct Halting;
+impl Transform for Halting {
+    fn request(&self, conn: &mut Conn) {
+        conn.set_halted(true);
+    }
+}
+
+async fn seen(conn: Conn) -> Conn {
+    let seen = conn.state::<String>().cloned().unwrap_or_default();
+    conn.ok(seen)
+}
+
+#[test]
+fn order() {
+    let handler = (Tag("a").into_handler(), Transformer::new(Tag("b")), seen);
+    assert_ok!(get("/").on(&handler), "ab", "x-order" => "b,a");
+}
+
+#[test]
+fn transforms_cannot_halt() {
+    let handler = (Halting.into_handler(), Tag("a").into_handler(), seen);
+    assert_ok!(get("/").on(&handler), "a");
+}
+
+#[test]
+fn response_transforms_run_after_halting() {
+    let handler = (
+        Tag("a").into_handler(),
+        |conn: Conn| async move { conn.ok("halted").halt() },
+        Tag("b").into_handler(),
+        seen,
+    );
+    assert_ok!(get("/").on(&handler), "halted", "x-order" => "b,a");
+}
+
+#[test]
+fn name() {
+    let handler = Tag("a").into_handler();
+    assert_eq!(
+        trillium::Handler::name(&handler),
+        "Transformer(transform::Tag)"
+    );
+    assert_eq!(format!("{handler:?}"), "Transformer(\"transform::Tag\")");
+}
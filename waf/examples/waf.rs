@@ -0,0 +1,28 @@
+use trillium::Conn;
+use trillium_waf::{packs, Action, Pattern, Rule, Target, Waf};
+
+fn main() {
+    env_logger::init();
+    let waf = Waf::new()
+        .with_rules(packs::scanner_user_agents())
+        .with_rules(packs::sensitive_paths())
+        .with_rules(packs::path_traversal())
+        .with_rule(
+            Rule::new("admin-probe", Target::Path, Pattern::prefix("/admin"))
+                .with_action(Action::LogOnly),
+        );
+    let metrics = waf.metrics();
+
+    // try `curl http://localhost:8080/.env` and then
+    // `curl http://localhost:8080/metrics`
+    trillium_smol::run((trillium_logger::logger(), waf, move |conn: Conn| {
+        let metrics = metrics.clone();
+        async move {
+            if conn.path() == "/metrics" {
+                conn.ok(format!("{:#?}", metrics.snapshot()))
+            } else {
+                conn.ok("ok")
+            }
+        }
+    }));
+}
@@ -0,0 +1,238 @@
+/*!
+# Request filtering for trillium.rs
+
+[`Waf`] is a lightweight web application firewall. It evaluates an
+ordered list of [`Rule`]s against each request, each of which matches
+a [`Pattern`] against part of the request (a [`Target`]) and takes an
+[`Action`] when it matches:
+
+* [`Action::Block`] halts the conn with `403 Forbidden`
+* [`Action::Tarpit`] waits before blocking, to slow down automated clients
+* [`Action::LogOnly`] logs the match and continues, which is useful
+  when evaluating new rules
+
+Rules are evaluated in the order they were added, and evaluation
+stops at the first rule that blocks. The ids of all rules that
+matched are available through [`WafConnExt::waf_matches`], and
+counts of matches per rule are available through [`WafMetrics`].
+
+A few built-in rule packs for common unwanted traffic are available
+in [`packs`].
+
+```
+use trillium_waf::{packs, Pattern, Rule, Target, Waf};
+
+let handler = (
+    Waf::new()
+        .with_rules(packs::scanner_user_agents())
+        .with_rules(packs::path_traversal())
+        .with_rule(Rule::new("no-trace", Target::Method, Pattern::exact("TRACE"))),
+    "ok",
+);
+
+use trillium_testing::prelude::*;
+assert_ok!(get("/").on(&handler), "ok");
+assert_status!(get("/static/../../etc/passwd").on(&handler), 403);
+assert_status!(
+    get("/").with_request_header("user-agent", "sqlmap/1.7").on(&handler),
+    403
+);
+```
+
+## Request bodies
+
+Rules that target [`Target::Body`] read the request body into memory
+with [`Conn::buffer_request_body`], so later handlers can still read
+it. Bodies longer than [`Waf::with_max_body_len`] are rejected with
+`413 Payload Too Large` when any body rule is configured, and bodies
+that cannot be read, including bodies that an earlier handler has
+already read, are rejected with `400 Bad Request`.
+*/
+#![forbid(unsafe_code)]
+#![deny(
+    clippy::dbg_macro,
+    missing_copy_implementations,
+    rustdoc::missing_crate_level_docs,
+    missing_debug_implementations,
+    missing_docs,
+    nonstandard_style,
+    unused_qualifications
+)]
+
+mod rule;
+pub use rule::{Action, Pattern, Rule, Target};
+
+mod metrics;
+pub use metrics::WafMetrics;
+
+pub mod packs;
+
+use async_io::Timer;
+use std::borrow::Cow;
+use trillium::{async_trait, Conn, Error, Handler, StateSet, Status};
+
+/**
+Trillium handler that filters requests with a list of [`Rule`]s
+
+See crate-level docs for an explanation
+*/
+#[derive(Debug)]
+pub struct Waf {
+    rules: Vec<Rule>,
+    max_body_len: u64,
+    inspects_body: bool,
+    metrics: WafMetrics,
+}
+
+impl Default for Waf {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_body_len: 64 * 1024,
+            inspects_body: false,
+            metrics: WafMetrics::default(),
+        }
+    }
+}
+
+impl Waf {
+    /// Constructs a new Waf with no rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Chainable method to add a rule, to be evaluated after any
+    /// previously added rules
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.metrics.register(rule.id.clone());
+        self.inspects_body |= rule.target == Target::Body;
+        self.rules.push(rule);
+        self
+    }
+
+    /// Chainable method to add several rules, such as a rule pack
+    /// from [`packs`]
+    pub fn with_rules(self, rules: impl IntoIterator<Item = Rule>) -> Self {
+        rules.into_iter().fold(self, Self::with_rule)
+    }
+
+    /// Sets the maximum length of request body that will be read for
+    /// [`Target::Body`] rules. The default is 64kb.
+    pub fn with_max_body_len(mut self, max_body_len: u64) -> Self {
+        self.max_body_len = max_body_len;
+        self
+    }
+
+    /// Borrow the rules of this Waf, in evaluation order
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Returns a handle to the [`WafMetrics`] for this Waf, which
+    /// can be retained and read while the server is running
+    pub fn metrics(&self) -> WafMetrics {
+        self.metrics.clone()
+    }
+
+    fn rule_matches(rule: &Rule, conn: &Conn, body: Option<&str>) -> bool {
+        match &rule.target {
+            Target::Method => rule.pattern.is_match(conn.method().as_ref()),
+            Target::Path => rule.pattern.is_match(conn.path()),
+            Target::Query => rule.pattern.is_match(conn.querystring()),
+            Target::Header(name) => {
+                conn.request_headers()
+                    .get_values(name.clone())
+                    .is_some_and(|values| {
+                        values.iter().any(|value| {
+                            rule.pattern
+                                .is_match(&String::from_utf8_lossy(value.as_ref()))
+                        })
+                    })
+            }
+            Target::Body => body.is_some_and(|body| rule.pattern.is_match(body)),
+        }
+    }
+}
+
+/// Alias for [`Waf::new`]
+pub fn waf() -> Waf {
+    Waf::new()
+}
+
+struct WafMatches(Vec<Cow<'static, str>>);
+
+#[async_trait]
+impl Handler for Waf {
+    async fn run(&self, mut conn: Conn) -> Conn {
+        let body = if self.inspects_body {
+            match conn.buffer_request_body(self.max_body_len).await {
+                Ok(body) => Some(String::from_utf8_lossy(body).into_owned()),
+                Err(e) => {
+                    log::warn!("waf could not read body for {}: {e}", conn.path());
+                    let status = match e {
+                        Error::ReceivedBodyTooLong(_) => Status::PayloadTooLarge,
+                        _ => Status::BadRequest,
+                    };
+                    return conn.with_status(status).halt();
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut matches = vec![];
+        let mut blocking_action = None;
+        for rule in &self.rules {
+            if !Self::rule_matches(rule, &conn, body.as_deref()) {
+                continue;
+            }
+
+            self.metrics.record(&rule.id);
+            matches.push(rule.id.clone());
+            log::warn!(
+                "waf rule {} matched {} {} ({:?})",
+                rule.id,
+                conn.method(),
+                conn.path(),
+                rule.action
+            );
+
+            if rule.action != Action::LogOnly {
+                blocking_action = Some(rule.action);
+                break;
+            }
+        }
+
+        if !matches.is_empty() {
+            conn.insert_state(WafMatches(matches));
+        }
+
+        match blocking_action {
+            None | Some(Action::LogOnly) => conn,
+            Some(Action::Tarpit(duration)) => {
+                Timer::after(duration).await;
+                conn.with_status(Status::Forbidden).halt()
+            }
+            Some(Action::Block) => conn.with_status(Status::Forbidden).halt(),
+        }
+    }
+}
+
+/// Extension trait to retrieve the results of a [`Waf`] handler
+pub trait WafConnExt {
+    /// Returns the ids of all rules that matched this conn, in
+    /// evaluation order. The last id is the rule that blocked the
+    /// conn, if it was blocked.
+    fn waf_matches(&self) -> &[Cow<'static, str>];
+}
+
+impl<ConnLike> WafConnExt for ConnLike
+where
+    ConnLike: AsRef<StateSet>,
+{
+    fn waf_matches(&self) -> &[Cow<'static, str>] {
+        self.as_ref()
+            .get::<WafMatches>()
+            .map_or(&[], |matches| &matches.0)
+    }
+}
@@ -0,0 +1,48 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+/**
+Counts of rule matches for a [`Waf`](crate::Waf), keyed by rule id.
+This is a cheap clone of a shared handle, so it can be retained
+and read while the server is running.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct WafMetrics(Arc<RwLock<BTreeMap<Cow<'static, str>, AtomicU64>>>);
+
+impl WafMetrics {
+    pub(crate) fn register(&self, id: Cow<'static, str>) {
+        self.0.write().unwrap().entry(id).or_default();
+    }
+
+    pub(crate) fn record(&self, id: &str) {
+        if let Some(count) = self.0.read().unwrap().get(id) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// the number of requests that matched the rule with this id
+    pub fn hits(&self, id: &str) -> u64 {
+        self.0
+            .read()
+            .unwrap()
+            .get(id)
+            .map_or(0, |count| count.load(Ordering::Relaxed))
+    }
+
+    /// the number of matches for every rule, including rules that
+    /// have not matched any requests
+    pub fn snapshot(&self) -> BTreeMap<String, u64> {
+        self.0
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(id, count)| (id.to_string(), count.load(Ordering::Relaxed)))
+            .collect()
+    }
+}
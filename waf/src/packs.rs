@@ -0,0 +1,63 @@
+/*!
+Built-in rule packs for common unwanted traffic.
+
+These are intended as a starting point, and each can be combined
+with application-specific rules or have its actions changed:
+
+```
+use trillium_waf::{packs, Action, Waf};
+let waf = Waf::new()
+    .with_rules(packs::scanner_user_agents())
+    .with_rules(
+        packs::path_traversal()
+            .into_iter()
+            .map(|rule| rule.with_action(Action::LogOnly)),
+    );
+```
+*/
+
+use crate::{Pattern, Rule, Target};
+use trillium::KnownHeaderName;
+
+fn regex(regex: &str) -> Pattern {
+    Pattern::regex(regex).expect("built-in patterns are valid")
+}
+
+/// Blocks requests from the user agents of common vulnerability
+/// scanners and brute-forcing tools, such as sqlmap, nikto, and
+/// nuclei. Rule id: `scanner-user-agent`
+pub fn scanner_user_agents() -> Vec<Rule> {
+    vec![Rule::new(
+        "scanner-user-agent",
+        Target::header(KnownHeaderName::UserAgent),
+        regex(
+            "(?i)(sqlmap|nikto|nmap|masscan|zgrab|nuclei|acunetix|netsparker|wpscan|\
+             dirbuster|gobuster|feroxbuster|wfuzz|havij|w3af|openvas)",
+        ),
+    )]
+}
+
+/// Blocks requests for paths that are commonly probed by scanners,
+/// such as version control directories, environment files, and
+/// popular admin panels. Rule id: `sensitive-path`
+pub fn sensitive_paths() -> Vec<Rule> {
+    vec![Rule::new(
+        "sensitive-path",
+        Target::Path,
+        regex(concat!(
+            r"(?i)^/(\.env|\.git(/|$)|\.svn(/|$)|\.hg(/|$)|\.ds_store|\.aws/|\.ssh/|",
+            r"wp-admin|wp-login\.php|xmlrpc\.php|phpmyadmin|cgi-bin/|server-status)",
+        )),
+    )]
+}
+
+/// Blocks directory traversal sequences such as `../` in the path or
+/// querystring, including common percent-encoded forms. Rule ids:
+/// `path-traversal-path` and `path-traversal-query`
+pub fn path_traversal() -> Vec<Rule> {
+    let pattern = r"(?i)(\.|%2e)(\.|%2e)(/|\\|%2f|%5c)";
+    vec![
+        Rule::new("path-traversal-path", Target::Path, regex(pattern)),
+        Rule::new("path-traversal-query", Target::Query, regex(pattern)),
+    ]
+}
@@ -0,0 +1,148 @@
+use regex::Regex;
+use std::{borrow::Cow, time::Duration};
+use trillium::HeaderName;
+
+/// The part of a request that a [`Rule`] matches against
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Target {
+    /// the request method, such as `GET`
+    Method,
+
+    /// the request path, as received and without the querystring
+    Path,
+
+    /// the querystring, as received and without the leading `?`
+    Query,
+
+    /// every value of the named request header
+    Header(HeaderName<'static>),
+
+    /// the request body, up to
+    /// [`Waf::with_max_body_len`](crate::Waf::with_max_body_len)
+    /// bytes. Bodies that are not valid utf8 are matched lossily.
+    Body,
+}
+
+impl Target {
+    /// match against every value of the named request header
+    pub fn header(name: impl Into<HeaderName<'static>>) -> Self {
+        Self::Header(name.into())
+    }
+}
+
+/// What a [`Rule`] looks for in its [`Target`]
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Pattern {
+    /// the target is exactly this string
+    Exact(String),
+
+    /// the target starts with this string
+    Prefix(String),
+
+    /// the target contains this string, ignoring ascii case
+    Contains(String),
+
+    /// the target matches this regular expression
+    Regex(Regex),
+}
+
+impl Pattern {
+    /// match targets that are exactly this string
+    pub fn exact(s: impl Into<String>) -> Self {
+        Self::Exact(s.into())
+    }
+
+    /// match targets that start with this string
+    pub fn prefix(s: impl Into<String>) -> Self {
+        Self::Prefix(s.into())
+    }
+
+    /// match targets that contain this string, ignoring ascii case
+    pub fn contains(s: impl Into<String>) -> Self {
+        Self::Contains(s.into().to_ascii_lowercase())
+    }
+
+    /// match targets with a regular expression
+    ///
+    /// # Errors
+    ///
+    /// returns an error if the regular expression is invalid
+    pub fn regex(regex: &str) -> Result<Self, regex::Error> {
+        Regex::new(regex).map(Self::Regex)
+    }
+
+    pub(crate) fn is_match(&self, haystack: &str) -> bool {
+        match self {
+            Self::Exact(s) => haystack == s,
+            Self::Prefix(s) => haystack.starts_with(&**s),
+            Self::Contains(s) => haystack.to_ascii_lowercase().contains(&**s),
+            Self::Regex(regex) => regex.is_match(haystack),
+        }
+    }
+}
+
+/// What [`Waf`](crate::Waf) does when a [`Rule`] matches
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Action {
+    /// halt the conn with a `403 Forbidden` response. This is the default.
+    #[default]
+    Block,
+
+    /// wait for the provided duration and then block, slowing down
+    /// automated clients
+    Tarpit(Duration),
+
+    /// log the match and continue evaluating rules
+    LogOnly,
+}
+
+/**
+A single request filtering rule, identified by an id that is used
+in logs and [`WafMetrics`](crate::WafMetrics)
+
+```
+use trillium_waf::{Action, Pattern, Rule, Target};
+let rule = Rule::new("no-admin", Target::Path, Pattern::prefix("/admin"))
+    .with_action(Action::LogOnly);
+assert_eq!(rule.id(), "no-admin");
+```
+*/
+#[derive(Clone, Debug)]
+pub struct Rule {
+    pub(crate) id: Cow<'static, str>,
+    pub(crate) target: Target,
+    pub(crate) pattern: Pattern,
+    pub(crate) action: Action,
+}
+
+impl Rule {
+    /// Builds a new rule that blocks requests when `pattern` matches
+    /// `target`
+    pub fn new(id: impl Into<Cow<'static, str>>, target: Target, pattern: Pattern) -> Self {
+        Self {
+            id: id.into(),
+            target,
+            pattern,
+            action: Action::Block,
+        }
+    }
+
+    /// Chainable method to set the [`Action`] taken when this rule matches
+    pub fn with_action(mut self, action: Action) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// The id of this rule
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The [`Action`] taken when this rule matches
+    pub fn action(&self) -> Action {
+        self.action
+    }
+}
@@ -0,0 +1,48 @@
+use async_compat::Compat;
+use bytes::Bytes;
+use http::Request;
+use std::sync::Arc;
+use trillium::Handler;
+use trillium_http::{Conn, HttpConfig, Stopper};
+use trillium_testing::{harness, TestTransport};
+use trillium_waf::{waf, Pattern, Rule, Target};
+
+#[test]
+fn body_rules_apply_to_http2_bodies_without_content_length() {
+    harness(|| async {
+        let handler = Arc::new((
+            waf().with_rule(Rule::new(
+                "body",
+                Target::Body,
+                Pattern::contains("union select"),
+            )),
+            "ok",
+        ));
+
+        let (client, server) = TestTransport::new();
+        trillium_testing::spawn(async move {
+            Conn::map_http2(HttpConfig::default(), server, Stopper::new(), |conn| {
+                let handler = Arc::clone(&handler);
+                async move {
+                    let conn = handler.run(conn.into()).await;
+                    handler.before_send(conn).await.into_inner()
+                }
+            })
+            .await
+            .unwrap();
+        });
+
+        let (send_request, connection) = h2::client::handshake(Compat::new(client)).await.unwrap();
+        trillium_testing::spawn(async move { connection.await.unwrap() });
+        let mut client = send_request.ready().await.unwrap();
+
+        for (body, status) in [("harmless", 200), ("1 union select password", 403)] {
+            let request = Request::post("https://example.com/").body(()).unwrap();
+            let (response, mut send_stream) = client.send_request(request, false).unwrap();
+            send_stream
+                .send_data(Bytes::from_static(body.as_bytes()), true)
+                .unwrap();
+            assert_eq!(response.await.unwrap().status(), status);
+        }
+    });
+}
@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+use trillium::{Conn, Status};
+use trillium_testing::prelude::*;
+use trillium_waf::{packs, waf, Action, Pattern, Rule, Target, WafConnExt};
+
+async fn echo_body(mut conn: Conn) -> Conn {
+    let body = conn.request_body_string().await.unwrap();
+    conn.ok(body)
+}
+
+#[test]
+fn targets_and_patterns() {
+    let handler = (
+        waf()
+            .with_rule(Rule::new("method", Target::Method, Pattern::exact("TRACE")))
+            .with_rule(Rule::new("path", Target::Path, Pattern::prefix("/admin")))
+            .with_rule(Rule::new(
+                "query",
+                Target::Query,
+                Pattern::contains("<SCRIPT"),
+            ))
+            .with_rule(Rule::new(
+                "header",
+                Target::header("x-forwarded-host"),
+                Pattern::regex(r"^evil\.").unwrap(),
+            )),
+        "ok",
+    );
+
+    assert_ok!(get("/").on(&handler), "ok");
+    assert_ok!(get("/public/admin").on(&handler), "ok");
+    assert_status!(
+        trillium_testing::TestConn::build("TRACE", "/", ()).on(&handler),
+        403
+    );
+    assert_status!(get("/admin/users").on(&handler), 403);
+    assert_status!(get("/search?q=<script>alert(1)").on(&handler), 403);
+    assert_status!(
+        get("/")
+            .with_request_header("x-forwarded-host", "evil.example")
+            .on(&handler),
+        403
+    );
+
+    let conn = get("/admin").on(&handler);
+    assert_eq!(conn.waf_matches(), ["path"]);
+}
+
+#[test]
+fn body_rules() {
+    let handler = (
+        waf()
+            .with_rule(Rule::new(
+                "body",
+                Target::Body,
+                Pattern::contains("union select"),
+            ))
+            .with_max_body_len(32),
+        echo_body,
+    );
+
+    assert_ok!(
+        post("/").with_request_body("harmless").on(&handler),
+        "harmless"
+    );
+    assert_status!(
+        post("/")
+            .with_request_body("1 UNION SELECT password")
+            .on(&handler),
+        403
+    );
+    assert_status!(
+        post("/").with_request_body("x".repeat(33)).on(&handler),
+        413
+    );
+
+    let already_read = (
+        |mut conn: Conn| async move {
+            conn.request_body_string().await.unwrap();
+            conn
+        },
+        handler,
+    );
+    assert_status!(
+        post("/").with_request_body("harmless").on(&already_read),
+        400
+    );
+}
+
+#[test]
+fn log_only_and_metrics() {
+    let waf = waf()
+        .with_rule(
+            Rule::new("observe", Target::Path, Pattern::prefix("/api"))
+                .with_action(Action::LogOnly),
+        )
+        .with_rule(Rule::new(
+            "block",
+            Target::Path,
+            Pattern::exact("/api/secret"),
+        ));
+    let metrics = waf.metrics();
+    let handler = (waf, "ok");
+
+    let mut conn = get("/api/users").on(&handler);
+    assert_ok!(&mut conn, "ok");
+    assert_eq!(conn.waf_matches(), ["observe"]);
+
+    let conn = get("/api/secret").on(&handler);
+    assert_status!(&conn, 403);
+    assert_eq!(conn.waf_matches(), ["observe", "block"]);
+
+    assert!(get("/").on(&handler).waf_matches().is_empty());
+
+    assert_eq!(metrics.hits("observe"), 2);
+    assert_eq!(metrics.hits("block"), 1);
+    assert_eq!(metrics.hits("unknown"), 0);
+    assert_eq!(
+        metrics.snapshot().into_iter().collect::<Vec<_>>(),
+        [("block".to_string(), 1), ("observe".to_string(), 2)]
+    );
+}
+
+#[test]
+fn tarpit() {
+    let handler = (
+        waf().with_rule(
+            Rule::new("slow", Target::Path, Pattern::exact("/wp-login.php"))
+                .with_action(Action::Tarpit(Duration::from_millis(50))),
+        ),
+        "ok",
+    );
+
+    let start = Instant::now();
+    assert_status!(get("/wp-login.php").on(&handler), 403);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn built_in_packs() {
+    let handler = (
+        waf()
+            .with_rules(packs::scanner_user_agents())
+            .with_rules(packs::sensitive_paths())
+            .with_rules(packs::path_traversal()),
+        "ok",
+    );
+
+    for path in [
+        "/.env",
+        "/.git/config",
+        "/wp-login.php",
+        "/static/%2e%2e/%2e%2e/etc/passwd",
+        "/download?file=..%2f..%2fetc%2fpasswd",
+        "/download?file=..\\windows",
+    ] {
+        assert_eq!(
+            get(path).on(&handler).status(),
+            Some(Status::Forbidden),
+            "{path}"
+        );
+    }
+
+    for user_agent in ["sqlmap/1.7.2#stable", "Mozilla/5.0 (compatible; Nuclei)"] {
+        assert_status!(
+            get("/")
+                .with_request_header("user-agent", user_agent)
+                .on(&handler),
+            403
+        );
+    }
+
+    for path in ["/", "/environment", "/docs/git", "/a.b/c", "/?q=1..2"] {
+        assert_eq!(get(path).on(&handler).status(), Some(Status::Ok), "{path}");
+    }
+
+    assert_ok!(
+        get("/")
+            .with_request_header("user-agent", "Mozilla/5.0 (X11; Linux x86_64)")
+            .on(&handler),
+        "ok"
+    );
+}
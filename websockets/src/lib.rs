@@ -51,9 +51,18 @@ use case.  See the [`JsonWebSocketHandler`] documentation for example
 usage. In order to use this trait, the `json` cargo feature must be
 enabled.
 
+## Rooms
+
+[`Rooms`] tracks which connections have joined named rooms and
+broadcasts messages to every connection in a room, for applications
+that need simple fanout without a full pub-sub protocol.
+
 */
 
 mod bidirectional_stream;
+mod memory_quota;
+mod rooms;
+mod stats;
 mod websocket_connection;
 mod websocket_handler;
 
@@ -82,6 +91,9 @@ pub use async_tungstenite::{
         Message,
     },
 };
+pub use rooms::{RoomMember, RoomMessages, Rooms};
+use stats::StatsCallback;
+pub use stats::WebSocketStats;
 pub use trillium::async_trait;
 pub use websocket_connection::WebSocketConn;
 pub use websocket_handler::WebSocketHandler;
@@ -122,6 +134,8 @@ pub struct WebSocket<H> {
     protocols: Vec<String>,
     config: Option<WebSocketConfig>,
     required: bool,
+    stats_callback: Option<StatsCallback>,
+    memory_quota: Option<usize>,
 }
 
 impl<H> Deref for WebSocket<H> {
@@ -161,6 +175,8 @@ where
             protocols: Default::default(),
             config: None,
             required: false,
+            stats_callback: None,
+            memory_quota: None,
         }
     }
 
@@ -182,12 +198,66 @@ where
         }
     }
 
+    /**
+    Sets a memory quota, in bytes, for each websocket connection. No
+    connection buffers an inbound message that is larger than the
+    quota, whether the quota is exceeded by the length of the message
+    as received or by its decoded (decompressed) length, and the
+    connection is closed with status 1009 (message too big).
+
+    This also lowers the `max_message_size` of the
+    [`WebSocketConfig`] to the quota. See also
+    [`WebSocketConn::set_memory_quota`] to adjust the quota for an
+    individual connection.
+
+    ```
+    use trillium_websockets::{websocket, WebSocketConn};
+    let handler = websocket(|conn: WebSocketConn| async move { drop(conn) })
+        .with_memory_quota(64 * 1024);
+    ```
+    */
+    pub fn with_memory_quota(mut self, memory_quota: usize) -> Self {
+        self.memory_quota = Some(memory_quota);
+        self
+    }
+
     /// configure this handler to halt and send back a [`426 Upgrade
     /// Required`][Status::UpgradeRequired] if a websocket cannot be negotiated
     pub fn required(mut self) -> Self {
         self.required = true;
         self
     }
+
+    /**
+    Registers a callback that receives the [`WebSocketStats`] of
+    each connection as it is established. The stats continue to
+    update for the lifetime of the connection, so they can be
+    retained to aggregate realtime connection health across all
+    connections, and discarded once [`WebSocketStats::is_closed`].
+
+    ```
+    use std::sync::{Arc, Mutex};
+    use trillium_websockets::{websocket, WebSocketConn, WebSocketStats};
+
+    let connections: Arc<Mutex<Vec<WebSocketStats>>> = Default::default();
+    let handler = websocket(|conn: WebSocketConn| async move { drop(conn) })
+        .with_stats_callback({
+            let connections = connections.clone();
+            move |stats| {
+                let mut connections = connections.lock().unwrap();
+                connections.retain(|stats| !stats.is_closed());
+                connections.push(stats);
+            }
+        });
+    ```
+    */
+    pub fn with_stats_callback(
+        mut self,
+        callback: impl Fn(WebSocketStats) + Send + Sync + 'static,
+    ) -> Self {
+        self.stats_callback = Some(StatsCallback::new(callback));
+        self
+    }
 }
 
 struct IsWebsocket;
@@ -249,8 +319,25 @@ where
 
     async fn upgrade(&self, mut upgrade: Upgrade) {
         let peer_ip = upgrade.state.take::<WebsocketPeerIp>().and_then(|i| i.0);
-        let mut conn = WebSocketConn::new(upgrade, self.config, Role::Server).await;
+        let config = match self.memory_quota {
+            Some(memory_quota) => {
+                let mut config = self.config.unwrap_or_default();
+                config.max_message_size = Some(
+                    config
+                        .max_message_size
+                        .map_or(memory_quota, |max| max.min(memory_quota)),
+                );
+                Some(config)
+            }
+            None => self.config,
+        };
+        let mut conn = WebSocketConn::new(upgrade, config, Role::Server).await;
         conn.set_peer_ip(peer_ip);
+        conn.set_memory_quota(self.memory_quota);
+
+        if let Some(stats_callback) = &self.stats_callback {
+            stats_callback.call(conn.stats().clone());
+        }
 
         let Some((mut conn, outbound)) = self.handler.connect(conn).await else {
             return;
@@ -0,0 +1,68 @@
+use async_tungstenite::tungstenite::error::CapacityError;
+use std::sync::{
+    atomic::{
+        AtomicBool, AtomicUsize,
+        Ordering::{Acquire, Relaxed, Release},
+    },
+    Arc,
+};
+
+const UNLIMITED: usize = usize::MAX;
+
+/// The shared memory quota for a single websocket connection, checked
+/// by the inbound stream and consulted when the connection is closed
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryQuota(Arc<MemoryQuotaInner>);
+
+#[derive(Debug)]
+struct MemoryQuotaInner {
+    limit: AtomicUsize,
+    exceeded: AtomicBool,
+}
+
+impl Default for MemoryQuota {
+    fn default() -> Self {
+        Self(Arc::new(MemoryQuotaInner {
+            limit: AtomicUsize::new(UNLIMITED),
+            exceeded: AtomicBool::new(false),
+        }))
+    }
+}
+
+impl MemoryQuota {
+    pub(crate) fn set_limit(&self, limit: Option<usize>) {
+        self.0.limit.store(limit.unwrap_or(UNLIMITED), Relaxed);
+    }
+
+    pub(crate) fn limit(&self) -> Option<usize> {
+        match self.0.limit.load(Relaxed) {
+            UNLIMITED => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// checks the decoded length of a received message against the
+    /// quota, returning the capacity error that tungstenite would
+    /// return for a message that exceeds its own `max_message_size`
+    pub(crate) fn check(&self, len: usize) -> Result<(), CapacityError> {
+        match self.limit() {
+            Some(max_size) if len > max_size => {
+                log::warn!("websocket message of {len} bytes exceeds memory quota of {max_size}");
+                self.mark_exceeded();
+                Err(CapacityError::MessageTooLong {
+                    size: len,
+                    max_size,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn mark_exceeded(&self) {
+        self.0.exceeded.store(true, Release);
+    }
+
+    pub(crate) fn is_exceeded(&self) -> bool {
+        self.0.exceeded.load(Acquire)
+    }
+}
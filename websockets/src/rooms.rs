@@ -0,0 +1,338 @@
+use crate::Message;
+use async_channel::{Receiver, Sender};
+use futures_lite::Stream;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Debug, Formatter},
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard},
+    task::{Context, Poll},
+};
+
+/**
+# Simple room-based fanout for websocket connections
+
+`Rooms` keeps track of which connections have joined which named
+rooms, and delivers messages broadcast to a room to every connection
+in that room. It does not define a protocol for joining or leaving
+rooms, so applications decide when to join and leave rooms and what
+the messages look like. For a full pub-sub protocol, see
+`trillium-channels`.
+
+Each connection is represented by a [`RoomMember`], which can join,
+leave, and broadcast to rooms, and a [`RoomMessages`] stream of the
+messages broadcast to rooms it has joined. The `RoomMessages` is
+typically returned from [`WebSocketHandler::connect`] as the outbound
+stream, and the `RoomMember` stored in the conn's state for use in
+[`WebSocketHandler::inbound`]. When the `RoomMessages` is dropped,
+the member leaves every room it joined.
+
+Rooms is generic over the message type, which defaults to
+[`Message`]. `Rooms` is cheap to clone, and all clones share the same
+rooms.
+
+[`WebSocketHandler::connect`]: crate::WebSocketHandler::connect
+[`WebSocketHandler::inbound`]: crate::WebSocketHandler::inbound
+
+```
+use trillium_websockets::{
+    async_trait, Message, RoomMember, RoomMessages, Rooms, WebSocket, WebSocketConn,
+    WebSocketHandler,
+};
+
+#[derive(Default)]
+struct Chat(Rooms);
+
+#[async_trait]
+impl WebSocketHandler for Chat {
+    type OutboundStream = RoomMessages;
+
+    async fn connect(&self, mut conn: WebSocketConn) -> Option<(WebSocketConn, RoomMessages)> {
+        let (member, messages) = self.0.member();
+        member.join(conn.path().trim_start_matches('/'));
+        conn.insert_state(member);
+        Some((conn, messages))
+    }
+
+    async fn inbound(&self, message: Message, conn: &mut WebSocketConn) {
+        if let Message::Text(text) = message {
+            let member = conn.state::<RoomMember>().unwrap();
+            for room in member.rooms() {
+                member.broadcast(&room, Message::text(text.clone()));
+            }
+        }
+    }
+}
+
+let handler = WebSocket::new(Chat::default());
+# // tests at tests/rooms.rs
+```
+*/
+pub struct Rooms<T = Message>(Arc<Mutex<RoomsInner<T>>>);
+
+struct RoomsInner<T> {
+    rooms: HashMap<String, HashMap<u64, Sender<T>>>,
+    next_id: u64,
+}
+
+impl<T> Default for Rooms<T> {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(RoomsInner {
+            rooms: HashMap::new(),
+            next_id: 0,
+        })))
+    }
+}
+
+impl<T> Clone for Rooms<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Debug for Rooms<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let inner = self.lock();
+        let mut map = f.debug_map();
+        for (room, members) in &inner.rooms {
+            map.entry(room, &members.len());
+        }
+        map.finish()
+    }
+}
+
+impl<T: Clone + Send + 'static> Rooms<T> {
+    /// Constructs a new Rooms with no rooms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /**
+    Register a new member, returning a [`RoomMember`] handle that can
+    join and leave rooms and a [`RoomMessages`] stream of the messages
+    broadcast to rooms that the member has joined.
+    */
+    pub fn member(&self) -> (RoomMember<T>, RoomMessages<T>) {
+        let id = {
+            let mut inner = self.lock();
+            inner.next_id += 1;
+            inner.next_id
+        };
+
+        let (sender, receiver) = async_channel::unbounded();
+
+        let member = RoomMember {
+            id,
+            rooms: self.clone(),
+            sender,
+        };
+
+        let messages = RoomMessages {
+            id,
+            rooms: self.clone(),
+            receiver: Box::pin(receiver),
+        };
+
+        (member, messages)
+    }
+
+    /**
+    Send a clone of the message to every member of the room, returning
+    the number of members it was sent to.
+    */
+    pub fn broadcast(&self, room: &str, message: T) -> usize {
+        self.send(room, message, None)
+    }
+
+    /// The number of members in the room
+    pub fn member_count(&self, room: &str) -> usize {
+        self.lock().rooms.get(room).map_or(0, HashMap::len)
+    }
+
+    /// The names of all rooms with at least one member
+    pub fn rooms(&self) -> Vec<String> {
+        let mut rooms = self.lock().rooms.keys().cloned().collect::<Vec<_>>();
+        rooms.sort();
+        rooms
+    }
+
+    fn send(&self, room: &str, message: T, except: Option<u64>) -> usize {
+        let mut inner = self.lock();
+        let Some(members) = inner.rooms.get_mut(room) else {
+            return 0;
+        };
+
+        let mut sent = 0;
+        members.retain(|id, sender| {
+            if Some(*id) == except {
+                true
+            } else if sender.try_send(message.clone()).is_ok() {
+                sent += 1;
+                true
+            } else {
+                false
+            }
+        });
+
+        if members.is_empty() {
+            inner.rooms.remove(room);
+        }
+
+        sent
+    }
+}
+
+impl<T> Rooms<T> {
+    fn lock(&self) -> MutexGuard<'_, RoomsInner<T>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn remove_member(&self, id: u64) {
+        self.lock().rooms.retain(|_, members| {
+            members.remove(&id);
+            !members.is_empty()
+        });
+    }
+}
+
+/**
+# A handle for a single member of [`Rooms`]
+
+Returned from [`Rooms::member`]. This handle is cheap to clone, and
+all clones represent the same member.
+*/
+pub struct RoomMember<T = Message> {
+    id: u64,
+    rooms: Rooms<T>,
+    sender: Sender<T>,
+}
+
+impl<T> Clone for RoomMember<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            rooms: self.rooms.clone(),
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<T> Debug for RoomMember<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoomMember").field("id", &self.id).finish()
+    }
+}
+
+impl<T: Clone + Send + 'static> RoomMember<T> {
+    /// A unique identifier for this member within its [`Rooms`]
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /**
+    Join the room, creating it if it does not exist. Returns false if
+    this member had already joined the room, or if its
+    [`RoomMessages`] has been dropped.
+    */
+    pub fn join(&self, room: impl Into<String>) -> bool {
+        if self.sender.is_closed() {
+            return false;
+        }
+
+        self.rooms
+            .lock()
+            .rooms
+            .entry(room.into())
+            .or_default()
+            .insert(self.id, self.sender.clone())
+            .is_none()
+    }
+
+    /**
+    Leave the room. Returns false if this member had not joined the
+    room.
+    */
+    pub fn leave(&self, room: &str) -> bool {
+        let mut inner = self.rooms.lock();
+        let Some(members) = inner.rooms.get_mut(room) else {
+            return false;
+        };
+
+        let removed = members.remove(&self.id).is_some();
+        if members.is_empty() {
+            inner.rooms.remove(room);
+        }
+        removed
+    }
+
+    /// Whether this member has joined the room
+    pub fn is_in(&self, room: &str) -> bool {
+        self.rooms
+            .lock()
+            .rooms
+            .get(room)
+            .is_some_and(|members| members.contains_key(&self.id))
+    }
+
+    /// The names of the rooms this member has joined
+    pub fn rooms(&self) -> BTreeSet<String> {
+        self.rooms
+            .lock()
+            .rooms
+            .iter()
+            .filter(|(_, members)| members.contains_key(&self.id))
+            .map(|(room, _)| room.clone())
+            .collect()
+    }
+
+    /**
+    Send a clone of the message to every other member of the room,
+    returning the number of members it was sent to. This member does
+    not need to have joined the room.
+    */
+    pub fn broadcast(&self, room: &str, message: T) -> usize {
+        self.rooms.send(room, message, Some(self.id))
+    }
+
+    /// Send a message to this member only
+    pub fn send(&self, message: T) {
+        let _ = self.sender.try_send(message);
+    }
+}
+
+/**
+# A stream of the messages sent to a [`RoomMember`]
+
+Returned from [`Rooms::member`], and usually used as the
+[`WebSocketHandler::OutboundStream`](crate::WebSocketHandler::OutboundStream).
+When this is dropped, the member leaves all rooms.
+*/
+pub struct RoomMessages<T = Message> {
+    id: u64,
+    rooms: Rooms<T>,
+    receiver: Pin<Box<Receiver<T>>>,
+}
+
+impl<T> Debug for RoomMessages<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoomMessages")
+            .field("id", &self.id)
+            .field("pending", &self.receiver.len())
+            .finish()
+    }
+}
+
+impl<T> Stream for RoomMessages<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.as_mut().poll_next(cx)
+    }
+}
+
+impl<T> Drop for RoomMessages<T> {
+    fn drop(&mut self) {
+        self.receiver.close();
+        self.rooms.remove_member(self.id);
+    }
+}
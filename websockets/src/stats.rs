@@ -0,0 +1,183 @@
+use crate::Message;
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::{
+        atomic::{
+            AtomicBool, AtomicU64,
+            Ordering::{Acquire, Relaxed, Release},
+        },
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/**
+Protocol-level statistics for a single [`WebSocketConn`](crate::WebSocketConn).
+
+A `WebSocketStats` is a cheaply-cloneable handle that continues to
+update for as long as the connection is open, so a clone can be
+retained elsewhere in an application, for example in a registry
+populated by [`WebSocket::with_stats_callback`](crate::WebSocket::with_stats_callback).
+
+Message counts include control frames that are visible to the
+application, such as pings and pongs, and byte counts are payload
+lengths, excluding websocket framing.
+*/
+#[derive(Clone)]
+pub struct WebSocketStats(Arc<StatsInner>);
+
+struct StatsInner {
+    connected_at: Instant,
+    messages_received: AtomicU64,
+    messages_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    last_activity: AtomicU64,
+    closed: AtomicBool,
+    ping: Mutex<PingState>,
+}
+
+#[derive(Default)]
+struct PingState {
+    outstanding: Option<(u64, Instant)>,
+    next_payload: u64,
+    rtt: Option<Duration>,
+}
+
+impl Default for WebSocketStats {
+    fn default() -> Self {
+        Self(Arc::new(StatsInner {
+            connected_at: Instant::now(),
+            messages_received: AtomicU64::new(0),
+            messages_sent: AtomicU64::new(0),
+            bytes_received: AtomicU64::new(0),
+            bytes_sent: AtomicU64::new(0),
+            last_activity: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            ping: Mutex::new(PingState::default()),
+        }))
+    }
+}
+
+impl Debug for WebSocketStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebSocketStats")
+            .field("connected_at", &self.connected_at())
+            .field("messages_received", &self.messages_received())
+            .field("messages_sent", &self.messages_sent())
+            .field("bytes_received", &self.bytes_received())
+            .field("bytes_sent", &self.bytes_sent())
+            .field("last_activity", &self.last_activity())
+            .field("ping_rtt", &self.ping_rtt())
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl WebSocketStats {
+    /// the time at which this websocket connection was established
+    pub fn connected_at(&self) -> Instant {
+        self.0.connected_at
+    }
+
+    /// the number of messages received from the peer
+    pub fn messages_received(&self) -> u64 {
+        self.0.messages_received.load(Relaxed)
+    }
+
+    /// the number of messages sent to the peer
+    pub fn messages_sent(&self) -> u64 {
+        self.0.messages_sent.load(Relaxed)
+    }
+
+    /// the total payload length of all messages received from the peer
+    pub fn bytes_received(&self) -> u64 {
+        self.0.bytes_received.load(Relaxed)
+    }
+
+    /// the total payload length of all messages sent to the peer
+    pub fn bytes_sent(&self) -> u64 {
+        self.0.bytes_sent.load(Relaxed)
+    }
+
+    /// the time at which a message was most recently sent or
+    /// received, or the time the connection was established if no
+    /// messages have been exchanged
+    pub fn last_activity(&self) -> Instant {
+        self.0.connected_at + Duration::from_nanos(self.0.last_activity.load(Relaxed))
+    }
+
+    /// the round trip time of the most recent ping sent with
+    /// [`WebSocketConn::ping`](crate::WebSocketConn::ping) that
+    /// has been answered by the peer
+    pub fn ping_rtt(&self) -> Option<Duration> {
+        self.0.ping.lock().unwrap().rtt
+    }
+
+    /// whether the websocket connection has been closed or dropped
+    pub fn is_closed(&self) -> bool {
+        self.0.closed.load(Acquire)
+    }
+
+    fn touch(&self) {
+        let elapsed = self.0.connected_at.elapsed().as_nanos();
+        self.0
+            .last_activity
+            .fetch_max(u64::try_from(elapsed).unwrap_or(u64::MAX), Relaxed);
+    }
+
+    pub(crate) fn record_received(&self, message: &Message) {
+        self.0.messages_received.fetch_add(1, Relaxed);
+        self.0
+            .bytes_received
+            .fetch_add(message.len() as u64, Relaxed);
+        self.touch();
+
+        if let Message::Pong(payload) = message {
+            let mut ping = self.0.ping.lock().unwrap();
+            if let Some((expected, sent_at)) = ping.outstanding {
+                if payload[..] == expected.to_be_bytes() {
+                    ping.rtt = Some(sent_at.elapsed());
+                    ping.outstanding = None;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn record_sent(&self, len: usize) {
+        self.0.messages_sent.fetch_add(1, Relaxed);
+        self.0.bytes_sent.fetch_add(len as u64, Relaxed);
+        self.touch();
+    }
+
+    pub(crate) fn start_ping(&self) -> Vec<u8> {
+        let mut ping = self.0.ping.lock().unwrap();
+        let payload = ping.next_payload;
+        ping.next_payload = ping.next_payload.wrapping_add(1);
+        ping.outstanding = Some((payload, Instant::now()));
+        payload.to_be_bytes().to_vec()
+    }
+
+    pub(crate) fn mark_closed(&self) {
+        self.0.closed.store(true, Release);
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct StatsCallback(Arc<dyn Fn(WebSocketStats) + Send + Sync + 'static>);
+
+impl StatsCallback {
+    pub(crate) fn new(callback: impl Fn(WebSocketStats) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    pub(crate) fn call(&self, stats: WebSocketStats) {
+        (self.0)(stats)
+    }
+}
+
+impl Debug for StatsCallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("StatsCallback").field(&"..").finish()
+    }
+}
@@ -1,6 +1,10 @@
-use crate::{Result, Role, WebSocketConfig};
+use crate::{memory_quota::MemoryQuota, Result, Role, WebSocketConfig, WebSocketStats};
 use async_tungstenite::{
-    tungstenite::{self, Message},
+    tungstenite::{
+        self,
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
     WebSocketStream,
 };
 use futures_util::{
@@ -37,6 +41,8 @@ pub struct WebSocketConn {
     stopper: Stopper,
     sink: SplitSink<Wss, Message>,
     stream: Option<WStream>,
+    stats: WebSocketStats,
+    memory_quota: MemoryQuota,
 }
 
 type Wss = WebSocketStream<BoxedTransport>;
@@ -61,7 +67,21 @@ impl WebSocketConn {
 
     /// Sends a [`Message`] to the client
     pub async fn send(&mut self, message: Message) -> Result<()> {
-        self.sink.send(message).await.map_err(Into::into)
+        let len = message.len();
+        self.sink.send(message).await?;
+        self.stats.record_sent(len);
+        Ok(())
+    }
+
+    /**
+    Sends a [`Message::Ping`] to the client. When the client's
+    pong is received, its round trip time is available from
+    [`WebSocketStats::ping_rtt`]. Inbound messages must be polled
+    for the pong to be observed.
+    */
+    pub async fn ping(&mut self) -> Result<()> {
+        let payload = self.stats.start_ping();
+        self.send(Message::Ping(payload)).await
     }
 
     /// Create a `WebSocketConn` from an HTTP upgrade, with optional config and the specified role
@@ -87,8 +107,12 @@ impl WebSocketConn {
         };
 
         let (sink, stream) = wss.split();
+        let stats = WebSocketStats::default();
+        let memory_quota = MemoryQuota::default();
         let stream = Some(WStream {
             stream: stopper.stop_stream(stream),
+            stats: stats.clone(),
+            memory_quota: memory_quota.clone(),
         });
 
         Self {
@@ -100,17 +124,54 @@ impl WebSocketConn {
             sink,
             stream,
             stopper,
+            stats,
+            memory_quota,
         }
     }
 
+    /// retrieve the [`WebSocketStats`] for this conn
+    pub fn stats(&self) -> &WebSocketStats {
+        &self.stats
+    }
+
     /// retrieve a clone of the server's [`Stopper`]
     pub fn stopper(&self) -> Stopper {
         self.stopper.clone()
     }
 
-    /// close the websocket connection gracefully
+    /**
+    Sets the memory quota for this connection, in bytes. The quota is
+    checked against the decoded length of each inbound message, so it
+    applies to the decompressed size of compressed messages. When a
+    message exceeds the quota, the inbound stream yields a
+    [`tungstenite::Error::Capacity`] error, and the connection is
+    closed with status 1009 (message too big) when
+    [`WebSocketConn::close`] is called. The default is no quota.
+
+    [`WebSocket::with_memory_quota`](crate::WebSocket::with_memory_quota)
+    sets a quota for every connection, and also prevents messages
+    larger than the quota from being buffered while they are received.
+    */
+    pub fn set_memory_quota(&mut self, memory_quota: Option<usize>) {
+        self.memory_quota.set_limit(memory_quota);
+    }
+
+    /// the memory quota for this connection, in bytes, if any
+    pub fn memory_quota(&self) -> Option<usize> {
+        self.memory_quota.limit()
+    }
+
+    /// close the websocket connection gracefully. If an inbound
+    /// message exceeded the memory quota, the close frame has status
+    /// 1009 (message too big)
     pub async fn close(&mut self) -> Result<()> {
-        self.send(Message::Close(None)).await
+        let close_frame = self.memory_quota.is_exceeded().then(|| CloseFrame {
+            code: CloseCode::Size,
+            reason: "message too big".into(),
+        });
+        let result = self.send(Message::Close(close_frame)).await;
+        self.stats.mark_closed();
+        result
     }
 
     /// retrieve the request headers for this conn
@@ -208,13 +269,38 @@ type MessageResult = std::result::Result<Message, tungstenite::Error>;
 #[derive(Debug)]
 pub struct WStream {
     stream: StreamStopper<SplitStream<Wss>>,
+    stats: WebSocketStats,
+    memory_quota: MemoryQuota,
 }
 
 impl Stream for WStream {
     type Item = MessageResult;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        self.stream.poll_next_unpin(cx)
+        match self.stream.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(message))) => {
+                self.memory_quota
+                    .check(message.len())
+                    .map_err(tungstenite::Error::Capacity)?;
+                self.stats.record_received(&message);
+                Poll::Ready(Some(Ok(message)))
+            }
+
+            // tungstenite's max_message_size is exceeded before the
+            // message is fully buffered
+            Poll::Ready(Some(Err(tungstenite::Error::Capacity(e)))) => {
+                self.memory_quota.mark_exceeded();
+                Poll::Ready(Some(Err(tungstenite::Error::Capacity(e))))
+            }
+
+            poll => poll,
+        }
+    }
+}
+
+impl Drop for WebSocketConn {
+    fn drop(&mut self) {
+        self.stats.mark_closed();
     }
 }
 
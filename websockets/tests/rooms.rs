@@ -0,0 +1,127 @@
+use futures_lite::{future::block_on, StreamExt};
+use futures_util::SinkExt;
+use trillium_testing::Connector;
+use trillium_websockets::{
+    async_trait, Message, RoomMember, RoomMessages, Rooms, WebSocket, WebSocketConn,
+    WebSocketHandler,
+};
+
+#[test]
+fn join_leave_and_broadcast() {
+    let rooms = Rooms::<String>::new();
+    let (alice, mut alice_messages) = rooms.member();
+    let (bob, mut bob_messages) = rooms.member();
+    assert_ne!(alice.id(), bob.id());
+
+    assert!(alice.join("lobby"));
+    assert!(!alice.join("lobby"));
+    assert!(bob.join("lobby"));
+    assert!(bob.join("games"));
+    assert_eq!(rooms.rooms(), ["games", "lobby"]);
+    assert_eq!(rooms.member_count("lobby"), 2);
+    assert!(alice.is_in("lobby") && !alice.is_in("games"));
+    assert_eq!(
+        bob.rooms().into_iter().collect::<Vec<_>>(),
+        ["games", "lobby"]
+    );
+
+    assert_eq!(rooms.broadcast("lobby", "to everyone".into()), 2);
+    assert_eq!(alice.broadcast("lobby", "from alice".into()), 1);
+    assert_eq!(alice.broadcast("games", "alice outside games".into()), 1);
+    assert_eq!(rooms.broadcast("empty", "to nobody".into()), 0);
+    alice.send("to alice directly".into());
+
+    block_on(async {
+        assert_eq!(alice_messages.next().await.unwrap(), "to everyone");
+        assert_eq!(alice_messages.next().await.unwrap(), "to alice directly");
+        assert_eq!(bob_messages.next().await.unwrap(), "to everyone");
+        assert_eq!(bob_messages.next().await.unwrap(), "from alice");
+        assert_eq!(bob_messages.next().await.unwrap(), "alice outside games");
+    });
+
+    assert!(bob.leave("games"));
+    assert!(!bob.leave("games"));
+    assert_eq!(rooms.rooms(), ["lobby"]);
+}
+
+#[test]
+fn dropping_messages_leaves_all_rooms() {
+    let rooms = Rooms::<String>::new();
+    let (alice, alice_messages) = rooms.member();
+    let (bob, _bob_messages) = rooms.member();
+    alice.join("lobby");
+    alice.join("games");
+    bob.join("lobby");
+
+    drop(alice_messages);
+    assert_eq!(rooms.rooms(), ["lobby"]);
+    assert_eq!(rooms.member_count("lobby"), 1);
+    assert!(!alice.join("lobby"));
+    assert_eq!(rooms.broadcast("lobby", "hello".into()), 1);
+}
+
+#[derive(Default)]
+struct Chat(Rooms);
+
+#[async_trait]
+impl WebSocketHandler for Chat {
+    type OutboundStream = RoomMessages;
+
+    async fn connect(&self, mut conn: WebSocketConn) -> Option<(WebSocketConn, RoomMessages)> {
+        let (member, messages) = self.0.member();
+        member.join(conn.path().trim_start_matches('/'));
+        conn.insert_state(member);
+        Some((conn, messages))
+    }
+
+    async fn inbound(&self, message: Message, conn: &mut WebSocketConn) {
+        if let Message::Text(text) = message {
+            let member = conn.state::<RoomMember>().unwrap();
+            for room in member.rooms() {
+                member.broadcast(&room, Message::text(format!("{room}: {text}")));
+            }
+        }
+    }
+}
+
+#[test]
+fn websocket_fanout() {
+    let chat = Chat::default();
+    let rooms = chat.0.clone();
+    trillium_testing::with_server(WebSocket::new(chat), |url| async move {
+        let client_config = trillium_testing::client_config();
+        let mut clients = vec![];
+        for path in ["lobby", "lobby", "games"] {
+            let transport = client_config.connect(&url).await?;
+            let (client, _) =
+                async_tungstenite::client_async(format!("ws://localhost/{path}"), transport)
+                    .await?;
+            clients.push(client);
+        }
+
+        while rooms.member_count("lobby") < 2 || rooms.member_count("games") < 1 {
+            futures_lite::future::yield_now().await;
+        }
+
+        clients[0].send(Message::text("hello")).await?;
+        assert_eq!(
+            clients[1].next().await.unwrap()?.into_text()?,
+            "lobby: hello"
+        );
+
+        clients[2].send(Message::text("gg")).await?;
+        clients[1].send(Message::text("hi")).await?;
+        assert_eq!(clients[0].next().await.unwrap()?.into_text()?, "lobby: hi");
+
+        for mut client in clients {
+            client.close(None).await?;
+            while client.next().await.is_some() {}
+        }
+
+        while !rooms.rooms().is_empty() {
+            futures_lite::future::yield_now().await;
+        }
+
+        Ok(())
+    });
+}
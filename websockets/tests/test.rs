@@ -155,3 +155,120 @@ fn test_handler(handler: impl Handler) {
         Ok(())
     });
 }
+
+#[test]
+fn stats() {
+    use std::sync::{Arc, Mutex};
+    use trillium_testing::Connector;
+    use trillium_websockets::WebSocketStats;
+
+    let captured: Arc<Mutex<Option<WebSocketStats>>> = Default::default();
+    let handler = WebSocket::new(|mut conn: WebSocketConn| async move {
+        while let Some(Ok(message)) = conn.next().await {
+            if let Message::Text(input) = message {
+                conn.send_string(format!("received {input}")).await.unwrap();
+                if input == "hello" {
+                    conn.ping().await.unwrap();
+                }
+            }
+        }
+    })
+    .with_stats_callback({
+        let captured = captured.clone();
+        move |stats| *captured.lock().unwrap() = Some(stats)
+    });
+
+    trillium_testing::with_server(handler, |url| async move {
+        let transport = trillium_testing::client_config().connect(&url).await?;
+        let (mut client, _) = async_tungstenite::client_async("ws://localhost/", transport).await?;
+
+        client.send(Message::text("hello")).await?;
+        assert_eq!(client.next().await.unwrap()?.into_text()?, "received hello");
+        assert!(matches!(client.next().await.unwrap()?, Message::Ping(_)));
+
+        client.send(Message::text("hey")).await?;
+        assert_eq!(client.next().await.unwrap()?.into_text()?, "received hey");
+
+        let stats = captured.lock().unwrap().clone().unwrap();
+        assert!(!stats.is_closed());
+
+        client.close(None).await?;
+        while client.next().await.is_some() {}
+        while !stats.is_closed() {
+            futures_lite::future::yield_now().await;
+        }
+
+        assert_eq!(stats.messages_received(), 4); // hello, hey, pong, close
+        assert_eq!(stats.bytes_received(), 16);
+        assert_eq!(stats.messages_sent(), 3);
+        assert_eq!(stats.bytes_sent(), 34);
+        assert!(stats.ping_rtt().is_some());
+        assert!(stats.last_activity() > stats.connected_at());
+        Ok(())
+    });
+}
+
+struct Echo;
+
+#[trillium::async_trait]
+impl WebSocketHandler for Echo {
+    type OutboundStream = Pending<Message>;
+
+    async fn connect(&self, conn: WebSocketConn) -> Option<(WebSocketConn, Self::OutboundStream)> {
+        Some((conn, pending()))
+    }
+
+    async fn inbound(&self, message: Message, conn: &mut WebSocketConn) {
+        conn.send(message).await.unwrap();
+    }
+}
+
+fn assert_closes_oversized_messages(handler: impl Handler) {
+    use async_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+
+    trillium_testing::with_transport(handler, |transport| async move {
+        let (mut client, _) = async_tungstenite::client_async("ws://localhost/", transport).await?;
+
+        client.send(Message::text("small")).await?;
+        assert_eq!(client.next().await.unwrap()?.into_text()?, "small");
+
+        client.send(Message::text("x".repeat(64))).await?;
+        match client.next().await.unwrap()? {
+            Message::Close(Some(close_frame)) => assert_eq!(close_frame.code, CloseCode::Size),
+            other => panic!("expected close frame, received {other:?}"),
+        }
+
+        Ok(())
+    });
+}
+
+#[test]
+fn memory_quota() {
+    assert_closes_oversized_messages(WebSocket::new(Echo).with_memory_quota(16));
+}
+
+#[test]
+fn per_connection_memory_quota() {
+    struct QuotaEcho;
+
+    #[trillium::async_trait]
+    impl WebSocketHandler for QuotaEcho {
+        type OutboundStream = Pending<Message>;
+
+        async fn connect(
+            &self,
+            mut conn: WebSocketConn,
+        ) -> Option<(WebSocketConn, Self::OutboundStream)> {
+            // the protocol config still accepts the message, so this
+            // quota is enforced against the decoded message
+            conn.set_memory_quota(Some(16));
+            Some((conn, pending()))
+        }
+
+        async fn inbound(&self, message: Message, conn: &mut WebSocketConn) {
+            Echo.inbound(message, conn).await;
+        }
+    }
+
+    assert_closes_oversized_messages(WebSocket::new(QuotaEcho));
+}